@@ -3,6 +3,7 @@ use tracing_subscriber::EnvFilter;
 use worldspace_common::Transform;
 use worldspace_kernel::World;
 use worldspace_persist::{Snapshot, SnapshotStore, WorldStore};
+use worldspace_tools::StatsHistory;
 
 #[derive(Parser)]
 #[command(name = "worldspace-cli", about = "CLI tool for worldspace operations")]
@@ -43,6 +44,22 @@ enum Commands {
         #[arg(short, long, default_value = "./world_data")]
         path: String,
     },
+    /// List entities added, removed and moved between two snapshots
+    Diff {
+        /// Path to world data directory
+        #[arg(short, long, default_value = "./world_data")]
+        path: String,
+        /// Snapshot index to diff from
+        from: u32,
+        /// Snapshot index to diff to
+        to: u32,
+    },
+    /// Print the recorded stats history of a persisted world
+    Stats {
+        /// Path to world data directory
+        #[arg(short, long, default_value = "./world_data")]
+        path: String,
+    },
 }
 
 fn main() -> anyhow::Result<()> {
@@ -65,7 +82,7 @@ fn main() -> anyhow::Result<()> {
             println!("input: {}", worldspace_input::crate_info());
         }
         Commands::Replay { path, ticks, seed } => {
-            match WorldStore::open(&path) {
+            match WorldStore::open_read_only(&path) {
                 Ok(store) => match store.load_latest() {
                     Ok(world) => {
                         let hash = world.state_hash();
@@ -128,7 +145,7 @@ fn main() -> anyhow::Result<()> {
         }
         Commands::Verify { path } => {
             println!("Verifying integrity of {path}...");
-            let store = WorldStore::open(&path)?;
+            let store = WorldStore::open_read_only(&path)?;
             match store.verify_integrity() {
                 Ok(()) => {
                     println!("Integrity: OK");
@@ -148,6 +165,59 @@ fn main() -> anyhow::Result<()> {
                 }
             }
         }
+        Commands::Diff { path, from, to } => {
+            let store = WorldStore::open_read_only(&path)?;
+            let diff = store.snapshot(from)?.diff(&store.snapshot(to)?);
+            println!(
+                "Snapshot {from} (tick {}) -> {to} (tick {}): {} added, {} removed, {} moved",
+                diff.from_tick,
+                diff.to_tick,
+                diff.added.len(),
+                diff.removed.len(),
+                diff.moved.len()
+            );
+            for (id, data) in &diff.added {
+                println!("  + {id:?} at {}", data.transform.position);
+            }
+            for (id, data) in &diff.removed {
+                println!("  - {id:?} at {}", data.transform.position);
+            }
+            for (id, delta) in &diff.moved {
+                println!(
+                    "  ~ {id:?} {} -> {} (moved by {})",
+                    delta.from.position,
+                    delta.to.position,
+                    delta.translation()
+                );
+            }
+        }
+        Commands::Stats { path } => {
+            let history = StatsHistory::load(&path)?;
+            println!(
+                "Stats history for {path}: {} samples (every {} ticks)",
+                history.len(),
+                history.interval()
+            );
+            println!(
+                "{:>8} {:>9} {:>7} {:>10} {:>11}",
+                "tick", "entities", "cells", "instances", "frame_us"
+            );
+            for sample in history.samples() {
+                println!(
+                    "{:>8} {:>9} {:>7} {:>10} {:>11}",
+                    sample.tick,
+                    sample.entity_count,
+                    sample.loaded_cells,
+                    sample.instance_count,
+                    sample.frame_time_us
+                );
+            }
+            println!(
+                "Trend: entity_growth={:+}, avg_frame_us={}",
+                history.entity_growth(),
+                history.average_frame_time_us()
+            );
+        }
     }
 
     Ok(())
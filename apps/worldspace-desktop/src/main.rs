@@ -1,7 +1,7 @@
 use anyhow::Result;
 use clap::Parser;
 use egui::Context as EguiContext;
-use glam::Vec3;
+use glam::{Vec2, Vec3};
 use std::sync::Arc;
 use std::time::Instant;
 use tracing_subscriber::EnvFilter;
@@ -11,14 +11,28 @@ use winit::event::{DeviceEvent, ElementState, KeyEvent, MouseButton, WindowEvent
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 use winit::keyboard::{KeyCode, PhysicalKey};
 use winit::window::{Window, WindowId};
+use worldspace_assets::{AssetEvent, AssetStore, AssetWatcher, Material};
 use worldspace_author::Editor;
 use worldspace_common::{EntityId, Transform};
-use worldspace_ecs::{ComponentStore, MaterialHandle, MeshHandle, Renderable};
-use worldspace_kernel::World;
+use worldspace_ecs::{
+    ComponentStore, Layer, MaterialHandle, MeshHandle, Renderable, Validators, Visible,
+};
+use worldspace_kernel::{TICK_SECONDS, World};
 use worldspace_persist::WorldStore;
-use worldspace_render_wgpu::{FlyCamera, WgpuRenderer};
-use worldspace_stream::GridPartition;
-use worldspace_tools::WorldInspector;
+use worldspace_render::RenderStats;
+use worldspace_render_wgpu::{
+    Antialiasing, FlyCamera, FrameLimiter, LodSettings, PresentMode, SsaoSettings, ViewPreset,
+    WgpuRenderer,
+};
+use worldspace_stream::{GridPartition, StreamConfig, StreamState};
+use worldspace_tools::{StatsHistory, WorldInspector};
+
+/// How far orthographic view presets place the camera from what they frame.
+const PRESET_DISTANCE: f32 = 20.0;
+
+/// Ticks between appends of pending world and component events to the store,
+/// which keep the fixed-rate tick from growing the logs for a whole session.
+const EVENT_FLUSH_TICKS: u64 = 600;
 
 #[derive(Parser)]
 #[command(name = "worldspace-desktop", about = "Worldspace desktop application")]
@@ -30,6 +44,18 @@ struct Cli {
     /// World data directory
     #[arg(long, default_value = "./world_data")]
     data_dir: String,
+
+    /// glTF/GLB files to import and hot-reload on change (repeatable)
+    #[arg(long)]
+    import: Vec<String>,
+
+    /// Cap the frame rate at this many frames per second
+    #[arg(long)]
+    fps_cap: Option<u32>,
+
+    /// Cull entities on the GPU and draw them with indirect draws
+    #[arg(long)]
+    gpu_culling: bool,
 }
 
 /// Application state.
@@ -37,14 +63,42 @@ struct AppState {
     world: World,
     editor: Editor,
     components: ComponentStore,
+    assets: AssetStore,
+    /// Material given to newly spawned entities.
+    default_material: MaterialHandle,
+    asset_watcher: AssetWatcher,
     camera: FlyCamera,
     grid: GridPartition,
+    /// Cells streamed around the camera; only entities in its active cells
+    /// are ticked.
+    stream: StreamState,
     selected: Option<EntityId>,
     show_inspector: bool,
+    /// Set by F12; the next rendered frame is saved as a PNG.
+    screenshot_requested: bool,
+    /// Toggled by F3; tint meshes by their active LOD level.
+    lod_debug_tint: bool,
+    /// Set by F; the camera frames the selected entity on the next frame.
+    focus_requested: bool,
+    /// Toggled by F4; draw collider wireframes on every layer.
+    show_colliders: bool,
+    /// Chosen in the Display settings; applied to the surface when changed.
+    present_mode: PresentMode,
+    frame_limiter: FrameLimiter,
+    /// Chosen in the Display settings; applied to the renderer when changed.
+    gpu_culling: bool,
+    depth_prepass: bool,
+    antialiasing: Antialiasing,
+    ssao: SsaoSettings,
     data_dir: String,
+    // Stats history for trend recording
+    stats: StatsHistory,
+    last_render_stats: RenderStats,
     // Input state
     keys_held: std::collections::HashSet<KeyCode>,
     mouse_captured: bool,
+    /// Cursor position in window pixels, for click-to-select.
+    cursor_position: Vec2,
     last_frame: Instant,
     // Fixed timestep
     tick_accumulator: f64,
@@ -56,6 +110,25 @@ impl AppState {
         let mut world = World::with_seed(42);
         let mut editor = Editor::new();
         let mut components = ComponentStore::new();
+        components.set_validators(Validators::standard());
+
+        // Materials for the initial entities; the renderer colors entities by
+        // their material's base color.
+        let mut assets = AssetStore::new();
+        let default_material = MaterialHandle(assets.register_default_material().0);
+        let mut color_material = |name: &str, base_color| {
+            MaterialHandle(
+                assets
+                    .register_material(Material {
+                        name: name.into(),
+                        base_color,
+                        ..Material::default()
+                    })
+                    .0,
+            )
+        };
+        let red = color_material("red", [0.9, 0.2, 0.2, 1.0]);
+        let blue = color_material("blue", [0.2, 0.4, 0.9, 1.0]);
 
         // Spawn initial entities
         let id1 = editor.spawn(&mut world, Transform::default());
@@ -64,7 +137,7 @@ impl AppState {
             id1,
             Renderable {
                 mesh: MeshHandle(0),
-                material: MaterialHandle(0),
+                material: default_material,
             },
         );
 
@@ -80,7 +153,7 @@ impl AppState {
             id2,
             Renderable {
                 mesh: MeshHandle(0),
-                material: MaterialHandle(1),
+                material: red,
             },
         );
 
@@ -96,7 +169,7 @@ impl AppState {
             id3,
             Renderable {
                 mesh: MeshHandle(0),
-                material: MaterialHandle(2),
+                material: blue,
             },
         );
 
@@ -107,16 +180,37 @@ impl AppState {
             world,
             editor,
             components,
+            assets,
+            default_material,
+            asset_watcher: AssetWatcher::new(),
             camera: FlyCamera::default(),
             grid,
+            stream: StreamState::new(StreamConfig {
+                // Cells carry no content, so there is nothing to load off-thread.
+                workers: 0,
+                ..StreamConfig::default()
+            }),
             selected: None,
             show_inspector: true,
+            screenshot_requested: false,
+            lod_debug_tint: false,
+            focus_requested: false,
+            show_colliders: false,
+            present_mode: PresentMode::default(),
+            frame_limiter: FrameLimiter::default(),
+            gpu_culling: false,
+            depth_prepass: false,
+            antialiasing: Antialiasing::default(),
+            ssao: SsaoSettings::default(),
+            stats: StatsHistory::load(&data_dir).unwrap_or_default(),
+            last_render_stats: RenderStats::default(),
             data_dir,
             keys_held: std::collections::HashSet::new(),
             mouse_captured: false,
+            cursor_position: Vec2::ZERO,
             last_frame: Instant::now(),
             tick_accumulator: 0.0,
-            tick_rate: 1.0 / 60.0,
+            tick_rate: TICK_SECONDS as f64,
         }
     }
 
@@ -146,15 +240,39 @@ impl AppState {
         if self.keys_held.contains(&KeyCode::ControlLeft) {
             self.camera.move_down(dt_scaled);
         }
+        self.camera.update(dt);
 
         // Fixed timestep for kernel ticking
         self.tick_accumulator += dt as f64;
         while self.tick_accumulator >= self.tick_rate {
             self.tick_accumulator -= self.tick_rate;
-            // Kernel stepping at fixed rate (editor mode skips this)
+            let changes = self.stream.activate(&mut self.world, &self.grid);
+            if !changes.is_empty() {
+                tracing::debug!(changes = changes.len(), "entities froze or thawed");
+            }
+            self.components
+                .step_animations(&mut self.world, &self.assets);
+            self.components.step_world(&mut self.world);
+            if self.world.tick() % EVENT_FLUSH_TICKS == 0 {
+                self.flush_events();
+            }
+        }
+
+        for event in self.asset_watcher.poll(&mut self.assets) {
+            if let AssetEvent::AssetReloaded { old_id, new_id } = event {
+                let remapped = self.components.remap_asset(old_id, new_id);
+                tracing::info!(?old_id, ?new_id, remapped, "asset reloaded");
+            }
         }
 
         self.grid.rebuild(&self.world);
+        self.stream.set_view_direction(Some(self.camera.forward()));
+        let viewer = self.grid.position_to_cell(self.camera.position);
+        self.stream.update(viewer, &self.grid);
+
+        let summary = WorldInspector::summary(&self.world);
+        self.stats
+            .record(&summary, self.stream.stats(), &self.last_render_stats);
     }
 
     fn handle_key(&mut self, key: KeyCode, pressed: bool) {
@@ -184,7 +302,7 @@ impl AppState {
                     id,
                     Renderable {
                         mesh: MeshHandle(0),
-                        material: MaterialHandle(0),
+                        material: self.default_material,
                     },
                 );
                 self.selected = Some(id);
@@ -218,6 +336,24 @@ impl AppState {
             KeyCode::F1 => {
                 self.show_inspector = !self.show_inspector;
             }
+            KeyCode::F12 => {
+                self.screenshot_requested = true;
+            }
+            KeyCode::F3 => {
+                self.lod_debug_tint = !self.lod_debug_tint;
+            }
+            KeyCode::KeyF => {
+                self.focus_requested = true;
+            }
+            KeyCode::F4 => {
+                self.show_colliders = !self.show_colliders;
+            }
+            KeyCode::Numpad7 => self.view_preset(ViewPreset::Top),
+            KeyCode::Numpad1 => self.view_preset(ViewPreset::Front),
+            KeyCode::Numpad3 => self.view_preset(ViewPreset::Side),
+            KeyCode::Numpad5 => {
+                self.camera.toggle_orthographic(PRESET_DISTANCE);
+            }
             KeyCode::Escape => {
                 self.selected = None;
             }
@@ -225,18 +361,36 @@ impl AppState {
         }
     }
 
+    /// Snap the camera to an orthographic `preset` around the selected
+    /// entity, or the point in front of the camera if nothing is selected.
+    fn view_preset(&mut self, preset: ViewPreset) {
+        let target = self
+            .selected
+            .and_then(|id| self.world.get(id))
+            .map(|data| data.transform.position)
+            .unwrap_or(self.camera.position + self.camera.forward() * PRESET_DISTANCE);
+        self.camera.set_view_preset(preset, target, PRESET_DISTANCE);
+    }
+
     fn save_world(&mut self) {
         match WorldStore::open(&self.data_dir) {
             Ok(mut store) => {
-                if let Err(e) = store.take_snapshot(&self.world) {
+                if let Err(e) = store.take_snapshot_with_components(&self.world, &self.components) {
                     tracing::error!("failed to save snapshot: {e}");
                     return;
                 }
                 let events = self.world.drain_events();
-                if let Err(e) = store.append_events(&events) {
+                let component_events = self.components.drain_events();
+                if let Err(e) = store.append_events_with_components(&events, &component_events) {
                     tracing::error!("failed to save events: {e}");
                     return;
                 }
+                if let Err(e) = store.save_edit_history(&self.editor.history()) {
+                    tracing::error!("failed to save undo history: {e}");
+                }
+                if let Err(e) = self.stats.save(&self.data_dir) {
+                    tracing::error!("failed to save stats history: {e}");
+                }
                 tracing::info!("world saved to {}", self.data_dir);
             }
             Err(e) => {
@@ -245,12 +399,58 @@ impl AppState {
         }
     }
 
+    /// Append the world and component events logged since the last save or
+    /// flush to the store as a new segment.
+    fn flush_events(&mut self) {
+        if self.world.events().is_empty() && self.components.events().is_empty() {
+            return;
+        }
+        match WorldStore::open(&self.data_dir) {
+            Ok(mut store) => {
+                let events = self.world.drain_events();
+                let component_events = self.components.drain_events();
+                if let Err(e) = store.append_events_with_components(&events, &component_events) {
+                    tracing::error!("failed to save events: {e}");
+                }
+            }
+            Err(e) => {
+                tracing::error!("failed to open store: {e}");
+            }
+        }
+    }
+
+    /// Write `image` to `<data_dir>/screenshots/screenshot-<unix ms>.png`.
+    fn save_screenshot(&self, image: &image::RgbaImage) {
+        let dir = std::path::Path::new(&self.data_dir).join("screenshots");
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            tracing::error!("failed to create {}: {e}", dir.display());
+            return;
+        }
+        let millis = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let path = dir.join(format!("screenshot-{millis}.png"));
+        match image.save(&path) {
+            Ok(()) => tracing::info!("screenshot saved to {}", path.display()),
+            Err(e) => tracing::error!("failed to save screenshot: {e}"),
+        }
+    }
+
     fn load_world(&mut self) {
         match WorldStore::open(&self.data_dir) {
-            Ok(store) => match store.load_latest() {
-                Ok(loaded) => {
+            Ok(store) => match store.load_latest_with_components() {
+                Ok((loaded, components)) => {
                     self.world = loaded;
-                    self.editor = Editor::new();
+                    self.components = components;
+                    self.components.set_validators(Validators::standard());
+                    self.editor = match store.load_edit_history() {
+                        Ok(history) => history.map(Editor::from_history).unwrap_or_default(),
+                        Err(e) => {
+                            tracing::error!("failed to load undo history: {e}");
+                            Editor::new()
+                        }
+                    };
                     self.selected = None;
                     self.grid.rebuild(&self.world);
                     tracing::info!("world loaded from {}", self.data_dir);
@@ -285,6 +485,82 @@ impl AppState {
                 ));
                 ui.separator();
 
+                ui.heading("Render");
+                let stats = &self.last_render_stats;
+                ui.label(format!(
+                    "Draw calls: {}  Triangles: {}",
+                    stats.draw_calls, stats.triangle_count
+                ));
+                ui.label(format!(
+                    "Instances: {}  Culled: {}",
+                    stats.instance_count, stats.culled_count
+                ));
+                ui.label(format!(
+                    "CPU: {:.2} ms",
+                    stats.frame_time.as_secs_f64() * 1000.0
+                ));
+                match stats.gpu_times {
+                    Some(gpu) => {
+                        let ms = |time: std::time::Duration| time.as_secs_f64() * 1000.0;
+                        ui.label(format!(
+                            "GPU: {:.2} ms (depth {:.2}, SSAO {:.2}, scene {:.2}, post {:.2}, \
+                             AA {:.2}, present {:.2})",
+                            ms(gpu.total()),
+                            ms(gpu.depth_prepass),
+                            ms(gpu.ssao),
+                            ms(gpu.scene),
+                            ms(gpu.post),
+                            ms(gpu.antialias),
+                            ms(gpu.present)
+                        ));
+                    }
+                    None => {
+                        ui.label("GPU: timing unavailable");
+                    }
+                }
+                ui.separator();
+
+                ui.heading("Display");
+                egui::ComboBox::from_label("Present mode")
+                    .selected_text(self.present_mode.label())
+                    .show_ui(ui, |ui| {
+                        for mode in PresentMode::ALL {
+                            ui.selectable_value(&mut self.present_mode, mode, mode.label());
+                        }
+                    });
+                let mut capped = self.frame_limiter.fps_cap().is_some();
+                let mut fps = self.frame_limiter.fps_cap().unwrap_or(60);
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut capped, "FPS cap");
+                    ui.add_enabled(
+                        capped,
+                        egui::DragValue::new(&mut fps)
+                            .range(10..=1000)
+                            .suffix(" fps"),
+                    );
+                });
+                let fps_cap = capped.then_some(fps);
+                if fps_cap != self.frame_limiter.fps_cap() {
+                    self.frame_limiter.set_fps_cap(fps_cap);
+                }
+                ui.checkbox(&mut self.gpu_culling, "GPU culling");
+                ui.checkbox(&mut self.depth_prepass, "Depth pre-pass");
+                egui::ComboBox::from_label("Antialiasing")
+                    .selected_text(self.antialiasing.label())
+                    .show_ui(ui, |ui| {
+                        for mode in Antialiasing::ALL {
+                            ui.selectable_value(&mut self.antialiasing, mode, mode.label());
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.ssao.enabled, "SSAO");
+                    ui.add_enabled(
+                        self.ssao.enabled,
+                        egui::Slider::new(&mut self.ssao.radius, 0.1..=2.0).text("radius"),
+                    );
+                });
+                ui.separator();
+
                 ui.heading("Tools");
                 if ui.button("Spawn Entity (N)").clicked() {
                     let pos = self.camera.position + self.camera.forward() * 5.0;
@@ -301,7 +577,7 @@ impl AppState {
                         id,
                         Renderable {
                             mesh: MeshHandle(0),
-                            material: MaterialHandle(0),
+                            material: self.default_material,
                         },
                     );
                     self.selected = Some(id);
@@ -420,6 +696,11 @@ impl AppState {
                             };
                             let _ = self.editor.set_transform(&mut self.world, id, new_t);
                         }
+
+                        let mut visible = self.components.is_visible(id);
+                        if ui.checkbox(&mut visible, "Visible").changed() {
+                            self.components.set_visible(id, Visible(visible));
+                        }
                     }
                 }
 
@@ -436,6 +717,8 @@ struct GpuApp {
     device: Option<wgpu::Device>,
     queue: Option<wgpu::Queue>,
     config: Option<wgpu::SurfaceConfiguration>,
+    /// Present modes the surface supports.
+    present_modes: Vec<wgpu::PresentMode>,
     renderer: Option<WgpuRenderer>,
     egui_ctx: EguiContext,
     egui_winit: Option<egui_winit::State>,
@@ -451,6 +734,7 @@ impl GpuApp {
             device: None,
             queue: None,
             config: None,
+            present_modes: Vec::new(),
             renderer: None,
             egui_ctx: EguiContext::default(),
             egui_winit: None,
@@ -489,7 +773,8 @@ impl ApplicationHandler for GpuApp {
         let (device, queue) = pollster::block_on(adapter.request_device(
             &wgpu::DeviceDescriptor {
                 label: Some("worldspace_device"),
-                required_features: wgpu::Features::empty(),
+                // Timestamp queries feed the GPU pass times in the inspector.
+                required_features: adapter.features() & wgpu::Features::TIMESTAMP_QUERY,
                 required_limits: wgpu::Limits::default(),
                 memory_hints: Default::default(),
             },
@@ -511,7 +796,7 @@ impl ApplicationHandler for GpuApp {
             format: surface_format,
             width: size.width.max(1),
             height: size.height.max(1),
-            present_mode: wgpu::PresentMode::AutoVsync,
+            present_mode: self.state.present_mode.resolve(&surface_caps.present_modes),
             alpha_mode: surface_caps.alpha_modes[0],
             view_formats: vec![],
             desired_maximum_frame_latency: 2,
@@ -520,7 +805,7 @@ impl ApplicationHandler for GpuApp {
 
         self.state.camera.aspect = size.width as f32 / size.height.max(1) as f32;
 
-        let renderer = WgpuRenderer::new(&device, surface_format, size.width, size.height);
+        let renderer = WgpuRenderer::new(&device, &queue, surface_format, size.width, size.height);
 
         let egui_winit = egui_winit::State::new(
             self.egui_ctx.clone(),
@@ -537,6 +822,7 @@ impl ApplicationHandler for GpuApp {
         self.device = Some(device);
         self.queue = Some(queue);
         self.config = Some(config);
+        self.present_modes = surface_caps.present_modes;
         self.renderer = Some(renderer);
         self.egui_winit = Some(egui_winit);
         self.egui_renderer = Some(egui_renderer);
@@ -600,8 +886,27 @@ impl ApplicationHandler for GpuApp {
                     let _ = window.set_cursor_visible(!self.state.mouse_captured);
                 }
             }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.state.cursor_position = Vec2::new(position.x as f32, position.y as f32);
+            }
+            WindowEvent::MouseInput {
+                button: MouseButton::Left,
+                state: ElementState::Pressed,
+                ..
+            } => {
+                // Clicks on egui panels were consumed above.
+                if let Some(renderer) = &self.renderer {
+                    self.state.selected = renderer.pick(
+                        self.state.cursor_position,
+                        &self.state.camera,
+                        &self.state.world,
+                        &self.state.components,
+                    );
+                }
+            }
             WindowEvent::RedrawRequested => {
                 let now = Instant::now();
+                self.state.frame_limiter.begin_frame(now);
                 let dt = (now - self.state.last_frame).as_secs_f32().min(0.1);
                 self.state.last_frame = now;
                 self.state.update(dt);
@@ -612,6 +917,44 @@ impl ApplicationHandler for GpuApp {
                     return;
                 };
 
+                if let Some(config) = &mut self.config {
+                    let present_mode = self.state.present_mode.resolve(&self.present_modes);
+                    if config.present_mode != present_mode {
+                        config.present_mode = present_mode;
+                        surface.configure(device, config);
+                    }
+                }
+
+                let asset_events = self.state.assets.drain_events();
+                if let Some(renderer) = &mut self.renderer {
+                    renderer.sync_assets(device, queue, &self.state.assets, &asset_events);
+                    if renderer.lod_settings().debug_tint != self.state.lod_debug_tint {
+                        renderer.set_lod_settings(LodSettings {
+                            debug_tint: self.state.lod_debug_tint,
+                            ..renderer.lod_settings().clone()
+                        });
+                    }
+                    if renderer.gpu_culling() != self.state.gpu_culling {
+                        renderer.set_gpu_culling(device, self.state.gpu_culling);
+                    }
+                    renderer.set_depth_prepass(self.state.depth_prepass);
+                    renderer.set_antialiasing(self.state.antialiasing);
+                    renderer.set_ssao(self.state.ssao);
+                    renderer.set_collider_layers(if self.state.show_colliders {
+                        Layer::ALL
+                    } else {
+                        Layer::NONE
+                    });
+                    if std::mem::take(&mut self.state.focus_requested) {
+                        let bounds = self.state.selected.and_then(|id| {
+                            renderer.entity_bounds(id, &self.state.world, &self.state.components)
+                        });
+                        if let Some(bounds) = bounds {
+                            self.state.camera.focus_on(&bounds);
+                        }
+                    }
+                }
+
                 let output = match surface.get_current_texture() {
                     Ok(t) => t,
                     Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
@@ -631,15 +974,28 @@ impl ApplicationHandler for GpuApp {
                     .create_view(&wgpu::TextureViewDescriptor::default());
 
                 if let Some(renderer) = &self.renderer {
-                    renderer.render(
+                    // Screenshots leave out editor content, like a play camera.
+                    let screenshot = std::mem::take(&mut self.state.screenshot_requested);
+                    let layers = self.state.camera.layers;
+                    if screenshot {
+                        self.state.camera.layers = layers.intersection(Layer::PLAY);
+                    }
+                    self.state.last_render_stats = renderer.render(
                         device,
                         queue,
                         &view,
                         &self.state.camera,
                         &self.state.world,
-                        self.state.components.renderables(),
+                        &self.state.components,
                         self.state.selected,
                     );
+                    self.state.camera.layers = layers;
+                    if screenshot {
+                        match renderer.capture_frame(device, queue) {
+                            Ok(image) => self.state.save_screenshot(&image),
+                            Err(e) => tracing::error!("failed to capture frame: {e}"),
+                        }
+                    }
                 }
 
                 let raw_input = self
@@ -725,12 +1081,31 @@ impl ApplicationHandler for GpuApp {
     ) {
         if let DeviceEvent::MouseMotion { delta } = event {
             if self.state.mouse_captured {
-                self.state.camera.rotate(delta.0 as f32, delta.1 as f32);
+                let (dx, dy) = (delta.0 as f32, delta.1 as f32);
+                // Alt orbits around the selected entity instead of turning.
+                let pivot = self
+                    .state
+                    .selected
+                    .filter(|_| self.state.keys_held.contains(&KeyCode::AltLeft))
+                    .and_then(|id| self.state.world.get(id))
+                    .map(|data| data.transform.position);
+                match pivot {
+                    Some(pivot) => self.state.camera.orbit(pivot, dx, dy),
+                    None => self.state.camera.rotate(dx, dy),
+                }
             }
         }
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        // Sleep until the next frame is due when the frame rate is capped.
+        if !self.state.frame_limiter.frame_due(Instant::now()) {
+            if let Some(next) = self.state.frame_limiter.next_frame() {
+                event_loop.set_control_flow(ControlFlow::WaitUntil(next));
+            }
+            return;
+        }
+        event_loop.set_control_flow(ControlFlow::Poll);
         if let Some(window) = &self.window {
             window.request_redraw();
         }
@@ -751,6 +1126,26 @@ fn main() -> Result<()> {
     event_loop.set_control_flow(ControlFlow::Poll);
 
     let mut app = GpuApp::new(cli.data_dir);
+    app.state.frame_limiter.set_fps_cap(cli.fps_cap);
+    app.state.gpu_culling = cli.gpu_culling;
+    for path in &cli.import {
+        let state = &mut app.state;
+        match state.asset_watcher.import(&mut state.assets, path) {
+            Ok(report) => {
+                for warning in &report.warnings {
+                    tracing::warn!(path = %path, %warning, "import warning");
+                }
+                tracing::info!(
+                    path = %path,
+                    assets = report.ids.len(),
+                    reused = report.dedup.reused.len(),
+                    bytes_saved = report.dedup.bytes_saved,
+                    "imported and watching"
+                );
+            }
+            Err(e) => tracing::warn!(path = %path, error = %e, "import failed"),
+        }
+    }
     event_loop.run_app(&mut app)?;
 
     Ok(())
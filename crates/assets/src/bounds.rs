@@ -0,0 +1,138 @@
+//! Mesh bounding volumes for culling, picking, and streaming.
+//!
+//! Bounds are in mesh space and computed from `Mesh::positions` on import;
+//! callers transform them by the instance's transform.
+
+use serde::{Deserialize, Serialize};
+
+use crate::Mesh;
+
+/// Axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Aabb {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Aabb {
+    /// The box around a unit cube centered on the origin, assumed for meshes
+    /// without geometry.
+    pub const UNIT_CUBE: Aabb = Aabb {
+        min: [-0.5; 3],
+        max: [0.5; 3],
+    };
+
+    /// The smallest box containing `points`; `None` if there are none.
+    pub fn from_points(points: &[[f32; 3]]) -> Option<Self> {
+        let (first, rest) = points.split_first()?;
+        let mut aabb = Aabb {
+            min: *first,
+            max: *first,
+        };
+        for p in rest {
+            aabb.min = [0, 1, 2].map(|i| aabb.min[i].min(p[i]));
+            aabb.max = [0, 1, 2].map(|i| aabb.max[i].max(p[i]));
+        }
+        Some(aabb)
+    }
+
+    pub fn center(&self) -> [f32; 3] {
+        [0, 1, 2].map(|i| (self.min[i] + self.max[i]) * 0.5)
+    }
+
+    pub fn half_extents(&self) -> [f32; 3] {
+        [0, 1, 2].map(|i| (self.max[i] - self.min[i]) * 0.5)
+    }
+}
+
+/// Bounding sphere.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingSphere {
+    pub center: [f32; 3],
+    pub radius: f32,
+}
+
+/// Both bounding volumes of a mesh.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MeshBounds {
+    pub aabb: Aabb,
+    /// Centered on the AABB, with the radius of the farthest vertex. Not
+    /// minimal, but cheap and never smaller than the geometry.
+    pub sphere: BoundingSphere,
+}
+
+impl MeshBounds {
+    /// Bounds of `points`; `None` if there are none.
+    pub fn from_points(points: &[[f32; 3]]) -> Option<Self> {
+        let aabb = Aabb::from_points(points)?;
+        let center = aabb.center();
+        let radius_sq = points
+            .iter()
+            .map(|p| (0..3).map(|i| (p[i] - center[i]).powi(2)).sum::<f32>())
+            .fold(0.0, f32::max);
+        Some(Self {
+            aabb,
+            sphere: BoundingSphere {
+                center,
+                radius: radius_sq.sqrt(),
+            },
+        })
+    }
+
+    /// Bounds of a unit cube centered on the origin.
+    pub fn unit_cube() -> Self {
+        Self {
+            aabb: Aabb::UNIT_CUBE,
+            sphere: BoundingSphere {
+                center: [0.0; 3],
+                radius: 0.75f32.sqrt(),
+            },
+        }
+    }
+}
+
+impl Mesh {
+    /// Recompute `bounds` from `positions`. Meshes without positions keep
+    /// their bounds, so placeholders can declare them.
+    pub fn update_bounds(&mut self) {
+        if let Some(bounds) = MeshBounds::from_points(&self.positions) {
+            self.bounds = Some(bounds);
+        }
+    }
+
+    /// `bounds`, or a unit cube's for meshes that have none.
+    pub fn bounds_or_unit(&self) -> MeshBounds {
+        self.bounds.unwrap_or_else(MeshBounds::unit_cube)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_enclose_positions() {
+        let mut mesh = Mesh {
+            positions: vec![[0.0, 0.0, 0.0], [2.0, 1.0, 0.0], [0.0, -1.0, 4.0]],
+            ..Default::default()
+        };
+        assert_eq!(mesh.bounds_or_unit(), MeshBounds::unit_cube());
+        mesh.update_bounds();
+        let bounds = mesh.bounds.unwrap();
+        assert_eq!(bounds.aabb.min, [0.0, -1.0, 0.0]);
+        assert_eq!(bounds.aabb.max, [2.0, 1.0, 4.0]);
+        assert_eq!(bounds.aabb.half_extents(), [1.0, 1.0, 2.0]);
+        assert_eq!(bounds.sphere.center, [1.0, 0.0, 2.0]);
+        for p in &mesh.positions {
+            let d = (0..3)
+                .map(|i| (p[i] - bounds.sphere.center[i]).powi(2))
+                .sum::<f32>()
+                .sqrt();
+            assert!(d <= bounds.sphere.radius + 1e-6);
+        }
+
+        mesh.positions.clear();
+        mesh.update_bounds();
+        assert_eq!(mesh.bounds, Some(bounds));
+    }
+}
@@ -0,0 +1,62 @@
+//! Cross-file content deduplication for imports.
+//!
+//! Asset IDs hash names too, so the same prop imported from two files under
+//! different names would be stored twice. Imports look assets up by a
+//! name-independent digest first and reuse the existing ID on a match.
+//! References are rewritten to reused IDs before their referrers are hashed,
+//! so materials and meshes that share deduplicated textures deduplicate too.
+
+use std::collections::BTreeMap;
+
+use crate::{Asset, AssetId, AssetStore, content_digest};
+
+/// Assets an import reused instead of registering a copy.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DedupStats {
+    /// Existing IDs reused, in import order.
+    pub reused: Vec<AssetId>,
+    /// Payload bytes not stored again.
+    pub bytes_saved: u64,
+}
+
+/// Resident assets of a store by name-independent content digest.
+#[derive(Debug, Default)]
+pub(crate) struct ContentIndex {
+    by_digest: BTreeMap<[u8; 32], AssetId>,
+}
+
+impl AssetStore {
+    /// Index every asset whose payload is in memory. Meshes from `open_pack`
+    /// whose geometry is not loaded are left out, since their digest would
+    /// not match.
+    pub(crate) fn content_index(&self) -> ContentIndex {
+        let by_digest = self
+            .assets
+            .iter()
+            .filter(|(id, _)| self.is_resident(**id))
+            .map(|(id, asset)| (content_digest(asset, false).0, *id))
+            .collect();
+        ContentIndex { by_digest }
+    }
+
+    /// Register `asset`, or return the ID of an indexed asset with the same
+    /// content under any name and count it in `stats`.
+    pub(crate) fn insert_dedup(
+        &mut self,
+        asset: Asset,
+        index: &mut ContentIndex,
+        stats: &mut DedupStats,
+    ) -> AssetId {
+        let (digest, len) = content_digest(&asset, false);
+        if let Some(&id) = index.by_digest.get(&digest)
+            && self.assets.contains_key(&id)
+        {
+            stats.reused.push(id);
+            stats.bytes_saved += len;
+            return id;
+        }
+        let id = self.insert(asset);
+        index.by_digest.insert(digest, id);
+        id
+    }
+}
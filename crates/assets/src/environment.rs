@@ -0,0 +1,407 @@
+//! Equirectangular HDR environment maps for skyboxes and image-based light.
+//!
+//! Radiance `.hdr` (RGBE) files are decoded to linear RGB floats. KTX2 files
+//! in a float format are converted the same way; BC6H KTX2 files are kept
+//! compressed for the GPU and cannot be pre-filtered on the CPU.
+//!
+//! Maps are equirectangular: `u` runs with the azimuth around +Y, starting
+//! at +X and turning toward +Z, and `v = 0` is straight up.
+
+use std::f32::consts::PI;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::texture::{KTX2_MAGIC, parse_ktx2};
+use crate::{AssetError, TextureData};
+
+/// Irradiance map size used by `AssetStore::import_environment`.
+pub const IRRADIANCE_SIZE: (u32, u32) = (32, 16);
+/// Sources are box-filtered down to about this width before the irradiance
+/// integral, which is smooth enough not to need more detail.
+const IRRADIANCE_SOURCE_WIDTH: u32 = 64;
+
+const VK_FORMAT_R16G16B16A16_SFLOAT: u32 = 97;
+const VK_FORMAT_R32G32B32_SFLOAT: u32 = 106;
+const VK_FORMAT_R32G32B32A32_SFLOAT: u32 = 109;
+const VK_FORMAT_BC6H_UFLOAT_BLOCK: u32 = 143;
+const VK_FORMAT_BC6H_SFLOAT_BLOCK: u32 = 144;
+
+/// Radiance of an environment map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EnvironmentData {
+    /// Linear RGB, row-major from the top row.
+    Rgb32F(Vec<[f32; 3]>),
+    /// BC6H mip levels (largest first) in the given `VkFormat`.
+    Compressed {
+        vk_format: u32,
+        levels: Vec<Vec<u8>>,
+    },
+}
+
+/// Diffuse irradiance for each direction of an equirectangular map: the
+/// cosine-weighted radiance arriving on a surface facing that way, over π.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IrradianceMap {
+    pub width: u32,
+    pub height: u32,
+    pub texels: Vec<[f32; 3]>,
+}
+
+/// An equirectangular HDR environment map asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Environment {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub data: EnvironmentData,
+    /// Pre-filtered diffuse irradiance, if computed on import.
+    #[serde(default)]
+    pub irradiance: Option<IrradianceMap>,
+}
+
+impl Environment {
+    /// Decode a Radiance `.hdr` or KTX2 file, detected by its magic bytes.
+    pub fn decode(name: impl Into<String>, bytes: &[u8]) -> Result<Self, AssetError> {
+        let name = name.into();
+        if bytes.starts_with(KTX2_MAGIC) {
+            return from_ktx2(name, bytes);
+        }
+        if !bytes.starts_with(b"#?") {
+            return Err(AssetError::TextureDecode(format!(
+                "{name}: not a Radiance HDR or KTX2 file"
+            )));
+        }
+        let (width, height, texels) = parse_radiance(&name, bytes)?;
+        Ok(Self {
+            name,
+            width,
+            height,
+            data: EnvironmentData::Rgb32F(texels),
+            irradiance: None,
+        })
+    }
+
+    /// Read and decode an environment map, named after its file stem.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AssetError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        let name = path.file_stem().map_or_else(
+            || "environment".to_string(),
+            |s| s.to_string_lossy().into_owned(),
+        );
+        Self::decode(name, &bytes)
+    }
+
+    /// Compute a `width` x `height` irradiance map by integrating the
+    /// cosine-weighted radiance over the sphere for each output texel.
+    ///
+    /// Fails for compressed maps, which would need decoding first.
+    pub fn prefilter_irradiance(&mut self, width: u32, height: u32) -> Result<(), AssetError> {
+        let err = |msg: &str| AssetError::TextureDecode(format!("{}: {msg}", self.name));
+        let EnvironmentData::Rgb32F(texels) = &self.data else {
+            return Err(err("irradiance needs uncompressed radiance"));
+        };
+        if width == 0 || height == 0 {
+            return Err(err("irradiance map size must be nonzero"));
+        }
+
+        // Box-filter the source, then weight each sample by its solid angle.
+        let (src_w, src_h) = (self.width as usize, self.height as usize);
+        let factor = (self.width / IRRADIANCE_SOURCE_WIDTH).max(1) as usize;
+        let (w, h) = (src_w / factor, (src_h / factor).max(1));
+        let mut samples = Vec::with_capacity(w * h);
+        for y in 0..h {
+            let (rows, theta) = (y * src_h / h..(y + 1) * src_h / h, texel_angle(y, h, PI));
+            let solid_angle = (2.0 * PI / w as f32) * (PI / h as f32) * theta.sin();
+            for x in 0..w {
+                let columns = x * src_w / w..(x + 1) * src_w / w;
+                let count = (rows.len() * columns.len()) as f32;
+                let mut radiance = [0.0f32; 3];
+                for row in rows.clone() {
+                    for texel in &texels[row * src_w + columns.start..row * src_w + columns.end] {
+                        for c in 0..3 {
+                            radiance[c] += texel[c];
+                        }
+                    }
+                }
+                let weight = solid_angle / count;
+                samples.push((direction(x, y, w, h), radiance.map(|c| c * weight)));
+            }
+        }
+
+        let mut out = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height as usize {
+            for x in 0..width as usize {
+                let normal = direction(x, y, width as usize, height as usize);
+                let mut irradiance = [0.0f32; 3];
+                for (dir, radiance) in &samples {
+                    let cos = normal[0] * dir[0] + normal[1] * dir[1] + normal[2] * dir[2];
+                    if cos > 0.0 {
+                        for c in 0..3 {
+                            irradiance[c] += radiance[c] * cos;
+                        }
+                    }
+                }
+                out.push(irradiance.map(|c| c / PI));
+            }
+        }
+        self.irradiance = Some(IrradianceMap {
+            width,
+            height,
+            texels: out,
+        });
+        Ok(())
+    }
+}
+
+/// Angle at the center of texel `i` of `n` spanning `range` radians.
+fn texel_angle(i: usize, n: usize, range: f32) -> f32 {
+    (i as f32 + 0.5) / n as f32 * range
+}
+
+/// Unit direction through the center of texel (`x`, `y`) of a `w` x `h`
+/// equirectangular map.
+fn direction(x: usize, y: usize, w: usize, h: usize) -> [f32; 3] {
+    let theta = texel_angle(y, h, PI);
+    let phi = texel_angle(x, w, 2.0 * PI);
+    [
+        theta.sin() * phi.cos(),
+        theta.cos(),
+        theta.sin() * phi.sin(),
+    ]
+}
+
+fn from_ktx2(name: String, bytes: &[u8]) -> Result<Environment, AssetError> {
+    let texture = parse_ktx2(name, bytes)?;
+    let err = |msg: String| AssetError::TextureDecode(format!("{}: {msg}", texture.name));
+    let TextureData::Compressed { vk_format, levels } = texture.data else {
+        return Err(err("KTX2 without levels".into()));
+    };
+    let data = match vk_format {
+        VK_FORMAT_BC6H_UFLOAT_BLOCK | VK_FORMAT_BC6H_SFLOAT_BLOCK => {
+            EnvironmentData::Compressed { vk_format, levels }
+        }
+        VK_FORMAT_R16G16B16A16_SFLOAT
+        | VK_FORMAT_R32G32B32_SFLOAT
+        | VK_FORMAT_R32G32B32A32_SFLOAT => {
+            let (component_size, components) = match vk_format {
+                VK_FORMAT_R16G16B16A16_SFLOAT => (2, 4),
+                VK_FORMAT_R32G32B32_SFLOAT => (4, 3),
+                _ => (4, 4),
+            };
+            let texel_size = component_size * components;
+            let count = texture.width as usize * texture.height as usize;
+            let level = levels.first().map_or(&[][..], Vec::as_slice);
+            if level.len() < count * texel_size {
+                return Err(err(format!("level 0 holds fewer than {count} texels")));
+            }
+            let component = |texel: &[u8], c: usize| {
+                let at = c * component_size;
+                if component_size == 2 {
+                    f16_to_f32(u16::from_le_bytes([texel[at], texel[at + 1]]))
+                } else {
+                    f32::from_le_bytes([texel[at], texel[at + 1], texel[at + 2], texel[at + 3]])
+                }
+            };
+            EnvironmentData::Rgb32F(
+                level
+                    .chunks_exact(texel_size)
+                    .take(count)
+                    .map(|texel| [0, 1, 2].map(|c| component(texel, c)))
+                    .collect(),
+            )
+        }
+        other => return Err(err(format!("VkFormat {other} is not an HDR format"))),
+    };
+    Ok(Environment {
+        name: texture.name,
+        width: texture.width,
+        height: texture.height,
+        data,
+        irradiance: None,
+    })
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1F);
+    let fraction = f32::from(bits & 0x3FF);
+    sign * match exponent {
+        0 => fraction * 2f32.powi(-24),
+        31 if fraction == 0.0 => f32::INFINITY,
+        31 => f32::NAN,
+        e => (1.0 + fraction / 1024.0) * 2f32.powi(e - 15),
+    }
+}
+
+/// Decode a Radiance RGBE file with the standard `-Y h +X w` orientation.
+/// Scanlines may be flat or new-style run-length encoded.
+fn parse_radiance(name: &str, bytes: &[u8]) -> Result<(u32, u32, Vec<[f32; 3]>), AssetError> {
+    let err = |msg: &str| AssetError::TextureDecode(format!("{name}: {msg}"));
+    let mut rest = bytes;
+    loop {
+        let line = take_line(&mut rest).ok_or_else(|| err("truncated HDR header"))?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix(b"FORMAT=")
+            && format != b"32-bit_rle_rgbe"
+        {
+            return Err(err("only 32-bit_rle_rgbe HDR files are supported"));
+        }
+    }
+    let resolution = take_line(&mut rest)
+        .and_then(|line| std::str::from_utf8(line).ok())
+        .ok_or_else(|| err("missing HDR resolution"))?;
+    let (height, width) = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", h, "+X", w] => h.parse::<u32>().ok().zip(w.parse::<u32>().ok()),
+        _ => None,
+    }
+    .ok_or_else(|| err("unsupported HDR orientation or resolution"))?;
+
+    let mut texels = Vec::new();
+    for _ in 0..height {
+        let row = read_scanline(&mut rest, width as usize)
+            .ok_or_else(|| err("truncated or corrupt HDR scanline"))?;
+        texels.extend(row.into_iter().map(rgbe_to_rgb));
+    }
+    Ok((width, height, texels))
+}
+
+fn take_line<'a>(rest: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let end = rest.iter().position(|b| *b == b'\n')?;
+    let line = &rest[..end];
+    *rest = &rest[end + 1..];
+    Some(line)
+}
+
+fn read_scanline(rest: &mut &[u8], width: usize) -> Option<Vec<[u8; 4]>> {
+    let rle = (8..=0x7FFF).contains(&width) && matches!(rest, [2, 2, hi, _, ..] if hi & 0x80 == 0);
+    if !rle {
+        let (row, tail) = rest.split_at_checked(width * 4)?;
+        *rest = tail;
+        return Some(
+            row.chunks_exact(4)
+                .map(|p| [p[0], p[1], p[2], p[3]])
+                .collect(),
+        );
+    }
+    if usize::from(rest[2]) << 8 | usize::from(rest[3]) != width {
+        return None;
+    }
+    *rest = &rest[4..];
+    // Each channel is encoded separately: a count above 128 repeats the next
+    // byte `count - 128` times, otherwise `count` literal bytes follow.
+    let mut row = vec![[0u8; 4]; width];
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let (&count, tail) = rest.split_first()?;
+            let (run, values, tail) = if count > 128 {
+                let run = usize::from(count - 128);
+                let (value, tail) = tail.split_at_checked(1)?;
+                (
+                    run,
+                    std::iter::repeat_n(value[0], run).collect::<Vec<_>>(),
+                    tail,
+                )
+            } else {
+                let run = usize::from(count);
+                let (values, tail) = tail.split_at_checked(run)?;
+                (run, values.to_vec(), tail)
+            };
+            if run == 0 || x + run > width {
+                return None;
+            }
+            for (texel, value) in row[x..x + run].iter_mut().zip(values) {
+                texel[channel] = value;
+            }
+            x += run;
+            *rest = tail;
+        }
+    }
+    Some(row)
+}
+
+fn rgbe_to_rgb([r, g, b, e]: [u8; 4]) -> [f32; 3] {
+    if e == 0 {
+        return [0.0; 3];
+    }
+    let scale = 2f32.powi(i32::from(e) - 136);
+    [r, g, b].map(|c| f32::from(c) * scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AssetStore;
+
+    /// A Radiance file of `width` x `height` copies of `texel`, with each
+    /// scanline run-length encoded as one run per channel.
+    fn hdr_bytes(texel: [u8; 4], width: u8, height: u32) -> Vec<u8> {
+        assert!((8..=127).contains(&width));
+        let mut out =
+            format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {height} +X {width}\n").into_bytes();
+        for _ in 0..height {
+            out.extend_from_slice(&[2, 2, 0, width]);
+            for value in texel {
+                out.extend_from_slice(&[128 + width, value]);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn decode_radiance_hdr() {
+        // Exponent 129 scales by 2^-7: 128 -> 1.0, 64 -> 0.5.
+        let env = Environment::decode("sky", &hdr_bytes([128, 64, 0, 129], 8, 2)).unwrap();
+        assert_eq!((env.width, env.height), (8, 2));
+        assert_eq!(env.data, EnvironmentData::Rgb32F(vec![[1.0, 0.5, 0.0]; 16]));
+
+        // Widths under 8 are always flat.
+        let flat = b"#?RGBE\n\n-Y 1 +X 2\n\x80\x80\x80\x81\x00\x00\x00\x00";
+        let env = Environment::decode("flat", flat).unwrap();
+        assert_eq!(env.data, EnvironmentData::Rgb32F(vec![[1.0; 3], [0.0; 3]]));
+
+        let bytes = hdr_bytes([1, 1, 1, 128], 8, 2);
+        assert!(Environment::decode("short", &bytes[..bytes.len() - 1]).is_err());
+        assert!(Environment::decode("png", b"\x89PNG").is_err());
+    }
+
+    #[test]
+    fn uniform_sky_irradiance_equals_radiance() {
+        let mut env = Environment {
+            name: "grey".into(),
+            width: 128,
+            height: 64,
+            data: EnvironmentData::Rgb32F(vec![[0.5; 3]; 128 * 64]),
+            irradiance: None,
+        };
+        env.prefilter_irradiance(8, 4).unwrap();
+        let map = env.irradiance.unwrap();
+        assert_eq!(map.texels.len(), 32);
+        for texel in map.texels {
+            assert!((texel[0] - 0.5).abs() < 0.01, "{texel:?}");
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("studio.hdr");
+        std::fs::write(&path, hdr_bytes([128, 128, 128, 129], 64, 32)).unwrap();
+        let mut store = AssetStore::new();
+        let id = store.import_environment(&path, true).unwrap();
+        let env = store.get_environment(id).unwrap();
+        assert_eq!(env.name, "studio");
+        let map = env.irradiance.as_ref().unwrap();
+        assert_eq!((map.width, map.height), IRRADIANCE_SIZE);
+        assert!(map.texels.iter().all(|t| (t[1] - 1.0).abs() < 0.01));
+    }
+
+    #[test]
+    fn half_float_conversion() {
+        assert_eq!(f16_to_f32(0x3C00), 1.0);
+        assert_eq!(f16_to_f32(0xC000), -2.0);
+        assert_eq!(f16_to_f32(0x0000), 0.0);
+        assert_eq!(f16_to_f32(0x7C00), f32::INFINITY);
+    }
+}
@@ -0,0 +1,367 @@
+//! glTF 2.0 export of registry meshes and materials.
+//!
+//! `AssetStore::export_gltf` writes a binary `.glb`, or a `.gltf` whose one
+//! buffer is embedded as a base64 `data:` URI. Each mesh becomes a single
+//! triangle primitive instanced by its own root node. Materials keep their
+//! base color factor and, for RGBA8 textures, their base color texture,
+//! embedded as PNG. Skins, LOD chains, and block-compressed textures are not
+//! exported.
+
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use crate::gltf::{self, FLOAT, TRIANGLES, UNSIGNED_INT};
+use crate::{Aabb, Asset, AssetError, AssetId, AssetStore, Mesh, TextureData};
+
+const ARRAY_BUFFER: u64 = 34962;
+const ELEMENT_ARRAY_BUFFER: u64 = 34963;
+
+impl AssetStore {
+    /// Export the meshes and materials in `ids` to `path`, as GLB if it has a
+    /// `.glb` extension and as glTF with an embedded buffer otherwise.
+    ///
+    /// Materials referenced by exported meshes are included. Geometry of
+    /// meshes from `open_pack` is loaded first. Fails for other asset kinds
+    /// and for meshes without positions.
+    pub fn export_gltf(
+        &mut self,
+        ids: &[AssetId],
+        path: impl AsRef<Path>,
+    ) -> Result<(), AssetError> {
+        let path = path.as_ref();
+        for &id in ids {
+            self.load_payload(id)?;
+        }
+        let mut writer = GltfWriter::default();
+        for &id in ids {
+            match self.get(id).ok_or(AssetError::NotFound(id))? {
+                Asset::Mesh(mesh) => writer.mesh(self, id, mesh)?,
+                Asset::Material(_) => {
+                    writer.material(self, id)?;
+                }
+                other => {
+                    return Err(AssetError::GltfExport(format!(
+                        "{id:?} is a {}, not a mesh or material",
+                        other.kind()
+                    )));
+                }
+            }
+        }
+        let binary = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("glb"));
+        std::fs::write(path, writer.finish(binary)?)?;
+        Ok(())
+    }
+}
+
+/// The binary buffer and the document arrays that index into it.
+#[derive(Default)]
+struct GltfWriter {
+    bin: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    images: Vec<Value>,
+    textures: Vec<Value>,
+    materials: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    /// Store assets already written, by their glTF index. Textures that
+    /// cannot be exported map to `None`.
+    written_meshes: BTreeMap<AssetId, usize>,
+    written_materials: BTreeMap<AssetId, usize>,
+    written_textures: BTreeMap<AssetId, Option<usize>>,
+}
+
+impl GltfWriter {
+    /// Append `data` to the buffer as a 4-byte aligned view.
+    fn view(&mut self, data: &[u8], target: Option<u64>) -> usize {
+        self.bin.resize(self.bin.len().next_multiple_of(4), 0);
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": (self.bin.len()),
+            "byteLength": (data.len())
+        });
+        if let Some(target) = target {
+            set(&mut view, "target", target);
+        }
+        self.bin.extend_from_slice(data);
+        push(&mut self.buffer_views, view)
+    }
+
+    fn float_accessor<const N: usize>(&mut self, values: &[[f32; N]]) -> usize {
+        let data: Vec<u8> = values
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let view = self.view(&data, Some(ARRAY_BUFFER));
+        let kind = match N {
+            2 => "VEC2",
+            3 => "VEC3",
+            _ => "VEC4",
+        };
+        let accessor = json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": (values.len()),
+            "type": kind
+        });
+        push(&mut self.accessors, accessor)
+    }
+
+    fn index_accessor(&mut self, indices: &[u32]) -> usize {
+        let data: Vec<u8> = indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+        let view = self.view(&data, Some(ELEMENT_ARRAY_BUFFER));
+        let accessor = json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_INT,
+            "count": (indices.len()),
+            "type": "SCALAR"
+        });
+        push(&mut self.accessors, accessor)
+    }
+
+    fn mesh(&mut self, store: &AssetStore, id: AssetId, mesh: &Mesh) -> Result<(), AssetError> {
+        if self.written_meshes.contains_key(&id) {
+            return Ok(());
+        }
+        let Some(aabb) = Aabb::from_points(&mesh.positions) else {
+            return Err(AssetError::GltfExport(format!(
+                "mesh {} has no geometry",
+                mesh.name
+            )));
+        };
+        let vertices = mesh.positions.len();
+        let position = self.float_accessor(&mesh.positions);
+        // POSITION accessors must declare their bounds.
+        set(&mut self.accessors[position], "min", aabb.min.to_vec());
+        set(&mut self.accessors[position], "max", aabb.max.to_vec());
+        let mut attributes = json!({ "POSITION": position });
+        if mesh.normals.len() == vertices {
+            set(
+                &mut attributes,
+                "NORMAL",
+                self.float_accessor(&mesh.normals),
+            );
+        }
+        if mesh.uvs.len() == vertices {
+            set(
+                &mut attributes,
+                "TEXCOORD_0",
+                self.float_accessor(&mesh.uvs),
+            );
+        }
+        if mesh.tangents.len() == vertices {
+            set(
+                &mut attributes,
+                "TANGENT",
+                self.float_accessor(&mesh.tangents),
+            );
+        }
+
+        let mut primitive = json!({ "attributes": attributes, "mode": TRIANGLES });
+        if !mesh.indices.is_empty() {
+            set(
+                &mut primitive,
+                "indices",
+                self.index_accessor(&mesh.indices),
+            );
+        }
+        if let Some(material) = mesh.material {
+            set(&mut primitive, "material", self.material(store, material)?);
+        }
+        let name = mesh.name.as_str();
+        let index = push(
+            &mut self.meshes,
+            json!({ "name": name, "primitives": [primitive] }),
+        );
+        self.nodes.push(json!({ "name": name, "mesh": index }));
+        self.written_meshes.insert(id, index);
+        Ok(())
+    }
+
+    fn material(&mut self, store: &AssetStore, id: AssetId) -> Result<usize, AssetError> {
+        if let Some(&index) = self.written_materials.get(&id) {
+            return Ok(index);
+        }
+        let material = store.get_material(id).ok_or(AssetError::NotFound(id))?;
+        let mut pbr = json!({ "baseColorFactor": (material.base_color.to_vec()) });
+        if let Some(texture) = material.base_color_texture
+            && let Some(index) = self.texture(store, texture)?
+        {
+            set(&mut pbr, "baseColorTexture", json!({ "index": index }));
+        }
+        let name = material.name.as_str();
+        let index = push(
+            &mut self.materials,
+            json!({ "name": name, "pbrMetallicRoughness": pbr }),
+        );
+        self.written_materials.insert(id, index);
+        Ok(index)
+    }
+
+    /// Write an RGBA8 texture as an embedded PNG; `None` for block-compressed
+    /// textures, which core glTF cannot reference.
+    fn texture(&mut self, store: &AssetStore, id: AssetId) -> Result<Option<usize>, AssetError> {
+        if let Some(&index) = self.written_textures.get(&id) {
+            return Ok(index);
+        }
+        let texture = store.get_texture(id).ok_or(AssetError::NotFound(id))?;
+        let index = match &texture.data {
+            TextureData::Rgba8(pixels) => {
+                let err = |msg: String| {
+                    AssetError::GltfExport(format!("texture {}: {msg}", texture.name))
+                };
+                let image =
+                    image::RgbaImage::from_raw(texture.width, texture.height, pixels.clone())
+                        .ok_or_else(|| err("pixel data does not match its size".into()))?;
+                let mut png = Vec::new();
+                image
+                    .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+                    .map_err(|e| err(e.to_string()))?;
+                let view = self.view(&png, None);
+                let name = texture.name.as_str();
+                let image = push(
+                    &mut self.images,
+                    json!({ "name": name, "bufferView": view, "mimeType": "image/png" }),
+                );
+                Some(push(&mut self.textures, json!({ "source": image })))
+            }
+            TextureData::Compressed { .. } => None,
+        };
+        self.written_textures.insert(id, index);
+        Ok(index)
+    }
+
+    /// Assemble the document. Empty arrays are left out, as the schema
+    /// requires at least one item in any that are present.
+    fn finish(self, binary: bool) -> Result<Vec<u8>, AssetError> {
+        let mut doc = json!({
+            "asset": { "version": "2.0", "generator": "worldspace-assets" }
+        });
+        if !self.nodes.is_empty() {
+            let nodes: Vec<usize> = (0..self.nodes.len()).collect();
+            set(&mut doc, "scene", 0u32);
+            set(&mut doc, "scenes", vec![json!({ "nodes": nodes })]);
+        }
+        if !self.bin.is_empty() {
+            let mut buffer = json!({ "byteLength": (self.bin.len()) });
+            if !binary {
+                let uri = format!(
+                    "data:application/octet-stream;base64,{}",
+                    gltf::encode_base64(&self.bin)
+                );
+                set(&mut buffer, "uri", uri);
+            }
+            set(&mut doc, "buffers", vec![buffer]);
+        }
+        for (key, items) in [
+            ("bufferViews", self.buffer_views),
+            ("accessors", self.accessors),
+            ("images", self.images),
+            ("textures", self.textures),
+            ("materials", self.materials),
+            ("meshes", self.meshes),
+            ("nodes", self.nodes),
+        ] {
+            if !items.is_empty() {
+                set(&mut doc, key, items);
+            }
+        }
+        if binary {
+            gltf::write_glb(&doc, &self.bin)
+        } else {
+            Ok(serde_json::to_vec_pretty(&doc)?)
+        }
+    }
+}
+
+/// Add `key` to a JSON object.
+fn set(object: &mut Value, key: &str, value: impl Into<Value>) {
+    if let Value::Object(map) = object {
+        map.insert(key.to_string(), value.into());
+    }
+}
+
+/// Append `item` and return its index.
+fn push(items: &mut Vec<Value>, item: Value) -> usize {
+    items.push(item);
+    items.len() - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AssetError, AssetStore, Material, Mesh, Skeleton};
+
+    fn textured_triangle(store: &mut AssetStore) -> crate::AssetId {
+        let material = store.register_material(Material {
+            name: "paint".into(),
+            base_color: [0.25, 0.5, 0.75, 1.0],
+            base_color_texture: None,
+            shader: None,
+        });
+        let mut mesh = Mesh {
+            name: "tri".into(),
+            vertex_count: 3,
+            index_count: 3,
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 2.0, 0.0]],
+            normals: vec![[0.0, 0.0, 1.0]; 3],
+            uvs: vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]],
+            indices: vec![0, 1, 2],
+            material: Some(material),
+            ..Default::default()
+        };
+        mesh.update_bounds();
+        store.register_mesh(mesh)
+    }
+
+    #[test]
+    fn export_roundtrips_through_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = AssetStore::new();
+        let id = textured_triangle(&mut store);
+        let source = store.get_mesh(id).unwrap().clone();
+
+        for file in ["out.gltf", "out.glb"] {
+            let path = dir.path().join(file);
+            store.export_gltf(&[id, id], &path).unwrap();
+
+            let mut imported = AssetStore::new();
+            let report = imported.import_gltf(&path).unwrap();
+            assert!(report.is_clean(), "{file}: {:?}", report.warnings);
+            let mesh = report
+                .ids
+                .iter()
+                .find_map(|id| imported.get_mesh(*id))
+                .unwrap();
+            assert_eq!(mesh.name, "tri_0");
+            assert_eq!(mesh.positions, source.positions);
+            assert_eq!(mesh.normals, source.normals);
+            assert_eq!(mesh.uvs, source.uvs);
+            assert_eq!(mesh.indices, source.indices);
+            assert_eq!(mesh.bounds, source.bounds);
+            let material = imported.get_material(mesh.material.unwrap()).unwrap();
+            assert_eq!(material.base_color, [0.25, 0.5, 0.75, 1.0]);
+        }
+    }
+
+    #[test]
+    fn export_rejects_unsupported_assets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.gltf");
+        let mut store = AssetStore::new();
+        let skeleton = store.register_skeleton(Skeleton::default());
+        let cube = store.register_default_cube();
+        for id in [skeleton, cube] {
+            assert!(matches!(
+                store.export_gltf(&[id], &path),
+                Err(AssetError::GltfExport(_))
+            ));
+        }
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,1067 @@
+//! glTF 2.0 geometry import: buffers, buffer views, and accessors.
+//!
+//! Only the parts of the format needed to build `Mesh` geometry and
+//! `Skeleton`s are read.
+//! Buffers may be external files (resolved relative to the glTF file),
+//! base64 `data:` URIs, or the BIN chunk of a binary `.glb` container.
+//! Sparse accessors and glTF extensions are not supported; files that
+//! require an extension are rejected.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::{AssetError, IDENTITY_MATRIX, ImportWarning, Joint, Mesh, Skeleton};
+
+pub(crate) const FLOAT: u64 = 5126;
+const UNSIGNED_BYTE: u64 = 5121;
+const UNSIGNED_SHORT: u64 = 5123;
+pub(crate) const UNSIGNED_INT: u64 = 5125;
+pub(crate) const TRIANGLES: u64 = 4;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;
+
+/// True if `bytes` start with the binary glTF (GLB) magic.
+pub(crate) fn is_glb(bytes: &[u8]) -> bool {
+    bytes.starts_with(GLB_MAGIC)
+}
+
+/// Split a GLB container into its JSON document and optional BIN chunk.
+pub(crate) fn parse_glb(bytes: &[u8]) -> Result<(Value, Option<Vec<u8>>), AssetError> {
+    let word = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes(read_bytes(b, 0)))
+            .ok_or_else(|| parse_err("truncated GLB".into()))
+    };
+    if !is_glb(bytes) {
+        return Err(parse_err("missing GLB magic".into()));
+    }
+    let version = word(4)?;
+    if version != 2 {
+        return Err(parse_err(format!("unsupported GLB version {version}")));
+    }
+    let length = word(8)? as usize;
+    if length > bytes.len() {
+        return Err(parse_err(format!(
+            "GLB header length {length} exceeds file size {}",
+            bytes.len()
+        )));
+    }
+
+    let mut json = None;
+    let mut bin = None;
+    let mut offset = 12;
+    while offset + 8 <= length {
+        let chunk_length = word(offset)? as usize;
+        let chunk_type = word(offset + 4)?;
+        let start = offset + 8;
+        let chunk = bytes
+            .get(start..start + chunk_length)
+            .filter(|_| start + chunk_length <= length)
+            .ok_or_else(|| parse_err("GLB chunk exceeds file length".into()))?;
+        match chunk_type {
+            GLB_CHUNK_JSON if json.is_none() => {
+                let text = std::str::from_utf8(chunk)
+                    .map_err(|e| parse_err(format!("GLB JSON chunk: {e}")))?;
+                json = Some(serde_json::from_str(text).map_err(|e| parse_err(e.to_string()))?);
+            }
+            GLB_CHUNK_BIN if bin.is_none() => bin = Some(chunk.to_vec()),
+            // Unknown chunks must be ignored per the spec.
+            _ => {}
+        }
+        // Chunks are 4-byte aligned.
+        offset = start + chunk_length.next_multiple_of(4);
+    }
+    let json = json.ok_or_else(|| parse_err("GLB has no JSON chunk".into()))?;
+    Ok((json, bin))
+}
+
+/// Pack a JSON document and BIN chunk into a GLB container, padding the JSON
+/// with spaces and the BIN chunk with zeros as the spec requires.
+pub(crate) fn write_glb(json: &Value, bin: &[u8]) -> Result<Vec<u8>, AssetError> {
+    let mut json = serde_json::to_vec(json)?;
+    json.resize(json.len().next_multiple_of(4), b' ');
+    let bin_len = bin.len().next_multiple_of(4);
+    let length = 12 + 8 + json.len() + if bin.is_empty() { 0 } else { 8 + bin_len };
+
+    let mut out = Vec::with_capacity(length);
+    out.extend_from_slice(GLB_MAGIC);
+    out.extend_from_slice(&2u32.to_le_bytes());
+    out.extend_from_slice(&(length as u32).to_le_bytes());
+    out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    out.extend_from_slice(&GLB_CHUNK_JSON.to_le_bytes());
+    out.extend_from_slice(&json);
+    if !bin.is_empty() {
+        out.extend_from_slice(&(bin_len as u32).to_le_bytes());
+        out.extend_from_slice(&GLB_CHUNK_BIN.to_le_bytes());
+        out.extend_from_slice(bin);
+        out.resize(length, 0);
+    }
+    Ok(out)
+}
+
+/// A parsed glTF document with its binary buffers loaded.
+pub(crate) struct GltfDocument {
+    json: Value,
+    buffers: Vec<Vec<u8>>,
+    base_dir: PathBuf,
+}
+
+impl GltfDocument {
+    /// Load every buffer referenced by `json`. Relative URIs resolve against
+    /// `base_dir`; the first buffer may omit its URI to refer to the GLB `bin`
+    /// chunk.
+    pub(crate) fn load(
+        json: Value,
+        base_dir: &Path,
+        mut bin: Option<Vec<u8>>,
+    ) -> Result<Self, AssetError> {
+        let mut buffers = Vec::new();
+        for (i, buffer) in array(&json, "buffers").iter().enumerate() {
+            let data = match buffer.get("uri").and_then(|u| u.as_str()) {
+                Some(uri) => load_uri(uri, base_dir)?,
+                None if i == 0 => bin
+                    .take()
+                    .ok_or_else(|| parse_err("buffer 0 has no uri and no GLB BIN chunk".into()))?,
+                None => return Err(parse_err(format!("buffer {i} has no uri"))),
+            };
+            let byte_length = usize_field(buffer, "byteLength")?;
+            if data.len() < byte_length {
+                return Err(parse_err(format!(
+                    "buffer {i}: expected {byte_length} bytes, found {}",
+                    data.len()
+                )));
+            }
+            buffers.push(data);
+        }
+        Ok(Self {
+            json,
+            buffers,
+            base_dir: base_dir.to_path_buf(),
+        })
+    }
+
+    /// The document's `images` array.
+    pub(crate) fn images(&self) -> &[Value] {
+        array(&self.json, "images")
+    }
+
+    /// Encoded bytes of image `index`, from a URI or (in GLB files) a buffer
+    /// view.
+    pub(crate) fn image_data(&self, index: usize) -> Result<Vec<u8>, AssetError> {
+        let image = self
+            .images()
+            .get(index)
+            .ok_or_else(|| parse_err(format!("image {index} out of range")))?;
+        if let Some(uri) = image.get("uri").and_then(|u| u.as_str()) {
+            return load_uri(uri, &self.base_dir);
+        }
+        let view = image
+            .get("bufferView")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| parse_err(format!("image {index} has no uri or bufferView")))?;
+        Ok(self.buffer_view(view as usize)?.to_vec())
+    }
+
+    /// The document JSON.
+    pub(crate) fn json(&self) -> &Value {
+        &self.json
+    }
+
+    pub(crate) fn into_json(self) -> Value {
+        self.json
+    }
+
+    /// The document's `meshes` array.
+    pub(crate) fn meshes(&self) -> &[Value] {
+        array(&self.json, "meshes")
+    }
+
+    /// Fail on extensions the file requires; warn about ones it only uses.
+    pub(crate) fn check_extensions(&self) -> Result<Vec<ImportWarning>, AssetError> {
+        let names = |key| array(&self.json, key).iter().filter_map(|e| e.as_str());
+        if let Some(required) = names("extensionsRequired").next() {
+            return Err(parse_err(format!(
+                "requires unsupported extension {required}"
+            )));
+        }
+        Ok(names("extensionsUsed")
+            .map(|e| ImportWarning::UnsupportedExtension(e.to_string()))
+            .collect())
+    }
+
+    /// The document's `skins` array.
+    pub(crate) fn skins(&self) -> &[Value] {
+        array(&self.json, "skins")
+    }
+
+    /// Build a `Skeleton` from glTF skin `index`.
+    ///
+    /// Joints keep the skin's order, so `JOINTS_0` indices address them
+    /// directly. A joint's parent is the joint node whose `children` lists
+    /// it; joints without a parent in the skin are roots. Skins without
+    /// inverse bind matrices use identity matrices, as the spec requires.
+    pub(crate) fn skeleton(&self, index: usize) -> Result<Skeleton, AssetError> {
+        let skin = self
+            .skins()
+            .get(index)
+            .ok_or_else(|| parse_err(format!("skin {index} out of range")))?;
+        let nodes = array(&self.json, "nodes");
+        let joint_nodes = array(skin, "joints")
+            .iter()
+            .map(|j| {
+                j.as_u64()
+                    .map(|j| j as usize)
+                    .filter(|j| *j < nodes.len())
+                    .ok_or_else(|| parse_err(format!("skin {index}: invalid joint node {j}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let matrices = match skin.get("inverseBindMatrices").and_then(|a| a.as_u64()) {
+            Some(a) => self.read_floats::<16>(a as usize)?,
+            None => vec![IDENTITY_MATRIX; joint_nodes.len()],
+        };
+        if matrices.len() < joint_nodes.len() {
+            return Err(parse_err(format!(
+                "skin {index}: {} inverse bind matrices for {} joints",
+                matrices.len(),
+                joint_nodes.len()
+            )));
+        }
+
+        let joint_of = |node: usize| joint_nodes.iter().position(|j| *j == node);
+        let mut parents = vec![None; joint_nodes.len()];
+        for (node, node_val) in nodes.iter().enumerate() {
+            let Some(parent) = joint_of(node) else {
+                continue;
+            };
+            for child in array(node_val, "children") {
+                if let Some(child) = child.as_u64().and_then(|c| joint_of(c as usize)) {
+                    parents[child] = Some(parent as u32);
+                }
+            }
+        }
+
+        let joints = joint_nodes
+            .iter()
+            .zip(parents)
+            .zip(matrices)
+            .map(|((&node, parent), inverse_bind_matrix)| Joint {
+                name: nodes[node]
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .map_or_else(|| format!("joint_{node}"), str::to_string),
+                parent,
+                inverse_bind_matrix,
+            })
+            .collect();
+        let name = skin.get("name").and_then(|n| n.as_str()).unwrap_or("skin");
+        Ok(Skeleton {
+            name: format!("{name}_{index}"),
+            joints,
+        })
+    }
+
+    /// Build a `Mesh` from glTF mesh `index`, merging its triangle primitives.
+    ///
+    /// Vertex attributes are either empty or one per position; primitives
+    /// missing an attribute another primitive has are padded with zeros.
+    /// Primitives without indices are indexed sequentially. Skinning weights
+    /// are renormalized to sum to 1. Skipped primitives and missing normals
+    /// are added to `warnings`.
+    pub(crate) fn mesh(
+        &self,
+        index: usize,
+        name: String,
+        warnings: &mut Vec<ImportWarning>,
+    ) -> Result<Mesh, AssetError> {
+        let mesh_val = self
+            .meshes()
+            .get(index)
+            .ok_or_else(|| parse_err(format!("mesh {index} out of range")))?;
+        let mut mesh = Mesh {
+            name,
+            ..Default::default()
+        };
+        let (mut has_normals, mut has_uvs, mut has_skin) = (false, false, false);
+        let mut missing_normals = false;
+
+        for (p, primitive) in array(mesh_val, "primitives").iter().enumerate() {
+            let mode = primitive
+                .get("mode")
+                .and_then(|m| m.as_u64())
+                .unwrap_or(TRIANGLES);
+            if mode != TRIANGLES {
+                warnings.push(ImportWarning::NonTrianglePrimitive {
+                    mesh: mesh.name.clone(),
+                    primitive: p,
+                    mode,
+                });
+                continue;
+            }
+            let attributes = primitive.get("attributes");
+            let attribute = |name: &str| {
+                attributes
+                    .and_then(|a| a.get(name))
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+            };
+            let position = attribute("POSITION")
+                .ok_or_else(|| parse_err(format!("mesh {index}: primitive has no POSITION")))?;
+
+            let base = mesh.positions.len();
+            let positions = self.read_floats::<3>(position)?;
+            let count = positions.len();
+            mesh.positions.extend(positions);
+
+            match attribute("NORMAL") {
+                Some(a) => {
+                    has_normals = true;
+                    mesh.normals
+                        .extend(self.read_vertex_attribute::<3>(a, count)?);
+                }
+                None => {
+                    missing_normals = true;
+                    mesh.normals.resize(base + count, [0.0; 3]);
+                }
+            }
+            match attribute("TEXCOORD_0") {
+                Some(a) => {
+                    has_uvs = true;
+                    mesh.uvs.extend(self.read_vertex_attribute::<2>(a, count)?);
+                }
+                None => mesh.uvs.resize(base + count, [0.0; 2]),
+            }
+            match (attribute("JOINTS_0"), attribute("WEIGHTS_0")) {
+                (Some(j), Some(w)) => {
+                    has_skin = true;
+                    let joints = self.read_joints(j)?;
+                    if joints.len() != count {
+                        return Err(parse_err(format!(
+                            "accessor {j}: {} elements, expected {count}",
+                            joints.len()
+                        )));
+                    }
+                    mesh.joints.extend(joints);
+                    mesh.weights.extend(
+                        self.read_vertex_attribute::<4>(w, count)?
+                            .into_iter()
+                            .map(normalize_weights),
+                    );
+                }
+                _ => {
+                    mesh.joints.resize(base + count, [0; 4]);
+                    mesh.weights.resize(base + count, [0.0; 4]);
+                }
+            }
+
+            let indices = match primitive.get("indices").and_then(|v| v.as_u64()) {
+                Some(a) => self.read_indices(a as usize)?,
+                None => (0..count as u32).collect(),
+            };
+            if let Some(bad) = indices.iter().find(|&&i| i as usize >= count) {
+                return Err(parse_err(format!(
+                    "mesh {index}: index {bad} out of range for {count} vertices"
+                )));
+            }
+            mesh.indices
+                .extend(indices.into_iter().map(|i| i + base as u32));
+        }
+
+        if missing_normals {
+            warnings.push(ImportWarning::MissingNormals {
+                mesh: mesh.name.clone(),
+            });
+        }
+        if !has_normals {
+            mesh.normals.clear();
+        }
+        if !has_uvs {
+            mesh.uvs.clear();
+        }
+        if !has_skin {
+            mesh.joints.clear();
+            mesh.weights.clear();
+        }
+        mesh.vertex_count = mesh.positions.len() as u32;
+        mesh.index_count = mesh.indices.len() as u32;
+        Ok(mesh)
+    }
+
+    fn read_vertex_attribute<const N: usize>(
+        &self,
+        accessor: usize,
+        count: usize,
+    ) -> Result<Vec<[f32; N]>, AssetError> {
+        let values = self.read_floats::<N>(accessor)?;
+        if values.len() != count {
+            return Err(parse_err(format!(
+                "accessor {accessor}: {} elements, expected {count}",
+                values.len()
+            )));
+        }
+        Ok(values)
+    }
+
+    /// Read a float vector accessor. Normalized unsigned byte/short
+    /// components are mapped to 0.0..=1.0.
+    fn read_floats<const N: usize>(&self, accessor: usize) -> Result<Vec<[f32; N]>, AssetError> {
+        let view = self.accessor_view(accessor, N)?;
+        let normalized = view.normalized;
+        let mut out = Vec::with_capacity(view.count);
+        for element in view.elements() {
+            let mut value = [0.0f32; N];
+            for (c, v) in value.iter_mut().enumerate() {
+                *v = match view.component_type {
+                    FLOAT => f32::from_le_bytes(read_bytes(element, c * 4)),
+                    UNSIGNED_BYTE if normalized => element[c] as f32 / 255.0,
+                    UNSIGNED_SHORT if normalized => {
+                        u16::from_le_bytes(read_bytes(element, c * 2)) as f32 / 65535.0
+                    }
+                    other => {
+                        return Err(parse_err(format!(
+                            "accessor {accessor}: unsupported component type {other} for floats"
+                        )));
+                    }
+                };
+            }
+            out.push(value);
+        }
+        Ok(out)
+    }
+
+    fn read_joints(&self, accessor: usize) -> Result<Vec<[u16; 4]>, AssetError> {
+        let view = self.accessor_view(accessor, 4)?;
+        view.elements()
+            .map(|element| match view.component_type {
+                UNSIGNED_BYTE => Ok([0, 1, 2, 3].map(|c| element[c] as u16)),
+                UNSIGNED_SHORT => {
+                    Ok([0, 1, 2, 3].map(|c| u16::from_le_bytes(read_bytes(element, c * 2))))
+                }
+                other => Err(parse_err(format!(
+                    "accessor {accessor}: unsupported joint component type {other}"
+                ))),
+            })
+            .collect()
+    }
+
+    fn read_indices(&self, accessor: usize) -> Result<Vec<u32>, AssetError> {
+        let view = self.accessor_view(accessor, 1)?;
+        view.elements()
+            .map(|element| match view.component_type {
+                UNSIGNED_BYTE => Ok(element[0] as u32),
+                UNSIGNED_SHORT => Ok(u16::from_le_bytes(read_bytes(element, 0)) as u32),
+                UNSIGNED_INT => Ok(u32::from_le_bytes(read_bytes(element, 0))),
+                other => Err(parse_err(format!(
+                    "accessor {accessor}: unsupported index component type {other}"
+                ))),
+            })
+            .collect()
+    }
+
+    /// Bytes of buffer view `index`, bounds-checked against its buffer.
+    fn buffer_view(&self, index: usize) -> Result<&[u8], AssetError> {
+        let view = array(&self.json, "bufferViews")
+            .get(index)
+            .ok_or_else(|| parse_err(format!("bufferView {index} out of range")))?;
+        let buffer = self
+            .buffers
+            .get(usize_field(view, "buffer")?)
+            .ok_or_else(|| parse_err(format!("bufferView {index}: buffer out of range")))?;
+        let offset = optional_usize(view, "byteOffset");
+        let length = usize_field(view, "byteLength")?;
+        offset
+            .checked_add(length)
+            .and_then(|end| buffer.get(offset..end))
+            .ok_or_else(|| parse_err(format!("bufferView {index} exceeds its buffer")))
+    }
+
+    /// Resolve an accessor to a bounds-checked byte range within its buffer.
+    fn accessor_view(
+        &self,
+        index: usize,
+        components: usize,
+    ) -> Result<AccessorView<'_>, AssetError> {
+        let accessor = array(&self.json, "accessors")
+            .get(index)
+            .ok_or_else(|| parse_err(format!("accessor {index} out of range")))?;
+        if accessor.get("sparse").is_some() {
+            return Err(parse_err(format!(
+                "accessor {index}: sparse accessors are not supported"
+            )));
+        }
+        let expected_type = match components {
+            1 => "SCALAR",
+            2 => "VEC2",
+            3 => "VEC3",
+            16 => "MAT4",
+            _ => "VEC4",
+        };
+        let ty = accessor.get("type").and_then(|t| t.as_str()).unwrap_or("");
+        if ty != expected_type {
+            return Err(parse_err(format!(
+                "accessor {index}: expected {expected_type}, found {ty:?}"
+            )));
+        }
+        let component_type = accessor
+            .get("componentType")
+            .and_then(|c| c.as_u64())
+            .ok_or_else(|| parse_err(format!("accessor {index} has no componentType")))?;
+        let component_size = match component_type {
+            UNSIGNED_BYTE => 1,
+            UNSIGNED_SHORT => 2,
+            FLOAT | UNSIGNED_INT => 4,
+            other => {
+                return Err(parse_err(format!(
+                    "accessor {index}: unsupported component type {other}"
+                )));
+            }
+        };
+        let count = usize_field(accessor, "count")?;
+        let element_size = component_size * components;
+        let normalized = accessor
+            .get("normalized")
+            .and_then(|n| n.as_bool())
+            .unwrap_or(false);
+
+        let view_index = accessor
+            .get("bufferView")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| parse_err(format!("accessor {index} has no bufferView")))?;
+        let view_bytes = self.buffer_view(view_index as usize)?;
+        let stride = array(&self.json, "bufferViews")[view_index as usize]
+            .get("byteStride")
+            .and_then(|s| s.as_u64())
+            .map_or(element_size, |s| s as usize);
+        let start = optional_usize(accessor, "byteOffset");
+        let needed = if count == 0 {
+            Some(0)
+        } else {
+            stride
+                .checked_mul(count - 1)
+                .and_then(|n| n.checked_add(start))
+                .and_then(|n| n.checked_add(element_size))
+        };
+        if needed.is_none_or(|needed| needed > view_bytes.len()) || stride < element_size {
+            return Err(parse_err(format!(
+                "accessor {index} exceeds bufferView {view_index}"
+            )));
+        }
+
+        Ok(AccessorView {
+            bytes: &view_bytes[start.min(view_bytes.len())..],
+            count,
+            stride,
+            element_size,
+            component_type,
+            normalized,
+        })
+    }
+}
+
+/// Strided, bounds-checked element access into a buffer view.
+struct AccessorView<'a> {
+    bytes: &'a [u8],
+    count: usize,
+    stride: usize,
+    element_size: usize,
+    component_type: u64,
+    normalized: bool,
+}
+
+impl AccessorView<'_> {
+    fn elements(&self) -> impl Iterator<Item = &[u8]> {
+        (0..self.count).map(|i| &self.bytes[i * self.stride..i * self.stride + self.element_size])
+    }
+}
+
+/// Scale skinning weights to sum to 1; quantized weights rarely sum exactly.
+fn normalize_weights(weights: [f32; 4]) -> [f32; 4] {
+    let sum: f32 = weights.iter().sum();
+    if sum > 0.0 {
+        weights.map(|w| w / sum)
+    } else {
+        weights
+    }
+}
+
+fn read_bytes<const N: usize>(element: &[u8], offset: usize) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    bytes.copy_from_slice(&element[offset..offset + N]);
+    bytes
+}
+
+fn array<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map_or(&[], |a| a.as_slice())
+}
+
+fn usize_field(value: &Value, key: &str) -> Result<usize, AssetError> {
+    value
+        .get(key)
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .ok_or_else(|| parse_err(format!("missing or invalid {key}")))
+}
+
+fn optional_usize(value: &Value, key: &str) -> usize {
+    value.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as usize
+}
+
+/// Read a buffer or image URI: a base64 `data:` URI or a path relative to
+/// `base_dir`.
+fn load_uri(uri: &str, base_dir: &Path) -> Result<Vec<u8>, AssetError> {
+    if let Some(rest) = uri.strip_prefix("data:") {
+        let (_, payload) = rest
+            .split_once(";base64,")
+            .ok_or_else(|| parse_err(format!("unsupported data uri: {rest:.32}")))?;
+        return decode_base64(payload)
+            .ok_or_else(|| parse_err("invalid base64 in data uri".into()));
+    }
+    Ok(std::fs::read(base_dir.join(uri))?)
+}
+
+fn parse_err(message: String) -> AssetError {
+    AssetError::GltfParse(message)
+}
+
+/// Decode standard (RFC 4648) base64, ignoring padding.
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for byte in input.bytes().filter(|b| *b != b'=') {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        acc = (acc << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+/// Encode standard (RFC 4648) base64 with padding.
+pub(crate) fn encode_base64(input: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, b)| acc | u32::from(*b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(group >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AssetStore;
+
+    /// One triangle: three VEC3 float positions followed by three u16 indices.
+    const TRIANGLE_URI: &str = "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAABAAIAAAA=";
+
+    fn triangle_gltf(uri: &str, position_count: u32) -> String {
+        format!(
+            r#"{{
+                "buffers": [{{ "uri": "{uri}", "byteLength": 44 }}],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+                    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+                ],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": {position_count}, "type": "VEC3" }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+                ],
+                "meshes": [{{
+                    "name": "tri",
+                    "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "indices": 1 }}]
+                }}]
+            }}"#
+        )
+    }
+
+    #[test]
+    fn base64_roundtrip() {
+        assert_eq!(decode_base64("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(decode_base64("AAEC").unwrap(), vec![0, 1, 2]);
+        assert!(decode_base64("a$b").is_none());
+        for input in [&b""[..], b"h", b"he", b"hel", b"hello"] {
+            assert_eq!(decode_base64(&encode_base64(input)).unwrap(), input);
+        }
+        assert_eq!(encode_base64(b"hello"), "aGVsbG8=");
+    }
+
+    #[test]
+    fn import_embedded_triangle() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tri.gltf");
+        std::fs::write(&path, triangle_gltf(TRIANGLE_URI, 3)).unwrap();
+
+        let mut store = AssetStore::new();
+        let ids = store.import_gltf(&path).unwrap().ids;
+        let mesh = store.get_mesh(ids[0]).unwrap();
+        assert_eq!(mesh.name, "tri_0");
+        assert_eq!(mesh.vertex_count, 3);
+        assert_eq!(mesh.positions[1], [1.0, 0.0, 0.0]);
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+        assert!(mesh.normals.is_empty());
+        assert_eq!(mesh.bounds.unwrap().aabb.max, [1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn import_external_interleaved_buffer() {
+        let dir = tempfile::tempdir().unwrap();
+        // Interleaved position + normal, 24-byte stride, no index accessor.
+        let mut bin = Vec::new();
+        for i in 0..3 {
+            for v in [i as f32, 0.0, 0.0, 0.0, 1.0, 0.0] {
+                bin.extend_from_slice(&f32::to_le_bytes(v));
+            }
+        }
+        std::fs::write(dir.path().join("tri.bin"), &bin).unwrap();
+        let gltf = r#"{
+            "buffers": [{ "uri": "tri.bin", "byteLength": 72 }],
+            "bufferViews": [{ "buffer": 0, "byteLength": 72, "byteStride": 24 }],
+            "accessors": [
+                { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" },
+                { "bufferView": 0, "byteOffset": 12, "componentType": 5126, "count": 3, "type": "VEC3" }
+            ],
+            "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0, "NORMAL": 1 } }] }]
+        }"#;
+        let path = dir.path().join("tri.gltf");
+        std::fs::write(&path, gltf).unwrap();
+
+        let mut store = AssetStore::new();
+        let ids = store.import_gltf(&path).unwrap().ids;
+        let mesh = store.get_mesh(ids[0]).unwrap();
+        assert_eq!(mesh.positions[2], [2.0, 0.0, 0.0]);
+        assert_eq!(mesh.normals, vec![[0.0, 1.0, 0.0]; 3]);
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+    }
+
+    fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+        let mut json = json.as_bytes().to_vec();
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let mut bin = bin.to_vec();
+        bin.resize(bin.len().next_multiple_of(4), 0);
+        let length = 12 + 8 + json.len() + 8 + bin.len();
+
+        let mut out = Vec::new();
+        out.extend_from_slice(b"glTF");
+        out.extend_from_slice(&2u32.to_le_bytes());
+        out.extend_from_slice(&(length as u32).to_le_bytes());
+        out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        out.extend_from_slice(&GLB_CHUNK_JSON.to_le_bytes());
+        out.extend_from_slice(&json);
+        out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        out.extend_from_slice(&GLB_CHUNK_BIN.to_le_bytes());
+        out.extend_from_slice(&bin);
+        out
+    }
+
+    #[test]
+    fn import_glb_with_embedded_image() {
+        let mut bin = decode_base64(TRIANGLE_URI.split_once(',').unwrap().1).unwrap();
+        bin.extend_from_slice(b"\x89PNG");
+        let json = r#"{
+            "buffers": [{ "byteLength": 48 }],
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+                { "buffer": 0, "byteOffset": 36, "byteLength": 6 },
+                { "buffer": 0, "byteOffset": 44, "byteLength": 4 }
+            ],
+            "accessors": [
+                { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" },
+                { "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }
+            ],
+            "images": [{ "bufferView": 2, "mimeType": "image/png" }],
+            "meshes": [{ "name": "tri", "primitives": [{ "attributes": { "POSITION": 0 }, "indices": 1 }] }]
+        }"#;
+        let bytes = glb(json, &bin);
+
+        let (doc_json, chunk) = parse_glb(&bytes).unwrap();
+        let doc = GltfDocument::load(doc_json, Path::new(""), chunk).unwrap();
+        assert_eq!(doc.image_data(0).unwrap(), b"\x89PNG");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tri.glb");
+        std::fs::write(&path, &bytes).unwrap();
+        let mut store = AssetStore::new();
+        let ids = store.import_gltf(&path).unwrap().ids;
+        let mesh = store.get_mesh(ids[0]).unwrap();
+        assert_eq!(mesh.vertex_count, 3);
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn import_links_material_texture() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("bc1.ktx2"),
+            crate::texture::tests::ktx2_bytes(),
+        )
+        .unwrap();
+        let gltf = r#"{
+            "images": [{ "name": "bricks", "uri": "bc1.ktx2" }, { "uri": "missing.gif" }],
+            "textures": [{ "source": 0 }],
+            "materials": [{
+                "name": "wall",
+                "pbrMetallicRoughness": { "baseColorTexture": { "index": 0 } }
+            }]
+        }"#;
+        let path = dir.path().join("wall.gltf");
+        std::fs::write(&path, gltf).unwrap();
+        std::fs::write(dir.path().join("missing.gif"), b"GIF89a").unwrap();
+
+        let mut store = AssetStore::new();
+        let report = store.import_gltf(&path).unwrap();
+        let ids = report.ids;
+        assert!(matches!(
+            report.warnings[..],
+            [ImportWarning::ImageDecode { image: 1, .. }]
+        ));
+        let texture = store.get_texture(ids[0]).unwrap();
+        assert_eq!(texture.name, "bricks");
+        let material = store.get_material(ids[1]).unwrap();
+        assert_eq!(material.base_color_texture, Some(ids[0]));
+        assert_eq!(ids.len(), 2);
+    }
+
+    #[test]
+    fn mesh_references_primitive_material() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tri.gltf");
+        let gltf = triangle_gltf(TRIANGLE_URI, 3)
+            .replace(
+                r#""meshes": ["#,
+                r#""materials": [{ "name": "red" }], "meshes": ["#,
+            )
+            .replace(r#""indices": 1 }"#, r#""indices": 1, "material": 0 }"#);
+        std::fs::write(&path, gltf).unwrap();
+
+        let mut store = AssetStore::new();
+        let ids = store.import_gltf(&path).unwrap().ids;
+        let material = ids[0];
+        assert_eq!(store.get_material(material).unwrap().name, "red_0");
+        assert_eq!(store.get_mesh(ids[1]).unwrap().material, Some(material));
+        assert_eq!(store.dependents(material), vec![ids[1]]);
+    }
+
+    #[test]
+    fn import_runs_pipeline_for_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tri.gltf");
+        std::fs::write(&path, triangle_gltf(TRIANGLE_URI, 3)).unwrap();
+
+        let pipeline = crate::ImportPipeline::new()
+            .with_stage("glb", crate::Processor::Scale { factor: 10.0 })
+            .with_stage("gltf", crate::Processor::Scale { factor: 0.5 });
+        let mut store = AssetStore::new();
+        let ids = pipeline.import(&mut store, &path).unwrap().ids;
+        assert_eq!(
+            store.get_mesh(ids[0]).unwrap().positions[1],
+            [0.5, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn import_skinned_mesh() {
+        let dir = tempfile::tempdir().unwrap();
+        // Positions, u8 joints, float weights, then two inverse bind matrices.
+        let mut bin = Vec::new();
+        let floats = |bin: &mut Vec<u8>, values: &[f32]| {
+            values
+                .iter()
+                .for_each(|v| bin.extend_from_slice(&v.to_le_bytes()))
+        };
+        floats(&mut bin, &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        bin.extend_from_slice(&[0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0]);
+        floats(&mut bin, &[1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0]);
+        floats(&mut bin, &[0.5, 0.5, 0.0, 0.0]);
+        floats(&mut bin, &IDENTITY_MATRIX);
+        let mut shifted = IDENTITY_MATRIX;
+        shifted[12] = -1.0;
+        floats(&mut bin, &shifted);
+        std::fs::write(dir.path().join("skin.bin"), &bin).unwrap();
+        let gltf = r#"{
+            "buffers": [{ "uri": "skin.bin", "byteLength": 224 }],
+            "bufferViews": [
+                { "buffer": 0, "byteLength": 36 },
+                { "buffer": 0, "byteOffset": 36, "byteLength": 12 },
+                { "buffer": 0, "byteOffset": 48, "byteLength": 48 },
+                { "buffer": 0, "byteOffset": 96, "byteLength": 128 }
+            ],
+            "accessors": [
+                { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" },
+                { "bufferView": 1, "componentType": 5121, "count": 3, "type": "VEC4" },
+                { "bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC4" },
+                { "bufferView": 3, "componentType": 5126, "count": 2, "type": "MAT4" }
+            ],
+            "meshes": [{ "name": "arm", "primitives": [{
+                "attributes": { "POSITION": 0, "JOINTS_0": 1, "WEIGHTS_0": 2 }
+            }] }],
+            "skins": [{ "name": "rig", "joints": [1, 2], "inverseBindMatrices": 3 }],
+            "nodes": [
+                { "mesh": 0, "skin": 0 },
+                { "name": "shoulder", "children": [2] },
+                { "name": "elbow" }
+            ]
+        }"#;
+        let path = dir.path().join("arm.gltf");
+        std::fs::write(&path, gltf).unwrap();
+
+        let mut store = AssetStore::new();
+        let ids = store.import_gltf(&path).unwrap().ids;
+        let skeleton = store.get_skeleton(ids[0]).unwrap();
+        assert_eq!(skeleton.name, "rig_0");
+        assert_eq!(skeleton.joints[0].name, "shoulder");
+        assert_eq!(skeleton.joints[0].parent, None);
+        assert_eq!(skeleton.joints[1].parent, Some(0));
+        assert_eq!(skeleton.joints[1].inverse_bind_matrix[12], -1.0);
+
+        let mesh = store.get_mesh(ids[1]).unwrap();
+        assert_eq!(mesh.skeleton, Some(ids[0]));
+        assert_eq!(mesh.joints, vec![[0, 0, 0, 0], [1, 0, 0, 0], [0, 1, 0, 0]]);
+        // Vertex 1's weights sum to 2 and are renormalized.
+        assert_eq!(mesh.weights[1], [1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(mesh.weights[2], [0.5, 0.5, 0.0, 0.0]);
+        assert_eq!(store.dependencies(ids[1]), vec![ids[0]]);
+    }
+
+    #[test]
+    fn import_reports_diagnostics() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tri.gltf");
+        let gltf = triangle_gltf(TRIANGLE_URI, 3)
+            .replace(
+                r#""meshes": ["#,
+                r#""extensionsUsed": ["KHR_materials_variants"], "meshes": ["#,
+            )
+            .replace(
+                r#""indices": 1 }"#,
+                r#""indices": 1 }, { "attributes": { "POSITION": 0 }, "mode": 1 }"#,
+            );
+        std::fs::write(&path, gltf).unwrap();
+
+        let mut store = AssetStore::new();
+        let report = store.import_gltf(&path).unwrap();
+        assert_eq!(report.ids.len(), 1);
+        assert_eq!(
+            report.warnings,
+            vec![
+                ImportWarning::UnsupportedExtension("KHR_materials_variants".into()),
+                ImportWarning::NonTrianglePrimitive {
+                    mesh: "tri_0".into(),
+                    primitive: 1,
+                    mode: 1,
+                },
+                ImportWarning::MissingNormals {
+                    mesh: "tri_0".into(),
+                },
+            ]
+        );
+
+        // Nothing importable registers nothing instead of a placeholder.
+        std::fs::write(&path, r#"{ "asset": { "version": "2.0" } }"#).unwrap();
+        let report = store.import_gltf(&path).unwrap();
+        assert!(report.ids.is_empty());
+        assert_eq!(report.warnings, vec![ImportWarning::NoAssets]);
+        assert_eq!(store.len(), 1);
+
+        std::fs::write(
+            &path,
+            r#"{ "extensionsRequired": ["KHR_draco_mesh_compression"] }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            store.import_gltf(&path),
+            Err(AssetError::GltfParse(_))
+        ));
+    }
+
+    #[test]
+    fn import_dedups_shared_content_across_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.gltf"), dir.path().join("b.gltf"));
+        std::fs::write(&a, triangle_gltf(TRIANGLE_URI, 3)).unwrap();
+        let renamed = triangle_gltf(TRIANGLE_URI, 3).replace(r#""tri""#, r#""prop""#);
+        std::fs::write(&b, renamed).unwrap();
+
+        let mut store = AssetStore::new();
+        let first = store.import_gltf(&a).unwrap();
+        assert!(first.dedup.reused.is_empty());
+        let second = store.import_gltf(&b).unwrap();
+        assert_eq!(second.ids, first.ids);
+        assert_eq!(second.dedup.reused, first.ids);
+        assert!(second.dedup.bytes_saved > 0);
+        assert_eq!(store.get_mesh(first.ids[0]).unwrap().name, "tri_0");
+        assert_eq!(
+            store.meta(first.ids[0]).unwrap().source_path.as_deref(),
+            Some(a.as_path())
+        );
+    }
+
+    #[test]
+    fn truncated_glb_rejected() {
+        let bytes = glb(r#"{"asset":{"version":"2.0"}}"#, &[]);
+        assert!(parse_glb(&bytes[..bytes.len() - 4]).is_err());
+        assert!(parse_glb(b"glTF\x01\0\0\0").is_err());
+    }
+
+    #[test]
+    fn accessor_out_of_bounds_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.gltf");
+        std::fs::write(&path, triangle_gltf(TRIANGLE_URI, 4)).unwrap();
+
+        let err = AssetStore::new().import_gltf(&path).unwrap_err();
+        assert!(matches!(err, AssetError::GltfParse(_)));
+
+        // A count whose byte extent overflows is rejected, not wrapped.
+        let huge_count = triangle_gltf(TRIANGLE_URI, 3).replace(
+            r#""count": 3, "type": "SCALAR""#,
+            r#""count": 18446744073709551615, "type": "SCALAR""#,
+        );
+        std::fs::write(&path, huge_count).unwrap();
+        let err = AssetStore::new().import_gltf(&path).unwrap_err();
+        assert!(matches!(err, AssetError::GltfParse(_)));
+    }
+
+    #[test]
+    fn buffer_view_out_of_bounds_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.gltf");
+        for view in [
+            r#""byteOffset": 40, "byteLength": 6"#,
+            r#""byteOffset": 18446744073709551615, "byteLength": 6"#,
+        ] {
+            let gltf = triangle_gltf(TRIANGLE_URI, 3)
+                .replace(r#""byteOffset": 36, "byteLength": 6"#, view);
+            std::fs::write(&path, gltf).unwrap();
+            let err = AssetStore::new().import_gltf(&path).unwrap_err();
+            assert!(matches!(err, AssetError::GltfParse(_)));
+        }
+    }
+}
@@ -1,26 +1,113 @@
-//! Asset pipeline scaffolding: content-addressed registry, import stubs.
+//! Asset pipeline: content-addressed registry and glTF import.
 //!
 //! Assets are identified by content-addressed hashes. The renderer consumes
 //! assets by handle, never by raw file paths.
 //!
 //! # Layout
-//! Assets are stored in the asset registry which can be persisted to disk.
+//! Assets are stored in the asset registry, which is persisted to disk as a
+//! binary `.wpack` pack (see the `pack` module) or as JSON for inspection.
 
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
+use std::sync::Arc;
+
+mod bounds;
+mod dedup;
+mod environment;
+mod export;
+mod gltf;
+mod lod;
+mod meta;
+mod migrate;
+mod pack;
+mod pipeline;
+mod report;
+mod scene;
+mod shader;
+mod skin;
+mod texture;
+mod watch;
+
+pub use bounds::{Aabb, BoundingSphere, MeshBounds};
+pub use dedup::DedupStats;
+pub use environment::{Environment, EnvironmentData, IRRADIANCE_SIZE, IrradianceMap};
+use gltf::GltfDocument;
+pub use lod::{LOD_RATIOS, MeshLodChain, simplify};
+pub use meta::{AssetKind, AssetMeta, AssetQuery};
+pub use migrate::{ASSET_SCHEMA_VERSION, AssetMigration, migrations};
+pub use pipeline::{ImportPipeline, Processor};
+pub use report::{ImportReport, ImportWarning};
+pub use scene::{NodeTransform, SceneGraph, SceneNode};
+pub use shader::Shader;
+pub use skin::{IDENTITY_MATRIX, Joint, Skeleton};
+pub use texture::{Texture, TextureData};
+pub use watch::AssetWatcher;
 
 /// Content-addressed asset ID computed from the asset data.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct AssetId(pub u64);
 
-/// A minimal mesh representation.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Triangle mesh geometry.
+///
+/// `normals`, `uvs`, `tangents`, `joints`, and `weights` are either empty or
+/// hold one entry per position.
+/// Placeholder meshes (e.g. the default cube) carry counts but no geometry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Mesh {
     pub name: String,
     pub vertex_count: u32,
     pub index_count: u32,
+    #[serde(default)]
+    pub positions: Vec<[f32; 3]>,
+    #[serde(default)]
+    pub normals: Vec<[f32; 3]>,
+    #[serde(default)]
+    pub uvs: Vec<[f32; 2]>,
+    /// Tangent xyz with bitangent handedness in w; empty unless generated.
+    #[serde(default)]
+    pub tangents: Vec<[f32; 4]>,
+    /// Triangle list indices into `positions`.
+    #[serde(default)]
+    pub indices: Vec<u32>,
+    /// Material the mesh is drawn with, if the source assigned one.
+    #[serde(default)]
+    pub material: Option<AssetId>,
+    /// Up to four joint indices into the skeleton's joints per vertex.
+    #[serde(default)]
+    pub joints: Vec<[u16; 4]>,
+    /// Weights of `joints`, summing to 1 for skinned vertices.
+    #[serde(default)]
+    pub weights: Vec<[f32; 4]>,
+    /// `Skeleton` the mesh is skinned to; `None` for static meshes.
+    #[serde(default)]
+    pub skeleton: Option<AssetId>,
+    /// Mesh-space bounds, computed on import (see `Mesh::update_bounds`).
+    #[serde(default)]
+    pub bounds: Option<MeshBounds>,
+}
+
+impl Mesh {
+    /// Estimated bytes the mesh's geometry occupies once loaded. Placeholder
+    /// meshes without geometry are sized from their counts as position,
+    /// normal and UV per vertex plus 32-bit indices.
+    pub fn memory_bytes(&self) -> usize {
+        fn bytes<T>(v: &[T]) -> usize {
+            std::mem::size_of_val(v)
+        }
+        if self.positions.is_empty() {
+            return self.vertex_count as usize * std::mem::size_of::<[f32; 8]>()
+                + self.index_count as usize * std::mem::size_of::<u32>();
+        }
+        bytes(&self.positions)
+            + bytes(&self.normals)
+            + bytes(&self.uvs)
+            + bytes(&self.tangents)
+            + bytes(&self.indices)
+            + bytes(&self.joints)
+            + bytes(&self.weights)
+    }
 }
 
 /// A minimal material representation.
@@ -28,6 +115,12 @@ pub struct Mesh {
 pub struct Material {
     pub name: String,
     pub base_color: [f32; 4],
+    /// Texture multiplied with `base_color`, if any.
+    #[serde(default)]
+    pub base_color_texture: Option<AssetId>,
+    /// Custom WGSL shader for this material's pipeline, if any.
+    #[serde(default)]
+    pub shader: Option<AssetId>,
 }
 
 impl Default for Material {
@@ -35,15 +128,121 @@ impl Default for Material {
         Self {
             name: "default".into(),
             base_color: [0.8, 0.8, 0.8, 1.0],
+            base_color_texture: None,
+            shader: None,
+        }
+    }
+}
+
+/// A single keyframe: a time in seconds and the value at that time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe<T> {
+    pub time: f32,
+    pub value: T,
+}
+
+/// A transform animation clip with independent, linearly interpolated
+/// translation, rotation (quaternion xyzw), and scale tracks.
+///
+/// Keyframes in each track must be sorted by time. An empty track leaves that
+/// part of the transform untouched.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnimationClip {
+    pub name: String,
+    /// Clip length in seconds.
+    pub duration: f32,
+    pub translation: Vec<Keyframe<[f32; 3]>>,
+    pub rotation: Vec<Keyframe<[f32; 4]>>,
+    pub scale: Vec<Keyframe<[f32; 3]>>,
+}
+
+/// Transform parts sampled from an `AnimationClip` at one point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClipSample {
+    pub translation: Option<[f32; 3]>,
+    pub rotation: Option<[f32; 4]>,
+    pub scale: Option<[f32; 3]>,
+}
+
+impl AnimationClip {
+    /// Sample every track at `time` seconds, clamped to the keyframe range.
+    ///
+    /// Rotations are interpolated component-wise and normalized (nlerp), which
+    /// is deterministic and close enough to slerp for densely keyed clips.
+    pub fn sample(&self, time: f32) -> ClipSample {
+        ClipSample {
+            translation: sample_track(&self.translation, time),
+            rotation: sample_track(&self.rotation, time).map(|mut q| {
+                let len = q.iter().map(|c| c * c).sum::<f32>().sqrt();
+                if len > 0.0 {
+                    q.iter_mut().for_each(|c| *c /= len);
+                }
+                q
+            }),
+            scale: sample_track(&self.scale, time),
         }
     }
 }
 
+fn sample_track<const N: usize>(track: &[Keyframe<[f32; N]>], time: f32) -> Option<[f32; N]> {
+    let first = track.first()?;
+    if time <= first.time {
+        return Some(first.value);
+    }
+    let next = track.partition_point(|k| k.time <= time);
+    let Some(b) = track.get(next) else {
+        return track.last().map(|k| k.value);
+    };
+    let a = &track[next - 1];
+    let t = (time - a.time) / (b.time - a.time);
+    let mut out = a.value;
+    for (o, v) in out.iter_mut().zip(b.value) {
+        *o += (v - *o) * t;
+    }
+    Some(out)
+}
+
 /// An asset entry in the registry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Asset {
     Mesh(Mesh),
     Material(Material),
+    Texture(Texture),
+    Animation(AnimationClip),
+    MeshLodChain(MeshLodChain),
+    Shader(Shader),
+    Skeleton(Skeleton),
+    Environment(Environment),
+}
+
+impl Asset {
+    /// The asset's name.
+    pub fn name(&self) -> &str {
+        match self {
+            Asset::Mesh(m) => &m.name,
+            Asset::Material(m) => &m.name,
+            Asset::Texture(t) => &t.name,
+            Asset::Animation(a) => &a.name,
+            Asset::MeshLodChain(c) => &c.name,
+            Asset::Shader(s) => &s.name,
+            Asset::Skeleton(s) => &s.name,
+            Asset::Environment(e) => &e.name,
+        }
+    }
+
+    /// IDs of the assets this asset references directly.
+    pub fn references(&self) -> Vec<AssetId> {
+        match self {
+            Asset::Mesh(m) => m.material.into_iter().chain(m.skeleton).collect(),
+            Asset::Material(m) => m.base_color_texture.into_iter().chain(m.shader).collect(),
+            Asset::MeshLodChain(c) => c.levels.clone(),
+            Asset::Texture(_)
+            | Asset::Animation(_)
+            | Asset::Shader(_)
+            | Asset::Skeleton(_)
+            | Asset::Environment(_) => Vec::new(),
+        }
+    }
 }
 
 /// Errors from asset operations.
@@ -53,20 +252,92 @@ pub enum AssetError {
     Io(#[from] std::io::Error),
     #[error("asset not found: {0:?}")]
     NotFound(AssetId),
+    #[error("asset {id:?} is still referenced by {dependents:?}")]
+    InUse {
+        id: AssetId,
+        dependents: Vec<AssetId>,
+    },
     #[error("glTF parse error: {0}")]
     GltfParse(String),
+    #[error("glTF export error: {0}")]
+    GltfExport(String),
+    #[error("texture decode error: {0}")]
+    TextureDecode(String),
+    #[error("shader compile error: {0}")]
+    ShaderCompile(String),
+    #[error("invalid asset query: {0}")]
+    InvalidQuery(String),
+    #[error(
+        "asset schema version mismatch: file has v{file_version}, expected v{expected_version}"
+    )]
+    SchemaMismatch {
+        file_version: u32,
+        expected_version: u32,
+    },
+    #[error("asset migration error: {0}")]
+    Migration(String),
+    #[error("asset pack error: {0}")]
+    Pack(String),
+    #[error("asset pack entry {0:?} failed integrity check")]
+    PackIntegrity(AssetId),
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 }
 
+/// Registry changes, drained from `AssetStore::drain_events`, and reloads
+/// reported by `AssetWatcher::poll`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetEvent {
+    /// An asset was registered under a new ID.
+    Added { id: AssetId, kind: AssetKind },
+    /// An asset was registered under an ID already in the store. The content
+    /// hashes equal, but a migrated entry's payload may differ.
+    Replaced { id: AssetId, kind: AssetKind },
+    /// An asset was removed.
+    Removed { id: AssetId, kind: AssetKind },
+    /// A re-import replaced `old_id` with `new_id`. Holders of `old_id`
+    /// should switch to `new_id`; the old asset stays in the store.
+    AssetReloaded { old_id: AssetId, new_id: AssetId },
+}
+
 /// Content-addressed asset registry.
 ///
-/// Assets are indexed by their content hash. The registry can be persisted
-/// to disk as JSON for inspection.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// Assets are indexed by their content hash. The registry is shipped as a
+/// compressed `.wpack` file (`write_pack` / `read_pack`); `save` / `load`
+/// write pretty JSON for inspection only.
+///
+/// Registering and removing assets queue `AssetEvent`s for `drain_events`;
+/// loading a registry starts with an empty queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssetStore {
+    /// Schema the registry was written with; see `ASSET_SCHEMA_VERSION`.
+    #[serde(default)]
+    schema_version: u32,
     assets: BTreeMap<AssetId, Asset>,
     next_id: u64,
+    #[serde(default)]
+    metadata: BTreeMap<AssetId, AssetMeta>,
+    #[serde(skip)]
+    events: Vec<AssetEvent>,
+    /// Payload locations of meshes opened lazily with `open_pack`.
+    #[serde(skip)]
+    payloads: BTreeMap<AssetId, pack::PayloadRef>,
+    #[serde(skip)]
+    pack_source: Option<Arc<pack::PackSource>>,
+}
+
+impl Default for AssetStore {
+    fn default() -> Self {
+        Self {
+            schema_version: ASSET_SCHEMA_VERSION,
+            assets: BTreeMap::new(),
+            next_id: 0,
+            metadata: BTreeMap::new(),
+            events: Vec::new(),
+            payloads: BTreeMap::new(),
+            pack_source: None,
+        }
+    }
 }
 
 impl AssetStore {
@@ -74,17 +345,127 @@ impl AssetStore {
         Self::default()
     }
 
+    /// Drain and return all pending asset events.
+    pub fn drain_events(&mut self) -> Vec<AssetEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Read-only access to pending events.
+    pub fn events(&self) -> &[AssetEvent] {
+        &self.events
+    }
+
     /// Register a mesh and return its asset ID.
     pub fn register_mesh(&mut self, mesh: Mesh) -> AssetId {
-        let id = self.content_hash(&mesh.name, mesh.vertex_count, mesh.index_count);
-        self.assets.insert(id, Asset::Mesh(mesh));
-        id
+        self.insert(Asset::Mesh(mesh))
     }
 
     /// Register a material and return its asset ID.
     pub fn register_material(&mut self, material: Material) -> AssetId {
-        let id = self.content_hash_material(&material.name, &material.base_color);
-        self.assets.insert(id, Asset::Material(material));
+        self.insert(Asset::Material(material))
+    }
+
+    /// Register a texture and return its asset ID.
+    pub fn register_texture(&mut self, texture: Texture) -> AssetId {
+        self.insert(Asset::Texture(texture))
+    }
+
+    /// Register an animation clip and return its asset ID.
+    pub fn register_animation(&mut self, clip: AnimationClip) -> AssetId {
+        self.insert(Asset::Animation(clip))
+    }
+
+    /// Register a validated shader and return its asset ID.
+    pub fn register_shader(&mut self, shader: Shader) -> AssetId {
+        self.insert(Asset::Shader(shader))
+    }
+
+    /// Register a skeleton and return its asset ID.
+    pub fn register_skeleton(&mut self, skeleton: Skeleton) -> AssetId {
+        self.insert(Asset::Skeleton(skeleton))
+    }
+
+    /// Register an environment map and return its asset ID.
+    pub fn register_environment(&mut self, environment: Environment) -> AssetId {
+        self.insert(Asset::Environment(environment))
+    }
+
+    /// Import a Radiance `.hdr` or KTX2 environment map. With `prefilter`,
+    /// an `IRRADIANCE_SIZE` irradiance map is computed on the CPU first,
+    /// which fails for BC6H-compressed maps.
+    pub fn import_environment(
+        &mut self,
+        path: impl AsRef<Path>,
+        prefilter: bool,
+    ) -> Result<AssetId, AssetError> {
+        let path = path.as_ref();
+        let mut environment = Environment::load(path)?;
+        if prefilter {
+            let (width, height) = IRRADIANCE_SIZE;
+            environment.prefilter_irradiance(width, height)?;
+        }
+        let id = self.register_environment(environment);
+        self.set_source_path(id, path)?;
+        Ok(id)
+    }
+
+    /// Import a `.wgsl` file, failing with `ShaderCompile` if it does not
+    /// parse or validate.
+    pub fn import_shader(&mut self, path: impl AsRef<Path>) -> Result<AssetId, AssetError> {
+        let path = path.as_ref();
+        let id = self.register_shader(Shader::load(path)?);
+        self.set_source_path(id, path)?;
+        Ok(id)
+    }
+
+    /// Register a mesh LOD chain and return its asset ID.
+    pub fn register_lod_chain(&mut self, chain: MeshLodChain) -> AssetId {
+        self.insert(Asset::MeshLodChain(chain))
+    }
+
+    /// Simplify `mesh` to each of `ratios` (fractions of its triangles) and
+    /// register the levels plus a `MeshLodChain` over them.
+    ///
+    /// Levels that would not reduce the triangle count of the previous level
+    /// are skipped; returns `None` if no level was generated.
+    pub fn generate_lod_chain(
+        &mut self,
+        mesh: AssetId,
+        ratios: &[f32],
+    ) -> Result<Option<AssetId>, AssetError> {
+        self.load_payload(mesh)?;
+        let source = self
+            .get_mesh(mesh)
+            .ok_or(AssetError::NotFound(mesh))?
+            .clone();
+        let mut levels = vec![mesh];
+        let mut triangles = source.indices.len() / 3;
+        for ratio in ratios {
+            let mut level = simplify(&source, *ratio);
+            if level.indices.len() / 3 >= triangles {
+                continue;
+            }
+            triangles = level.indices.len() / 3;
+            level.name = format!("{}_lod{}", source.name, levels.len());
+            levels.push(self.register_mesh(level));
+        }
+        if levels.len() == 1 {
+            return Ok(None);
+        }
+        Ok(Some(self.register_lod_chain(MeshLodChain {
+            name: source.name,
+            levels,
+        })))
+    }
+
+    fn insert(&mut self, asset: Asset) -> AssetId {
+        let id = content_id(&asset);
+        let kind = asset.kind();
+        self.payloads.remove(&id);
+        self.events.push(match self.assets.insert(id, asset) {
+            Some(_) => AssetEvent::Replaced { id, kind },
+            None => AssetEvent::Added { id, kind },
+        });
         id
     }
 
@@ -94,6 +475,10 @@ impl AssetStore {
     }
 
     /// Get a mesh by ID.
+    ///
+    /// Meshes from `open_pack` have empty geometry arrays until
+    /// `load_payload` is called for them; `mesh_with_geometry` reads it
+    /// without a mutable store.
     pub fn get_mesh(&self, id: AssetId) -> Option<&Mesh> {
         match self.assets.get(&id) {
             Some(Asset::Mesh(m)) => Some(m),
@@ -109,6 +494,123 @@ impl AssetStore {
         }
     }
 
+    /// Get a texture by ID.
+    pub fn get_texture(&self, id: AssetId) -> Option<&Texture> {
+        match self.assets.get(&id) {
+            Some(Asset::Texture(t)) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Get an animation clip by ID.
+    pub fn get_animation(&self, id: AssetId) -> Option<&AnimationClip> {
+        match self.assets.get(&id) {
+            Some(Asset::Animation(a)) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Remove an asset. Fails with `InUse` while other assets reference it;
+    /// remove those first (see `transitive_dependents`).
+    pub fn remove(&mut self, id: AssetId) -> Result<Asset, AssetError> {
+        if !self.assets.contains_key(&id) {
+            return Err(AssetError::NotFound(id));
+        }
+        let dependents = self.dependents(id);
+        if !dependents.is_empty() {
+            return Err(AssetError::InUse { id, dependents });
+        }
+        self.metadata.remove(&id);
+        self.payloads.remove(&id);
+        let asset = self.assets.remove(&id).ok_or(AssetError::NotFound(id))?;
+        self.events.push(AssetEvent::Removed {
+            id,
+            kind: asset.kind(),
+        });
+        Ok(asset)
+    }
+
+    /// Assets `id` references directly (mesh → material, material →
+    /// texture), in ID order. References to unregistered assets are included.
+    pub fn dependencies(&self, id: AssetId) -> Vec<AssetId> {
+        let mut deps: Vec<AssetId> = self
+            .assets
+            .get(&id)
+            .map(Asset::references)
+            .unwrap_or_default();
+        deps.sort();
+        deps.dedup();
+        deps
+    }
+
+    /// Registered assets that reference `id` directly, in ID order.
+    pub fn dependents(&self, id: AssetId) -> Vec<AssetId> {
+        self.assets
+            .iter()
+            .filter(|(_, asset)| asset.references().contains(&id))
+            .map(|(dependent, _)| *dependent)
+            .collect()
+    }
+
+    /// Every asset reachable from `id` through references, excluding `id`.
+    pub fn transitive_dependencies(&self, id: AssetId) -> BTreeSet<AssetId> {
+        self.walk(id, |store, id| store.dependencies(id))
+    }
+
+    /// Every asset that reaches `id` through references, excluding `id`.
+    pub fn transitive_dependents(&self, id: AssetId) -> BTreeSet<AssetId> {
+        self.walk(id, |store, id| store.dependents(id))
+    }
+
+    fn walk(
+        &self,
+        start: AssetId,
+        edges: impl Fn(&Self, AssetId) -> Vec<AssetId>,
+    ) -> BTreeSet<AssetId> {
+        let mut seen = BTreeSet::new();
+        let mut stack = vec![start];
+        while let Some(id) = stack.pop() {
+            for next in edges(self, id) {
+                if next != start && seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        seen
+    }
+
+    /// Get a shader by ID.
+    pub fn get_shader(&self, id: AssetId) -> Option<&Shader> {
+        match self.assets.get(&id) {
+            Some(Asset::Shader(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get a skeleton by ID.
+    pub fn get_skeleton(&self, id: AssetId) -> Option<&Skeleton> {
+        match self.assets.get(&id) {
+            Some(Asset::Skeleton(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get an environment map by ID.
+    pub fn get_environment(&self, id: AssetId) -> Option<&Environment> {
+        match self.assets.get(&id) {
+            Some(Asset::Environment(e)) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Get a mesh LOD chain by ID.
+    pub fn get_lod_chain(&self, id: AssetId) -> Option<&MeshLodChain> {
+        match self.assets.get(&id) {
+            Some(Asset::MeshLodChain(c)) => Some(c),
+            _ => None,
+        }
+    }
+
     /// Number of registered assets.
     pub fn len(&self) -> usize {
         self.assets.len()
@@ -118,35 +620,107 @@ impl AssetStore {
         self.assets.is_empty()
     }
 
-    /// Import a glTF file (stub).
+    /// Import a glTF file.
+    ///
+    /// Accepts both JSON `.gltf` and binary `.glb` files (detected by the GLB
+    /// magic, not the extension). Each glTF mesh becomes one `Mesh` with
+    /// positions, normals, UVs, skinning joints and weights, and indices read
+    /// from its triangle primitives.
+    /// Buffers are loaded from files next to the glTF, embedded data URIs, or
+    /// the GLB binary chunk. Images are registered as textures, and materials
+    /// from their base color factor and base color texture, and skins as
+    /// `Skeleton`s. Each mesh references the material of its first primitive
+    /// that has one and the skin of the first node instancing it with one, and
+    /// is followed by its simplified LOD meshes and `MeshLodChain` (at
+    /// `LOD_RATIOS`).
     ///
-    /// Currently reads the glTF JSON metadata and registers placeholder
-    /// mesh/material assets. Full vertex data import is a future task.
-    pub fn import_gltf(&mut self, path: impl AsRef<Path>) -> Result<Vec<AssetId>, AssetError> {
-        let data = std::fs::read_to_string(path.as_ref())?;
-        let json: serde_json::Value =
-            serde_json::from_str(&data).map_err(|e| AssetError::GltfParse(e.to_string()))?;
+    /// Parts of the file that cannot be imported (undecodable images,
+    /// non-triangle primitives, extensions) are skipped and listed in the
+    /// report's warnings; a file with nothing importable registers nothing.
+    /// Malformed data and required extensions fail the whole import.
+    pub fn import_gltf(&mut self, path: impl AsRef<Path>) -> Result<ImportReport, AssetError> {
+        self.import_gltf_with(path, &ImportPipeline::default())
+    }
+
+    /// Import a glTF file, running `pipeline`'s processors for its file type
+    /// over the meshes before they are registered.
+    pub fn import_gltf_with(
+        &mut self,
+        path: impl AsRef<Path>,
+        pipeline: &ImportPipeline,
+    ) -> Result<ImportReport, AssetError> {
+        Ok(self.import_document(path.as_ref(), pipeline)?.0)
+    }
+
+    /// Import a glTF file, also returning its JSON document and the ID of
+    /// each mesh `pipeline` produced, in order.
+    fn import_document(
+        &mut self,
+        path: &Path,
+        pipeline: &ImportPipeline,
+    ) -> Result<(ImportReport, serde_json::Value, Vec<AssetId>), AssetError> {
+        let data = std::fs::read(path)?;
+        let (json, bin) = if gltf::is_glb(&data) {
+            gltf::parse_glb(&data)?
+        } else {
+            let text =
+                std::str::from_utf8(&data).map_err(|e| AssetError::GltfParse(e.to_string()))?;
+            let json: serde_json::Value =
+                serde_json::from_str(text).map_err(|e| AssetError::GltfParse(e.to_string()))?;
+            (json, None)
+        };
+        let doc = GltfDocument::load(json, path.parent().unwrap_or(Path::new("")), bin)?;
 
         let mut ids = Vec::new();
+        let mut warnings = doc.check_extensions()?;
+        // Content shared with earlier imports (or repeated within this file)
+        // reuses the existing asset; its metadata keeps the first source.
+        let existing: BTreeSet<AssetId> = self.assets.keys().copied().collect();
+        let mut index = self.content_index();
+        let mut dedup = DedupStats::default();
 
-        // Extract meshes from glTF JSON
-        if let Some(meshes) = json.get("meshes").and_then(|m| m.as_array()) {
-            for (i, mesh_val) in meshes.iter().enumerate() {
-                let name = mesh_val
-                    .get("name")
-                    .and_then(|n| n.as_str())
-                    .unwrap_or("unnamed")
-                    .to_string();
-                let mesh = Mesh {
-                    name: format!("{name}_{i}"),
-                    vertex_count: 0, // Stub: real import would parse accessors
-                    index_count: 0,
-                };
-                ids.push(self.register_mesh(mesh));
-            }
+        // Images that fail to decode (e.g. unsupported formats) are skipped so
+        // the rest of the file still imports; materials just lose the texture.
+        let mut image_ids = Vec::new();
+        for (i, image) in doc.images().iter().enumerate() {
+            let name = image
+                .get("name")
+                .and_then(|n| n.as_str())
+                .map_or_else(|| format!("image_{i}"), str::to_string);
+            let id = match Texture::decode(name, &doc.image_data(i)?) {
+                Ok(texture) => {
+                    Some(self.insert_dedup(Asset::Texture(texture), &mut index, &mut dedup))
+                }
+                Err(e) => {
+                    warnings.push(ImportWarning::ImageDecode {
+                        image: i,
+                        error: e.to_string(),
+                    });
+                    None
+                }
+            };
+            ids.extend(id);
+            image_ids.push(id);
         }
+        let texture_ids: Vec<Option<AssetId>> = doc
+            .json()
+            .get("textures")
+            .and_then(|t| t.as_array())
+            .map(|textures| {
+                textures
+                    .iter()
+                    .map(|t| {
+                        let source = t.get("source").and_then(|s| s.as_u64())?;
+                        image_ids.get(source as usize).copied().flatten()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let json = doc.json();
 
         // Extract materials from glTF JSON
+        let mut material_ids = Vec::new();
         if let Some(materials) = json.get("materials").and_then(|m| m.as_array()) {
             for (i, mat_val) in materials.iter().enumerate() {
                 let name = mat_val
@@ -169,25 +743,105 @@ impl AssetStore {
                     })
                     .unwrap_or([0.8, 0.8, 0.8, 1.0]);
 
+                let base_color_texture = mat_val
+                    .get("pbrMetallicRoughness")
+                    .and_then(|pbr| pbr.get("baseColorTexture"))
+                    .and_then(|t| t.get("index"))
+                    .and_then(|i| i.as_u64())
+                    .and_then(|i| texture_ids.get(i as usize).copied().flatten());
+
                 let material = Material {
                     name: format!("{name}_{i}"),
                     base_color,
+                    base_color_texture,
+                    shader: None,
                 };
-                ids.push(self.register_material(material));
+                let id = self.insert_dedup(Asset::Material(material), &mut index, &mut dedup);
+                ids.push(id);
+                material_ids.push(id);
+            }
+        }
+
+        let mut skeleton_ids = Vec::new();
+        for i in 0..doc.skins().len() {
+            let skeleton = Asset::Skeleton(doc.skeleton(i)?);
+            let id = self.insert_dedup(skeleton, &mut index, &mut dedup);
+            ids.push(id);
+            skeleton_ids.push(id);
+        }
+        // Skins are bound to meshes through the nodes that instance them; a
+        // mesh takes the skin of the first node that instances it with one.
+        let mesh_skins: BTreeMap<u64, usize> = json
+            .get("nodes")
+            .and_then(|n| n.as_array())
+            .into_iter()
+            .flatten()
+            .rev()
+            .filter_map(|node| {
+                let mesh = node.get("mesh")?.as_u64()?;
+                let skin = node.get("skin")?.as_u64()?;
+                Some((mesh, skin as usize))
+            })
+            .collect();
+
+        let mut meshes = Vec::new();
+        for (i, mesh_val) in doc.meshes().iter().enumerate() {
+            let name = mesh_val
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("unnamed");
+            let mut mesh = doc.mesh(i, format!("{name}_{i}"), &mut warnings)?;
+            // A mesh carries one material; take the first primitive's.
+            mesh.material = mesh_val
+                .get("primitives")
+                .and_then(|p| p.as_array())
+                .and_then(|prims| prims.iter().find_map(|p| p.get("material")?.as_u64()))
+                .and_then(|m| material_ids.get(m as usize).copied());
+            if let Some(&skin) = mesh_skins.get(&(i as u64)) {
+                let joint_count = doc.skins()[skin]
+                    .get("joints")
+                    .and_then(|j| j.as_array())
+                    .map_or(0, Vec::len);
+                if let Some(bad) = mesh
+                    .joints
+                    .iter()
+                    .flatten()
+                    .find(|&&j| j as usize >= joint_count)
+                {
+                    return Err(AssetError::GltfParse(format!(
+                        "mesh {i}: joint {bad} out of range for skin {skin} with {joint_count} joints"
+                    )));
+                }
+                mesh.skeleton = skeleton_ids.get(skin).copied();
+            }
+            meshes.push(mesh);
+        }
+        let mut mesh_ids = Vec::new();
+        for mesh in pipeline.process(path, meshes) {
+            let id = self.insert_dedup(Asset::Mesh(mesh), &mut index, &mut dedup);
+            ids.push(id);
+            mesh_ids.push(id);
+            if let Some(chain) = self.generate_lod_chain(id, &LOD_RATIOS)? {
+                if let Some(c) = self.get_lod_chain(chain) {
+                    ids.extend(&c.levels[1..]);
+                }
+                ids.push(chain);
             }
         }
 
         if ids.is_empty() {
-            // Register a default mesh and material for minimal glTF files
-            let mesh_id = self.register_mesh(Mesh {
-                name: "gltf_default".into(),
-                vertex_count: 0,
-                index_count: 0,
-            });
-            ids.push(mesh_id);
+            warnings.push(ImportWarning::NoAssets);
         }
 
-        Ok(ids)
+        for id in ids.iter().filter(|id| !existing.contains(id)) {
+            self.set_source_path(*id, path)?;
+        }
+        let report = ImportReport {
+            ids,
+            warnings,
+            dedup,
+        };
+        Ok((report, doc.into_json(), mesh_ids))
     }
 
     /// Register a default unit cube mesh.
@@ -196,6 +850,8 @@ impl AssetStore {
             name: "unit_cube".into(),
             vertex_count: 24,
             index_count: 36,
+            bounds: Some(MeshBounds::unit_cube()),
+            ..Default::default()
         })
     }
 
@@ -204,42 +860,219 @@ impl AssetStore {
         self.register_material(Material::default())
     }
 
-    /// Save the asset registry to a JSON file.
+    /// Save the asset registry to a JSON file, including the geometry of
+    /// meshes that are not resident.
     pub fn save(&self, path: impl AsRef<Path>) -> Result<(), AssetError> {
+        if self.payloads.keys().any(|id| !self.is_resident(*id)) {
+            let mut resident = self.clone();
+            resident.load_all_payloads()?;
+            return resident.save(path);
+        }
         let file = std::fs::File::create(path)?;
         serde_json::to_writer_pretty(file, self)?;
         Ok(())
     }
 
-    /// Load an asset registry from a JSON file.
+    /// Load an asset registry from a JSON file, migrating registries written
+    /// with an older asset schema.
     pub fn load(path: impl AsRef<Path>) -> Result<Self, AssetError> {
         let file = std::fs::File::open(path)?;
-        let store: Self = serde_json::from_reader(file)?;
+        let mut value: serde_json::Value = serde_json::from_reader(file)?;
+        // Registries from before versioning have no version field.
+        let version = value
+            .get("schema_version")
+            .and_then(|v| v.as_u64())
+            .map_or(1, |v| v as u32);
+        migrate::check_version(version)?;
+        if version < ASSET_SCHEMA_VERSION
+            && let Some(assets) = value.get_mut("assets").and_then(|a| a.as_object_mut())
+        {
+            for asset in assets.values_mut() {
+                migrate::migrate_asset(asset, version)?;
+            }
+        }
+        let mut store: Self = serde_json::from_value(value)?;
+        store.schema_version = ASSET_SCHEMA_VERSION;
         Ok(store)
     }
+}
 
-    fn content_hash(&mut self, name: &str, vertex_count: u32, index_count: u32) -> AssetId {
-        let mut hasher = Sha256::new();
-        hasher.update(name.as_bytes());
-        hasher.update(vertex_count.to_le_bytes());
-        hasher.update(index_count.to_le_bytes());
-        let result = hasher.finalize();
-        let mut bytes = [0u8; 8];
-        bytes.copy_from_slice(&result[..8]);
-        AssetId(u64::from_le_bytes(bytes))
-    }
+/// Derive an asset's ID from its complete payload.
+///
+/// Every field is hashed, variable-length fields with a length prefix, behind
+/// a per-kind tag, so distinct assets only share an ID on a SHA-256
+/// collision — never because their names and counts happen to match.
+fn content_id(asset: &Asset) -> AssetId {
+    let (digest, _) = content_digest(asset, true);
+    let mut id = [0u8; 8];
+    id.copy_from_slice(&digest[..8]);
+    AssetId(u64::from_le_bytes(id))
+}
 
-    fn content_hash_material(&mut self, name: &str, color: &[f32; 4]) -> AssetId {
-        let mut hasher = Sha256::new();
-        hasher.update(name.as_bytes());
-        for c in color {
-            hasher.update(c.to_le_bytes());
+/// SHA-256 of an asset's payload as hashed for `content_id`, and the number
+/// of payload bytes hashed. Without `with_name` the asset's own name is left
+/// out, so identical content imported under different names compares equal.
+pub(crate) fn content_digest(asset: &Asset, with_name: bool) -> ([u8; 32], u64) {
+    let mut hasher = Sha256::new();
+    let mut len = 0u64;
+    let mut bytes = |data: &[u8]| {
+        hasher.update((data.len() as u64).to_le_bytes());
+        hasher.update(data);
+        len += data.len() as u64;
+    };
+    let named = |name: &str| {
+        if with_name {
+            name.as_bytes().to_vec()
+        } else {
+            Vec::new()
+        }
+    };
+    match asset {
+        Asset::Mesh(mesh) => {
+            bytes(b"mesh");
+            bytes(&named(&mesh.name));
+            bytes(&mesh.vertex_count.to_le_bytes());
+            bytes(&mesh.index_count.to_le_bytes());
+            bytes(&floats(mesh.positions.iter().flatten().copied()));
+            bytes(&floats(mesh.normals.iter().flatten().copied()));
+            bytes(&floats(mesh.uvs.iter().flatten().copied()));
+            bytes(&floats(mesh.tangents.iter().flatten().copied()));
+            bytes(
+                &mesh
+                    .indices
+                    .iter()
+                    .flat_map(|i| i.to_le_bytes())
+                    .collect::<Vec<_>>(),
+            );
+            bytes(&reference(mesh.material));
+            bytes(
+                &mesh
+                    .joints
+                    .iter()
+                    .flatten()
+                    .flat_map(|j| j.to_le_bytes())
+                    .collect::<Vec<_>>(),
+            );
+            bytes(&floats(mesh.weights.iter().flatten().copied()));
+            bytes(&reference(mesh.skeleton));
+            bytes(&mesh.bounds.map_or_else(Vec::new, |b| {
+                floats(
+                    b.aabb
+                        .min
+                        .into_iter()
+                        .chain(b.aabb.max)
+                        .chain(b.sphere.center)
+                        .chain([b.sphere.radius]),
+                )
+            }));
+        }
+        Asset::Material(material) => {
+            bytes(b"material");
+            bytes(&named(&material.name));
+            bytes(&floats(material.base_color));
+            bytes(&reference(material.base_color_texture));
+            bytes(&reference(material.shader));
+        }
+        Asset::Texture(texture) => {
+            bytes(b"texture");
+            bytes(&named(&texture.name));
+            bytes(&texture.width.to_le_bytes());
+            bytes(&texture.height.to_le_bytes());
+            match &texture.data {
+                TextureData::Rgba8(pixels) => {
+                    bytes(b"rgba8");
+                    bytes(pixels);
+                }
+                TextureData::Compressed { vk_format, levels } => {
+                    bytes(b"compressed");
+                    bytes(&vk_format.to_le_bytes());
+                    bytes(&(levels.len() as u64).to_le_bytes());
+                    levels.iter().for_each(|level| bytes(level));
+                }
+            }
+        }
+        Asset::Animation(clip) => {
+            bytes(b"animation");
+            bytes(&named(&clip.name));
+            bytes(&clip.duration.to_le_bytes());
+            bytes(&keyframes(&clip.translation));
+            bytes(&keyframes(&clip.rotation));
+            bytes(&keyframes(&clip.scale));
+        }
+        Asset::Shader(shader) => {
+            bytes(b"shader");
+            bytes(&named(&shader.name));
+            bytes(shader.source.as_bytes());
+        }
+        Asset::Skeleton(skeleton) => {
+            bytes(b"skeleton");
+            bytes(&named(&skeleton.name));
+            bytes(&(skeleton.joints.len() as u64).to_le_bytes());
+            for joint in &skeleton.joints {
+                bytes(joint.name.as_bytes());
+                bytes(
+                    &joint
+                        .parent
+                        .map_or_else(Vec::new, |p| p.to_le_bytes().to_vec()),
+                );
+                bytes(&floats(joint.inverse_bind_matrix));
+            }
+        }
+        Asset::Environment(environment) => {
+            bytes(b"environment");
+            bytes(&named(&environment.name));
+            bytes(&environment.width.to_le_bytes());
+            bytes(&environment.height.to_le_bytes());
+            match &environment.data {
+                EnvironmentData::Rgb32F(texels) => {
+                    bytes(b"rgb32f");
+                    bytes(&floats(texels.iter().flatten().copied()));
+                }
+                EnvironmentData::Compressed { vk_format, levels } => {
+                    bytes(b"compressed");
+                    bytes(&vk_format.to_le_bytes());
+                    bytes(&(levels.len() as u64).to_le_bytes());
+                    levels.iter().for_each(|level| bytes(level));
+                }
+            }
+            match &environment.irradiance {
+                Some(map) => {
+                    bytes(&map.width.to_le_bytes());
+                    bytes(&map.height.to_le_bytes());
+                    bytes(&floats(map.texels.iter().flatten().copied()));
+                }
+                None => bytes(&[]),
+            }
+        }
+        Asset::MeshLodChain(chain) => {
+            bytes(b"mesh_lod_chain");
+            bytes(&named(&chain.name));
+            bytes(
+                &chain
+                    .levels
+                    .iter()
+                    .flat_map(|id| id.0.to_le_bytes())
+                    .collect::<Vec<_>>(),
+            );
         }
-        let result = hasher.finalize();
-        let mut bytes = [0u8; 8];
-        bytes.copy_from_slice(&result[..8]);
-        AssetId(u64::from_le_bytes(bytes))
     }
+    (hasher.finalize().into(), len)
+}
+
+fn floats(values: impl IntoIterator<Item = f32>) -> Vec<u8> {
+    values.into_iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn keyframes<const N: usize>(track: &[Keyframe<[f32; N]>]) -> Vec<u8> {
+    floats(
+        track
+            .iter()
+            .flat_map(|k| [k.time].into_iter().chain(k.value)),
+    )
+}
+
+fn reference(id: Option<AssetId>) -> Vec<u8> {
+    id.map_or_else(Vec::new, |id| id.0.to_le_bytes().to_vec())
 }
 
 pub fn crate_info() -> &'static str {
@@ -257,11 +1090,31 @@ mod tests {
             name: "cube".into(),
             vertex_count: 24,
             index_count: 36,
+            ..Default::default()
         });
         assert!(store.get_mesh(id).is_some());
         assert_eq!(store.len(), 1);
     }
 
+    #[test]
+    fn mesh_memory_bytes() {
+        let placeholder = Mesh {
+            vertex_count: 24,
+            index_count: 36,
+            ..Default::default()
+        };
+        assert_eq!(placeholder.memory_bytes(), 24 * 32 + 36 * 4);
+        let triangle = Mesh {
+            vertex_count: 3,
+            index_count: 3,
+            positions: vec![[0.0; 3]; 3],
+            normals: vec![[0.0; 3]; 3],
+            indices: vec![0, 1, 2],
+            ..Default::default()
+        };
+        assert_eq!(triangle.memory_bytes(), 2 * 36 + 12);
+    }
+
     #[test]
     fn register_material() {
         let mut store = AssetStore::new();
@@ -276,16 +1129,52 @@ mod tests {
             name: "cube".into(),
             vertex_count: 24,
             index_count: 36,
+            ..Default::default()
         });
         let id2 = store.register_mesh(Mesh {
             name: "cube".into(),
             vertex_count: 24,
             index_count: 36,
+            ..Default::default()
         });
         assert_eq!(id1, id2);
         assert_eq!(store.len(), 1);
     }
 
+    #[test]
+    fn content_id_covers_payload() {
+        let mut store = AssetStore::new();
+        let triangle = |x: f32| Mesh {
+            name: "tri".into(),
+            vertex_count: 3,
+            index_count: 3,
+            positions: vec![[0.0, 0.0, 0.0], [x, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            indices: vec![0, 1, 2],
+            ..Default::default()
+        };
+        let a = store.register_mesh(triangle(1.0));
+        let b = store.register_mesh(triangle(2.0));
+        assert_ne!(a, b);
+        assert_eq!(store.get_mesh(b).unwrap().positions[1], [2.0, 0.0, 0.0]);
+
+        let texture = |pixel: u8| Texture {
+            name: "swatch".into(),
+            width: 1,
+            height: 1,
+            data: TextureData::Rgba8(vec![pixel; 4]),
+        };
+        assert_ne!(
+            store.register_texture(texture(0)),
+            store.register_texture(texture(255))
+        );
+        assert_eq!(store.len(), 4);
+
+        let mut skinned = triangle(1.0);
+        skinned.joints = vec![[0; 4]; 3];
+        skinned.weights = vec![[1.0, 0.0, 0.0, 0.0]; 3];
+        assert_ne!(store.register_mesh(skinned), a);
+    }
+
     #[test]
     fn default_cube_and_material() {
         let mut store = AssetStore::new();
@@ -296,6 +1185,154 @@ mod tests {
         assert_eq!(store.len(), 2);
     }
 
+    #[test]
+    fn animation_clip_sampling() {
+        let clip = AnimationClip {
+            name: "slide".into(),
+            duration: 2.0,
+            translation: vec![
+                Keyframe {
+                    time: 0.0,
+                    value: [0.0, 0.0, 0.0],
+                },
+                Keyframe {
+                    time: 2.0,
+                    value: [4.0, 0.0, 0.0],
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(clip.sample(0.5).translation, Some([1.0, 0.0, 0.0]));
+        assert_eq!(clip.sample(5.0).translation, Some([4.0, 0.0, 0.0]));
+        assert_eq!(clip.sample(1.0).rotation, None);
+
+        let mut store = AssetStore::new();
+        let id = store.register_animation(clip.clone());
+        assert_eq!(store.register_animation(clip), id);
+        assert_eq!(store.get_animation(id).unwrap().name, "slide");
+        assert!(store.get_mesh(id).is_none());
+    }
+
+    #[test]
+    fn mutations_emit_events() {
+        let mut store = AssetStore::new();
+        let material = store.register_default_material();
+        assert_eq!(store.register_default_material(), material);
+        let kind = AssetKind::Material;
+        assert_eq!(
+            store.drain_events(),
+            vec![
+                AssetEvent::Added { id: material, kind },
+                AssetEvent::Replaced { id: material, kind },
+            ]
+        );
+        assert!(store.events().is_empty());
+
+        store.remove(material).unwrap();
+        assert!(store.remove(material).is_err());
+        assert_eq!(
+            store.drain_events(),
+            vec![AssetEvent::Removed { id: material, kind }]
+        );
+    }
+
+    #[test]
+    fn dependency_graph() {
+        let mut store = AssetStore::new();
+        let texture = store.register_texture(Texture {
+            name: "bricks".into(),
+            width: 1,
+            height: 1,
+            data: TextureData::Rgba8(vec![255; 4]),
+        });
+        let material = store.register_material(Material {
+            base_color_texture: Some(texture),
+            ..Default::default()
+        });
+        let mesh = store.register_mesh(Mesh {
+            name: "wall".into(),
+            material: Some(material),
+            ..Default::default()
+        });
+
+        assert_eq!(store.dependencies(mesh), vec![material]);
+        assert_eq!(store.dependents(texture), vec![material]);
+        assert_eq!(
+            store.transitive_dependencies(mesh),
+            BTreeSet::from([material, texture])
+        );
+        assert_eq!(
+            store.transitive_dependents(texture),
+            BTreeSet::from([material, mesh])
+        );
+
+        assert!(matches!(
+            store.remove(texture),
+            Err(AssetError::InUse { dependents, .. }) if dependents == vec![material]
+        ));
+        store.remove(mesh).unwrap();
+        store.remove(material).unwrap();
+        store.remove(texture).unwrap();
+        assert!(store.is_empty());
+        assert!(matches!(store.remove(mesh), Err(AssetError::NotFound(_))));
+    }
+
+    #[test]
+    fn lod_chain_generation() {
+        let mut store = AssetStore::new();
+        let mesh = store.register_mesh(lod::tests::grid(10));
+        let chain = store
+            .generate_lod_chain(mesh, &LOD_RATIOS)
+            .unwrap()
+            .unwrap();
+        let levels = store.get_lod_chain(chain).unwrap().levels.clone();
+        assert_eq!(levels.len(), 4);
+        assert_eq!(levels[0], mesh);
+        assert_eq!(store.dependencies(chain).len(), 4);
+
+        let triangles: Vec<usize> = levels
+            .iter()
+            .map(|id| store.get_mesh(*id).unwrap().indices.len() / 3)
+            .collect();
+        assert_eq!(triangles[0], 200);
+        assert!(triangles.windows(2).all(|w| w[1] < w[0]), "{triangles:?}");
+        assert_eq!(store.get_mesh(levels[3]).unwrap().name, "grid_lod3");
+
+        let tri = store.register_mesh(Mesh {
+            positions: vec![[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            indices: vec![0, 1, 2],
+            ..Default::default()
+        });
+        assert_eq!(store.generate_lod_chain(tri, &LOD_RATIOS).unwrap(), None);
+    }
+
+    #[test]
+    fn shader_import_and_material_reference() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("unlit.wgsl");
+        std::fs::write(
+            &path,
+            "@fragment fn fs_main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }",
+        )
+        .unwrap();
+
+        let mut store = AssetStore::new();
+        let shader = store.import_shader(&path).unwrap();
+        assert_eq!(store.get_shader(shader).unwrap().name, "unlit");
+        let material = store.register_material(Material {
+            shader: Some(shader),
+            ..Default::default()
+        });
+        assert_eq!(store.dependencies(material), vec![shader]);
+        assert_ne!(material, store.register_default_material());
+
+        std::fs::write(&path, "@fragment fn fs_main( {").unwrap();
+        assert!(matches!(
+            store.import_shader(&path),
+            Err(AssetError::ShaderCompile(_))
+        ));
+    }
+
     #[test]
     fn save_and_load() {
         let tmp = tempfile::NamedTempFile::new().unwrap();
@@ -307,4 +1344,31 @@ mod tests {
         let loaded = AssetStore::load(tmp.path()).unwrap();
         assert_eq!(loaded.len(), 2);
     }
+
+    #[test]
+    fn load_migrates_unversioned_registry() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            tmp.path(),
+            r#"{ "assets": { "7": { "Mesh": { "name": "cube", "vertex_count": 24, "index_count": 36 } } },
+                "next_id": 0 }"#,
+        )
+        .unwrap();
+        let loaded = AssetStore::load(tmp.path()).unwrap();
+        assert_eq!(loaded.get_mesh(AssetId(7)).unwrap().name, "cube");
+        assert_eq!(loaded.schema_version, ASSET_SCHEMA_VERSION);
+
+        std::fs::write(
+            tmp.path(),
+            r#"{ "schema_version": 99, "assets": {}, "next_id": 0 }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            AssetStore::load(tmp.path()),
+            Err(AssetError::SchemaMismatch {
+                file_version: 99,
+                ..
+            })
+        ));
+    }
 }
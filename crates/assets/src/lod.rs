@@ -0,0 +1,363 @@
+//! Mesh LOD chains generated by quadric error metric edge collapse.
+//!
+//! `simplify` follows Garland & Heckbert: every vertex accumulates the planes
+//! of its triangles as a quadric, and the edge whose collapse adds the least
+//! error goes first. Collapse targets are limited to the two endpoints and
+//! their midpoint, and collapses that would flip a triangle are skipped.
+//! Only edges of shared vertices collapse, so vertices split at UV or normal
+//! seams stay split.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, BinaryHeap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AssetId, Mesh};
+
+/// Triangle ratios of the LOD levels generated on import, after the source.
+pub const LOD_RATIOS: [f32; 3] = [0.5, 0.25, 0.1];
+
+/// A mesh and its simplified versions, from most to least detailed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeshLodChain {
+    pub name: String,
+    /// Mesh asset per level; `levels[0]` is the source mesh.
+    pub levels: Vec<AssetId>,
+}
+
+/// Symmetric 4x4 error quadric, upper triangle in row order.
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn plane([a, b, c]: [f64; 3], d: f64) -> Self {
+        Self([
+            a * a,
+            a * b,
+            a * c,
+            a * d,
+            b * b,
+            b * c,
+            b * d,
+            c * c,
+            c * d,
+            d * d,
+        ])
+    }
+
+    fn add(&mut self, other: &Self) {
+        for (x, y) in self.0.iter_mut().zip(other.0) {
+            *x += y;
+        }
+    }
+
+    fn error(&self, [x, y, z]: [f64; 3]) -> f64 {
+        let q = &self.0;
+        q[0] * x * x
+            + 2.0 * (q[1] * x * y + q[2] * x * z + q[3] * x)
+            + q[4] * y * y
+            + 2.0 * (q[5] * y * z + q[6] * y)
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+/// A candidate edge collapse, ordered so the cheapest pops first from a
+/// max-heap. `stamps` are the endpoints' versions when it was computed.
+#[derive(Debug)]
+struct Collapse {
+    cost: f64,
+    keep: usize,
+    remove: usize,
+    target: [f64; 3],
+    stamps: (u32, u32),
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| (other.keep, other.remove).cmp(&(self.keep, self.remove)))
+    }
+}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+struct Simplifier {
+    positions: Vec<[f64; 3]>,
+    quadrics: Vec<Quadric>,
+    stamps: Vec<u32>,
+    removed: Vec<bool>,
+    triangles: Vec<[u32; 3]>,
+    alive: Vec<bool>,
+    vertex_triangles: Vec<Vec<usize>>,
+}
+
+impl Simplifier {
+    fn candidate(&self, a: usize, b: usize) -> Collapse {
+        let (keep, remove) = (a.min(b), a.max(b));
+        let mut quadric = self.quadrics[keep];
+        quadric.add(&self.quadrics[remove]);
+        let (p, q) = (self.positions[keep], self.positions[remove]);
+        let midpoint = [0, 1, 2].map(|i| (p[i] + q[i]) * 0.5);
+        let (cost, target) = [p, q, midpoint]
+            .into_iter()
+            .map(|target| (quadric.error(target), target))
+            .min_by(|x, y| x.0.total_cmp(&y.0))
+            .unwrap_or((0.0, p));
+        Collapse {
+            cost,
+            keep,
+            remove,
+            target,
+            stamps: (self.stamps[keep], self.stamps[remove]),
+        }
+    }
+
+    /// True if moving `vertex` to `target` turns any of its triangles that
+    /// survive the collapse (those not shared with `other`) upside down.
+    fn flips(&self, vertex: usize, other: usize, target: [f64; 3]) -> bool {
+        self.vertex_triangles[vertex].iter().any(|&t| {
+            let tri = self.triangles[t];
+            if !self.alive[t] || tri.contains(&(other as u32)) {
+                return false;
+            }
+            let corners = tri.map(|v| self.positions[v as usize]);
+            let moved = tri.map(|v| {
+                if v as usize == vertex {
+                    target
+                } else {
+                    self.positions[v as usize]
+                }
+            });
+            dot(normal(corners), normal(moved)) <= 0.0
+        })
+    }
+
+    /// Collapse `remove` into `keep`; returns the number of triangles removed.
+    fn collapse(&mut self, c: &Collapse) -> usize {
+        let (keep, remove) = (c.keep, c.remove);
+        self.positions[keep] = c.target;
+        let removed_quadric = self.quadrics[remove];
+        self.quadrics[keep].add(&removed_quadric);
+        self.removed[remove] = true;
+        self.stamps[keep] += 1;
+
+        let mut degenerate = 0;
+        for t in std::mem::take(&mut self.vertex_triangles[remove]) {
+            if !self.alive[t] {
+                continue;
+            }
+            if self.triangles[t].contains(&(keep as u32)) {
+                self.alive[t] = false;
+                degenerate += 1;
+                continue;
+            }
+            for v in &mut self.triangles[t] {
+                if *v as usize == remove {
+                    *v = keep as u32;
+                }
+            }
+            self.vertex_triangles[keep].push(t);
+        }
+        let alive = &self.alive;
+        self.vertex_triangles[keep].retain(|t| alive[*t]);
+        degenerate
+    }
+
+    fn neighbors(&self, vertex: usize) -> BTreeSet<usize> {
+        self.vertex_triangles[vertex]
+            .iter()
+            .flat_map(|t| self.triangles[*t])
+            .map(|v| v as usize)
+            .filter(|v| *v != vertex)
+            .collect()
+    }
+}
+
+/// Simplify `mesh` to roughly `ratio` of its triangles.
+///
+/// Stops early if no remaining collapse is valid. The result keeps the
+/// source's name, material, and skeleton, carries the attributes of surviving
+/// vertices, and has bounds computed from its own positions.
+pub fn simplify(mesh: &Mesh, ratio: f32) -> Mesh {
+    let triangles: Vec<[u32; 3]> = mesh
+        .indices
+        .chunks_exact(3)
+        .map(|t| [t[0], t[1], t[2]])
+        .filter(|t| t.iter().all(|v| (*v as usize) < mesh.positions.len()))
+        .collect();
+    let target = ((triangles.len() as f32 * ratio.clamp(0.0, 1.0)).ceil() as usize).max(1);
+
+    let vertex_count = mesh.positions.len();
+    let mut s = Simplifier {
+        positions: mesh.positions.iter().map(|p| p.map(f64::from)).collect(),
+        quadrics: vec![Quadric::default(); vertex_count],
+        stamps: vec![0; vertex_count],
+        removed: vec![false; vertex_count],
+        alive: vec![true; triangles.len()],
+        vertex_triangles: vec![Vec::new(); vertex_count],
+        triangles,
+    };
+    for (t, tri) in s.triangles.iter().enumerate() {
+        let corners = tri.map(|v| s.positions[v as usize]);
+        let n = normal(corners);
+        let len = dot(n, n).sqrt();
+        if len > 0.0 {
+            let n = n.map(|c| c / len);
+            let plane = Quadric::plane(n, -dot(n, corners[0]));
+            for v in tri {
+                s.quadrics[*v as usize].add(&plane);
+            }
+        }
+        for v in tri {
+            s.vertex_triangles[*v as usize].push(t);
+        }
+    }
+
+    let mut heap = BinaryHeap::new();
+    for tri in &s.triangles {
+        for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+            heap.push(s.candidate(a as usize, b as usize));
+        }
+    }
+
+    let mut live = s.triangles.len();
+    while live > target {
+        let Some(c) = heap.pop() else {
+            break;
+        };
+        if s.removed[c.keep]
+            || s.removed[c.remove]
+            || (s.stamps[c.keep], s.stamps[c.remove]) != c.stamps
+        {
+            continue;
+        }
+        if s.flips(c.keep, c.remove, c.target) || s.flips(c.remove, c.keep, c.target) {
+            continue;
+        }
+        live -= s.collapse(&c);
+        for n in s.neighbors(c.keep) {
+            heap.push(s.candidate(c.keep, n));
+        }
+    }
+
+    let mut out = Mesh {
+        name: mesh.name.clone(),
+        material: mesh.material,
+        skeleton: mesh.skeleton,
+        ..Default::default()
+    };
+    let mut remap = vec![u32::MAX; vertex_count];
+    for (tri, _) in s
+        .triangles
+        .iter()
+        .zip(&s.alive)
+        .filter(|(_, alive)| **alive)
+    {
+        for &v in tri {
+            let v = v as usize;
+            if remap[v] == u32::MAX {
+                remap[v] = out.positions.len() as u32;
+                out.positions.push(s.positions[v].map(|c| c as f32));
+                out.normals.extend(mesh.normals.get(v));
+                out.uvs.extend(mesh.uvs.get(v));
+                out.tangents.extend(mesh.tangents.get(v));
+                out.joints.extend(mesh.joints.get(v));
+                out.weights.extend(mesh.weights.get(v));
+            }
+            out.indices.push(remap[v]);
+        }
+    }
+    out.vertex_count = out.positions.len() as u32;
+    out.index_count = out.indices.len() as u32;
+    out.update_bounds();
+    out
+}
+
+fn normal([a, b, c]: [[f64; 3]; 3]) -> [f64; 3] {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A flat `n` x `n` quad grid on the XZ plane with UVs.
+    pub(crate) fn grid(n: u32) -> Mesh {
+        let mut mesh = Mesh {
+            name: "grid".into(),
+            ..Default::default()
+        };
+        for z in 0..=n {
+            for x in 0..=n {
+                mesh.positions.push([x as f32, 0.0, z as f32]);
+                mesh.uvs.push([x as f32 / n as f32, z as f32 / n as f32]);
+            }
+        }
+        for z in 0..n {
+            for x in 0..n {
+                let i = z * (n + 1) + x;
+                mesh.indices
+                    .extend([i, i + n + 1, i + 1, i + 1, i + n + 1, i + n + 2]);
+            }
+        }
+        mesh.vertex_count = mesh.positions.len() as u32;
+        mesh.index_count = mesh.indices.len() as u32;
+        mesh
+    }
+
+    #[test]
+    fn simplify_reaches_target() {
+        let mesh = grid(10);
+        let lod = simplify(&mesh, 0.25);
+        let triangles = lod.indices.len() / 3;
+        assert!(triangles > 0 && triangles <= 50, "{triangles} triangles");
+        assert_eq!(lod.uvs.len(), lod.positions.len());
+        assert!(
+            lod.indices
+                .iter()
+                .all(|i| (*i as usize) < lod.positions.len())
+        );
+        // A flat grid stays flat and facing up.
+        assert!(lod.positions.iter().all(|p| p[1] == 0.0));
+        for t in lod.indices.chunks_exact(3) {
+            let corners = [0, 1, 2].map(|i| lod.positions[t[i] as usize].map(f64::from));
+            assert!(normal(corners)[1] > 0.0);
+        }
+    }
+
+    #[test]
+    fn simplify_is_deterministic() {
+        let mesh = grid(6);
+        let a = simplify(&mesh, 0.5);
+        let b = simplify(&mesh, 0.5);
+        assert_eq!(a.positions, b.positions);
+        assert_eq!(a.indices, b.indices);
+        assert_eq!(simplify(&mesh, 1.0).indices.len(), mesh.indices.len());
+    }
+}
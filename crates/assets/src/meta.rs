@@ -0,0 +1,252 @@
+//! Per-asset metadata (labels, source path) and asset search.
+//!
+//! Metadata is editor data kept beside the content: it never affects an
+//! asset's ID, is saved with the JSON registry, and is not written to packs.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Asset, AssetError, AssetId, AssetStore};
+
+/// The kind of an asset, for filtering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AssetKind {
+    Mesh,
+    Material,
+    Texture,
+    Animation,
+    MeshLodChain,
+    Shader,
+    Skeleton,
+    Environment,
+}
+
+impl AssetKind {
+    pub const ALL: [AssetKind; 8] = [
+        AssetKind::Mesh,
+        AssetKind::Material,
+        AssetKind::Texture,
+        AssetKind::Animation,
+        AssetKind::MeshLodChain,
+        AssetKind::Shader,
+        AssetKind::Skeleton,
+        AssetKind::Environment,
+    ];
+
+    /// Lowercase name used in queries, e.g. `type:mesh`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AssetKind::Mesh => "mesh",
+            AssetKind::Material => "material",
+            AssetKind::Texture => "texture",
+            AssetKind::Animation => "animation",
+            AssetKind::MeshLodChain => "lod_chain",
+            AssetKind::Shader => "shader",
+            AssetKind::Skeleton => "skeleton",
+            AssetKind::Environment => "environment",
+        }
+    }
+}
+
+impl fmt::Display for AssetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Asset {
+    pub fn kind(&self) -> AssetKind {
+        match self {
+            Asset::Mesh(_) => AssetKind::Mesh,
+            Asset::Material(_) => AssetKind::Material,
+            Asset::Texture(_) => AssetKind::Texture,
+            Asset::Animation(_) => AssetKind::Animation,
+            Asset::MeshLodChain(_) => AssetKind::MeshLodChain,
+            Asset::Shader(_) => AssetKind::Shader,
+            Asset::Skeleton(_) => AssetKind::Skeleton,
+            Asset::Environment(_) => AssetKind::Environment,
+        }
+    }
+}
+
+/// User-editable metadata for one asset.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetMeta {
+    pub labels: BTreeSet<String>,
+    /// File the asset was imported from, if any.
+    pub source_path: Option<PathBuf>,
+}
+
+/// Filters for `AssetStore::find`. Every set filter must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssetQuery {
+    /// Case-insensitive substring of the asset name.
+    pub name: Option<String>,
+    /// Labels the asset must all carry.
+    pub labels: Vec<String>,
+    pub kind: Option<AssetKind>,
+}
+
+impl AssetQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.labels.push(label.into());
+        self
+    }
+
+    pub fn kind(mut self, kind: AssetKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Parse a search box query: `type:<kind>` and `label:<label>` terms
+    /// filter, remaining words are joined into the name filter.
+    ///
+    /// `"type:mesh label:hero rock"` finds meshes labeled `hero` whose name
+    /// contains `rock`. Unknown kinds are an error rather than matching all.
+    pub fn parse(query: &str) -> Result<Self, AssetError> {
+        let mut parsed = Self::new();
+        let mut words = Vec::new();
+        for term in query.split_whitespace() {
+            if let Some(kind) = term.strip_prefix("type:") {
+                let kind = AssetKind::ALL
+                    .into_iter()
+                    .find(|k| k.as_str().eq_ignore_ascii_case(kind))
+                    .ok_or_else(|| {
+                        AssetError::InvalidQuery(format!("unknown asset type '{kind}'"))
+                    })?;
+                parsed.kind = Some(kind);
+            } else if let Some(label) = term.strip_prefix("label:") {
+                parsed.labels.push(label.to_string());
+            } else {
+                words.push(term);
+            }
+        }
+        if !words.is_empty() {
+            parsed.name = Some(words.join(" "));
+        }
+        Ok(parsed)
+    }
+
+    fn matches(&self, asset: &Asset, meta: Option<&AssetMeta>) -> bool {
+        if self.kind.is_some_and(|kind| asset.kind() != kind) {
+            return false;
+        }
+        let name_matches =
+            |name: &String| asset.name().to_lowercase().contains(&name.to_lowercase());
+        if self.name.as_ref().is_some_and(|name| !name_matches(name)) {
+            return false;
+        }
+        self.labels
+            .iter()
+            .all(|label| meta.is_some_and(|m| m.labels.contains(label)))
+    }
+}
+
+impl AssetStore {
+    /// Metadata for `id`, if any has been set.
+    pub fn meta(&self, id: AssetId) -> Option<&AssetMeta> {
+        self.metadata.get(&id)
+    }
+
+    fn meta_mut(&mut self, id: AssetId) -> Result<&mut AssetMeta, AssetError> {
+        if !self.assets.contains_key(&id) {
+            return Err(AssetError::NotFound(id));
+        }
+        Ok(self.metadata.entry(id).or_default())
+    }
+
+    /// Add a label. Returns false if the asset already had it.
+    pub fn add_label(&mut self, id: AssetId, label: impl Into<String>) -> Result<bool, AssetError> {
+        Ok(self.meta_mut(id)?.labels.insert(label.into()))
+    }
+
+    /// Remove a label. Returns false if the asset did not have it.
+    pub fn remove_label(&mut self, id: AssetId, label: &str) -> Result<bool, AssetError> {
+        Ok(self.meta_mut(id)?.labels.remove(label))
+    }
+
+    /// Record the file an asset was imported from.
+    pub fn set_source_path(
+        &mut self,
+        id: AssetId,
+        path: impl Into<PathBuf>,
+    ) -> Result<(), AssetError> {
+        self.meta_mut(id)?.source_path = Some(path.into());
+        Ok(())
+    }
+
+    /// IDs of assets matching every filter in `query`, in ID order.
+    pub fn find(&self, query: &AssetQuery) -> Vec<AssetId> {
+        self.assets
+            .iter()
+            .filter(|(id, asset)| query.matches(asset, self.metadata.get(id)))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Material, Mesh};
+
+    #[test]
+    fn find_by_name_label_and_kind() {
+        let mut store = AssetStore::new();
+        let rock = store.register_mesh(Mesh {
+            name: "Rock_Large".into(),
+            ..Default::default()
+        });
+        let pebble = store.register_mesh(Mesh {
+            name: "rock_small".into(),
+            ..Default::default()
+        });
+        let paint = store.register_material(Material {
+            name: "rock_paint".into(),
+            ..Default::default()
+        });
+        assert!(store.add_label(rock, "hero").unwrap());
+        assert!(!store.add_label(rock, "hero").unwrap());
+        store.add_label(paint, "hero").unwrap();
+
+        let all_rocks = store.find(&AssetQuery::new().name("ROCK"));
+        assert_eq!(all_rocks.len(), 3);
+        let meshes = store.find(&AssetQuery::new().name("rock").kind(AssetKind::Mesh));
+        assert_eq!(meshes.len(), 2);
+        assert!(meshes.contains(&rock) && meshes.contains(&pebble));
+        assert_eq!(
+            store.find(&AssetQuery::parse("type:mesh label:hero rock").unwrap()),
+            vec![rock]
+        );
+
+        assert!(store.remove_label(rock, "hero").unwrap());
+        assert_eq!(store.find(&AssetQuery::new().label("hero")), vec![paint]);
+    }
+
+    #[test]
+    fn metadata_requires_registered_asset() {
+        let mut store = AssetStore::new();
+        let missing = AssetId(42);
+        assert!(matches!(
+            store.add_label(missing, "x"),
+            Err(AssetError::NotFound(_))
+        ));
+        assert!(store.meta(missing).is_none());
+        assert!(matches!(
+            AssetQuery::parse("type:sound"),
+            Err(AssetError::InvalidQuery(_))
+        ));
+    }
+}
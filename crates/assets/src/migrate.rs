@@ -0,0 +1,148 @@
+//! Asset schema versions and migrations.
+//!
+//! Registries (`save`/`load`) and packs record the asset schema version they
+//! were written with. Loading an older version runs every migration from that
+//! version up to `ASSET_SCHEMA_VERSION` on each serialized asset, as JSON,
+//! before it is deserialized. Newer versions are rejected, like the persist
+//! crate's schema checks.
+
+use serde_json::Value;
+
+use crate::AssetError;
+
+/// Current asset schema version.
+///
+/// - 1: registries written before versioning (no `schema_version` field).
+/// - 2: adds mesh geometry, texture, animation, shader, and LOD chain assets.
+/// - 3: adds skeleton assets and mesh skinning attributes.
+/// - 4: adds mesh bounds.
+/// - 5: adds environment map assets.
+pub const ASSET_SCHEMA_VERSION: u32 = 5;
+
+/// Upgrades one serialized asset from `from_version` to `from_version + 1`.
+#[derive(Debug, Clone, Copy)]
+pub struct AssetMigration {
+    pub from_version: u32,
+    pub description: &'static str,
+    pub migrate: fn(&mut Value) -> Result<(), AssetError>,
+}
+
+const MIGRATIONS: &[AssetMigration] = &[
+    AssetMigration {
+        from_version: 1,
+        description: "mesh geometry and material texture fields default to empty",
+        migrate: v1_to_v2,
+    },
+    AssetMigration {
+        from_version: 2,
+        description: "mesh skinning fields default to empty",
+        migrate: v2_to_v3,
+    },
+    AssetMigration {
+        from_version: 3,
+        description: "mesh bounds default to none",
+        migrate: v3_to_v4,
+    },
+    AssetMigration {
+        from_version: 4,
+        description: "environment map assets added",
+        migrate: v4_to_v5,
+    },
+];
+
+/// Registered migrations, ordered by `from_version`.
+pub fn migrations() -> &'static [AssetMigration] {
+    MIGRATIONS
+}
+
+/// Fail closed on data written by a newer engine.
+pub(crate) fn check_version(file_version: u32) -> Result<(), AssetError> {
+    if file_version > ASSET_SCHEMA_VERSION {
+        return Err(AssetError::SchemaMismatch {
+            file_version,
+            expected_version: ASSET_SCHEMA_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// Migrate one serialized asset from `file_version` to the current schema.
+pub(crate) fn migrate_asset(asset: &mut Value, file_version: u32) -> Result<(), AssetError> {
+    check_version(file_version)?;
+    for version in file_version..ASSET_SCHEMA_VERSION {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|m| m.from_version == version)
+            .ok_or_else(|| AssetError::Migration(format!("no migration from v{version}")))?;
+        (migration.migrate)(asset)?;
+    }
+    Ok(())
+}
+
+/// v1 assets are structurally valid v2 assets: every field added since is
+/// `#[serde(default)]`. IDs are kept as stored even though v2 derives IDs
+/// from the full payload, so references between assets stay intact.
+fn v1_to_v2(asset: &mut Value) -> Result<(), AssetError> {
+    let known = ["Mesh", "Material"];
+    match asset.as_object() {
+        Some(variant) if variant.keys().all(|k| known.contains(&k.as_str())) => Ok(()),
+        _ => Err(AssetError::Migration(
+            "v1 assets are meshes or materials".to_string(),
+        )),
+    }
+}
+
+/// Skinning fields are `#[serde(default)]`, so v2 assets load unchanged.
+fn v2_to_v3(_asset: &mut Value) -> Result<(), AssetError> {
+    Ok(())
+}
+
+/// Bounds are `#[serde(default)]`; v3 meshes load without them and callers
+/// fall back to `Mesh::bounds_or_unit` until they are re-imported.
+fn v3_to_v4(_asset: &mut Value) -> Result<(), AssetError> {
+    Ok(())
+}
+
+/// Environment maps are a new asset type; existing assets are unchanged.
+fn v4_to_v5(_asset: &mut Value) -> Result<(), AssetError> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v1_mesh() -> Value {
+        let mut mesh = serde_json::Map::new();
+        mesh.insert("name".to_string(), Value::from("cube"));
+        mesh.insert("vertex_count".to_string(), Value::from(24u32));
+        mesh.insert("index_count".to_string(), Value::from(36u32));
+        let mut asset = serde_json::Map::new();
+        asset.insert("Mesh".to_string(), Value::from(mesh));
+        Value::from(asset)
+    }
+
+    #[test]
+    fn migrations_cover_every_old_version() {
+        for version in 1..ASSET_SCHEMA_VERSION {
+            assert!(migrations().iter().any(|m| m.from_version == version));
+        }
+        let mut asset = v1_mesh();
+        migrate_asset(&mut asset, 1).unwrap();
+        assert_eq!(asset, v1_mesh());
+        migrate_asset(&mut asset, ASSET_SCHEMA_VERSION).unwrap();
+    }
+
+    #[test]
+    fn newer_or_malformed_rejected() {
+        let mut asset = v1_mesh();
+        assert!(matches!(
+            migrate_asset(&mut asset, ASSET_SCHEMA_VERSION + 1),
+            Err(AssetError::SchemaMismatch { .. })
+        ));
+        assert!(matches!(
+            migrate_asset(&mut Value::from("Mesh"), 1),
+            Err(AssetError::Migration(_))
+        ));
+    }
+}
@@ -0,0 +1,690 @@
+//! Binary asset packs (`.wpack`).
+//!
+//! A pack is a single file: a fixed header, an index of fixed-size entries,
+//! each asset as zstd-compressed CBOR, then the geometry of each mesh as a
+//! raw payload. Index entries record the asset ID, the byte ranges of the
+//! entry and its payload, and the SHA-256 of both, so a reader can locate
+//! and verify any entry without decoding the others.
+//!
+//! ```text
+//! header   "WPAK" | pack version: u32 | schema version: u32 | reserved: u32
+//!          | entry count: u64
+//! index    (id: u64 | offset: u64 | length: u64 | sha256: [u8; 32]
+//!          | payload offset: u64 | payload length: u64
+//!          | payload sha256: [u8; 32]) * count
+//! data     zstd(cbor(Asset)) * count
+//! payloads (element counts: u64 * 7 | positions | normals | uvs | tangents
+//!          | joints | weights | indices) * meshes with geometry
+//! ```
+//!
+//! All integers are little-endian; offsets are from the start of the file.
+//! Mesh entries are stored without their vertex and index arrays, which are
+//! kept in the payload; entries without geometry have an all-zero payload
+//! range. Payloads are uncompressed so they can be read straight from a
+//! memory map.
+//!
+//! `AssetStore::read_pack` loads everything. `AssetStore::open_pack` reads
+//! only the entries and leaves mesh geometry in the file until
+//! `AssetStore::load_payload` asks for it, so opening a pack costs memory
+//! for the registry, not for the geometry.
+//!
+//! The pack version covers this layout; the schema version covers the assets
+//! inside, which are migrated on read (see `migrate`). Version 2 packs have
+//! no payload fields and keep geometry in the entries; version 1 packs also
+//! have no schema or reserved fields and hold schema version 2 assets.
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use sha2::{Digest, Sha256};
+
+use crate::migrate::{self, ASSET_SCHEMA_VERSION};
+use crate::{Asset, AssetError, AssetId, AssetStore, Mesh, content_id};
+
+const PACK_MAGIC: &[u8; 4] = b"WPAK";
+const PACK_VERSION: u32 = 3;
+const HEADER_LEN: usize = 24;
+const V1_HEADER_LEN: usize = 16;
+const V1_SCHEMA_VERSION: u32 = 2;
+const INDEX_ENTRY_LEN: usize = 104;
+const V2_INDEX_ENTRY_LEN: usize = 56;
+
+/// One index entry: where an asset's compressed bytes live and their hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PackEntry {
+    id: AssetId,
+    offset: u64,
+    length: u64,
+    sha256: [u8; 32],
+    payload: Option<PayloadRef>,
+}
+
+/// Where a mesh's geometry lives in the pack it was opened from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PayloadRef {
+    offset: u64,
+    length: u64,
+    sha256: [u8; 32],
+    /// The entry came from an older schema and keeps its stored ID, so the
+    /// loaded mesh is not checked against it.
+    migrated: bool,
+}
+
+/// An open pack file that mesh payloads are read from on demand.
+#[derive(Debug)]
+pub(crate) enum PackSource {
+    File(Mutex<File>),
+    #[cfg(feature = "mmap")]
+    Mmap(memmap2::Mmap),
+}
+
+impl PackSource {
+    fn open(path: &Path) -> Result<Self, AssetError> {
+        Ok(PackSource::File(Mutex::new(File::open(path)?)))
+    }
+
+    fn len(&self) -> Result<u64, AssetError> {
+        match self {
+            PackSource::File(file) => {
+                let file = file.lock().unwrap_or_else(PoisonError::into_inner);
+                Ok(file.metadata()?.len())
+            }
+            #[cfg(feature = "mmap")]
+            PackSource::Mmap(map) => Ok(map.len() as u64),
+        }
+    }
+
+    /// Bytes `offset..offset + length`, failing if the range leaves the file.
+    fn read(&self, offset: u64, length: u64) -> Result<Cow<'_, [u8]>, AssetError> {
+        let out_of_range = || {
+            AssetError::Pack(format!(
+                "byte range {offset}+{length} exceeds the file size"
+            ))
+        };
+        let end = offset.checked_add(length).ok_or_else(out_of_range)?;
+        if end > self.len()? {
+            return Err(out_of_range());
+        }
+        match self {
+            PackSource::File(file) => {
+                let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+                let mut bytes = vec![0; usize::try_from(length).map_err(|_| out_of_range())?];
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(&mut bytes)?;
+                Ok(Cow::Owned(bytes))
+            }
+            #[cfg(feature = "mmap")]
+            PackSource::Mmap(map) => Ok(Cow::Borrowed(&map[offset as usize..end as usize])),
+        }
+    }
+}
+
+impl AssetStore {
+    /// Write every asset to a compressed `.wpack` file, in ID order.
+    ///
+    /// Geometry of meshes that are not resident is copied from the pack the
+    /// store was opened from.
+    pub fn write_pack(&self, path: impl AsRef<Path>) -> Result<(), AssetError> {
+        let mut blobs = Vec::with_capacity(self.assets.len());
+        for (id, asset) in &self.assets {
+            let (asset, payload) = match asset {
+                Asset::Mesh(mesh) => {
+                    let payload = match self.payloads.get(id) {
+                        Some(payload) if !self.is_resident(*id) => {
+                            self.payload_bytes(*id, payload)?.into_owned()
+                        }
+                        _ => encode_payload(mesh),
+                    };
+                    (Cow::Owned(Asset::Mesh(strip_geometry(mesh))), payload)
+                }
+                other => (Cow::Borrowed(other), Vec::new()),
+            };
+            let mut cbor = Vec::new();
+            ciborium::into_writer(&*asset, &mut cbor)
+                .map_err(|e| AssetError::Pack(format!("encode {id:?}: {e}")))?;
+            let mut encoder = zstd::Encoder::new(Vec::new(), 3)?;
+            encoder.write_all(&cbor)?;
+            blobs.push((*id, encoder.finish()?, payload));
+        }
+
+        let mut offset = (HEADER_LEN + blobs.len() * INDEX_ENTRY_LEN) as u64;
+        let mut payload_offset = offset + blobs.iter().map(|b| b.1.len() as u64).sum::<u64>();
+        let mut out = Vec::new();
+        out.extend_from_slice(PACK_MAGIC);
+        out.extend_from_slice(&PACK_VERSION.to_le_bytes());
+        out.extend_from_slice(&ASSET_SCHEMA_VERSION.to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&(blobs.len() as u64).to_le_bytes());
+        for (id, blob, payload) in &blobs {
+            out.extend_from_slice(&id.0.to_le_bytes());
+            out.extend_from_slice(&offset.to_le_bytes());
+            out.extend_from_slice(&(blob.len() as u64).to_le_bytes());
+            out.extend_from_slice(&Sha256::digest(blob));
+            offset += blob.len() as u64;
+            if payload.is_empty() {
+                out.extend_from_slice(&[0; 48]);
+            } else {
+                out.extend_from_slice(&payload_offset.to_le_bytes());
+                out.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+                out.extend_from_slice(&Sha256::digest(payload));
+                payload_offset += payload.len() as u64;
+            }
+        }
+        for (_, blob, _) in &blobs {
+            out.extend_from_slice(blob);
+        }
+        for (_, _, payload) in &blobs {
+            out.extend_from_slice(payload);
+        }
+        std::fs::write(path, out)?;
+        Ok(())
+    }
+
+    /// Read a `.wpack` file written by [`AssetStore::write_pack`].
+    ///
+    /// Fails closed: a bad header, a newer schema, an entry outside the
+    /// file, a hash mismatch, or a current-schema asset whose content no
+    /// longer matches its ID rejects the whole pack. Assets from older
+    /// schemas are migrated and keep their stored IDs.
+    pub fn read_pack(path: impl AsRef<Path>) -> Result<Self, AssetError> {
+        let mut store = Self::open_pack(path)?;
+        store.load_all_payloads()?;
+        store.payloads.clear();
+        store.pack_source = None;
+        Ok(store)
+    }
+
+    /// Open a `.wpack` file without loading mesh geometry.
+    ///
+    /// Every entry is read and verified as in `read_pack`, but meshes with a
+    /// payload start with empty vertex and index arrays (their counts and
+    /// bounds are kept) until `load_payload` reads them from the file. A
+    /// mesh's content is checked against its ID when its payload loads. The
+    /// file stays open while the store or its clones need it.
+    pub fn open_pack(path: impl AsRef<Path>) -> Result<Self, AssetError> {
+        Self::open_source(PackSource::open(path.as_ref())?)
+    }
+
+    /// Like `open_pack`, but memory-maps the file so payloads are read
+    /// without copying the file through read buffers.
+    ///
+    /// The pack file must not be modified or truncated while it is mapped.
+    #[cfg(feature = "mmap")]
+    pub fn open_pack_mmap(path: impl AsRef<Path>) -> Result<Self, AssetError> {
+        let file = File::open(path)?;
+        // SAFETY: the map is read-only and only read through bounds-checked
+        // slices; the caller guarantees the file is not changed underneath it.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Self::open_source(PackSource::Mmap(map))
+    }
+
+    fn open_source(source: PackSource) -> Result<Self, AssetError> {
+        let mut store = Self::new();
+        let (schema_version, entries) = read_index(&source)?;
+        migrate::check_version(schema_version)?;
+        let migrated = schema_version < ASSET_SCHEMA_VERSION;
+        for entry in entries {
+            let blob = source.read(entry.offset, entry.length)?;
+            if Sha256::digest(&blob).as_slice() != entry.sha256 {
+                return Err(AssetError::PackIntegrity(entry.id));
+            }
+
+            let mut cbor = Vec::new();
+            zstd::Decoder::new(blob.as_ref())?.read_to_end(&mut cbor)?;
+            let decode_err =
+                |e: &dyn std::fmt::Display| AssetError::Pack(format!("decode {:?}: {e}", entry.id));
+            let asset: Asset = if migrated {
+                let mut value: serde_json::Value =
+                    ciborium::from_reader(cbor.as_slice()).map_err(|e| decode_err(&e))?;
+                migrate::migrate_asset(&mut value, schema_version)?;
+                serde_json::from_value(value).map_err(|e| decode_err(&e))?
+            } else {
+                ciborium::from_reader(cbor.as_slice()).map_err(|e| decode_err(&e))?
+            };
+            match entry.payload {
+                Some(payload) if matches!(asset, Asset::Mesh(_)) => {
+                    store.payloads.insert(
+                        entry.id,
+                        PayloadRef {
+                            migrated,
+                            ..payload
+                        },
+                    );
+                }
+                Some(_) => {
+                    return Err(AssetError::Pack(format!(
+                        "entry {:?} has a payload but is not a mesh",
+                        entry.id
+                    )));
+                }
+                None if !migrated && content_id(&asset) != entry.id => {
+                    return Err(AssetError::PackIntegrity(entry.id));
+                }
+                None => {}
+            }
+            store.assets.insert(entry.id, asset);
+        }
+        store.pack_source = Some(Arc::new(source));
+        Ok(store)
+    }
+
+    /// True unless `id` is a mesh from `open_pack` whose geometry has not
+    /// been loaded.
+    pub fn is_resident(&self, id: AssetId) -> bool {
+        !self.payloads.contains_key(&id) || self.get_mesh(id).is_some_and(has_geometry)
+    }
+
+    /// Load the geometry of mesh `id` from the pack it was opened from,
+    /// verifying it against the payload hash and the mesh's ID. Does nothing
+    /// for resident assets.
+    pub fn load_payload(&mut self, id: AssetId) -> Result<(), AssetError> {
+        if !self.assets.contains_key(&id) {
+            return Err(AssetError::NotFound(id));
+        }
+        if self.is_resident(id) {
+            return Ok(());
+        }
+        let mesh = self.mesh_with_geometry(id)?.into_owned();
+        self.assets.insert(id, Asset::Mesh(mesh));
+        Ok(())
+    }
+
+    /// Mesh `id` with its geometry, for readers that cannot `load_payload`
+    /// through a shared store (e.g. a renderer uploading it). A mesh that is
+    /// not resident is read and verified as by `load_payload`, but stays
+    /// non-resident in the store.
+    pub fn mesh_with_geometry(&self, id: AssetId) -> Result<Cow<'_, Mesh>, AssetError> {
+        let mesh = self.get_mesh(id).ok_or(AssetError::NotFound(id))?;
+        let Some(&payload) = self.payloads.get(&id).filter(|_| !has_geometry(mesh)) else {
+            return Ok(Cow::Borrowed(mesh));
+        };
+        let mut mesh = mesh.clone();
+        decode_payload(&mut mesh, &self.payload_bytes(id, &payload)?)
+            .ok_or_else(|| AssetError::Pack(format!("malformed payload for {id:?}")))?;
+        let asset = Asset::Mesh(mesh);
+        if !payload.migrated && content_id(&asset) != id {
+            return Err(AssetError::PackIntegrity(id));
+        }
+        let Asset::Mesh(mesh) = asset else {
+            unreachable!("asset was built as a mesh");
+        };
+        Ok(Cow::Owned(mesh))
+    }
+
+    /// Load every mesh payload that is not resident.
+    pub fn load_all_payloads(&mut self) -> Result<(), AssetError> {
+        let ids: Vec<AssetId> = self.payloads.keys().copied().collect();
+        ids.into_iter().try_for_each(|id| self.load_payload(id))
+    }
+
+    /// Drop the geometry of a mesh opened from a pack, keeping its counts and
+    /// bounds; `load_payload` reads it again. Returns false if `id` has no
+    /// payload to reload from or is not resident.
+    pub fn unload_payload(&mut self, id: AssetId) -> bool {
+        if !self.payloads.contains_key(&id) || !self.is_resident(id) {
+            return false;
+        }
+        let Some(Asset::Mesh(mesh)) = self.assets.get_mut(&id) else {
+            return false;
+        };
+        *mesh = strip_geometry(mesh);
+        true
+    }
+
+    /// The verified payload bytes of mesh `id`.
+    fn payload_bytes(
+        &self,
+        id: AssetId,
+        payload: &PayloadRef,
+    ) -> Result<Cow<'_, [u8]>, AssetError> {
+        let source = self
+            .pack_source
+            .as_ref()
+            .ok_or_else(|| AssetError::Pack(format!("no pack open for {id:?}")))?;
+        let bytes = source.read(payload.offset, payload.length)?;
+        if Sha256::digest(&bytes).as_slice() != payload.sha256 {
+            return Err(AssetError::PackIntegrity(id));
+        }
+        Ok(bytes)
+    }
+}
+
+fn has_geometry(mesh: &Mesh) -> bool {
+    !(mesh.positions.is_empty()
+        && mesh.normals.is_empty()
+        && mesh.uvs.is_empty()
+        && mesh.tangents.is_empty()
+        && mesh.joints.is_empty()
+        && mesh.weights.is_empty()
+        && mesh.indices.is_empty())
+}
+
+/// `mesh` without the arrays stored in its payload.
+fn strip_geometry(mesh: &Mesh) -> Mesh {
+    Mesh {
+        positions: Vec::new(),
+        normals: Vec::new(),
+        uvs: Vec::new(),
+        tangents: Vec::new(),
+        joints: Vec::new(),
+        weights: Vec::new(),
+        indices: Vec::new(),
+        ..mesh.clone()
+    }
+}
+
+/// Serialize a mesh's arrays as a payload; empty if it has no geometry.
+fn encode_payload(mesh: &Mesh) -> Vec<u8> {
+    if !has_geometry(mesh) {
+        return Vec::new();
+    }
+    let counts = [
+        mesh.positions.len(),
+        mesh.normals.len(),
+        mesh.uvs.len(),
+        mesh.tangents.len(),
+        mesh.joints.len(),
+        mesh.weights.len(),
+        mesh.indices.len(),
+    ];
+    let mut out: Vec<u8> = counts
+        .iter()
+        .flat_map(|count| (*count as u64).to_le_bytes())
+        .collect();
+    out.extend(
+        mesh.positions
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes()),
+    );
+    out.extend(mesh.normals.iter().flatten().flat_map(|v| v.to_le_bytes()));
+    out.extend(mesh.uvs.iter().flatten().flat_map(|v| v.to_le_bytes()));
+    out.extend(mesh.tangents.iter().flatten().flat_map(|v| v.to_le_bytes()));
+    out.extend(mesh.joints.iter().flatten().flat_map(|v| v.to_le_bytes()));
+    out.extend(mesh.weights.iter().flatten().flat_map(|v| v.to_le_bytes()));
+    out.extend(mesh.indices.iter().flat_map(|v| v.to_le_bytes()));
+    out
+}
+
+/// Fill `mesh`'s arrays from a payload; `None` if it is malformed.
+fn decode_payload(mesh: &mut Mesh, bytes: &[u8]) -> Option<()> {
+    let mut reader = PayloadReader(bytes);
+    let mut counts = [0usize; 7];
+    for count in &mut counts {
+        *count = usize::try_from(u64::from_le_bytes(reader.take()?)).ok()?;
+    }
+    let [positions, normals, uvs, tangents, joints, weights, indices] = counts;
+    mesh.positions = reader.array(positions, |r| r.take().map(f32::from_le_bytes))?;
+    mesh.normals = reader.array(normals, |r| r.take().map(f32::from_le_bytes))?;
+    mesh.uvs = reader.array(uvs, |r| r.take().map(f32::from_le_bytes))?;
+    mesh.tangents = reader.array(tangents, |r| r.take().map(f32::from_le_bytes))?;
+    mesh.joints = reader.array(joints, |r| r.take().map(u16::from_le_bytes))?;
+    mesh.weights = reader.array(weights, |r| r.take().map(f32::from_le_bytes))?;
+    mesh.indices = reader
+        .array(indices, |r| r.take().map(u32::from_le_bytes))?
+        .into_iter()
+        .map(|[index]| index)
+        .collect();
+    reader.0.is_empty().then_some(())
+}
+
+/// Little-endian cursor over a payload. Counts come from the file, so
+/// arrays grow as elements are read instead of being preallocated.
+struct PayloadReader<'a>(&'a [u8]);
+
+impl PayloadReader<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (head, rest) = self.0.split_first_chunk::<N>()?;
+        self.0 = rest;
+        Some(*head)
+    }
+
+    fn array<T: Copy + Default, const N: usize>(
+        &mut self,
+        count: usize,
+        mut read: impl FnMut(&mut Self) -> Option<T>,
+    ) -> Option<Vec<[T; N]>> {
+        (0..count)
+            .map(|_| {
+                let mut element = [T::default(); N];
+                for component in &mut element {
+                    *component = read(self)?;
+                }
+                Some(element)
+            })
+            .collect()
+    }
+}
+
+/// Parse the header and index; returns the schema version and entries.
+fn read_index(source: &PackSource) -> Result<(u32, Vec<PackEntry>), AssetError> {
+    let err = |msg: &str| AssetError::Pack(msg.to_string());
+    let file_len = source.len()?;
+    let header = source.read(0, file_len.min(HEADER_LEN as u64))?;
+    if header.len() < V1_HEADER_LEN || &header[..4] != PACK_MAGIC {
+        return Err(err("not an asset pack"));
+    }
+    let u32_at = |offset: usize| {
+        header
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let (schema_version, header_len, entry_len) = match u32_at(4) {
+        Some(1) => (V1_SCHEMA_VERSION, V1_HEADER_LEN, V2_INDEX_ENTRY_LEN),
+        Some(version @ (2 | PACK_VERSION)) => (
+            u32_at(8).ok_or_else(|| err("truncated header"))?,
+            HEADER_LEN,
+            if version == 2 {
+                V2_INDEX_ENTRY_LEN
+            } else {
+                INDEX_ENTRY_LEN
+            },
+        ),
+        version => {
+            return Err(AssetError::Pack(format!(
+                "unsupported pack version {}, expected {PACK_VERSION}",
+                version.unwrap_or_default()
+            )));
+        }
+    };
+    let count = header
+        .get(header_len - 8..header_len)
+        .map(u64_le)
+        .ok_or_else(|| err("truncated header"))?;
+    let index_len = usize::try_from(count)
+        .ok()
+        .and_then(|count| count.checked_mul(entry_len))
+        .filter(|len| (header_len + len) as u64 <= file_len)
+        .ok_or_else(|| err("truncated index"))?;
+    let index = source.read(header_len as u64, index_len as u64)?;
+
+    let entries = index
+        .chunks_exact(entry_len)
+        .map(|entry| {
+            let field = |n: usize| u64_le(&entry[n * 8..n * 8 + 8]);
+            let sha = |start: usize| {
+                let mut sha256 = [0u8; 32];
+                sha256.copy_from_slice(&entry[start..start + 32]);
+                sha256
+            };
+            let payload = (entry_len == INDEX_ENTRY_LEN && field(8) > 0).then(|| PayloadRef {
+                offset: field(7),
+                length: field(8),
+                sha256: sha(72),
+                migrated: false,
+            });
+            PackEntry {
+                id: AssetId(field(0)),
+                offset: field(1),
+                length: field(2),
+                sha256: sha(24),
+                payload,
+            }
+        })
+        .collect();
+    Ok((schema_version, entries))
+}
+
+fn u64_le(bytes: &[u8]) -> u64 {
+    let mut word = [0u8; 8];
+    word.copy_from_slice(bytes);
+    u64::from_le_bytes(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lod::tests::grid;
+
+    fn sample_store() -> AssetStore {
+        let mut store = AssetStore::new();
+        store.register_default_cube();
+        store.register_default_material();
+        store
+    }
+
+    #[test]
+    fn pack_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("assets.wpack");
+        let store = sample_store();
+        store.write_pack(&path).unwrap();
+
+        let loaded = AssetStore::read_pack(&path).unwrap();
+        assert_eq!(loaded.len(), store.len());
+        for id in store.assets.keys() {
+            assert_eq!(
+                loaded.get(*id).unwrap().name(),
+                store.get(*id).unwrap().name()
+            );
+        }
+    }
+
+    #[test]
+    fn pack_index_locates_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("assets.wpack");
+        let mut store = sample_store();
+        let mesh = store.register_mesh(grid(2));
+        store.write_pack(&path).unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        let (schema_version, entries) = read_index(&PackSource::open(&path).unwrap()).unwrap();
+        assert_eq!(schema_version, ASSET_SCHEMA_VERSION);
+        let ids: Vec<AssetId> = entries.iter().map(|e| e.id).collect();
+        assert_eq!(ids, store.assets.keys().copied().collect::<Vec<_>>());
+        assert_eq!(entries[0].offset as usize, HEADER_LEN + 3 * INDEX_ENTRY_LEN);
+
+        // Only the mesh with geometry has a payload, and it ends the file.
+        let with_payload: Vec<&PackEntry> =
+            entries.iter().filter(|e| e.payload.is_some()).collect();
+        assert_eq!(with_payload.len(), 1);
+        assert_eq!(with_payload[0].id, mesh);
+        let payload = with_payload[0].payload.unwrap();
+        let last = entries.last().unwrap();
+        assert_eq!(payload.offset, last.offset + last.length);
+        assert_eq!((payload.offset + payload.length) as usize, data.len());
+    }
+
+    #[test]
+    fn payload_roundtrip() {
+        let mut mesh = grid(3);
+        mesh.joints = vec![[1, 2, 3, 4]; mesh.positions.len()];
+        mesh.weights = vec![[0.25; 4]; mesh.positions.len()];
+        let payload = encode_payload(&mesh);
+
+        let mut decoded = strip_geometry(&mesh);
+        assert!(!has_geometry(&decoded));
+        decode_payload(&mut decoded, &payload).unwrap();
+        assert_eq!(decoded.positions, mesh.positions);
+        assert_eq!(decoded.uvs, mesh.uvs);
+        assert_eq!(decoded.joints, mesh.joints);
+        assert_eq!(decoded.weights, mesh.weights);
+        assert_eq!(decoded.indices, mesh.indices);
+        assert_eq!(
+            content_id(&Asset::Mesh(decoded)),
+            content_id(&Asset::Mesh(mesh))
+        );
+
+        assert!(decode_payload(&mut Mesh::default(), &payload[..payload.len() - 1]).is_none());
+        assert!(encode_payload(&Mesh::default()).is_empty());
+    }
+
+    #[test]
+    fn open_pack_loads_geometry_on_demand() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("assets.wpack");
+        let mut store = sample_store();
+        let id = store.register_mesh(grid(4));
+        store.write_pack(&path).unwrap();
+
+        let mut lazy = AssetStore::open_pack(&path).unwrap();
+        assert!(!lazy.is_resident(id));
+        let mesh = lazy.get_mesh(id).unwrap();
+        assert!(mesh.positions.is_empty());
+        assert_eq!(mesh.vertex_count, 25);
+        assert!(mesh.bounds.is_some());
+
+        let loaded = lazy.mesh_with_geometry(id).unwrap();
+        assert!(matches!(loaded, Cow::Owned(_)));
+        assert_eq!(loaded.positions, grid(4).positions);
+        assert!(!lazy.is_resident(id));
+
+        lazy.load_payload(id).unwrap();
+        assert!(lazy.is_resident(id));
+        assert!(matches!(
+            lazy.mesh_with_geometry(id).unwrap(),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(lazy.get_mesh(id).unwrap().positions, grid(4).positions);
+        assert!(lazy.unload_payload(id));
+        assert!(!lazy.is_resident(id));
+
+        // A lazily opened store writes its geometry through from the source.
+        let copy = dir.path().join("copy.wpack");
+        lazy.write_pack(&copy).unwrap();
+        assert_eq!(std::fs::read(&copy).unwrap(), std::fs::read(&path).unwrap());
+
+        let mut data = std::fs::read(&path).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 0xFF;
+        std::fs::write(&path, &data).unwrap();
+        let mut corrupt = AssetStore::open_pack(&path).unwrap();
+        assert!(matches!(
+            corrupt.load_payload(id),
+            Err(AssetError::PackIntegrity(_))
+        ));
+        assert!(!corrupt.is_resident(id));
+    }
+
+    #[test]
+    fn corrupt_pack_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("assets.wpack");
+        sample_store().write_pack(&path).unwrap();
+
+        let mut data = std::fs::read(&path).unwrap();
+        let source = PackSource::open(&path).unwrap();
+        let first = read_index(&source).unwrap().1[0].offset as usize;
+        data[first] ^= 0xFF;
+        std::fs::write(&path, &data).unwrap();
+        assert!(matches!(
+            AssetStore::read_pack(&path),
+            Err(AssetError::PackIntegrity(_))
+        ));
+
+        std::fs::write(&path, &data[..HEADER_LEN + 10]).unwrap();
+        assert!(matches!(
+            AssetStore::read_pack(&path),
+            Err(AssetError::Pack(_))
+        ));
+        std::fs::write(&path, b"{}").unwrap();
+        assert!(matches!(
+            AssetStore::read_pack(&path),
+            Err(AssetError::Pack(_))
+        ));
+    }
+}
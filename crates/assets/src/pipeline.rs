@@ -0,0 +1,345 @@
+//! Configurable import pipeline: mesh processors chained per file type.
+//!
+//! Processors run on a file's meshes after they are read and before they are
+//! registered, so asset IDs and LOD chains reflect the processed content.
+//! Mesh bounds are recomputed after the chain runs. Skeletons are not
+//! processed: transforming skinned meshes also requires transforming their
+//! inverse bind matrices. A
+//! pipeline is usually loaded from a JSON config checked in with the content:
+//!
+//! ```json
+//! { "stages": {
+//!     "glb": [
+//!         { "processor": "scale", "factor": 0.01 },
+//!         { "processor": "z_up_to_y_up" },
+//!         { "processor": "generate_tangents" }
+//!     ]
+//! } }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AssetError, AssetStore, ImportReport, Mesh};
+
+/// One mesh processing stage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "processor", rename_all = "snake_case")]
+pub enum Processor {
+    /// Multiply positions by `factor`, e.g. 0.01 for centimeter sources.
+    Scale { factor: f32 },
+    /// Convert Z-up content to the engine's Y-up convention.
+    ZUpToYUp,
+    /// Compute per-vertex tangents from positions, normals, and UVs.
+    /// Meshes without normals or UVs are left without tangents.
+    GenerateTangents,
+    /// Merge the file's meshes that share a material and skeleton into one
+    /// mesh each.
+    MergeMeshes,
+}
+
+impl Processor {
+    fn apply(&self, meshes: Vec<Mesh>) -> Vec<Mesh> {
+        match self {
+            Processor::Scale { factor } => map_meshes(meshes, |mesh| {
+                for p in &mut mesh.positions {
+                    *p = p.map(|c| c * factor);
+                }
+            }),
+            Processor::ZUpToYUp => map_meshes(meshes, |mesh| {
+                let convert = |[x, y, z]: [f32; 3]| [x, z, -y];
+                for p in &mut mesh.positions {
+                    *p = convert(*p);
+                }
+                for n in &mut mesh.normals {
+                    *n = convert(*n);
+                }
+                for t in &mut mesh.tangents {
+                    let [x, y, z] = convert([t[0], t[1], t[2]]);
+                    *t = [x, y, z, t[3]];
+                }
+            }),
+            Processor::GenerateTangents => map_meshes(meshes, generate_tangents),
+            Processor::MergeMeshes => merge_meshes(meshes),
+        }
+    }
+}
+
+fn map_meshes(mut meshes: Vec<Mesh>, f: impl Fn(&mut Mesh)) -> Vec<Mesh> {
+    meshes.iter_mut().for_each(f);
+    meshes
+}
+
+/// Processor chains per file type, applied by `AssetStore::import_gltf_with`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportPipeline {
+    /// Processors in order, keyed by lowercase file extension (`gltf`, `glb`).
+    #[serde(default)]
+    pub stages: BTreeMap<String, Vec<Processor>>,
+}
+
+impl ImportPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `processor` to the chain for files with `extension`.
+    pub fn with_stage(mut self, extension: &str, processor: Processor) -> Self {
+        self.stages
+            .entry(extension.to_ascii_lowercase())
+            .or_default()
+            .push(processor);
+        self
+    }
+
+    /// Load a pipeline from a JSON config file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AssetError> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// The processors that apply to `path`, by its extension.
+    pub fn processors_for(&self, path: &Path) -> &[Processor] {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(|e| self.stages.get(&e.to_ascii_lowercase()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Run the chain for `path` over `meshes`, then update their bounds.
+    pub fn process(&self, path: &Path, meshes: Vec<Mesh>) -> Vec<Mesh> {
+        let meshes = self
+            .processors_for(path)
+            .iter()
+            .fold(meshes, |meshes, processor| processor.apply(meshes));
+        map_meshes(meshes, Mesh::update_bounds)
+    }
+
+    /// Import a glTF/GLB file through this pipeline.
+    pub fn import(
+        &self,
+        store: &mut AssetStore,
+        path: impl AsRef<Path>,
+    ) -> Result<ImportReport, AssetError> {
+        store.import_gltf_with(path, self)
+    }
+}
+
+/// Per-vertex tangents (xyz + handedness in w) by accumulating each
+/// triangle's UV-space tangent and orthonormalizing against the normal.
+fn generate_tangents(mesh: &mut Mesh) {
+    let count = mesh.positions.len();
+    if mesh.normals.len() != count || mesh.uvs.len() != count {
+        tracing::warn!(mesh = %mesh.name, "tangent generation needs normals and UVs");
+        return;
+    }
+    let mut tangents = vec![[0.0f32; 3]; count];
+    let mut bitangents = vec![[0.0f32; 3]; count];
+    for tri in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| i as usize);
+        let (p, uv) = (&mesh.positions, &mesh.uvs);
+        let e1 = sub(p[b], p[a]);
+        let e2 = sub(p[c], p[a]);
+        let (du1, dv1) = (uv[b][0] - uv[a][0], uv[b][1] - uv[a][1]);
+        let (du2, dv2) = (uv[c][0] - uv[a][0], uv[c][1] - uv[a][1]);
+        let det = du1 * dv2 - du2 * dv1;
+        if det.abs() <= f32::EPSILON {
+            continue;
+        }
+        let r = 1.0 / det;
+        let t = [0, 1, 2].map(|i| (e1[i] * dv2 - e2[i] * dv1) * r);
+        let s = [0, 1, 2].map(|i| (e2[i] * du1 - e1[i] * du2) * r);
+        for v in [a, b, c] {
+            tangents[v] = add(tangents[v], t);
+            bitangents[v] = add(bitangents[v], s);
+        }
+    }
+
+    mesh.tangents = (0..count)
+        .map(|v| {
+            let n = mesh.normals[v];
+            let t = tangents[v];
+            // Gram-Schmidt: remove the normal component, then normalize.
+            let t = sub(t, n.map(|c| c * dot(n, t)));
+            let len = dot(t, t).sqrt();
+            let [x, y, z] = if len > 0.0 {
+                t.map(|c| c / len)
+            } else {
+                [1.0, 0.0, 0.0]
+            };
+            let w = if dot(cross(n, [x, y, z]), bitangents[v]) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            [x, y, z, w]
+        })
+        .collect();
+}
+
+/// Merge meshes sharing a material and skeleton, keeping the first mesh's
+/// name. Optional attributes present on any merged mesh are zero-padded on
+/// the others.
+fn merge_meshes(meshes: Vec<Mesh>) -> Vec<Mesh> {
+    let mut groups: Vec<Vec<Mesh>> = Vec::new();
+    for mesh in meshes {
+        let key = (mesh.material, mesh.skeleton);
+        match groups
+            .iter_mut()
+            .find(|g| (g[0].material, g[0].skeleton) == key)
+        {
+            Some(group) => group.push(mesh),
+            None => groups.push(vec![mesh]),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|group| {
+            let has_normals = group.iter().any(|m| !m.normals.is_empty());
+            let has_uvs = group.iter().any(|m| !m.uvs.is_empty());
+            let has_tangents = group.iter().any(|m| !m.tangents.is_empty());
+            let has_skin = group.iter().any(|m| !m.joints.is_empty());
+            let mut merged = Mesh {
+                name: group[0].name.clone(),
+                material: group[0].material,
+                skeleton: group[0].skeleton,
+                ..Default::default()
+            };
+            for mesh in group {
+                let base = merged.positions.len();
+                let count = mesh.positions.len();
+                merged
+                    .indices
+                    .extend(mesh.indices.iter().map(|i| i + base as u32));
+                merged.positions.extend(mesh.positions);
+                if has_normals {
+                    merged.normals.extend(mesh.normals);
+                    merged.normals.resize(base + count, [0.0; 3]);
+                }
+                if has_uvs {
+                    merged.uvs.extend(mesh.uvs);
+                    merged.uvs.resize(base + count, [0.0; 2]);
+                }
+                if has_tangents {
+                    merged.tangents.extend(mesh.tangents);
+                    merged.tangents.resize(base + count, [0.0; 4]);
+                }
+                if has_skin {
+                    merged.joints.extend(mesh.joints);
+                    merged.joints.resize(base + count, [0; 4]);
+                    merged.weights.extend(mesh.weights);
+                    merged.weights.resize(base + count, [0.0; 4]);
+                }
+            }
+            merged.vertex_count = merged.positions.len() as u32;
+            merged.index_count = merged.indices.len() as u32;
+            merged
+        })
+        .collect()
+}
+
+fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AssetId;
+
+    /// A unit quad on the XY plane facing +Z, with UVs matching XY.
+    fn quad(name: &str, material: Option<AssetId>) -> Mesh {
+        Mesh {
+            name: name.into(),
+            vertex_count: 4,
+            index_count: 6,
+            positions: vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+            ],
+            normals: vec![[0.0, 0.0, 1.0]; 4],
+            uvs: vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            material,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stages_apply_in_order_per_extension() {
+        let pipeline = ImportPipeline::new()
+            .with_stage("GLB", Processor::Scale { factor: 2.0 })
+            .with_stage("glb", Processor::ZUpToYUp)
+            .with_stage("glb", Processor::GenerateTangents);
+        assert!(pipeline.processors_for(Path::new("a.gltf")).is_empty());
+
+        let out = pipeline.process(Path::new("model.glb"), vec![quad("q", None)]);
+        assert_eq!(out[0].positions[2], [2.0, 0.0, -2.0]);
+        assert_eq!(out[0].normals[0], [0.0, 1.0, 0.0]);
+        // U runs along +X on the quad; the tangent follows it.
+        assert_eq!(out[0].tangents, vec![[1.0, 0.0, 0.0, 1.0]; 4]);
+        let aabb = out[0].bounds.unwrap().aabb;
+        assert_eq!((aabb.min, aabb.max), ([0.0, 0.0, -2.0], [2.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn tangents_need_uvs() {
+        let mut mesh = quad("q", None);
+        mesh.uvs.clear();
+        generate_tangents(&mut mesh);
+        assert!(mesh.tangents.is_empty());
+    }
+
+    #[test]
+    fn merge_groups_by_material() {
+        let (red, blue) = (Some(AssetId(1)), Some(AssetId(2)));
+        let mut bare = quad("c", red);
+        bare.uvs.clear();
+        let merged = merge_meshes(vec![quad("a", red), quad("b", blue), bare]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].name, "a");
+        assert_eq!(merged[0].vertex_count, 8);
+        assert_eq!(merged[0].indices[6..9], [4, 5, 6]);
+        assert_eq!(merged[0].uvs.len(), 8);
+        assert_eq!(merged[1].material, blue);
+    }
+
+    #[test]
+    fn config_file_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("import.json");
+        std::fs::write(
+            &path,
+            r#"{ "stages": { "gltf": [
+                { "processor": "scale", "factor": 0.01 },
+                { "processor": "merge_meshes" }
+            ] } }"#,
+        )
+        .unwrap();
+        let pipeline = ImportPipeline::load(&path).unwrap();
+        assert_eq!(
+            pipeline.processors_for(Path::new("x.gltf")),
+            &[Processor::Scale { factor: 0.01 }, Processor::MergeMeshes]
+        );
+    }
+}
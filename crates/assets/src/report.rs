@@ -0,0 +1,44 @@
+//! Import diagnostics.
+//!
+//! Importers register what they can and describe what they could not use in
+//! an `ImportReport`, so callers can surface partial imports instead of
+//! finding out from missing or placeholder assets.
+
+use crate::{AssetId, DedupStats};
+
+/// Something an import skipped or could not represent. The rest of the file
+/// was still imported.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ImportWarning {
+    #[error("mesh {mesh}: primitives without normals")]
+    MissingNormals { mesh: String },
+    #[error("mesh {mesh}: skipped primitive {primitive} with non-triangle mode {mode}")]
+    NonTrianglePrimitive {
+        mesh: String,
+        primitive: usize,
+        mode: u64,
+    },
+    #[error("unsupported glTF extension {0} ignored")]
+    UnsupportedExtension(String),
+    #[error("image {image}: {error}")]
+    ImageDecode { image: usize, error: String },
+    #[error("file contains no importable assets")]
+    NoAssets,
+}
+
+/// The outcome of importing one source file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Registered assets in import order.
+    pub ids: Vec<AssetId>,
+    pub warnings: Vec<ImportWarning>,
+    /// Assets reused from the store instead of being registered again.
+    pub dedup: DedupStats,
+}
+
+impl ImportReport {
+    /// True if the import produced no warnings.
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
@@ -0,0 +1,235 @@
+//! glTF node hierarchies for scene import.
+//!
+//! `AssetStore::import_gltf_scene` registers a file's assets like
+//! `import_gltf` and also returns the node hierarchy of its default scene,
+//! with mesh references resolved to asset IDs, for callers that instantiate
+//! it (e.g. `worldspace-ecs`). Node transforms are kept as authored: local to
+//! the parent node, either TRS or a column-major matrix.
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::{AssetError, AssetId, AssetStore, ImportPipeline, ImportReport};
+
+/// A node's transform relative to its parent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeTransform {
+    Trs {
+        translation: [f32; 3],
+        /// Unit quaternion as `[x, y, z, w]`.
+        rotation: [f32; 4],
+        scale: [f32; 3],
+    },
+    /// Column-major 4x4 matrix.
+    Matrix([f32; 16]),
+}
+
+impl Default for NodeTransform {
+    fn default() -> Self {
+        NodeTransform::Trs {
+            translation: [0.0; 3],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            scale: [1.0; 3],
+        }
+    }
+}
+
+/// One glTF node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneNode {
+    pub name: String,
+    pub transform: NodeTransform,
+    /// The registered mesh this node instances.
+    pub mesh: Option<AssetId>,
+    /// Indices into `SceneGraph::nodes`.
+    pub children: Vec<usize>,
+}
+
+/// The node hierarchy of a glTF file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SceneGraph {
+    /// Every node in the file, by glTF node index.
+    pub nodes: Vec<SceneNode>,
+    /// Root nodes of the default scene (`scene`, else the first one); all
+    /// parentless nodes if the file has no scenes.
+    pub roots: Vec<usize>,
+}
+
+impl SceneGraph {
+    /// Indices of the nodes reachable from `roots`, parents before children.
+    pub fn hierarchy_order(&self) -> Vec<usize> {
+        let mut order = Vec::new();
+        let mut stack: Vec<usize> = self.roots.iter().rev().copied().collect();
+        while let Some(node) = stack.pop() {
+            order.push(node);
+            stack.extend(self.nodes[node].children.iter().rev());
+        }
+        order
+    }
+}
+
+impl AssetStore {
+    /// Import a glTF file like `import_gltf` and return its node hierarchy.
+    ///
+    /// No pipeline processors run, so each glTF mesh maps to exactly one
+    /// registered mesh.
+    pub fn import_gltf_scene(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<(ImportReport, SceneGraph), AssetError> {
+        let (report, json, meshes) =
+            self.import_document(path.as_ref(), &ImportPipeline::default())?;
+        Ok((report, scene_graph(&json, &meshes)?))
+    }
+}
+
+/// Read the node hierarchy, checking that it forms a forest: child indices
+/// are in range and no node has two parents.
+fn scene_graph(json: &Value, meshes: &[AssetId]) -> Result<SceneGraph, AssetError> {
+    let err = |message: String| AssetError::GltfParse(message);
+    let entries = json
+        .get("nodes")
+        .and_then(Value::as_array)
+        .map_or(&[][..], Vec::as_slice);
+    let mut parents = vec![None; entries.len()];
+    let mut nodes = Vec::with_capacity(entries.len());
+    for (i, node) in entries.iter().enumerate() {
+        let name = node
+            .get("name")
+            .and_then(Value::as_str)
+            .map_or_else(|| format!("node_{i}"), str::to_string);
+        let mesh = match node.get("mesh").and_then(Value::as_u64) {
+            Some(mesh) => Some(
+                *meshes
+                    .get(mesh as usize)
+                    .ok_or_else(|| err(format!("node {i}: mesh {mesh} out of range")))?,
+            ),
+            None => None,
+        };
+        let children = indices(node, "children", entries.len())
+            .ok_or_else(|| err(format!("node {i}: child index out of range")))?;
+        for &child in &children {
+            if child == i {
+                return Err(err(format!("node {i} is its own child")));
+            }
+            if parents[child].replace(i).is_some() {
+                return Err(err(format!("node {child} has more than one parent")));
+            }
+        }
+        let transform = match node.get("matrix") {
+            Some(matrix) => NodeTransform::Matrix(
+                floats(matrix).ok_or_else(|| err(format!("node {i}: malformed matrix")))?,
+            ),
+            None => NodeTransform::Trs {
+                translation: trs_field(node, "translation", [0.0; 3], i)?,
+                rotation: trs_field(node, "rotation", [0.0, 0.0, 0.0, 1.0], i)?,
+                scale: trs_field(node, "scale", [1.0; 3], i)?,
+            },
+        };
+        nodes.push(SceneNode {
+            name,
+            transform,
+            mesh,
+            children,
+        });
+    }
+
+    let scene = json.get("scene").and_then(Value::as_u64).unwrap_or(0) as usize;
+    let roots = match json
+        .get("scenes")
+        .and_then(Value::as_array)
+        .and_then(|scenes| scenes.get(scene))
+    {
+        Some(scene) => {
+            let roots = indices(scene, "nodes", nodes.len())
+                .ok_or_else(|| err("scene node index out of range".into()))?;
+            if let Some(&root) = roots.iter().find(|&&root| parents[root].is_some()) {
+                return Err(err(format!("scene root {root} has a parent")));
+            }
+            roots
+        }
+        None => (0..nodes.len()).filter(|&i| parents[i].is_none()).collect(),
+    };
+    Ok(SceneGraph { nodes, roots })
+}
+
+/// The index array at `key`, if every entry is below `len`.
+fn indices(value: &Value, key: &str, len: usize) -> Option<Vec<usize>> {
+    let Some(array) = value.get(key).and_then(Value::as_array) else {
+        return Some(Vec::new());
+    };
+    array
+        .iter()
+        .map(|i| i.as_u64().map(|i| i as usize).filter(|&i| i < len))
+        .collect()
+}
+
+/// Node `i`'s TRS component at `key`, or `default` if it is absent.
+fn trs_field<const N: usize>(
+    node: &Value,
+    key: &str,
+    default: [f32; N],
+    i: usize,
+) -> Result<[f32; N], AssetError> {
+    match node.get(key) {
+        Some(value) => {
+            floats(value).ok_or_else(|| AssetError::GltfParse(format!("node {i}: malformed {key}")))
+        }
+        None => Ok(default),
+    }
+}
+
+fn floats<const N: usize>(value: &Value) -> Option<[f32; N]> {
+    let array = value.as_array().filter(|a| a.len() == N)?;
+    let mut out = [0.0; N];
+    for (slot, v) in out.iter_mut().zip(array) {
+        *slot = v.as_f64()? as f32;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scene_graph_reads_hierarchy() {
+        let json: Value = serde_json::from_str(
+            r#"{
+                "scene": 0,
+                "scenes": [{ "nodes": [0] }],
+                "nodes": [
+                    { "name": "root", "children": [1, 2], "translation": [1, 0, 0] },
+                    { "mesh": 0, "scale": [2, 2, 2] },
+                    { "matrix": [1,0,0,0, 0,1,0,0, 0,0,1,0, 0,3,0,1] },
+                    { "name": "unused" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let graph = scene_graph(&json, &[AssetId(7)]).unwrap();
+        assert_eq!(graph.roots, vec![0]);
+        assert_eq!(graph.hierarchy_order(), vec![0, 1, 2]);
+        assert_eq!(graph.nodes[1].name, "node_1");
+        assert_eq!(graph.nodes[1].mesh, Some(AssetId(7)));
+        assert_eq!(
+            graph.nodes[0].transform,
+            NodeTransform::Trs {
+                translation: [1.0, 0.0, 0.0],
+                rotation: [0.0, 0.0, 0.0, 1.0],
+                scale: [1.0; 3],
+            }
+        );
+        assert!(matches!(graph.nodes[2].transform, NodeTransform::Matrix(m) if m[13] == 3.0));
+
+        let shared_child: Value =
+            serde_json::from_str(r#"{ "nodes": [{ "children": [2] }, { "children": [2] }, {}] }"#)
+                .unwrap();
+        assert!(scene_graph(&shared_child, &[]).is_err());
+        let cycle: Value = serde_json::from_str(r#"{ "nodes": [{ "children": [0] }] }"#).unwrap();
+        assert!(scene_graph(&cycle, &[]).is_err());
+        let bad_mesh: Value = serde_json::from_str(r#"{ "nodes": [{ "mesh": 1 }] }"#).unwrap();
+        assert!(scene_graph(&bad_mesh, &[AssetId(7)]).is_err());
+    }
+}
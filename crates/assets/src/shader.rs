@@ -0,0 +1,77 @@
+//! WGSL shader assets for custom material pipelines.
+//!
+//! Sources are parsed and validated with naga when the asset is created, so
+//! a shader in the store is known to compile; the renderer still builds the
+//! pipeline itself.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::AssetError;
+
+/// A validated WGSL shader module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shader {
+    pub name: String,
+    pub source: String,
+}
+
+impl Shader {
+    /// Parse and validate `source` as WGSL.
+    pub fn compile(name: impl Into<String>, source: impl Into<String>) -> Result<Self, AssetError> {
+        let name = name.into();
+        let source = source.into();
+        let module = naga::front::wgsl::parse_str(&source).map_err(|e| {
+            AssetError::ShaderCompile(format!("{name}: {}", e.emit_to_string(&source)))
+        })?;
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .map_err(|e| AssetError::ShaderCompile(format!("{name}: {}", e.emit_to_string(&source))))?;
+        Ok(Self { name, source })
+    }
+
+    /// Read and validate a `.wgsl` file, named after its file stem.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AssetError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+        let name = path.file_stem().map_or_else(
+            || "shader".to_string(),
+            |s| s.to_string_lossy().into_owned(),
+        );
+        Self::compile(name, source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLAT: &str = r#"
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 1.0, 1.0);
+}
+"#;
+
+    #[test]
+    fn valid_wgsl_compiles() {
+        let shader = Shader::compile("flat", FLAT).unwrap();
+        assert_eq!(shader.name, "flat");
+        assert_eq!(shader.source, FLAT);
+    }
+
+    #[test]
+    fn invalid_wgsl_rejected() {
+        let err = Shader::compile("broken", "fn fs_main( -> {").unwrap_err();
+        assert!(matches!(err, AssetError::ShaderCompile(msg) if msg.starts_with("broken:")));
+    }
+}
@@ -0,0 +1,93 @@
+//! Skeletons for skinned meshes.
+//!
+//! A `Skeleton` is the joint hierarchy of one glTF skin. Skinned meshes
+//! reference it and carry per-vertex joint indices into `Skeleton::joints`
+//! with matching weights; see `Mesh::joints` and `Mesh::weights`.
+
+use serde::{Deserialize, Serialize};
+
+/// Column-major 4x4 identity, the inverse bind matrix of a joint bound at the
+/// origin.
+pub const IDENTITY_MATRIX: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+];
+
+/// One joint of a skeleton.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Joint {
+    pub name: String,
+    /// Index of the parent joint in `Skeleton::joints`; `None` for roots.
+    pub parent: Option<u32>,
+    /// Column-major matrix from model space to this joint's bind-pose space.
+    pub inverse_bind_matrix: [f32; 16],
+}
+
+/// A joint hierarchy that skinned meshes are bound to.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Skeleton {
+    pub name: String,
+    pub joints: Vec<Joint>,
+}
+
+impl Skeleton {
+    /// Index of the joint called `name`.
+    pub fn joint_index(&self, name: &str) -> Option<u32> {
+        self.joints
+            .iter()
+            .position(|j| j.name == name)
+            .map(|i| i as u32)
+    }
+
+    /// Joint indices ordered so every parent precedes its children, for
+    /// computing world transforms in one pass. Joints whose parent chain is
+    /// broken or cyclic are left out.
+    pub fn hierarchy_order(&self) -> Vec<u32> {
+        let mut order = Vec::with_capacity(self.joints.len());
+        let mut placed = vec![false; self.joints.len()];
+        loop {
+            let before = order.len();
+            for (i, joint) in self.joints.iter().enumerate() {
+                let ready = match joint.parent {
+                    None => true,
+                    Some(p) => placed.get(p as usize).copied().unwrap_or(false),
+                };
+                if !placed[i] && ready {
+                    placed[i] = true;
+                    order.push(i as u32);
+                }
+            }
+            if order.len() == before {
+                return order;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joint(name: &str, parent: Option<u32>) -> Joint {
+        Joint {
+            name: name.into(),
+            parent,
+            inverse_bind_matrix: IDENTITY_MATRIX,
+        }
+    }
+
+    #[test]
+    fn hierarchy_order_puts_parents_first() {
+        let skeleton = Skeleton {
+            name: "rig".into(),
+            joints: vec![
+                joint("hand", Some(2)),
+                joint("hips", None),
+                joint("arm", Some(1)),
+                joint("loop", Some(3)),
+            ],
+        };
+        assert_eq!(skeleton.hierarchy_order(), vec![1, 2, 0]);
+        assert_eq!(skeleton.joint_index("arm"), Some(2));
+        assert_eq!(skeleton.joint_index("tail"), None);
+    }
+}
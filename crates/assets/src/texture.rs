@@ -0,0 +1,164 @@
+//! Texture assets: PNG/JPEG decoding and KTX2 container parsing.
+//!
+//! PNG and JPEG are decoded to RGBA8 on import. KTX2 payloads are already
+//! GPU-ready, so their mip levels are kept as-is together with the Vulkan
+//! format code; supercompressed (Basis/zstd) KTX2 files are rejected.
+
+use serde::{Deserialize, Serialize};
+
+use crate::AssetError;
+
+const PNG_MAGIC: &[u8] = b"\x89PNG\r\n\x1a\n";
+const JPEG_MAGIC: &[u8] = &[0xFF, 0xD8, 0xFF];
+pub(crate) const KTX2_MAGIC: &[u8] = &[
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+/// Identifier (12) + header (36) + index (32) bytes before the level index.
+const KTX2_LEVEL_INDEX_OFFSET: usize = 80;
+
+/// Pixel payload of a texture.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TextureData {
+    /// Decoded 8-bit RGBA, row-major, no padding.
+    Rgba8(Vec<u8>),
+    /// Block-compressed mip levels (largest first) in the given `VkFormat`.
+    Compressed {
+        vk_format: u32,
+        levels: Vec<Vec<u8>>,
+    },
+}
+
+/// A 2D texture asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Texture {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub data: TextureData,
+}
+
+impl Texture {
+    /// Decode a PNG, JPEG, or KTX2 image, detected by its magic bytes.
+    pub fn decode(name: impl Into<String>, bytes: &[u8]) -> Result<Self, AssetError> {
+        let name = name.into();
+        if bytes.starts_with(KTX2_MAGIC) {
+            return parse_ktx2(name, bytes);
+        }
+        if !bytes.starts_with(PNG_MAGIC) && !bytes.starts_with(JPEG_MAGIC) {
+            return Err(AssetError::TextureDecode(format!(
+                "{name}: unrecognized image format"
+            )));
+        }
+        let rgba = image::load_from_memory(bytes)
+            .map_err(|e| AssetError::TextureDecode(format!("{name}: {e}")))?
+            .into_rgba8();
+        Ok(Self {
+            name,
+            width: rgba.width(),
+            height: rgba.height(),
+            data: TextureData::Rgba8(rgba.into_raw()),
+        })
+    }
+}
+
+pub(crate) fn parse_ktx2(name: String, bytes: &[u8]) -> Result<Texture, AssetError> {
+    let err = |msg: &str| AssetError::TextureDecode(format!("{name}: {msg}"));
+    let u32_at = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let u64_at = |offset: usize| {
+        bytes.get(offset..offset + 8).map(|b| {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(b);
+            u64::from_le_bytes(word)
+        })
+    };
+
+    let header = |index: usize| u32_at(12 + index * 4).ok_or_else(|| err("truncated KTX2 header"));
+    let vk_format = header(0)?;
+    let width = header(2)?;
+    let height = header(3)?.max(1);
+    let level_count = header(7)?.max(1) as usize;
+    let supercompression = header(8)?;
+    if vk_format == 0 {
+        return Err(err(
+            "KTX2 with VK_FORMAT_UNDEFINED (Basis) needs transcoding",
+        ));
+    }
+    if supercompression != 0 {
+        return Err(err("supercompressed KTX2 is not supported"));
+    }
+
+    // `level_count` is untrusted: the level index runs out of file first.
+    let mut levels = Vec::new();
+    for level in 0..level_count {
+        let entry = KTX2_LEVEL_INDEX_OFFSET + level * 24;
+        let (offset, length) = u64_at(entry)
+            .zip(u64_at(entry + 8))
+            .ok_or_else(|| err("truncated KTX2 level index"))?;
+        let data = usize::try_from(offset)
+            .ok()
+            .zip(usize::try_from(length).ok())
+            .and_then(|(offset, length)| bytes.get(offset..offset.checked_add(length)?))
+            .ok_or_else(|| err("KTX2 level exceeds file size"))?;
+        levels.push(data.to_vec());
+    }
+
+    Ok(Texture {
+        name,
+        width,
+        height,
+        data: TextureData::Compressed { vk_format, levels },
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// VK_FORMAT_BC1_RGB_UNORM_BLOCK.
+    pub(crate) const BC1: u32 = 131;
+
+    /// A 4x4 single-level BC1 KTX2 file with one 8-byte block.
+    pub(crate) fn ktx2_bytes() -> Vec<u8> {
+        let mut out = KTX2_MAGIC.to_vec();
+        for v in [BC1, 1, 4, 4, 0, 0, 1, 1, 0] {
+            out.extend_from_slice(&u32::to_le_bytes(v));
+        }
+        out.resize(KTX2_LEVEL_INDEX_OFFSET, 0);
+        let data_offset = (KTX2_LEVEL_INDEX_OFFSET + 24) as u64;
+        for v in [data_offset, 8, 8] {
+            out.extend_from_slice(&u64::to_le_bytes(v));
+        }
+        out.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        out
+    }
+
+    #[test]
+    fn ktx2_levels_kept_compressed() {
+        let texture = Texture::decode("bc1", &ktx2_bytes()).unwrap();
+        assert_eq!((texture.width, texture.height), (4, 4));
+        assert_eq!(
+            texture.data,
+            TextureData::Compressed {
+                vk_format: BC1,
+                levels: vec![vec![1, 2, 3, 4, 5, 6, 7, 8]],
+            }
+        );
+    }
+
+    #[test]
+    fn truncated_or_unknown_rejected() {
+        let bytes = ktx2_bytes();
+        assert!(Texture::decode("short", &bytes[..bytes.len() - 1]).is_err());
+        let mut huge_level_count = bytes.clone();
+        huge_level_count[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Texture::decode("levels", &huge_level_count).is_err());
+        assert!(matches!(
+            Texture::decode("gif", b"GIF89a"),
+            Err(AssetError::TextureDecode(_))
+        ));
+    }
+}
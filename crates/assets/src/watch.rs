@@ -0,0 +1,210 @@
+//! Hot reload of imported asset source files.
+//!
+//! The watcher polls each source file's modification time and size rather
+//! than subscribing to OS notifications, so reloads happen at a point the
+//! caller chooses (e.g. once per frame) and never on a background thread.
+//! Only the source file itself is watched; external `.bin` buffers and images
+//! referenced by a `.gltf` do not trigger a reload on their own.
+
+use std::collections::BTreeMap;
+use std::mem::discriminant;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::{Asset, AssetError, AssetEvent, AssetId, AssetStore, ImportPipeline, ImportReport};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl Stamp {
+    fn read(path: &Path) -> Result<Self, AssetError> {
+        let meta = std::fs::metadata(path)?;
+        Ok(Self {
+            modified: meta.modified().ok(),
+            len: meta.len(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct WatchedSource {
+    stamp: Stamp,
+    ids: Vec<AssetId>,
+}
+
+/// Tracks imported glTF source files and re-imports them when they change.
+#[derive(Debug, Default)]
+pub struct AssetWatcher {
+    sources: BTreeMap<PathBuf, WatchedSource>,
+    pipeline: ImportPipeline,
+}
+
+impl AssetWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run imports and re-imports through `pipeline`.
+    pub fn with_pipeline(mut self, pipeline: ImportPipeline) -> Self {
+        self.pipeline = pipeline;
+        self
+    }
+
+    /// Import a glTF file into `store` and start watching it.
+    pub fn import(
+        &mut self,
+        store: &mut AssetStore,
+        path: impl AsRef<Path>,
+    ) -> Result<ImportReport, AssetError> {
+        let path = path.as_ref();
+        let stamp = Stamp::read(path)?;
+        let report = store.import_gltf_with(path, &self.pipeline)?;
+        self.sources.insert(
+            path.to_path_buf(),
+            WatchedSource {
+                stamp,
+                ids: report.ids.clone(),
+            },
+        );
+        Ok(report)
+    }
+
+    /// Stop watching `path`. Returns false if it was not watched.
+    pub fn unwatch(&mut self, path: impl AsRef<Path>) -> bool {
+        self.sources.remove(path.as_ref()).is_some()
+    }
+
+    /// Watched source files in path order.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.sources.keys().map(PathBuf::as_path)
+    }
+
+    /// Re-import every watched file whose modification time or size changed.
+    ///
+    /// New assets are paired with the previous import's assets by kind and
+    /// name; an `AssetReloaded` event is emitted for each pair whose ID
+    /// changed. A file that fails to re-import (mid-save, deleted) keeps its
+    /// previous assets and is retried after its next change.
+    pub fn poll(&mut self, store: &mut AssetStore) -> Vec<AssetEvent> {
+        let mut events = Vec::new();
+        for (path, source) in &mut self.sources {
+            let stamp = match Stamp::read(path) {
+                Ok(stamp) => stamp,
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "watched asset unreadable");
+                    continue;
+                }
+            };
+            if stamp == source.stamp {
+                continue;
+            }
+            source.stamp = stamp;
+
+            let new_ids = match store.import_gltf_with(path, &self.pipeline) {
+                Ok(report) => {
+                    for warning in &report.warnings {
+                        tracing::warn!(path = %path.display(), %warning, "asset re-import warning");
+                    }
+                    report.ids
+                }
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "asset re-import failed");
+                    continue;
+                }
+            };
+            let before = events.len();
+            for old_id in &source.ids {
+                let Some(old) = store.get(*old_id) else {
+                    continue;
+                };
+                let matched = new_ids
+                    .iter()
+                    .copied()
+                    .find(|new_id| store.get(*new_id).is_some_and(|new| same_slot(old, new)));
+                if let Some(new_id) = matched.filter(|new_id| new_id != old_id) {
+                    // Labels are user data; carry them over to the new version.
+                    let labels = store
+                        .meta(*old_id)
+                        .map(|m| m.labels.clone())
+                        .unwrap_or_default();
+                    for label in labels {
+                        let _ = store.add_label(new_id, label);
+                    }
+                    events.push(AssetEvent::AssetReloaded {
+                        old_id: *old_id,
+                        new_id,
+                    });
+                }
+            }
+            tracing::info!(path = %path.display(), reloaded = events.len() - before, "asset source reloaded");
+            source.ids = new_ids;
+        }
+        events
+    }
+}
+
+/// True if `a` and `b` are the same kind of asset with the same name.
+fn same_slot(a: &Asset, b: &Asset) -> bool {
+    discriminant(a) == discriminant(b) && a.name() == b.name()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn material_gltf(color: f32) -> String {
+        format!(
+            r#"{{ "materials": [
+                {{ "name": "paint", "pbrMetallicRoughness": {{ "baseColorFactor": [{color}, 0, 0, 1] }} }},
+                {{ "name": "trim" }}
+            ] }}"#
+        )
+    }
+
+    #[test]
+    fn changed_source_emits_reloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paint.gltf");
+        std::fs::write(&path, material_gltf(0.5)).unwrap();
+
+        let mut store = AssetStore::new();
+        let mut watcher = AssetWatcher::new();
+        let old_ids = watcher.import(&mut store, &path).unwrap().ids;
+        store.add_label(old_ids[0], "car").unwrap();
+        assert!(watcher.poll(&mut store).is_empty());
+
+        std::fs::write(&path, material_gltf(0.25)).unwrap();
+        let events = watcher.poll(&mut store);
+        // Only the edited material changed content; "trim" keeps its ID.
+        assert_eq!(events.len(), 1);
+        let AssetEvent::AssetReloaded { old_id, new_id } = events[0] else {
+            panic!("expected a reload, got {:?}", events[0]);
+        };
+        assert_eq!(old_id, old_ids[0]);
+        assert_eq!(store.get_material(new_id).unwrap().base_color[0], 0.25);
+        let meta = store.meta(new_id).unwrap();
+        assert!(meta.labels.contains("car"));
+        assert_eq!(meta.source_path.as_deref(), Some(path.as_path()));
+        assert!(watcher.poll(&mut store).is_empty());
+    }
+
+    #[test]
+    fn failed_reimport_keeps_previous_assets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paint.gltf");
+        std::fs::write(&path, material_gltf(0.5)).unwrap();
+
+        let mut store = AssetStore::new();
+        let mut watcher = AssetWatcher::new();
+        watcher.import(&mut store, &path).unwrap();
+
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(watcher.poll(&mut store).is_empty());
+        assert_eq!(store.len(), 2);
+        assert!(watcher.unwatch(&path));
+        assert_eq!(watcher.paths().count(), 0);
+    }
+}
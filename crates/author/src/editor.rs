@@ -1,10 +1,11 @@
+use serde::{Deserialize, Serialize};
 use worldspace_common::{EntityId, Transform};
 use worldspace_kernel::World;
 
 /// An editing command that can be applied to the world and reversed.
 ///
 /// Each command carries enough context to undo itself.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EditCommand {
     /// Spawn an entity. Undo = despawn it.
     Spawn { id: EntityId, transform: Transform },
@@ -46,6 +47,14 @@ pub enum EditError {
     EntityNotFound(EntityId),
 }
 
+/// The undo and redo stacks of an `Editor`, oldest command first, as saved
+/// between sessions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EditHistory {
+    pub undo: Vec<EditCommand>,
+    pub redo: Vec<EditCommand>,
+}
+
 /// Editor with undo/redo support for non-destructive world authoring.
 ///
 /// Wraps a `World` and tracks all edit operations in undo/redo stacks.
@@ -64,6 +73,22 @@ impl Editor {
         }
     }
 
+    /// Create an editor that continues from a saved history.
+    pub fn from_history(history: EditHistory) -> Self {
+        Self {
+            undo_stack: history.undo,
+            redo_stack: history.redo,
+        }
+    }
+
+    /// A copy of the undo and redo stacks, for saving.
+    pub fn history(&self) -> EditHistory {
+        EditHistory {
+            undo: self.undo_stack.clone(),
+            redo: self.redo_stack.clone(),
+        }
+    }
+
     /// Spawn an entity and push to undo stack.
     pub fn spawn(&mut self, world: &mut World, transform: Transform) -> EntityId {
         let id = world.spawn(transform);
@@ -329,4 +354,25 @@ mod tests {
         editor.redo(&mut world);
         assert_eq!(world.state_hash(), hash_after_move);
     }
+
+    #[test]
+    fn history_round_trips_through_from_history() {
+        let mut world = World::new();
+        let mut editor = Editor::new();
+        let a = editor.spawn(&mut world, Transform::default());
+        let moved = Transform {
+            position: Vec3::X,
+            ..Transform::default()
+        };
+        editor.set_transform(&mut world, a, moved).unwrap();
+        editor.undo(&mut world);
+
+        let mut restored = Editor::from_history(editor.history());
+        assert_eq!((restored.undo_count(), restored.redo_count()), (1, 1));
+        assert!(restored.redo(&mut world));
+        assert_eq!(world.get(a).unwrap().transform.position, Vec3::X);
+        assert!(restored.undo(&mut world));
+        assert!(restored.undo(&mut world));
+        assert!(world.get(a).is_none());
+    }
 }
@@ -6,7 +6,7 @@
 
 mod editor;
 
-pub use editor::{EditCommand, EditError, Editor};
+pub use editor::{EditCommand, EditError, EditHistory, Editor};
 
 pub fn crate_info() -> &'static str {
     "worldspace-author v0.1.0"
@@ -0,0 +1,127 @@
+use glam::{Mat4, Vec3, Vec4};
+
+/// A camera's view volume: six inward-facing planes and eight corners.
+///
+/// Built from a view-projection matrix with a 0..1 depth range (as produced
+/// by `Mat4::perspective_rh`), so streaming and culling test against the
+/// volume the camera actually sees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    /// Left, right, bottom, top, near, far. `xyz` is the unit normal
+    /// pointing into the volume and `w` the offset, so a point `p` is inside
+    /// a plane when `normal.dot(p) + w >= 0`.
+    planes: [Vec4; 6],
+    /// Near corners then far corners, in world space.
+    corners: [Vec3; 8],
+}
+
+impl Frustum {
+    /// Extract the frustum of a view-projection matrix.
+    pub fn from_view_projection(view_projection: Mat4) -> Self {
+        let m = view_projection;
+        let (r0, r1, r2, r3) = (m.row(0), m.row(1), m.row(2), m.row(3));
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2].map(|plane| {
+            let length = plane.truncate().length();
+            if length > 0.0 { plane / length } else { plane }
+        });
+        let inverse = m.inverse();
+        let mut corners = [Vec3::ZERO; 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let ndc = Vec3::new(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { 0.0 } else { 1.0 },
+            );
+            *corner = inverse.project_point3(ndc);
+        }
+        Self { planes, corners }
+    }
+
+    /// The six planes: left, right, bottom, top, near, far.
+    pub fn planes(&self) -> &[Vec4; 6] {
+        &self.planes
+    }
+
+    /// The eight corners, near plane first.
+    pub fn corners(&self) -> &[Vec3; 8] {
+        &self.corners
+    }
+
+    /// Axis-aligned bounds of the volume as `(min, max)`.
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        self.corners
+            .iter()
+            .fold((self.corners[0], self.corners[0]), |(min, max), c| {
+                (min.min(*c), max.max(*c))
+            })
+    }
+
+    /// True if `point` is inside the volume or on its boundary.
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(point) + plane.w >= 0.0)
+    }
+
+    /// True unless the box from `min` to `max` lies entirely outside one of
+    /// the planes. Conservative: a box near an edge of the volume may pass
+    /// without touching it, as is usual for culling.
+    pub fn intersects_aabb(&self, min: Vec3, max: Vec3) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            // The box corner furthest along the plane normal.
+            let farthest = Vec3::new(
+                if normal.x >= 0.0 { max.x } else { min.x },
+                if normal.y >= 0.0 { max.y } else { min.y },
+                if normal.z >= 0.0 { max.z } else { min.z },
+            );
+            normal.dot(farthest) + plane.w >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn looking_down_neg_z() -> Frustum {
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y);
+        let projection = Mat4::perspective_rh(90f32.to_radians(), 1.0, 1.0, 100.0);
+        Frustum::from_view_projection(projection * view)
+    }
+
+    #[test]
+    fn contains_points_in_view() {
+        let frustum = looking_down_neg_z();
+        assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -10.0)));
+        assert!(frustum.contains_point(Vec3::new(9.0, -9.0, -10.0)));
+        assert!(!frustum.contains_point(Vec3::new(11.0, 0.0, -10.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 10.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -0.5)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -150.0)));
+    }
+
+    #[test]
+    fn corners_bound_the_volume() {
+        let frustum = looking_down_neg_z();
+        let (min, max) = frustum.bounds();
+        assert!((min - Vec3::new(-100.0, -100.0, -100.0)).length() < 1e-2);
+        assert!((max - Vec3::new(100.0, 100.0, -1.0)).length() < 1e-2);
+        for corner in frustum.corners() {
+            assert!(frustum.contains_point(*corner * 0.999 + Vec3::new(0.0, 0.0, -0.01)));
+        }
+    }
+
+    #[test]
+    fn boxes_outside_a_plane_are_rejected() {
+        let frustum = looking_down_neg_z();
+        let unit = Vec3::splat(1.0);
+        assert!(frustum.intersects_aabb(Vec3::new(-1.0, -1.0, -20.0), Vec3::new(1.0, 1.0, -18.0)));
+        // Straddling the right plane.
+        assert!(frustum.intersects_aabb(Vec3::new(9.0, 0.0, -11.0), Vec3::new(12.0, 1.0, -9.0)));
+        assert!(
+            !frustum.intersects_aabb(Vec3::new(5.0, 0.0, 5.0), Vec3::new(5.0, 0.0, 5.0) + unit)
+        );
+        assert!(!frustum.intersects_aabb(Vec3::new(0.0, 0.0, -300.0), Vec3::new(1.0, 1.0, -200.0)));
+    }
+}
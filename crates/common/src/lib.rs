@@ -1,5 +1,7 @@
 //! Shared types and utilities for the worldspace engine.
 
+pub mod frustum;
 pub mod types;
 
+pub use frustum::Frustum;
 pub use types::{EntityId, Transform};
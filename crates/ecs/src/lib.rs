@@ -8,9 +8,26 @@
 //! - Iteration order is deterministic (BTreeMap).
 //! - Component storage is independent of entity creation order.
 
+use glam::{Quat, Vec3};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use worldspace_assets::{AssetId, AssetStore};
 use worldspace_common::EntityId;
+use worldspace_kernel::{TICK_SECONDS, World};
+
+use sparse::SparseTag;
+
+mod plugin;
+mod reflect;
+mod scene;
+mod sparse;
+mod validate;
+
+pub use plugin::{EcsPlugin, EventHandler, PluginError, PluginHost, System};
+pub use reflect::{ComponentInfo, ComponentRegistry, FieldInfo, FieldType, Reflect, UiHint};
+pub use scene::SceneImport;
+pub use sparse::SparseSet;
+pub use validate::{Validate, Validation, Validator, Validators};
 
 /// A handle referencing a mesh asset.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -20,6 +37,10 @@ pub struct MeshHandle(pub u64);
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct MaterialHandle(pub u64);
 
+/// A handle referencing a texture asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TextureHandle(pub u64);
+
 /// Human-readable name component.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Name(pub String);
@@ -62,6 +83,282 @@ impl Default for Collider {
     }
 }
 
+/// Script component: references a registered script asset for the scripting host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Script {
+    pub asset: AssetId,
+    pub enabled: bool,
+}
+
+/// Audio source component: places a registered audio asset on an entity.
+///
+/// No audio backend consumes this yet; it lets authored scenes describe sound
+/// placement ahead of one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AudioSource {
+    pub asset: AssetId,
+    pub looping: bool,
+    /// Linear gain, 1.0 = unchanged.
+    pub gain: f32,
+}
+
+/// Animator component: plays a registered `AnimationClip` on the entity's
+/// transform.
+///
+/// `time` advances by `speed` seconds per second of simulation while
+/// `playing`, wrapping at the clip duration.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Animator {
+    pub clip: AssetId,
+    /// Playback position in seconds.
+    pub time: f32,
+    /// Playback rate, 1.0 = authored speed.
+    pub speed: f32,
+    pub playing: bool,
+}
+
+impl Animator {
+    /// An animator for `clip`, playing from the start at authored speed.
+    pub fn new(clip: AssetId) -> Self {
+        Self {
+            clip,
+            time: 0.0,
+            speed: 1.0,
+            playing: true,
+        }
+    }
+}
+
+/// Sprite component: draws the entity as a camera-facing quad showing a
+/// region of a texture atlas, for markers, light icons and particle-like
+/// effects. An entity with a Sprite is drawn as the sprite instead of its
+/// mesh.
+///
+/// The quad is centered on the entity's position and scaled by its
+/// transform's x and y scale; it always faces the camera, so the entity's
+/// rotation is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sprite {
+    /// Atlas texture.
+    pub texture: TextureHandle,
+    /// Atlas region as `[u_min, v_min, u_max, v_max]` in normalized texture
+    /// coordinates, v pointing down.
+    pub uv_rect: [f32; 4],
+    /// World-space width and height of the quad.
+    pub size: [f32; 2],
+    /// Multiplied with the texture color; alpha blends the sprite.
+    pub color: [f32; 4],
+}
+
+impl Sprite {
+    /// A white, one unit sprite showing all of `texture`.
+    pub fn new(texture: TextureHandle) -> Self {
+        Self {
+            texture,
+            uv_rect: [0.0, 0.0, 1.0, 1.0],
+            size: [1.0, 1.0],
+            color: [1.0; 4],
+        }
+    }
+
+    /// A sprite showing cell `index` of an atlas laid out as a grid of
+    /// `columns` x `rows` equal cells, numbered row by row from the top-left.
+    pub fn from_atlas(texture: TextureHandle, columns: u32, rows: u32, index: u32) -> Self {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        let (column, row) = (index % columns, (index / columns).min(rows - 1));
+        let (width, height) = (1.0 / columns as f32, 1.0 / rows as f32);
+        Self {
+            uv_rect: [
+                column as f32 * width,
+                row as f32 * height,
+                (column + 1) as f32 * width,
+                (row + 1) as f32 * height,
+            ],
+            ..Self::new(texture)
+        }
+    }
+}
+
+/// Kind of light source.
+///
+/// Directional and spot lights shine along the entity's local -Z axis; point
+/// and spot lights are positioned at the entity's transform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LightKind {
+    Directional,
+    Point,
+    Spot,
+}
+
+/// Light component consumed by the renderer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    /// Distance at which point and spot lights fade out; within it they fall
+    /// off with the inverse square of distance. Ignored for directional lights.
+    pub range: f32,
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Self {
+            kind: LightKind::Directional,
+            color: [1.0, 1.0, 1.0],
+            intensity: 1.0,
+            range: 10.0,
+        }
+    }
+}
+
+/// Velocity component: integrated into the entity transform each tick.
+///
+/// `angular` is an axis scaled by radians per second.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Velocity {
+    pub linear: Vec3,
+    pub angular: Vec3,
+}
+
+/// Layer membership bitmask: bit `n` set means the entity is on layer `n`.
+///
+/// Entities without a Layer component are on `Layer::DEFAULT`. Streaming,
+/// rendering, and selection filter entities by intersecting with a query mask.
+/// Bit 31 is `Layer::EDITOR`, for editor-only content that play cameras and
+/// screenshots leave out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Layer(pub u32);
+
+impl Layer {
+    pub const NONE: Layer = Layer(0);
+    pub const DEFAULT: Layer = Layer(1);
+    pub const ALL: Layer = Layer(u32::MAX);
+    /// Editor-only content: gizmos, the grid, debug draws.
+    pub const EDITOR: Layer = Layer(1 << 31);
+    /// Every layer but `EDITOR`, for in-world cameras.
+    pub const PLAY: Layer = Layer(!Self::EDITOR.0);
+
+    /// Mask containing only layer `index` (0..32).
+    pub fn single(index: u8) -> Self {
+        assert!(index < 32, "layer index out of range");
+        Layer(1 << index)
+    }
+
+    /// True if this mask shares any layer with `other`.
+    pub fn intersects(self, other: Layer) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn union(self, other: Layer) -> Layer {
+        Layer(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: Layer) -> Layer {
+        Layer(self.0 & other.0)
+    }
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Visibility component: `Visible(false)` hides an entity from rendering
+/// without despawning it. Entities without the component are visible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Visible(pub bool);
+
+impl Default for Visible {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Parent component: attaches an entity under another in a hierarchy, e.g.
+/// the node tree of an imported glTF scene.
+///
+/// Transforms are not propagated through parents yet; each entity's world
+/// transform is authoritative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Parent(pub EntityId);
+
+/// Named layers (e.g. "gizmos", "background", "gameplay") mapped to layer bits.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LayerNames {
+    names: BTreeMap<String, u8>,
+}
+
+impl LayerNames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the layer for `name`, assigning the next free bit if it is new.
+    /// Bit 0 is reserved for `Layer::DEFAULT` and bit 31 for `Layer::EDITOR`.
+    /// Returns None once all 30 named layers are taken.
+    pub fn define(&mut self, name: &str) -> Option<Layer> {
+        if let Some(index) = self.names.get(name) {
+            return Some(Layer::single(*index));
+        }
+        let index = (1..31).find(|i| !self.names.values().any(|used| used == i))?;
+        self.names.insert(name.to_string(), index);
+        Some(Layer::single(index))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Layer> {
+        self.names.get(name).map(|index| Layer::single(*index))
+    }
+
+    /// Combined mask of the given layer names; unknown names are ignored.
+    pub fn mask<'a>(&self, names: impl IntoIterator<Item = &'a str>) -> Layer {
+        names
+            .into_iter()
+            .filter_map(|name| self.get(name))
+            .fold(Layer::NONE, Layer::union)
+    }
+}
+
+/// Zero-sized marker components. Each tag's members are stored as a set,
+/// of the kind `Tag::storage` selects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Tag {
+    /// Entity is not expected to move.
+    Static,
+    /// Entity is skipped by renderers.
+    Hidden,
+    /// Entity is left out of snapshots and cells persisted with the component
+    /// store (e.g. `WorldStore::take_snapshot_with_components`).
+    NoSerialize,
+    /// Entity is highlighted as selected in an editor.
+    Selected,
+    /// Entity is in a streamed cell that is currently loaded.
+    Resident,
+}
+
+/// How a tag's members are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagStorage {
+    /// A BTreeSet that is serialized with the store; each change emits its
+    /// event as it happens.
+    Ordered,
+    /// A `SparseSet` with O(1) add and remove, for tags that churn every
+    /// frame. Membership is transient and not serialized, and changes are
+    /// coalesced per entity and emitted by `drain_events` in EntityId order.
+    Sparse,
+}
+
+impl Tag {
+    /// The storage the tag's members are kept in.
+    pub fn storage(self) -> TagStorage {
+        match self {
+            Tag::Static | Tag::Hidden | Tag::NoSerialize => TagStorage::Ordered,
+            Tag::Selected | Tag::Resident => TagStorage::Sparse,
+        }
+    }
+}
+
 /// Events produced by component mutations.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ComponentEvent {
@@ -73,21 +370,91 @@ pub enum ComponentEvent {
     RenderableUpdated { entity: EntityId, old: Renderable, new: Renderable },
     RigidBodyAdded { entity: EntityId, body: RigidBody },
     RigidBodyRemoved { entity: EntityId, body: RigidBody },
+    RigidBodyUpdated { entity: EntityId, old: RigidBody, new: RigidBody },
     ColliderAdded { entity: EntityId, collider: Collider },
     ColliderRemoved { entity: EntityId, collider: Collider },
+    ColliderUpdated { entity: EntityId, old: Collider, new: Collider },
+    ScriptAdded { entity: EntityId, script: Script },
+    ScriptRemoved { entity: EntityId, script: Script },
+    ScriptUpdated { entity: EntityId, old: Script, new: Script },
+    AudioSourceAdded { entity: EntityId, source: AudioSource },
+    AudioSourceRemoved { entity: EntityId, source: AudioSource },
+    AudioSourceUpdated { entity: EntityId, old: AudioSource, new: AudioSource },
+    AnimatorAdded { entity: EntityId, animator: Animator },
+    AnimatorRemoved { entity: EntityId, animator: Animator },
+    AnimatorUpdated { entity: EntityId, old: Animator, new: Animator },
+    SpriteAdded { entity: EntityId, sprite: Sprite },
+    SpriteRemoved { entity: EntityId, sprite: Sprite },
+    SpriteUpdated { entity: EntityId, old: Sprite, new: Sprite },
+    LightAdded { entity: EntityId, light: Light },
+    LightRemoved { entity: EntityId, light: Light },
+    LightUpdated { entity: EntityId, old: Light, new: Light },
+    VelocityAdded { entity: EntityId, velocity: Velocity },
+    VelocityRemoved { entity: EntityId, velocity: Velocity },
+    VelocityUpdated { entity: EntityId, old: Velocity, new: Velocity },
+    LayerAdded { entity: EntityId, layer: Layer },
+    LayerRemoved { entity: EntityId, layer: Layer },
+    LayerUpdated { entity: EntityId, old: Layer, new: Layer },
+    VisibleAdded { entity: EntityId, visible: Visible },
+    VisibleRemoved { entity: EntityId, visible: Visible },
+    VisibleUpdated { entity: EntityId, old: Visible, new: Visible },
+    ParentAdded { entity: EntityId, parent: Parent },
+    ParentRemoved { entity: EntityId, parent: Parent },
+    ParentUpdated { entity: EntityId, old: Parent, new: Parent },
+    TagAdded { entity: EntityId, tag: Tag },
+    TagRemoved { entity: EntityId, tag: Tag },
+    /// Several mutations recorded as one event by a batch operation.
+    Batch(Vec<ComponentEvent>),
+    /// A validator clamped (`clamped: true`) or rejected a component value.
+    ValidationFailed {
+        entity: EntityId,
+        component: String,
+        reason: String,
+        clamped: bool,
+    },
+}
+
+impl ComponentEvent {
+    /// True for changes to sparse-storage tags. Their membership is not
+    /// serialized with the store, so persisted component logs leave these
+    /// events out too.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ComponentEvent::TagAdded { tag, .. } | ComponentEvent::TagRemoved { tag, .. }
+                if tag.storage() == TagStorage::Sparse
+        )
+    }
 }
 
 /// Deterministic component storage for all component types.
 ///
 /// Uses BTreeMap for canonical iteration order. All mutations produce events.
+/// Component maps missing from serialized data deserialize empty, so
+/// component snapshots written before a component type existed still load.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ComponentStore {
     names: BTreeMap<EntityId, Name>,
     renderables: BTreeMap<EntityId, Renderable>,
     rigid_bodies: BTreeMap<EntityId, RigidBody>,
     colliders: BTreeMap<EntityId, Collider>,
+    scripts: BTreeMap<EntityId, Script>,
+    audio_sources: BTreeMap<EntityId, AudioSource>,
+    animators: BTreeMap<EntityId, Animator>,
+    sprites: BTreeMap<EntityId, Sprite>,
+    lights: BTreeMap<EntityId, Light>,
+    velocities: BTreeMap<EntityId, Velocity>,
+    layers: BTreeMap<EntityId, Layer>,
+    visibility: BTreeMap<EntityId, Visible>,
+    parents: BTreeMap<EntityId, Parent>,
+    tags: BTreeMap<Tag, BTreeSet<EntityId>>,
+    #[serde(skip)]
+    sparse_tags: BTreeMap<Tag, SparseTag>,
     #[serde(skip)]
     events: Vec<ComponentEvent>,
+    #[serde(skip)]
+    validators: Validators,
 }
 
 impl ComponentStore {
@@ -95,16 +462,139 @@ impl ComponentStore {
         Self::default()
     }
 
-    /// Drain and return all pending component events.
+    /// Drain and return all pending component events, followed by the net
+    /// changes to sparse-storage tags, by tag and then in EntityId order.
     pub fn drain_events(&mut self) -> Vec<ComponentEvent> {
-        std::mem::take(&mut self.events)
+        let mut events = std::mem::take(&mut self.events);
+        for (tag, members) in &mut self.sparse_tags {
+            events.extend(members.drain_changes().into_iter().map(|(entity, added)| {
+                if added {
+                    ComponentEvent::TagAdded { entity, tag: *tag }
+                } else {
+                    ComponentEvent::TagRemoved { entity, tag: *tag }
+                }
+            }));
+        }
+        events
     }
 
-    /// Read-only access to pending events.
+    /// Read-only access to pending events. Changes to sparse-storage tags
+    /// only appear once drained.
     pub fn events(&self) -> &[ComponentEvent] {
         &self.events
     }
 
+    /// Register a validator run before values of type `T` are stored.
+    pub fn add_validator<T: Validate>(
+        &mut self,
+        f: impl Fn(&T) -> Validation<T> + Send + Sync + 'static,
+    ) {
+        self.validators.add(f);
+    }
+
+    /// Replace all validators, e.g. with `Validators::standard()`.
+    pub fn set_validators(&mut self, validators: Validators) {
+        self.validators = validators;
+    }
+
+    pub fn validators(&self) -> &Validators {
+        &self.validators
+    }
+
+    /// Run the validators for `T`. Returns the value to store, or None if it
+    /// was rejected. Clamps and rejections emit `ValidationFailed`.
+    fn validate<T: Validate>(&mut self, entity: EntityId, mut value: T) -> Option<T> {
+        for validator in T::validators(&self.validators) {
+            match validator(&value) {
+                Validation::Accept => {}
+                Validation::Clamp(clamped, reason) => {
+                    self.events.push(ComponentEvent::ValidationFailed {
+                        entity,
+                        component: T::NAME.to_string(),
+                        reason,
+                        clamped: true,
+                    });
+                    value = clamped;
+                }
+                Validation::Reject(reason) => {
+                    tracing::warn!(%reason, component = T::NAME, "component rejected");
+                    self.events.push(ComponentEvent::ValidationFailed {
+                        entity,
+                        component: T::NAME.to_string(),
+                        reason,
+                        clamped: false,
+                    });
+                    return None;
+                }
+            }
+        }
+        Some(value)
+    }
+
+    /// Run `f` and record every event it produces as a single `Batch` event.
+    ///
+    /// Bulk operations such as scene import use this so listeners see one
+    /// event instead of one per component. Nothing is recorded if `f` makes no
+    /// changes.
+    pub fn batch(&mut self, f: impl FnOnce(&mut Self)) {
+        let outer = std::mem::take(&mut self.events);
+        f(self);
+        let inner = std::mem::replace(&mut self.events, outer);
+        if !inner.is_empty() {
+            self.events.push(ComponentEvent::Batch(inner));
+        }
+    }
+
+    pub fn set_names_batch(&mut self, names: impl IntoIterator<Item = (EntityId, String)>) {
+        self.batch(|store| {
+            for (entity, name) in names {
+                store.set_name(entity, name);
+            }
+        });
+    }
+
+    pub fn set_renderables_batch(
+        &mut self,
+        renderables: impl IntoIterator<Item = (EntityId, Renderable)>,
+    ) {
+        self.batch(|store| {
+            for (entity, renderable) in renderables {
+                store.set_renderable(entity, renderable);
+            }
+        });
+    }
+
+    pub fn set_rigid_bodies_batch(
+        &mut self,
+        bodies: impl IntoIterator<Item = (EntityId, RigidBody)>,
+    ) {
+        self.batch(|store| {
+            for (entity, body) in bodies {
+                store.set_rigid_body(entity, body);
+            }
+        });
+    }
+
+    pub fn set_colliders_batch(
+        &mut self,
+        colliders: impl IntoIterator<Item = (EntityId, Collider)>,
+    ) {
+        self.batch(|store| {
+            for (entity, collider) in colliders {
+                store.set_collider(entity, collider);
+            }
+        });
+    }
+
+    /// Remove all components of several entities as one batched event.
+    pub fn remove_entities_batch(&mut self, entities: impl IntoIterator<Item = EntityId>) {
+        self.batch(|store| {
+            for entity in entities {
+                store.remove_entity(entity);
+            }
+        });
+    }
+
     // --- Name ---
     pub fn set_name(&mut self, entity: EntityId, name: String) {
         if let Some(old) = self.names.get(&entity) {
@@ -179,10 +669,19 @@ impl ComponentStore {
 
     // --- RigidBody ---
     pub fn set_rigid_body(&mut self, entity: EntityId, body: RigidBody) {
-        self.events.push(ComponentEvent::RigidBodyAdded {
-            entity,
-            body,
-        });
+        let Some(body) = self.validate(entity, body) else {
+            return;
+        };
+        if let Some(old) = self.rigid_bodies.get(&entity) {
+            self.events.push(ComponentEvent::RigidBodyUpdated {
+                entity,
+                old: *old,
+                new: body,
+            });
+        } else {
+            self.events
+                .push(ComponentEvent::RigidBodyAdded { entity, body });
+        }
         self.rigid_bodies.insert(entity, body);
     }
 
@@ -198,12 +697,25 @@ impl ComponentStore {
         self.rigid_bodies.get(&entity)
     }
 
+    pub fn rigid_bodies(&self) -> &BTreeMap<EntityId, RigidBody> {
+        &self.rigid_bodies
+    }
+
     // --- Collider ---
     pub fn set_collider(&mut self, entity: EntityId, collider: Collider) {
-        self.events.push(ComponentEvent::ColliderAdded {
-            entity,
-            collider,
-        });
+        let Some(collider) = self.validate(entity, collider) else {
+            return;
+        };
+        if let Some(old) = self.colliders.get(&entity) {
+            self.events.push(ComponentEvent::ColliderUpdated {
+                entity,
+                old: *old,
+                new: collider,
+            });
+        } else {
+            self.events
+                .push(ComponentEvent::ColliderAdded { entity, collider });
+        }
         self.colliders.insert(entity, collider);
     }
 
@@ -219,117 +731,1376 @@ impl ComponentStore {
         self.colliders.get(&entity)
     }
 
-    /// Remove all components for an entity.
-    pub fn remove_entity(&mut self, entity: EntityId) {
-        self.remove_name(entity);
-        self.remove_renderable(entity);
-        self.remove_rigid_body(entity);
-        self.remove_collider(entity);
+    pub fn colliders(&self) -> &BTreeMap<EntityId, Collider> {
+        &self.colliders
     }
 
-    /// Replay a component event (for undo/redo or persistence replay).
-    pub fn apply_event(&mut self, event: &ComponentEvent) {
-        match event {
-            ComponentEvent::NameAdded { entity, name } => {
-                self.names.insert(*entity, Name(name.clone()));
-            }
-            ComponentEvent::NameRemoved { entity, .. } => {
-                self.names.remove(entity);
-            }
-            ComponentEvent::NameUpdated { entity, new, .. } => {
-                self.names.insert(*entity, Name(new.clone()));
-            }
-            ComponentEvent::RenderableAdded { entity, renderable } => {
-                self.renderables.insert(*entity, *renderable);
-            }
-            ComponentEvent::RenderableRemoved { entity, .. } => {
-                self.renderables.remove(entity);
-            }
-            ComponentEvent::RenderableUpdated { entity, new, .. } => {
-                self.renderables.insert(*entity, *new);
-            }
-            ComponentEvent::RigidBodyAdded { entity, body } => {
-                self.rigid_bodies.insert(*entity, *body);
-            }
-            ComponentEvent::RigidBodyRemoved { entity, .. } => {
-                self.rigid_bodies.remove(entity);
-            }
-            ComponentEvent::ColliderAdded { entity, collider } => {
-                self.colliders.insert(*entity, *collider);
-            }
-            ComponentEvent::ColliderRemoved { entity, .. } => {
-                self.colliders.remove(entity);
-            }
+    // --- Script ---
+    pub fn set_script(&mut self, entity: EntityId, script: Script) {
+        if let Some(old) = self.scripts.get(&entity) {
+            self.events.push(ComponentEvent::ScriptUpdated {
+                entity,
+                old: *old,
+                new: script,
+            });
+        } else {
+            self.events
+                .push(ComponentEvent::ScriptAdded { entity, script });
         }
+        self.scripts.insert(entity, script);
     }
-}
 
-pub fn crate_info() -> &'static str {
-    "worldspace-ecs v0.1.0"
-}
+    pub fn remove_script(&mut self, entity: EntityId) -> Option<Script> {
+        let removed = self.scripts.remove(&entity);
+        if let Some(script) = removed {
+            self.events
+                .push(ComponentEvent::ScriptRemoved { entity, script });
+        }
+        removed
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    pub fn get_script(&self, entity: EntityId) -> Option<&Script> {
+        self.scripts.get(&entity)
+    }
 
-    #[test]
-    fn name_add_remove() {
-        let mut store = ComponentStore::new();
-        let id = EntityId::new();
-        store.set_name(id, "Test".into());
-        assert_eq!(store.get_name(id).unwrap().0, "Test");
+    pub fn scripts(&self) -> &BTreeMap<EntityId, Script> {
+        &self.scripts
+    }
 
-        store.remove_name(id);
-        assert!(store.get_name(id).is_none());
-        assert_eq!(store.events().len(), 2);
+    // --- AudioSource ---
+    pub fn set_audio_source(&mut self, entity: EntityId, source: AudioSource) {
+        let Some(source) = self.validate(entity, source) else {
+            return;
+        };
+        if let Some(old) = self.audio_sources.get(&entity) {
+            self.events.push(ComponentEvent::AudioSourceUpdated {
+                entity,
+                old: *old,
+                new: source,
+            });
+        } else {
+            self.events
+                .push(ComponentEvent::AudioSourceAdded { entity, source });
+        }
+        self.audio_sources.insert(entity, source);
     }
 
-    #[test]
-    fn name_update_produces_event() {
-        let mut store = ComponentStore::new();
-        let id = EntityId::new();
-        store.set_name(id, "First".into());
-        store.set_name(id, "Second".into());
-        assert_eq!(store.get_name(id).unwrap().0, "Second");
-        // Add + Update
-        assert_eq!(store.events().len(), 2);
+    pub fn remove_audio_source(&mut self, entity: EntityId) -> Option<AudioSource> {
+        let removed = self.audio_sources.remove(&entity);
+        if let Some(source) = removed {
+            self.events
+                .push(ComponentEvent::AudioSourceRemoved { entity, source });
+        }
+        removed
     }
 
-    #[test]
-    fn renderable_add_remove() {
-        let mut store = ComponentStore::new();
-        let id = EntityId::new();
-        let r = Renderable {
-            mesh: MeshHandle(1),
-            material: MaterialHandle(2),
-        };
-        store.set_renderable(id, r);
-        assert_eq!(store.get_renderable(id), Some(&r));
+    pub fn get_audio_source(&self, entity: EntityId) -> Option<&AudioSource> {
+        self.audio_sources.get(&entity)
+    }
 
-        store.remove_renderable(id);
-        assert!(store.get_renderable(id).is_none());
+    pub fn audio_sources(&self) -> &BTreeMap<EntityId, AudioSource> {
+        &self.audio_sources
     }
 
-    #[test]
-    fn remove_entity_clears_all() {
-        let mut store = ComponentStore::new();
-        let id = EntityId::new();
-        store.set_name(id, "Test".into());
-        store.set_renderable(
-            id,
-            Renderable {
-                mesh: MeshHandle(0),
+    // --- Animator ---
+    pub fn set_animator(&mut self, entity: EntityId, animator: Animator) {
+        let Some(animator) = self.validate(entity, animator) else {
+            return;
+        };
+        if let Some(old) = self.animators.get(&entity) {
+            self.events.push(ComponentEvent::AnimatorUpdated {
+                entity,
+                old: *old,
+                new: animator,
+            });
+        } else {
+            self.events
+                .push(ComponentEvent::AnimatorAdded { entity, animator });
+        }
+        self.animators.insert(entity, animator);
+    }
+
+    pub fn remove_animator(&mut self, entity: EntityId) -> Option<Animator> {
+        let removed = self.animators.remove(&entity);
+        if let Some(animator) = removed {
+            self.events
+                .push(ComponentEvent::AnimatorRemoved { entity, animator });
+        }
+        removed
+    }
+
+    pub fn get_animator(&self, entity: EntityId) -> Option<&Animator> {
+        self.animators.get(&entity)
+    }
+
+    pub fn animators(&self) -> &BTreeMap<EntityId, Animator> {
+        &self.animators
+    }
+
+    // --- Sprite ---
+    pub fn set_sprite(&mut self, entity: EntityId, sprite: Sprite) {
+        let Some(sprite) = self.validate(entity, sprite) else {
+            return;
+        };
+        if let Some(old) = self.sprites.get(&entity) {
+            self.events.push(ComponentEvent::SpriteUpdated {
+                entity,
+                old: *old,
+                new: sprite,
+            });
+        } else {
+            self.events
+                .push(ComponentEvent::SpriteAdded { entity, sprite });
+        }
+        self.sprites.insert(entity, sprite);
+    }
+
+    pub fn remove_sprite(&mut self, entity: EntityId) -> Option<Sprite> {
+        let removed = self.sprites.remove(&entity);
+        if let Some(sprite) = removed {
+            self.events
+                .push(ComponentEvent::SpriteRemoved { entity, sprite });
+        }
+        removed
+    }
+
+    pub fn get_sprite(&self, entity: EntityId) -> Option<&Sprite> {
+        self.sprites.get(&entity)
+    }
+
+    pub fn sprites(&self) -> &BTreeMap<EntityId, Sprite> {
+        &self.sprites
+    }
+
+    // --- Light ---
+    pub fn set_light(&mut self, entity: EntityId, light: Light) {
+        let Some(light) = self.validate(entity, light) else {
+            return;
+        };
+        if let Some(old) = self.lights.get(&entity) {
+            self.events.push(ComponentEvent::LightUpdated {
+                entity,
+                old: *old,
+                new: light,
+            });
+        } else {
+            self.events
+                .push(ComponentEvent::LightAdded { entity, light });
+        }
+        self.lights.insert(entity, light);
+    }
+
+    pub fn remove_light(&mut self, entity: EntityId) -> Option<Light> {
+        let removed = self.lights.remove(&entity);
+        if let Some(light) = removed {
+            self.events
+                .push(ComponentEvent::LightRemoved { entity, light });
+        }
+        removed
+    }
+
+    pub fn get_light(&self, entity: EntityId) -> Option<&Light> {
+        self.lights.get(&entity)
+    }
+
+    pub fn lights(&self) -> &BTreeMap<EntityId, Light> {
+        &self.lights
+    }
+
+    // --- Velocity ---
+    pub fn set_velocity(&mut self, entity: EntityId, velocity: Velocity) {
+        let Some(velocity) = self.validate(entity, velocity) else {
+            return;
+        };
+        if let Some(old) = self.velocities.get(&entity) {
+            self.events.push(ComponentEvent::VelocityUpdated {
+                entity,
+                old: *old,
+                new: velocity,
+            });
+        } else {
+            self.events
+                .push(ComponentEvent::VelocityAdded { entity, velocity });
+        }
+        self.velocities.insert(entity, velocity);
+    }
+
+    pub fn remove_velocity(&mut self, entity: EntityId) -> Option<Velocity> {
+        let removed = self.velocities.remove(&entity);
+        if let Some(velocity) = removed {
+            self.events
+                .push(ComponentEvent::VelocityRemoved { entity, velocity });
+        }
+        removed
+    }
+
+    pub fn get_velocity(&self, entity: EntityId) -> Option<&Velocity> {
+        self.velocities.get(&entity)
+    }
+
+    pub fn velocities(&self) -> &BTreeMap<EntityId, Velocity> {
+        &self.velocities
+    }
+
+    // --- Layer ---
+    pub fn set_layer(&mut self, entity: EntityId, layer: Layer) {
+        if let Some(old) = self.layers.get(&entity) {
+            self.events.push(ComponentEvent::LayerUpdated {
+                entity,
+                old: *old,
+                new: layer,
+            });
+        } else {
+            self.events
+                .push(ComponentEvent::LayerAdded { entity, layer });
+        }
+        self.layers.insert(entity, layer);
+    }
+
+    pub fn remove_layer(&mut self, entity: EntityId) -> Option<Layer> {
+        let removed = self.layers.remove(&entity);
+        if let Some(layer) = removed {
+            self.events
+                .push(ComponentEvent::LayerRemoved { entity, layer });
+        }
+        removed
+    }
+
+    pub fn get_layer(&self, entity: EntityId) -> Option<&Layer> {
+        self.layers.get(&entity)
+    }
+
+    pub fn layers(&self) -> &BTreeMap<EntityId, Layer> {
+        &self.layers
+    }
+
+    /// The entity's layers, or `Layer::DEFAULT` if it has no Layer component.
+    pub fn layer_of(&self, entity: EntityId) -> Layer {
+        self.layers.get(&entity).copied().unwrap_or_default()
+    }
+
+    /// True if the entity is on any layer in `mask`.
+    pub fn in_layers(&self, entity: EntityId, mask: Layer) -> bool {
+        self.layer_of(entity).intersects(mask)
+    }
+
+    /// Entities with a Layer component on any layer in `mask`, in EntityId order.
+    /// Entities without a Layer component are not included; use `in_layers`
+    /// to test those against the default layer.
+    pub fn with_layers(&self, mask: Layer) -> impl Iterator<Item = EntityId> + '_ {
+        self.layers
+            .iter()
+            .filter(move |(_, layer)| layer.intersects(mask))
+            .map(|(id, _)| *id)
+    }
+
+    // --- Visible ---
+    pub fn set_visible(&mut self, entity: EntityId, visible: Visible) {
+        if let Some(old) = self.visibility.get(&entity) {
+            self.events.push(ComponentEvent::VisibleUpdated {
+                entity,
+                old: *old,
+                new: visible,
+            });
+        } else {
+            self.events
+                .push(ComponentEvent::VisibleAdded { entity, visible });
+        }
+        self.visibility.insert(entity, visible);
+    }
+
+    pub fn remove_visible(&mut self, entity: EntityId) -> Option<Visible> {
+        let removed = self.visibility.remove(&entity);
+        if let Some(visible) = removed {
+            self.events
+                .push(ComponentEvent::VisibleRemoved { entity, visible });
+        }
+        removed
+    }
+
+    pub fn get_visible(&self, entity: EntityId) -> Option<&Visible> {
+        self.visibility.get(&entity)
+    }
+
+    pub fn visibility(&self) -> &BTreeMap<EntityId, Visible> {
+        &self.visibility
+    }
+
+    /// False only if the entity has `Visible(false)`.
+    pub fn is_visible(&self, entity: EntityId) -> bool {
+        self.visibility.get(&entity).copied().unwrap_or_default().0
+    }
+
+    // --- Parent ---
+    pub fn set_parent(&mut self, entity: EntityId, parent: Parent) {
+        if let Some(old) = self.parents.get(&entity) {
+            self.events.push(ComponentEvent::ParentUpdated {
+                entity,
+                old: *old,
+                new: parent,
+            });
+        } else {
+            self.events
+                .push(ComponentEvent::ParentAdded { entity, parent });
+        }
+        self.parents.insert(entity, parent);
+    }
+
+    pub fn remove_parent(&mut self, entity: EntityId) -> Option<Parent> {
+        let removed = self.parents.remove(&entity);
+        if let Some(parent) = removed {
+            self.events
+                .push(ComponentEvent::ParentRemoved { entity, parent });
+        }
+        removed
+    }
+
+    pub fn get_parent(&self, entity: EntityId) -> Option<&Parent> {
+        self.parents.get(&entity)
+    }
+
+    pub fn parents(&self) -> &BTreeMap<EntityId, Parent> {
+        &self.parents
+    }
+
+    /// Entities whose `Parent` is `entity`, in ID order.
+    pub fn children(&self, entity: EntityId) -> impl Iterator<Item = EntityId> + '_ {
+        self.parents
+            .iter()
+            .filter(move |(_, parent)| parent.0 == entity)
+            .map(|(id, _)| *id)
+    }
+
+    // --- Tags ---
+    /// Add a tag to an entity. Returns false if the entity already had it.
+    pub fn add_tag(&mut self, entity: EntityId, tag: Tag) -> bool {
+        if tag.storage() == TagStorage::Sparse {
+            return self.sparse_tags.entry(tag).or_default().insert(entity);
+        }
+        let added = self.tags.entry(tag).or_default().insert(entity);
+        if added {
+            self.events.push(ComponentEvent::TagAdded { entity, tag });
+        }
+        added
+    }
+
+    /// Remove a tag from an entity. Returns false if the entity did not have it.
+    pub fn remove_tag(&mut self, entity: EntityId, tag: Tag) -> bool {
+        if tag.storage() == TagStorage::Sparse {
+            return self
+                .sparse_tags
+                .get_mut(&tag)
+                .is_some_and(|set| set.remove(entity));
+        }
+        let removed = self
+            .tags
+            .get_mut(&tag)
+            .is_some_and(|set| set.remove(&entity));
+        if removed {
+            self.events.push(ComponentEvent::TagRemoved { entity, tag });
+        }
+        removed
+    }
+
+    pub fn has_tag(&self, entity: EntityId, tag: Tag) -> bool {
+        match tag.storage() {
+            TagStorage::Ordered => self.tags.get(&tag).is_some_and(|set| set.contains(&entity)),
+            TagStorage::Sparse => self
+                .sparse_tags
+                .get(&tag)
+                .is_some_and(|set| set.contains(entity)),
+        }
+    }
+
+    /// Membership sets of the `TagStorage::Ordered` tags, keyed by tag.
+    pub fn tags(&self) -> &BTreeMap<Tag, BTreeSet<EntityId>> {
+        &self.tags
+    }
+
+    /// All entities carrying `tag`, in EntityId order.
+    pub fn with_tag(&self, tag: Tag) -> impl Iterator<Item = EntityId> + '_ {
+        let ordered = self.tags.get(&tag).into_iter().flatten().copied();
+        let sparse = self.sparse_tags.get(&tag).map(SparseTag::iter_sorted);
+        ordered.chain(sparse.into_iter().flatten())
+    }
+
+    /// Integrate every Velocity into its entity's transform, then advance the
+    /// world one tick.
+    ///
+    /// Motion is applied through `World::integrate`, so each moved entity logs a
+    /// `TransformUpdated` event and replaying the world log reproduces it.
+    /// Entities frozen by `World::set_active` are not moved.
+    pub fn step_world(&self, world: &mut World) {
+        for (id, velocity) in &self.velocities {
+            if !world.is_active(*id) {
+                continue;
+            }
+            world.integrate(*id, velocity.linear, velocity.angular, TICK_SECONDS);
+        }
+        world.step();
+    }
+
+    /// Point every component referencing asset `old` at `new`, e.g. after an
+    /// `AssetEvent::AssetReloaded`. Each change goes through the normal
+    /// setter and emits an `Updated` event. Returns the number of components
+    /// changed.
+    pub fn remap_asset(&mut self, old: AssetId, new: AssetId) -> usize {
+        if old == new {
+            return 0;
+        }
+        let remap = |id: u64| if id == old.0 { new.0 } else { id };
+        let mut changed = 0;
+
+        let renderables: Vec<(EntityId, Renderable)> = self
+            .renderables
+            .iter()
+            .filter(|(_, r)| r.mesh.0 == old.0 || r.material.0 == old.0)
+            .map(|(id, r)| (*id, *r))
+            .collect();
+        for (id, r) in renderables {
+            let renderable = Renderable {
+                mesh: MeshHandle(remap(r.mesh.0)),
+                material: MaterialHandle(remap(r.material.0)),
+            };
+            self.set_renderable(id, renderable);
+            changed += 1;
+        }
+        let scripts: Vec<(EntityId, Script)> = self
+            .scripts
+            .iter()
+            .filter(|(_, s)| s.asset == old)
+            .map(|(id, s)| (*id, *s))
+            .collect();
+        for (id, mut script) in scripts {
+            script.asset = new;
+            self.set_script(id, script);
+            changed += 1;
+        }
+        let sources: Vec<(EntityId, AudioSource)> = self
+            .audio_sources
+            .iter()
+            .filter(|(_, s)| s.asset == old)
+            .map(|(id, s)| (*id, *s))
+            .collect();
+        for (id, mut source) in sources {
+            source.asset = new;
+            self.set_audio_source(id, source);
+            changed += 1;
+        }
+        let animators: Vec<(EntityId, Animator)> = self
+            .animators
+            .iter()
+            .filter(|(_, a)| a.clip == old)
+            .map(|(id, a)| (*id, *a))
+            .collect();
+        for (id, mut animator) in animators {
+            animator.clip = new;
+            self.set_animator(id, animator);
+            changed += 1;
+        }
+        let sprites: Vec<(EntityId, Sprite)> = self
+            .sprites
+            .iter()
+            .filter(|(_, s)| s.texture.0 == old.0)
+            .map(|(id, s)| (*id, *s))
+            .collect();
+        for (id, mut sprite) in sprites {
+            sprite.texture = TextureHandle(new.0);
+            self.set_sprite(id, sprite);
+            changed += 1;
+        }
+        changed
+    }
+
+    /// Advance every playing Animator by one tick and apply its clip to the
+    /// entity's transform.
+    ///
+    /// Time advances as `AnimatorUpdated` events and poses are applied through
+    /// `World::set_transform`, so both component and world replay reproduce the
+    /// animation. Animators whose clip is missing from `assets` and those of
+    /// entities frozen by `World::set_active` are left alone. Call before
+    /// `step_world`.
+    pub fn step_animations(&mut self, world: &mut World, assets: &AssetStore) {
+        let playing: Vec<(EntityId, Animator)> = self
+            .animators
+            .iter()
+            .filter(|(id, a)| a.playing && a.speed != 0.0 && world.is_active(**id))
+            .map(|(id, a)| (*id, *a))
+            .collect();
+        for (id, animator) in playing {
+            let Some(clip) = assets.get_animation(animator.clip) else {
+                continue;
+            };
+            let Some(current) = world.get(id).map(|data| data.transform) else {
+                continue;
+            };
+            let mut time = animator.time + animator.speed * TICK_SECONDS;
+            if clip.duration > 0.0 {
+                time = time.rem_euclid(clip.duration);
+            }
+            self.set_animator(id, Animator { time, ..animator });
+
+            let sample = clip.sample(time);
+            let mut transform = current;
+            if let Some(t) = sample.translation {
+                transform.position = Vec3::from_array(t);
+            }
+            if let Some(r) = sample.rotation {
+                transform.rotation = Quat::from_array(r);
+            }
+            if let Some(s) = sample.scale {
+                transform.scale = Vec3::from_array(s);
+            }
+            if transform != current {
+                world.set_transform(id, transform);
+            }
+        }
+    }
+
+    /// Remove all components for an entity.
+    pub fn remove_entity(&mut self, entity: EntityId) {
+        self.remove_name(entity);
+        self.remove_renderable(entity);
+        self.remove_rigid_body(entity);
+        self.remove_collider(entity);
+        self.remove_script(entity);
+        self.remove_audio_source(entity);
+        self.remove_animator(entity);
+        self.remove_sprite(entity);
+        self.remove_light(entity);
+        self.remove_velocity(entity);
+        self.remove_layer(entity);
+        self.remove_visible(entity);
+        self.remove_parent(entity);
+        let tags: Vec<Tag> = self
+            .tags
+            .keys()
+            .chain(self.sparse_tags.keys())
+            .copied()
+            .collect();
+        for tag in tags {
+            self.remove_tag(entity, tag);
+        }
+    }
+
+    /// Replay a component event (for undo/redo or persistence replay).
+    pub fn apply_event(&mut self, event: &ComponentEvent) {
+        match event {
+            ComponentEvent::NameAdded { entity, name } => {
+                self.names.insert(*entity, Name(name.clone()));
+            }
+            ComponentEvent::NameRemoved { entity, .. } => {
+                self.names.remove(entity);
+            }
+            ComponentEvent::NameUpdated { entity, new, .. } => {
+                self.names.insert(*entity, Name(new.clone()));
+            }
+            ComponentEvent::RenderableAdded { entity, renderable } => {
+                self.renderables.insert(*entity, *renderable);
+            }
+            ComponentEvent::RenderableRemoved { entity, .. } => {
+                self.renderables.remove(entity);
+            }
+            ComponentEvent::RenderableUpdated { entity, new, .. } => {
+                self.renderables.insert(*entity, *new);
+            }
+            ComponentEvent::RigidBodyAdded { entity, body } => {
+                self.rigid_bodies.insert(*entity, *body);
+            }
+            ComponentEvent::RigidBodyRemoved { entity, .. } => {
+                self.rigid_bodies.remove(entity);
+            }
+            ComponentEvent::RigidBodyUpdated { entity, new, .. } => {
+                self.rigid_bodies.insert(*entity, *new);
+            }
+            ComponentEvent::ColliderAdded { entity, collider } => {
+                self.colliders.insert(*entity, *collider);
+            }
+            ComponentEvent::ColliderRemoved { entity, .. } => {
+                self.colliders.remove(entity);
+            }
+            ComponentEvent::ColliderUpdated { entity, new, .. } => {
+                self.colliders.insert(*entity, *new);
+            }
+            ComponentEvent::ScriptAdded { entity, script } => {
+                self.scripts.insert(*entity, *script);
+            }
+            ComponentEvent::ScriptRemoved { entity, .. } => {
+                self.scripts.remove(entity);
+            }
+            ComponentEvent::ScriptUpdated { entity, new, .. } => {
+                self.scripts.insert(*entity, *new);
+            }
+            ComponentEvent::AudioSourceAdded { entity, source } => {
+                self.audio_sources.insert(*entity, *source);
+            }
+            ComponentEvent::AudioSourceRemoved { entity, .. } => {
+                self.audio_sources.remove(entity);
+            }
+            ComponentEvent::AudioSourceUpdated { entity, new, .. } => {
+                self.audio_sources.insert(*entity, *new);
+            }
+            ComponentEvent::AnimatorAdded { entity, animator } => {
+                self.animators.insert(*entity, *animator);
+            }
+            ComponentEvent::AnimatorRemoved { entity, .. } => {
+                self.animators.remove(entity);
+            }
+            ComponentEvent::AnimatorUpdated { entity, new, .. } => {
+                self.animators.insert(*entity, *new);
+            }
+            ComponentEvent::SpriteAdded { entity, sprite } => {
+                self.sprites.insert(*entity, *sprite);
+            }
+            ComponentEvent::SpriteRemoved { entity, .. } => {
+                self.sprites.remove(entity);
+            }
+            ComponentEvent::SpriteUpdated { entity, new, .. } => {
+                self.sprites.insert(*entity, *new);
+            }
+            ComponentEvent::LightAdded { entity, light } => {
+                self.lights.insert(*entity, *light);
+            }
+            ComponentEvent::LightRemoved { entity, .. } => {
+                self.lights.remove(entity);
+            }
+            ComponentEvent::LightUpdated { entity, new, .. } => {
+                self.lights.insert(*entity, *new);
+            }
+            ComponentEvent::VelocityAdded { entity, velocity } => {
+                self.velocities.insert(*entity, *velocity);
+            }
+            ComponentEvent::VelocityRemoved { entity, .. } => {
+                self.velocities.remove(entity);
+            }
+            ComponentEvent::VelocityUpdated { entity, new, .. } => {
+                self.velocities.insert(*entity, *new);
+            }
+            ComponentEvent::LayerAdded { entity, layer } => {
+                self.layers.insert(*entity, *layer);
+            }
+            ComponentEvent::LayerRemoved { entity, .. } => {
+                self.layers.remove(entity);
+            }
+            ComponentEvent::LayerUpdated { entity, new, .. } => {
+                self.layers.insert(*entity, *new);
+            }
+            ComponentEvent::VisibleAdded { entity, visible } => {
+                self.visibility.insert(*entity, *visible);
+            }
+            ComponentEvent::VisibleRemoved { entity, .. } => {
+                self.visibility.remove(entity);
+            }
+            ComponentEvent::VisibleUpdated { entity, new, .. } => {
+                self.visibility.insert(*entity, *new);
+            }
+            ComponentEvent::ParentAdded { entity, parent } => {
+                self.parents.insert(*entity, *parent);
+            }
+            ComponentEvent::ParentRemoved { entity, .. } => {
+                self.parents.remove(entity);
+            }
+            ComponentEvent::ParentUpdated { entity, new, .. } => {
+                self.parents.insert(*entity, *new);
+            }
+            ComponentEvent::TagAdded { entity, tag } => match tag.storage() {
+                TagStorage::Ordered => {
+                    self.tags.entry(*tag).or_default().insert(*entity);
+                }
+                TagStorage::Sparse => {
+                    self.sparse_tags
+                        .entry(*tag)
+                        .or_default()
+                        .apply(*entity, true);
+                }
+            },
+            ComponentEvent::TagRemoved { entity, tag } => match tag.storage() {
+                TagStorage::Ordered => {
+                    if let Some(set) = self.tags.get_mut(tag) {
+                        set.remove(entity);
+                    }
+                }
+                TagStorage::Sparse => {
+                    if let Some(set) = self.sparse_tags.get_mut(tag) {
+                        set.apply(*entity, false);
+                    }
+                }
+            },
+            ComponentEvent::Batch(events) => {
+                for event in events {
+                    self.apply_event(event);
+                }
+            }
+            ComponentEvent::ValidationFailed { .. } => {}
+        }
+    }
+}
+
+pub fn crate_info() -> &'static str {
+    "worldspace-ecs v0.1.0"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_add_remove() {
+        let mut store = ComponentStore::new();
+        let id = EntityId::new();
+        store.set_name(id, "Test".into());
+        assert_eq!(store.get_name(id).unwrap().0, "Test");
+
+        store.remove_name(id);
+        assert!(store.get_name(id).is_none());
+        assert_eq!(store.events().len(), 2);
+    }
+
+    #[test]
+    fn name_update_produces_event() {
+        let mut store = ComponentStore::new();
+        let id = EntityId::new();
+        store.set_name(id, "First".into());
+        store.set_name(id, "Second".into());
+        assert_eq!(store.get_name(id).unwrap().0, "Second");
+        // Add + Update
+        assert_eq!(store.events().len(), 2);
+    }
+
+    #[test]
+    fn renderable_add_remove() {
+        let mut store = ComponentStore::new();
+        let id = EntityId::new();
+        let r = Renderable {
+            mesh: MeshHandle(1),
+            material: MaterialHandle(2),
+        };
+        store.set_renderable(id, r);
+        assert_eq!(store.get_renderable(id), Some(&r));
+
+        store.remove_renderable(id);
+        assert!(store.get_renderable(id).is_none());
+    }
+
+    #[test]
+    fn physics_overwrite_emits_updated() {
+        let mut store = ComponentStore::new();
+        let id = EntityId::new();
+        store.set_rigid_body(id, RigidBody::default());
+        store.set_rigid_body(
+            id,
+            RigidBody {
+                mass: 5.0,
+                is_kinematic: true,
+            },
+        );
+        store.set_collider(id, Collider::default());
+        store.set_collider(id, Collider::Sphere { radius: 2.0 });
+
+        let events = store.events();
+        assert!(matches!(events[0], ComponentEvent::RigidBodyAdded { .. }));
+        assert!(matches!(
+            events[1],
+            ComponentEvent::RigidBodyUpdated { old, .. } if old == RigidBody::default()
+        ));
+        assert!(matches!(events[2], ComponentEvent::ColliderAdded { .. }));
+        assert!(matches!(
+            events[3],
+            ComponentEvent::ColliderUpdated {
+                new: Collider::Sphere { .. },
+                ..
+            }
+        ));
+
+        let mut replayed = ComponentStore::new();
+        for event in events {
+            replayed.apply_event(event);
+        }
+        assert_eq!(replayed.get_rigid_body(id).unwrap().mass, 5.0);
+        assert_eq!(
+            replayed.get_collider(id),
+            Some(&Collider::Sphere { radius: 2.0 })
+        );
+    }
+
+    #[test]
+    fn script_add_update_remove() {
+        let mut store = ComponentStore::new();
+        let id = EntityId::new();
+        let script = Script {
+            asset: AssetId(7),
+            enabled: true,
+        };
+        store.set_script(id, script);
+        store.set_script(
+            id,
+            Script {
+                enabled: false,
+                ..script
+            },
+        );
+        assert!(!store.get_script(id).unwrap().enabled);
+        assert!(matches!(
+            store.events()[1],
+            ComponentEvent::ScriptUpdated { .. }
+        ));
+
+        store.remove_script(id);
+        assert!(store.get_script(id).is_none());
+        assert_eq!(store.events().len(), 3);
+    }
+
+    #[test]
+    fn audio_source_add_update_remove() {
+        let mut store = ComponentStore::new();
+        store.set_validators(Validators::standard());
+        let id = EntityId::new();
+        let source = AudioSource {
+            asset: AssetId(3),
+            looping: true,
+            gain: 0.5,
+        };
+        store.set_audio_source(id, source);
+        store.set_audio_source(
+            id,
+            AudioSource {
+                gain: -1.0,
+                ..source
+            },
+        );
+        assert_eq!(store.get_audio_source(id).unwrap().gain, 0.0);
+        assert!(matches!(
+            store.events()[2],
+            ComponentEvent::AudioSourceUpdated { .. }
+        ));
+
+        store.remove_entity(id);
+        assert!(store.audio_sources().is_empty());
+        assert!(matches!(
+            store.events()[3],
+            ComponentEvent::AudioSourceRemoved { .. }
+        ));
+    }
+
+    #[test]
+    fn animator_plays_clip_into_transform() {
+        use worldspace_assets::{AnimationClip, Keyframe};
+        use worldspace_common::Transform;
+
+        let mut assets = AssetStore::new();
+        let clip = assets.register_animation(AnimationClip {
+            name: "slide".into(),
+            duration: 1.0,
+            translation: vec![
+                Keyframe {
+                    time: 0.0,
+                    value: [0.0, 0.0, 0.0],
+                },
+                Keyframe {
+                    time: 1.0,
+                    value: [60.0, 0.0, 0.0],
+                },
+            ],
+            ..Default::default()
+        });
+
+        let mut world = World::new();
+        let id = world.spawn(Transform::default());
+        let mut store = ComponentStore::new();
+        store.set_animator(id, Animator::new(clip));
+
+        for _ in 0..3 {
+            store.step_animations(&mut world, &assets);
+            store.step_world(&mut world);
+        }
+        assert!((store.get_animator(id).unwrap().time - 3.0 * TICK_SECONDS).abs() < 1e-6);
+        assert!((world.get(id).unwrap().transform.position.x - 3.0).abs() < 1e-4);
+        assert!(matches!(
+            store.events()[1],
+            ComponentEvent::AnimatorUpdated { .. }
+        ));
+
+        let replayed = World::replay(world.events());
+        assert_eq!(replayed.state_hash(), world.state_hash());
+
+        store.set_animator(
+            id,
+            Animator {
+                playing: false,
+                ..*store.get_animator(id).unwrap()
+            },
+        );
+        let before = store.events().len();
+        store.step_animations(&mut world, &assets);
+        assert_eq!(store.events().len(), before);
+
+        store.remove_entity(id);
+        assert!(store.animators().is_empty());
+    }
+
+    #[test]
+    fn remap_asset_updates_references() {
+        let mut store = ComponentStore::new();
+        let a = EntityId::new();
+        let b = EntityId::new();
+        store.set_renderable(
+            a,
+            Renderable {
+                mesh: MeshHandle(7),
+                material: MaterialHandle(8),
+            },
+        );
+        store.set_script(
+            b,
+            Script {
+                asset: AssetId(7),
+                enabled: true,
+            },
+        );
+        store.set_animator(b, Animator::new(AssetId(9)));
+        store.drain_events();
+
+        assert_eq!(store.remap_asset(AssetId(7), AssetId(70)), 2);
+        assert_eq!(store.get_renderable(a).unwrap().mesh, MeshHandle(70));
+        assert_eq!(store.get_renderable(a).unwrap().material, MaterialHandle(8));
+        assert_eq!(store.get_script(b).unwrap().asset, AssetId(70));
+        assert_eq!(store.get_animator(b).unwrap().clip, AssetId(9));
+        assert!(matches!(
+            store.events()[0],
+            ComponentEvent::RenderableUpdated { .. }
+        ));
+        assert_eq!(store.remap_asset(AssetId(7), AssetId(70)), 0);
+    }
+
+    #[test]
+    fn sprite_add_update_remove() {
+        let mut store = ComponentStore::new();
+        store.set_validators(Validators::standard());
+        let id = EntityId::new();
+        let atlas = TextureHandle(7);
+
+        store.set_sprite(id, Sprite::from_atlas(atlas, 4, 2, 5));
+        assert_eq!(store.get_sprite(id).unwrap().uv_rect, [0.25, 0.5, 0.5, 1.0]);
+        store.set_sprite(
+            id,
+            Sprite {
+                size: [-1.0, 2.0],
+                ..Sprite::new(atlas)
+            },
+        );
+        assert_eq!(store.get_sprite(id).unwrap().size, [0.0, 2.0]);
+        assert!(matches!(
+            store.events().last(),
+            Some(ComponentEvent::SpriteUpdated { .. })
+        ));
+
+        assert_eq!(store.remap_asset(AssetId(7), AssetId(9)), 1);
+        assert_eq!(store.get_sprite(id).unwrap().texture, TextureHandle(9));
+
+        let mut replayed = ComponentStore::new();
+        for event in store.events() {
+            replayed.apply_event(event);
+        }
+        assert_eq!(replayed.sprites(), store.sprites());
+
+        store.remove_entity(id);
+        assert!(store.sprites().is_empty());
+    }
+
+    #[test]
+    fn light_add_update_remove() {
+        let mut store = ComponentStore::new();
+        let id = EntityId::new();
+        store.set_light(id, Light::default());
+        store.set_light(
+            id,
+            Light {
+                kind: LightKind::Point,
+                intensity: 2.0,
+                ..Light::default()
+            },
+        );
+        assert_eq!(store.get_light(id).unwrap().kind, LightKind::Point);
+        assert!(matches!(
+            store.events()[1],
+            ComponentEvent::LightUpdated { .. }
+        ));
+
+        store.remove_light(id);
+        assert!(store.lights().is_empty());
+        assert_eq!(store.events().len(), 3);
+    }
+
+    #[test]
+    fn velocity_integrated_by_step_and_replayed() {
+        use worldspace_common::Transform;
+
+        let mut world = World::new();
+        let id = world.spawn(Transform::default());
+        let mut store = ComponentStore::new();
+        store.set_velocity(
+            id,
+            Velocity {
+                linear: Vec3::new(60.0, 0.0, 0.0),
+                angular: Vec3::ZERO,
+            },
+        );
+
+        store.step_world(&mut world);
+        store.step_world(&mut world);
+        assert_eq!(world.tick(), 2);
+        assert!((world.get(id).unwrap().transform.position.x - 2.0).abs() < 1e-5);
+
+        let replayed = World::replay(world.events());
+        assert_eq!(replayed.state_hash(), world.state_hash());
+    }
+
+    #[test]
+    fn frozen_entities_are_not_stepped() {
+        use worldspace_common::Transform;
+
+        let mut world = World::new();
+        let moving = world.spawn(Transform::default());
+        let frozen = world.spawn(Transform::default());
+        let mut store = ComponentStore::new();
+        for id in [moving, frozen] {
+            store.set_velocity(
+                id,
+                Velocity {
+                    linear: Vec3::new(60.0, 0.0, 0.0),
+                    angular: Vec3::ZERO,
+                },
+            );
+        }
+
+        world.set_active(&BTreeSet::from([moving]));
+        store.step_world(&mut world);
+        assert_eq!(world.get(frozen).unwrap().transform.position, Vec3::ZERO);
+        assert!((world.get(moving).unwrap().transform.position.x - 1.0).abs() < 1e-5);
+        assert_eq!(world.tick(), 1);
+    }
+
+    #[test]
+    fn frozen_entities_are_not_animated() {
+        use worldspace_assets::{AnimationClip, Keyframe};
+        use worldspace_common::Transform;
+
+        let mut assets = AssetStore::new();
+        let clip = assets.register_animation(AnimationClip {
+            name: "rise".into(),
+            duration: 1.0,
+            translation: vec![
+                Keyframe {
+                    time: 0.0,
+                    value: [0.0, 0.0, 0.0],
+                },
+                Keyframe {
+                    time: 1.0,
+                    value: [0.0, 60.0, 0.0],
+                },
+            ],
+            ..Default::default()
+        });
+
+        let mut world = World::new();
+        let playing = world.spawn(Transform::default());
+        let frozen = world.spawn(Transform::default());
+        let mut store = ComponentStore::new();
+        for id in [playing, frozen] {
+            store.set_animator(id, Animator::new(clip));
+        }
+        world.set_active(&BTreeSet::from([playing]));
+        world.drain_events();
+        store.drain_events();
+
+        store.step_animations(&mut world, &assets);
+        assert_eq!(store.get_animator(frozen).unwrap().time, 0.0);
+        assert_eq!(world.get(frozen).unwrap().transform, Transform::default());
+        assert!(store.get_animator(playing).unwrap().time > 0.0);
+        assert_eq!(store.events().len(), 1);
+        assert_eq!(world.events().len(), 1);
+    }
+
+    #[test]
+    fn layer_bitmask_queries() {
+        let mut names = LayerNames::new();
+        let gizmos = names.define("gizmos").unwrap();
+        let gameplay = names.define("gameplay").unwrap();
+        assert_eq!(names.define("gizmos"), Some(gizmos));
+        assert_eq!(
+            names.mask(["gizmos", "gameplay", "unknown"]),
+            gizmos.union(gameplay)
+        );
+
+        let mut store = ComponentStore::new();
+        let a = EntityId::new();
+        let b = EntityId::new();
+        let plain = EntityId::new();
+        store.set_layer(a, gizmos);
+        store.set_layer(b, gameplay.union(Layer::DEFAULT));
+
+        assert_eq!(store.with_layers(gizmos).collect::<Vec<_>>(), vec![a]);
+        assert_eq!(store.with_layers(Layer::ALL).count(), 2);
+        assert!(store.in_layers(plain, Layer::DEFAULT));
+        assert!(store.in_layers(b, Layer::DEFAULT));
+        assert!(!store.in_layers(a, Layer::DEFAULT));
+
+        store.set_layer(a, gameplay);
+        assert!(matches!(
+            store.events()[2],
+            ComponentEvent::LayerUpdated { .. }
+        ));
+        assert_eq!(store.with_layers(gizmos).count(), 0);
+
+        // Named layers never take the editor bit.
+        let mut names = LayerNames::new();
+        let defined: Vec<Layer> = (0..40)
+            .map_while(|i| names.define(&format!("layer{i}")))
+            .collect();
+        assert_eq!(defined.len(), 30);
+        assert!(!defined.iter().any(|layer| layer.intersects(Layer::EDITOR)));
+        assert_eq!(Layer::PLAY.union(Layer::EDITOR), Layer::ALL);
+        assert_eq!(Layer::PLAY.intersection(Layer::EDITOR), Layer::NONE);
+    }
+
+    #[test]
+    fn visible_defaults_to_true() {
+        let mut store = ComponentStore::new();
+        let id = EntityId::new();
+        assert!(store.is_visible(id));
+
+        store.set_visible(id, Visible(false));
+        assert!(!store.is_visible(id));
+        store.set_visible(id, Visible(true));
+        assert!(matches!(
+            store.events()[1],
+            ComponentEvent::VisibleUpdated { .. }
+        ));
+
+        store.set_visible(id, Visible(false));
+        store.remove_entity(id);
+        assert!(store.is_visible(id));
+        assert!(store.visibility().is_empty());
+    }
+
+    #[test]
+    fn batch_setters_emit_one_event() {
+        let ids: Vec<EntityId> = (0..100).map(|_| EntityId::new()).collect();
+        let mut store = ComponentStore::new();
+        store.set_renderables_batch(ids.iter().map(|id| {
+            (
+                *id,
+                Renderable {
+                    mesh: MeshHandle(1),
+                    material: MaterialHandle(1),
+                },
+            )
+        }));
+        store.set_names_batch(ids.iter().map(|id| (*id, "Imported".to_string())));
+
+        assert_eq!(store.events().len(), 2);
+        assert_eq!(store.renderables().len(), 100);
+        match &store.events()[0] {
+            ComponentEvent::Batch(inner) => assert_eq!(inner.len(), 100),
+            other => panic!("expected batch, got {other:?}"),
+        }
+
+        let mut replayed = ComponentStore::new();
+        for event in store.events() {
+            replayed.apply_event(event);
+        }
+        assert_eq!(replayed.names().len(), 100);
+
+        store.set_colliders_batch(std::iter::empty());
+        assert_eq!(store.events().len(), 2);
+
+        store.remove_entities_batch(ids.iter().copied());
+        assert_eq!(store.events().len(), 3);
+        assert!(store.renderables().is_empty());
+    }
+
+    #[test]
+    fn validators_reject_and_clamp() {
+        let mut store = ComponentStore::new();
+        store.set_validators(Validators::standard());
+        let id = EntityId::new();
+
+        store.set_rigid_body(
+            id,
+            RigidBody {
+                mass: f32::NAN,
+                is_kinematic: false,
+            },
+        );
+        assert!(store.get_rigid_body(id).is_none());
+        assert!(matches!(
+            &store.events()[0],
+            ComponentEvent::ValidationFailed { clamped: false, component, .. } if component == "RigidBody"
+        ));
+
+        store.set_collider(
+            id,
+            Collider::Box {
+                half_extents: [-1.0, 0.5, 0.5],
+            },
+        );
+        assert_eq!(
+            store.get_collider(id),
+            Some(&Collider::Box {
+                half_extents: [1.0, 0.5, 0.5]
+            })
+        );
+        assert!(matches!(
+            store.events()[1],
+            ComponentEvent::ValidationFailed { clamped: true, .. }
+        ));
+        assert!(matches!(
+            store.events()[2],
+            ComponentEvent::ColliderAdded { .. }
+        ));
+    }
+
+    #[test]
+    fn custom_validator() {
+        let mut store = ComponentStore::new();
+        store.add_validator::<Light>(|light| {
+            if light.intensity > 10.0 {
+                Validation::Clamp(
+                    Light {
+                        intensity: 10.0,
+                        ..*light
+                    },
+                    "too bright".into(),
+                )
+            } else {
+                Validation::Accept
+            }
+        });
+        let id = EntityId::new();
+        store.set_light(
+            id,
+            Light {
+                intensity: 50.0,
+                ..Light::default()
+            },
+        );
+        assert_eq!(store.get_light(id).unwrap().intensity, 10.0);
+    }
+
+    #[test]
+    fn tags_membership_and_events() {
+        let mut store = ComponentStore::new();
+        let a = EntityId::new();
+        let b = EntityId::new();
+        assert!(store.add_tag(a, Tag::Static));
+        assert!(!store.add_tag(a, Tag::Static));
+        store.add_tag(b, Tag::Static);
+        store.add_tag(b, Tag::Hidden);
+
+        assert!(store.has_tag(a, Tag::Static));
+        assert!(!store.has_tag(a, Tag::Hidden));
+        assert_eq!(store.with_tag(Tag::Static).count(), 2);
+        assert_eq!(store.with_tag(Tag::NoSerialize).count(), 0);
+
+        assert!(store.remove_tag(b, Tag::Static));
+        assert!(!store.remove_tag(b, Tag::Static));
+        assert_eq!(store.with_tag(Tag::Static).collect::<Vec<_>>(), vec![a]);
+        assert_eq!(store.events().len(), 4);
+
+        let mut replayed = ComponentStore::new();
+        for event in store.events() {
+            replayed.apply_event(event);
+        }
+        assert!(replayed.has_tag(b, Tag::Hidden));
+        assert!(!replayed.has_tag(b, Tag::Static));
+    }
+
+    #[test]
+    fn sparse_tags_drain_net_changes_in_entity_order() {
+        let mut ids: Vec<EntityId> = (0..4).map(|_| EntityId::new()).collect();
+        ids.sort();
+        let mut store = ComponentStore::new();
+        store.add_tag(ids[3], Tag::Resident);
+        store.drain_events();
+
+        // Mutations in reverse order; a select and deselect cancel out.
+        store.add_tag(ids[2], Tag::Selected);
+        store.add_tag(ids[1], Tag::Selected);
+        store.remove_tag(ids[2], Tag::Selected);
+        store.add_tag(ids[0], Tag::Selected);
+        assert!(store.remove_tag(ids[3], Tag::Resident));
+        assert!(!store.remove_tag(ids[3], Tag::Resident));
+        assert!(store.events().is_empty());
+        assert!(store.has_tag(ids[1], Tag::Selected));
+        assert_eq!(
+            store.with_tag(Tag::Selected).collect::<Vec<_>>(),
+            vec![ids[0], ids[1]]
+        );
+
+        let events = store.drain_events();
+        let changes: Vec<(EntityId, Tag, bool)> = events
+            .iter()
+            .map(|event| match event {
+                ComponentEvent::TagAdded { entity, tag } => (*entity, *tag, true),
+                ComponentEvent::TagRemoved { entity, tag } => (*entity, *tag, false),
+                other => panic!("unexpected event {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (ids[0], Tag::Selected, true),
+                (ids[1], Tag::Selected, true),
+                (ids[3], Tag::Resident, false),
+            ]
+        );
+        assert!(events.iter().all(ComponentEvent::is_transient));
+        assert!(store.drain_events().is_empty());
+        store.add_tag(ids[0], Tag::Static);
+        assert!(!store.drain_events()[0].is_transient());
+
+        let mut replayed = ComponentStore::new();
+        for event in &events {
+            replayed.apply_event(event);
+        }
+        assert_eq!(
+            replayed.with_tag(Tag::Selected).collect::<Vec<_>>(),
+            vec![ids[0], ids[1]]
+        );
+        assert!(replayed.tags().is_empty());
+    }
+
+    #[test]
+    fn remove_entity_clears_all() {
+        let mut store = ComponentStore::new();
+        let id = EntityId::new();
+        store.set_name(id, "Test".into());
+        store.set_renderable(
+            id,
+            Renderable {
+                mesh: MeshHandle(0),
                 material: MaterialHandle(0),
             },
         );
         store.set_rigid_body(id, RigidBody::default());
         store.set_collider(id, Collider::default());
+        store.set_script(
+            id,
+            Script {
+                asset: AssetId(1),
+                enabled: true,
+            },
+        );
+
+        store.add_tag(id, Tag::Hidden);
 
         store.remove_entity(id);
         assert!(store.get_name(id).is_none());
         assert!(store.get_renderable(id).is_none());
         assert!(store.get_rigid_body(id).is_none());
         assert!(store.get_collider(id).is_none());
+        assert!(store.get_script(id).is_none());
+        assert!(!store.has_tag(id, Tag::Hidden));
     }
 
     #[test]
@@ -0,0 +1,193 @@
+//! Plugin extension point for third-party component packs.
+//!
+//! A plugin registers component metadata, systems that run each tick, and
+//! handlers that observe component events. Plugins keep their own component
+//! storage; the host only sequences them.
+
+use worldspace_kernel::World;
+
+use crate::reflect::{ComponentInfo, ComponentRegistry, Reflect};
+use crate::{ComponentEvent, ComponentStore};
+
+/// A system run once per tick, in registration order.
+pub type System = Box<dyn FnMut(&mut World, &mut ComponentStore)>;
+
+/// A handler invoked for every dispatched component event.
+pub type EventHandler = Box<dyn FnMut(&ComponentEvent)>;
+
+/// Errors from plugin registration.
+#[derive(Debug, thiserror::Error)]
+pub enum PluginError {
+    #[error("plugin already registered: {0}")]
+    AlreadyRegistered(String),
+}
+
+/// Implemented by external crates to extend the component model.
+pub trait EcsPlugin {
+    /// Unique plugin name.
+    fn name(&self) -> &'static str;
+
+    /// Register components, systems, and event handlers with the host.
+    fn build(&self, host: &mut PluginHost);
+}
+
+/// Owns registered plugins, their systems, and their event handlers.
+pub struct PluginHost {
+    registry: ComponentRegistry,
+    plugins: Vec<&'static str>,
+    systems: Vec<(&'static str, System)>,
+    handlers: Vec<EventHandler>,
+}
+
+impl PluginHost {
+    /// Create a host whose registry already contains the built-in components.
+    pub fn new() -> Self {
+        Self {
+            registry: ComponentRegistry::with_builtins(),
+            plugins: Vec::new(),
+            systems: Vec::new(),
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Register a plugin. Fails if a plugin with the same name is present.
+    pub fn add_plugin(&mut self, plugin: &dyn EcsPlugin) -> Result<(), PluginError> {
+        let name = plugin.name();
+        if self.plugins.contains(&name) {
+            return Err(PluginError::AlreadyRegistered(name.to_string()));
+        }
+        self.plugins.push(name);
+        plugin.build(self);
+        tracing::debug!(plugin = name, "ecs plugin registered");
+        Ok(())
+    }
+
+    pub fn register_component<T: Reflect>(&mut self) {
+        self.registry.register::<T>();
+    }
+
+    pub fn register_component_info(&mut self, info: ComponentInfo) {
+        self.registry.register_info(info);
+    }
+
+    pub fn add_system(
+        &mut self,
+        name: &'static str,
+        system: impl FnMut(&mut World, &mut ComponentStore) + 'static,
+    ) {
+        self.systems.push((name, Box::new(system)));
+    }
+
+    pub fn add_event_handler(&mut self, handler: impl FnMut(&ComponentEvent) + 'static) {
+        self.handlers.push(Box::new(handler));
+    }
+
+    /// Component metadata for built-in and plugin components.
+    pub fn registry(&self) -> &ComponentRegistry {
+        &self.registry
+    }
+
+    /// Names of registered plugins in registration order.
+    pub fn plugins(&self) -> &[&'static str] {
+        &self.plugins
+    }
+
+    /// Names of registered systems in run order.
+    pub fn system_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.systems.iter().map(|(name, _)| *name)
+    }
+
+    /// Run every system once, in registration order.
+    pub fn run_systems(&mut self, world: &mut World, components: &mut ComponentStore) {
+        for (_, system) in &mut self.systems {
+            system(world, components);
+        }
+    }
+
+    /// Pass each event to every handler, in order.
+    pub fn dispatch(&mut self, events: &[ComponentEvent]) {
+        for event in events {
+            for handler in &mut self.handlers {
+                handler(event);
+            }
+        }
+    }
+}
+
+impl Default for PluginHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reflect::{FieldInfo, FieldType};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use worldspace_common::{EntityId, Transform};
+
+    struct HealthPlugin {
+        seen: Rc<Cell<usize>>,
+    }
+
+    impl EcsPlugin for HealthPlugin {
+        fn name(&self) -> &'static str {
+            "health"
+        }
+
+        fn build(&self, host: &mut PluginHost) {
+            host.register_component_info(
+                ComponentInfo::new("Health").field(FieldInfo::new("current", FieldType::F32)),
+            );
+            host.add_system("name_spawned", |world, components| {
+                let ids: Vec<EntityId> = world.entities().keys().copied().collect();
+                for id in ids {
+                    if components.get_name(id).is_none() {
+                        components.set_name(id, "Spawned".into());
+                    }
+                }
+            });
+            let seen = self.seen.clone();
+            host.add_event_handler(move |_| seen.set(seen.get() + 1));
+        }
+    }
+
+    #[test]
+    fn plugin_registers_components_systems_and_handlers() {
+        let seen = Rc::new(Cell::new(0));
+        let mut host = PluginHost::new();
+        host.add_plugin(&HealthPlugin { seen: seen.clone() })
+            .unwrap();
+
+        assert!(host.registry().get("Health").is_some());
+        assert!(host.registry().get("Light").is_some());
+        assert_eq!(
+            host.system_names().collect::<Vec<_>>(),
+            vec!["name_spawned"]
+        );
+
+        let mut world = World::new();
+        let id = world.spawn(Transform::default());
+        let mut components = ComponentStore::new();
+        host.run_systems(&mut world, &mut components);
+        assert_eq!(components.get_name(id).unwrap().0, "Spawned");
+
+        host.dispatch(components.events());
+        assert_eq!(seen.get(), 1);
+    }
+
+    #[test]
+    fn duplicate_plugin_rejected() {
+        let mut host = PluginHost::new();
+        let plugin = HealthPlugin {
+            seen: Rc::new(Cell::new(0)),
+        };
+        host.add_plugin(&plugin).unwrap();
+        assert!(matches!(
+            host.add_plugin(&plugin),
+            Err(PluginError::AlreadyRegistered(_))
+        ));
+    }
+}
@@ -0,0 +1,335 @@
+//! Reflection metadata for components.
+//!
+//! Describes each component's fields (name, type, valid range, UI hint) so
+//! inspectors and editors can generate property UIs without per-type code.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    Animator, AudioSource, Collider, Layer, Light, Name, Parent, Renderable, RigidBody, Script,
+    Sprite, Velocity, Visible,
+};
+
+/// Value type of a reflected field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Bool,
+    F32,
+    U64,
+    String,
+    Vec2,
+    Vec3,
+    Vec4,
+    /// An `EntityId` referring to another entity.
+    Entity,
+    /// One of a fixed set of named variants.
+    Enum(&'static [&'static str]),
+}
+
+/// How an editor should present a field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UiHint {
+    #[default]
+    Default,
+    Slider,
+    Drag,
+    ColorPicker,
+    AssetPicker,
+    ReadOnly,
+}
+
+/// Metadata for a single component field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    pub ty: FieldType,
+    /// Inclusive numeric range, if the field is bounded.
+    pub range: Option<(f32, f32)>,
+    pub hint: UiHint,
+}
+
+impl FieldInfo {
+    pub fn new(name: &'static str, ty: FieldType) -> Self {
+        Self {
+            name,
+            ty,
+            range: None,
+            hint: UiHint::Default,
+        }
+    }
+
+    pub fn range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub fn hint(mut self, hint: UiHint) -> Self {
+        self.hint = hint;
+        self
+    }
+}
+
+/// Metadata for a component type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentInfo {
+    pub name: &'static str,
+    pub fields: Vec<FieldInfo>,
+}
+
+impl ComponentInfo {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn field(mut self, field: FieldInfo) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Look up a field by name.
+    pub fn get_field(&self, name: &str) -> Option<&FieldInfo> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+/// Implemented by components that expose reflection metadata.
+pub trait Reflect {
+    fn component_info() -> ComponentInfo;
+}
+
+/// Registry of component metadata keyed by component name.
+#[derive(Debug, Clone, Default)]
+pub struct ComponentRegistry {
+    components: BTreeMap<&'static str, ComponentInfo>,
+}
+
+impl ComponentRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with all built-in components registered.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register::<Name>();
+        registry.register::<Renderable>();
+        registry.register::<RigidBody>();
+        registry.register::<Collider>();
+        registry.register::<Script>();
+        registry.register::<Light>();
+        registry.register::<Velocity>();
+        registry.register::<Layer>();
+        registry.register::<AudioSource>();
+        registry.register::<Animator>();
+        registry.register::<Visible>();
+        registry.register::<Parent>();
+        registry.register::<Sprite>();
+        registry
+    }
+
+    /// Register a reflected component type, replacing any previous entry with
+    /// the same name.
+    pub fn register<T: Reflect>(&mut self) {
+        self.register_info(T::component_info());
+    }
+
+    /// Register metadata directly, for components described at runtime.
+    pub fn register_info(&mut self, info: ComponentInfo) {
+        self.components.insert(info.name, info);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ComponentInfo> {
+        self.components.get(name)
+    }
+
+    /// All registered components in name order.
+    pub fn iter(&self) -> impl Iterator<Item = &ComponentInfo> {
+        self.components.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+}
+
+impl Reflect for Name {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("Name").field(FieldInfo::new("name", FieldType::String))
+    }
+}
+
+impl Reflect for Renderable {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("Renderable")
+            .field(FieldInfo::new("mesh", FieldType::U64).hint(UiHint::AssetPicker))
+            .field(FieldInfo::new("material", FieldType::U64).hint(UiHint::AssetPicker))
+    }
+}
+
+impl Reflect for RigidBody {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("RigidBody")
+            .field(
+                FieldInfo::new("mass", FieldType::F32)
+                    .range(0.0, 10_000.0)
+                    .hint(UiHint::Drag),
+            )
+            .field(FieldInfo::new("is_kinematic", FieldType::Bool))
+    }
+}
+
+impl Reflect for Collider {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("Collider")
+            .field(FieldInfo::new("shape", FieldType::Enum(&["Box", "Sphere"])))
+            .field(
+                FieldInfo::new("half_extents", FieldType::Vec3)
+                    .range(0.0, 1_000.0)
+                    .hint(UiHint::Drag),
+            )
+            .field(
+                FieldInfo::new("radius", FieldType::F32)
+                    .range(0.0, 1_000.0)
+                    .hint(UiHint::Drag),
+            )
+    }
+}
+
+impl Reflect for Script {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("Script")
+            .field(FieldInfo::new("asset", FieldType::U64).hint(UiHint::AssetPicker))
+            .field(FieldInfo::new("enabled", FieldType::Bool))
+    }
+}
+
+impl Reflect for Light {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("Light")
+            .field(FieldInfo::new(
+                "kind",
+                FieldType::Enum(&["Directional", "Point", "Spot"]),
+            ))
+            .field(
+                FieldInfo::new("color", FieldType::Vec3)
+                    .range(0.0, 1.0)
+                    .hint(UiHint::ColorPicker),
+            )
+            .field(
+                FieldInfo::new("intensity", FieldType::F32)
+                    .range(0.0, 100.0)
+                    .hint(UiHint::Slider),
+            )
+            .field(
+                FieldInfo::new("range", FieldType::F32)
+                    .range(0.0, 1_000.0)
+                    .hint(UiHint::Drag),
+            )
+    }
+}
+
+impl Reflect for Velocity {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("Velocity")
+            .field(FieldInfo::new("linear", FieldType::Vec3).hint(UiHint::Drag))
+            .field(FieldInfo::new("angular", FieldType::Vec3).hint(UiHint::Drag))
+    }
+}
+
+impl Reflect for Layer {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("Layer").field(FieldInfo::new("mask", FieldType::U64))
+    }
+}
+
+impl Reflect for AudioSource {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("AudioSource")
+            .field(FieldInfo::new("asset", FieldType::U64).hint(UiHint::AssetPicker))
+            .field(FieldInfo::new("looping", FieldType::Bool))
+            .field(
+                FieldInfo::new("gain", FieldType::F32)
+                    .range(0.0, 4.0)
+                    .hint(UiHint::Slider),
+            )
+    }
+}
+
+impl Reflect for Animator {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("Animator")
+            .field(FieldInfo::new("clip", FieldType::U64).hint(UiHint::AssetPicker))
+            .field(FieldInfo::new("time", FieldType::F32).hint(UiHint::Drag))
+            .field(
+                FieldInfo::new("speed", FieldType::F32)
+                    .range(-10.0, 10.0)
+                    .hint(UiHint::Slider),
+            )
+            .field(FieldInfo::new("playing", FieldType::Bool))
+    }
+}
+
+impl Reflect for Sprite {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("Sprite")
+            .field(FieldInfo::new("texture", FieldType::U64).hint(UiHint::AssetPicker))
+            .field(FieldInfo::new("uv_rect", FieldType::Vec4).range(0.0, 1.0))
+            .field(FieldInfo::new("size", FieldType::Vec2).hint(UiHint::Drag))
+            .field(
+                FieldInfo::new("color", FieldType::Vec4)
+                    .range(0.0, 1.0)
+                    .hint(UiHint::ColorPicker),
+            )
+    }
+}
+
+impl Reflect for Visible {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("Visible").field(FieldInfo::new("visible", FieldType::Bool))
+    }
+}
+
+impl Reflect for Parent {
+    fn component_info() -> ComponentInfo {
+        ComponentInfo::new("Parent").field(FieldInfo::new("parent", FieldType::Entity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtins_registered() {
+        let registry = ComponentRegistry::with_builtins();
+        assert_eq!(registry.len(), 13);
+        let light = registry.get("Light").unwrap();
+        let intensity = light.get_field("intensity").unwrap();
+        assert_eq!(intensity.ty, FieldType::F32);
+        assert_eq!(intensity.range, Some((0.0, 100.0)));
+        assert_eq!(intensity.hint, UiHint::Slider);
+        let parent = registry.get("Parent").unwrap();
+        assert_eq!(parent.get_field("parent").unwrap().ty, FieldType::Entity);
+    }
+
+    #[test]
+    fn register_custom_component() {
+        let mut registry = ComponentRegistry::new();
+        registry.register_info(
+            ComponentInfo::new("Health")
+                .field(FieldInfo::new("current", FieldType::F32).range(0.0, 100.0)),
+        );
+        assert!(registry.get("Health").is_some());
+        assert!(registry.get("Light").is_none());
+        let names: Vec<_> = registry.iter().map(|c| c.name).collect();
+        assert_eq!(names, vec!["Health"]);
+    }
+}
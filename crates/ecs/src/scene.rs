@@ -0,0 +1,176 @@
+//! Spawning imported glTF scenes as entities.
+
+use std::path::Path;
+
+use glam::{Mat4, Quat, Vec3};
+use worldspace_assets::{AssetError, AssetStore, ImportReport, NodeTransform};
+use worldspace_common::{EntityId, Transform};
+use worldspace_kernel::World;
+
+use crate::{ComponentStore, MaterialHandle, MeshHandle, Parent, Renderable};
+
+/// The result of `ComponentStore::import_gltf_scene`.
+#[derive(Debug, Clone)]
+pub struct SceneImport {
+    pub report: ImportReport,
+    /// Entity spawned for each glTF node, by node index; `None` for nodes
+    /// outside the default scene.
+    pub entities: Vec<Option<EntityId>>,
+    /// Entities of the scene's root nodes.
+    pub roots: Vec<EntityId>,
+}
+
+impl ComponentStore {
+    /// Import the glTF file at `path` into `assets` and spawn the nodes of
+    /// its default scene into `world`.
+    ///
+    /// Every node becomes an entity with a `Name`; nodes with a mesh get a
+    /// `Renderable` (with the default material if the mesh has none) and
+    /// child nodes a `Parent`. Since transforms are not propagated through
+    /// `Parent`, each entity is spawned with its world transform, composed
+    /// down the node hierarchy.
+    pub fn import_gltf_scene(
+        &mut self,
+        world: &mut World,
+        assets: &mut AssetStore,
+        path: impl AsRef<Path>,
+    ) -> Result<SceneImport, AssetError> {
+        let (report, graph) = assets.import_gltf_scene(path)?;
+        let mut entities = vec![None; graph.nodes.len()];
+        let mut world_matrices = vec![Mat4::IDENTITY; graph.nodes.len()];
+        let mut parents = vec![None; graph.nodes.len()];
+        for (i, node) in graph.nodes.iter().enumerate() {
+            for &child in &node.children {
+                parents[child] = Some(i);
+            }
+        }
+
+        for i in graph.hierarchy_order() {
+            let node = &graph.nodes[i];
+            let parent = parents[i];
+            let parent_matrix = parent.map_or(Mat4::IDENTITY, |p| world_matrices[p]);
+            world_matrices[i] = parent_matrix * local_matrix(node.transform);
+            let (scale, rotation, position) = world_matrices[i].to_scale_rotation_translation();
+            let entity = world.spawn(Transform {
+                position,
+                rotation,
+                scale,
+            });
+            entities[i] = Some(entity);
+
+            self.set_name(entity, node.name.clone());
+            if let Some(mesh) = node.mesh {
+                let material = match assets.get_mesh(mesh).and_then(|m| m.material) {
+                    Some(material) => material,
+                    None => assets.register_default_material(),
+                };
+                self.set_renderable(
+                    entity,
+                    Renderable {
+                        mesh: MeshHandle(mesh.0),
+                        material: MaterialHandle(material.0),
+                    },
+                );
+            }
+            if let Some(parent) = parent.and_then(|p| entities[p]) {
+                self.set_parent(entity, Parent(parent));
+            }
+        }
+
+        let roots = graph.roots.iter().filter_map(|&i| entities[i]).collect();
+        Ok(SceneImport {
+            report,
+            entities,
+            roots,
+        })
+    }
+}
+
+fn local_matrix(transform: NodeTransform) -> Mat4 {
+    match transform {
+        NodeTransform::Trs {
+            translation,
+            rotation,
+            scale,
+        } => Mat4::from_scale_rotation_translation(
+            Vec3::from_array(scale),
+            Quat::from_array(rotation),
+            Vec3::from_array(translation),
+        ),
+        NodeTransform::Matrix(matrix) => Mat4::from_cols_array(&matrix),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRIANGLE_URI: &str = "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAABAAIAAAA=";
+
+    #[test]
+    fn import_scene_spawns_node_hierarchy() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scene.gltf");
+        let gltf = format!(
+            r#"{{
+                "buffers": [{{ "uri": "{TRIANGLE_URI}", "byteLength": 44 }}],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+                    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+                ],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+                ],
+                "meshes": [{{
+                    "name": "tri",
+                    "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "indices": 1 }}]
+                }}],
+                "scenes": [{{ "nodes": [0] }}],
+                "nodes": [
+                    {{ "name": "table", "translation": [10, 0, 0], "scale": [2, 2, 2], "children": [1] }},
+                    {{ "name": "lamp", "mesh": 0, "translation": [0, 1, 0] }},
+                    {{ "name": "stray" }}
+                ]
+            }}"#
+        );
+        std::fs::write(&path, gltf).unwrap();
+
+        let mut world = World::new();
+        let mut assets = AssetStore::new();
+        let mut store = ComponentStore::new();
+        let scene = store
+            .import_gltf_scene(&mut world, &mut assets, &path)
+            .unwrap();
+
+        assert_eq!(world.entity_count(), 2);
+        assert_eq!(scene.entities[2], None);
+        let (table, lamp) = (scene.entities[0].unwrap(), scene.entities[1].unwrap());
+        assert_eq!(scene.roots, vec![table]);
+        assert_eq!(store.get_name(lamp).unwrap().0, "lamp");
+        assert_eq!(store.get_parent(lamp), Some(&Parent(table)));
+        assert_eq!(store.children(table).collect::<Vec<_>>(), vec![lamp]);
+        assert!(store.get_renderable(table).is_none());
+
+        let renderable = store.get_renderable(lamp).unwrap();
+        assert!(
+            assets
+                .get_mesh(worldspace_assets::AssetId(renderable.mesh.0))
+                .is_some()
+        );
+        assert!(
+            assets
+                .get_material(worldspace_assets::AssetId(renderable.material.0))
+                .is_some()
+        );
+
+        // The child's translation is scaled by its parent.
+        let transform = world.get(lamp).unwrap().transform;
+        assert!(
+            transform
+                .position
+                .abs_diff_eq(Vec3::new(10.0, 2.0, 0.0), 1e-5)
+        );
+        assert!(transform.scale.abs_diff_eq(Vec3::splat(2.0), 1e-5));
+    }
+}
@@ -0,0 +1,221 @@
+//! Sparse-set storage for high-churn components.
+//!
+//! Components added and removed every frame (selection highlight, streaming
+//! residency) pay O(log n) per mutation in a BTreeMap. A sparse set gives O(1)
+//! insert and remove by keeping values densely packed and swap-removing.
+//!
+//! Dense order depends on the mutation history, so anything that feeds events,
+//! persistence, or hashing must use the sorted accessors, which always yield
+//! entries in EntityId order. The set is not serializable: high-churn data is
+//! transient and should not reach persisted state.
+//!
+//! `ComponentStore` keeps the members of `TagStorage::Sparse` tags in sparse
+//! sets. Their changes are coalesced per entity and turned into events only
+//! when drained, in EntityId order, so the event stream does not depend on
+//! the order of the mutations within a frame.
+
+use std::collections::HashMap;
+use worldspace_common::EntityId;
+
+/// O(1) insert/remove component storage with deterministic sorted views.
+#[derive(Debug, Clone)]
+pub struct SparseSet<T> {
+    dense: Vec<(EntityId, T)>,
+    /// Position of each entity in `dense`. Never iterated.
+    sparse: HashMap<EntityId, usize>,
+}
+
+impl<T> SparseSet<T> {
+    pub fn new() -> Self {
+        Self {
+            dense: Vec::new(),
+            sparse: HashMap::new(),
+        }
+    }
+
+    /// Insert or replace a value. Returns the previous value, if any.
+    pub fn insert(&mut self, entity: EntityId, value: T) -> Option<T> {
+        if let Some(&index) = self.sparse.get(&entity) {
+            return Some(std::mem::replace(&mut self.dense[index].1, value));
+        }
+        self.sparse.insert(entity, self.dense.len());
+        self.dense.push((entity, value));
+        None
+    }
+
+    /// Remove a value in O(1) by swapping the last entry into its slot.
+    pub fn remove(&mut self, entity: EntityId) -> Option<T> {
+        let index = self.sparse.remove(&entity)?;
+        let (_, value) = self.dense.swap_remove(index);
+        if let Some((moved, _)) = self.dense.get(index) {
+            self.sparse.insert(*moved, index);
+        }
+        Some(value)
+    }
+
+    pub fn get(&self, entity: EntityId) -> Option<&T> {
+        self.sparse.get(&entity).map(|&i| &self.dense[i].1)
+    }
+
+    pub fn get_mut(&mut self, entity: EntityId) -> Option<&mut T> {
+        self.sparse.get(&entity).map(|&i| &mut self.dense[i].1)
+    }
+
+    pub fn contains(&self, entity: EntityId) -> bool {
+        self.sparse.contains_key(&entity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    /// Entries in dense (storage) order. Fast, but the order depends on the
+    /// insert/remove history; use `iter_sorted` where determinism matters.
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &T)> {
+        self.dense.iter().map(|(id, v)| (*id, v))
+    }
+
+    /// Entries in EntityId order.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (EntityId, &T)> {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_by_key(|(id, _)| *id);
+        entries.into_iter()
+    }
+
+    /// Remove every entry, returning them in EntityId order.
+    pub fn drain_sorted(&mut self) -> Vec<(EntityId, T)> {
+        self.sparse.clear();
+        let mut entries = std::mem::take(&mut self.dense);
+        entries.sort_by_key(|(id, _)| *id);
+        entries
+    }
+
+    pub fn clear(&mut self) {
+        self.dense.clear();
+        self.sparse.clear();
+    }
+}
+
+impl<T> Default for SparseSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Members of a sparse-storage tag and the entities whose membership
+/// changed since the last drain.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SparseTag {
+    members: SparseSet<()>,
+    /// Whether each changed entity was a member at the last drain.
+    changed: SparseSet<bool>,
+}
+
+impl SparseTag {
+    /// Add `entity`, recording the change. Returns false if it was a member.
+    pub fn insert(&mut self, entity: EntityId) -> bool {
+        if self.members.insert(entity, ()).is_some() {
+            return false;
+        }
+        self.record(entity, false);
+        true
+    }
+
+    /// Remove `entity`, recording the change. Returns false if it was not a
+    /// member.
+    pub fn remove(&mut self, entity: EntityId) -> bool {
+        if self.members.remove(entity).is_none() {
+            return false;
+        }
+        self.record(entity, true);
+        true
+    }
+
+    /// Set membership without recording a change, e.g. when replaying.
+    pub fn apply(&mut self, entity: EntityId, member: bool) {
+        if member {
+            self.members.insert(entity, ());
+        } else {
+            self.members.remove(entity);
+        }
+    }
+
+    pub fn contains(&self, entity: EntityId) -> bool {
+        self.members.contains(entity)
+    }
+
+    /// Members in EntityId order.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = EntityId> {
+        self.members.iter_sorted().map(|(id, _)| id)
+    }
+
+    /// Net membership changes since the last drain, in EntityId order: true
+    /// for entities that joined, false for those that left. An entity added
+    /// and removed again in between reports nothing.
+    pub fn drain_changes(&mut self) -> Vec<(EntityId, bool)> {
+        self.changed
+            .drain_sorted()
+            .into_iter()
+            .filter_map(|(id, was_member)| {
+                let member = self.members.contains(id);
+                (member != was_member).then_some((id, member))
+            })
+            .collect()
+    }
+
+    fn record(&mut self, entity: EntityId, was_member: bool) {
+        if !self.changed.contains(entity) {
+            self.changed.insert(entity, was_member);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_remove_keeps_index_consistent() {
+        let mut set = SparseSet::new();
+        let ids: Vec<EntityId> = (0..5).map(|_| EntityId::new()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            assert!(set.insert(*id, i).is_none());
+        }
+        assert_eq!(set.insert(ids[2], 20), Some(2));
+
+        assert_eq!(set.remove(ids[0]), Some(0));
+        assert_eq!(set.remove(ids[0]), None);
+        assert_eq!(set.len(), 4);
+        // The entry swapped into slot 0 must still resolve.
+        assert_eq!(set.get(ids[4]), Some(&4));
+        assert_eq!(set.get(ids[2]), Some(&20));
+        assert!(!set.contains(ids[0]));
+    }
+
+    #[test]
+    fn sorted_views_independent_of_history() {
+        let ids: Vec<EntityId> = (0..6).map(|_| EntityId::new()).collect();
+        let mut a = SparseSet::new();
+        let mut b = SparseSet::new();
+        for id in &ids {
+            a.insert(*id, ());
+        }
+        for id in ids.iter().rev() {
+            b.insert(*id, ());
+        }
+        a.remove(ids[1]);
+        b.remove(ids[1]);
+
+        let a_sorted: Vec<EntityId> = a.iter_sorted().map(|(id, _)| id).collect();
+        let b_sorted: Vec<EntityId> = b.iter_sorted().map(|(id, _)| id).collect();
+        assert_eq!(a_sorted, b_sorted);
+
+        let drained: Vec<EntityId> = a.drain_sorted().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(drained, b_sorted);
+        assert!(a.is_empty());
+    }
+}
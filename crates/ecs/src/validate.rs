@@ -0,0 +1,216 @@
+//! Validation hooks run before components enter the store.
+//!
+//! Validators see each value passed to a setter and may accept it, replace it
+//! with a clamped value, or reject it. Clamps and rejections are recorded as
+//! `ComponentEvent::ValidationFailed`; rejected values are never stored.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{Animator, AudioSource, Collider, Light, RigidBody, Sprite, Velocity};
+
+/// Outcome of validating a component value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Validation<T> {
+    Accept,
+    /// Store the given value instead, for the given reason.
+    Clamp(T, String),
+    /// Do not store the value, for the given reason.
+    Reject(String),
+}
+
+/// A validator for component type `T`.
+pub type Validator<T> = Arc<dyn Fn(&T) -> Validation<T> + Send + Sync>;
+
+/// Per-component validator lists, run in registration order.
+#[derive(Clone, Default)]
+pub struct Validators {
+    rigid_bodies: Vec<Validator<RigidBody>>,
+    colliders: Vec<Validator<Collider>>,
+    lights: Vec<Validator<Light>>,
+    velocities: Vec<Validator<Velocity>>,
+    audio_sources: Vec<Validator<AudioSource>>,
+    animators: Vec<Validator<Animator>>,
+    sprites: Vec<Validator<Sprite>>,
+}
+
+impl fmt::Debug for Validators {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Validators")
+            .field("rigid_bodies", &self.rigid_bodies.len())
+            .field("colliders", &self.colliders.len())
+            .field("lights", &self.lights.len())
+            .field("velocities", &self.velocities.len())
+            .field("audio_sources", &self.audio_sources.len())
+            .field("animators", &self.animators.len())
+            .field("sprites", &self.sprites.len())
+            .finish()
+    }
+}
+
+/// Component types that support validators.
+pub trait Validate: Sized + 'static {
+    /// Component name used in `ValidationFailed` events.
+    const NAME: &'static str;
+
+    fn validators(all: &Validators) -> &[Validator<Self>];
+    fn validators_mut(all: &mut Validators) -> &mut Vec<Validator<Self>>;
+}
+
+macro_rules! impl_validate {
+    ($ty:ty, $name:literal, $field:ident) => {
+        impl Validate for $ty {
+            const NAME: &'static str = $name;
+
+            fn validators(all: &Validators) -> &[Validator<Self>] {
+                &all.$field
+            }
+
+            fn validators_mut(all: &mut Validators) -> &mut Vec<Validator<Self>> {
+                &mut all.$field
+            }
+        }
+    };
+}
+
+impl_validate!(RigidBody, "RigidBody", rigid_bodies);
+impl_validate!(Collider, "Collider", colliders);
+impl_validate!(Light, "Light", lights);
+impl_validate!(Velocity, "Velocity", velocities);
+impl_validate!(AudioSource, "AudioSource", audio_sources);
+impl_validate!(Animator, "Animator", animators);
+impl_validate!(Sprite, "Sprite", sprites);
+
+impl Validators {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validators that keep NaN, infinite, and non-physical values out of
+    /// deterministic state.
+    pub fn standard() -> Self {
+        let mut v = Self::new();
+        v.add::<RigidBody>(|body| {
+            if !body.mass.is_finite() || body.mass <= 0.0 {
+                Validation::Reject(format!("mass must be finite and > 0, got {}", body.mass))
+            } else {
+                Validation::Accept
+            }
+        });
+        v.add::<Collider>(|collider| match *collider {
+            Collider::Box { half_extents } => {
+                if half_extents.iter().any(|e| !e.is_finite()) {
+                    Validation::Reject("half_extents must be finite".into())
+                } else if half_extents.iter().any(|e| *e < 0.0) {
+                    Validation::Clamp(
+                        Collider::Box {
+                            half_extents: half_extents.map(f32::abs),
+                        },
+                        "negative half_extents".into(),
+                    )
+                } else {
+                    Validation::Accept
+                }
+            }
+            Collider::Sphere { radius } => {
+                if !radius.is_finite() {
+                    Validation::Reject("radius must be finite".into())
+                } else if radius < 0.0 {
+                    Validation::Clamp(
+                        Collider::Sphere {
+                            radius: radius.abs(),
+                        },
+                        "negative radius".into(),
+                    )
+                } else {
+                    Validation::Accept
+                }
+            }
+        });
+        v.add::<Light>(|light| {
+            let finite = light.color.iter().all(|c| c.is_finite())
+                && light.intensity.is_finite()
+                && light.range.is_finite();
+            if !finite {
+                Validation::Reject("light values must be finite".into())
+            } else if light.intensity < 0.0 || light.range < 0.0 {
+                Validation::Clamp(
+                    Light {
+                        intensity: light.intensity.max(0.0),
+                        range: light.range.max(0.0),
+                        ..*light
+                    },
+                    "negative intensity or range".into(),
+                )
+            } else {
+                Validation::Accept
+            }
+        });
+        v.add::<Velocity>(|velocity| {
+            if velocity.linear.is_finite() && velocity.angular.is_finite() {
+                Validation::Accept
+            } else {
+                Validation::Reject("velocity must be finite".into())
+            }
+        });
+        v.add::<AudioSource>(|source| {
+            if !source.gain.is_finite() {
+                Validation::Reject("gain must be finite".into())
+            } else if source.gain < 0.0 {
+                Validation::Clamp(
+                    AudioSource {
+                        gain: 0.0,
+                        ..*source
+                    },
+                    "negative gain".into(),
+                )
+            } else {
+                Validation::Accept
+            }
+        });
+        v.add::<Animator>(|animator| {
+            if animator.time.is_finite() && animator.speed.is_finite() {
+                Validation::Accept
+            } else {
+                Validation::Reject("time and speed must be finite".into())
+            }
+        });
+        v.add::<Sprite>(|sprite| {
+            let finite = sprite
+                .uv_rect
+                .iter()
+                .chain(&sprite.size)
+                .chain(&sprite.color)
+                .all(|x| x.is_finite());
+            if !finite {
+                Validation::Reject("sprite values must be finite".into())
+            } else if sprite.size.iter().any(|s| *s < 0.0) {
+                Validation::Clamp(
+                    Sprite {
+                        size: sprite.size.map(|s| s.max(0.0)),
+                        ..*sprite
+                    },
+                    "negative size".into(),
+                )
+            } else {
+                Validation::Accept
+            }
+        });
+        v
+    }
+
+    /// Register a validator for component type `T`.
+    pub fn add<T: Validate>(&mut self, f: impl Fn(&T) -> Validation<T> + Send + Sync + 'static) {
+        T::validators_mut(self).push(Arc::new(f));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rigid_bodies.is_empty()
+            && self.colliders.is_empty()
+            && self.lights.is_empty()
+            && self.velocities.is_empty()
+            && self.audio_sources.is_empty()
+            && self.animators.is_empty()
+            && self.sprites.is_empty()
+    }
+}
@@ -6,4 +6,4 @@
 
 pub mod world;
 
-pub use world::{EntityData, World, WorldEvent};
+pub use world::{ActivationEvent, EntityData, TICK_SECONDS, World, WorldEvent};
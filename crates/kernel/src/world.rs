@@ -1,7 +1,11 @@
+use glam::{Quat, Vec3};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use worldspace_common::{EntityId, Transform};
 
+/// Simulated time covered by one tick, in seconds (fixed 60 Hz timestep).
+pub const TICK_SECONDS: f32 = 1.0 / 60.0;
+
 /// An event record produced by every mutation to the world.
 ///
 /// The event log is the foundation for persistence, replay, and undo/redo.
@@ -22,6 +26,17 @@ pub enum WorldEvent {
     Stepped { tick: u64, seed: u64 },
 }
 
+/// A change in whether an entity is ticked, reported by `World::set_active`.
+///
+/// Activation is not an edit: it is not logged and does not replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivationEvent {
+    /// Entity left the active set and is no longer ticked.
+    Frozen(EntityId),
+    /// Entity rejoined the active set and is ticked again.
+    Thawed(EntityId),
+}
+
 /// The authoritative world state.
 ///
 /// All mutations go through explicit operations. The kernel owns the truth;
@@ -39,6 +54,9 @@ pub struct World {
     /// Append-only event log of all mutations.
     #[serde(skip)]
     event_log: Vec<WorldEvent>,
+    /// Resident entities outside the active set; schedulers skip them.
+    #[serde(skip)]
+    frozen: BTreeSet<EntityId>,
 }
 
 /// Per-entity data stored in the world.
@@ -111,6 +129,7 @@ impl World {
 
     /// Remove an entity. Returns the data if it existed.
     pub fn despawn(&mut self, id: EntityId) -> Option<EntityData> {
+        self.frozen.remove(&id);
         let data = self.entities.remove(&id);
         if let Some(ref d) = data {
             self.event_log.push(WorldEvent::Despawned {
@@ -121,6 +140,69 @@ impl World {
         data
     }
 
+    /// Insert an entity paged in from storage. Unlike `spawn_with_id`, no
+    /// event is logged: the entity already exists in the world's history.
+    pub fn page_in(&mut self, id: EntityId, data: EntityData) {
+        self.entities.insert(id, data);
+    }
+
+    /// Remove an entity paged out to storage without logging a despawn.
+    /// Returns the data if it was resident.
+    pub fn page_out(&mut self, id: EntityId) -> Option<EntityData> {
+        self.frozen.remove(&id);
+        self.entities.remove(&id)
+    }
+
+    /// Restrict ticking to `active`, e.g. the entities in a streamer's
+    /// active cells: every other resident entity is frozen. Entities spawned
+    /// or paged in later start active. Returns the entities that froze or
+    /// thawed, frozen first, each in EntityId order.
+    pub fn set_active(&mut self, active: &BTreeSet<EntityId>) -> Vec<ActivationEvent> {
+        let frozen: BTreeSet<EntityId> = self
+            .entities
+            .keys()
+            .filter(|id| !active.contains(id))
+            .copied()
+            .collect();
+        let events = frozen
+            .difference(&self.frozen)
+            .map(|id| ActivationEvent::Frozen(*id))
+            .chain(
+                self.frozen
+                    .difference(&frozen)
+                    .map(|id| ActivationEvent::Thawed(*id)),
+            )
+            .collect();
+        self.frozen = frozen;
+        events
+    }
+
+    /// Thaw every frozen entity, so the whole world ticks again.
+    pub fn clear_active(&mut self) -> Vec<ActivationEvent> {
+        std::mem::take(&mut self.frozen)
+            .into_iter()
+            .map(ActivationEvent::Thawed)
+            .collect()
+    }
+
+    /// Whether entity `id` is resident and not frozen.
+    pub fn is_active(&self, id: EntityId) -> bool {
+        self.entities.contains_key(&id) && !self.frozen.contains(&id)
+    }
+
+    /// Resident entities that are not frozen, in EntityId order.
+    pub fn active_entities(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.entities
+            .keys()
+            .filter(|id| !self.frozen.contains(id))
+            .copied()
+    }
+
+    /// Frozen entities, in EntityId order.
+    pub fn frozen_entities(&self) -> &BTreeSet<EntityId> {
+        &self.frozen
+    }
+
     /// Get a reference to entity data.
     pub fn get(&self, id: EntityId) -> Option<&EntityData> {
         self.entities.get(&id)
@@ -144,6 +226,24 @@ impl World {
         }
     }
 
+    /// Integrate linear and angular velocity into an entity's transform over `dt`
+    /// seconds. The change goes through `set_transform`, so it is logged and replayed.
+    ///
+    /// Angular velocity is an axis scaled by radians per second. Returns false if
+    /// the entity does not exist or both velocities are zero (nothing is logged).
+    pub fn integrate(&mut self, id: EntityId, linear: Vec3, angular: Vec3, dt: f32) -> bool {
+        if linear == Vec3::ZERO && angular == Vec3::ZERO {
+            return false;
+        }
+        let Some(data) = self.entities.get(&id) else {
+            return false;
+        };
+        let mut new = data.transform;
+        new.position += linear * dt;
+        new.rotation = (Quat::from_scaled_axis(angular * dt) * new.rotation).normalize();
+        self.set_transform(id, new)
+    }
+
     /// Advance the simulation by one tick.
     ///
     /// Uses a deterministic seed that increments each step. Given the same
@@ -251,6 +351,69 @@ mod tests {
         assert_eq!(w.entity_count(), 0);
     }
 
+    #[test]
+    fn paging_logs_no_events() {
+        let mut w = World::new();
+        let id = w.spawn(Transform::default());
+        w.drain_events();
+
+        let data = w.page_out(id).unwrap();
+        assert_eq!(w.entity_count(), 0);
+        w.page_in(id, data);
+        assert!(w.get(id).is_some());
+        assert!(w.events().is_empty());
+    }
+
+    #[test]
+    fn activation_freezes_and_thaws() {
+        let mut w = World::new();
+        let a = w.spawn(Transform::default());
+        let b = w.spawn(Transform::default());
+        w.drain_events();
+
+        let events = w.set_active(&BTreeSet::from([a]));
+        assert_eq!(events, vec![ActivationEvent::Frozen(b)]);
+        assert!(w.is_active(a) && !w.is_active(b));
+        assert!(w.set_active(&BTreeSet::from([a])).is_empty());
+
+        let c = w.spawn(Transform::default());
+        assert!(w.is_active(c));
+        let events = w.set_active(&BTreeSet::from([b]));
+        assert!(events.contains(&ActivationEvent::Thawed(b)));
+        assert!(events.contains(&ActivationEvent::Frozen(a)));
+        assert!(events.contains(&ActivationEvent::Frozen(c)));
+        assert_eq!(w.active_entities().collect::<Vec<_>>(), vec![b]);
+
+        w.page_out(a);
+        assert!(!w.frozen_entities().contains(&a));
+        assert_eq!(w.clear_active(), vec![ActivationEvent::Thawed(c)]);
+        assert_eq!(w.events().len(), 1);
+    }
+
+    #[test]
+    fn integrate_logs_transform_update() {
+        let mut w = World::new();
+        let id = w.spawn(Transform::default());
+        w.drain_events();
+
+        assert!(w.integrate(id, Vec3::new(2.0, 0.0, 0.0), Vec3::ZERO, 0.5));
+        assert_eq!(
+            w.get(id).unwrap().transform.position,
+            Vec3::new(1.0, 0.0, 0.0)
+        );
+        assert!(!w.integrate(id, Vec3::ZERO, Vec3::ZERO, 0.5));
+        assert_eq!(w.events().len(), 1);
+
+        let replayed = World::replay(&[
+            WorldEvent::Spawned {
+                id,
+                transform: Transform::default(),
+            },
+            w.events()[0].clone(),
+        ]);
+        assert_eq!(replayed.get(id).unwrap().transform.position.x, 1.0);
+    }
+
     #[test]
     fn step_increments_tick() {
         let mut w = World::new();
@@ -0,0 +1,301 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use worldspace_ecs::{Collider, ComponentStore, LightKind, Tag};
+
+/// A content-addressed snapshot of all authored component data.
+///
+/// Mirrors the kernel `Snapshot`: the hash is computed over every component
+/// map in canonical (BTreeMap) order, so restoring a verified snapshot rolls
+/// back names, renderables, physics stubs, and the rest alongside transforms.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentSnapshot {
+    /// Component data at capture time. Pending events are not captured.
+    pub components: ComponentStore,
+    /// SHA-256 hash for integrity verification (hex encoded).
+    pub hash: String,
+}
+
+impl ComponentSnapshot {
+    /// Create a snapshot from the current component state. Entities tagged
+    /// `NoSerialize` are left out.
+    pub fn capture(store: &ComponentStore) -> Self {
+        let mut components = store.clone();
+        let skipped: Vec<_> = store.with_tag(Tag::NoSerialize).collect();
+        for id in skipped {
+            components.remove_entity(id);
+        }
+        components.drain_events();
+        let hash = Self::compute_hash(&components);
+        Self { components, hash }
+    }
+
+    /// Verify the snapshot integrity by recomputing the hash.
+    pub fn verify(&self) -> bool {
+        self.hash == Self::compute_hash(&self.components)
+    }
+
+    /// Restore a component store from this snapshot. The restored store has
+    /// no pending events since restore is not an authoring operation.
+    pub fn restore(&self) -> ComponentStore {
+        let mut store = self.components.clone();
+        store.drain_events();
+        store
+    }
+
+    /// Maps added after the first component schema are hashed only when
+    /// non-empty, so snapshots written before they existed keep their hash.
+    fn compute_hash(store: &ComponentStore) -> String {
+        let mut hasher = Sha256::new();
+        let floats = |hasher: &mut Sha256, values: &[f32]| {
+            for v in values {
+                hasher.update(v.to_le_bytes());
+            }
+        };
+
+        hasher.update(b"names");
+        for (id, name) in store.names() {
+            hasher.update(id.0.as_bytes());
+            hasher.update((name.0.len() as u64).to_le_bytes());
+            hasher.update(name.0.as_bytes());
+        }
+        hasher.update(b"renderables");
+        for (id, r) in store.renderables() {
+            hasher.update(id.0.as_bytes());
+            hasher.update(r.mesh.0.to_le_bytes());
+            hasher.update(r.material.0.to_le_bytes());
+        }
+        hasher.update(b"rigid_bodies");
+        for (id, body) in store.rigid_bodies() {
+            hasher.update(id.0.as_bytes());
+            floats(&mut hasher, &[body.mass]);
+            hasher.update([body.is_kinematic as u8]);
+        }
+        hasher.update(b"colliders");
+        for (id, collider) in store.colliders() {
+            hasher.update(id.0.as_bytes());
+            match collider {
+                Collider::Box { half_extents } => {
+                    hasher.update([0u8]);
+                    floats(&mut hasher, half_extents);
+                }
+                Collider::Sphere { radius } => {
+                    hasher.update([1u8]);
+                    floats(&mut hasher, &[*radius]);
+                }
+            }
+        }
+        hasher.update(b"scripts");
+        for (id, script) in store.scripts() {
+            hasher.update(id.0.as_bytes());
+            hasher.update(script.asset.0.to_le_bytes());
+            hasher.update([script.enabled as u8]);
+        }
+        if !store.audio_sources().is_empty() {
+            hasher.update(b"audio_sources");
+            for (id, source) in store.audio_sources() {
+                hasher.update(id.0.as_bytes());
+                hasher.update(source.asset.0.to_le_bytes());
+                hasher.update([source.looping as u8]);
+                floats(&mut hasher, &[source.gain]);
+            }
+        }
+        if !store.animators().is_empty() {
+            hasher.update(b"animators");
+            for (id, animator) in store.animators() {
+                hasher.update(id.0.as_bytes());
+                hasher.update(animator.clip.0.to_le_bytes());
+                floats(&mut hasher, &[animator.time, animator.speed]);
+                hasher.update([animator.playing as u8]);
+            }
+        }
+        if !store.sprites().is_empty() {
+            hasher.update(b"sprites");
+            for (id, sprite) in store.sprites() {
+                hasher.update(id.0.as_bytes());
+                hasher.update(sprite.texture.0.to_le_bytes());
+                floats(&mut hasher, &sprite.uv_rect);
+                floats(&mut hasher, &sprite.size);
+                floats(&mut hasher, &sprite.color);
+            }
+        }
+        hasher.update(b"lights");
+        for (id, light) in store.lights() {
+            hasher.update(id.0.as_bytes());
+            hasher.update([match light.kind {
+                LightKind::Directional => 0u8,
+                LightKind::Point => 1,
+                LightKind::Spot => 2,
+            }]);
+            floats(&mut hasher, &light.color);
+            floats(&mut hasher, &[light.intensity, light.range]);
+        }
+        hasher.update(b"velocities");
+        for (id, velocity) in store.velocities() {
+            hasher.update(id.0.as_bytes());
+            floats(&mut hasher, &velocity.linear.to_array());
+            floats(&mut hasher, &velocity.angular.to_array());
+        }
+        if !store.layers().is_empty() {
+            hasher.update(b"layers");
+            for (id, layer) in store.layers() {
+                hasher.update(id.0.as_bytes());
+                hasher.update(layer.0.to_le_bytes());
+            }
+        }
+        if !store.visibility().is_empty() {
+            hasher.update(b"visibility");
+            for (id, visible) in store.visibility() {
+                hasher.update(id.0.as_bytes());
+                hasher.update([visible.0 as u8]);
+            }
+        }
+        if !store.parents().is_empty() {
+            hasher.update(b"parents");
+            for (id, parent) in store.parents() {
+                hasher.update(id.0.as_bytes());
+                hasher.update(parent.0.0.as_bytes());
+            }
+        }
+        hasher.update(b"tags");
+        for (tag, members) in store.tags() {
+            hasher.update([*tag as u8]);
+            hasher.update((members.len() as u64).to_le_bytes());
+            for id in members {
+                hasher.update(id.0.as_bytes());
+            }
+        }
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+    use worldspace_common::EntityId;
+    use worldspace_ecs::{MaterialHandle, MeshHandle, Renderable, RigidBody, Tag};
+
+    /// Hash of `first_schema_store`, as written by the first component schema
+    /// (names, renderables, rigid bodies, colliders, scripts, lights,
+    /// velocities and tags).
+    const FIRST_SCHEMA_HASH: &str =
+        "db870b2f000fdeac1105c17a0c2dd9199c8849a7fa7bd503ef06afffc2139f41";
+
+    fn first_schema_store() -> (ComponentStore, EntityId) {
+        let mut store = ComponentStore::new();
+        let id = EntityId(Uuid::from_bytes([0x11; 16]));
+        store.set_name(id, "Crate".into());
+        store.set_renderable(
+            id,
+            Renderable {
+                mesh: MeshHandle(1),
+                material: MaterialHandle(2),
+            },
+        );
+        store.set_collider(id, Collider::Sphere { radius: 1.0 });
+        store.add_tag(id, Tag::Static);
+        (store, id)
+    }
+
+    fn authored_store() -> (ComponentStore, EntityId) {
+        let mut store = ComponentStore::new();
+        let id = EntityId::new();
+        store.set_name(id, "Crate".into());
+        store.set_renderable(
+            id,
+            Renderable {
+                mesh: MeshHandle(1),
+                material: MaterialHandle(2),
+            },
+        );
+        store.set_rigid_body(id, RigidBody::default());
+        store.set_collider(id, Collider::Sphere { radius: 1.0 });
+        store.add_tag(id, Tag::Static);
+        (store, id)
+    }
+
+    #[test]
+    fn capture_and_verify() {
+        let (store, _) = authored_store();
+        let snap = ComponentSnapshot::capture(&store);
+        assert!(snap.verify());
+        assert!(snap.components.events().is_empty());
+        assert_eq!(snap.hash, ComponentSnapshot::capture(&store).hash);
+    }
+
+    #[test]
+    fn no_serialize_entities_are_not_captured() {
+        let (mut store, id) = authored_store();
+        let scratch = EntityId::new();
+        store.set_name(scratch, "Preview".into());
+        store.add_tag(scratch, Tag::NoSerialize);
+
+        let snap = ComponentSnapshot::capture(&store);
+        assert!(snap.verify());
+        assert!(snap.components.get_name(scratch).is_none());
+        assert_eq!(snap.components.with_tag(Tag::NoSerialize).count(), 0);
+        assert_eq!(snap.components.get_name(id).unwrap().0, "Crate");
+    }
+
+    #[test]
+    fn first_schema_hash_is_stable() {
+        let (store, _) = first_schema_store();
+        assert_eq!(ComponentSnapshot::capture(&store).hash, FIRST_SCHEMA_HASH);
+    }
+
+    #[test]
+    fn snapshots_without_newer_maps_load_and_verify() {
+        let fixture = format!(
+            r#"{{
+                "components": {{
+                    "names": {{ "11111111-1111-1111-1111-111111111111": "Crate" }},
+                    "renderables": {{
+                        "11111111-1111-1111-1111-111111111111": {{ "mesh": 1, "material": 2 }}
+                    }},
+                    "rigid_bodies": {{}},
+                    "colliders": {{
+                        "11111111-1111-1111-1111-111111111111": {{ "Sphere": {{ "radius": 1.0 }} }}
+                    }},
+                    "scripts": {{}},
+                    "lights": {{}},
+                    "velocities": {{}},
+                    "tags": {{ "Static": ["11111111-1111-1111-1111-111111111111"] }}
+                }},
+                "hash": "{FIRST_SCHEMA_HASH}"
+            }}"#
+        );
+        let snap: ComponentSnapshot = serde_json::from_str(&fixture).unwrap();
+        assert!(snap.verify());
+
+        let (expected, id) = first_schema_store();
+        let restored = snap.restore();
+        assert_eq!(restored.get_name(id), expected.get_name(id));
+        assert!(restored.has_tag(id, Tag::Static));
+        assert!(restored.layers().is_empty());
+        assert!(restored.parents().is_empty());
+    }
+
+    #[test]
+    fn corruption_detected() {
+        let (store, id) = authored_store();
+        let mut snap = ComponentSnapshot::capture(&store);
+        snap.components
+            .set_collider(id, Collider::Sphere { radius: 9.0 });
+        assert!(!snap.verify());
+    }
+
+    #[test]
+    fn restore_rolls_back_authored_state() {
+        let (mut store, id) = authored_store();
+        let snap = ComponentSnapshot::capture(&store);
+
+        store.remove_entity(id);
+        assert!(store.get_name(id).is_none());
+
+        let restored = snap.restore();
+        assert_eq!(restored.get_name(id).unwrap().0, "Crate");
+        assert!(restored.get_rigid_body(id).is_some());
+        assert!(restored.has_tag(id, Tag::Static));
+        assert!(restored.events().is_empty());
+    }
+}
@@ -6,12 +6,36 @@
 //! - Rollback reconstructs prior state via snapshot + log replay.
 //! - File-backed persistence uses CBOR + zstd compression with hash chain integrity.
 //! - Schema versioning ensures fail-closed on mismatch.
+//! - Component data is persisted alongside world segments under its own schema version.
+//! - Named branches fork from a snapshot and keep their own snapshots and event segments.
+//! - Concurrent edit logs merge deterministically (`EditLog`, last-writer-wins per field).
+//! - Streamed cells are saved before their entities are paged out (`CellStreamer`).
 
+mod component_snapshot;
+#[cfg(feature = "encryption")]
+mod encryption;
+mod merge;
+#[cfg(feature = "signing")]
+mod signing;
 mod snapshot;
 pub mod store;
+mod streaming;
 
-pub use snapshot::{EventLog, Snapshot, SnapshotStore};
-pub use store::{StoreError, WorldStore};
+pub use component_snapshot::ComponentSnapshot;
+#[cfg(feature = "encryption")]
+pub use encryption::EncryptionKey;
+pub use merge::{EditLog, Stamp};
+#[cfg(feature = "signing")]
+pub use signing::{SigningKey, VerifyingKey};
+pub use snapshot::{
+    DeltaSnapshot, EventLog, Snapshot, SnapshotDiff, SnapshotStore, TransformDelta,
+};
+pub use store::{
+    BranchMeta, Checkpoint, CompactReport, MAIN_BRANCH, MigrationReport, Migrations, RepairReport,
+    ReplayCursor, ReplayIter, RetentionPolicy, SchemaKind, StoreError, StoreMigration, SyncReport,
+    WorldStore,
+};
+pub use streaming::CellStreamer;
 
 pub fn crate_info() -> &'static str {
     "worldspace-persist v0.1.0"
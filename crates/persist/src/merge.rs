@@ -0,0 +1,297 @@
+//! Deterministic merging of concurrent edit logs.
+//!
+//! Each replica records its `WorldEvent`s into an `EditLog`, stamping them
+//! with a Lamport clock. Logs merge by union, and the merged state resolves
+//! every entity field as a last-writer-wins register: the op with the
+//! highest `Stamp` wins, ties broken by replica ID. Because resolution only
+//! compares stamps, merging is commutative, associative and idempotent, so
+//! two users editing offline copies of a world converge whatever order they
+//! exchange logs in.
+//!
+//! Registers are per entity: whether it exists, and its position, rotation
+//! and scale separately, so one user moving an entity while another scales
+//! it keeps both edits. A despawn does not lose to a concurrent transform
+//! edit, only to a later spawn of the same ID.
+
+use std::collections::BTreeMap;
+
+use glam::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use worldspace_common::{EntityId, Transform};
+use worldspace_kernel::{World, WorldEvent};
+
+/// A Lamport timestamp, ordered by counter and then replica ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Stamp {
+    pub counter: u64,
+    pub replica: u64,
+}
+
+/// One replica's stamped edits, merged with other replicas' logs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditLog {
+    replica: u64,
+    clock: u64,
+    ops: BTreeMap<Stamp, WorldEvent>,
+}
+
+impl EditLog {
+    /// Create an empty log for `replica`, which must be unique among the
+    /// replicas whose logs are merged.
+    pub fn new(replica: u64) -> Self {
+        Self {
+            replica,
+            clock: 0,
+            ops: BTreeMap::new(),
+        }
+    }
+
+    pub fn replica(&self) -> u64 {
+        self.replica
+    }
+
+    /// Stamp and record local edits. `Stepped` events are not edits and are
+    /// skipped.
+    pub fn record(&mut self, events: &[WorldEvent]) {
+        for event in events {
+            if matches!(event, WorldEvent::Stepped { .. }) {
+                continue;
+            }
+            self.clock += 1;
+            let stamp = Stamp {
+                counter: self.clock,
+                replica: self.replica,
+            };
+            self.ops.insert(stamp, event.clone());
+        }
+    }
+
+    /// Add every op of `other` not yet in this log. Later local edits are
+    /// stamped after everything merged so far.
+    pub fn merge(&mut self, other: &EditLog) {
+        for (stamp, event) in &other.ops {
+            self.ops.entry(*stamp).or_insert_with(|| event.clone());
+        }
+        self.clock = self.clock.max(other.clock);
+    }
+
+    /// All ops in stamp order.
+    pub fn ops(&self) -> impl Iterator<Item = (&Stamp, &WorldEvent)> {
+        self.ops.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Bring `world` to the merged state: every entity the log touches gets
+    /// its winning field values, and entities whose winning op is a despawn
+    /// are removed. Entities the log never mentions are left alone.
+    pub fn apply(&self, world: &mut World) {
+        for (id, registers) in self.resolve() {
+            match registers.alive.get() {
+                Some(false) => {
+                    world.despawn(id);
+                    continue;
+                }
+                None if world.get(id).is_none() => continue,
+                _ => {}
+            }
+            let current = world.get(id).map(|e| e.transform);
+            let base = current.unwrap_or_default();
+            let merged = Transform {
+                position: registers.position.get().unwrap_or(base.position),
+                rotation: registers.rotation.get().unwrap_or(base.rotation),
+                scale: registers.scale.get().unwrap_or(base.scale),
+            };
+            match current {
+                None => world.spawn_with_id(id, merged),
+                Some(current) if current != merged => {
+                    world.set_transform(id, merged);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn resolve(&self) -> BTreeMap<EntityId, Registers> {
+        let mut entities: BTreeMap<EntityId, Registers> = BTreeMap::new();
+        for (&stamp, event) in &self.ops {
+            match event {
+                WorldEvent::Spawned { id, transform } => {
+                    let registers = entities.entry(*id).or_default();
+                    registers.alive.write(stamp, true);
+                    registers.position.write(stamp, transform.position);
+                    registers.rotation.write(stamp, transform.rotation);
+                    registers.scale.write(stamp, transform.scale);
+                }
+                WorldEvent::Despawned { id, .. } => {
+                    entities.entry(*id).or_default().alive.write(stamp, false);
+                }
+                WorldEvent::TransformUpdated { id, old, new } => {
+                    let registers = entities.entry(*id).or_default();
+                    if old.position != new.position {
+                        registers.position.write(stamp, new.position);
+                    }
+                    if old.rotation != new.rotation {
+                        registers.rotation.write(stamp, new.rotation);
+                    }
+                    if old.scale != new.scale {
+                        registers.scale.write(stamp, new.scale);
+                    }
+                }
+                WorldEvent::Stepped { .. } => {}
+            }
+        }
+        entities
+    }
+}
+
+/// A last-writer-wins register.
+#[derive(Debug, Clone, Copy)]
+struct Lww<T>(Option<(Stamp, T)>);
+
+impl<T> Default for Lww<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T: Copy> Lww<T> {
+    fn write(&mut self, stamp: Stamp, value: T) {
+        if self.0.is_none_or(|(current, _)| stamp > current) {
+            self.0 = Some((stamp, value));
+        }
+    }
+
+    fn get(&self) -> Option<T> {
+        self.0.map(|(_, value)| value)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Registers {
+    alive: Lww<bool>,
+    position: Lww<Vec3>,
+    rotation: Lww<Quat>,
+    scale: Lww<Vec3>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Edit two offline copies of `base` and merge their logs in both orders.
+    fn merge_both_ways(
+        base: &World,
+        edit_a: impl FnOnce(&mut World),
+        edit_b: impl FnOnce(&mut World),
+    ) -> (World, World) {
+        let (mut a, mut b) = (base.clone(), base.clone());
+        let (mut log_a, mut log_b) = (EditLog::new(1), EditLog::new(2));
+        edit_a(&mut a);
+        log_a.record(&a.drain_events());
+        edit_b(&mut b);
+        log_b.record(&b.drain_events());
+
+        let mut ab = log_a.clone();
+        ab.merge(&log_b);
+        let mut ba = log_b.clone();
+        ba.merge(&log_a);
+        ab.apply(&mut a);
+        ba.apply(&mut b);
+        (a, b)
+    }
+
+    fn moved(world: &World, id: EntityId, f: impl FnOnce(&mut Transform)) -> Transform {
+        let mut transform = world.get(id).unwrap().transform;
+        f(&mut transform);
+        transform
+    }
+
+    #[test]
+    fn concurrent_field_edits_both_survive() {
+        let mut base = World::with_seed(1);
+        let id = base.spawn(Transform::default());
+        base.drain_events();
+
+        let (a, b) = merge_both_ways(
+            &base,
+            |w| {
+                let t = moved(w, id, |t| t.position = Vec3::X);
+                w.set_transform(id, t);
+            },
+            |w| {
+                let t = moved(w, id, |t| t.scale = Vec3::splat(2.0));
+                w.set_transform(id, t);
+            },
+        );
+        let merged = a.get(id).unwrap().transform;
+        assert_eq!(merged.position, Vec3::X);
+        assert_eq!(merged.scale, Vec3::splat(2.0));
+        assert_eq!(a.state_hash(), b.state_hash());
+    }
+
+    #[test]
+    fn conflicting_writes_resolve_deterministically() {
+        let mut base = World::with_seed(1);
+        let id = base.spawn(Transform::default());
+        base.drain_events();
+
+        let (a, b) = merge_both_ways(
+            &base,
+            |w| {
+                let t = moved(w, id, |t| t.position = Vec3::X);
+                w.set_transform(id, t);
+            },
+            |w| {
+                let t = moved(w, id, |t| t.position = Vec3::Y);
+                w.set_transform(id, t);
+            },
+        );
+        // Equal counters: the higher replica ID wins.
+        assert_eq!(a.get(id).unwrap().transform.position, Vec3::Y);
+        assert_eq!(a.state_hash(), b.state_hash());
+    }
+
+    #[test]
+    fn spawns_and_despawns_merge() {
+        let mut base = World::with_seed(1);
+        let doomed = base.spawn(Transform::default());
+        base.drain_events();
+
+        let mut spawned = None;
+        let (a, b) = merge_both_ways(
+            &base,
+            |w| {
+                w.despawn(doomed);
+            },
+            |w| {
+                let t = moved(w, doomed, |t| t.position = Vec3::Z);
+                w.set_transform(doomed, t);
+                spawned = Some(w.spawn(Transform::default()));
+            },
+        );
+        assert!(a.get(doomed).is_none());
+        assert!(a.get(spawned.unwrap()).is_some());
+        assert_eq!(a.state_hash(), b.state_hash());
+    }
+
+    #[test]
+    fn merge_is_idempotent() {
+        let mut world = World::with_seed(1);
+        let mut log = EditLog::new(7);
+        world.spawn(Transform::default());
+        world.step();
+        log.record(&world.drain_events());
+        assert_eq!(log.len(), 1);
+
+        let copy = log.clone();
+        log.merge(&copy);
+        assert_eq!(log.len(), 1);
+    }
+}
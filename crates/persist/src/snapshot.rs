@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
-use worldspace_common::EntityId;
+use worldspace_common::{EntityId, Transform};
 use worldspace_kernel::{EntityData, World, WorldEvent};
 
 /// A content-addressed snapshot of the world state at a specific tick.
@@ -55,6 +55,40 @@ impl Snapshot {
         world
     }
 
+    /// What changed from this snapshot to `other`: entities only in `other`
+    /// are added, entities only in this one removed, and entities whose
+    /// transform differs moved.
+    pub fn diff(&self, other: &Snapshot) -> SnapshotDiff {
+        let mut diff = SnapshotDiff {
+            from_tick: self.tick,
+            to_tick: other.tick,
+            ..SnapshotDiff::default()
+        };
+        for (id, data) in &self.entities {
+            match other.entities.get(id) {
+                None => {
+                    diff.removed.insert(*id, data.clone());
+                }
+                Some(new) if new.transform != data.transform => {
+                    diff.moved.insert(
+                        *id,
+                        TransformDelta {
+                            from: data.transform,
+                            to: new.transform,
+                        },
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+        for (id, data) in &other.entities {
+            if !self.entities.contains_key(id) {
+                diff.added.insert(*id, data.clone());
+            }
+        }
+        diff
+    }
+
     fn compute_hash(tick: u64, seed: u64, entities: &BTreeMap<EntityId, EntityData>) -> String {
         let mut hasher = Sha256::new();
         hasher.update(tick.to_le_bytes());
@@ -77,6 +111,127 @@ impl Snapshot {
     }
 }
 
+/// Entity-level changes between two snapshots, from `Snapshot::diff`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    /// Tick of the snapshot diffed from.
+    pub from_tick: u64,
+    /// Tick of the snapshot diffed to.
+    pub to_tick: u64,
+    /// Entities present only in the later snapshot.
+    pub added: BTreeMap<EntityId, EntityData>,
+    /// Entities present only in the earlier snapshot, with their last state.
+    pub removed: BTreeMap<EntityId, EntityData>,
+    /// Entities in both whose transform changed.
+    pub moved: BTreeMap<EntityId, TransformDelta>,
+}
+
+impl SnapshotDiff {
+    /// True if both snapshots hold the same entities with the same transforms.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+}
+
+/// An entity's transform before and after a change.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TransformDelta {
+    pub from: Transform,
+    pub to: Transform,
+}
+
+impl TransformDelta {
+    /// Offset from the old position to the new one.
+    pub fn translation(&self) -> glam::Vec3 {
+        self.to.position - self.from.position
+    }
+
+    /// Rotation taking the old orientation to the new one.
+    pub fn rotation(&self) -> glam::Quat {
+        self.to.rotation * self.from.rotation.inverse()
+    }
+
+    /// Per-axis ratio of the new scale to the old one.
+    pub fn scale(&self) -> glam::Vec3 {
+        self.to.scale / self.from.scale
+    }
+}
+
+/// The entities that changed between a base snapshot and a later world state.
+///
+/// Applying the delta to its base yields the full snapshot; the base hash and
+/// the resulting state hash are both checked, so a delta applied to the wrong
+/// base is rejected rather than producing a mixed state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeltaSnapshot {
+    /// The tick at which this snapshot was taken.
+    pub tick: u64,
+    /// The seed at snapshot time.
+    pub seed: u64,
+    /// Hash of the snapshot this delta applies to.
+    pub base_hash: String,
+    /// Entities spawned or moved since the base.
+    pub changed: BTreeMap<EntityId, EntityData>,
+    /// Entities despawned since the base.
+    pub removed: Vec<EntityId>,
+    /// Hash of the full snapshot the delta reconstructs.
+    pub hash: String,
+}
+
+impl DeltaSnapshot {
+    /// Capture the difference between `base` and the current world state.
+    pub fn capture(base: &Snapshot, world: &World) -> Self {
+        let changed = world
+            .entities()
+            .iter()
+            .filter(|(id, data)| {
+                base.entities
+                    .get(id)
+                    .is_none_or(|old| old.transform != data.transform)
+            })
+            .map(|(id, data)| (*id, data.clone()))
+            .collect();
+        let removed = base
+            .entities
+            .keys()
+            .filter(|id| world.get(**id).is_none())
+            .copied()
+            .collect();
+        let hash = Snapshot::compute_hash(world.tick(), world.seed(), world.entities());
+
+        Self {
+            tick: world.tick(),
+            seed: world.seed(),
+            base_hash: base.hash.clone(),
+            changed,
+            removed,
+            hash,
+        }
+    }
+
+    /// Reconstruct the full snapshot from `base`. Returns `None` if `base` is
+    /// not the snapshot this delta was taken against or the result does not
+    /// match the recorded hash.
+    pub fn apply(&self, base: &Snapshot) -> Option<Snapshot> {
+        if base.hash != self.base_hash {
+            return None;
+        }
+        let mut entities = base.entities.clone();
+        for id in &self.removed {
+            entities.remove(id);
+        }
+        entities.extend(self.changed.iter().map(|(id, data)| (*id, data.clone())));
+
+        let snapshot = Snapshot {
+            tick: self.tick,
+            seed: self.seed,
+            entities,
+            hash: self.hash.clone(),
+        };
+        snapshot.verify().then_some(snapshot)
+    }
+}
+
 /// Append-only event log for persistence and replay.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EventLog {
@@ -240,6 +395,75 @@ mod tests {
         assert!(restored.get(id).is_some());
     }
 
+    #[test]
+    fn delta_snapshot_roundtrip() {
+        let mut world = World::with_seed(9);
+        let kept = world.spawn(Transform::default());
+        let moved = world.spawn(Transform::default());
+        let despawned = world.spawn(Transform::default());
+        let base = Snapshot::capture(&world);
+
+        world.set_transform(
+            moved,
+            Transform {
+                position: glam::Vec3::X,
+                ..Transform::default()
+            },
+        );
+        world.despawn(despawned);
+        let spawned = world.spawn(Transform::default());
+        world.step();
+
+        let delta = DeltaSnapshot::capture(&base, &world);
+        assert_eq!(delta.changed.len(), 2);
+        assert!(delta.changed.contains_key(&moved) && delta.changed.contains_key(&spawned));
+        assert!(!delta.changed.contains_key(&kept));
+        assert_eq!(delta.removed, vec![despawned]);
+
+        let full = delta.apply(&base).unwrap();
+        assert_eq!(full.hash, Snapshot::capture(&world).hash);
+        assert_eq!(full.restore().state_hash(), world.state_hash());
+
+        // Applying to a different base is rejected.
+        assert!(delta.apply(&full).is_none());
+    }
+
+    #[test]
+    fn snapshot_diff_reports_changes() {
+        let mut world = World::with_seed(3);
+        let kept = world.spawn(Transform::default());
+        let moved = world.spawn(Transform::default());
+        let despawned = world.spawn(Transform::default());
+        let before = Snapshot::capture(&world);
+        assert!(before.diff(&before).is_empty());
+
+        let target = Transform {
+            position: glam::Vec3::new(1.0, 2.0, 0.0),
+            scale: glam::Vec3::splat(2.0),
+            ..Transform::default()
+        };
+        world.set_transform(moved, target);
+        world.despawn(despawned);
+        let spawned = world.spawn(Transform::default());
+        world.step();
+        let after = Snapshot::capture(&world);
+
+        let diff = before.diff(&after);
+        assert_eq!((diff.from_tick, diff.to_tick), (0, 1));
+        assert_eq!(diff.added.keys().collect::<Vec<_>>(), vec![&spawned]);
+        assert_eq!(diff.removed.keys().collect::<Vec<_>>(), vec![&despawned]);
+        assert_eq!(diff.moved.len(), 1);
+        assert!(!diff.moved.contains_key(&kept));
+        let delta = diff.moved[&moved];
+        assert_eq!(delta.translation(), glam::Vec3::new(1.0, 2.0, 0.0));
+        assert_eq!(delta.scale(), glam::Vec3::splat(2.0));
+        assert_eq!(delta.rotation(), glam::Quat::IDENTITY);
+
+        let back = after.diff(&before);
+        assert_eq!(back.added.len(), 1);
+        assert!(back.removed.contains_key(&spawned));
+    }
+
     #[test]
     fn event_log_append_and_read() {
         let mut log = EventLog::new();
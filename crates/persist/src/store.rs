@@ -3,24 +3,110 @@
 //! Layout inside the store directory:
 //! ```text
 //! world.meta.json          - metadata and schema versions
+//! world.lock               - writer lock and lease
 //! snapshots/
-//!   000001.snapshot.cbor.zst - CBOR+zstd compressed snapshots
+//!   000001.snapshot.cbor.zst            - CBOR+zstd compressed snapshots
+//!   000001.components.snapshot.cbor.zst - component snapshot taken with it
+//!   000002.delta.snapshot.cbor.zst      - delta against the previous snapshot
 //! events/
-//!   000001.log.cbor.zst      - CBOR+zstd compressed event log segments
+//!   000001.log.cbor.zst                 - CBOR+zstd compressed event log segments
+//!   000001.components.log.cbor.zst      - component events for the same segment
+//! cells/
+//!   -1_3.cell.cbor.zst                  - entities of streaming cell (-1, 3)
+//! history/
+//!   edit.history.cbor.zst               - editor undo/redo stacks
+//! objects/
+//!   3f/3f9a…                            - deduplicated snapshot chunks
 //! integrity/
 //!   manifest.json            - hash chain manifest
+//!   append.wal               - event append in progress (write-ahead log)
+//!   migrate.journal          - schema migration in progress
+//! quarantine/                - corrupt segments set aside by `repair`
 //! ```
-
-use crate::snapshot::Snapshot;
+//!
+//! The files above belong to the `main` branch. Every other named branch keeps
+//! its own snapshots and event segments in a subdirectory named after it, e.g.
+//! `snapshots/experiment/000001.snapshot.cbor.zst`, starting from a copy of the
+//! snapshot it was branched from. All files share one integrity manifest.
+//!
+//! With the `signing` feature, manifest entries can be signed with an ed25519
+//! key (`WorldStore::set_signing_key`) and `verify_integrity` checks them
+//! against a trusted public key (`WorldStore::require_signatures`).
+//!
+//! With the `encryption` feature, `WorldStore::open_encrypted` seals every
+//! segment file with a user-supplied key after compression; the key's ID is
+//! recorded in `world.meta.json`.
+//!
+//! Only one process can open a store for writing at a time; others fail with
+//! `StoreError::Locked`. `WorldStore::open_read_only` opens without the lock
+//! for inspection while a writer is active.
+//!
+//! Event appends go through a write-ahead log and `world.meta.json` and the
+//! manifest are replaced atomically, so a crash mid-save is recovered on the
+//! next `open`.
+//!
+//! `WorldStore::export_archive` packs the store into a single `.world` file
+//! for sharing and backup; `WorldStore::import_archive` unpacks one.
+//! `WorldStore::pull_from` and `push_to` mirror a store onto another by
+//! copying only the segments it lacks.
+
+mod archive;
+mod cells;
+mod checkpoint;
+mod chunks;
+mod history;
+mod jsonl;
+mod lock;
+mod migrate;
+mod repair;
+mod replay;
+mod retention;
+mod sync;
+mod wal;
+
+use crate::component_snapshot::ComponentSnapshot;
+#[cfg(feature = "encryption")]
+use crate::encryption::EncryptionKey;
+#[cfg(feature = "signing")]
+use crate::signing::{SigningKey, VerifyingKey};
+use crate::snapshot::{DeltaSnapshot, Snapshot};
+pub use checkpoint::Checkpoint;
+pub use migrate::{MigrationReport, Migrations, SchemaKind, StoreMigration};
+pub use repair::RepairReport;
+pub use replay::ReplayIter;
+pub use retention::RetentionPolicy;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+pub use sync::SyncReport;
+use worldspace_ecs::{ComponentEvent, ComponentStore, Tag};
 use worldspace_kernel::{World, WorldEvent};
 
 /// Current schema versions.
 const WORLD_SCHEMA_VERSION: u32 = 1;
 const EVENT_SCHEMA_VERSION: u32 = 1;
+/// Schema version of component snapshots and component event segments.
+const COMPONENT_SCHEMA_VERSION: u32 = 1;
+
+/// Stores written before component persistence carry no component data, so
+/// they are compatible with the first component schema.
+fn default_component_schema_version() -> u32 {
+    COMPONENT_SCHEMA_VERSION
+}
+
+/// Replayed events kept in the world's log before `load_latest` drains it.
+const REPLAY_DRAIN_INTERVAL: usize = 4096;
+
+/// The branch every store starts on. Its files live at the top level of the
+/// `snapshots/` and `events/` directories.
+pub const MAIN_BRANCH: &str = "main";
+
+fn default_branch() -> String {
+    MAIN_BRANCH.into()
+}
 
 /// Errors from file-backed persistence operations.
 #[derive(Debug, thiserror::Error)]
@@ -33,6 +119,11 @@ pub enum StoreError {
     CborDecode(String),
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
+    #[error("JSON error on line {line}: {source}")]
+    JsonLine {
+        line: usize,
+        source: serde_json::Error,
+    },
     #[error("integrity check failed: expected {expected}, got {actual}")]
     IntegrityMismatch { expected: String, actual: String },
     #[error("schema version mismatch: file has v{file_version}, expected v{expected_version}")]
@@ -44,6 +135,50 @@ pub enum StoreError {
     NoSnapshots,
     #[error("store not initialized")]
     NotInitialized,
+    #[error("snapshot {0} not found on the current branch")]
+    SnapshotNotFound(u32),
+    #[error("no snapshot at or before tick {0}")]
+    TickNotFound(u64),
+    #[error("invalid branch name '{0}'")]
+    InvalidBranchName(String),
+    #[error("branch '{0}' already exists")]
+    BranchExists(String),
+    #[error("unknown branch '{0}'")]
+    UnknownBranch(String),
+    #[error("encryption key mismatch: store uses {expected:?}, opened with {actual:?}")]
+    EncryptionKeyMismatch {
+        expected: Option<String>,
+        actual: Option<String>,
+    },
+    #[error("encryption error: {0}")]
+    Encryption(String),
+    #[error("cell size mismatch: store uses {expected}, got {actual}")]
+    CellSizeMismatch { expected: f32, actual: f32 },
+    #[error("cell layer height mismatch: store uses {expected:?}, got {actual:?}")]
+    CellLayerMismatch {
+        expected: Option<f32>,
+        actual: Option<f32>,
+    },
+    #[error("archive error: {0}")]
+    Archive(String),
+    #[error("store migration error: {0}")]
+    Migration(String),
+    #[error("missing or invalid signature on {0}")]
+    SignatureInvalid(String),
+    #[error("signing error: {0}")]
+    Signing(String),
+    #[error("invalid checkpoint name '{0}'")]
+    InvalidCheckpointName(String),
+    #[error("checkpoint '{0}' already exists")]
+    CheckpointExists(String),
+    #[error("unknown checkpoint '{0}'")]
+    UnknownCheckpoint(String),
+    #[error("store is locked for writing by {0}")]
+    Locked(String),
+    #[error("store is open read-only")]
+    ReadOnly,
+    #[error("stores have diverged at {0}")]
+    Diverged(String),
 }
 
 /// Metadata stored in world.meta.json.
@@ -51,7 +186,54 @@ pub enum StoreError {
 pub struct WorldMeta {
     pub world_schema_version: u32,
     pub event_schema_version: u32,
+    #[serde(default = "default_component_schema_version")]
+    pub component_schema_version: u32,
+    /// Snapshots on the main branch.
+    pub snapshot_count: u32,
+    /// Event segments on the main branch.
+    pub event_segment_count: u32,
+    /// The branch that loads, appends and snapshots operate on.
+    #[serde(default = "default_branch")]
+    pub current_branch: String,
+    /// Named branches other than main.
+    #[serde(default)]
+    pub branches: BTreeMap<String, BranchMeta>,
+    /// ID of the key segment files are encrypted with; `None` if unencrypted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption_key_id: Option<String>,
+    /// Streaming cell size of the files under `cells/`, set by the first
+    /// cell save.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell_size: Option<f32>,
+    /// Layer height of the partition the stored cells belong to; `None` for
+    /// a flat partition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell_layer_height: Option<f32>,
+    /// Hex public key of the last key set to sign manifest entries. For
+    /// information only; verification uses a key the reader trusts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_by: Option<String>,
+    /// Named snapshots, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkpoints: Vec<Checkpoint>,
+    /// Which snapshots `compact` keeps; `None` folds everything into one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionPolicy>,
+    /// Whether new snapshots are stored as deduplicated chunks.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub chunk_snapshots: bool,
+}
+
+/// A named branch, forked from a snapshot of another branch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchMeta {
+    /// The branch this one was created from.
+    pub parent: String,
+    /// The parent's snapshot index this branch starts from.
+    pub from_snapshot: u32,
+    /// Snapshots on this branch, including the copied starting snapshot.
     pub snapshot_count: u32,
+    /// Event segments appended on this branch.
     pub event_segment_count: u32,
 }
 
@@ -61,6 +243,36 @@ pub struct ManifestEntry {
     pub filename: String,
     pub sha256: String,
     pub prev_hash: Option<String>,
+    /// For snapshot files, the first event not already reflected in the
+    /// snapshot. `None` for other files and for snapshots written before
+    /// cursors were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay_cursor: Option<ReplayCursor>,
+    /// For snapshot files, the tick of the world they hold, so a snapshot can
+    /// be picked by tick without decoding it. `None` for other files and for
+    /// snapshots written before ticks were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_tick: Option<u64>,
+    /// Hex ed25519 signature over the filename, hash, previous hash, replay
+    /// cursor, snapshot tick and chunked flag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Write time in seconds since the Unix epoch; `None` for files written
+    /// before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    /// The file holds a chunk list; the payload is in `objects/`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub chunked: bool,
+}
+
+/// Position in a branch's event log: event `offset` of segment `segment`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayCursor {
+    /// 1-based event segment index.
+    pub segment: u32,
+    /// Events of that segment before the position.
+    pub offset: u32,
 }
 
 /// Integrity manifest tracking all segment hashes in a chain.
@@ -69,36 +281,146 @@ pub struct IntegrityManifest {
     pub entries: Vec<ManifestEntry>,
 }
 
+/// What `WorldStore::compact` removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactReport {
+    /// Snapshots folded into the fresh one.
+    pub snapshots_removed: u32,
+    /// Event segments folded into the fresh snapshot.
+    pub segments_removed: u32,
+    /// Size of the deleted files.
+    pub bytes_freed: u64,
+}
+
+/// Component snapshot as written to disk, tagged with the number of event
+/// segments that existed when it was taken. Only component segments after that
+/// point are replayed on load.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredComponents {
+    after_segment: u32,
+    snapshot: ComponentSnapshot,
+}
+
 /// File-backed world store with schema versioning and integrity checking.
 pub struct WorldStore {
     root: PathBuf,
     meta: WorldMeta,
     manifest: IntegrityManifest,
+    /// The latest snapshot written, as `(branch, index, snapshot)`, so delta
+    /// snapshots can be diffed without reading it back.
+    last_snapshot: Option<(String, u32, Snapshot)>,
+    /// Writer lock; `None` for a read-only store.
+    lock: Option<lock::StoreLock>,
+    #[cfg(feature = "encryption")]
+    key: Option<EncryptionKey>,
+    #[cfg(feature = "signing")]
+    signing_key: Option<SigningKey>,
+    /// Key every manifest entry must be signed with in `verify_integrity`.
+    #[cfg(feature = "signing")]
+    trusted_key: Option<VerifyingKey>,
 }
 
 impl WorldStore {
-    /// Open or create a world store at the given path.
+    /// Open or create an unencrypted world store at the given path, migrating
+    /// stores written with older schema versions by the built-in migrations.
     pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
-        let root = path.as_ref().to_path_buf();
-        std::fs::create_dir_all(root.join("snapshots"))?;
-        std::fs::create_dir_all(root.join("events"))?;
-        std::fs::create_dir_all(root.join("integrity"))?;
+        Self::open_with_migrations(path, &Migrations::builtin())
+    }
+
+    /// Open or create an unencrypted world store, upgrading older schema
+    /// versions with `migrations`. Stores from a newer engine, or versions
+    /// with no migration path, fail with `SchemaMismatch` or `Migration`.
+    ///
+    /// Migrated segment files are staged and journaled before they replace
+    /// the originals, so a crash mid-migration is finished on the next open;
+    /// still, use `dry_run_migrations` first, or keep an `export_archive`
+    /// backup.
+    pub fn open_with_migrations(
+        path: impl AsRef<Path>,
+        migrations: &Migrations,
+    ) -> Result<Self, StoreError> {
+        let mut store = Self::open_with_key_id(path.as_ref(), None, false)?;
+        store.recover_migration()?;
+        store.recover_wal()?;
+        store.run_migrations(migrations, false)?;
+        Ok(store)
+    }
+
+    /// Open an existing unencrypted store for reading only. Takes no writer
+    /// lock, so it works while another process has the store open; every
+    /// method that would write fails with `ReadOnly`.
+    ///
+    /// An append interrupted by a crash is invisible until a writable open
+    /// recovers it. Stores that need migrating fail with `Migration`.
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let mut store = Self::open_with_key_id(path.as_ref(), None, true)?;
+        let pending = store.run_migrations(&Migrations::builtin(), true)?;
+        if !pending.is_empty() {
+            return Err(StoreError::Migration(
+                "store needs migrating; open it writable first".into(),
+            ));
+        }
+        Ok(store)
+    }
 
+    /// Run `migrations` over the unencrypted store at `path` without writing
+    /// anything, reporting what opening it would change.
+    pub fn dry_run_migrations(
+        path: impl AsRef<Path>,
+        migrations: &Migrations,
+    ) -> Result<MigrationReport, StoreError> {
+        let mut store = Self::open_with_key_id(path.as_ref(), None, true)?;
+        store.run_migrations(migrations, true)
+    }
+
+    /// Open or create a world store whose segment files are encrypted with
+    /// `key`. A new store records the key's ID; an existing one must have
+    /// been created with the same key ID.
+    #[cfg(feature = "encryption")]
+    pub fn open_encrypted(path: impl AsRef<Path>, key: EncryptionKey) -> Result<Self, StoreError> {
+        let mut store = Self::open_with_key_id(path.as_ref(), Some(key.id()), false)?;
+        store.key = Some(key);
+        store.recover_migration()?;
+        store.recover_wal()?;
+        store.run_migrations(&Migrations::builtin(), false)?;
+        Ok(store)
+    }
+
+    /// Open the store without checking schema versions; callers run
+    /// `run_migrations` before using it. A writable open takes the writer
+    /// lock and creates the store if needed; a read-only one requires it to
+    /// exist.
+    fn open_with_key_id(
+        path: &Path,
+        key_id: Option<&str>,
+        read_only: bool,
+    ) -> Result<Self, StoreError> {
+        let root = path.to_path_buf();
         let meta_path = root.join("world.meta.json");
         let manifest_path = root.join("integrity").join("manifest.json");
 
-        let (meta, manifest) = if meta_path.exists() {
-            let meta: WorldMeta = serde_json::from_reader(std::fs::File::open(&meta_path)?)?;
-            if meta.world_schema_version != WORLD_SCHEMA_VERSION {
-                return Err(StoreError::SchemaMismatch {
-                    file_version: meta.world_schema_version,
-                    expected_version: WORLD_SCHEMA_VERSION,
-                });
+        let lock = if read_only {
+            if !meta_path.exists() {
+                return Err(StoreError::NotInitialized);
             }
-            if meta.event_schema_version != EVENT_SCHEMA_VERSION {
-                return Err(StoreError::SchemaMismatch {
-                    file_version: meta.event_schema_version,
-                    expected_version: EVENT_SCHEMA_VERSION,
+            None
+        } else {
+            std::fs::create_dir_all(&root)?;
+            let lock = lock::StoreLock::acquire(&root)?;
+            std::fs::create_dir_all(root.join("snapshots"))?;
+            std::fs::create_dir_all(root.join("events"))?;
+            std::fs::create_dir_all(root.join("cells"))?;
+            std::fs::create_dir_all(root.join("history"))?;
+            std::fs::create_dir_all(root.join("integrity"))?;
+            Some(lock)
+        };
+
+        let (meta, manifest) = if meta_path.exists() {
+            let meta = Self::read_meta(&root)?;
+            if meta.encryption_key_id.as_deref() != key_id {
+                return Err(StoreError::EncryptionKeyMismatch {
+                    expected: meta.encryption_key_id,
+                    actual: key_id.map(str::to_string),
                 });
             }
             let manifest: IntegrityManifest = if manifest_path.exists() {
@@ -111,8 +433,18 @@ impl WorldStore {
             let meta = WorldMeta {
                 world_schema_version: WORLD_SCHEMA_VERSION,
                 event_schema_version: EVENT_SCHEMA_VERSION,
+                component_schema_version: COMPONENT_SCHEMA_VERSION,
                 snapshot_count: 0,
                 event_segment_count: 0,
+                current_branch: default_branch(),
+                branches: BTreeMap::new(),
+                encryption_key_id: key_id.map(str::to_string),
+                cell_size: None,
+                cell_layer_height: None,
+                signed_by: None,
+                checkpoints: Vec::new(),
+                retention: None,
+                chunk_snapshots: false,
             };
             let manifest = IntegrityManifest::default();
             // Write initial meta
@@ -125,15 +457,60 @@ impl WorldStore {
             root,
             meta,
             manifest,
+            last_snapshot: None,
+            lock,
+            #[cfg(feature = "encryption")]
+            key: None,
+            #[cfg(feature = "signing")]
+            signing_key: None,
+            #[cfg(feature = "signing")]
+            trusted_key: None,
         })
     }
 
     /// Load the latest snapshot and replay events to reconstruct the world.
     pub fn load_latest(&self) -> Result<World, StoreError> {
-        if self.meta.snapshot_count == 0 {
+        let (snapshot_count, _) = self.counts();
+        if snapshot_count == 0 {
             return Err(StoreError::NoSnapshots);
         }
-        let snap = self.load_snapshot(self.meta.snapshot_count)?;
+        let snap = self.load_snapshot(snapshot_count)?;
+        self.replay_snapshot(snapshot_count, snap, u64::MAX)
+    }
+
+    /// Reconstruct the world as it was at `tick` on the current branch: the
+    /// nearest snapshot at or before it, plus the events up to the end of
+    /// that tick. Ticks past the end of the log give the latest state.
+    ///
+    /// The snapshot is picked by the tick recorded in the manifest; only
+    /// snapshots written before ticks were recorded are decoded to find it.
+    pub fn world_at(&self, tick: u64) -> Result<World, StoreError> {
+        let (snapshot_count, _) = self.counts();
+        for index in (1..=snapshot_count).rev() {
+            if self
+                .snapshot_entry(index)
+                .and_then(|e| e.snapshot_tick)
+                .is_some_and(|t| t > tick)
+            {
+                continue;
+            }
+            let snap = self.load_snapshot(index)?;
+            if snap.tick <= tick {
+                return self.replay_snapshot(index, snap, tick);
+            }
+        }
+        Err(StoreError::TickNotFound(tick))
+    }
+
+    /// Restore snapshot `index` of the current branch and replay the events
+    /// logged after it, stopping before the step past `until_tick`.
+    fn replay_snapshot(
+        &self,
+        index: u32,
+        snap: Snapshot,
+        until_tick: u64,
+    ) -> Result<World, StoreError> {
+        let (_, event_segment_count) = self.counts();
         if !snap.verify() {
             return Err(StoreError::IntegrityMismatch {
                 expected: "valid snapshot hash".into(),
@@ -141,101 +518,435 @@ impl WorldStore {
             });
         }
 
-        // Replay event segments after the snapshot
+        // Replay events after the snapshot's cursor, one segment in memory at
+        // a time. Replay re-records every event in the world's own log, so it
+        // is drained as it goes.
+        //
+        // Snapshots without a cursor replay every segment, skipping only
+        // events the snapshot's tick shows were already applied.
+        let cursor = self.snapshot_cursor(index);
+        let legacy = cursor.is_none();
+        let from = cursor.unwrap_or(ReplayCursor {
+            segment: 1,
+            offset: 0,
+        });
         let mut world = snap.restore();
-        for seg_idx in 1..=self.meta.event_segment_count {
-            let events = self.load_event_segment(seg_idx)?;
-            for event in &events {
-                match event {
-                    WorldEvent::Spawned { id, transform } => {
-                        // Only replay events past the snapshot tick
-                        if world.tick() < snap.tick {
-                            continue;
-                        }
-                        world.spawn_with_id(*id, *transform);
-                    }
-                    WorldEvent::Despawned { id, .. } => {
-                        world.despawn(*id);
-                    }
-                    WorldEvent::TransformUpdated { id, new, .. } => {
-                        world.set_transform(*id, *new);
-                    }
-                    WorldEvent::Stepped { tick, seed: _ } => {
-                        if *tick <= snap.tick {
-                            continue;
-                        }
-                        world.step();
-                    }
+        for event in self.replay_iter_from(from, event_segment_count) {
+            match event? {
+                WorldEvent::Spawned { .. } if legacy && world.tick() < snap.tick => {}
+                WorldEvent::Spawned { id, transform } => {
+                    world.spawn_with_id(id, transform);
+                }
+                WorldEvent::Despawned { id, .. } => {
+                    world.despawn(id);
+                }
+                WorldEvent::TransformUpdated { id, new, .. } => {
+                    world.set_transform(id, new);
+                }
+                WorldEvent::Stepped { tick, .. } if legacy && tick <= snap.tick => {}
+                WorldEvent::Stepped { tick, .. } if tick > until_tick => break,
+                WorldEvent::Stepped { .. } => {
+                    world.step();
                 }
             }
+            if world.events().len() >= REPLAY_DRAIN_INTERVAL {
+                world.drain_events();
+            }
         }
         world.drain_events();
         Ok(world)
     }
 
+    /// Load the latest snapshot together with its component data, replaying
+    /// world and component event segments written after it.
+    ///
+    /// Stores written without component data load with an empty `ComponentStore`.
+    pub fn load_latest_with_components(&self) -> Result<(World, ComponentStore), StoreError> {
+        let world = self.load_latest()?;
+
+        let branch = &self.meta.current_branch;
+        let (snapshot_count, event_segment_count) = self.counts();
+        let filename = component_snapshot_filename(branch, snapshot_count);
+        let (mut components, after_segment) = if self.in_manifest(&filename) {
+            let stored: StoredComponents = self.read_segment("snapshots", &filename)?;
+            if !stored.snapshot.verify() {
+                return Err(StoreError::IntegrityMismatch {
+                    expected: "valid component snapshot hash".into(),
+                    actual: "component snapshot hash mismatch".into(),
+                });
+            }
+            (stored.snapshot.restore(), stored.after_segment)
+        } else {
+            (ComponentStore::new(), 0)
+        };
+
+        for seg_idx in after_segment + 1..=event_segment_count {
+            let filename = component_log_filename(branch, seg_idx);
+            if !self.in_manifest(&filename) {
+                continue;
+            }
+            let events: Vec<ComponentEvent> = self.read_segment("events", &filename)?;
+            // Logs written before transient events were left out may hold them.
+            for event in events.iter().filter(|event| !event.is_transient()) {
+                components.apply_event(event);
+            }
+        }
+        components.drain_events();
+        Ok((world, components))
+    }
+
     /// Append events to the store as a new segment.
     pub fn append_events(&mut self, events: &[WorldEvent]) -> Result<(), StoreError> {
-        if events.is_empty() {
+        self.append_events_with_components(events, &[])
+    }
+
+    /// Append world and component events to the store as a new segment.
+    ///
+    /// Component events are written to a companion file with the same segment
+    /// index; it is omitted when there are none. Transient events (see
+    /// `ComponentEvent::is_transient`) are not written, as component
+    /// snapshots do not keep the state they change either.
+    pub fn append_events_with_components(
+        &mut self,
+        events: &[WorldEvent],
+        component_events: &[ComponentEvent],
+    ) -> Result<(), StoreError> {
+        let component_events: Vec<ComponentEvent> = component_events
+            .iter()
+            .filter(|event| !event.is_transient())
+            .cloned()
+            .collect();
+        if events.is_empty() && component_events.is_empty() {
             return Ok(());
         }
-        self.meta.event_segment_count += 1;
-        let seg_idx = self.meta.event_segment_count;
-        let filename = format!("{:06}.log.cbor.zst", seg_idx);
-        let path = self.root.join("events").join(&filename);
+        let (_, event_segment_count) = self.counts();
+        self.append_logged(event_segment_count + 1, events, &component_events)
+    }
 
-        let cbor_bytes = cbor_serialize(events)?;
-        let compressed = zstd_compress(&cbor_bytes)?;
+    /// Take a snapshot of the world and write it to disk.
+    ///
+    /// Events still in the world's log are reflected in the snapshot; they
+    /// are expected to be the first events of the next appended segment and
+    /// are skipped when loading from this snapshot.
+    pub fn take_snapshot(&mut self, world: &World) -> Result<(), StoreError> {
+        self.ensure_writable()?;
+        let snap = Snapshot::capture(world);
+        let cursor = self.next_cursor(world);
+        let snap_idx = {
+            let (snapshot_count, _) = self.counts_mut();
+            *snapshot_count += 1;
+            *snapshot_count
+        };
+        let branch = self.meta.current_branch.clone();
+        self.write_segment("snapshots", snapshot_filename(&branch, snap_idx), &snap)?;
+        self.set_replay_cursor(cursor, snap.tick);
+        self.last_snapshot = Some((branch, snap_idx, snap));
 
-        let hash = sha256_hex(&compressed);
-        let prev_hash = self.manifest.entries.last().map(|e| e.sha256.clone());
+        self.save_meta()?;
+        self.save_manifest()?;
+        Ok(())
+    }
 
-        std::fs::write(&path, &compressed)?;
+    /// Take a snapshot that stores only the entities changed since the
+    /// previous snapshot of the current branch. Loading reconstructs it from
+    /// the nearest full snapshot and the deltas after it.
+    ///
+    /// Falls back to a full snapshot on a branch without one. Long delta
+    /// chains slow loading; `compact` folds them into a full snapshot.
+    pub fn take_delta_snapshot(&mut self, world: &World) -> Result<(), StoreError> {
+        self.ensure_writable()?;
+        let (snapshot_count, _) = self.counts();
+        if snapshot_count == 0 {
+            return self.take_snapshot(world);
+        }
+        let branch = self.meta.current_branch.clone();
+        let base = match self.last_snapshot.take() {
+            Some((b, index, snap)) if b == branch && index == snapshot_count => snap,
+            _ => self.load_snapshot(snapshot_count)?,
+        };
 
-        self.manifest.entries.push(ManifestEntry {
-            filename,
-            sha256: hash,
-            prev_hash,
-        });
+        let delta = DeltaSnapshot::capture(&base, world);
+        let cursor = self.next_cursor(world);
+        let snap_idx = snapshot_count + 1;
+        *self.counts_mut().0 = snap_idx;
+        self.write_segment("snapshots", delta_filename(&branch, snap_idx), &delta)?;
+        self.set_replay_cursor(cursor, delta.tick);
+        let snap = delta
+            .apply(&base)
+            .ok_or_else(|| StoreError::IntegrityMismatch {
+                expected: delta.hash.clone(),
+                actual: "delta snapshot does not apply to its base".into(),
+            })?;
+        self.last_snapshot = Some((branch, snap_idx, snap));
 
         self.save_meta()?;
         self.save_manifest()?;
         Ok(())
     }
 
-    /// Take a snapshot of the world and write it to disk.
-    pub fn take_snapshot(&mut self, world: &World) -> Result<(), StoreError> {
-        let snap = Snapshot::capture(world);
-        self.meta.snapshot_count += 1;
-        let snap_idx = self.meta.snapshot_count;
-        let filename = format!("{:06}.snapshot.cbor.zst", snap_idx);
-        let path = self.root.join("snapshots").join(&filename);
+    /// Take a snapshot of the world and its components and write both to disk.
+    /// Entities tagged `NoSerialize` are left out of both.
+    pub fn take_snapshot_with_components(
+        &mut self,
+        world: &World,
+        components: &ComponentStore,
+    ) -> Result<(), StoreError> {
+        self.take_snapshot(&persisted_world(world, components))?;
+        self.write_component_snapshot(components)
+    }
 
-        let cbor_bytes = cbor_serialize(&snap)?;
-        let compressed = zstd_compress(&cbor_bytes)?;
+    /// Take a delta snapshot of the world (see `take_delta_snapshot`) together
+    /// with a full snapshot of its components, leaving out entities tagged
+    /// `NoSerialize`.
+    pub fn take_delta_snapshot_with_components(
+        &mut self,
+        world: &World,
+        components: &ComponentStore,
+    ) -> Result<(), StoreError> {
+        self.take_delta_snapshot(&persisted_world(world, components))?;
+        self.write_component_snapshot(components)
+    }
 
-        let hash = sha256_hex(&compressed);
-        let prev_hash = self.manifest.entries.last().map(|e| e.sha256.clone());
+    /// Create branch `name` from snapshot `from_snapshot` of the current branch.
+    ///
+    /// The snapshot (with its component data, if any) is copied to become the
+    /// new branch's first snapshot; later events on either branch do not
+    /// affect the other. The current branch is unchanged; use
+    /// `switch_branch` to work on the new one.
+    pub fn branch(&mut self, name: &str, from_snapshot: u32) -> Result<(), StoreError> {
+        self.ensure_writable()?;
+        if !is_valid_branch_name(name) {
+            return Err(StoreError::InvalidBranchName(name.into()));
+        }
+        if name == MAIN_BRANCH || self.meta.branches.contains_key(name) {
+            return Err(StoreError::BranchExists(name.into()));
+        }
+        let (snapshot_count, _) = self.counts();
+        if from_snapshot == 0 || from_snapshot > snapshot_count {
+            return Err(StoreError::SnapshotNotFound(from_snapshot));
+        }
 
-        std::fs::write(&path, &compressed)?;
+        let parent = self.meta.current_branch.clone();
+        let snap = self.resolve_snapshot(&parent, from_snapshot)?;
+        let components_filename = component_snapshot_filename(&parent, from_snapshot);
+        let components: Option<StoredComponents> = if self.in_manifest(&components_filename) {
+            Some(self.read_segment("snapshots", &components_filename)?)
+        } else {
+            None
+        };
 
-        self.manifest.entries.push(ManifestEntry {
-            filename,
-            sha256: hash,
-            prev_hash,
-        });
+        std::fs::create_dir_all(self.root.join("snapshots").join(name))?;
+        std::fs::create_dir_all(self.root.join("events").join(name))?;
+        self.write_segment("snapshots", snapshot_filename(name, 1), &snap)?;
+        self.set_replay_cursor(
+            ReplayCursor {
+                segment: 1,
+                offset: 0,
+            },
+            snap.tick,
+        );
+        if let Some(components) = components {
+            let stored = StoredComponents {
+                after_segment: 0,
+                snapshot: components.snapshot,
+            };
+            self.write_segment("snapshots", component_snapshot_filename(name, 1), &stored)?;
+        }
+        self.meta.branches.insert(
+            name.into(),
+            BranchMeta {
+                parent,
+                from_snapshot,
+                snapshot_count: 1,
+                event_segment_count: 0,
+            },
+        );
 
         self.save_meta()?;
         self.save_manifest()?;
         Ok(())
     }
 
+    /// Make `name` the branch that loads, appends and snapshots operate on.
+    pub fn switch_branch(&mut self, name: &str) -> Result<(), StoreError> {
+        self.ensure_writable()?;
+        if name != MAIN_BRANCH && !self.meta.branches.contains_key(name) {
+            return Err(StoreError::UnknownBranch(name.into()));
+        }
+        self.meta.current_branch = name.into();
+        self.save_meta()
+    }
+
+    /// Names of all branches, main first.
+    pub fn branches(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(MAIN_BRANCH).chain(self.meta.branches.keys().map(String::as_str))
+    }
+
+    /// Metadata of a named branch; `None` for main and unknown names.
+    pub fn branch_meta(&self, name: &str) -> Option<&BranchMeta> {
+        self.meta.branches.get(name)
+    }
+
+    /// The branch that loads, appends and snapshots operate on.
+    pub fn current_branch(&self) -> &str {
+        &self.meta.current_branch
+    }
+
+    /// Fold the current branch's snapshots and event segments into a single
+    /// fresh snapshot, delete the superseded files and rechain the integrity
+    /// manifest over the remaining ones.
+    ///
+    /// The branch's history restarts at snapshot 1, so `from_snapshot` of
+    /// branches created from it refers to the numbering before compaction.
+    /// Checkpoints on the branch are forgotten. Other branches' files and
+    /// stored cells are untouched.
+    ///
+    /// With a retention policy set (`set_retention`), compaction instead
+    /// deletes only the snapshots the policy does not keep and the event
+    /// segments before the oldest kept one; see `RetentionPolicy`.
+    pub fn compact(&mut self) -> Result<CompactReport, StoreError> {
+        self.ensure_writable()?;
+        let mut report = match self.meta.retention {
+            Some(policy) => self.compact_retaining(policy)?,
+            None => self.compact_folding()?,
+        };
+        report.bytes_freed += self.gc_chunks()?;
+        Ok(report)
+    }
+
+    /// `compact` without a retention policy.
+    fn compact_folding(&mut self) -> Result<CompactReport, StoreError> {
+        let (world, components) = self.load_latest_with_components()?;
+        let branch = self.meta.current_branch.clone();
+        let (snapshot_count, event_segment_count) = self.counts();
+
+        // Write the fresh snapshot past the existing ones so nothing is
+        // overwritten before the old files are gone.
+        let fresh = snapshot_count + 1;
+        let fresh_files = [
+            snapshot_filename(&branch, fresh),
+            component_snapshot_filename(&branch, fresh),
+        ];
+        self.write_segment(
+            "snapshots",
+            fresh_files[0].clone(),
+            &Snapshot::capture(&world),
+        )?;
+        self.set_replay_cursor(
+            ReplayCursor {
+                segment: 1,
+                offset: 0,
+            },
+            world.tick(),
+        );
+        let stored = StoredComponents {
+            after_segment: 0,
+            snapshot: ComponentSnapshot::capture(&components),
+        };
+        self.write_segment("snapshots", fresh_files[1].clone(), &stored)?;
+
+        let mut report = CompactReport {
+            snapshots_removed: snapshot_count,
+            segments_removed: event_segment_count,
+            bytes_freed: 0,
+        };
+        let mut kept = Vec::with_capacity(self.manifest.entries.len());
+        for entry in std::mem::take(&mut self.manifest.entries) {
+            if branch_of(&entry.filename) != branch
+                || fresh_files.contains(&entry.filename)
+                || entry.filename.ends_with(cells::CELL_SUFFIX)
+                || entry.filename.ends_with(history::HISTORY_SUFFIX)
+            {
+                kept.push(entry);
+                continue;
+            }
+            let path = self.segment_path(&entry.filename);
+            report.bytes_freed += std::fs::metadata(&path)?.len();
+            std::fs::remove_file(&path)?;
+        }
+
+        let renamed = [
+            snapshot_filename(&branch, 1),
+            component_snapshot_filename(&branch, 1),
+        ];
+        for entry in &mut kept {
+            if let Some(i) = fresh_files.iter().position(|f| *f == entry.filename) {
+                std::fs::rename(
+                    self.segment_path(&entry.filename),
+                    self.segment_path(&renamed[i]),
+                )?;
+                entry.filename = renamed[i].clone();
+            }
+        }
+        self.manifest.entries = kept;
+        self.rechain_manifest();
+        self.last_snapshot = None;
+
+        let (snapshot_count, event_segment_count) = self.counts_mut();
+        *snapshot_count = 1;
+        *event_segment_count = 0;
+        self.drop_checkpoints(&branch, 1);
+        self.save_meta()?;
+        self.save_manifest()?;
+        Ok(report)
+    }
+
+    /// Snapshot `index` (1-based) of the current branch, reconstructed from
+    /// its full snapshot if it is a delta.
+    pub fn snapshot(&self, index: u32) -> Result<Snapshot, StoreError> {
+        let (snapshot_count, _) = self.counts();
+        if index == 0 || index > snapshot_count {
+            return Err(StoreError::SnapshotNotFound(index));
+        }
+        self.load_snapshot(index)
+    }
+
+    /// Replay cursor of the current branch's latest snapshot; `None` without
+    /// snapshots or for a snapshot written before cursors were recorded.
+    pub fn replay_cursor(&self) -> Option<ReplayCursor> {
+        self.snapshot_cursor(self.counts().0)
+    }
+
     /// Replay from persistence: load latest snapshot and replay all event segments.
     /// Returns the reconstructed world.
     pub fn replay(&self) -> Result<World, StoreError> {
         self.load_latest()
     }
 
-    /// Verify all integrity hashes in the manifest.
+    /// Sign manifest entries written from now on with `key`, and record its
+    /// public key in `world.meta.json`. Existing entries keep their
+    /// signatures; `sign_manifest` re-signs them.
+    #[cfg(feature = "signing")]
+    pub fn set_signing_key(&mut self, key: SigningKey) -> Result<(), StoreError> {
+        self.ensure_writable()?;
+        self.meta.signed_by = Some(key.verifying_key().to_hex());
+        self.signing_key = Some(key);
+        self.save_meta()
+    }
+
+    /// Sign every manifest entry with the key from `set_signing_key`. Run
+    /// `verify_integrity` first: this vouches for the files as they are.
+    #[cfg(feature = "signing")]
+    pub fn sign_manifest(&mut self) -> Result<(), StoreError> {
+        self.ensure_writable()?;
+        if self.signing_key.is_none() {
+            return Err(StoreError::Signing("no signing key set".into()));
+        }
+        for i in 0..self.manifest.entries.len() {
+            self.sign_entry(i);
+        }
+        self.save_manifest()
+    }
+
+    /// Make `verify_integrity` require a valid signature by `key` on every
+    /// manifest entry.
+    #[cfg(feature = "signing")]
+    pub fn require_signatures(&mut self, key: VerifyingKey) {
+        self.trusted_key = Some(key);
+    }
+
+    /// Verify all integrity hashes in the manifest, and signatures if
+    /// `require_signatures` set a trusted key.
     pub fn verify_integrity(&self) -> Result<(), StoreError> {
         let mut prev_hash: Option<String> = None;
         for entry in &self.manifest.entries {
@@ -243,21 +954,12 @@ impl WorldStore {
             if entry.prev_hash != prev_hash {
                 return Err(StoreError::IntegrityMismatch {
                     expected: prev_hash.unwrap_or_else(|| "None".into()),
-                    actual: entry
-                        .prev_hash
-                        .clone()
-                        .unwrap_or_else(|| "None".into()),
+                    actual: entry.prev_hash.clone().unwrap_or_else(|| "None".into()),
                 });
             }
 
             // Find the file and verify its hash
-            let file_path = if entry.filename.contains("snapshot") {
-                self.root.join("snapshots").join(&entry.filename)
-            } else {
-                self.root.join("events").join(&entry.filename)
-            };
-
-            let data = std::fs::read(&file_path)?;
+            let data = std::fs::read(self.segment_path(&entry.filename))?;
             let actual_hash = sha256_hex(&data);
             if actual_hash != entry.sha256 {
                 return Err(StoreError::IntegrityMismatch {
@@ -265,12 +967,21 @@ impl WorldStore {
                     actual: actual_hash,
                 });
             }
+            self.verify_signature(entry)?;
+            if entry.chunked {
+                self.load_chunks(&self.read_chunk_list(&entry.filename)?)?;
+            }
 
             prev_hash = Some(entry.sha256.clone());
         }
         Ok(())
     }
 
+    /// True if the store was opened with `open_read_only`.
+    pub fn is_read_only(&self) -> bool {
+        self.lock.is_none()
+    }
+
     /// Get the path to the store root.
     pub fn root(&self) -> &Path {
         &self.root
@@ -281,29 +992,259 @@ impl WorldStore {
         &self.meta
     }
 
+    /// Where a manifest entry's file lives.
+    fn segment_path(&self, filename: &str) -> PathBuf {
+        self.root.join(segment_dir(filename)).join(filename)
+    }
+
+    /// Snapshot and event segment counts of the current branch.
+    fn counts(&self) -> (u32, u32) {
+        match self.meta.branches.get(&self.meta.current_branch) {
+            Some(branch) => (branch.snapshot_count, branch.event_segment_count),
+            None => (self.meta.snapshot_count, self.meta.event_segment_count),
+        }
+    }
+
+    fn counts_mut(&mut self) -> (&mut u32, &mut u32) {
+        match self.meta.branches.get_mut(&self.meta.current_branch) {
+            Some(branch) => (&mut branch.snapshot_count, &mut branch.event_segment_count),
+            None => (
+                &mut self.meta.snapshot_count,
+                &mut self.meta.event_segment_count,
+            ),
+        }
+    }
+
     fn load_snapshot(&self, index: u32) -> Result<Snapshot, StoreError> {
-        let filename = format!("{:06}.snapshot.cbor.zst", index);
-        let path = self.root.join("snapshots").join(&filename);
-        let compressed = std::fs::read(&path)?;
+        self.resolve_snapshot(&self.meta.current_branch, index)
+    }
 
-        // Verify hash against manifest
-        self.verify_file_hash(&filename, &compressed)?;
+    /// Read snapshot `index` of `branch`, applying any delta snapshots on top
+    /// of the nearest full snapshot before it.
+    fn resolve_snapshot(&self, branch: &str, index: u32) -> Result<Snapshot, StoreError> {
+        let mut deltas = Vec::new();
+        let mut base_idx = index;
+        while self.in_manifest(&delta_filename(branch, base_idx)) {
+            let delta: DeltaSnapshot =
+                self.read_segment("snapshots", &delta_filename(branch, base_idx))?;
+            deltas.push(delta);
+            base_idx -= 1;
+        }
 
-        let cbor_bytes = zstd_decompress(&compressed)?;
-        cbor_deserialize(&cbor_bytes)
+        let mut snap: Snapshot =
+            self.read_segment("snapshots", &snapshot_filename(branch, base_idx))?;
+        for delta in deltas.iter().rev() {
+            snap = delta
+                .apply(&snap)
+                .ok_or_else(|| StoreError::IntegrityMismatch {
+                    expected: delta.base_hash.clone(),
+                    actual: snap.hash.clone(),
+                })?;
+        }
+        Ok(snap)
+    }
+
+    /// Write a component snapshot for the latest snapshot of the current branch.
+    fn write_component_snapshot(&mut self, components: &ComponentStore) -> Result<(), StoreError> {
+        let (snapshot_count, event_segment_count) = self.counts();
+        let stored = StoredComponents {
+            after_segment: event_segment_count,
+            snapshot: ComponentSnapshot::capture(components),
+        };
+        let filename = component_snapshot_filename(&self.meta.current_branch, snapshot_count);
+        self.write_segment("snapshots", filename, &stored)?;
+        self.save_manifest()?;
+        Ok(())
+    }
+
+    /// Cursor for a snapshot of `world` taken now: the world's pending events
+    /// open the next segment.
+    fn next_cursor(&self, world: &World) -> ReplayCursor {
+        ReplayCursor {
+            segment: self.counts().1 + 1,
+            offset: world.events().len() as u32,
+        }
+    }
+
+    /// Record `cursor` and the world's `tick` on the snapshot segment just
+    /// written, re-signing its entry.
+    fn set_replay_cursor(&mut self, cursor: ReplayCursor, tick: u64) {
+        if let Some(entry) = self.manifest.entries.last_mut() {
+            entry.replay_cursor = Some(cursor);
+            entry.snapshot_tick = Some(tick);
+            self.sign_entry(self.manifest.entries.len() - 1);
+        }
+    }
+
+    /// Manifest entry of snapshot `index` of the current branch, full or
+    /// delta.
+    fn snapshot_entry(&self, index: u32) -> Option<&ManifestEntry> {
+        if index == 0 {
+            return None;
+        }
+        let branch = &self.meta.current_branch;
+        let names = [
+            snapshot_filename(branch, index),
+            delta_filename(branch, index),
+        ];
+        self.manifest
+            .entries
+            .iter()
+            .find(|e| names.contains(&e.filename))
+    }
+
+    fn snapshot_cursor(&self, index: u32) -> Option<ReplayCursor> {
+        self.snapshot_entry(index).and_then(|e| e.replay_cursor)
     }
 
     fn load_event_segment(&self, index: u32) -> Result<Vec<WorldEvent>, StoreError> {
-        let filename = format!("{:06}.log.cbor.zst", index);
-        let path = self.root.join("events").join(&filename);
-        let compressed = std::fs::read(&path)?;
+        self.read_segment("events", &log_filename(&self.meta.current_branch, index))
+    }
+
+    /// Compress and write a segment, chaining its hash into the manifest.
+    /// Callers save the manifest once all segments of an operation are written.
+    fn write_segment<T: Serialize + ?Sized>(
+        &mut self,
+        dir: &str,
+        filename: String,
+        value: &T,
+    ) -> Result<(), StoreError> {
+        self.ensure_writable()?;
+        let path = self.root.join(dir).join(&filename);
+
+        let mut cbor_bytes = cbor_serialize(value)?;
+        let chunked = self.meta.chunk_snapshots && chunks::is_chunkable(&filename);
+        if chunked {
+            cbor_bytes = cbor_serialize(&self.store_chunks(&cbor_bytes)?)?;
+        }
+        let compressed = self.seal(zstd_compress(&cbor_bytes)?)?;
+
+        let hash = sha256_hex(&compressed);
+        let prev_hash = self.manifest.entries.last().map(|e| e.sha256.clone());
+
+        let mut file = std::fs::File::create(&path)?;
+        file.write_all(&compressed)?;
+        file.sync_all()?;
 
-        self.verify_file_hash(&filename, &compressed)?;
+        self.manifest.entries.push(ManifestEntry {
+            filename,
+            sha256: hash,
+            prev_hash,
+            replay_cursor: None,
+            snapshot_tick: None,
+            signature: None,
+            created_at: Some(unix_now()),
+            chunked,
+        });
+        self.sign_entry(self.manifest.entries.len() - 1);
+        Ok(())
+    }
 
-        let cbor_bytes = zstd_decompress(&compressed)?;
+    fn read_segment<T: for<'de> Deserialize<'de>>(
+        &self,
+        dir: &str,
+        filename: &str,
+    ) -> Result<T, StoreError> {
+        let path = self.root.join(dir).join(filename);
+        let mut cbor_bytes = self.decode_segment(filename, std::fs::read(&path)?)?;
+        if self
+            .manifest
+            .entries
+            .iter()
+            .any(|e| e.filename == filename && e.chunked)
+        {
+            cbor_bytes = self.load_chunks(&cbor_deserialize(&cbor_bytes)?)?;
+        }
         cbor_deserialize(&cbor_bytes)
     }
 
+    /// Check a segment file's contents against the manifest, then decrypt
+    /// and decompress them.
+    fn decode_segment(&self, filename: &str, compressed: Vec<u8>) -> Result<Vec<u8>, StoreError> {
+        self.verify_file_hash(filename, &compressed)?;
+        zstd_decompress(&self.unseal(compressed)?)
+    }
+
+    /// Encrypt a compressed segment if the store has a key.
+    #[cfg(feature = "encryption")]
+    fn seal(&self, data: Vec<u8>) -> Result<Vec<u8>, StoreError> {
+        match &self.key {
+            Some(key) => key.seal(&data),
+            None => Ok(data),
+        }
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn seal(&self, data: Vec<u8>) -> Result<Vec<u8>, StoreError> {
+        Ok(data)
+    }
+
+    /// Decrypt a segment read from disk if the store has a key.
+    #[cfg(feature = "encryption")]
+    fn unseal(&self, data: Vec<u8>) -> Result<Vec<u8>, StoreError> {
+        match &self.key {
+            Some(key) => key.open(&data),
+            None => Ok(data),
+        }
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn unseal(&self, data: Vec<u8>) -> Result<Vec<u8>, StoreError> {
+        Ok(data)
+    }
+
+    /// Recompute the manifest's hash chain after entries were removed or
+    /// renamed.
+    fn rechain_manifest(&mut self) {
+        let mut prev_hash = None;
+        for entry in &mut self.manifest.entries {
+            entry.prev_hash = prev_hash;
+            prev_hash = Some(entry.sha256.clone());
+        }
+        for i in 0..self.manifest.entries.len() {
+            self.sign_entry(i);
+        }
+    }
+
+    /// Sign manifest entry `index` if the store has a signing key.
+    #[cfg(feature = "signing")]
+    fn sign_entry(&mut self, index: usize) {
+        if let Some(key) = &self.signing_key {
+            let entry = &mut self.manifest.entries[index];
+            entry.signature = Some(key.sign(entry));
+        }
+    }
+
+    #[cfg(not(feature = "signing"))]
+    fn sign_entry(&mut self, _index: usize) {}
+
+    /// Check an entry's signature against the trusted key, if one is set.
+    #[cfg(feature = "signing")]
+    fn verify_signature(&self, entry: &ManifestEntry) -> Result<(), StoreError> {
+        match &self.trusted_key {
+            Some(key) if !key.verify(entry) => {
+                Err(StoreError::SignatureInvalid(entry.filename.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    #[cfg(not(feature = "signing"))]
+    fn verify_signature(&self, _entry: &ManifestEntry) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    fn ensure_writable(&self) -> Result<(), StoreError> {
+        match self.lock {
+            Some(_) => Ok(()),
+            None => Err(StoreError::ReadOnly),
+        }
+    }
+
+    fn in_manifest(&self, filename: &str) -> bool {
+        self.manifest.entries.iter().any(|e| e.filename == filename)
+    }
+
     fn verify_file_hash(&self, filename: &str, data: &[u8]) -> Result<(), StoreError> {
         let actual = sha256_hex(data);
         for entry in &self.manifest.entries {
@@ -321,19 +1262,109 @@ impl WorldStore {
         Ok(())
     }
 
+    fn read_meta(root: &Path) -> Result<WorldMeta, StoreError> {
+        let file = std::fs::File::open(root.join("world.meta.json"))?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
     fn save_meta(&self) -> Result<(), StoreError> {
-        let path = self.root.join("world.meta.json");
-        serde_json::to_writer_pretty(std::fs::File::create(path)?, &self.meta)?;
-        Ok(())
+        self.ensure_writable()?;
+        let json = serde_json::to_vec_pretty(&self.meta)?;
+        write_atomic(&self.root.join("world.meta.json"), &json)
     }
 
     fn save_manifest(&self) -> Result<(), StoreError> {
-        let path = self.root.join("integrity").join("manifest.json");
-        serde_json::to_writer_pretty(std::fs::File::create(path)?, &self.manifest)?;
-        Ok(())
+        self.ensure_writable()?;
+        let json = serde_json::to_vec_pretty(&self.manifest)?;
+        write_atomic(&self.root.join("integrity").join("manifest.json"), &json)
     }
 }
 
+/// Write `data` next to `path`, sync it and rename it over `path`, so readers
+/// see either the old or the new file.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), StoreError> {
+    let tmp = path.with_extension("tmp");
+    let mut file = std::fs::File::create(&tmp)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// The directory a manifest entry's file lives in.
+/// `world` without the entities `components` tags `NoSerialize`. They are
+/// paged out rather than despawned, so the world's event log, and with it
+/// the replay cursor, is unchanged.
+fn persisted_world<'a>(world: &'a World, components: &ComponentStore) -> Cow<'a, World> {
+    let mut skipped = components.with_tag(Tag::NoSerialize).peekable();
+    if skipped.peek().is_none() {
+        return Cow::Borrowed(world);
+    }
+    let mut world = world.clone();
+    for id in skipped {
+        world.page_out(id);
+    }
+    Cow::Owned(world)
+}
+
+fn segment_dir(filename: &str) -> &'static str {
+    if filename.ends_with(".snapshot.cbor.zst") {
+        "snapshots"
+    } else if filename.ends_with(cells::CELL_SUFFIX) {
+        "cells"
+    } else if filename.ends_with(history::HISTORY_SUFFIX) {
+        "history"
+    } else {
+        "events"
+    }
+}
+
+/// Branch names become directory names, so they are limited to ASCII
+/// letters, digits, `-` and `_`.
+fn is_valid_branch_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Main-branch files sit directly in their directory, other branches' files
+/// in a subdirectory named after the branch.
+fn branch_filename(branch: &str, filename: String) -> String {
+    if branch == MAIN_BRANCH {
+        filename
+    } else {
+        format!("{branch}/{filename}")
+    }
+}
+
+/// The branch a manifest entry's file belongs to.
+fn branch_of(filename: &str) -> &str {
+    filename
+        .split_once('/')
+        .map_or(MAIN_BRANCH, |(branch, _)| branch)
+}
+
+fn snapshot_filename(branch: &str, index: u32) -> String {
+    branch_filename(branch, format!("{:06}.snapshot.cbor.zst", index))
+}
+
+fn delta_filename(branch: &str, index: u32) -> String {
+    branch_filename(branch, format!("{:06}.delta.snapshot.cbor.zst", index))
+}
+
+fn log_filename(branch: &str, index: u32) -> String {
+    branch_filename(branch, format!("{:06}.log.cbor.zst", index))
+}
+
+fn component_snapshot_filename(branch: &str, index: u32) -> String {
+    branch_filename(branch, format!("{:06}.components.snapshot.cbor.zst", index))
+}
+
+fn component_log_filename(branch: &str, index: u32) -> String {
+    branch_filename(branch, format!("{:06}.components.log.cbor.zst", index))
+}
+
 fn cbor_serialize<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, StoreError> {
     let mut buf = Vec::new();
     ciborium::into_writer(value, &mut buf).map_err(|e| StoreError::CborEncode(e.to_string()))?;
@@ -357,6 +1388,12 @@ fn zstd_decompress(data: &[u8]) -> Result<Vec<u8>, StoreError> {
     Ok(buf)
 }
 
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
 fn sha256_hex(data: &[u8]) -> String {
     let mut hasher = Sha256::new();
     hasher.update(data);
@@ -393,7 +1430,7 @@ mod tests {
         store.append_events(&world.drain_events()).unwrap();
 
         // Reopen and load
-        let store2 = WorldStore::open(tmp.path().join("world_data")).unwrap();
+        let store2 = WorldStore::open_read_only(tmp.path().join("world_data")).unwrap();
         let loaded = store2.load_latest().unwrap();
         assert_eq!(loaded.tick(), world.tick());
         assert_eq!(loaded.seed(), world.seed());
@@ -435,7 +1472,7 @@ mod tests {
         std::fs::write(&snap_path, &data).unwrap();
 
         // Reopen and verify ... should fail
-        let store2 = WorldStore::open(&path).unwrap();
+        let store2 = WorldStore::open_read_only(&path).unwrap();
         assert!(store2.verify_integrity().is_err());
     }
 
@@ -462,6 +1499,596 @@ mod tests {
         let store = WorldStore::open(tmp.path().join("world_data")).unwrap();
         assert_eq!(store.meta().world_schema_version, WORLD_SCHEMA_VERSION);
         assert_eq!(store.meta().event_schema_version, EVENT_SCHEMA_VERSION);
+        assert_eq!(
+            store.meta().component_schema_version,
+            COMPONENT_SCHEMA_VERSION
+        );
+    }
+
+    #[test]
+    fn transient_tag_events_are_not_persisted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+
+        let mut world = World::with_seed(3);
+        let id = world.spawn(Transform::default());
+        let mut components = ComponentStore::new();
+        store
+            .take_snapshot_with_components(&world, &components)
+            .unwrap();
+        store
+            .append_events_with_components(&world.drain_events(), &components.drain_events())
+            .unwrap();
+
+        components.add_tag(id, Tag::Selected);
+        store
+            .append_events_with_components(&[], &components.drain_events())
+            .unwrap();
+        assert_eq!(store.meta().event_segment_count, 1);
+
+        components.add_tag(id, Tag::Resident);
+        components.add_tag(id, Tag::Static);
+        store
+            .append_events_with_components(&[], &components.drain_events())
+            .unwrap();
+        let (_, loaded) = store.load_latest_with_components().unwrap();
+        assert!(loaded.has_tag(id, Tag::Static));
+        assert!(!loaded.has_tag(id, Tag::Selected));
+        assert!(!loaded.has_tag(id, Tag::Resident));
+    }
+
+    #[test]
+    fn component_segments_written_and_verified() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+
+        let mut world = World::with_seed(3);
+        let id = world.spawn(Transform::default());
+        let mut components = ComponentStore::new();
+        components.set_name(id, "Crate".into());
+
+        store
+            .take_snapshot_with_components(&world, &components)
+            .unwrap();
+        store
+            .append_events_with_components(&world.drain_events(), &components.drain_events())
+            .unwrap();
+
+        assert!(
+            path.join("snapshots")
+                .join("000001.components.snapshot.cbor.zst")
+                .exists()
+        );
+        assert!(
+            path.join("events")
+                .join("000001.components.log.cbor.zst")
+                .exists()
+        );
+        assert_eq!(store.meta().event_segment_count, 1);
+        WorldStore::open_read_only(&path)
+            .unwrap()
+            .verify_integrity()
+            .unwrap();
+    }
+
+    #[test]
+    fn store_load_with_components() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+
+        let mut world = World::with_seed(3);
+        let id = world.spawn(Transform::default());
+        let mut components = ComponentStore::new();
+        components.set_name(id, "Crate".into());
+        store
+            .take_snapshot_with_components(&world, &components)
+            .unwrap();
+
+        components.set_name(id, "Barrel".into());
+        store
+            .append_events_with_components(&world.drain_events(), &components.drain_events())
+            .unwrap();
+
+        let (loaded, loaded_components) = WorldStore::open_read_only(&path)
+            .unwrap()
+            .load_latest_with_components()
+            .unwrap();
+        assert!(loaded.get(id).is_some());
+        assert_eq!(loaded_components.get_name(id).unwrap().0, "Barrel");
+    }
+
+    #[test]
+    fn no_serialize_entities_are_not_persisted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+
+        let mut world = World::with_seed(3);
+        let kept = world.spawn(Transform::default());
+        let scratch = world.spawn(Transform::default());
+        let mut components = ComponentStore::new();
+        components.set_name(kept, "Crate".into());
+        components.set_name(scratch, "Preview".into());
+        components.add_tag(scratch, Tag::NoSerialize);
+        store
+            .take_snapshot_with_components(&world, &components)
+            .unwrap();
+        assert!(world.get(scratch).is_some());
+
+        let (loaded, loaded_components) = WorldStore::open_read_only(&path)
+            .unwrap()
+            .load_latest_with_components()
+            .unwrap();
+        assert!(loaded.get(kept).is_some());
+        assert!(loaded.get(scratch).is_none());
+        assert_eq!(loaded_components.get_name(kept).unwrap().0, "Crate");
+        assert!(loaded_components.get_name(scratch).is_none());
+    }
+
+    #[test]
+    fn branches_diverge_from_snapshot() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+
+        let mut world = World::with_seed(5);
+        let base = world.spawn(Transform::default());
+        let mut components = ComponentStore::new();
+        components.set_name(base, "Crate".into());
+        store
+            .take_snapshot_with_components(&world, &components)
+            .unwrap();
+        world.drain_events();
+        components.drain_events();
+
+        store.branch("experiment", 1).unwrap();
+        assert_eq!(
+            store.branches().collect::<Vec<_>>(),
+            vec![MAIN_BRANCH, "experiment"]
+        );
+        assert_eq!(store.current_branch(), MAIN_BRANCH);
+
+        // Main moves on after the branch point.
+        let main_only = world.spawn(Transform::default());
+        store.append_events(&world.drain_events()).unwrap();
+
+        // The branch starts from snapshot 1 and gets its own segments.
+        store.switch_branch("experiment").unwrap();
+        let (mut branch_world, mut branch_components) =
+            store.load_latest_with_components().unwrap();
+        assert!(branch_world.get(main_only).is_none());
+        branch_components.set_name(base, "Barrel".into());
+        branch_world.spawn(Transform::default());
+        store
+            .append_events_with_components(
+                &branch_world.drain_events(),
+                &branch_components.drain_events(),
+            )
+            .unwrap();
+        assert!(path.join("events/experiment/000001.log.cbor.zst").exists());
+
+        drop(store);
+        let reopened = WorldStore::open(&path).unwrap();
+        assert_eq!(reopened.current_branch(), "experiment");
+        let meta = reopened.branch_meta("experiment").unwrap();
+        assert_eq!((meta.parent.as_str(), meta.from_snapshot), (MAIN_BRANCH, 1));
+        let (loaded, loaded_components) = reopened.load_latest_with_components().unwrap();
+        assert_eq!(loaded.entity_count(), 2);
+        assert_eq!(loaded_components.get_name(base).unwrap().0, "Barrel");
+        reopened.verify_integrity().unwrap();
+
+        let mut reopened = reopened;
+        reopened.switch_branch(MAIN_BRANCH).unwrap();
+        let (main_world, main_components) = reopened.load_latest_with_components().unwrap();
+        assert!(main_world.get(main_only).is_some());
+        assert_eq!(main_components.get_name(base).unwrap().0, "Crate");
+    }
+
+    #[test]
+    fn compact_folds_segments_into_snapshot() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+
+        let mut world = World::with_seed(11);
+        let id = world.spawn(Transform::default());
+        let mut components = ComponentStore::new();
+        components.set_name(id, "Crate".into());
+        store
+            .take_snapshot_with_components(&world, &components)
+            .unwrap();
+        store
+            .append_events_with_components(&world.drain_events(), &components.drain_events())
+            .unwrap();
+        store.branch("experiment", 1).unwrap();
+        for i in 0..3 {
+            world.set_transform(
+                id,
+                Transform {
+                    position: glam::Vec3::splat(i as f32),
+                    ..Transform::default()
+                },
+            );
+            world.step();
+            components.set_name(id, format!("Crate {i}"));
+            store
+                .append_events_with_components(&world.drain_events(), &components.drain_events())
+                .unwrap();
+        }
+        let (before, before_components) = store.load_latest_with_components().unwrap();
+
+        let report = store.compact().unwrap();
+        assert_eq!(report.snapshots_removed, 1);
+        assert_eq!(report.segments_removed, 4);
+        assert!(report.bytes_freed > 0);
+        assert_eq!(store.meta().snapshot_count, 1);
+        assert_eq!(store.meta().event_segment_count, 0);
+        assert!(!path.join("events").join("000001.log.cbor.zst").exists());
+        assert!(
+            path.join("snapshots")
+                .join("experiment")
+                .join("000001.snapshot.cbor.zst")
+                .exists()
+        );
+
+        drop(store);
+        let reopened = WorldStore::open(&path).unwrap();
+        reopened.verify_integrity().unwrap();
+        let (after, after_components) = reopened.load_latest_with_components().unwrap();
+        assert_eq!(after.state_hash(), before.state_hash());
+        assert_eq!(
+            after_components.get_name(id),
+            before_components.get_name(id)
+        );
+    }
+
+    #[test]
+    fn delta_snapshots_reconstruct_on_load() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+
+        let mut world = World::with_seed(13);
+        let ids: Vec<_> = (0..100)
+            .map(|_| world.spawn(Transform::default()))
+            .collect();
+        store.take_snapshot(&world).unwrap();
+        let full_len = std::fs::metadata(path.join("snapshots/000001.snapshot.cbor.zst"))
+            .unwrap()
+            .len();
+
+        world.set_transform(
+            ids[3],
+            Transform {
+                position: glam::Vec3::Y,
+                ..Transform::default()
+            },
+        );
+        world.step();
+        store.take_delta_snapshot(&world).unwrap();
+        world.despawn(ids[7]);
+        store.take_delta_snapshot(&world).unwrap();
+
+        let delta_len = std::fs::metadata(path.join("snapshots/000002.delta.snapshot.cbor.zst"))
+            .unwrap()
+            .len();
+        assert!(delta_len < full_len);
+        assert_eq!(store.meta().snapshot_count, 3);
+
+        // A reopened store has no cached base and reads the chain from disk.
+        drop(store);
+        let mut reopened = WorldStore::open(&path).unwrap();
+        reopened.verify_integrity().unwrap();
+        assert_eq!(
+            reopened.load_latest().unwrap().state_hash(),
+            world.state_hash()
+        );
+        world.spawn(Transform::default());
+        reopened.take_delta_snapshot(&world).unwrap();
+        assert_eq!(
+            reopened.load_latest().unwrap().state_hash(),
+            world.state_hash()
+        );
+
+        reopened.compact().unwrap();
+        assert!(
+            !path
+                .join("snapshots/000002.delta.snapshot.cbor.zst")
+                .exists()
+        );
+        assert_eq!(
+            reopened.load_latest().unwrap().state_hash(),
+            world.state_hash()
+        );
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypted_store_requires_its_key() {
+        use crate::EncryptionKey;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let key = EncryptionKey::new("studio", [1; 32]);
+        let mut store = WorldStore::open_encrypted(&path, key.clone()).unwrap();
+        assert_eq!(store.meta().encryption_key_id.as_deref(), Some("studio"));
+        let mut world = World::with_seed(4);
+        world.spawn(Transform::default());
+        store.take_snapshot(&world).unwrap();
+        drop(store);
+
+        assert!(matches!(
+            WorldStore::open(&path),
+            Err(StoreError::EncryptionKeyMismatch { actual: None, .. })
+        ));
+        let wrong = WorldStore::open_encrypted(&path, EncryptionKey::new("studio", [2; 32]));
+        assert!(matches!(
+            wrong.unwrap().load_latest(),
+            Err(StoreError::Encryption(_))
+        ));
+        let reopened = WorldStore::open_encrypted(&path, key).unwrap();
+        reopened.verify_integrity().unwrap();
+        assert_eq!(
+            reopened.load_latest().unwrap().state_hash(),
+            world.state_hash()
+        );
+
+        let plain = tmp.path().join("plain");
+        WorldStore::open(&plain).unwrap();
+        assert!(matches!(
+            WorldStore::open_encrypted(&plain, EncryptionKey::new("studio", [1; 32])),
+            Err(StoreError::EncryptionKeyMismatch { expected: None, .. })
+        ));
+    }
+
+    #[test]
+    fn replay_starts_at_snapshot_cursor() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut store = WorldStore::open(tmp.path().join("world_data")).unwrap();
+        assert_eq!(store.replay_cursor(), None);
+
+        let mut world = World::with_seed(8);
+        world.spawn(Transform::default());
+        let discarded = world.spawn(Transform::default());
+        store.append_events(&world.drain_events()).unwrap();
+        // The despawn never reaches the log; the snapshot alone records it.
+        world.despawn(discarded);
+        world.drain_events();
+        store.take_snapshot(&world).unwrap();
+        assert_eq!(
+            store.replay_cursor(),
+            Some(ReplayCursor {
+                segment: 2,
+                offset: 0
+            })
+        );
+        assert_eq!(store.load_latest().unwrap().entity_count(), 1);
+
+        // Pending events are part of the snapshot and open the next segment.
+        world.spawn(Transform::default());
+        world.step();
+        store.take_snapshot(&world).unwrap();
+        assert_eq!(
+            store.replay_cursor(),
+            Some(ReplayCursor {
+                segment: 2,
+                offset: 2
+            })
+        );
+        store.append_events(&world.drain_events()).unwrap();
+        world.spawn(Transform::default());
+        store.append_events(&world.drain_events()).unwrap();
+
+        let loaded = store.load_latest().unwrap();
+        assert_eq!(loaded.entity_count(), 3);
+        assert_eq!(loaded.state_hash(), world.state_hash());
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn signed_manifest_verifies_against_trusted_key() {
+        use crate::SigningKey;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let mut store = WorldStore::open(tmp.path().join("world_data")).unwrap();
+        let mut world = World::with_seed(11);
+        store.take_snapshot(&world).unwrap();
+
+        let key = SigningKey::from_bytes([9; 32]);
+        store.set_signing_key(key.clone()).unwrap();
+        assert_eq!(store.meta().signed_by, Some(key.verifying_key().to_hex()));
+        world.spawn(Transform::default());
+        store.append_events(&world.drain_events()).unwrap();
+        store.verify_integrity().unwrap();
+
+        // The snapshot was written before the key was set.
+        store.require_signatures(key.verifying_key());
+        assert!(matches!(
+            store.verify_integrity(),
+            Err(StoreError::SignatureInvalid(f)) if f == "000001.snapshot.cbor.zst"
+        ));
+        store.sign_manifest().unwrap();
+        store.verify_integrity().unwrap();
+
+        store.require_signatures(SigningKey::from_bytes([10; 32]).verifying_key());
+        assert!(matches!(
+            store.verify_integrity(),
+            Err(StoreError::SignatureInvalid(_))
+        ));
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn tampered_replay_cursor_fails_signature_check() {
+        use crate::SigningKey;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let key = SigningKey::from_bytes([9; 32]);
+        {
+            let mut store = WorldStore::open(&path).unwrap();
+            store.set_signing_key(key.clone()).unwrap();
+            let mut world = World::with_seed(11);
+            world.spawn(Transform::default());
+            store.append_events(&world.drain_events()).unwrap();
+            store.take_snapshot(&world).unwrap();
+        }
+
+        let manifest_path = path.join("integrity").join("manifest.json");
+        let mut manifest: IntegrityManifest =
+            serde_json::from_slice(&std::fs::read(&manifest_path).unwrap()).unwrap();
+        let entry = manifest
+            .entries
+            .iter_mut()
+            .find(|e| e.replay_cursor.is_some())
+            .unwrap();
+        entry.replay_cursor = Some(ReplayCursor {
+            segment: 1,
+            offset: 0,
+        });
+        std::fs::write(&manifest_path, serde_json::to_vec(&manifest).unwrap()).unwrap();
+
+        let mut store = WorldStore::open(&path).unwrap();
+        store.require_signatures(key.verifying_key());
+        assert!(matches!(
+            store.verify_integrity(),
+            Err(StoreError::SignatureInvalid(f)) if f == "000001.snapshot.cbor.zst"
+        ));
+    }
+
+    #[test]
+    fn second_writer_is_locked_out() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let store = WorldStore::open(&path).unwrap();
+        assert!(!store.is_read_only());
+        match WorldStore::open(&path) {
+            Err(StoreError::Locked(holder)) => {
+                assert!(holder.contains(&std::process::id().to_string()))
+            }
+            other => panic!("expected Locked, got {:?}", other.map(|_| ())),
+        }
+        drop(store);
+        WorldStore::open(&path).unwrap();
+    }
+
+    #[test]
+    fn read_only_open_coexists_with_writer() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        assert!(matches!(
+            WorldStore::open_read_only(&path),
+            Err(StoreError::NotInitialized)
+        ));
+        let mut writer = WorldStore::open(&path).unwrap();
+        let mut world = World::with_seed(12);
+        world.spawn(Transform::default());
+        writer.take_snapshot(&world).unwrap();
+
+        let mut reader = WorldStore::open_read_only(&path).unwrap();
+        assert!(reader.is_read_only());
+        reader.verify_integrity().unwrap();
+        assert_eq!(reader.load_latest().unwrap().entity_count(), 1);
+        assert!(matches!(
+            reader.take_snapshot(&world),
+            Err(StoreError::ReadOnly)
+        ));
+        assert!(matches!(
+            reader.append_events(&world.drain_events()),
+            Err(StoreError::ReadOnly)
+        ));
+        assert_eq!(reader.meta().snapshot_count, 1);
+    }
+
+    #[test]
+    fn world_at_replays_to_requested_tick() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut store = WorldStore::open(tmp.path().join("world_data")).unwrap();
+        let mut world = World::with_seed(14);
+        world.set_tick(2);
+        store.take_snapshot(&world).unwrap();
+
+        // hashes[t] is the state at the end of tick t + 2, after its edits.
+        let mut hashes = Vec::new();
+        for t in 2..8 {
+            world.spawn(Transform::default());
+            hashes.push(world.state_hash());
+            if t == 5 {
+                store.take_snapshot(&world).unwrap();
+            }
+            world.step();
+            store.append_events(&world.drain_events()).unwrap();
+        }
+
+        for (t, hash) in (2..8).zip(&hashes) {
+            let at = store.world_at(t).unwrap();
+            assert_eq!((at.tick(), at.state_hash()), (t, *hash), "tick {t}");
+        }
+        assert_eq!(
+            store.world_at(100).unwrap().state_hash(),
+            world.state_hash()
+        );
+        assert!(matches!(
+            store.world_at(1),
+            Err(StoreError::TickNotFound(1))
+        ));
+    }
+
+    #[test]
+    fn world_at_picks_its_base_snapshot_by_recorded_tick() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+        let mut world = World::with_seed(15);
+        for _ in 0..3 {
+            world.spawn(Transform::default());
+            store.take_snapshot(&world).unwrap();
+            world.step();
+            store.append_events(&world.drain_events()).unwrap();
+        }
+        let ticks: Vec<Option<u64>> = store
+            .manifest
+            .entries
+            .iter()
+            .filter(|e| e.filename.ends_with(".snapshot.cbor.zst"))
+            .map(|e| e.snapshot_tick)
+            .collect();
+        assert_eq!(ticks, vec![Some(0), Some(1), Some(2)]);
+
+        // Snapshots past the requested tick are skipped without being read.
+        std::fs::write(
+            path.join("snapshots").join("000003.snapshot.cbor.zst"),
+            b"junk",
+        )
+        .unwrap();
+        assert_eq!(store.world_at(1).unwrap().tick(), 1);
+        assert!(store.world_at(2).is_err());
+    }
+
+    #[test]
+    fn branch_rejects_bad_requests() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut store = WorldStore::open(tmp.path().join("world_data")).unwrap();
+        assert!(matches!(
+            store.branch("experiment", 1),
+            Err(StoreError::SnapshotNotFound(1))
+        ));
+        assert!(matches!(
+            store.branch("../escape", 1),
+            Err(StoreError::InvalidBranchName(_))
+        ));
+        assert!(matches!(
+            store.branch(MAIN_BRANCH, 1),
+            Err(StoreError::BranchExists(_))
+        ));
+        assert!(matches!(
+            store.switch_branch("missing"),
+            Err(StoreError::UnknownBranch(_))
+        ));
+        assert_eq!(store.branches().collect::<Vec<_>>(), vec![MAIN_BRANCH]);
     }
 
     /// Phase I: persistence round-trip preserves state_hash
@@ -485,7 +2112,7 @@ mod tests {
         store.take_snapshot(&world).unwrap();
 
         // Reopen and load
-        let store2 = WorldStore::open(&path).unwrap();
+        let store2 = WorldStore::open_read_only(&path).unwrap();
         let loaded = store2.load_latest().unwrap();
         assert_eq!(loaded.state_hash(), hash_before);
     }
@@ -497,7 +2124,7 @@ mod tests {
         let path = tmp.path().join("world_data");
 
         // Create a valid store
-        let _store = WorldStore::open(&path).unwrap();
+        WorldStore::open(&path).unwrap();
 
         // Tamper with the meta file to have a wrong version
         let meta_path = path.join("world.meta.json");
@@ -0,0 +1,163 @@
+//! Single-file `.world` archives of a store directory.
+//!
+//! An archive holds `world.meta.json`, the integrity manifest, every file
+//! the manifest lists and the snapshot chunks they reference, as CBOR
+//! compressed with zstd behind a magic header.
+//! Importing unpacks them into a new directory and verifies the manifest
+//! chain before returning, so a damaged archive is never silently accepted.
+
+use std::path::{Component, Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    StoreError, WorldStore, cbor_deserialize, cbor_serialize, zstd_compress, zstd_decompress,
+};
+
+const ARCHIVE_MAGIC: &[u8; 8] = b"WSWORLD\0";
+const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WorldArchive {
+    version: u32,
+    files: Vec<ArchiveFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchiveFile {
+    /// Path relative to the store root, with `/` separators.
+    path: String,
+    data: Vec<u8>,
+}
+
+impl WorldStore {
+    /// Write the store to a single `.world` archive at `path` for sharing
+    /// or backup. Only files the store manages are included.
+    pub fn export_archive(&self, path: impl AsRef<Path>) -> Result<(), StoreError> {
+        let mut paths = vec![
+            "world.meta.json".to_string(),
+            "integrity/manifest.json".to_string(),
+        ];
+        for entry in &self.manifest.entries {
+            let full = self.segment_path(&entry.filename);
+            let relative = full.strip_prefix(&self.root).unwrap_or(&full);
+            paths.push(
+                relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+            );
+        }
+
+        paths.extend(self.referenced_chunks()?);
+
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let data = std::fs::read(self.root.join(&path))?;
+                Ok(ArchiveFile { path, data })
+            })
+            .collect::<Result<_, StoreError>>()?;
+        let archive = WorldArchive {
+            version: ARCHIVE_VERSION,
+            files,
+        };
+
+        let mut bytes = ARCHIVE_MAGIC.to_vec();
+        bytes.extend(zstd_compress(&cbor_serialize(&archive)?)?);
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    /// Unpack the archive at `archive` into the store directory `dest`, which
+    /// must not already hold a store, and verify its integrity. Open the
+    /// result with `open`, or `open_encrypted` for an encrypted store.
+    pub fn import_archive(
+        archive: impl AsRef<Path>,
+        dest: impl AsRef<Path>,
+    ) -> Result<(), StoreError> {
+        let dest = dest.as_ref();
+        if dest.join("world.meta.json").exists() {
+            return Err(StoreError::Archive(format!(
+                "{} already contains a world store",
+                dest.display()
+            )));
+        }
+
+        let bytes = std::fs::read(archive)?;
+        let body = bytes
+            .strip_prefix(ARCHIVE_MAGIC)
+            .ok_or_else(|| StoreError::Archive("not a world archive".into()))?;
+        let archive: WorldArchive = cbor_deserialize(&zstd_decompress(body)?)?;
+        if archive.version != ARCHIVE_VERSION {
+            return Err(StoreError::SchemaMismatch {
+                file_version: archive.version,
+                expected_version: ARCHIVE_VERSION,
+            });
+        }
+
+        for file in &archive.files {
+            let relative = Path::new(&file.path);
+            if !relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+            {
+                return Err(StoreError::Archive(format!(
+                    "invalid path '{}' in archive",
+                    file.path
+                )));
+            }
+            let target = dest.join(relative);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(target, &file.data)?;
+        }
+
+        let meta = Self::read_meta(dest)?;
+        let store = Self::open_with_key_id(dest, meta.encryption_key_id.as_deref(), true)?;
+        store.verify_integrity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+    use worldspace_kernel::World;
+
+    #[test]
+    fn archive_roundtrip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut store = WorldStore::open(tmp.path().join("world_data")).unwrap();
+        let mut world = World::with_seed(21);
+        world.spawn(Transform::default());
+        store.take_snapshot(&world).unwrap();
+        world.step();
+        store.append_events(&world.drain_events()).unwrap();
+
+        let archive = tmp.path().join("shared.world");
+        store.export_archive(&archive).unwrap();
+        assert!(WorldStore::import_archive(&archive, tmp.path().join("world_data")).is_err());
+
+        let copy = tmp.path().join("copy");
+        WorldStore::import_archive(&archive, &copy).unwrap();
+        let imported = WorldStore::open(&copy).unwrap();
+        assert_eq!(
+            imported.load_latest().unwrap().state_hash(),
+            store.load_latest().unwrap().state_hash()
+        );
+    }
+
+    #[test]
+    fn import_rejects_foreign_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let bogus = tmp.path().join("bogus.world");
+        std::fs::write(&bogus, b"PK\x03\x04 not ours").unwrap();
+        assert!(matches!(
+            WorldStore::import_archive(&bogus, tmp.path().join("dest")),
+            Err(StoreError::Archive(_))
+        ));
+    }
+}
@@ -0,0 +1,274 @@
+//! Per-cell partitioned persistence for streaming worlds.
+//!
+//! Besides snapshots, entities can be stored bucketed by streaming
+//! `CellCoord`, one file per cell, so a streaming world pages regions in and
+//! out without holding the whole world in memory. Cell files are rewritten on
+//! every save of their cell: the old manifest entry is replaced and the hash
+//! chain rebuilt. Like other segments, cells belong to the current branch.
+//!
+//! Cells of a flat partition are named `x_z`; cells of a layered partition
+//! are named `x_y_z`, except on layer 0, which keeps the flat name.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use worldspace_common::EntityId;
+use worldspace_ecs::ComponentStore;
+use worldspace_kernel::{EntityData, World};
+use worldspace_stream::{CellCoord, GridPartition};
+
+use super::{StoreError, WorldStore, branch_filename, branch_of, persisted_world};
+
+pub(super) const CELL_SUFFIX: &str = ".cell.cbor.zst";
+
+/// One cell's entities as written to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredCell {
+    x: i32,
+    #[serde(default)]
+    y: i32,
+    z: i32,
+    entities: BTreeMap<EntityId, EntityData>,
+}
+
+impl WorldStore {
+    /// Write the entities of `world` that lie in `coord` under `grid`'s cell
+    /// size, replacing what was stored for the cell. Returns the number of
+    /// entities written.
+    ///
+    /// An entity that moved to another cell is dropped from this one, so
+    /// save the cells it left and entered together.
+    pub fn save_cell(
+        &mut self,
+        world: &World,
+        grid: &GridPartition,
+        coord: CellCoord,
+    ) -> Result<usize, StoreError> {
+        self.save_cells(world, grid, &[coord])
+    }
+
+    /// Save each of `coords` like `save_cells`, leaving out the entities
+    /// `components` tags `NoSerialize`.
+    pub fn save_cells_with_components(
+        &mut self,
+        world: &World,
+        components: &ComponentStore,
+        grid: &GridPartition,
+        coords: &[CellCoord],
+    ) -> Result<usize, StoreError> {
+        self.save_cells(&persisted_world(world, components), grid, coords)
+    }
+
+    /// Save each of `coords` like `save_cell`. Cells without entities are
+    /// removed from the store.
+    pub fn save_cells(
+        &mut self,
+        world: &World,
+        grid: &GridPartition,
+        coords: &[CellCoord],
+    ) -> Result<usize, StoreError> {
+        self.ensure_writable()?;
+        match self.meta.cell_size {
+            Some(expected) if expected != grid.cell_size() => {
+                return Err(StoreError::CellSizeMismatch {
+                    expected,
+                    actual: grid.cell_size(),
+                });
+            }
+            Some(_) if self.meta.cell_layer_height != grid.layer_height() => {
+                return Err(StoreError::CellLayerMismatch {
+                    expected: self.meta.cell_layer_height,
+                    actual: grid.layer_height(),
+                });
+            }
+            Some(_) => {}
+            None => {
+                self.meta.cell_size = Some(grid.cell_size());
+                self.meta.cell_layer_height = grid.layer_height();
+            }
+        }
+
+        let mut buckets: BTreeMap<(i32, i32, i32), BTreeMap<EntityId, EntityData>> = coords
+            .iter()
+            .map(|coord| ((coord.x, coord.y, coord.z), BTreeMap::new()))
+            .collect();
+        for (id, data) in world.entities() {
+            let cell = grid.position_to_cell(data.transform.position);
+            if let Some(bucket) = buckets.get_mut(&(cell.x, cell.y, cell.z)) {
+                bucket.insert(*id, data.clone());
+            }
+        }
+
+        let branch = self.meta.current_branch.clone();
+        let dir = self.root.join("cells");
+        std::fs::create_dir_all(dir.join(branch_filename(&branch, String::new())))?;
+        let mut written = 0;
+        for ((x, y, z), entities) in buckets {
+            let filename = cell_filename(&branch, CellCoord::new_3d(x, y, z));
+            if self.in_manifest(&filename) {
+                self.manifest.entries.retain(|e| e.filename != filename);
+                std::fs::remove_file(self.segment_path(&filename))?;
+            }
+            if entities.is_empty() {
+                continue;
+            }
+            written += entities.len();
+            self.write_segment("cells", filename, &StoredCell { x, y, z, entities })?;
+        }
+        self.rechain_manifest();
+
+        self.save_meta()?;
+        self.save_manifest()?;
+        Ok(written)
+    }
+
+    /// Entities stored for `coord` on the current branch; empty if the cell
+    /// was never saved or held no entities.
+    pub fn load_cell(
+        &self,
+        coord: CellCoord,
+    ) -> Result<BTreeMap<EntityId, EntityData>, StoreError> {
+        let filename = cell_filename(&self.meta.current_branch, coord);
+        if !self.in_manifest(&filename) {
+            return Ok(BTreeMap::new());
+        }
+        let stored: StoredCell = self.read_segment("cells", &filename)?;
+        if (stored.x, stored.y, stored.z) != (coord.x, coord.y, coord.z) {
+            return Err(StoreError::IntegrityMismatch {
+                expected: format!("cell ({}, {}, {})", coord.x, coord.y, coord.z),
+                actual: format!("cell ({}, {}, {})", stored.x, stored.y, stored.z),
+            });
+        }
+        Ok(stored.entities)
+    }
+
+    /// Cells stored on the current branch, in manifest order.
+    pub fn stored_cells(&self) -> Vec<CellCoord> {
+        self.manifest
+            .entries
+            .iter()
+            .filter(|e| branch_of(&e.filename) == self.meta.current_branch)
+            .filter_map(|e| {
+                let name = e.filename.rsplit('/').next()?.strip_suffix(CELL_SUFFIX)?;
+                let parts = name
+                    .split('_')
+                    .map(|part| part.parse().ok())
+                    .collect::<Option<Vec<i32>>>()?;
+                match parts[..] {
+                    [x, z] => Some(CellCoord::new(x, z)),
+                    [x, y, z] => Some(CellCoord::new_3d(x, y, z)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+}
+
+fn cell_filename(branch: &str, coord: CellCoord) -> String {
+    let CellCoord { x, y, z } = coord;
+    let name = if y == 0 {
+        format!("{x}_{z}")
+    } else {
+        format!("{x}_{y}_{z}")
+    };
+    branch_filename(branch, format!("{name}{CELL_SUFFIX}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+
+    fn at(x: f32, z: f32) -> Transform {
+        Transform {
+            position: glam::Vec3::new(x, 0.0, z),
+            ..Transform::default()
+        }
+    }
+
+    #[test]
+    fn cells_save_and_load_independently() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+        let grid = GridPartition::new(16.0);
+
+        let mut world = World::with_seed(1);
+        let near = world.spawn(at(1.0, 1.0));
+        let far = world.spawn(at(-20.0, 40.0));
+        let (origin, remote) = (CellCoord::new(0, 0), CellCoord::new(-2, 2));
+        assert_eq!(
+            store.save_cells(&world, &grid, &[origin, remote]).unwrap(),
+            2
+        );
+        assert!(path.join("cells").join("-2_2.cell.cbor.zst").exists());
+        assert_eq!(store.stored_cells(), vec![remote, origin]);
+
+        // Moving an entity across cells and saving both moves it on disk.
+        world.set_transform(near, at(-20.0, 40.0));
+        store.save_cells(&world, &grid, &[origin, remote]).unwrap();
+        assert_eq!(store.stored_cells(), vec![remote]);
+        assert!(!path.join("cells").join("0_0.cell.cbor.zst").exists());
+        assert!(matches!(
+            store.save_cell(&world, &GridPartition::new(8.0), origin),
+            Err(StoreError::CellSizeMismatch { .. })
+        ));
+
+        assert!(matches!(
+            store.save_cell(&world, &GridPartition::new_3d(16.0, 16.0), origin),
+            Err(StoreError::CellLayerMismatch { .. })
+        ));
+
+        let reopened = WorldStore::open_read_only(&path).unwrap();
+        reopened.verify_integrity().unwrap();
+        assert!(reopened.load_cell(origin).unwrap().is_empty());
+        let loaded = reopened.load_cell(remote).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded.contains_key(&near) && loaded.contains_key(&far));
+    }
+
+    #[test]
+    fn no_serialize_entities_are_not_saved_to_cells() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut store = WorldStore::open(tmp.path().join("world_data")).unwrap();
+        let grid = GridPartition::new(16.0);
+
+        let mut world = World::with_seed(1);
+        let kept = world.spawn(at(1.0, 1.0));
+        let scratch = world.spawn(at(2.0, 2.0));
+        let mut components = ComponentStore::new();
+        components.add_tag(scratch, worldspace_ecs::Tag::NoSerialize);
+        let origin = CellCoord::new(0, 0);
+        assert_eq!(
+            store
+                .save_cells_with_components(&world, &components, &grid, &[origin])
+                .unwrap(),
+            1
+        );
+        let loaded = store.load_cell(origin).unwrap();
+        assert!(loaded.contains_key(&kept) && !loaded.contains_key(&scratch));
+    }
+
+    #[test]
+    fn layered_cells_are_stored_per_layer() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+        let grid = GridPartition::new_3d(16.0, 8.0);
+
+        let mut world = World::with_seed(1);
+        let ground = world.spawn(at(1.0, 1.0));
+        let cave = world.spawn(Transform {
+            position: glam::Vec3::new(1.0, -20.0, 1.0),
+            ..Transform::default()
+        });
+        let (surface, below) = (CellCoord::new(0, 0), CellCoord::new_3d(0, -3, 0));
+        store.save_cells(&world, &grid, &[surface, below]).unwrap();
+        assert_eq!(store.meta().cell_layer_height, Some(8.0));
+        assert!(path.join("cells").join("0_-3_0.cell.cbor.zst").exists());
+        assert!(path.join("cells").join("0_0.cell.cbor.zst").exists());
+        assert_eq!(store.stored_cells(), vec![below, surface]);
+        assert!(store.load_cell(surface).unwrap().contains_key(&ground));
+        assert!(store.load_cell(below).unwrap().contains_key(&cave));
+    }
+}
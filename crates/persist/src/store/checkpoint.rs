@@ -0,0 +1,178 @@
+//! Named checkpoints: snapshots with a name and message, recorded in
+//! `world.meta.json` so a world can be returned to "before terrain rework"
+//! instead of a snapshot index.
+
+use serde::{Deserialize, Serialize};
+use worldspace_kernel::World;
+
+use super::{StoreError, WorldStore, unix_now};
+
+/// A named snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Unique name within the store.
+    pub name: String,
+    /// Free-form description, like a commit message.
+    pub message: String,
+    /// The branch the snapshot is on.
+    pub branch: String,
+    /// Index of the snapshot on its branch.
+    pub snapshot: u32,
+    /// World tick of the snapshot.
+    pub tick: u64,
+    /// Creation time in seconds since the Unix epoch.
+    pub created_at: u64,
+}
+
+impl WorldStore {
+    /// Take a snapshot of `world` and record it as checkpoint `name`.
+    pub fn take_checkpoint(
+        &mut self,
+        world: &World,
+        name: &str,
+        message: &str,
+    ) -> Result<&Checkpoint, StoreError> {
+        self.check_checkpoint_name(name)?;
+        self.take_snapshot(world)?;
+        let (snapshot_count, _) = self.counts();
+        self.label_snapshot(snapshot_count, name, message)
+    }
+
+    /// Record existing snapshot `index` of the current branch as checkpoint
+    /// `name`, e.g. after `take_snapshot_with_components`.
+    pub fn label_snapshot(
+        &mut self,
+        index: u32,
+        name: &str,
+        message: &str,
+    ) -> Result<&Checkpoint, StoreError> {
+        self.ensure_writable()?;
+        self.check_checkpoint_name(name)?;
+        let tick = match &self.last_snapshot {
+            Some((branch, i, snap)) if *branch == self.meta.current_branch && *i == index => {
+                snap.tick
+            }
+            _ => self.snapshot(index)?.tick,
+        };
+        let created_at = unix_now();
+        self.meta.checkpoints.push(Checkpoint {
+            name: name.into(),
+            message: message.into(),
+            branch: self.meta.current_branch.clone(),
+            snapshot: index,
+            tick,
+            created_at,
+        });
+        self.save_meta()?;
+        Ok(self.meta.checkpoints.last().expect("just pushed"))
+    }
+
+    /// All checkpoints, oldest first.
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.meta.checkpoints
+    }
+
+    /// The checkpoint named `name`.
+    pub fn checkpoint(&self, name: &str) -> Option<&Checkpoint> {
+        self.meta.checkpoints.iter().find(|c| c.name == name)
+    }
+
+    /// Switch to the checkpoint's branch and restore the world as it was at
+    /// the checkpoint. Events appended after it are not replayed.
+    pub fn load_checkpoint(&mut self, name: &str) -> Result<World, StoreError> {
+        let checkpoint = self
+            .checkpoint(name)
+            .ok_or_else(|| StoreError::UnknownCheckpoint(name.into()))?
+            .clone();
+        if checkpoint.branch != self.meta.current_branch {
+            self.switch_branch(&checkpoint.branch)?;
+        }
+        Ok(self.snapshot(checkpoint.snapshot)?.restore())
+    }
+
+    /// Forget checkpoints of `branch` whose snapshot index is `from` or later,
+    /// after those snapshots were removed.
+    pub(super) fn drop_checkpoints(&mut self, branch: &str, from: u32) {
+        self.meta
+            .checkpoints
+            .retain(|c| c.branch != branch || c.snapshot < from);
+    }
+
+    fn check_checkpoint_name(&self, name: &str) -> Result<(), StoreError> {
+        if name.trim().is_empty() {
+            return Err(StoreError::InvalidCheckpointName(name.into()));
+        }
+        if self.checkpoint(name).is_some() {
+            return Err(StoreError::CheckpointExists(name.into()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+
+    #[test]
+    fn checkpoints_are_listed_and_found_by_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut store = WorldStore::open(tmp.path().join("world_data")).unwrap();
+        let mut world = World::with_seed(13);
+        world.spawn(Transform::default());
+        world.step();
+
+        let checkpoint = store
+            .take_checkpoint(&world, "before terrain rework", "one crate on flat ground")
+            .unwrap();
+        assert_eq!((checkpoint.snapshot, checkpoint.tick), (1, 1));
+        store.take_snapshot(&world).unwrap();
+        store
+            .label_snapshot(2, "after", "same world, second snapshot")
+            .unwrap();
+
+        let names: Vec<_> = store
+            .checkpoints()
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["before terrain rework", "after"]);
+        assert_eq!(store.checkpoint("after").unwrap().branch, "main");
+        assert!(store.checkpoint("missing").is_none());
+
+        assert!(matches!(
+            store.take_checkpoint(&world, "after", ""),
+            Err(StoreError::CheckpointExists(_))
+        ));
+        assert!(matches!(
+            store.label_snapshot(1, " ", ""),
+            Err(StoreError::InvalidCheckpointName(_))
+        ));
+        assert!(matches!(
+            store.label_snapshot(9, "nine", ""),
+            Err(StoreError::SnapshotNotFound(9))
+        ));
+        assert!(matches!(
+            store.load_checkpoint("missing"),
+            Err(StoreError::UnknownCheckpoint(_))
+        ));
+        // A rejected name does not take a snapshot.
+        assert_eq!(store.meta().snapshot_count, 2);
+    }
+
+    #[test]
+    fn load_checkpoint_restores_its_snapshot() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut store = WorldStore::open(tmp.path().join("world_data")).unwrap();
+        let mut world = World::with_seed(13);
+        world.spawn(Transform::default());
+        store.take_checkpoint(&world, "one", "").unwrap();
+        let hash = world.state_hash();
+
+        world.spawn(Transform::default());
+        store.append_events(&world.drain_events()).unwrap();
+        store.take_snapshot(&world).unwrap();
+
+        assert_eq!(store.load_checkpoint("one").unwrap().state_hash(), hash);
+    }
+}
@@ -0,0 +1,296 @@
+//! Content-defined chunk deduplication of snapshot payloads.
+//!
+//! With chunking enabled (`WorldStore::set_snapshot_chunking`), a snapshot's
+//! CBOR payload is cut into chunks at content-defined boundaries (a gear
+//! rolling hash, as in FastCDC) and each chunk is stored once under
+//! `objects/`, named by its SHA-256. The snapshot file itself then holds only
+//! the list of chunk hashes. Successive snapshots of a mostly static world
+//! share almost all of their chunks, and because boundaries follow content
+//! rather than offsets, a change early in the payload does not shift every
+//! later chunk.
+//!
+//! Chunks are compressed and sealed like segments. Their hash covers the
+//! plain chunk and is checked on every read; a stored chunk that fails the
+//! check is written again by the next snapshot containing it, and `repair`
+//! quarantines it. Chunked files are marked in the manifest, so stores
+//! mixing chunked and plain snapshots load normally. `compact` deletes
+//! chunks no snapshot references any more.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    StoreError, WorldStore, cbor_deserialize, sha256_hex, write_atomic, zstd_compress,
+    zstd_decompress,
+};
+
+/// Chunks are never cut shorter than this, except at the end of a payload.
+const MIN_CHUNK: usize = 2 * 1024;
+/// A boundary falls where the rolling hash has these bits clear: 8 KiB on
+/// average.
+const BOUNDARY_MASK: u64 = (1 << 13) - 1;
+/// Chunks are always cut at this length.
+const MAX_CHUNK: usize = 64 * 1024;
+
+/// Random values the rolling hash mixes in per byte.
+static GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    // splitmix64, so the table is fixed without spelling out 256 constants.
+    let mut table = [0; 256];
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+/// The contents of a chunked snapshot file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct ChunkList {
+    /// Hex SHA-256 of each chunk, in payload order.
+    pub(super) chunks: Vec<String>,
+}
+
+/// True for files stored as chunk lists when chunking is enabled: full
+/// world and component snapshots. Delta snapshots are small already.
+pub(super) fn is_chunkable(filename: &str) -> bool {
+    filename.ends_with(".snapshot.cbor.zst") && !filename.ends_with(".delta.snapshot.cbor.zst")
+}
+
+/// Cut `data` at content-defined boundaries.
+fn split(data: &[u8]) -> Vec<&[u8]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut hash: u64 = 0;
+    for (i, &byte) in data.iter().enumerate() {
+        hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        let len = i + 1 - start;
+        if (len >= MIN_CHUNK && hash & BOUNDARY_MASK == 0) || len >= MAX_CHUNK {
+            chunks.push(&data[start..=i]);
+            start = i + 1;
+            hash = 0;
+        }
+    }
+    if start < data.len() {
+        chunks.push(&data[start..]);
+    }
+    chunks
+}
+
+impl WorldStore {
+    /// Store snapshots taken from now on as deduplicated chunks, or stop
+    /// doing so. Existing snapshots keep their format.
+    pub fn set_snapshot_chunking(&mut self, enabled: bool) -> Result<(), StoreError> {
+        self.ensure_writable()?;
+        self.meta.chunk_snapshots = enabled;
+        self.save_meta()
+    }
+
+    /// Write the chunks of `payload` that are not stored yet, or no longer
+    /// match their hash, and return the list that reassembles it.
+    pub(super) fn store_chunks(&self, payload: &[u8]) -> Result<ChunkList, StoreError> {
+        let mut chunks = Vec::new();
+        for chunk in split(payload) {
+            let hash = sha256_hex(chunk);
+            let path = self.object_path(&hash);
+            if !path.exists() || self.read_chunk(&hash).is_err() {
+                std::fs::create_dir_all(path.parent().expect("objects are in a directory"))?;
+                write_atomic(&path, &self.seal(zstd_compress(chunk)?)?)?;
+            }
+            chunks.push(hash);
+        }
+        Ok(ChunkList { chunks })
+    }
+
+    /// Reassemble a payload from its chunks, checking each chunk's hash.
+    pub(super) fn load_chunks(&self, list: &ChunkList) -> Result<Vec<u8>, StoreError> {
+        let mut payload = Vec::new();
+        for hash in &list.chunks {
+            payload.extend(self.read_chunk(hash)?);
+        }
+        Ok(payload)
+    }
+
+    /// Read chunk `hash`, checking its contents against the hash.
+    pub(super) fn read_chunk(&self, hash: &str) -> Result<Vec<u8>, StoreError> {
+        let chunk = zstd_decompress(&self.unseal(std::fs::read(self.object_path(hash))?)?)?;
+        let actual = sha256_hex(&chunk);
+        if actual != hash {
+            return Err(StoreError::IntegrityMismatch {
+                expected: hash.into(),
+                actual,
+            });
+        }
+        Ok(chunk)
+    }
+
+    /// The chunk list in a chunked segment file.
+    pub(super) fn read_chunk_list(&self, filename: &str) -> Result<ChunkList, StoreError> {
+        let data = std::fs::read(self.segment_path(filename))?;
+        cbor_deserialize(&self.decode_segment(filename, data)?)
+    }
+
+    /// Paths relative to the store root of the chunks the chunked files in
+    /// the manifest reference.
+    pub(super) fn referenced_chunks(&self) -> Result<BTreeSet<String>, StoreError> {
+        let mut referenced = BTreeSet::new();
+        for entry in self.manifest.entries.iter().filter(|e| e.chunked) {
+            for hash in self.read_chunk_list(&entry.filename)?.chunks {
+                referenced.insert(object_relative_path(&hash));
+            }
+        }
+        Ok(referenced)
+    }
+
+    /// Copy the chunks `filename` in `other` references and this store
+    /// lacks. Returns the bytes copied.
+    pub(super) fn copy_chunks_from(
+        &self,
+        other: &WorldStore,
+        filename: &str,
+    ) -> Result<u64, StoreError> {
+        let mut copied = 0;
+        for hash in other.read_chunk_list(filename)?.chunks {
+            let path = self.object_path(&hash);
+            if path.exists() {
+                continue;
+            }
+            let data = std::fs::read(other.object_path(&hash))?;
+            std::fs::create_dir_all(path.parent().expect("objects are in a directory"))?;
+            write_atomic(&path, &data)?;
+            copied += data.len() as u64;
+        }
+        Ok(copied)
+    }
+
+    /// Delete chunks no file in the manifest references. Returns the bytes
+    /// freed.
+    pub(super) fn gc_chunks(&self) -> Result<u64, StoreError> {
+        let objects = self.root.join("objects");
+        if !objects.exists() {
+            return Ok(0);
+        }
+        let referenced = self.referenced_chunks()?;
+        let mut freed = 0;
+        for dir in std::fs::read_dir(&objects)? {
+            let dir = dir?.path();
+            for file in std::fs::read_dir(&dir)? {
+                let path = file?.path();
+                let relative = path
+                    .strip_prefix(&self.root)
+                    .unwrap_or(&path)
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                if !referenced.contains(&relative) {
+                    freed += std::fs::metadata(&path)?.len();
+                    std::fs::remove_file(&path)?;
+                }
+            }
+        }
+        Ok(freed)
+    }
+
+    pub(super) fn object_path(&self, hash: &str) -> PathBuf {
+        self.root.join(object_relative_path(hash))
+    }
+}
+
+/// Where chunk `hash` lives, relative to the store root: fanned out by its
+/// first two hex digits.
+pub(super) fn object_relative_path(hash: &str) -> String {
+    format!("objects/{}/{hash}", hash.get(..2).unwrap_or("00"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+    use worldspace_kernel::World;
+
+    fn pseudo_random(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chunks_resynchronize_after_an_insertion() {
+        let data = pseudo_random(256 * 1024, 1);
+        let chunks = split(&data);
+        assert_eq!(chunks.concat(), data);
+        assert!(chunks.len() > 8);
+        assert!(
+            chunks[..chunks.len() - 1]
+                .iter()
+                .all(|c| c.len() >= MIN_CHUNK)
+        );
+        assert!(chunks.iter().all(|c| c.len() <= MAX_CHUNK));
+
+        let mut edited = b"a few new bytes".to_vec();
+        edited.extend(&data);
+        let before: BTreeSet<_> = chunks.iter().map(|c| sha256_hex(c)).collect();
+        let after: Vec<_> = split(&edited).iter().map(|c| sha256_hex(c)).collect();
+        let shared = after.iter().filter(|h| before.contains(*h)).count();
+        assert!(shared >= after.len() - 2, "{shared} of {}", after.len());
+    }
+
+    #[test]
+    fn unchanged_snapshots_share_chunks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+        store.set_snapshot_chunking(true).unwrap();
+        let mut world = World::with_seed(12);
+        for _ in 0..500 {
+            world.spawn(Transform::default());
+        }
+        store.take_snapshot(&world).unwrap();
+        let chunks = store.referenced_chunks().unwrap();
+        store.take_snapshot(&world).unwrap();
+        assert_eq!(store.referenced_chunks().unwrap(), chunks);
+        store.verify_integrity().unwrap();
+
+        store.compact().unwrap();
+        assert_eq!(store.referenced_chunks().unwrap(), chunks);
+        assert_eq!(
+            store.load_latest().unwrap().state_hash(),
+            world.state_hash()
+        );
+    }
+
+    #[test]
+    fn corrupt_chunks_are_rewritten_by_later_snapshots() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+        store.set_snapshot_chunking(true).unwrap();
+        let mut world = World::with_seed(13);
+        for _ in 0..100 {
+            world.spawn(Transform::default());
+        }
+        store.take_snapshot(&world).unwrap();
+        let chunk = store.referenced_chunks().unwrap().pop_first().unwrap();
+        std::fs::write(path.join(&chunk), b"not a chunk").unwrap();
+        assert!(store.verify_integrity().is_err());
+
+        store.take_snapshot(&world).unwrap();
+        store.verify_integrity().unwrap();
+    }
+}
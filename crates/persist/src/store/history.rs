@@ -0,0 +1,83 @@
+//! Editor undo/redo history.
+//!
+//! An `Editor`'s undo and redo stacks are stored as their own segment,
+//! `history/edit.history.cbor.zst`, so reopening a project continues the
+//! previous session's history. Like cells, the file is rewritten on every
+//! save and its manifest entry replaced. Each branch keeps its own history.
+//!
+//! History is editor data, not world data: migrations and compaction leave it
+//! alone.
+
+use worldspace_author::EditHistory;
+
+use super::{StoreError, WorldStore, branch_filename};
+
+pub(super) const HISTORY_SUFFIX: &str = ".history.cbor.zst";
+
+impl WorldStore {
+    /// Store `history` for the current branch, replacing what was saved
+    /// before.
+    pub fn save_edit_history(&mut self, history: &EditHistory) -> Result<(), StoreError> {
+        self.ensure_writable()?;
+        let branch = self.meta.current_branch.clone();
+        let filename = history_filename(&branch);
+        std::fs::create_dir_all(
+            self.root
+                .join("history")
+                .join(branch_filename(&branch, String::new())),
+        )?;
+        if self.in_manifest(&filename) {
+            self.manifest.entries.retain(|e| e.filename != filename);
+            std::fs::remove_file(self.segment_path(&filename))?;
+        }
+        self.write_segment("history", filename, history)?;
+        self.rechain_manifest();
+        self.save_manifest()
+    }
+
+    /// The history saved for the current branch, or `None` if none was.
+    pub fn load_edit_history(&self) -> Result<Option<EditHistory>, StoreError> {
+        let filename = history_filename(&self.meta.current_branch);
+        if !self.in_manifest(&filename) {
+            return Ok(None);
+        }
+        self.read_segment("history", &filename).map(Some)
+    }
+}
+
+fn history_filename(branch: &str) -> String {
+    branch_filename(branch, format!("edit{HISTORY_SUFFIX}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_author::Editor;
+    use worldspace_common::Transform;
+    use worldspace_kernel::World;
+
+    #[test]
+    fn edit_history_survives_reopen() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+        assert!(store.load_edit_history().unwrap().is_none());
+
+        let mut world = World::with_seed(21);
+        let mut editor = Editor::new();
+        editor.spawn(&mut world, Transform::default());
+        editor.spawn(&mut world, Transform::default());
+        editor.undo(&mut world);
+        store.take_snapshot(&world).unwrap();
+        store.save_edit_history(&editor.history()).unwrap();
+        // Saving again replaces the segment instead of adding one.
+        store.save_edit_history(&editor.history()).unwrap();
+        drop(store);
+
+        let store = WorldStore::open(&path).unwrap();
+        store.verify_integrity().unwrap();
+        let editor = Editor::from_history(store.load_edit_history().unwrap().unwrap());
+        assert_eq!((editor.undo_count(), editor.redo_count()), (1, 1));
+        assert!(path.join("history").join("edit.history.cbor.zst").exists());
+    }
+}
@@ -0,0 +1,98 @@
+//! JSON Lines export and import of event history.
+//!
+//! `WorldStore::export_events_jsonl` writes every world event of the current
+//! branch as one JSON object per line, oldest first, in serde's externally
+//! tagged form (`{"Stepped":{"tick":1,"seed":7}}`), so analytics and
+//! scripting pipelines can consume the history without linking the Rust
+//! crates. `WorldStore::import_events_jsonl` reads the same format back.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use worldspace_kernel::WorldEvent;
+
+use super::{StoreError, WorldStore};
+
+impl WorldStore {
+    /// Write the current branch's world events to `path` as JSON Lines.
+    /// Returns the number of events written.
+    pub fn export_events_jsonl(&self, path: impl AsRef<Path>) -> Result<usize, StoreError> {
+        let mut out = BufWriter::new(File::create(path)?);
+        let mut written = 0;
+        for event in self.replay_iter() {
+            serde_json::to_writer(&mut out, &event?)?;
+            out.write_all(b"\n")?;
+            written += 1;
+        }
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        Ok(written)
+    }
+
+    /// Append the world events in the JSON Lines file at `path` to the
+    /// current branch as one new segment. Blank lines are skipped; nothing is
+    /// appended if any line fails to parse. Returns the number of events
+    /// appended.
+    pub fn import_events_jsonl(&mut self, path: impl AsRef<Path>) -> Result<usize, StoreError> {
+        self.ensure_writable()?;
+        let mut events = Vec::new();
+        for (i, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event: WorldEvent =
+                serde_json::from_str(&line).map_err(|source| StoreError::JsonLine {
+                    line: i + 1,
+                    source,
+                })?;
+            events.push(event);
+        }
+        self.append_events(&events)?;
+        Ok(events.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+    use worldspace_kernel::World;
+
+    #[test]
+    fn events_round_trip_through_jsonl() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut source = WorldStore::open(tmp.path().join("source")).unwrap();
+        let mut world = World::with_seed(8);
+        let id = world.spawn(Transform::default());
+        world.step();
+        world.despawn(id);
+        let events = world.drain_events();
+        source.append_events(&events).unwrap();
+
+        let file = tmp.path().join("events.jsonl");
+        assert_eq!(source.export_events_jsonl(&file).unwrap(), events.len());
+        let text = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(text.lines().count(), events.len());
+        assert!(text.lines().nth(1).unwrap().starts_with(r#"{"Stepped""#));
+
+        let mut target = WorldStore::open(tmp.path().join("target")).unwrap();
+        assert_eq!(target.import_events_jsonl(&file).unwrap(), events.len());
+        let imported: Vec<_> = target.replay_iter().map(Result::unwrap).collect();
+        assert_eq!(format!("{imported:?}"), format!("{events:?}"));
+    }
+
+    #[test]
+    fn import_reports_the_bad_line_and_appends_nothing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut store = WorldStore::open(tmp.path().join("world_data")).unwrap();
+        let file = tmp.path().join("events.jsonl");
+        std::fs::write(&file, "{\"Stepped\":{\"tick\":1,\"seed\":2}}\n\nnot json\n").unwrap();
+
+        assert!(matches!(
+            store.import_events_jsonl(&file),
+            Err(StoreError::JsonLine { line: 3, .. })
+        ));
+        assert_eq!(store.meta().event_segment_count, 0);
+    }
+}
@@ -0,0 +1,55 @@
+//! Single-writer locking of a store directory.
+//!
+//! A writable `WorldStore` holds an exclusive advisory lock on `world.lock`
+//! for as long as it is open, and writes a lease line naming its process into
+//! the file so a second writer can report who holds the store. The OS drops
+//! the lock when the owning process exits, so a crash never leaves a stale
+//! lease behind.
+//!
+//! Read-only stores take no lock: meta and manifest are replaced atomically,
+//! so a reader always sees a consistent pair even while a writer is active.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::Path;
+
+use super::{StoreError, unix_now};
+
+const LOCK_FILE: &str = "world.lock";
+
+/// The exclusive lock of a writable store; released on drop.
+#[derive(Debug)]
+pub(super) struct StoreLock {
+    _file: File,
+}
+
+impl StoreLock {
+    /// Lock the store at `root` for writing, failing with
+    /// `StoreError::Locked` if another writer holds it.
+    pub(super) fn acquire(root: &Path) -> Result<Self, StoreError> {
+        let path = root.join(LOCK_FILE);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let holder = std::fs::read_to_string(&path)
+                    .ok()
+                    .map(|lease| lease.trim().to_string())
+                    .filter(|lease| !lease.is_empty())
+                    .unwrap_or_else(|| "another process".into());
+                return Err(StoreError::Locked(holder));
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        file.set_len(0)?;
+        writeln!(file, "pid {} since {}", std::process::id(), unix_now())?;
+        file.sync_all()?;
+        Ok(Self { _file: file })
+    }
+}
@@ -0,0 +1,363 @@
+//! Store schema versions and migrations.
+//!
+//! `world.meta.json` records a schema version for world data (snapshots,
+//! deltas and cells), world event segments and component data. Opening a
+//! store written with older versions runs the registered migrations for each
+//! kind, one version step at a time, over every segment of that kind as a
+//! CBOR value, then rewrites the segments and rechains the manifest. Newer
+//! versions still fail closed.
+//!
+//! Migrated segments are first written next to the originals as
+//! `<segment>.migrating` files. Once all of them are on disk, the new hashes
+//! go to `integrity/migrate.journal`, and only then are the staged files
+//! renamed over the originals and the manifest and meta saved. Opening a
+//! store that still has a journal finishes the renames, so a crash leaves
+//! either the old segments with the old manifest or the migrated ones with
+//! the new manifest; it never re-runs steps over migrated segments.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use ciborium::Value;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    COMPONENT_SCHEMA_VERSION, EVENT_SCHEMA_VERSION, StoreError, WORLD_SCHEMA_VERSION, WorldStore,
+    cbor_deserialize, cbor_serialize, segment_dir, write_atomic, zstd_compress,
+};
+
+const JOURNAL_FILE: &str = "migrate.journal";
+
+/// The independently versioned kinds of store data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SchemaKind {
+    /// World snapshots, delta snapshots and cells.
+    World,
+    /// World event segments.
+    Event,
+    /// Component snapshots and component event segments.
+    Component,
+}
+
+impl SchemaKind {
+    /// The kind of data in a manifest entry's file.
+    fn of(filename: &str) -> Self {
+        if filename.ends_with(".components.snapshot.cbor.zst")
+            || filename.ends_with(".components.log.cbor.zst")
+        {
+            SchemaKind::Component
+        } else if filename.ends_with(".log.cbor.zst") {
+            SchemaKind::Event
+        } else {
+            SchemaKind::World
+        }
+    }
+
+    fn current_version(self) -> u32 {
+        match self {
+            SchemaKind::World => WORLD_SCHEMA_VERSION,
+            SchemaKind::Event => EVENT_SCHEMA_VERSION,
+            SchemaKind::Component => COMPONENT_SCHEMA_VERSION,
+        }
+    }
+}
+
+/// Upgrades one segment of `kind` from `from_version` to `from_version + 1`.
+#[derive(Debug, Clone, Copy)]
+pub struct StoreMigration {
+    pub kind: SchemaKind,
+    pub from_version: u32,
+    pub description: &'static str,
+    pub migrate: fn(&mut Value) -> Result<(), StoreError>,
+}
+
+/// The migrations a store may run when opened.
+#[derive(Debug, Clone, Default)]
+pub struct Migrations {
+    migrations: Vec<StoreMigration>,
+}
+
+impl Migrations {
+    /// No migrations: any version mismatch fails.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The migrations shipped with the engine. Every schema is still at
+    /// version 1, so there are none yet.
+    pub fn builtin() -> Self {
+        Self::new()
+    }
+
+    /// Add a migration, replacing any registered for the same step.
+    pub fn register(&mut self, migration: StoreMigration) -> &mut Self {
+        self.migrations
+            .retain(|m| (m.kind, m.from_version) != (migration.kind, migration.from_version));
+        self.migrations.push(migration);
+        self
+    }
+
+    /// The steps upgrading `kind` from `file_version` to the current version.
+    fn plan(&self, kind: SchemaKind, file_version: u32) -> Result<Vec<StoreMigration>, StoreError> {
+        let expected_version = kind.current_version();
+        if file_version > expected_version || file_version == 0 {
+            return Err(StoreError::SchemaMismatch {
+                file_version,
+                expected_version,
+            });
+        }
+        (file_version..expected_version)
+            .map(|version| {
+                self.migrations
+                    .iter()
+                    .find(|m| m.kind == kind && m.from_version == version)
+                    .copied()
+                    .ok_or_else(|| {
+                        StoreError::Migration(format!("no {kind:?} migration from v{version}"))
+                    })
+            })
+            .collect()
+    }
+}
+
+/// What migrating a store did, or would do in a dry run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Descriptions of the steps run, in order.
+    pub steps: Vec<String>,
+    /// Segment files migrated.
+    pub files: usize,
+    /// Whether files were left unchanged.
+    pub dry_run: bool,
+}
+
+impl MigrationReport {
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// A migration whose segments are all staged: the files to swap in, with
+/// their new hashes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MigrationJournal {
+    files: Vec<(String, String)>,
+}
+
+impl WorldStore {
+    /// Bring every segment up to the current schema versions. In a dry run
+    /// the migrations still run, so failures surface, but nothing is written.
+    pub(super) fn run_migrations(
+        &mut self,
+        migrations: &Migrations,
+        dry_run: bool,
+    ) -> Result<MigrationReport, StoreError> {
+        let world = migrations.plan(SchemaKind::World, self.meta.world_schema_version)?;
+        let event = migrations.plan(SchemaKind::Event, self.meta.event_schema_version)?;
+        let component =
+            migrations.plan(SchemaKind::Component, self.meta.component_schema_version)?;
+        let mut report = MigrationReport {
+            steps: [&world, &event, &component]
+                .into_iter()
+                .flatten()
+                .map(|m| format!("{:?} v{}: {}", m.kind, m.from_version, m.description))
+                .collect(),
+            files: 0,
+            dry_run,
+        };
+        if report.is_empty() {
+            return Ok(report);
+        }
+        if !dry_run {
+            self.ensure_writable()?;
+        }
+
+        let mut journal = MigrationJournal { files: Vec::new() };
+        for i in 0..self.manifest.entries.len() {
+            let filename = self.manifest.entries[i].filename.clone();
+            if filename.ends_with(super::history::HISTORY_SUFFIX) {
+                continue;
+            }
+            let steps = match SchemaKind::of(&filename) {
+                SchemaKind::World => &world,
+                SchemaKind::Event => &event,
+                SchemaKind::Component => &component,
+            };
+            if steps.is_empty() {
+                continue;
+            }
+
+            let mut value: Value = self.read_segment(segment_dir(&filename), &filename)?;
+            for step in steps {
+                (step.migrate)(&mut value).map_err(|e| {
+                    StoreError::Migration(format!("{filename}: {}: {e}", step.description))
+                })?;
+            }
+            report.files += 1;
+            if dry_run {
+                continue;
+            }
+            let sealed = self.seal(zstd_compress(&cbor_serialize(&value)?)?)?;
+            write_atomic(&self.staged_path(&filename), &sealed)?;
+            journal.files.push((filename, super::sha256_hex(&sealed)));
+        }
+        if dry_run {
+            return Ok(report);
+        }
+
+        write_atomic(&self.journal_path(), &cbor_serialize(&journal)?)?;
+        self.apply_journal(journal)?;
+        Ok(report)
+    }
+
+    /// Finish a migration interrupted by a crash, if the store has a journal.
+    pub(super) fn recover_migration(&mut self) -> Result<(), StoreError> {
+        let data = match std::fs::read(self.journal_path()) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        self.apply_journal(cbor_deserialize(&data)?)
+    }
+
+    /// Move the journal's staged files over the originals unless already
+    /// moved, record their hashes, save the manifest and meta at the current
+    /// schema versions and drop the journal.
+    fn apply_journal(&mut self, journal: MigrationJournal) -> Result<(), StoreError> {
+        for (filename, sha256) in journal.files {
+            let staged = self.staged_path(&filename);
+            if staged.exists() {
+                std::fs::rename(&staged, self.segment_path(&filename))?;
+            }
+            if let Some(entry) = self
+                .manifest
+                .entries
+                .iter_mut()
+                .find(|e| e.filename == filename)
+            {
+                entry.sha256 = sha256;
+                // Migrated payloads are written whole; compaction drops the old chunks.
+                entry.chunked = false;
+            }
+        }
+
+        self.rechain_manifest();
+        self.last_snapshot = None;
+        self.meta.world_schema_version = WORLD_SCHEMA_VERSION;
+        self.meta.event_schema_version = EVENT_SCHEMA_VERSION;
+        self.meta.component_schema_version = COMPONENT_SCHEMA_VERSION;
+        self.save_meta()?;
+        self.save_manifest()?;
+        std::fs::remove_file(self.journal_path())?;
+        Ok(())
+    }
+
+    /// Where the migrated contents of `filename` wait for the journal.
+    fn staged_path(&self, filename: &str) -> PathBuf {
+        let mut path = OsString::from(self.segment_path(filename));
+        path.push(".migrating");
+        path.into()
+    }
+
+    fn journal_path(&self) -> PathBuf {
+        self.root.join("integrity").join(JOURNAL_FILE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noop(_value: &mut Value) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    #[test]
+    fn plans_follow_registered_steps() {
+        let mut migrations = Migrations::builtin();
+        assert!(migrations.plan(SchemaKind::World, 1).unwrap().is_empty());
+        assert!(matches!(
+            migrations.plan(SchemaKind::Event, EVENT_SCHEMA_VERSION + 1),
+            Err(StoreError::SchemaMismatch { .. })
+        ));
+        assert!(matches!(
+            migrations.plan(SchemaKind::Component, 0),
+            Err(StoreError::SchemaMismatch { .. })
+        ));
+
+        migrations.register(StoreMigration {
+            kind: SchemaKind::World,
+            from_version: 1,
+            description: "first",
+            migrate: noop,
+        });
+        migrations.register(StoreMigration {
+            kind: SchemaKind::World,
+            from_version: 1,
+            description: "replacement",
+            migrate: noop,
+        });
+        assert_eq!(migrations.migrations.len(), 1);
+        assert_eq!(migrations.migrations[0].description, "replacement");
+    }
+
+    #[test]
+    fn interrupted_migration_finishes_on_open() {
+        use worldspace_common::Transform;
+        use worldspace_kernel::World;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut world = World::with_seed(8);
+        world.spawn(Transform::default());
+        let mut store = WorldStore::open(&path).unwrap();
+        store.take_snapshot(&world).unwrap();
+
+        // Simulate a crash after the migrated segment was staged and the
+        // journal written, but before the segment was moved into place.
+        let filename = store.manifest.entries[0].filename.clone();
+        let value: Value = store.read_segment("snapshots", &filename).unwrap();
+        let sealed = zstd_compress(&cbor_serialize(&value).unwrap()).unwrap();
+        write_atomic(&store.staged_path(&filename), &sealed).unwrap();
+        let journal = MigrationJournal {
+            files: vec![(filename.clone(), crate::store::sha256_hex(&sealed))],
+        };
+        write_atomic(&store.journal_path(), &cbor_serialize(&journal).unwrap()).unwrap();
+        drop(store);
+
+        let store = WorldStore::open(&path).unwrap();
+        assert!(!store.journal_path().exists());
+        assert!(!store.staged_path(&filename).exists());
+        assert_eq!(
+            store.manifest.entries[0].sha256,
+            crate::store::sha256_hex(&sealed)
+        );
+        store.verify_integrity().unwrap();
+        assert_eq!(
+            store.load_latest().unwrap().state_hash(),
+            world.state_hash()
+        );
+    }
+
+    #[test]
+    fn segments_map_to_schema_kinds() {
+        assert_eq!(
+            SchemaKind::of("000001.snapshot.cbor.zst"),
+            SchemaKind::World
+        );
+        assert_eq!(
+            SchemaKind::of("b/000002.delta.snapshot.cbor.zst"),
+            SchemaKind::World
+        );
+        assert_eq!(SchemaKind::of("0_-1.cell.cbor.zst"), SchemaKind::World);
+        assert_eq!(SchemaKind::of("000001.log.cbor.zst"), SchemaKind::Event);
+        assert_eq!(
+            SchemaKind::of("000001.components.log.cbor.zst"),
+            SchemaKind::Component
+        );
+        assert_eq!(
+            SchemaKind::of("000001.components.snapshot.cbor.zst"),
+            SchemaKind::Component
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Recovery of stores with corrupt or missing segment files.
+//!
+//! `WorldStore::repair` re-hashes every file listed in the manifest and moves
+//! the ones that no longer match into `quarantine/`, keeping their relative
+//! path. A branch's snapshots and event segments form numbered series that
+//! are only usable without gaps, so intact files after a lost one in the same
+//! series are quarantined too and the branch's counts are cut back to the
+//! last usable index. The manifest chain is then rebuilt over the survivors.
+//!
+//! Chunked snapshots are only intact if every chunk they list is: their
+//! chunks under `objects/` are checked too, corrupt chunks are quarantined
+//! and snapshots with a corrupt or missing chunk are lost like corrupt files.
+//!
+//! Repair needs a readable `world.meta.json` and manifest; it only deals with
+//! the segment files they list and the chunks those reference.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::cells::CELL_SUFFIX;
+use super::chunks::object_relative_path;
+use super::{StoreError, WorldStore, branch_of, sha256_hex};
+
+/// What `WorldStore::repair` found and moved aside.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairReport {
+    /// Files whose hash matched the manifest and that were kept.
+    pub verified: usize,
+    /// Files and chunks whose contents no longer match their hash, and
+    /// chunked snapshots with such a chunk, moved to `quarantine/`. Chunks
+    /// are listed by their path under `objects/`.
+    pub corrupt: Vec<String>,
+    /// Files listed in the manifest but missing on disk, and chunks listed by
+    /// a chunked snapshot but missing under `objects/`.
+    pub missing: Vec<String>,
+    /// Intact files after a lost one in the same series, moved to `quarantine/`.
+    pub orphaned: Vec<String>,
+}
+
+impl RepairReport {
+    /// True if every listed file was intact.
+    pub fn is_clean(&self) -> bool {
+        self.corrupt.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// The numbered series a segment file belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Series {
+    Snapshots,
+    Events,
+}
+
+impl WorldStore {
+    /// Quarantine corrupt segment files and everything that depends on them,
+    /// then rebuild the integrity manifest over the remaining files.
+    ///
+    /// The store loads again afterwards, from the last snapshot and event
+    /// segment before the damage. Checkpoints of quarantined snapshots are
+    /// forgotten. Quarantined files are kept under `quarantine/` for
+    /// inspection; nothing is deleted.
+    pub fn repair(&mut self) -> Result<RepairReport, StoreError> {
+        self.ensure_writable()?;
+        let mut report = RepairReport::default();
+        let mut first_lost: BTreeMap<(String, Series), u32> = BTreeMap::new();
+        let mut intact = Vec::with_capacity(self.manifest.entries.len());
+        let mut chunks = BTreeMap::new();
+
+        for entry in std::mem::take(&mut self.manifest.entries) {
+            let path = self.segment_path(&entry.filename);
+            match std::fs::read(&path) {
+                Ok(data) if sha256_hex(&data) == entry.sha256 => {
+                    if !entry.chunked
+                        || self.chunks_intact(&entry.filename, &mut chunks, &mut report)?
+                    {
+                        intact.push(entry);
+                        continue;
+                    }
+                    self.quarantine(&entry.filename)?;
+                    report.corrupt.push(entry.filename.clone());
+                }
+                Ok(_) => {
+                    self.quarantine(&entry.filename)?;
+                    report.corrupt.push(entry.filename.clone());
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    report.missing.push(entry.filename.clone());
+                }
+                Err(e) => return Err(e.into()),
+            }
+            if let Some((series, index)) = series_of(&entry.filename) {
+                let lost = first_lost
+                    .entry((branch_of(&entry.filename).to_string(), series))
+                    .or_insert(index);
+                *lost = (*lost).min(index);
+            }
+        }
+
+        for entry in intact {
+            let orphaned = series_of(&entry.filename).is_some_and(|(series, index)| {
+                first_lost
+                    .get(&(branch_of(&entry.filename).to_string(), series))
+                    .is_some_and(|&lost| index >= lost)
+            });
+            if orphaned {
+                self.quarantine(&entry.filename)?;
+                report.orphaned.push(entry.filename);
+            } else {
+                report.verified += 1;
+                self.manifest.entries.push(entry);
+            }
+        }
+
+        for ((branch, series), lost) in first_lost {
+            let (snapshot_count, event_segment_count) = match self.meta.branches.get_mut(&branch) {
+                Some(meta) => (&mut meta.snapshot_count, &mut meta.event_segment_count),
+                None => (
+                    &mut self.meta.snapshot_count,
+                    &mut self.meta.event_segment_count,
+                ),
+            };
+            let count = match series {
+                Series::Snapshots => snapshot_count,
+                Series::Events => event_segment_count,
+            };
+            *count = (*count).min(lost - 1);
+            if series == Series::Snapshots {
+                self.drop_checkpoints(&branch, lost);
+            }
+        }
+
+        self.rechain_manifest();
+        self.last_snapshot = None;
+        self.save_meta()?;
+        self.save_manifest()?;
+        Ok(report)
+    }
+
+    /// True if every chunk the chunked file `filename` lists is intact.
+    /// Chunks are checked once per repair, with the results kept in
+    /// `checked`; corrupt ones are quarantined.
+    fn chunks_intact(
+        &self,
+        filename: &str,
+        checked: &mut BTreeMap<String, bool>,
+        report: &mut RepairReport,
+    ) -> Result<bool, StoreError> {
+        let mut intact = true;
+        for hash in self.read_chunk_list(filename)?.chunks {
+            if let Some(&ok) = checked.get(&hash) {
+                intact &= ok;
+                continue;
+            }
+            let ok = match self.read_chunk(&hash) {
+                Ok(_) => true,
+                Err(StoreError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                    report.missing.push(object_relative_path(&hash));
+                    false
+                }
+                Err(_) => {
+                    self.quarantine_path(&self.object_path(&hash))?;
+                    report.corrupt.push(object_relative_path(&hash));
+                    false
+                }
+            };
+            checked.insert(hash, ok);
+            intact &= ok;
+        }
+        Ok(intact)
+    }
+
+    /// Move a segment file into `quarantine/`, keeping its relative path.
+    fn quarantine(&self, filename: &str) -> Result<(), StoreError> {
+        self.quarantine_path(&self.segment_path(filename))
+    }
+
+    /// Move the file at `from`, inside the store, into `quarantine/`, keeping
+    /// its path relative to the store root.
+    fn quarantine_path(&self, from: &Path) -> Result<(), StoreError> {
+        let to = self
+            .root
+            .join("quarantine")
+            .join(from.strip_prefix(&self.root).unwrap_or(from));
+        if let Some(parent) = to.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::rename(from, to)?;
+        Ok(())
+    }
+}
+
+/// The series and index of a snapshot or event segment file; `None` for
+/// cells, which do not depend on each other.
+fn series_of(filename: &str) -> Option<(Series, u32)> {
+    if filename.ends_with(CELL_SUFFIX) {
+        return None;
+    }
+    let name = filename.rsplit('/').next()?;
+    let index = name.split('.').next()?.parse().ok()?;
+    if name.ends_with(".snapshot.cbor.zst") {
+        Some((Series::Snapshots, index))
+    } else if name.ends_with(".log.cbor.zst") {
+        Some((Series::Events, index))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+    use worldspace_kernel::World;
+
+    #[test]
+    fn series_of_parses_segment_names() {
+        assert_eq!(
+            series_of("000002.delta.snapshot.cbor.zst"),
+            Some((Series::Snapshots, 2))
+        );
+        assert_eq!(
+            series_of("experiment/000003.components.log.cbor.zst"),
+            Some((Series::Events, 3))
+        );
+        assert_eq!(series_of("-1_3.cell.cbor.zst"), None);
+    }
+
+    #[test]
+    fn repair_quarantines_corrupt_segment_and_its_successors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+        let mut world = World::with_seed(5);
+        store.take_snapshot(&world).unwrap();
+        for _ in 0..3 {
+            world.spawn(Transform::default());
+            store.append_events(&world.drain_events()).unwrap();
+        }
+        assert!(store.repair().unwrap().is_clean());
+
+        let corrupt = path.join("events").join("000002.log.cbor.zst");
+        let mut data = std::fs::read(&corrupt).unwrap();
+        data[0] ^= 1;
+        std::fs::write(&corrupt, data).unwrap();
+        assert!(store.verify_integrity().is_err());
+
+        let report = store.repair().unwrap();
+        assert_eq!(report.corrupt, vec!["000002.log.cbor.zst".to_string()]);
+        assert_eq!(report.orphaned, vec!["000003.log.cbor.zst".to_string()]);
+        assert!(report.missing.is_empty());
+        assert_eq!(report.verified, 2);
+        assert_eq!(store.meta().snapshot_count, 1);
+        assert_eq!(store.meta().event_segment_count, 1);
+        assert!(path.join("quarantine/events/000002.log.cbor.zst").exists());
+        store.verify_integrity().unwrap();
+    }
+
+    #[test]
+    fn repair_quarantines_chunked_snapshots_with_a_corrupt_chunk() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+        let mut world = World::with_seed(6);
+        store.take_snapshot(&world).unwrap();
+        store.set_snapshot_chunking(true).unwrap();
+        for _ in 0..100 {
+            world.spawn(Transform::default());
+        }
+        store.take_snapshot(&world).unwrap();
+        assert!(store.repair().unwrap().is_clean());
+
+        let chunk = store.referenced_chunks().unwrap().pop_first().unwrap();
+        std::fs::write(path.join(&chunk), b"not a chunk").unwrap();
+        assert!(store.verify_integrity().is_err());
+
+        let report = store.repair().unwrap();
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.corrupt,
+            vec![chunk.clone(), "000002.snapshot.cbor.zst".to_string()]
+        );
+        assert_eq!(store.meta().snapshot_count, 1);
+        assert!(path.join("quarantine").join(&chunk).exists());
+        store.verify_integrity().unwrap();
+        assert!(store.repair().unwrap().is_clean());
+    }
+}
@@ -0,0 +1,124 @@
+//! Streaming replay of event segments.
+
+use std::vec;
+
+use worldspace_kernel::WorldEvent;
+
+use super::{ReplayCursor, StoreError, WorldStore};
+
+/// Iterator over the current branch's world events in log order, decoding
+/// one event segment at a time.
+///
+/// A segment that fails to load or verify yields its error once and ends
+/// the iteration.
+pub struct ReplayIter<'a> {
+    store: &'a WorldStore,
+    next_segment: u32,
+    last_segment: u32,
+    /// Events to skip at the start of the first segment.
+    skip: usize,
+    events: vec::IntoIter<WorldEvent>,
+}
+
+impl Iterator for ReplayIter<'_> {
+    type Item = Result<WorldEvent, StoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.events.next() {
+                return Some(Ok(event));
+            }
+            if self.next_segment > self.last_segment {
+                return None;
+            }
+            let segment = self.next_segment;
+            self.next_segment += 1;
+            match self.store.load_event_segment(segment) {
+                Ok(events) => {
+                    self.events = events.into_iter();
+                    if self.skip > 0 {
+                        self.events.nth(self.skip - 1);
+                        self.skip = 0;
+                    }
+                }
+                Err(e) => {
+                    self.next_segment = self.last_segment + 1;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+impl WorldStore {
+    /// Every world event on the current branch, loaded segment by segment
+    /// rather than all at once.
+    pub fn replay_iter(&self) -> ReplayIter<'_> {
+        let start = ReplayCursor {
+            segment: 1,
+            offset: 0,
+        };
+        self.replay_iter_from(start, self.counts().1)
+    }
+
+    /// Events from `cursor` to the end of segment `last_segment`.
+    pub(super) fn replay_iter_from(
+        &self,
+        cursor: ReplayCursor,
+        last_segment: u32,
+    ) -> ReplayIter<'_> {
+        ReplayIter {
+            store: self,
+            next_segment: cursor.segment.max(1),
+            last_segment,
+            skip: cursor.offset as usize,
+            events: Vec::new().into_iter(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+    use worldspace_kernel::World;
+
+    #[test]
+    fn replay_iter_streams_segments_in_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut store = WorldStore::open(tmp.path().join("world_data")).unwrap();
+        assert!(store.replay_iter().next().is_none());
+
+        let mut world = World::with_seed(2);
+        store.take_snapshot(&world).unwrap();
+        let mut expected = Vec::new();
+        for _ in 0..3 {
+            world.spawn(Transform::default());
+            world.step();
+            let events = world.drain_events();
+            expected.extend(events.iter().map(|e| format!("{e:?}")));
+            store.append_events(&events).unwrap();
+        }
+
+        let replayed: Vec<_> = store
+            .replay_iter()
+            .map(|e| format!("{:?}", e.unwrap()))
+            .collect();
+        assert_eq!(replayed, expected);
+    }
+
+    #[test]
+    fn replay_iter_stops_after_missing_segment() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+        let mut world = World::with_seed(2);
+        world.spawn(Transform::default());
+        store.append_events(&world.drain_events()).unwrap();
+        std::fs::remove_file(path.join("events").join("000001.log.cbor.zst")).unwrap();
+
+        let mut iter = store.replay_iter();
+        assert!(matches!(iter.next(), Some(Err(StoreError::Io(_)))));
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,348 @@
+//! Snapshot retention for autosaving worlds.
+//!
+//! A store that snapshots every few minutes grows without bound. With a
+//! `RetentionPolicy` set, `WorldStore::compact` thins the current branch's
+//! snapshots to the ones the policy keeps instead of folding everything into
+//! one, and deletes the event segments older than the oldest kept snapshot.
+//! Events after it stay, so every kept snapshot still replays forward and
+//! `world_at` works from the oldest kept snapshot on.
+//!
+//! Kept snapshots are renumbered from 1 and event segments from the first
+//! kept one, like a compacted branch. Kept delta snapshots are rewritten as
+//! full snapshots since their base may be gone. Snapshots with a checkpoint
+//! are always kept.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    CompactReport, ReplayCursor, StoreError, StoredComponents, WorldStore, branch_of,
+    component_log_filename, component_snapshot_filename, delta_filename, log_filename,
+    snapshot_filename,
+};
+use crate::snapshot::Snapshot;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Which snapshots `WorldStore::compact` keeps. The latest snapshot is
+/// always kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Keep the most recent `keep_last` snapshots.
+    pub keep_last: u32,
+    /// Also keep the newest snapshot of each of the last `keep_daily` days
+    /// (UTC) that have one.
+    pub keep_daily: u32,
+}
+
+impl RetentionPolicy {
+    /// Indices to keep out of `snapshots`, given as `(index, created_at)` in
+    /// index order.
+    fn retained(&self, snapshots: &[(u32, u64)]) -> BTreeSet<u32> {
+        let mut keep: BTreeSet<u32> = snapshots
+            .iter()
+            .rev()
+            .take(self.keep_last.max(1) as usize)
+            .map(|&(index, _)| index)
+            .collect();
+        let mut days = BTreeSet::new();
+        for &(index, created_at) in snapshots.iter().rev() {
+            if days.len() == self.keep_daily as usize {
+                break;
+            }
+            if days.insert(created_at / SECONDS_PER_DAY) {
+                keep.insert(index);
+            }
+        }
+        keep
+    }
+}
+
+/// A snapshot that survives retention, read before any file is touched.
+struct Kept {
+    index: u32,
+    /// Delta snapshots, materialized to be rewritten as full snapshots
+    /// with the delta's cursor and write time.
+    full: Option<(Snapshot, Option<ReplayCursor>, Option<u64>)>,
+    /// Component snapshot to rewrite with a shifted `after_segment`.
+    components: Option<StoredComponents>,
+}
+
+impl WorldStore {
+    /// Set the retention policy `compact` enforces, or `None` to go back to
+    /// folding the branch into one snapshot.
+    pub fn set_retention(&mut self, policy: Option<RetentionPolicy>) -> Result<(), StoreError> {
+        self.ensure_writable()?;
+        self.meta.retention = policy;
+        self.save_meta()
+    }
+
+    /// The retention policy `compact` enforces, if any.
+    pub fn retention(&self) -> Option<RetentionPolicy> {
+        self.meta.retention
+    }
+
+    /// Delete the current branch's snapshots `policy` does not keep and the
+    /// event segments no kept snapshot needs.
+    pub(super) fn compact_retaining(
+        &mut self,
+        policy: RetentionPolicy,
+    ) -> Result<CompactReport, StoreError> {
+        let branch = self.meta.current_branch.clone();
+        let (snapshot_count, event_segment_count) = self.counts();
+        let mut snapshots = Vec::with_capacity(snapshot_count as usize);
+        for index in 1..=snapshot_count {
+            snapshots.push((index, self.snapshot_created_at(index).unwrap_or(0)));
+        }
+        let mut keep = policy.retained(&snapshots);
+        keep.extend(
+            self.meta
+                .checkpoints
+                .iter()
+                .filter(|c| c.branch == branch)
+                .map(|c| c.snapshot),
+        );
+        let Some(&oldest) = keep.first() else {
+            return Ok(CompactReport::default());
+        };
+        // Snapshots without a cursor replay from the start of the log.
+        let shift = if keep.iter().all(|&i| self.snapshot_cursor(i).is_some()) {
+            self.snapshot_cursor(oldest)
+                .map_or(0, |c| c.segment - 1)
+                .min(event_segment_count)
+        } else {
+            0
+        };
+        if keep.len() == snapshot_count as usize && shift == 0 {
+            return Ok(CompactReport::default());
+        }
+
+        let mut kept = Vec::with_capacity(keep.len());
+        for &index in &keep {
+            let delta = delta_filename(&branch, index);
+            let full = if self.in_manifest(&delta) {
+                let entry = self.entry(&delta).expect("listed in the manifest");
+                let (cursor, created_at) = (entry.replay_cursor, entry.created_at);
+                Some((self.resolve_snapshot(&branch, index)?, cursor, created_at))
+            } else {
+                None
+            };
+            let components_filename = component_snapshot_filename(&branch, index);
+            let components = if shift > 0 && self.in_manifest(&components_filename) {
+                Some(self.read_segment("snapshots", &components_filename)?)
+            } else {
+                None
+            };
+            kept.push(Kept {
+                index,
+                full,
+                components,
+            });
+        }
+
+        let mut report = CompactReport {
+            snapshots_removed: snapshot_count - keep.len() as u32,
+            segments_removed: shift,
+            bytes_freed: 0,
+        };
+        for index in 1..=snapshot_count {
+            let kept = kept.iter().find(|k| k.index == index);
+            if kept.is_none_or(|k| k.full.is_some()) {
+                report.bytes_freed += self.remove_segment(&snapshot_filename(&branch, index))?;
+                report.bytes_freed += self.remove_segment(&delta_filename(&branch, index))?;
+            }
+            if kept.is_none_or(|k| k.components.is_some()) {
+                report.bytes_freed +=
+                    self.remove_segment(&component_snapshot_filename(&branch, index))?;
+            }
+        }
+        for segment in 1..=shift {
+            report.bytes_freed += self.remove_segment(&log_filename(&branch, segment))?;
+            report.bytes_freed += self.remove_segment(&component_log_filename(&branch, segment))?;
+        }
+
+        // Renumber in ascending order, so every target name is already free.
+        for (rank, kept) in (1..).zip(kept) {
+            let filename = snapshot_filename(&branch, rank);
+            match kept.full {
+                Some((snap, cursor, created_at)) => {
+                    self.write_segment("snapshots", filename, &snap)?;
+                    let entry = self.manifest.entries.last_mut().expect("just written");
+                    entry.replay_cursor = cursor;
+                    entry.snapshot_tick = Some(snap.tick);
+                    entry.created_at = created_at;
+                }
+                None => self.rename_segment(&snapshot_filename(&branch, kept.index), filename)?,
+            }
+            let components_filename = component_snapshot_filename(&branch, rank);
+            match kept.components {
+                Some(mut stored) => {
+                    stored.after_segment = stored.after_segment.saturating_sub(shift);
+                    self.write_segment("snapshots", components_filename, &stored)?;
+                }
+                None => self.rename_segment(
+                    &component_snapshot_filename(&branch, kept.index),
+                    components_filename,
+                )?,
+            }
+            for checkpoint in &mut self.meta.checkpoints {
+                if checkpoint.branch == branch && checkpoint.snapshot == kept.index {
+                    checkpoint.snapshot = rank;
+                }
+            }
+        }
+        for segment in shift + 1..=event_segment_count {
+            self.rename_segment(
+                &log_filename(&branch, segment),
+                log_filename(&branch, segment - shift),
+            )?;
+            self.rename_segment(
+                &component_log_filename(&branch, segment),
+                component_log_filename(&branch, segment - shift),
+            )?;
+        }
+        for entry in &mut self.manifest.entries {
+            if branch_of(&entry.filename) == branch
+                && let Some(cursor) = &mut entry.replay_cursor
+            {
+                cursor.segment = cursor.segment.saturating_sub(shift).max(1);
+            }
+        }
+
+        let (snapshot_count, event_segment_count) = self.counts_mut();
+        *snapshot_count = keep.len() as u32;
+        *event_segment_count -= shift;
+        self.rechain_manifest();
+        self.last_snapshot = None;
+        self.save_meta()?;
+        self.save_manifest()?;
+        Ok(report)
+    }
+
+    /// When snapshot `index` of the current branch was written: from the
+    /// manifest, or the file's modification time for older entries.
+    fn snapshot_created_at(&self, index: u32) -> Option<u64> {
+        let branch = &self.meta.current_branch;
+        let entry = self
+            .entry(&snapshot_filename(branch, index))
+            .or_else(|| self.entry(&delta_filename(branch, index)))?;
+        entry.created_at.or_else(|| {
+            let modified = std::fs::metadata(self.segment_path(&entry.filename))
+                .and_then(|m| m.modified())
+                .ok()?;
+            let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+            Some(since_epoch.as_secs())
+        })
+    }
+
+    fn entry(&self, filename: &str) -> Option<&super::ManifestEntry> {
+        self.manifest
+            .entries
+            .iter()
+            .find(|e| e.filename == filename)
+    }
+
+    /// Delete a listed segment file and its manifest entry, returning its
+    /// size; 0 if the manifest does not list it.
+    fn remove_segment(&mut self, filename: &str) -> Result<u64, StoreError> {
+        if !self.in_manifest(filename) {
+            return Ok(0);
+        }
+        let path = self.segment_path(filename);
+        let size = std::fs::metadata(&path)?.len();
+        std::fs::remove_file(path)?;
+        self.manifest.entries.retain(|e| e.filename != filename);
+        Ok(size)
+    }
+
+    /// Rename a listed segment file and its manifest entry; nothing if the
+    /// manifest does not list it.
+    fn rename_segment(&mut self, from: &str, to: String) -> Result<(), StoreError> {
+        if from == to || !self.in_manifest(from) {
+            return Ok(());
+        }
+        std::fs::rename(self.segment_path(from), self.segment_path(&to))?;
+        if let Some(entry) = self
+            .manifest
+            .entries
+            .iter_mut()
+            .find(|e| e.filename == from)
+        {
+            entry.filename = to;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+    use worldspace_kernel::World;
+
+    #[test]
+    fn policy_keeps_last_n_and_newest_per_day() {
+        let day = SECONDS_PER_DAY;
+        let snapshots = [
+            (1, 10),
+            (2, day - 1),
+            (3, day + 5),
+            (4, 2 * day),
+            (5, 2 * day + 60),
+            (6, 2 * day + 120),
+        ];
+        let policy = RetentionPolicy {
+            keep_last: 2,
+            keep_daily: 0,
+        };
+        assert_eq!(policy.retained(&snapshots), BTreeSet::from([5, 6]));
+
+        let policy = RetentionPolicy {
+            keep_last: 1,
+            keep_daily: 3,
+        };
+        assert_eq!(policy.retained(&snapshots), BTreeSet::from([2, 3, 6]));
+        // The latest snapshot survives even a policy that keeps nothing.
+        assert_eq!(
+            RetentionPolicy::default().retained(&snapshots),
+            BTreeSet::from([6])
+        );
+    }
+
+    #[test]
+    fn compact_with_retention_keeps_recent_snapshots_and_their_events() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut store = WorldStore::open(&path).unwrap();
+        store
+            .set_retention(Some(RetentionPolicy {
+                keep_last: 2,
+                keep_daily: 0,
+            }))
+            .unwrap();
+        let mut world = World::with_seed(9);
+        for _ in 0..4 {
+            world.spawn(Transform::default());
+            world.step();
+            store.take_snapshot(&world).unwrap();
+            store.append_events(&world.drain_events()).unwrap();
+        }
+        store.label_snapshot(4, "latest", "").unwrap();
+
+        let report = store.compact().unwrap();
+        assert_eq!((report.snapshots_removed, report.segments_removed), (2, 2));
+        assert_eq!(store.meta().snapshot_count, 2);
+        assert_eq!(store.meta().event_segment_count, 2);
+        assert_eq!(store.replay_cursor().unwrap().segment, 2);
+        assert_eq!(store.checkpoint("latest").unwrap().snapshot, 2);
+        assert!(path.join("snapshots/000002.snapshot.cbor.zst").exists());
+        assert!(!path.join("snapshots/000003.snapshot.cbor.zst").exists());
+        assert!(!path.join("events/000003.log.cbor.zst").exists());
+        store.verify_integrity().unwrap();
+        assert_eq!(
+            store.load_latest().unwrap().state_hash(),
+            world.state_hash()
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Log-shipping replication between stores.
+//!
+//! `WorldStore::pull_from` copies the segment files another store has and
+//! this one lacks, byte for byte, and appends their manifest entries in the
+//! other store's order before rebuilding the hash chain. Snapshots and event
+//! segments are append-only, so a file with the same name but a different
+//! hash means the stores were written independently (or one was compacted)
+//! and the pull fails with `StoreError::Diverged`. Cells and editor history
+//! are rewritten in place and are replaced with the other store's version.
+//!
+//! Files are copied still compressed and sealed, so both stores must use the
+//! same encryption key and schema versions. Chunked snapshots bring the
+//! chunks they reference that the receiving store lacks.
+
+use super::cells::CELL_SUFFIX;
+use super::history::HISTORY_SUFFIX;
+use super::{StoreError, WorldStore, sha256_hex, write_atomic};
+
+/// What `WorldStore::pull_from` transferred.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Files the receiving store did not have.
+    pub copied: Vec<String>,
+    /// Cell and history files replaced with a newer version.
+    pub replaced: Vec<String>,
+    /// Size of the transferred files.
+    pub bytes: u64,
+}
+
+impl SyncReport {
+    /// True if the stores already held the same files.
+    pub fn is_empty(&self) -> bool {
+        self.copied.is_empty() && self.replaced.is_empty()
+    }
+}
+
+impl WorldStore {
+    /// Copy the segments of `other` that this store is missing, along with
+    /// its branches, counts and checkpoints, so this store mirrors it.
+    pub fn pull_from(&mut self, other: &WorldStore) -> Result<SyncReport, StoreError> {
+        self.ensure_writable()?;
+        self.check_compatible(other)?;
+        let mut report = SyncReport::default();
+
+        // Decide everything before writing, so a diverged pull changes nothing.
+        let mut transfers = Vec::new();
+        for entry in &other.manifest.entries {
+            let rewritable =
+                entry.filename.ends_with(CELL_SUFFIX) || entry.filename.ends_with(HISTORY_SUFFIX);
+            match self
+                .manifest
+                .entries
+                .iter()
+                .find(|e| e.filename == entry.filename)
+            {
+                Some(ours) if ours.sha256 == entry.sha256 => {}
+                Some(_) if rewritable => {
+                    report.replaced.push(entry.filename.clone());
+                    transfers.push(entry);
+                }
+                Some(_) => return Err(StoreError::Diverged(entry.filename.clone())),
+                None => {
+                    report.copied.push(entry.filename.clone());
+                    transfers.push(entry);
+                }
+            }
+        }
+
+        for entry in transfers {
+            if entry.chunked {
+                report.bytes += self.copy_chunks_from(other, &entry.filename)?;
+            }
+            let data = std::fs::read(other.segment_path(&entry.filename))?;
+            let actual = sha256_hex(&data);
+            if actual != entry.sha256 {
+                return Err(StoreError::IntegrityMismatch {
+                    expected: entry.sha256.clone(),
+                    actual,
+                });
+            }
+            let path = self.segment_path(&entry.filename);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            write_atomic(&path, &data)?;
+            report.bytes += data.len() as u64;
+            self.manifest
+                .entries
+                .retain(|e| e.filename != entry.filename);
+            self.manifest.entries.push(entry.clone());
+        }
+        if report.is_empty() {
+            return Ok(report);
+        }
+
+        self.meta.snapshot_count = self.meta.snapshot_count.max(other.meta.snapshot_count);
+        self.meta.event_segment_count = self
+            .meta
+            .event_segment_count
+            .max(other.meta.event_segment_count);
+        for (name, theirs) in &other.meta.branches {
+            let ours = self
+                .meta
+                .branches
+                .entry(name.clone())
+                .or_insert_with(|| theirs.clone());
+            ours.snapshot_count = ours.snapshot_count.max(theirs.snapshot_count);
+            ours.event_segment_count = ours.event_segment_count.max(theirs.event_segment_count);
+        }
+        for checkpoint in &other.meta.checkpoints {
+            if self.checkpoint(&checkpoint.name).is_none() {
+                self.meta.checkpoints.push(checkpoint.clone());
+            }
+        }
+        if self.meta.cell_size.is_none() {
+            self.meta.cell_size = other.meta.cell_size;
+            self.meta.cell_layer_height = other.meta.cell_layer_height;
+        }
+
+        self.rechain_manifest();
+        self.last_snapshot = None;
+        self.save_meta()?;
+        self.save_manifest()?;
+        Ok(report)
+    }
+
+    /// Copy the segments this store has and `other` is missing to `other`.
+    pub fn push_to(&self, other: &mut WorldStore) -> Result<SyncReport, StoreError> {
+        other.pull_from(self)
+    }
+
+    /// Fail unless `other`'s segment files can be used here unchanged.
+    fn check_compatible(&self, other: &WorldStore) -> Result<(), StoreError> {
+        if self.meta.encryption_key_id != other.meta.encryption_key_id {
+            return Err(StoreError::EncryptionKeyMismatch {
+                expected: self.meta.encryption_key_id.clone(),
+                actual: other.meta.encryption_key_id.clone(),
+            });
+        }
+        let versions = [
+            (
+                self.meta.world_schema_version,
+                other.meta.world_schema_version,
+            ),
+            (
+                self.meta.event_schema_version,
+                other.meta.event_schema_version,
+            ),
+            (
+                self.meta.component_schema_version,
+                other.meta.component_schema_version,
+            ),
+        ];
+        for (ours, theirs) in versions {
+            if ours != theirs {
+                return Err(StoreError::SchemaMismatch {
+                    file_version: theirs,
+                    expected_version: ours,
+                });
+            }
+        }
+        if let (Some(ours), Some(theirs)) = (self.meta.cell_size, other.meta.cell_size)
+            && ours != theirs
+        {
+            return Err(StoreError::CellSizeMismatch {
+                expected: ours,
+                actual: theirs,
+            });
+        }
+        if self.meta.cell_size.is_some()
+            && other.meta.cell_size.is_some()
+            && self.meta.cell_layer_height != other.meta.cell_layer_height
+        {
+            return Err(StoreError::CellLayerMismatch {
+                expected: self.meta.cell_layer_height,
+                actual: other.meta.cell_layer_height,
+            });
+        }
+        for (name, theirs) in &other.meta.branches {
+            if let Some(ours) = self.meta.branches.get(name)
+                && (ours.parent != theirs.parent || ours.from_snapshot != theirs.from_snapshot)
+            {
+                return Err(StoreError::Diverged(format!("branch '{name}'")));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+    use worldspace_kernel::World;
+
+    #[test]
+    fn pull_copies_only_missing_segments() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut source = WorldStore::open(tmp.path().join("source")).unwrap();
+        let mut mirror = WorldStore::open(tmp.path().join("mirror")).unwrap();
+        let mut world = World::with_seed(17);
+        source.take_snapshot(&world).unwrap();
+        world.spawn(Transform::default());
+        source.append_events(&world.drain_events()).unwrap();
+
+        let report = mirror.pull_from(&source).unwrap();
+        assert_eq!(
+            report.copied,
+            vec!["000001.snapshot.cbor.zst", "000001.log.cbor.zst"]
+        );
+        assert!(mirror.pull_from(&source).unwrap().is_empty());
+
+        world.spawn(Transform::default());
+        source.append_events(&world.drain_events()).unwrap();
+        let report = source.push_to(&mut mirror).unwrap();
+        assert_eq!(report.copied, vec!["000002.log.cbor.zst"]);
+        assert_eq!(mirror.meta().event_segment_count, 2);
+        mirror.verify_integrity().unwrap();
+        assert_eq!(
+            mirror.load_latest().unwrap().state_hash(),
+            world.state_hash()
+        );
+    }
+
+    #[test]
+    fn pull_refuses_diverged_stores() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut a = WorldStore::open(tmp.path().join("a")).unwrap();
+        let mut b = WorldStore::open(tmp.path().join("b")).unwrap();
+        let mut world = World::with_seed(17);
+        a.take_snapshot(&world).unwrap();
+        world.spawn(Transform::default());
+        b.take_snapshot(&world).unwrap();
+
+        assert!(matches!(
+            b.pull_from(&a),
+            Err(StoreError::Diverged(name)) if name == "000001.snapshot.cbor.zst"
+        ));
+        assert_eq!(b.meta().snapshot_count, 1);
+    }
+}
@@ -0,0 +1,169 @@
+//! Write-ahead log for event appends.
+//!
+//! An append first writes its events to `integrity/append.wal` and syncs it,
+//! then writes the segment files, `world.meta.json` and the manifest. The WAL
+//! is removed once all of them are on disk. Opening a store that still has a
+//! WAL finishes the interrupted append, so a crash can never leave the
+//! manifest referencing a half-written segment or the meta counting a
+//! segment the manifest lacks.
+//!
+//! The WAL file is the SHA-256 of its payload followed by the payload,
+//! compressed (and sealed, for encrypted stores) like a segment. A WAL whose
+//! hash does not match was cut short before the append touched anything else
+//! and is discarded.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use worldspace_ecs::ComponentEvent;
+use worldspace_kernel::WorldEvent;
+
+use super::{
+    StoreError, WorldStore, cbor_deserialize, cbor_serialize, component_log_filename, log_filename,
+    zstd_compress, zstd_decompress,
+};
+
+const WAL_FILE: &str = "append.wal";
+
+/// An event append in progress.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WalRecord {
+    branch: String,
+    segment: u32,
+    events: Vec<WorldEvent>,
+    component_events: Vec<ComponentEvent>,
+}
+
+impl WorldStore {
+    /// Append `events` and `component_events` as segment `segment` of the
+    /// current branch, logging them to the WAL first.
+    pub(super) fn append_logged(
+        &mut self,
+        segment: u32,
+        events: &[WorldEvent],
+        component_events: &[ComponentEvent],
+    ) -> Result<(), StoreError> {
+        self.ensure_writable()?;
+        let record = WalRecord {
+            branch: self.meta.current_branch.clone(),
+            segment,
+            events: events.to_vec(),
+            component_events: component_events.to_vec(),
+        };
+        let payload = self.seal(zstd_compress(&cbor_serialize(&record)?)?)?;
+        let mut file = std::fs::File::create(self.wal_path())?;
+        file.write_all(&Sha256::digest(&payload))?;
+        file.write_all(&payload)?;
+        file.sync_all()?;
+
+        self.apply_wal(record)
+    }
+
+    /// Finish an append interrupted by a crash, if the store has a WAL.
+    pub(super) fn recover_wal(&mut self) -> Result<(), StoreError> {
+        let path = self.wal_path();
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        if data.len() < 32 || Sha256::digest(&data[32..]).as_slice() != &data[..32] {
+            std::fs::remove_file(&path)?;
+            return Ok(());
+        }
+        let record: WalRecord =
+            cbor_deserialize(&zstd_decompress(&self.unseal(data[32..].to_vec())?)?)?;
+        if record.branch != super::MAIN_BRANCH && !self.meta.branches.contains_key(&record.branch) {
+            return Err(StoreError::UnknownBranch(record.branch));
+        }
+        self.apply_wal(record)
+    }
+
+    /// Write the record's segments unless the manifest already lists them,
+    /// count them in the meta, save both and drop the WAL.
+    fn apply_wal(&mut self, record: WalRecord) -> Result<(), StoreError> {
+        let log = log_filename(&record.branch, record.segment);
+        if !self.in_manifest(&log) {
+            self.write_segment("events", log, &record.events)?;
+            if !record.component_events.is_empty() {
+                self.write_segment(
+                    "events",
+                    component_log_filename(&record.branch, record.segment),
+                    &record.component_events,
+                )?;
+            }
+        }
+        let count = match self.meta.branches.get_mut(&record.branch) {
+            Some(branch) => &mut branch.event_segment_count,
+            None => &mut self.meta.event_segment_count,
+        };
+        *count = (*count).max(record.segment);
+
+        self.save_meta()?;
+        self.save_manifest()?;
+        std::fs::remove_file(self.wal_path())?;
+        Ok(())
+    }
+
+    fn wal_path(&self) -> PathBuf {
+        self.root.join("integrity").join(WAL_FILE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+    use worldspace_kernel::World;
+
+    #[test]
+    fn interrupted_append_recovers_on_open() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let mut world = World::with_seed(6);
+        let mut store = WorldStore::open(&path).unwrap();
+        store.take_snapshot(&world).unwrap();
+        world.spawn(Transform::default());
+        store.append_events(&world.drain_events()).unwrap();
+        assert!(!store.wal_path().exists());
+
+        // Simulate a crash after the WAL was synced but before the segment,
+        // meta and manifest were written.
+        world.spawn(Transform::default());
+        let record = WalRecord {
+            branch: store.current_branch().into(),
+            segment: 2,
+            events: world.drain_events(),
+            component_events: Vec::new(),
+        };
+        let payload = zstd_compress(&cbor_serialize(&record).unwrap()).unwrap();
+        let mut data = Sha256::digest(&payload).to_vec();
+        data.extend(payload);
+        std::fs::write(store.wal_path(), data).unwrap();
+        drop(store);
+
+        let store = WorldStore::open(&path).unwrap();
+        assert!(!store.wal_path().exists());
+        assert_eq!(store.meta().event_segment_count, 2);
+        store.verify_integrity().unwrap();
+        assert_eq!(
+            store.load_latest().unwrap().state_hash(),
+            world.state_hash()
+        );
+    }
+
+    #[test]
+    fn torn_wal_is_discarded() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("world_data");
+        let store = WorldStore::open(&path).unwrap();
+        std::fs::write(store.wal_path(), b"half a reco").unwrap();
+        drop(store);
+
+        let store = WorldStore::open(&path).unwrap();
+        assert!(!store.wal_path().exists());
+        assert_eq!(store.meta().event_segment_count, 0);
+    }
+}
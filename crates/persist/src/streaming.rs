@@ -0,0 +1,336 @@
+//! Out-of-core streaming of a world through its store's cells.
+//!
+//! `CellStreamer` drives a `StreamState` whose loader reads cells from a
+//! `WorldStore` on the worker threads. Loaded cells page their entities into
+//! the `World`; unloaded cells are saved back to the store and paged out, so
+//! only the cells around the viewer stay in memory. Paging logs no world
+//! events: entities moving in and out of memory is not an edit.
+//!
+//! Resident entities outside the preload radius (e.g. everything in a
+//! freshly generated world, or an entity that walked out of the loaded
+//! region) are saved and paged out as well. Entities already resident when
+//! their cell loads win over the stored copy, since they are newer.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+
+use worldspace_common::EntityId;
+use worldspace_kernel::{ActivationEvent, EntityData, World};
+use worldspace_stream::{
+    CellCoord, ENTITY_COST_BYTES, GridPartition, StreamConfig, StreamEvent, StreamState,
+};
+
+use crate::store::{StoreError, WorldStore};
+
+/// Entities of a cell as read from the store by a loader thread.
+type CellLoad = Result<BTreeMap<EntityId, EntityData>, StoreError>;
+
+/// Streams a world's cells in from and out to a `WorldStore`.
+pub struct CellStreamer {
+    store: Arc<RwLock<WorldStore>>,
+    state: StreamState<CellLoad>,
+    grid: GridPartition,
+    /// Cells the store holds entities for.
+    stored: HashSet<CellCoord>,
+    /// Loaded cells whose load failed; they hold nothing to save.
+    failed: HashSet<CellCoord>,
+}
+
+impl CellStreamer {
+    /// Stream cells of `grid`'s size to and from `store`.
+    pub fn new(
+        store: WorldStore,
+        grid: GridPartition,
+        config: StreamConfig,
+    ) -> Result<Self, StoreError> {
+        if let Some(expected) = store.meta().cell_size
+            && expected != grid.cell_size()
+        {
+            return Err(StoreError::CellSizeMismatch {
+                expected,
+                actual: grid.cell_size(),
+            });
+        }
+        let stored = store.stored_cells().into_iter().collect();
+        let store = Arc::new(RwLock::new(store));
+        let loader = {
+            let store = Arc::clone(&store);
+            move |coord| store.read().expect("store lock poisoned").load_cell(coord)
+        };
+        let mut state = StreamState::with_loader(config, loader);
+        state.set_content_cost(|load: &CellLoad| {
+            load.as_ref()
+                .map_or(0, |entities| entities.len() * ENTITY_COST_BYTES)
+        });
+        Ok(Self {
+            store,
+            state,
+            grid,
+            stored,
+            failed: HashSet::new(),
+        })
+    }
+
+    /// The streaming state, for its loaded cells, LOD tiers and stats.
+    pub fn state(&self) -> &StreamState<CellLoad> {
+        &self.state
+    }
+
+    /// Mutable streaming state, e.g. to set the view direction.
+    pub fn state_mut(&mut self) -> &mut StreamState<CellLoad> {
+        &mut self.state
+    }
+
+    /// The store cells stream through.
+    pub fn store(&self) -> RwLockReadGuard<'_, WorldStore> {
+        self.store.read().expect("store lock poisoned")
+    }
+
+    /// Stream `world` around `viewer`: page in the cells that finished
+    /// loading and save and page out the cells that were unloaded. Returns
+    /// the streaming events, listing the entities resident in each cell:
+    /// after paging in for loaded cells, before paging out for unloaded
+    /// ones.
+    ///
+    /// A cell that fails to load is reported after the other events are
+    /// applied and stays empty until it is unloaded; nothing is saved for it.
+    pub fn update(
+        &mut self,
+        world: &mut World,
+        viewer: CellCoord,
+    ) -> Result<Vec<StreamEvent<()>>, StoreError> {
+        self.grid.rebuild(world);
+        let (grid, stored) = (&self.grid, &self.stored);
+        let raw = self.state.update_with(viewer, grid, |c| {
+            stored.contains(&c) || !grid.entities_in_cell(c).is_empty()
+        });
+
+        let mut error = None;
+        let mut changes = Vec::with_capacity(raw.len());
+        for event in raw {
+            if let StreamEvent::CellLoaded { coord, content, .. } = event {
+                match content {
+                    Ok(entities) => {
+                        page_in(world, entities);
+                    }
+                    Err(e) => {
+                        tracing::warn!(?coord, error = %e, "failed to load cell");
+                        self.failed.insert(coord);
+                        error.get_or_insert(e);
+                    }
+                }
+                changes.push(StreamEvent::CellLoaded {
+                    coord,
+                    entities: Vec::new(),
+                    content: (),
+                });
+            } else {
+                changes.push(event.map_content(|_| ()));
+            }
+        }
+
+        // List entities against a grid that includes the cells paged in,
+        // which may also be evicted this frame.
+        self.grid.rebuild(world);
+        let mut events = Vec::with_capacity(changes.len());
+        for mut event in changes {
+            let coord = event.coord();
+            let mut resident: Vec<EntityId> =
+                self.grid.entities_in_cell(coord).into_iter().collect();
+            resident.sort();
+            match &mut event {
+                StreamEvent::CellLoaded { entities, .. }
+                | StreamEvent::CellLodChanged { entities, .. }
+                | StreamEvent::CellLoadFailed { entities, .. } => *entities = resident,
+                StreamEvent::CellUnloaded { entities, .. } => {
+                    *entities = resident;
+                    if !self.failed.remove(&coord) {
+                        self.page_out(world, coord, false)?;
+                    }
+                }
+            }
+            events.push(event);
+        }
+        let preload = self.state.config.preload_radius;
+        let strays: Vec<CellCoord> = self
+            .grid
+            .occupied_cells()
+            .filter(|c| viewer.distance(*c) > preload && !self.state.is_loaded(*c))
+            .collect();
+        for coord in strays {
+            self.page_out(world, coord, true)?;
+        }
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(events),
+        }
+    }
+
+    /// Freeze the resident entities outside the active cells of the last
+    /// `update`, so only those within `active_radius` are ticked, and thaw
+    /// those that came back in. Returns the entities that froze or thawed.
+    pub fn activate(&self, world: &mut World) -> Vec<ActivationEvent> {
+        self.state.activate(world, &self.grid)
+    }
+
+    /// Save every resident cell without paging anything out, so the store
+    /// holds the current state of the streamed region. Returns the number
+    /// of entities written.
+    pub fn flush(&mut self, world: &mut World) -> Result<usize, StoreError> {
+        self.grid.rebuild(world);
+        let strays: Vec<CellCoord> = self
+            .grid
+            .occupied_cells()
+            .filter(|c| !self.state.is_loaded(*c))
+            .collect();
+        for coord in strays {
+            self.merge_stored(world, coord)?;
+        }
+        self.grid.rebuild(world);
+        let coords: HashSet<CellCoord> = self
+            .grid
+            .occupied_cells()
+            .chain(self.state.loaded_cells().iter().copied())
+            .filter(|c| !self.failed.contains(c))
+            .collect();
+        let mut written = 0;
+        for coord in coords {
+            written += self.save(world, coord)?;
+        }
+        Ok(written)
+    }
+
+    /// Stop streaming and return the store. Call `flush` first to keep the
+    /// resident cells.
+    pub fn into_store(self) -> WorldStore {
+        let Self { store, state, .. } = self;
+        // Dropping the state joins the loader threads, releasing their
+        // handles on the store.
+        drop(state);
+        match Arc::try_unwrap(store) {
+            Ok(lock) => lock.into_inner().expect("store lock poisoned"),
+            Err(_) => unreachable!("loader threads have exited"),
+        }
+    }
+
+    /// Save cell `coord` and page its entities out of `world`. A cell that
+    /// was never loaded (`stray`) first takes in its stored entities, so
+    /// saving it keeps them.
+    fn page_out(
+        &mut self,
+        world: &mut World,
+        coord: CellCoord,
+        stray: bool,
+    ) -> Result<(), StoreError> {
+        if stray && self.merge_stored(world, coord)? {
+            self.grid.rebuild(world);
+        }
+        let count = self.save(world, coord)?;
+        for id in self.grid.entities_in_cell(coord) {
+            world.page_out(id);
+        }
+        tracing::debug!(?coord, count, "paged out cell");
+        Ok(())
+    }
+
+    /// Save the resident entities of cell `coord` to the store.
+    fn save(&mut self, world: &World, coord: CellCoord) -> Result<usize, StoreError> {
+        let count = self
+            .store
+            .write()
+            .expect("store lock poisoned")
+            .save_cell(world, &self.grid, coord)?;
+        if count > 0 {
+            self.stored.insert(coord);
+        } else {
+            self.stored.remove(&coord);
+        }
+        Ok(count)
+    }
+
+    /// Page in the stored entities of a cell that is not loaded. Returns
+    /// true if the store held any.
+    fn merge_stored(&mut self, world: &mut World, coord: CellCoord) -> Result<bool, StoreError> {
+        if !self.stored.contains(&coord) {
+            return Ok(false);
+        }
+        let entities = self.store().load_cell(coord)?;
+        Ok(page_in(world, entities) > 0)
+    }
+}
+
+/// Insert the entities `world` does not hold yet; returns how many.
+fn page_in(world: &mut World, entities: BTreeMap<EntityId, EntityData>) -> usize {
+    let mut count = 0;
+    for (id, data) in entities {
+        if world.get(id).is_none() {
+            world.page_in(id, data);
+            count += 1;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+
+    fn at(x: f32) -> Transform {
+        Transform {
+            position: glam::Vec3::new(x, 0.0, 0.0),
+            ..Transform::default()
+        }
+    }
+
+    #[test]
+    fn cells_page_through_the_store() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = WorldStore::open(tmp.path().join("world_data")).unwrap();
+        let config = StreamConfig {
+            preload_radius: 1,
+            workers: 0,
+            ..StreamConfig::default()
+        };
+        let mut streamer = CellStreamer::new(store, GridPartition::new(16.0), config).unwrap();
+
+        let mut world = World::with_seed(3);
+        let near = world.spawn(at(1.0));
+        let far = world.spawn(at(100.0));
+        world.drain_events();
+
+        // The far cell is outside the preload radius: saved and paged out.
+        streamer.update(&mut world, CellCoord::new(0, 0)).unwrap();
+        assert!(streamer.activate(&mut world).is_empty());
+        assert!(world.is_active(near));
+        assert!(streamer.state().is_loaded(CellCoord::new(0, 0)));
+        assert!(world.get(far).is_none());
+        assert_eq!(streamer.store().stored_cells(), vec![CellCoord::new(6, 0)]);
+
+        // Walking over swaps the resident cells.
+        let events = streamer.update(&mut world, CellCoord::new(6, 0)).unwrap();
+        assert!(events.contains(&StreamEvent::CellUnloaded {
+            coord: CellCoord::new(0, 0),
+            entities: vec![near],
+        }));
+        assert!(events.contains(&StreamEvent::CellLoaded {
+            coord: CellCoord::new(6, 0),
+            entities: vec![far],
+            content: (),
+        }));
+        assert!(world.get(near).is_none());
+        assert_eq!(world.get(far).unwrap().transform, at(100.0));
+        assert!(world.events().is_empty());
+
+        assert_eq!(streamer.flush(&mut world).unwrap(), 1);
+        let store = streamer.into_store();
+        assert_eq!(store.stored_cells().len(), 2);
+        assert!(
+            store
+                .load_cell(CellCoord::new(0, 0))
+                .unwrap()
+                .contains_key(&near)
+        );
+    }
+}
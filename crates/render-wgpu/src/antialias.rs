@@ -0,0 +1,26 @@
+//! Post-process antialiasing.
+//!
+//! Antialiasing runs on the tonemapped frame instead of multisampling the
+//! scene, so its cost depends on the resolution alone and not on scene
+//! complexity, at the price of some softening of fine detail.
+
+/// Post-process antialiasing applied to the tonemapped frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Antialiasing {
+    #[default]
+    Off,
+    /// Fast approximate antialiasing: smooths edges found from luma
+    /// contrast in a single pass.
+    Fxaa,
+}
+
+impl Antialiasing {
+    pub const ALL: [Antialiasing; 2] = [Self::Off, Self::Fxaa];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Fxaa => "FXAA",
+        }
+    }
+}
@@ -1,4 +1,42 @@
-use glam::{Mat4, Vec3};
+use glam::{Mat4, Vec2, Vec3};
+use std::f32::consts::{FRAC_PI_2, PI};
+use worldspace_assets::Aabb;
+use worldspace_common::Frustum;
+use worldspace_ecs::Layer;
+
+/// Room left around a focused box, as a multiple of its bounding radius.
+const FOCUS_MARGIN: f32 = 1.2;
+
+/// How a camera projects the scene onto the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Perspective with the camera's vertical field of view.
+    Perspective,
+    /// Parallel projection showing `height` world units vertically, for
+    /// blockout and alignment work where perspective gets in the way.
+    Orthographic { height: f32 },
+}
+
+/// Axis-aligned orthographic views.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewPreset {
+    /// Looking down -Y, with -Z towards the top of the screen.
+    Top,
+    /// Looking along -Z.
+    Front,
+    /// Looking along -X, from the +X side.
+    Side,
+}
+
+/// A `FlyCamera::focus_on` move in progress: the camera glides until it
+/// looks at `target` from `distance` away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraFocus {
+    pub target: Vec3,
+    pub distance: f32,
+    /// Orthographic height framing the focused box.
+    pub height: f32,
+}
 
 /// Fly camera with position, yaw, pitch, and projection parameters.
 /// Camera motion is NOT deterministic ... it exists outside the kernel boundary.
@@ -7,11 +45,19 @@ pub struct FlyCamera {
     pub yaw: f32,
     pub pitch: f32,
     pub fov: f32,
+    pub projection: Projection,
     pub aspect: f32,
     pub near: f32,
     pub far: f32,
     pub speed: f32,
     pub sensitivity: f32,
+    /// Rate per second at which `update` closes the gap to a focus target;
+    /// `f32::INFINITY` snaps immediately.
+    pub smoothing: f32,
+    pub focus: Option<CameraFocus>,
+    /// Layers this camera draws. In-world cameras use `Layer::PLAY` to leave
+    /// out editor content such as the grid and debug draws.
+    pub layers: Layer,
 }
 
 impl Default for FlyCamera {
@@ -21,11 +67,15 @@ impl Default for FlyCamera {
             yaw: -90.0_f32.to_radians(),
             pitch: -30.0_f32.to_radians(),
             fov: 60.0_f32.to_radians(),
+            projection: Projection::Perspective,
             aspect: 16.0 / 9.0,
             near: 0.1,
             far: 1000.0,
             speed: 10.0,
             sensitivity: 0.003,
+            smoothing: 10.0,
+            focus: None,
+            layers: Layer::ALL,
         }
     }
 }
@@ -40,8 +90,14 @@ impl FlyCamera {
         .normalize()
     }
 
+    /// Horizontal right vector, from yaw alone so it stays defined when
+    /// looking straight up or down.
     pub fn right(&self) -> Vec3 {
-        self.forward().cross(Vec3::Y).normalize()
+        Vec3::new(-self.yaw.sin(), 0.0, self.yaw.cos())
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.right().cross(self.forward())
     }
 
     pub fn move_forward(&mut self, dt: f32) {
@@ -81,17 +137,124 @@ impl FlyCamera {
         );
     }
 
+    /// Rotate like `rotate`, but swing the camera around `target` at its
+    /// current distance instead of turning in place.
+    pub fn orbit(&mut self, target: Vec3, dx: f32, dy: f32) {
+        let distance = (target - self.position).length();
+        self.rotate(dx, dy);
+        self.position = target - self.forward() * distance;
+    }
+
+    /// Glide towards a view of the world-space box `aabb` filling most of
+    /// the screen, keeping the current orientation. `update` moves the
+    /// camera there over the next frames.
+    pub fn focus_on(&mut self, aabb: &Aabb) {
+        let radius = Vec3::from(aabb.half_extents()).length().max(self.near);
+        // The narrower of the vertical and horizontal fields of view.
+        let half_fov = ((self.fov * 0.5).tan() * self.aspect.min(1.0)).atan();
+        let distance = match self.projection {
+            Projection::Perspective => radius * FOCUS_MARGIN / half_fov.sin(),
+            Projection::Orthographic { .. } => radius * FOCUS_MARGIN * 2.0,
+        };
+        self.focus = Some(CameraFocus {
+            target: Vec3::from(aabb.center()),
+            distance,
+            height: 2.0 * radius * FOCUS_MARGIN / self.aspect.min(1.0),
+        });
+    }
+
+    /// Advance a focus move by `dt` seconds; it ends once the camera arrives.
+    pub fn update(&mut self, dt: f32) {
+        let Some(focus) = self.focus else {
+            return;
+        };
+        let t = 1.0 - (-self.smoothing * dt).exp();
+        let goal = focus.target - self.forward() * focus.distance;
+        self.position = self.position.lerp(goal, t);
+        let mut arrived = self.position.distance(goal) < focus.distance * 1e-3;
+        if let Projection::Orthographic { height } = &mut self.projection {
+            *height += (focus.height - *height) * t;
+            arrived &= (*height - focus.height).abs() < focus.height * 1e-3;
+        }
+        if arrived {
+            self.position = goal;
+            if let Projection::Orthographic { height } = &mut self.projection {
+                *height = focus.height;
+            }
+            self.focus = None;
+        }
+    }
+
     pub fn view_matrix(&self) -> Mat4 {
-        Mat4::look_at_rh(self.position, self.position + self.forward(), Vec3::Y)
+        Mat4::look_to_rh(self.position, self.forward(), self.up())
     }
 
     pub fn projection_matrix(&self) -> Mat4 {
-        Mat4::perspective_rh(self.fov, self.aspect, self.near, self.far)
+        self.projection_with_aspect(self.aspect)
+    }
+
+    /// The camera's projection for a view `aspect` other than its own.
+    pub fn projection_with_aspect(&self, aspect: f32) -> Mat4 {
+        match self.projection {
+            Projection::Perspective => Mat4::perspective_rh(self.fov, aspect, self.near, self.far),
+            Projection::Orthographic { height } => {
+                let (half_w, half_h) = (height * aspect * 0.5, height * 0.5);
+                Mat4::orthographic_rh(-half_w, half_w, -half_h, half_h, self.near, self.far)
+            }
+        }
+    }
+
+    /// Switch to an orthographic `preset` view looking at `target` from
+    /// `distance` away. An orthographic camera keeps its height; a
+    /// perspective one gets the height its field of view covers at
+    /// `distance`, so the framing stays about the same.
+    pub fn set_view_preset(&mut self, preset: ViewPreset, target: Vec3, distance: f32) {
+        let (yaw, pitch) = match preset {
+            ViewPreset::Top => (-FRAC_PI_2, -FRAC_PI_2),
+            ViewPreset::Front => (-FRAC_PI_2, 0.0),
+            ViewPreset::Side => (PI, 0.0),
+        };
+        self.yaw = yaw;
+        self.pitch = pitch;
+        self.position = target - self.forward() * distance;
+        if self.projection == Projection::Perspective {
+            self.projection = Projection::Orthographic {
+                height: 2.0 * distance * (self.fov * 0.5).tan(),
+            };
+        }
+    }
+
+    /// Toggle between perspective and an orthographic projection framing
+    /// about what the perspective view shows at `distance`.
+    pub fn toggle_orthographic(&mut self, distance: f32) {
+        self.projection = match self.projection {
+            Projection::Perspective => Projection::Orthographic {
+                height: 2.0 * distance * (self.fov * 0.5).tan(),
+            },
+            Projection::Orthographic { .. } => Projection::Perspective,
+        };
     }
 
     pub fn view_projection(&self) -> Mat4 {
         self.projection_matrix() * self.view_matrix()
     }
+
+    /// The camera's view volume, for culling and streaming cell queries.
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_view_projection(self.view_projection())
+    }
+
+    /// World-space ray through `screen_pos`, in pixels from the top-left of a
+    /// `viewport` sized in pixels. Returns the point on the near plane and
+    /// the unit direction away from the camera.
+    pub fn screen_ray(&self, screen_pos: Vec2, viewport: Vec2) -> (Vec3, Vec3) {
+        let ndc_x = 2.0 * screen_pos.x / viewport.x.max(1.0) - 1.0;
+        let ndc_y = 1.0 - 2.0 * screen_pos.y / viewport.y.max(1.0);
+        let inverse = self.view_projection().inverse();
+        let near = inverse.project_point3(Vec3::new(ndc_x, ndc_y, 0.0));
+        let far = inverse.project_point3(Vec3::new(ndc_x, ndc_y, 1.0));
+        (near, (far - near).normalize())
+    }
 }
 
 #[cfg(test)]
@@ -114,4 +277,105 @@ mod tests {
         cam.move_forward(1.0);
         assert_ne!(cam.position, start);
     }
+
+    #[test]
+    fn screen_center_ray_looks_forward() {
+        let cam = FlyCamera {
+            aspect: 2.0,
+            ..FlyCamera::default()
+        };
+        let viewport = Vec2::new(800.0, 400.0);
+        let (origin, dir) = cam.screen_ray(viewport * 0.5, viewport);
+        assert!((dir - cam.forward()).length() < 1e-3);
+        assert!((origin - (cam.position + cam.forward() * cam.near)).length() < 1e-3);
+
+        let (_, left) = cam.screen_ray(Vec2::new(0.0, 200.0), viewport);
+        assert!(left.dot(cam.right()) < 0.0);
+        let (_, up) = cam.screen_ray(Vec2::new(400.0, 0.0), viewport);
+        assert!(up.y > cam.forward().y);
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let mut cam = FlyCamera {
+            aspect: 2.0,
+            ..FlyCamera::default()
+        };
+        cam.set_view_preset(ViewPreset::Top, Vec3::ZERO, 20.0);
+        assert!((cam.position - Vec3::new(0.0, 20.0, 0.0)).length() < 1e-3);
+        let Projection::Orthographic { height } = cam.projection else {
+            panic!("preset should switch to orthographic");
+        };
+        assert!((height - 40.0 * 30f32.to_radians().tan()).abs() < 1e-3);
+
+        let viewport = Vec2::new(800.0, 400.0);
+        let (center, down) = cam.screen_ray(viewport * 0.5, viewport);
+        let (corner, corner_dir) = cam.screen_ray(Vec2::ZERO, viewport);
+        assert!((down - Vec3::NEG_Y).length() < 1e-3);
+        assert!((corner_dir - down).length() < 1e-3);
+        // The top-left corner is half the view up (-Z) and left (-X).
+        let offset = corner - center;
+        assert!((offset.x + height).abs() < 1e-2);
+        assert!((offset.z + height * 0.5).abs() < 1e-2);
+    }
+
+    #[test]
+    fn focus_glides_to_frame_box() {
+        let mut cam = FlyCamera {
+            aspect: 1.0,
+            fov: 90f32.to_radians(),
+            ..FlyCamera::default()
+        };
+        let aabb = Aabb {
+            min: [9.0, -1.0, -1.0],
+            max: [11.0, 1.0, 1.0],
+        };
+        cam.focus_on(&aabb);
+        let start = cam.position;
+        cam.update(0.01);
+        assert!(cam.position != start && cam.focus.is_some());
+        for _ in 0..100 {
+            cam.update(0.05);
+        }
+        assert_eq!(cam.focus, None);
+        // The box is centered, its bounding sphere inside the view.
+        let to_center = Vec3::new(10.0, 0.0, 0.0) - cam.position;
+        assert!(to_center.normalize().dot(cam.forward()) > 0.9999);
+        let radius = 3f32.sqrt();
+        assert!((to_center.length() - radius * FOCUS_MARGIN * 2f32.sqrt()).abs() < 1e-3);
+
+        cam.smoothing = f32::INFINITY;
+        cam.projection = Projection::Orthographic { height: 50.0 };
+        cam.focus_on(&aabb);
+        cam.update(0.016);
+        assert_eq!(cam.focus, None);
+        let Projection::Orthographic { height } = cam.projection else {
+            panic!("focus should keep the projection");
+        };
+        assert!((height - 2.0 * radius * FOCUS_MARGIN).abs() < 1e-4);
+    }
+
+    #[test]
+    fn orbit_keeps_distance_to_target() {
+        let mut cam = FlyCamera::default();
+        let target = Vec3::new(1.0, 0.0, -2.0);
+        cam.position = target - cam.forward() * 8.0;
+        cam.orbit(target, 300.0, -50.0);
+        assert!(((target - cam.position).length() - 8.0).abs() < 1e-4);
+        assert!((target - cam.position).normalize().dot(cam.forward()) > 0.9999);
+    }
+
+    #[test]
+    fn presets_look_along_axes() {
+        let mut cam = FlyCamera::default();
+        cam.set_view_preset(ViewPreset::Front, Vec3::ZERO, 10.0);
+        assert!((cam.forward() - Vec3::NEG_Z).length() < 1e-5);
+        assert!((cam.up() - Vec3::Y).length() < 1e-5);
+        cam.set_view_preset(ViewPreset::Side, Vec3::ZERO, 10.0);
+        assert!((cam.forward() - Vec3::NEG_X).length() < 1e-5);
+        assert!((cam.position - Vec3::new(10.0, 0.0, 0.0)).length() < 1e-4);
+
+        cam.toggle_orthographic(10.0);
+        assert_eq!(cam.projection, Projection::Perspective);
+    }
 }
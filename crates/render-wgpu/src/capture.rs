@@ -0,0 +1,83 @@
+//! Readback of rendered frames for screenshots.
+//!
+//! Texture-to-buffer copies pad each row to `COPY_BYTES_PER_ROW_ALIGNMENT`,
+//! so the mapped buffer is unpadded into a tightly packed image.
+
+use image::RgbaImage;
+
+/// Copy an RGBA8 `texture` into an image, blocking until the GPU has
+/// finished the copy.
+pub(crate) fn read_rgba8(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> Result<RgbaImage, wgpu::BufferAsyncError> {
+    let size = texture.size();
+    let row_bytes = size.width * 4;
+    let padded_row_bytes = row_bytes.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("capture_buffer"),
+        size: padded_row_bytes as u64 * size.height as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("capture_encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row_bytes),
+                rows_per_image: Some(size.height),
+            },
+        },
+        size,
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    let _ = device.poll(wgpu::Maintain::Wait);
+    receiver.recv().unwrap_or(Err(wgpu::BufferAsyncError))?;
+
+    let pixels = unpad_rows(
+        &slice.get_mapped_range(),
+        row_bytes as usize,
+        padded_row_bytes as usize,
+    );
+    buffer.unmap();
+    Ok(RgbaImage::from_raw(size.width, size.height, pixels)
+        .expect("unpadded rows hold width * height pixels"))
+}
+
+/// The first `row_bytes` of each `padded_row_bytes` row of `data`.
+fn unpad_rows(data: &[u8], row_bytes: usize, padded_row_bytes: usize) -> Vec<u8> {
+    data.chunks(padded_row_bytes)
+        .flat_map(|row| &row[..row_bytes])
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpad_drops_row_padding() {
+        let data = [1, 2, 3, 0, 0, 4, 5, 6, 0, 0];
+        assert_eq!(unpad_rows(&data, 3, 5), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(unpad_rows(&data, 5, 5), data.to_vec());
+    }
+}
@@ -0,0 +1,188 @@
+//! Immediate debug line drawing.
+//!
+//! `DebugDraw` collects world-space colored line segments that the renderer
+//! draws over the scene in every viewport, depth tested like the grid floor.
+//! Collider wireframes are generated into the same line list each frame.
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Quat, Vec3};
+use std::f32::consts::TAU;
+use worldspace_ecs::{Collider, ComponentStore, Layer, Tag};
+use worldspace_kernel::World;
+
+/// Segments per circle of a wire sphere.
+const CIRCLE_SEGMENTS: usize = 32;
+
+/// Color of collider wireframes.
+pub(crate) const COLLIDER_COLOR: [f32; 4] = [0.2, 1.0, 0.4, 1.0];
+
+/// GPU layout of one line end point (matches the grid shader's input).
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub(crate) struct LineVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+/// World-space line segments to draw over the scene. Lines stay until
+/// `clear` is called.
+#[derive(Debug, Clone, Default)]
+pub struct DebugDraw {
+    vertices: Vec<LineVertex>,
+}
+
+impl DebugDraw {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn line(&mut self, from: Vec3, to: Vec3, color: [f32; 4]) {
+        self.vertices.push(LineVertex {
+            position: from.to_array(),
+            color,
+        });
+        self.vertices.push(LineVertex {
+            position: to.to_array(),
+            color,
+        });
+    }
+
+    /// The twelve edges of a box around `center` with `half_extents` along
+    /// the axes of `rotation`.
+    pub fn wire_box(&mut self, center: Vec3, half_extents: Vec3, rotation: Quat, color: [f32; 4]) {
+        let corner = |i: usize| {
+            let sign = Vec3::new(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            center + rotation * (sign * half_extents)
+        };
+        for i in 0..8 {
+            // Connect each corner to its neighbours with a higher index.
+            for bit in [1, 2, 4] {
+                if i & bit == 0 {
+                    self.line(corner(i), corner(i | bit), color);
+                }
+            }
+        }
+    }
+
+    /// Three great circles of a sphere, one around each axis.
+    pub fn wire_sphere(&mut self, center: Vec3, radius: f32, color: [f32; 4]) {
+        let axes = [(Vec3::X, Vec3::Y), (Vec3::Y, Vec3::Z), (Vec3::Z, Vec3::X)];
+        for (u, v) in axes {
+            let point = |i: usize| {
+                let angle = i as f32 / CIRCLE_SEGMENTS as f32 * TAU;
+                center + (u * angle.cos() + v * angle.sin()) * radius
+            };
+            for i in 0..CIRCLE_SEGMENTS {
+                self.line(point(i), point(i + 1), color);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+
+    /// Number of line segments.
+    pub fn len(&self) -> usize {
+        self.vertices.len() / 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    pub(crate) fn vertices(&self) -> &[LineVertex] {
+        &self.vertices
+    }
+}
+
+/// Wireframes of the colliders of visible entities on `layers`, scaled and
+/// rotated by their entity's transform. Spheres scale by the largest axis.
+pub(crate) fn collider_lines(
+    draw: &mut DebugDraw,
+    world: &World,
+    components: &ComponentStore,
+    layers: Layer,
+) {
+    for (id, collider) in components.colliders() {
+        let Some(entity) = world.get(*id) else {
+            continue;
+        };
+        if components.has_tag(*id, Tag::Hidden)
+            || !components.is_visible(*id)
+            || !components.in_layers(*id, layers)
+        {
+            continue;
+        }
+        let t = &entity.transform;
+        match *collider {
+            Collider::Box { half_extents } => draw.wire_box(
+                t.position,
+                Vec3::from(half_extents) * t.scale,
+                t.rotation,
+                COLLIDER_COLOR,
+            ),
+            Collider::Sphere { radius } => draw.wire_sphere(
+                t.position,
+                radius * t.scale.abs().max_element(),
+                COLLIDER_COLOR,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+
+    #[test]
+    fn box_and_sphere_outlines() {
+        let mut draw = DebugDraw::new();
+        draw.wire_box(Vec3::ZERO, Vec3::ONE, Quat::IDENTITY, COLLIDER_COLOR);
+        assert_eq!(draw.len(), 12);
+        // Every edge has unit half-length corners and runs along one axis.
+        for edge in draw.vertices().chunks(2) {
+            let (a, b) = (Vec3::from(edge[0].position), Vec3::from(edge[1].position));
+            assert_eq!(a.abs(), Vec3::ONE);
+            assert_eq!((b - a).length(), 2.0);
+        }
+
+        draw.clear();
+        draw.wire_sphere(Vec3::X, 2.0, COLLIDER_COLOR);
+        assert_eq!(draw.len(), 3 * CIRCLE_SEGMENTS);
+        for v in draw.vertices() {
+            assert!(((Vec3::from(v.position) - Vec3::X).length() - 2.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn collider_lines_filter_by_layer() {
+        let mut world = World::new();
+        let mut components = ComponentStore::new();
+        let boxed = world.spawn(Transform {
+            scale: Vec3::splat(2.0),
+            ..Transform::default()
+        });
+        components.set_collider(boxed, Collider::default());
+        let sphere = world.spawn(Transform::default());
+        components.set_collider(sphere, Collider::Sphere { radius: 1.0 });
+        components.set_layer(sphere, Layer::single(3));
+
+        let mut draw = DebugDraw::new();
+        collider_lines(&mut draw, &world, &components, Layer::DEFAULT);
+        assert_eq!(draw.len(), 12);
+        assert!(draw.vertices().iter().all(|v| v.position[0].abs() == 1.0));
+
+        draw.clear();
+        collider_lines(&mut draw, &world, &components, Layer::ALL);
+        assert_eq!(draw.len(), 12 + 3 * CIRCLE_SEGMENTS);
+        draw.clear();
+        collider_lines(&mut draw, &world, &components, Layer::NONE);
+        assert!(draw.is_empty());
+    }
+}
@@ -1,24 +1,50 @@
+use crate::antialias::Antialiasing;
 use crate::camera::FlyCamera;
+use crate::capture;
+use crate::debug_draw::{self, DebugDraw, LineVertex};
+use crate::gpu_cull::{CullDraw, GpuCuller};
+use crate::lights::{self, GpuLight, MAX_LIGHTS};
+use crate::lod::{self, LodHistory, LodSettings};
+use crate::meshes::{GpuMesh, MeshCache};
+use crate::picking;
+use crate::profiler::GpuProfiler;
 use crate::shaders;
+use crate::sprites::{self, SpriteInstance};
+use crate::ssao::{SsaoPass, SsaoSettings, SsaoUniforms};
+use crate::textures::{GpuTexture, TextureCache};
+use crate::tonemap::{HDR_FORMAT, Tonemap, TonemapUniforms};
+use crate::viewport::{MAX_VIEWPORTS, Viewport, ViewportRect};
 use bytemuck::{Pod, Zeroable};
-use glam::Mat4;
+use glam::{Mat4, Vec2, Vec3};
+use image::RgbaImage;
 use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
 use wgpu::util::DeviceExt;
-use worldspace_common::EntityId;
-use worldspace_ecs::Renderable;
+use worldspace_assets::{
+    Aabb, AssetError, AssetEvent, AssetId, AssetKind, AssetStore, Material, Mesh, Shader, Texture,
+};
+use worldspace_common::{EntityId, Frustum};
+use worldspace_ecs::{ComponentStore, Layer, TextureHandle};
 use worldspace_kernel::World;
+use worldspace_render::{DrawAssets, RenderStats, drawn_entities, world_aabb};
 
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
 struct Uniforms {
     view_proj: [[f32; 4]; 4],
+    light_count: u32,
+    _pad: [u32; 3],
+    lights: [GpuLight; MAX_LIGHTS],
 }
 
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
-struct Vertex {
-    position: [f32; 3],
-    normal: [f32; 3],
+pub(crate) struct Vertex {
+    pub(crate) position: [f32; 3],
+    pub(crate) normal: [f32; 3],
+    pub(crate) uv: [f32; 2],
 }
 
 #[repr(C)]
@@ -31,48 +57,41 @@ struct InstanceData {
     color: [f32; 4],
 }
 
-#[repr(C)]
-#[derive(Copy, Clone, Pod, Zeroable)]
-struct GridVertex {
-    position: [f32; 3],
-    color: [f32; 4],
-}
-
-/// Generate unit cube vertices and indices.
+/// Generate unit cube vertices and indices. Each face maps the whole texture.
 fn cube_mesh() -> (Vec<Vertex>, Vec<u16>) {
     let p = 0.5_f32;
     #[rustfmt::skip]
     let vertices = vec![
         // +Z face
-        Vertex { position: [-p, -p,  p], normal: [0.0, 0.0, 1.0] },
-        Vertex { position: [ p, -p,  p], normal: [0.0, 0.0, 1.0] },
-        Vertex { position: [ p,  p,  p], normal: [0.0, 0.0, 1.0] },
-        Vertex { position: [-p,  p,  p], normal: [0.0, 0.0, 1.0] },
+        Vertex { position: [-p, -p,  p], normal: [0.0, 0.0, 1.0], uv: [0.0, 1.0] },
+        Vertex { position: [ p, -p,  p], normal: [0.0, 0.0, 1.0], uv: [1.0, 1.0] },
+        Vertex { position: [ p,  p,  p], normal: [0.0, 0.0, 1.0], uv: [1.0, 0.0] },
+        Vertex { position: [-p,  p,  p], normal: [0.0, 0.0, 1.0], uv: [0.0, 0.0] },
         // -Z face
-        Vertex { position: [ p, -p, -p], normal: [0.0, 0.0, -1.0] },
-        Vertex { position: [-p, -p, -p], normal: [0.0, 0.0, -1.0] },
-        Vertex { position: [-p,  p, -p], normal: [0.0, 0.0, -1.0] },
-        Vertex { position: [ p,  p, -p], normal: [0.0, 0.0, -1.0] },
+        Vertex { position: [ p, -p, -p], normal: [0.0, 0.0, -1.0], uv: [0.0, 1.0] },
+        Vertex { position: [-p, -p, -p], normal: [0.0, 0.0, -1.0], uv: [1.0, 1.0] },
+        Vertex { position: [-p,  p, -p], normal: [0.0, 0.0, -1.0], uv: [1.0, 0.0] },
+        Vertex { position: [ p,  p, -p], normal: [0.0, 0.0, -1.0], uv: [0.0, 0.0] },
         // +X face
-        Vertex { position: [ p, -p,  p], normal: [1.0, 0.0, 0.0] },
-        Vertex { position: [ p, -p, -p], normal: [1.0, 0.0, 0.0] },
-        Vertex { position: [ p,  p, -p], normal: [1.0, 0.0, 0.0] },
-        Vertex { position: [ p,  p,  p], normal: [1.0, 0.0, 0.0] },
+        Vertex { position: [ p, -p,  p], normal: [1.0, 0.0, 0.0], uv: [0.0, 1.0] },
+        Vertex { position: [ p, -p, -p], normal: [1.0, 0.0, 0.0], uv: [1.0, 1.0] },
+        Vertex { position: [ p,  p, -p], normal: [1.0, 0.0, 0.0], uv: [1.0, 0.0] },
+        Vertex { position: [ p,  p,  p], normal: [1.0, 0.0, 0.0], uv: [0.0, 0.0] },
         // -X face
-        Vertex { position: [-p, -p, -p], normal: [-1.0, 0.0, 0.0] },
-        Vertex { position: [-p, -p,  p], normal: [-1.0, 0.0, 0.0] },
-        Vertex { position: [-p,  p,  p], normal: [-1.0, 0.0, 0.0] },
-        Vertex { position: [-p,  p, -p], normal: [-1.0, 0.0, 0.0] },
+        Vertex { position: [-p, -p, -p], normal: [-1.0, 0.0, 0.0], uv: [0.0, 1.0] },
+        Vertex { position: [-p, -p,  p], normal: [-1.0, 0.0, 0.0], uv: [1.0, 1.0] },
+        Vertex { position: [-p,  p,  p], normal: [-1.0, 0.0, 0.0], uv: [1.0, 0.0] },
+        Vertex { position: [-p,  p, -p], normal: [-1.0, 0.0, 0.0], uv: [0.0, 0.0] },
         // +Y face
-        Vertex { position: [-p,  p,  p], normal: [0.0, 1.0, 0.0] },
-        Vertex { position: [ p,  p,  p], normal: [0.0, 1.0, 0.0] },
-        Vertex { position: [ p,  p, -p], normal: [0.0, 1.0, 0.0] },
-        Vertex { position: [-p,  p, -p], normal: [0.0, 1.0, 0.0] },
+        Vertex { position: [-p,  p,  p], normal: [0.0, 1.0, 0.0], uv: [0.0, 1.0] },
+        Vertex { position: [ p,  p,  p], normal: [0.0, 1.0, 0.0], uv: [1.0, 1.0] },
+        Vertex { position: [ p,  p, -p], normal: [0.0, 1.0, 0.0], uv: [1.0, 0.0] },
+        Vertex { position: [-p,  p, -p], normal: [0.0, 1.0, 0.0], uv: [0.0, 0.0] },
         // -Y face
-        Vertex { position: [-p, -p, -p], normal: [0.0, -1.0, 0.0] },
-        Vertex { position: [ p, -p, -p], normal: [0.0, -1.0, 0.0] },
-        Vertex { position: [ p, -p,  p], normal: [0.0, -1.0, 0.0] },
-        Vertex { position: [-p, -p,  p], normal: [0.0, -1.0, 0.0] },
+        Vertex { position: [-p, -p, -p], normal: [0.0, -1.0, 0.0], uv: [0.0, 1.0] },
+        Vertex { position: [ p, -p, -p], normal: [0.0, -1.0, 0.0], uv: [1.0, 1.0] },
+        Vertex { position: [ p, -p,  p], normal: [0.0, -1.0, 0.0], uv: [1.0, 0.0] },
+        Vertex { position: [-p, -p,  p], normal: [0.0, -1.0, 0.0], uv: [0.0, 0.0] },
     ];
     #[rustfmt::skip]
     let indices: Vec<u16> = vec![
@@ -87,7 +106,7 @@ fn cube_mesh() -> (Vec<Vertex>, Vec<u16>) {
 }
 
 /// Generate grid floor line vertices.
-fn grid_mesh(half_extent: i32, spacing: f32) -> Vec<GridVertex> {
+fn grid_mesh(half_extent: i32, spacing: f32) -> Vec<LineVertex> {
     let mut verts = Vec::new();
     let color = [0.4, 0.4, 0.4, 1.0];
     let extent = half_extent as f32 * spacing;
@@ -95,20 +114,20 @@ fn grid_mesh(half_extent: i32, spacing: f32) -> Vec<GridVertex> {
     for i in -half_extent..=half_extent {
         let offset = i as f32 * spacing;
         // Lines along X
-        verts.push(GridVertex {
+        verts.push(LineVertex {
             position: [-extent, 0.0, offset],
             color,
         });
-        verts.push(GridVertex {
+        verts.push(LineVertex {
             position: [extent, 0.0, offset],
             color,
         });
         // Lines along Z
-        verts.push(GridVertex {
+        verts.push(LineVertex {
             position: [offset, 0.0, -extent],
             color,
         });
-        verts.push(GridVertex {
+        verts.push(LineVertex {
             position: [offset, 0.0, extent],
             color,
         });
@@ -116,48 +135,157 @@ fn grid_mesh(half_extent: i32, spacing: f32) -> Vec<GridVertex> {
     verts
 }
 
+/// Format of the tonemapped frame, before it is copied to the surface; 8-bit
+/// RGBA so frames read back as PNG-ready pixels.
+const FRAME_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Capacity of the debug line buffer; lines past it are dropped.
+const MAX_DEBUG_VERTICES: u32 = 65_536;
+
+/// Instances the GPU culling path holds per frame, well past the CPU path's
+/// `max_instances`.
+const GPU_CULL_CAPACITY: u32 = 1 << 18;
+
+/// Draws of one viewport within the frame's instance buffers.
+struct ViewportPass {
+    /// Dynamic offset of the viewport's uniforms.
+    offset: u32,
+    rect: ViewportRect,
+    /// Range of the viewport's mesh draws.
+    draws: Range<usize>,
+    /// Range of the viewport's sprite draws.
+    sprite_draws: Range<usize>,
+    /// Draw the grid and debug lines (the camera shows `Layer::EDITOR`).
+    editor: bool,
+}
+
+/// Color selected entities are tinted towards.
+const SELECTION_COLOR: [f32; 3] = [1.0, 0.8, 0.0];
+
+/// `color` tinted halfway towards `SELECTION_COLOR`, keeping its alpha.
+fn highlight(color: [f32; 4]) -> [f32; 4] {
+    let [r, g, b] = SELECTION_COLOR;
+    [
+        (color[0] + r) * 0.5,
+        (color[1] + g) * 0.5,
+        (color[2] + b) * 0.5,
+        color[3],
+    ]
+}
+
+/// Material shader, mesh and base color texture an instanced draw uses;
+/// `None` shader is the standard world shader.
+type Batch = (Option<AssetId>, Option<AssetId>, Option<TextureHandle>);
+
 /// wgpu-based world renderer.
 pub struct WgpuRenderer {
     cube_pipeline: wgpu::RenderPipeline,
+    /// Depth-only variant of `cube_pipeline`; see `set_depth_prepass`.
+    depth_prepass_pipeline: wgpu::RenderPipeline,
+    depth_prepass: bool,
+    /// Layout shared by the standard and custom material pipelines.
+    mesh_pipeline_layout: wgpu::PipelineLayout,
+    /// Pipelines of material shader assets, built by `load_shader`.
+    material_pipelines: BTreeMap<AssetId, wgpu::RenderPipeline>,
     grid_pipeline: wgpu::RenderPipeline,
+    sprite_pipeline: wgpu::RenderPipeline,
+    tonemap_pipeline: wgpu::RenderPipeline,
+    tonemap_bind_group_layout: wgpu::BindGroupLayout,
+    tonemap_bind_group: wgpu::BindGroup,
+    tonemap_buffer: wgpu::Buffer,
+    tonemap: Tonemap,
+    exposure: f32,
+    blit_pipeline: wgpu::RenderPipeline,
+    fxaa_pipeline: wgpu::RenderPipeline,
+    fxaa_bind_group_layout: wgpu::BindGroupLayout,
+    fxaa_sampler: wgpu::Sampler,
+    /// Reads `ldr_texture`.
+    fxaa_bind_group: wgpu::BindGroup,
+    antialiasing: Antialiasing,
+    blit_bind_group_layout: wgpu::BindGroupLayout,
+    blit_bind_group: wgpu::BindGroup,
     uniform_buffer: wgpu::Buffer,
     uniform_bind_group: wgpu::BindGroup,
+    /// Bytes between viewports' uniforms, aligned for dynamic offsets.
+    uniform_stride: u64,
     cube_vertex_buffer: wgpu::Buffer,
     cube_index_buffer: wgpu::Buffer,
     cube_index_count: u32,
     grid_vertex_buffer: wgpu::Buffer,
     grid_vertex_count: u32,
     instance_buffer: wgpu::Buffer,
+    sprite_instance_buffer: wgpu::Buffer,
+    /// Line end points from `debug_draw` and the collider pass.
+    debug_vertex_buffer: wgpu::Buffer,
+    /// Capacity of each of the entity and sprite instance buffers.
     max_instances: u32,
     depth_texture: wgpu::TextureView,
+    /// Lit scene color, tonemapped into `frame_texture`.
+    hdr_texture: wgpu::Texture,
+    /// The last rendered frame, before UI drawn over the surface.
+    frame_texture: wgpu::Texture,
+    /// The tonemapped scene, antialiased into `frame_texture` when
+    /// antialiasing is on.
+    ldr_texture: wgpu::Texture,
+    /// Size of the render target in pixels, for picking.
+    viewport: Vec2,
     surface_format: wgpu::TextureFormat,
+    visible_layers: Layer,
+    textures: TextureCache,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    /// Bind group of a 1x1 white texture, for untextured materials.
+    white_bind_group: wgpu::BindGroup,
+    ao_bind_group_layout: wgpu::BindGroupLayout,
+    /// Bound in place of the SSAO result while SSAO is off.
+    white_ao_bind_group: wgpu::BindGroup,
+    ssao: SsaoPass,
+    ssao_settings: SsaoSettings,
+    texture_bind_groups: BTreeMap<TextureHandle, wgpu::BindGroup>,
+    materials: BTreeMap<AssetId, Material>,
+    meshes: MeshCache,
+    shader_modules: BTreeMap<AssetId, wgpu::ShaderModule>,
+    /// Pass timing, when the device supports timestamp queries.
+    profiler: Option<GpuProfiler>,
+    /// Chain asset and LOD levels of each source mesh with a LOD chain.
+    lod_chains: BTreeMap<AssetId, (AssetId, Vec<AssetId>)>,
+    lod_settings: LodSettings,
+    lod_history: Mutex<LodHistory>,
+    debug_draw: DebugDraw,
+    /// Layers whose colliders are drawn as wireframes.
+    collider_layers: Layer,
+    /// Set while GPU culling is on; see `set_gpu_culling`.
+    gpu_culler: Option<GpuCuller>,
 }
 
 impl WgpuRenderer {
     pub fn new(
         device: &wgpu::Device,
+        queue: &wgpu::Queue,
         surface_format: wgpu::TextureFormat,
         width: u32,
         height: u32,
     ) -> Self {
-        // Uniform buffer
-        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        // Uniform buffer, one slot per viewport selected by dynamic offset
+        let uniform_size = std::mem::size_of::<Uniforms>() as u64;
+        let uniform_stride = uniform_size
+            .next_multiple_of(device.limits().min_uniform_buffer_offset_alignment as u64);
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("uniform_buffer"),
-            contents: bytemuck::bytes_of(&Uniforms {
-                view_proj: Mat4::IDENTITY.to_cols_array_2d(),
-            }),
+            size: uniform_stride * MAX_VIEWPORTS as u64,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
         });
 
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("uniform_bind_group_layout"),
             entries: &[wgpu::BindGroupLayoutEntry {
                 binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+                    has_dynamic_offset: true,
+                    min_binding_size: wgpu::BufferSize::new(uniform_size),
                 },
                 count: None,
             }],
@@ -168,12 +296,93 @@ impl WgpuRenderer {
             layout: &bind_group_layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &uniform_buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(uniform_size),
+                }),
+            }],
+        });
+
+        // Base color texture and sampler, bound per material
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("texture_bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("base_color_sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            address_mode_w: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let white_view = Self::create_white_texture(device, queue);
+        let white_bind_group = Self::create_texture_bind_group(
+            device,
+            &texture_bind_group_layout,
+            &sampler,
+            &white_view,
+        );
+
+        // Ambient occlusion, bound per frame: the SSAO result or white
+        let ao_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("ao_bind_group_layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                }],
+            });
+        let white_ao_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("white_ao_bind_group"),
+            layout: &ao_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&white_view),
             }],
         });
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("pipeline_layout"),
+            bind_group_layouts: &[
+                &bind_group_layout,
+                &texture_bind_group_layout,
+                &ao_bind_group_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+
+        let grid_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("grid_pipeline_layout"),
             bind_group_layouts: &[&bind_group_layout],
             push_constant_ranges: &[],
         });
@@ -184,48 +393,55 @@ impl WgpuRenderer {
             source: wgpu::ShaderSource::Wgsl(shaders::WORLD_SHADER.into()),
         });
 
-        let cube_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("cube_pipeline"),
-            layout: Some(&pipeline_layout),
+        let cube_pipeline = Self::create_mesh_pipeline(
+            device,
+            "cube_pipeline",
+            &pipeline_layout,
+            &cube_shader,
+            false,
+        );
+        let depth_prepass_pipeline = Self::create_mesh_pipeline(
+            device,
+            "depth_prepass_pipeline",
+            &pipeline_layout,
+            &cube_shader,
+            true,
+        );
+
+        // Grid pipeline
+        let grid_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("grid_shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::GRID_SHADER.into()),
+        });
+
+        let grid_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("grid_pipeline"),
+            layout: Some(&grid_pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &cube_shader,
-                entry_point: Some("vs_main"),
+                module: &grid_shader,
+                entry_point: Some("vs_grid"),
                 compilation_options: Default::default(),
-                buffers: &[
-                    wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<Vertex>() as u64,
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        attributes: &wgpu::vertex_attr_array![
-                            0 => Float32x3,
-                            1 => Float32x3,
-                        ],
-                    },
-                    wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<InstanceData>() as u64,
-                        step_mode: wgpu::VertexStepMode::Instance,
-                        attributes: &wgpu::vertex_attr_array![
-                            2 => Float32x4,
-                            3 => Float32x4,
-                            4 => Float32x4,
-                            5 => Float32x4,
-                            6 => Float32x4,
-                        ],
-                    },
-                ],
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<LineVertex>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float32x3,
+                        1 => Float32x4,
+                    ],
+                }],
             },
             fragment: Some(wgpu::FragmentState {
-                module: &cube_shader,
-                entry_point: Some("fs_main"),
+                module: &grid_shader,
+                entry_point: Some("fs_grid"),
                 compilation_options: Default::default(),
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_format,
+                    format: HDR_FORMAT,
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
             }),
             primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                cull_mode: Some(wgpu::Face::Back),
+                topology: wgpu::PrimitiveTopology::LineList,
                 ..Default::default()
             },
             depth_stencil: Some(wgpu::DepthStencilState {
@@ -240,45 +456,49 @@ impl WgpuRenderer {
             cache: None,
         });
 
-        // Grid pipeline
-        let grid_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("grid_shader"),
-            source: wgpu::ShaderSource::Wgsl(shaders::GRID_SHADER.into()),
+        // Sprite pipeline: alpha-blended camera-facing quads, depth tested
+        // against the scene but not written
+        let sprite_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("sprite_shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::SPRITE_SHADER.into()),
         });
 
-        let grid_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("grid_pipeline"),
+        let sprite_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("sprite_pipeline"),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &grid_shader,
-                entry_point: Some("vs_grid"),
+                module: &sprite_shader,
+                entry_point: Some("vs_sprite"),
                 compilation_options: Default::default(),
                 buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: std::mem::size_of::<GridVertex>() as u64,
-                    step_mode: wgpu::VertexStepMode::Vertex,
+                    array_stride: std::mem::size_of::<SpriteInstance>() as u64,
+                    step_mode: wgpu::VertexStepMode::Instance,
                     attributes: &wgpu::vertex_attr_array![
                         0 => Float32x3,
-                        1 => Float32x4,
+                        1 => Float32x3,
+                        2 => Float32x3,
+                        3 => Float32x4,
+                        4 => Float32x4,
                     ],
                 }],
             },
             fragment: Some(wgpu::FragmentState {
-                module: &grid_shader,
-                entry_point: Some("fs_grid"),
+                module: &sprite_shader,
+                entry_point: Some("fs_sprite"),
                 compilation_options: Default::default(),
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_format,
-                    blend: Some(wgpu::BlendState::REPLACE),
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
             }),
             primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::LineList,
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
                 ..Default::default()
             },
             depth_stencil: Some(wgpu::DepthStencilState {
                 format: wgpu::TextureFormat::Depth32Float,
-                depth_write_enabled: true,
+                depth_write_enabled: false,
                 depth_compare: wgpu::CompareFunction::Less,
                 stencil: Default::default(),
                 bias: Default::default(),
@@ -319,35 +539,731 @@ impl WgpuRenderer {
             usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
+        let sprite_instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("sprite_instance_buffer"),
+            size: (max_instances as u64) * std::mem::size_of::<SpriteInstance>() as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let debug_vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("debug_vertex_buffer"),
+            size: MAX_DEBUG_VERTICES as u64 * std::mem::size_of::<LineVertex>() as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
 
         let depth_texture = Self::create_depth_texture(device, width, height);
+        let ssao = SsaoPass::new(device, &depth_texture, &ao_bind_group_layout, width, height);
+
+        // Blit pipeline: copies the offscreen frame to the surface
+        let blit_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("blit_bind_group_layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                }],
+            });
+        let blit_pipeline = Self::create_fullscreen_pipeline(
+            device,
+            "blit",
+            &blit_bind_group_layout,
+            shaders::BLIT_SHADER,
+            surface_format,
+        );
+
+        // Tonemap pipeline: exposure and tonemapping from the HDR target into
+        // the frame
+        let tonemap_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("tonemap_bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+        let tonemap_pipeline = Self::create_fullscreen_pipeline(
+            device,
+            "tonemap",
+            &tonemap_bind_group_layout,
+            shaders::TONEMAP_SHADER,
+            FRAME_FORMAT,
+        );
+        let tonemap = Tonemap::default();
+        let exposure = 1.0;
+        let tonemap_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("tonemap_buffer"),
+            contents: bytemuck::bytes_of(&TonemapUniforms::new(tonemap, exposure)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let hdr_texture =
+            Self::create_target_texture(device, "hdr_texture", HDR_FORMAT, width, height);
+        let tonemap_bind_group = Self::create_tonemap_bind_group(
+            device,
+            &tonemap_bind_group_layout,
+            &hdr_texture,
+            &tonemap_buffer,
+        );
+        let frame_texture =
+            Self::create_target_texture(device, "frame_texture", FRAME_FORMAT, width, height);
+        let blit_bind_group =
+            Self::create_blit_bind_group(device, &blit_bind_group_layout, &frame_texture);
+
+        // FXAA pipeline: antialiases the tonemapped scene into the frame
+        let fxaa_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("fxaa_bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+        let fxaa_pipeline = Self::create_fullscreen_pipeline(
+            device,
+            "fxaa",
+            &fxaa_bind_group_layout,
+            shaders::FXAA_SHADER,
+            FRAME_FORMAT,
+        );
+        let fxaa_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("fxaa_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let ldr_texture =
+            Self::create_target_texture(device, "ldr_texture", FRAME_FORMAT, width, height);
+        let fxaa_bind_group = Self::create_fxaa_bind_group(
+            device,
+            &fxaa_bind_group_layout,
+            &ldr_texture,
+            &fxaa_sampler,
+        );
 
         Self {
             cube_pipeline,
+            depth_prepass_pipeline,
+            depth_prepass: false,
+            mesh_pipeline_layout: pipeline_layout,
+            material_pipelines: BTreeMap::new(),
             grid_pipeline,
+            sprite_pipeline,
+            tonemap_pipeline,
+            tonemap_bind_group_layout,
+            tonemap_bind_group,
+            tonemap_buffer,
+            tonemap,
+            exposure,
+            blit_pipeline,
+            fxaa_pipeline,
+            fxaa_bind_group_layout,
+            fxaa_sampler,
+            fxaa_bind_group,
+            antialiasing: Antialiasing::default(),
+            blit_bind_group_layout,
+            blit_bind_group,
             uniform_buffer,
             uniform_bind_group,
+            uniform_stride,
             cube_vertex_buffer,
             cube_index_buffer,
             cube_index_count,
             grid_vertex_buffer,
             grid_vertex_count,
             instance_buffer,
+            sprite_instance_buffer,
+            debug_vertex_buffer,
             max_instances,
             depth_texture,
+            hdr_texture,
+            frame_texture,
+            ldr_texture,
+            viewport: Vec2::new(width as f32, height as f32),
             surface_format,
+            visible_layers: Layer::ALL,
+            textures: TextureCache::new(),
+            texture_bind_group_layout,
+            sampler,
+            white_bind_group,
+            ao_bind_group_layout,
+            white_ao_bind_group,
+            ssao,
+            ssao_settings: SsaoSettings::default(),
+            texture_bind_groups: BTreeMap::new(),
+            materials: BTreeMap::new(),
+            meshes: MeshCache::new(),
+            shader_modules: BTreeMap::new(),
+            profiler: GpuProfiler::new(device, queue),
+            lod_chains: BTreeMap::new(),
+            lod_settings: LodSettings::default(),
+            lod_history: Mutex::new(LodHistory::default()),
+            debug_draw: DebugDraw::new(),
+            collider_layers: Layer::NONE,
+            gpu_culler: None,
         }
     }
 
     pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
         self.depth_texture = Self::create_depth_texture(device, width, height);
+        self.ssao.resize(
+            device,
+            &self.depth_texture,
+            &self.ao_bind_group_layout,
+            width,
+            height,
+        );
+        self.hdr_texture =
+            Self::create_target_texture(device, "hdr_texture", HDR_FORMAT, width, height);
+        self.tonemap_bind_group = Self::create_tonemap_bind_group(
+            device,
+            &self.tonemap_bind_group_layout,
+            &self.hdr_texture,
+            &self.tonemap_buffer,
+        );
+        self.frame_texture =
+            Self::create_target_texture(device, "frame_texture", FRAME_FORMAT, width, height);
+        self.blit_bind_group =
+            Self::create_blit_bind_group(device, &self.blit_bind_group_layout, &self.frame_texture);
+        self.ldr_texture =
+            Self::create_target_texture(device, "ldr_texture", FRAME_FORMAT, width, height);
+        self.fxaa_bind_group = Self::create_fxaa_bind_group(
+            device,
+            &self.fxaa_bind_group_layout,
+            &self.ldr_texture,
+            &self.fxaa_sampler,
+        );
+        self.viewport = Vec2::new(width as f32, height as f32);
     }
 
     pub fn surface_format(&self) -> wgpu::TextureFormat {
         self.surface_format
     }
 
-    /// Render one frame: grid floor + entity cubes.
+    /// Operator mapping the HDR scene into display range. Defaults to
+    /// `Tonemap::Aces`.
+    pub fn set_tonemap(&mut self, tonemap: Tonemap) {
+        self.tonemap = tonemap;
+    }
+
+    pub fn tonemap(&self) -> Tonemap {
+        self.tonemap
+    }
+
+    /// Linear scale applied to scene color before tonemapping. Defaults to
+    /// 1; negative values render black.
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.exposure = exposure;
+    }
+
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    /// Post-process antialiasing of the tonemapped frame. Defaults to
+    /// `Antialiasing::Off`.
+    pub fn set_antialiasing(&mut self, antialiasing: Antialiasing) {
+        self.antialiasing = antialiasing;
+    }
+
+    pub fn antialiasing(&self) -> Antialiasing {
+        self.antialiasing
+    }
+
+    /// Only entities on these layers are drawn. Defaults to `Layer::ALL`.
+    pub fn set_visible_layers(&mut self, layers: Layer) {
+        self.visible_layers = layers;
+    }
+
+    pub fn visible_layers(&self) -> Layer {
+        self.visible_layers
+    }
+
+    /// Layers drawn for `camera`: the visible layers it also shows.
+    fn drawn_layers(&self, camera: &FlyCamera) -> Layer {
+        self.visible_layers.intersection(camera.layers)
+    }
+
+    /// Set how LOD levels are picked for meshes with a LOD chain.
+    pub fn set_lod_settings(&mut self, settings: LodSettings) {
+        self.lod_settings = settings;
+    }
+
+    pub fn lod_settings(&self) -> &LodSettings {
+        &self.lod_settings
+    }
+
+    /// Lines drawn over the scene in every frame until cleared.
+    pub fn debug_draw(&mut self) -> &mut DebugDraw {
+        &mut self.debug_draw
+    }
+
+    /// Draw wireframes of the colliders of entities on `layers`;
+    /// `Layer::NONE` (the default) turns them off.
+    pub fn set_collider_layers(&mut self, layers: Layer) {
+        self.collider_layers = layers;
+    }
+
+    pub fn collider_layers(&self) -> Layer {
+        self.collider_layers
+    }
+
+    /// Cull entity instances in a compute pass and draw them with indirect
+    /// draws instead of culling on the CPU. Worth it for worlds of around
+    /// 100k instances and up, where CPU culling dominates the frame; it
+    /// also raises the per-frame instance limit. Culling results stay on the
+    /// GPU, so `RenderStats` then count instances submitted rather than
+    /// drawn, and report none culled.
+    pub fn set_gpu_culling(&mut self, device: &wgpu::Device, enabled: bool) {
+        self.gpu_culler = match (enabled, self.gpu_culler.take()) {
+            (false, _) => None,
+            (true, Some(culler)) => Some(culler),
+            (true, None) => Some(GpuCuller::new(
+                device,
+                GPU_CULL_CAPACITY,
+                std::mem::size_of::<InstanceData>() as u64,
+            )),
+        };
+    }
+
+    pub fn gpu_culling(&self) -> bool {
+        self.gpu_culler.is_some()
+    }
+
+    /// Lay down scene depth in a depth-only pass before the lit pass, so the
+    /// lit pass shades each pixel about once instead of once per overlapping
+    /// surface. Pays off in dense scenes with expensive lighting; compare
+    /// `GpuPassTimes::depth_prepass` plus `scene` with it on and off.
+    /// Entities drawn with custom material shaders are left out of the
+    /// pre-pass. Off by default.
+    pub fn set_depth_prepass(&mut self, enabled: bool) {
+        self.depth_prepass = enabled;
+    }
+
+    pub fn depth_prepass(&self) -> bool {
+        self.depth_prepass
+    }
+
+    /// Screen-space ambient occlusion darkening ambient light where nearby
+    /// geometry hides it. Runs the depth pre-pass while enabled, whatever
+    /// `set_depth_prepass` says. Off by default.
+    pub fn set_ssao(&mut self, settings: SsaoSettings) {
+        self.ssao_settings = settings;
+    }
+
+    pub fn ssao(&self) -> &SsaoSettings {
+        &self.ssao_settings
+    }
+
+    /// The ambient occlusion bound in the lit pass: the SSAO result while
+    /// SSAO is on, white otherwise.
+    fn ao_bind_group(&self) -> &wgpu::BindGroup {
+        if self.ssao_settings.enabled {
+            self.ssao.output_bind_group()
+        } else {
+            &self.white_ao_bind_group
+        }
+    }
+
+    /// Upload a texture asset for use under `handle`, and as the base color
+    /// texture of materials referencing the asset `AssetId(handle.0)`.
+    /// Returns false if the device cannot sample its format.
+    pub fn upload_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        handle: TextureHandle,
+        texture: &Texture,
+    ) -> bool {
+        if !self.textures.upload(device, queue, handle, texture) {
+            self.texture_bind_groups.remove(&handle);
+            return false;
+        }
+        let Some(uploaded) = self.textures.get(handle) else {
+            return false;
+        };
+        let bind_group = Self::create_texture_bind_group(
+            device,
+            &self.texture_bind_group_layout,
+            &self.sampler,
+            &uploaded.view,
+        );
+        self.texture_bind_groups.insert(handle, bind_group);
+        true
+    }
+
+    pub fn texture(&self, handle: TextureHandle) -> Option<&GpuTexture> {
+        self.textures.get(handle)
+    }
+
+    /// Upload a mesh asset's geometry so entities whose `Renderable` mesh is
+    /// `id` draw with it instead of the unit cube. Returns false if the mesh
+    /// has no geometry to upload.
+    pub fn upload_mesh(&mut self, device: &wgpu::Device, id: AssetId, mesh: &Mesh) -> bool {
+        self.meshes.upload(device, id, mesh)
+    }
+
+    pub fn mesh(&self, id: AssetId) -> Option<&GpuMesh> {
+        self.meshes.get(id)
+    }
+
+    /// Use `material` for entities whose `Renderable` material is `id`: its
+    /// base color tints them, sampling its base color texture once that is
+    /// uploaded.
+    pub fn load_material(&mut self, id: AssetId, material: &Material) {
+        self.materials.insert(id, material.clone());
+    }
+
+    pub fn material(&self, id: AssetId) -> Option<&Material> {
+        self.materials.get(&id)
+    }
+
+    /// Create a shader module from a shader asset and build the pipeline
+    /// of materials referencing it. The asset was validated on import, so
+    /// creating the module does not fail.
+    ///
+    /// Material shaders follow the contract of `WORLD_SHADER`: `vs_main`
+    /// takes the mesh vertex (locations 0-2) and instance (model matrix
+    /// columns at 3-6, color at 7), and `fs_main` writes one HDR color.
+    /// Group 0 holds the view-projection and lights uniforms, group 1 the
+    /// base color texture and sampler. A shader without both entry points
+    /// gets no pipeline, and its materials draw with the standard shader.
+    pub fn load_shader(&mut self, device: &wgpu::Device, id: AssetId, shader: &Shader) {
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(shader.name.as_str()),
+            source: wgpu::ShaderSource::Wgsl(shader.source.as_str().into()),
+        });
+        if shaders::has_material_entry_points(&shader.source) {
+            let label = format!("{}_pipeline", shader.name);
+            let pipeline = Self::create_mesh_pipeline(
+                device,
+                &label,
+                &self.mesh_pipeline_layout,
+                &module,
+                false,
+            );
+            self.material_pipelines.insert(id, pipeline);
+        } else {
+            tracing::warn!(
+                shader = %shader.name,
+                "shader lacks vs_main/fs_main; materials using it draw with the standard shader"
+            );
+            self.material_pipelines.remove(&id);
+        }
+        self.shader_modules.insert(id, module);
+    }
+
+    pub fn shader_module(&self, id: AssetId) -> Option<&wgpu::ShaderModule> {
+        self.shader_modules.get(&id)
+    }
+
+    /// Apply drained `AssetStore` events: upload registered meshes and
+    /// textures, load registered materials and LOD chains, create modules for
+    /// registered shaders and drop the GPU resources of removed assets.
+    pub fn sync_assets(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        store: &AssetStore,
+        events: &[AssetEvent],
+    ) {
+        for event in events {
+            match *event {
+                AssetEvent::Added {
+                    id,
+                    kind: AssetKind::Shader,
+                }
+                | AssetEvent::Replaced {
+                    id,
+                    kind: AssetKind::Shader,
+                } => {
+                    if let Some(shader) = store.get_shader(id) {
+                        self.load_shader(device, id, shader);
+                    }
+                }
+                AssetEvent::Added {
+                    id,
+                    kind: AssetKind::Mesh,
+                }
+                | AssetEvent::Replaced {
+                    id,
+                    kind: AssetKind::Mesh,
+                } => {
+                    // Meshes opened lazily from a pack are read from it here.
+                    match store.mesh_with_geometry(id) {
+                        Ok(mesh) => {
+                            self.upload_mesh(device, id, &mesh);
+                        }
+                        Err(AssetError::NotFound(_)) => {}
+                        Err(e) => {
+                            tracing::warn!(
+                                ?id,
+                                error = %e,
+                                "mesh geometry unavailable; entities using it draw as unit cubes"
+                            );
+                        }
+                    }
+                }
+                AssetEvent::Added {
+                    id,
+                    kind: AssetKind::Texture,
+                }
+                | AssetEvent::Replaced {
+                    id,
+                    kind: AssetKind::Texture,
+                } => {
+                    if let Some(texture) = store.get_texture(id) {
+                        self.upload_texture(device, queue, TextureHandle(id.0), texture);
+                    }
+                }
+                AssetEvent::Added {
+                    id,
+                    kind: AssetKind::Material,
+                }
+                | AssetEvent::Replaced {
+                    id,
+                    kind: AssetKind::Material,
+                } => {
+                    if let Some(material) = store.get_material(id) {
+                        self.load_material(id, material);
+                    }
+                }
+                AssetEvent::Added {
+                    id,
+                    kind: AssetKind::MeshLodChain,
+                }
+                | AssetEvent::Replaced {
+                    id,
+                    kind: AssetKind::MeshLodChain,
+                } => {
+                    if let Some(chain) = store.get_lod_chain(id) {
+                        if let Some(&source) = chain.levels.first() {
+                            self.lod_chains.insert(source, (id, chain.levels.clone()));
+                        }
+                    }
+                }
+                AssetEvent::Removed { id, .. } => {
+                    self.lod_chains.retain(|_, (chain, _)| *chain != id);
+                    self.shader_modules.remove(&id);
+                    self.material_pipelines.remove(&id);
+                    self.meshes.remove(id);
+                    self.textures.remove(TextureHandle(id.0));
+                    self.texture_bind_groups.remove(&TextureHandle(id.0));
+                    self.materials.remove(&id);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Read back the last frame drawn by `render`, without anything drawn
+    /// over the surface afterwards (such as UI). Blocks until the GPU has
+    /// finished the copy.
+    pub fn capture_frame(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<RgbaImage, wgpu::BufferAsyncError> {
+        capture::read_rgba8(device, queue, &self.frame_texture)
+    }
+
+    /// The entity drawn under `screen_pos` (pixels from the top-left of the
+    /// render target), found by casting a ray from `camera` against the
+    /// oriented bounding box of each drawn entity's mesh. Hidden entities and
+    /// those on layers not drawn are skipped; the nearest hit wins.
+    pub fn pick(
+        &self,
+        screen_pos: Vec2,
+        camera: &FlyCamera,
+        world: &World,
+        components: &ComponentStore,
+    ) -> Option<EntityId> {
+        let (origin, dir) = camera.screen_ray(screen_pos, self.viewport);
+        let renderables = components.renderables();
+        let layers = self.drawn_layers(camera);
+        picking::pick_entity(origin, dir, world, components, layers, |id| {
+            renderables
+                .get(&id)
+                .and_then(|r| self.meshes.get(AssetId(r.mesh.0)))
+                .map_or(Aabb::UNIT_CUBE, |mesh| mesh.aabb)
+        })
+    }
+
+    /// World-space box around `id`'s uploaded mesh, or a unit cube if it has
+    /// none; `None` if the entity does not exist.
+    pub fn entity_bounds(
+        &self,
+        id: EntityId,
+        world: &World,
+        components: &ComponentStore,
+    ) -> Option<Aabb> {
+        let t = &world.get(id)?.transform;
+        let aabb = components
+            .renderables()
+            .get(&id)
+            .and_then(|r| self.meshes.get(AssetId(r.mesh.0)))
+            .map_or(Aabb::UNIT_CUBE, |mesh| mesh.aabb);
+        let model = Mat4::from_scale_rotation_translation(t.scale, t.rotation, t.position);
+        let (min, max) = world_aabb(model, &aabb);
+        Some(Aabb {
+            min: min.to_array(),
+            max: max.to_array(),
+        })
+    }
+
+    /// Bounds and index count of a draw of `mesh` (the unit cube if it has
+    /// no uploaded geometry) in viewport `slot` starting at `first_instance`.
+    fn cull_draw(&self, mesh: Option<AssetId>, slot: u32, first_instance: u32) -> CullDraw {
+        let (aabb, index_count) = match mesh.and_then(|id| self.meshes.get(id)) {
+            Some(mesh) => (mesh.aabb, mesh.index_count),
+            None => (Aabb::UNIT_CUBE, self.cube_index_count),
+        };
+        CullDraw {
+            center: aabb.center(),
+            radius: Vec3::from(aabb.half_extents()).length(),
+            viewport: slot,
+            first_instance,
+            index_count,
+            _pad: 0,
+        }
+    }
+
+    /// Bind the vertex and index buffers of `mesh`, or the unit cube if it
+    /// has no uploaded geometry, and return its index count.
+    fn set_mesh_buffers(&self, pass: &mut wgpu::RenderPass<'_>, mesh: Option<AssetId>) -> u32 {
+        match mesh.and_then(|id| self.meshes.get(id)) {
+            Some(mesh) => {
+                pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                mesh.index_count
+            }
+            None => {
+                pass.set_vertex_buffer(0, self.cube_vertex_buffer.slice(..));
+                pass.set_index_buffer(self.cube_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                self.cube_index_count
+            }
+        }
+    }
+
+    /// Record the depth pre-pass: the depth of every viewport's entities
+    /// drawn with the standard shader. Returns the number of draw calls.
+    fn record_depth_prepass(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        passes: &[ViewportPass],
+        draws: &[(Batch, Range<u32>)],
+        timestamp_writes: Option<wgpu::RenderPassTimestampWrites<'_>>,
+    ) -> usize {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("depth_prepass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes,
+            ..Default::default()
+        });
+        pass.set_pipeline(&self.depth_prepass_pipeline);
+        pass.set_bind_group(1, &self.white_bind_group, &[]);
+        pass.set_bind_group(2, &self.white_ao_bind_group, &[]);
+        if self.gpu_culler.is_none() {
+            pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        }
+        let mut draw_calls = 0;
+        for viewport in passes {
+            let rect = viewport.rect;
+            pass.set_viewport(
+                rect.x as f32,
+                rect.y as f32,
+                rect.width as f32,
+                rect.height as f32,
+                0.0,
+                1.0,
+            );
+            pass.set_scissor_rect(rect.x, rect.y, rect.width, rect.height);
+            pass.set_bind_group(0, &self.uniform_bind_group, &[viewport.offset]);
+            for index in viewport.draws.clone() {
+                let ((shader, mesh, _), range) = draws[index].clone();
+                if shader.is_some() {
+                    continue;
+                }
+                let index_count = self.set_mesh_buffers(&mut pass, mesh);
+                self.draw_instances(&mut pass, index, 0..index_count, range);
+                draw_calls += 1;
+            }
+        }
+        draw_calls
+    }
+
+    /// Draw `instances` of draw `index` with `indices` of the bound mesh.
+    /// With GPU culling the draw's culled instances and count are used
+    /// instead.
+    fn draw_instances(
+        &self,
+        pass: &mut wgpu::RenderPass<'_>,
+        index: usize,
+        indices: Range<u32>,
+        instances: Range<u32>,
+    ) {
+        match &self.gpu_culler {
+            Some(culler) => {
+                let len = instances.end - instances.start;
+                pass.set_vertex_buffer(1, culler.culled_instances(instances.start, len));
+                pass.draw_indexed_indirect(culler.args(), GpuCuller::args_offset(index));
+            }
+            None => pass.draw_indexed(indices, 0, instances),
+        }
+    }
+
+    /// Render one frame: grid floor + entity meshes lit by all Light components.
+    /// Entities without an uploaded mesh draw as unit cubes. Each entity is
+    /// colored by the base color of its `MaterialHandle`'s material as loaded
+    /// by `sync_assets`, or the default material's if it has none loaded;
+    /// the selected entity is tinted yellow.
+    /// Returns stats for the frame.
     pub fn render(
         &self,
         device: &wgpu::Device,
@@ -355,65 +1271,236 @@ impl WgpuRenderer {
         view: &wgpu::TextureView,
         camera: &FlyCamera,
         world: &World,
-        renderables: &BTreeMap<EntityId, Renderable>,
+        components: &ComponentStore,
         selected: Option<EntityId>,
-    ) {
-        let vp = camera.view_projection();
-        queue.write_buffer(
-            &self.uniform_buffer,
-            0,
-            bytemuck::bytes_of(&Uniforms {
-                view_proj: vp.to_cols_array_2d(),
-            }),
-        );
+    ) -> RenderStats {
+        let full = ViewportRect::full(self.viewport.x as u32, self.viewport.y as u32);
+        self.render_viewports(
+            device,
+            queue,
+            view,
+            &[Viewport::new(camera, full)],
+            world,
+            components,
+            selected,
+        )
+    }
 
-        // Build instance data from entities
+    /// Render one frame with each viewport's camera drawn into its rectangle
+    /// of the target, e.g. a quad view or an editor preview camera inset over
+    /// the main view. Later viewports draw over earlier ones where they
+    /// overlap. Rectangles are clipped to the target; at most
+    /// [`MAX_VIEWPORTS`] are drawn. Each viewport is culled against its own
+    /// camera, and the returned stats sum over all of them. A viewport draws
+    /// the entities on the layers in both `visible_layers` and its camera's
+    /// `layers`, and the grid and debug lines only if the camera shows
+    /// `Layer::EDITOR`.
+    pub fn render_viewports(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        view: &wgpu::TextureView,
+        viewports: &[Viewport],
+        world: &World,
+        components: &ComponentStore,
+        selected: Option<EntityId>,
+    ) -> RenderStats {
+        let frame_start = Instant::now();
+        if viewports.len() > MAX_VIEWPORTS {
+            tracing::warn!(
+                count = viewports.len(),
+                max = MAX_VIEWPORTS,
+                "too many viewports, extra viewports skipped"
+            );
+        }
+        let (lights, light_count) = lights::pack_lights(world, components.lights());
+        let (width, height) = (self.viewport.x as u32, self.viewport.y as u32);
+
+        let mut lod_history = self
+            .lod_history
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut passes: Vec<ViewportPass> = Vec::new();
         let mut instances: Vec<InstanceData> = Vec::new();
-        for (id, entity_data) in world.entities() {
-            if instances.len() >= self.max_instances as usize {
-                break;
-            }
-            let t = &entity_data.transform;
-            let model = Mat4::from_scale_rotation_translation(t.scale, t.rotation, t.position);
-            let cols = model.to_cols_array_2d();
+        let mut draws: Vec<(Batch, Range<u32>)> = Vec::new();
+        let mut sprites: Vec<SpriteInstance> = Vec::new();
+        let mut sprite_draws: Vec<(TextureHandle, Range<u32>)> = Vec::new();
+        // With GPU culling: the draw of each instance, each draw's bounds and
+        // each viewport slot's frustum.
+        let mut instance_draws: Vec<u32> = Vec::new();
+        let mut cull_draws: Vec<CullDraw> = Vec::new();
+        let mut frustums: [Option<Frustum>; MAX_VIEWPORTS] = [None; MAX_VIEWPORTS];
+        let capacity = self
+            .gpu_culler
+            .as_ref()
+            .map_or(self.max_instances, GpuCuller::capacity);
+        // With SSAO: the slot and rectangle of each viewport.
+        let mut ao_viewports: Vec<(usize, ViewportRect)> = Vec::new();
+        let mut culled_count = 0;
+        for (slot, viewport) in viewports.iter().take(MAX_VIEWPORTS).enumerate() {
+            let Some(rect) = viewport.rect.clip(width, height) else {
+                continue;
+            };
+            let vp = viewport.view_projection();
+            let offset = slot as u64 * self.uniform_stride;
+            queue.write_buffer(
+                &self.uniform_buffer,
+                offset,
+                bytemuck::bytes_of(&Uniforms {
+                    view_proj: vp.to_cols_array_2d(),
+                    light_count,
+                    _pad: [0; 3],
+                    lights,
+                }),
+            );
 
-            let is_renderable = renderables.contains_key(id);
-            let is_selected = selected == Some(*id);
+            let frustum = Frustum::from_view_projection(vp);
+            let room = capacity as usize - instances.len();
+            lod_history.begin_viewport(slot);
+            let (batches, culled) = self.build_batches(
+                viewport,
+                room,
+                world,
+                components,
+                selected,
+                &mut lod_history,
+            );
+            culled_count += culled;
 
-            let color = if is_selected {
-                [1.0, 0.8, 0.0, 1.0] // Yellow for selected
-            } else if is_renderable {
-                [0.2, 0.6, 1.0, 1.0] // Blue for renderable
-            } else {
-                [0.7, 0.7, 0.7, 1.0] // Gray default
-            };
+            // Lay the batches out contiguously in the instance buffer.
+            let first_draw = draws.len();
+            for (key, batch) in batches {
+                let start = instances.len() as u32;
+                if self.gpu_culler.is_some() {
+                    let (_, mesh, _) = key;
+                    cull_draws.push(self.cull_draw(mesh, slot as u32, start));
+                    instance_draws.extend(std::iter::repeat_n(draws.len() as u32, batch.len()));
+                }
+                instances.extend(batch);
+                draws.push((key, start..instances.len() as u32));
+            }
+            frustums[slot] = Some(frustum);
+            if self.ssao_settings.enabled {
+                let projection = viewport.camera.projection_with_aspect(rect.aspect());
+                let uniforms = SsaoUniforms::new(projection, rect, &self.ssao_settings);
+                self.ssao.write_uniforms(queue, slot, &uniforms);
+                ao_viewports.push((slot, rect));
+            }
+
+            // Sprites stay in back-to-front order; consecutive sprites from
+            // the same atlas share a draw.
+            let room = self.max_instances as usize - sprites.len();
+            let (quads, culled) = sprites::sprite_instances(
+                viewport.camera,
+                &frustum,
+                room,
+                world,
+                components,
+                self.drawn_layers(viewport.camera),
+            );
+            culled_count += culled;
+            let first_sprite_draw = sprite_draws.len();
+            for (texture, quad) in quads {
+                let index = sprites.len() as u32;
+                sprites.push(quad);
+                match sprite_draws[first_sprite_draw..].last_mut() {
+                    Some((last, range)) if *last == texture => range.end = index + 1,
+                    _ => sprite_draws.push((texture, index..index + 1)),
+                }
+            }
 
-            instances.push(InstanceData {
-                model_0: cols[0],
-                model_1: cols[1],
-                model_2: cols[2],
-                model_3: cols[3],
-                color,
+            passes.push(ViewportPass {
+                offset: offset as u32,
+                rect,
+                draws: first_draw..draws.len(),
+                sprite_draws: first_sprite_draw..sprite_draws.len(),
+                editor: viewport.camera.layers.intersects(Layer::EDITOR),
             });
         }
+        lod_history.end_frame();
+        drop(lod_history);
 
-        if !instances.is_empty() {
+        match &self.gpu_culler {
+            Some(culler) => {
+                culler.prepare(queue, &instances, &instance_draws, &cull_draws, &frustums);
+            }
+            None if !instances.is_empty() => {
+                queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
+            }
+            None => {}
+        }
+        if !sprites.is_empty() {
             queue.write_buffer(
-                &self.instance_buffer,
+                &self.sprite_instance_buffer,
                 0,
-                bytemuck::cast_slice(&instances),
+                bytemuck::cast_slice(&sprites),
             );
         }
 
+        // Debug lines, then collider wireframes, up to the buffer's capacity
+        let mut colliders = DebugDraw::new();
+        debug_draw::collider_lines(&mut colliders, world, components, self.collider_layers);
+        let lines: Vec<LineVertex> = self
+            .debug_draw
+            .vertices()
+            .iter()
+            .chain(colliders.vertices())
+            .take(MAX_DEBUG_VERTICES as usize)
+            .copied()
+            .collect();
+        let line_vertex_count = lines.len() as u32;
+        if !lines.is_empty() {
+            queue.write_buffer(&self.debug_vertex_buffer, 0, bytemuck::cast_slice(&lines));
+        }
+
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("render_encoder"),
         });
+        if let Some(culler) = &self.gpu_culler {
+            culler.dispatch(&mut encoder, instances.len() as u32);
+        }
+        let mut draw_calls = 0;
+        let mut triangle_count = 0;
+        // Time the passes unless an earlier frame's timings are still being
+        // read back.
+        let profiler = self
+            .profiler
+            .as_ref()
+            .filter(|profiler| profiler.begin_frame(device));
+        let timestamp_writes = |index| profiler.map(|profiler| profiler.pass_writes(index));
+        let hdr_view = self.hdr_texture.create_view(&Default::default());
+        let frame_view = self.frame_texture.create_view(&Default::default());
+        // With antialiasing the scene is tonemapped into the LDR target first
+        let ldr_view = self.ldr_texture.create_view(&Default::default());
+        let tonemap_view = match self.antialiasing {
+            Antialiasing::Off => &frame_view,
+            Antialiasing::Fxaa => &ldr_view,
+        };
+        queue.write_buffer(
+            &self.tonemap_buffer,
+            0,
+            bytemuck::bytes_of(&TonemapUniforms::new(self.tonemap, self.exposure)),
+        );
 
+        // SSAO reads the pre-pass depth before the lit pass
+        let depth_load = if self.depth_prepass || self.ssao_settings.enabled {
+            draw_calls +=
+                self.record_depth_prepass(&mut encoder, &passes, &draws, timestamp_writes(0));
+            wgpu::LoadOp::Load
+        } else {
+            wgpu::LoadOp::Clear(1.0)
+        };
+        if self.ssao_settings.enabled {
+            let ssao_writes =
+                profiler.map(|profiler| (profiler.begin_writes(1), profiler.end_writes(1)));
+            self.ssao.record(&mut encoder, &ao_viewports, ssao_writes);
+            draw_calls += ao_viewports.len() + 1;
+        }
         {
             let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("main_pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view,
+                    view: &hdr_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -428,42 +1515,529 @@ impl WgpuRenderer {
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                     view: &self.depth_texture,
                     depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
+                        load: depth_load,
                         store: wgpu::StoreOp::Store,
                     }),
                     stencil_ops: None,
                 }),
+                timestamp_writes: timestamp_writes(2),
                 ..Default::default()
             });
 
-            // Draw grid floor
-            pass.set_pipeline(&self.grid_pipeline);
-            pass.set_bind_group(0, &self.uniform_bind_group, &[]);
-            pass.set_vertex_buffer(0, self.grid_vertex_buffer.slice(..));
-            pass.draw(0..self.grid_vertex_count, 0..1);
-
-            // Draw entity cubes
-            if !instances.is_empty() {
-                pass.set_pipeline(&self.cube_pipeline);
-                pass.set_bind_group(0, &self.uniform_bind_group, &[]);
-                pass.set_vertex_buffer(0, self.cube_vertex_buffer.slice(..));
-                pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
-                pass.set_index_buffer(
-                    self.cube_index_buffer.slice(..),
-                    wgpu::IndexFormat::Uint16,
+            for ViewportPass {
+                offset,
+                rect,
+                draws: range,
+                sprite_draws: sprite_range,
+                editor,
+            } in passes
+            {
+                pass.set_viewport(
+                    rect.x as f32,
+                    rect.y as f32,
+                    rect.width as f32,
+                    rect.height as f32,
+                    0.0,
+                    1.0,
                 );
-                pass.draw_indexed(0..self.cube_index_count, 0, 0..instances.len() as u32);
+                pass.set_scissor_rect(rect.x, rect.y, rect.width, rect.height);
+
+                // Draw grid floor
+                if editor {
+                    pass.set_pipeline(&self.grid_pipeline);
+                    pass.set_bind_group(0, &self.uniform_bind_group, &[offset]);
+                    pass.set_vertex_buffer(0, self.grid_vertex_buffer.slice(..));
+                    pass.draw(0..self.grid_vertex_count, 0..1);
+                    draw_calls += 1;
+                }
+
+                // Draw entities, one instanced draw per material shader and
+                // mesh; batches sort by shader, so each pipeline is set once
+                if !range.is_empty() {
+                    pass.set_bind_group(0, &self.uniform_bind_group, &[offset]);
+                    pass.set_bind_group(2, self.ao_bind_group(), &[]);
+                    if self.gpu_culler.is_none() {
+                        pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+                    }
+                }
+                let mut current_shader = None;
+                for index in range {
+                    let ((shader, mesh, texture), range) = draws[index].clone();
+                    if current_shader != Some(shader) {
+                        let pipeline = shader
+                            .and_then(|id| self.material_pipelines.get(&id))
+                            .unwrap_or(&self.cube_pipeline);
+                        pass.set_pipeline(pipeline);
+                        current_shader = Some(shader);
+                    }
+                    let texture_bind_group = texture
+                        .and_then(|handle| self.texture_bind_groups.get(&handle))
+                        .unwrap_or(&self.white_bind_group);
+                    pass.set_bind_group(1, texture_bind_group, &[]);
+                    let index_count = self.set_mesh_buffers(&mut pass, mesh);
+                    triangle_count += index_count as usize / 3 * range.len();
+                    self.draw_instances(&mut pass, index, 0..index_count, range);
+                    draw_calls += 1;
+                }
+
+                // Draw debug lines and collider wireframes
+                if editor && line_vertex_count > 0 {
+                    pass.set_pipeline(&self.grid_pipeline);
+                    pass.set_bind_group(0, &self.uniform_bind_group, &[offset]);
+                    pass.set_vertex_buffer(0, self.debug_vertex_buffer.slice(..));
+                    pass.draw(0..line_vertex_count, 0..1);
+                    draw_calls += 1;
+                }
+
+                // Draw sprites over the opaque scene, one draw per atlas run
+                if !sprite_range.is_empty() {
+                    pass.set_pipeline(&self.sprite_pipeline);
+                    pass.set_bind_group(0, &self.uniform_bind_group, &[offset]);
+                    pass.set_vertex_buffer(0, self.sprite_instance_buffer.slice(..));
+                }
+                for (texture, range) in sprite_draws[sprite_range].iter().cloned() {
+                    let texture_bind_group = self
+                        .texture_bind_groups
+                        .get(&texture)
+                        .unwrap_or(&self.white_bind_group);
+                    pass.set_bind_group(1, texture_bind_group, &[]);
+                    triangle_count += 2 * range.len();
+                    pass.draw(0..4, range);
+                    draw_calls += 1;
+                }
             }
         }
 
+        // Tonemap the HDR scene into the frame
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("tonemap_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: tonemap_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                timestamp_writes: timestamp_writes(3),
+                ..Default::default()
+            });
+            pass.set_pipeline(&self.tonemap_pipeline);
+            pass.set_bind_group(0, &self.tonemap_bind_group, &[]);
+            pass.draw(0..3, 0..1);
+            draw_calls += 1;
+        }
+
+        // Antialias the tonemapped scene into the frame
+        if self.antialiasing == Antialiasing::Fxaa {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("fxaa_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &frame_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                timestamp_writes: timestamp_writes(4),
+                ..Default::default()
+            });
+            pass.set_pipeline(&self.fxaa_pipeline);
+            pass.set_bind_group(0, &self.fxaa_bind_group, &[]);
+            pass.draw(0..3, 0..1);
+            draw_calls += 1;
+        }
+
+        // Copy the frame to the surface
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("blit_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                timestamp_writes: timestamp_writes(5),
+                ..Default::default()
+            });
+            pass.set_pipeline(&self.blit_pipeline);
+            pass.set_bind_group(0, &self.blit_bind_group, &[]);
+            pass.draw(0..3, 0..1);
+            draw_calls += 1;
+        }
+
+        if let Some(profiler) = profiler {
+            profiler.resolve(&mut encoder);
+        }
         queue.submit(std::iter::once(encoder.finish()));
+        if let Some(profiler) = profiler {
+            profiler.end_frame();
+        }
+
+        RenderStats {
+            instance_count: instances.len() + sprites.len(),
+            culled_count,
+            draw_calls,
+            triangle_count,
+            frame_time: frame_start.elapsed(),
+            gpu_times: self.profiler.as_ref().and_then(GpuProfiler::last),
+        }
+    }
+
+    /// Instance data for up to `limit` entities in `viewport`, batched by the
+    /// material shader, uploaded mesh and base color texture they draw with;
+    /// `None` is the standard shader, the unit cube or the white texture.
+    /// Meshes with a LOD chain draw the level `lod_settings` picks for their
+    /// size in `viewport`. Entities with a Sprite are left to the sprite
+    /// pass. Also returns how many entities were culled.
+    fn build_batches(
+        &self,
+        viewport: &Viewport,
+        limit: usize,
+        world: &World,
+        components: &ComponentStore,
+        selected: Option<EntityId>,
+        lod_history: &mut LodHistory,
+    ) -> (BTreeMap<Batch, Vec<InstanceData>>, usize) {
+        let frustum = Frustum::from_view_projection(viewport.view_projection());
+        let layers = self.drawn_layers(viewport.camera);
+        let default_color = Material::default().base_color;
+        let mut batches: BTreeMap<Batch, Vec<InstanceData>> = BTreeMap::new();
+        let mut instance_count = 0;
+        let mut culled_count = 0;
+        for drawn in drawn_entities(world, components, self, layers) {
+            if instance_count >= limit {
+                break;
+            }
+            // The GPU culler tests every instance itself.
+            if self.gpu_culler.is_none() && !drawn.in_frustum(&frustum) {
+                culled_count += 1;
+                continue;
+            }
+            let id = &drawn.entity;
+            let model = drawn.model;
+            let cols = model.to_cols_array_2d();
+
+            // Draw the LOD level matching the mesh's size on screen, keeping
+            // the source mesh while that level is not uploaded.
+            let mut lod_level = None;
+            let mesh = drawn.mesh;
+            let mesh = match mesh.and_then(|mesh| self.lod_chains.get(&mesh)) {
+                Some((_, levels)) => {
+                    let (min, max) = drawn.world_bounds();
+                    let radius = (max - min).length() * 0.5;
+                    let coverage = lod::screen_coverage(viewport.camera, (min + max) * 0.5, radius);
+                    let level = lod_history.select(&self.lod_settings, *id, coverage, levels.len());
+                    lod_level = Some(level);
+                    Some(levels[level])
+                        .filter(|level| self.meshes.get(*level).is_some())
+                        .or(mesh)
+                }
+                None => mesh,
+            };
+
+            let is_selected = selected == Some(*id);
+
+            let material = drawn.material;
+            let base_color = match lod_level {
+                Some(level) if self.lod_settings.debug_tint => {
+                    lod::LOD_TINTS[level.min(lod::LOD_TINTS.len() - 1)]
+                }
+                _ => material.map_or(default_color, |m| m.base_color),
+            };
+            let color = if is_selected {
+                highlight(base_color)
+            } else {
+                base_color
+            };
+            let texture = material
+                .and_then(|m| m.base_color_texture)
+                .map(|texture| TextureHandle(texture.0))
+                .filter(|handle| self.texture_bind_groups.contains_key(handle));
+            let shader = material
+                .and_then(|m| m.shader)
+                .filter(|shader| self.material_pipelines.contains_key(shader));
+
+            batches
+                .entry((shader, mesh, texture))
+                .or_default()
+                .push(InstanceData {
+                    model_0: cols[0],
+                    model_1: cols[1],
+                    model_2: cols[2],
+                    model_3: cols[3],
+                    color,
+                });
+            instance_count += 1;
+        }
+        (batches, culled_count)
+    }
+
+    fn create_texture_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        view: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("texture_bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+
+    fn create_white_texture(device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::TextureView {
+        let size = wgpu::Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("white_texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &[255; 4],
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4),
+                rows_per_image: Some(1),
+            },
+            size,
+        );
+        texture.create_view(&Default::default())
+    }
+
+    /// A pipeline drawing instanced meshes with `module`'s `vs_main` and
+    /// `fs_main` entry points into the HDR target; with `depth_only`, into
+    /// the depth buffer alone. Depth tests pass on equal
+    /// depth so the lit pass can redraw what a depth pre-pass laid down.
+    fn create_mesh_pipeline(
+        device: &wgpu::Device,
+        label: &str,
+        layout: &wgpu::PipelineLayout,
+        module: &wgpu::ShaderModule,
+        depth_only: bool,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[
+                    wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<Vertex>() as u64,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![
+                            0 => Float32x3,
+                            1 => Float32x3,
+                            2 => Float32x2,
+                        ],
+                    },
+                    wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<InstanceData>() as u64,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &wgpu::vertex_attr_array![
+                            3 => Float32x4,
+                            4 => Float32x4,
+                            5 => Float32x4,
+                            6 => Float32x4,
+                            7 => Float32x4,
+                        ],
+                    },
+                ],
+            },
+            fragment: (!depth_only).then_some(wgpu::FragmentState {
+                module,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: Default::default(),
+                bias: Default::default(),
+            }),
+            multisample: Default::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// A fullscreen-triangle pipeline drawing `shader`'s `vs_<name>` and
+    /// `fs_<name>` entry points into a `format` target.
+    fn create_fullscreen_pipeline(
+        device: &wgpu::Device,
+        name: &str,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        shader: &str,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&format!("{name}_shader")),
+            source: wgpu::ShaderSource::Wgsl(shader.into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(&format!("{name}_pipeline_layout")),
+            bind_group_layouts: &[bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let (vs_entry, fs_entry) = (format!("vs_{name}"), format!("fs_{name}"));
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&format!("{name}_pipeline")),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: Some(&vs_entry),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: Some(&fs_entry),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            multiview: None,
+            cache: None,
+        })
     }
 
-    fn create_depth_texture(
+    /// A render target the size of the surface that later passes read.
+    fn create_target_texture(
         device: &wgpu::Device,
+        label: &str,
+        format: wgpu::TextureFormat,
         width: u32,
         height: u32,
-    ) -> wgpu::TextureView {
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    fn create_tonemap_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        hdr_texture: &wgpu::Texture,
+        settings: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        let view = hdr_texture.create_view(&Default::default());
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("tonemap_bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: settings.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    fn create_fxaa_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        ldr_texture: &wgpu::Texture,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        let view = ldr_texture.create_view(&Default::default());
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("fxaa_bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+
+    fn create_blit_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        frame_texture: &wgpu::Texture,
+    ) -> wgpu::BindGroup {
+        let view = frame_texture.create_view(&Default::default());
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("blit_bind_group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            }],
+        })
+    }
+
+    fn create_depth_texture(device: &wgpu::Device, width: u32, height: u32) -> wgpu::TextureView {
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("depth_texture"),
             size: wgpu::Extent3d {
@@ -475,9 +2049,20 @@ impl WgpuRenderer {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Depth32Float,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
             view_formats: &[],
         });
         texture.create_view(&Default::default())
     }
 }
+
+/// Entities draw with the meshes and materials uploaded so far.
+impl DrawAssets for WgpuRenderer {
+    fn mesh_bounds(&self, id: AssetId) -> Option<Aabb> {
+        self.meshes.get(id).map(|mesh| mesh.aabb)
+    }
+
+    fn material(&self, id: AssetId) -> Option<&Material> {
+        self.materials.get(&id)
+    }
+}
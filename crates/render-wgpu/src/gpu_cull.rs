@@ -0,0 +1,309 @@
+//! Opt-in GPU frustum culling with indirect draws.
+//!
+//! With GPU culling on, the renderer uploads the frame's instances unculled,
+//! each tagged with the draw it belongs to. A compute pass tests every
+//! instance's bounding sphere against its viewport's frustum, appends the
+//! survivors to a compacted instance buffer and counts them into the draw's
+//! indirect arguments, so culling results never travel back to the CPU.
+
+use crate::shaders;
+use crate::viewport::MAX_VIEWPORTS;
+use bytemuck::{Pod, Zeroable};
+use worldspace_common::Frustum;
+
+/// Instances culled per compute workgroup (matches `CULL_SHADER`).
+const WORKGROUP_SIZE: u32 = 64;
+
+/// One instanced draw as the cull shader sees it.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub(crate) struct CullDraw {
+    /// Mesh-space bounding sphere of the draw's mesh.
+    pub center: [f32; 3],
+    pub radius: f32,
+    /// Viewport slot whose frustum the draw is culled against.
+    pub viewport: u32,
+    /// Start of the draw's instances, in both the source and compacted
+    /// instance buffers.
+    pub first_instance: u32,
+    pub index_count: u32,
+    pub _pad: u32,
+}
+
+/// `wgpu`'s indexed indirect draw arguments.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub(crate) struct DrawIndexedArgs {
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub base_vertex: i32,
+    pub first_instance: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+struct CullUniforms {
+    planes: [[[f32; 4]; 6]; MAX_VIEWPORTS],
+    instance_count: u32,
+    _pad: [u32; 3],
+}
+
+/// Indirect arguments of `draws` before culling: no instances yet. The
+/// compacted instances of a draw are bound at its own offset, so
+/// `first_instance` stays 0 and needs no `INDIRECT_FIRST_INSTANCE`.
+pub(crate) fn initial_args(draws: &[CullDraw]) -> Vec<DrawIndexedArgs> {
+    draws
+        .iter()
+        .map(|draw| DrawIndexedArgs {
+            index_count: draw.index_count,
+            instance_count: 0,
+            first_index: 0,
+            base_vertex: 0,
+            first_instance: 0,
+        })
+        .collect()
+}
+
+/// Buffers and pipeline of the cull pass, sized for `capacity` instances.
+pub(crate) struct GpuCuller {
+    pipeline: wgpu::ComputePipeline,
+    bind_group: wgpu::BindGroup,
+    uniforms: wgpu::Buffer,
+    instances: wgpu::Buffer,
+    instance_draws: wgpu::Buffer,
+    draws: wgpu::Buffer,
+    culled: wgpu::Buffer,
+    args: wgpu::Buffer,
+    instance_size: u64,
+    capacity: u32,
+}
+
+impl GpuCuller {
+    /// A culler for up to `capacity` instances of `instance_size` bytes.
+    pub fn new(device: &wgpu::Device, capacity: u32, instance_size: u64) -> Self {
+        let storage = |label: &str, size: u64, usage: wgpu::BufferUsages| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage: wgpu::BufferUsages::STORAGE | usage,
+                mapped_at_creation: false,
+            })
+        };
+        let count = capacity as u64;
+        let instances = storage(
+            "cull_instances",
+            count * instance_size,
+            wgpu::BufferUsages::COPY_DST,
+        );
+        let instance_draws = storage(
+            "cull_instance_draws",
+            count * 4,
+            wgpu::BufferUsages::COPY_DST,
+        );
+        let draws = storage(
+            "cull_draws",
+            count * std::mem::size_of::<CullDraw>() as u64,
+            wgpu::BufferUsages::COPY_DST,
+        );
+        let culled = storage(
+            "culled_instances",
+            count * instance_size,
+            wgpu::BufferUsages::VERTEX,
+        );
+        let args = storage(
+            "cull_draw_args",
+            count * std::mem::size_of::<DrawIndexedArgs>() as u64,
+            wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::COPY_DST,
+        );
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("cull_uniforms"),
+            size: std::mem::size_of::<CullUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let entry = |binding: u32, ty: wgpu::BufferBindingType| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let read = wgpu::BufferBindingType::Storage { read_only: true };
+        let write = wgpu::BufferBindingType::Storage { read_only: false };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("cull_bind_group_layout"),
+            entries: &[
+                entry(0, wgpu::BufferBindingType::Uniform),
+                entry(1, read),
+                entry(2, read),
+                entry(3, read),
+                entry(4, write),
+                entry(5, write),
+            ],
+        });
+        let buffers = [
+            &uniforms,
+            &instances,
+            &instance_draws,
+            &draws,
+            &culled,
+            &args,
+        ];
+        let entries: Vec<wgpu::BindGroupEntry> = buffers
+            .iter()
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("cull_bind_group"),
+            layout: &layout,
+            entries: &entries,
+        });
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("cull_shader"),
+            source: wgpu::ShaderSource::Wgsl(shaders::CULL_SHADER.into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("cull_pipeline_layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("cull_pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: Some("cs_cull"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group,
+            uniforms,
+            instances,
+            instance_draws,
+            draws,
+            culled,
+            args,
+            instance_size,
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Upload a frame's unculled `instances`, the draw each belongs to, the
+    /// draws and the frustum of each viewport slot, and reset the draws'
+    /// instance counts.
+    pub fn prepare<T: Pod>(
+        &self,
+        queue: &wgpu::Queue,
+        instances: &[T],
+        instance_draws: &[u32],
+        draws: &[CullDraw],
+        frustums: &[Option<Frustum>; MAX_VIEWPORTS],
+    ) {
+        let mut uniforms = CullUniforms {
+            planes: [[[0.0; 4]; 6]; MAX_VIEWPORTS],
+            instance_count: instances.len() as u32,
+            _pad: [0; 3],
+        };
+        for (planes, frustum) in uniforms.planes.iter_mut().zip(frustums) {
+            if let Some(frustum) = frustum {
+                *planes = frustum.planes().map(|plane| plane.to_array());
+            }
+        }
+        queue.write_buffer(&self.uniforms, 0, bytemuck::bytes_of(&uniforms));
+        if instances.is_empty() {
+            return;
+        }
+        queue.write_buffer(&self.instances, 0, bytemuck::cast_slice(instances));
+        queue.write_buffer(
+            &self.instance_draws,
+            0,
+            bytemuck::cast_slice(instance_draws),
+        );
+        queue.write_buffer(&self.draws, 0, bytemuck::cast_slice(draws));
+        queue.write_buffer(&self.args, 0, bytemuck::cast_slice(&initial_args(draws)));
+    }
+
+    /// Record the cull pass over `instance_count` instances.
+    pub fn dispatch(&self, encoder: &mut wgpu::CommandEncoder, instance_count: u32) {
+        if instance_count == 0 {
+            return;
+        }
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("cull_pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.dispatch_workgroups(instance_count.div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+
+    /// The compacted instances of the draw starting at `first_instance` with
+    /// room for `len`, to bind as its instance buffer.
+    pub fn culled_instances(&self, first_instance: u32, len: u32) -> wgpu::BufferSlice<'_> {
+        let start = first_instance as u64 * self.instance_size;
+        self.culled
+            .slice(start..start + len as u64 * self.instance_size)
+    }
+
+    pub fn args(&self) -> &wgpu::Buffer {
+        &self.args
+    }
+
+    /// Offset of draw `index`'s arguments in `args`.
+    pub fn args_offset(index: usize) -> u64 {
+        (index * std::mem::size_of::<DrawIndexedArgs>()) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layouts_match_shader_and_wgpu() {
+        // `Draw` in CULL_SHADER and wgpu's DrawIndexedIndirectArgs.
+        assert_eq!(std::mem::size_of::<CullDraw>(), 32);
+        assert_eq!(std::mem::size_of::<DrawIndexedArgs>(), 20);
+        assert_eq!(GpuCuller::args_offset(3), 60);
+        assert_eq!(std::mem::size_of::<CullUniforms>() % 16, 0);
+    }
+
+    #[test]
+    fn args_start_empty() {
+        let draw = CullDraw {
+            center: [0.0; 3],
+            radius: 1.0,
+            viewport: 1,
+            first_instance: 40,
+            index_count: 36,
+            _pad: 0,
+        };
+        let args = initial_args(&[draw]);
+        assert_eq!(
+            args,
+            vec![DrawIndexedArgs {
+                index_count: 36,
+                instance_count: 0,
+                first_index: 0,
+                base_vertex: 0,
+                first_instance: 0,
+            }]
+        );
+    }
+}
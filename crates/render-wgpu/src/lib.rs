@@ -1,16 +1,57 @@
 //! wgpu render backend for the world engine.
 //!
-//! Renders a grid floor and instanced cubes for entities with Renderable components.
-//! Camera uses a fly-camera model with WASD + mouse look.
+//! Renders a grid floor and entities instanced by the mesh of their Renderable
+//! component (unit cubes when the mesh has no uploaded geometry), colored by
+//! their material's base color and texture and lit by the scene's Light
+//! components, with ambient light optionally darkened by screen-space ambient
+//! occlusion (see `SsaoSettings`). Materials referencing a Shader asset draw
+//! with a pipeline built from it (see `WORLD_SHADER` for the contract it
+//! follows). Entities with a Sprite component draw as camera-facing quads from
+//! a texture atlas instead. Meshes with a LOD chain draw the level matching
+//! their size on screen (see `LodSettings`). Entities can optionally be culled
+//! on the GPU and drawn with indirect draws (see
+//! `WgpuRenderer::set_gpu_culling`). Debug lines (see `DebugDraw`) and, per
+//! layer, collider wireframes draw over the scene. The scene is lit in HDR,
+//! tonemapped (see `Tonemap`) and optionally antialiased (see `Antialiasing`)
+//! before it is presented. Several cameras can be drawn into viewport
+//! rectangles of one frame (see `Viewport`). When the device supports timestamp
+//! queries, the GPU time of each pass is reported in `RenderStats::gpu_times`.
+//! `PresentMode` and `FrameLimiter` choose how the application presents and
+//! paces frames. Camera uses a fly-camera model with WASD + mouse look.
 //!
 //! # Invariants
 //! - Renderer never mutates world state.
 //! - Camera motion is NOT part of the deterministic kernel.
 //! - Kernel tick is separate from render frame rate.
 
+mod antialias;
 mod camera;
+mod capture;
+mod debug_draw;
 mod gpu;
+mod gpu_cull;
+mod lights;
+mod lod;
+mod meshes;
+mod picking;
+mod present;
+mod profiler;
 mod shaders;
+mod sprites;
+mod ssao;
+mod textures;
+mod tonemap;
+mod viewport;
 
-pub use camera::FlyCamera;
+pub use antialias::Antialiasing;
+pub use camera::{CameraFocus, FlyCamera, Projection, ViewPreset};
+pub use debug_draw::DebugDraw;
 pub use gpu::WgpuRenderer;
+pub use lod::LodSettings;
+pub use meshes::{GpuMesh, MeshCache};
+pub use present::{FrameLimiter, PresentMode};
+pub use shaders::WORLD_SHADER;
+pub use ssao::SsaoSettings;
+pub use textures::{GpuTexture, TextureCache, wgpu_format};
+pub use tonemap::Tonemap;
+pub use viewport::{MAX_VIEWPORTS, Viewport, ViewportRect};
@@ -0,0 +1,151 @@
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
+use std::collections::BTreeMap;
+use worldspace_common::EntityId;
+use worldspace_ecs::{Light, LightKind};
+use worldspace_kernel::World;
+
+/// Maximum number of lights uploaded per frame. Must match `MAX_LIGHTS` in the shader.
+pub(crate) const MAX_LIGHTS: usize = 16;
+
+const KIND_DIRECTIONAL: u32 = 0;
+const KIND_POINT: u32 = 1;
+const KIND_SPOT: u32 = 2;
+
+/// GPU layout of a single light (48 bytes, matches `Light` in the shader).
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub(crate) struct GpuLight {
+    pub position: [f32; 3],
+    pub range: f32,
+    pub direction: [f32; 3],
+    pub kind: u32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+impl GpuLight {
+    /// Directional light used when the scene has no Light components.
+    pub fn fallback() -> Self {
+        Self {
+            position: [0.0; 3],
+            range: 0.0,
+            direction: (-Vec3::new(0.3, 1.0, 0.5).normalize()).to_array(),
+            kind: KIND_DIRECTIONAL,
+            color: [1.0; 3],
+            intensity: 0.7,
+        }
+    }
+}
+
+/// Pack the lights of live entities into a fixed-size array for upload.
+///
+/// Lights are taken in EntityId order and truncated to `MAX_LIGHTS`. Only the
+/// first directional light is used, so the rest of the budget goes to point
+/// and spot lights. Lights attached to entities missing from the world are
+/// skipped. If no lights remain, a single fallback directional light is used.
+pub(crate) fn pack_lights(
+    world: &World,
+    lights: &BTreeMap<EntityId, Light>,
+) -> ([GpuLight; MAX_LIGHTS], u32) {
+    let mut packed = [GpuLight::zeroed(); MAX_LIGHTS];
+    let mut count = 0;
+    let mut has_directional = false;
+
+    for (id, light) in lights {
+        if count == MAX_LIGHTS {
+            break;
+        }
+        let Some(entity) = world.get(*id) else {
+            continue;
+        };
+        let t = &entity.transform;
+        let kind = match light.kind {
+            LightKind::Directional if has_directional => continue,
+            LightKind::Directional => {
+                has_directional = true;
+                KIND_DIRECTIONAL
+            }
+            LightKind::Point => KIND_POINT,
+            LightKind::Spot => KIND_SPOT,
+        };
+        packed[count] = GpuLight {
+            position: t.position.to_array(),
+            range: light.range,
+            direction: (t.rotation * Vec3::NEG_Z).normalize().to_array(),
+            kind,
+            color: light.color,
+            intensity: light.intensity,
+        };
+        count += 1;
+    }
+
+    if count == 0 {
+        packed[0] = GpuLight::fallback();
+        count = 1;
+    }
+    (packed, count as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+
+    #[test]
+    fn empty_scene_uses_fallback() {
+        let world = World::new();
+        let (packed, count) = pack_lights(&world, &BTreeMap::new());
+        assert_eq!(count, 1);
+        assert_eq!(packed[0], GpuLight::fallback());
+    }
+
+    #[test]
+    fn lights_follow_entity_transform() {
+        let mut world = World::new();
+        let id = world.spawn(Transform {
+            position: Vec3::new(1.0, 2.0, 3.0),
+            ..Transform::default()
+        });
+        let mut lights = BTreeMap::new();
+        lights.insert(
+            id,
+            Light {
+                kind: LightKind::Point,
+                ..Light::default()
+            },
+        );
+        lights.insert(EntityId::new(), Light::default());
+
+        let (packed, count) = pack_lights(&world, &lights);
+        assert_eq!(count, 1);
+        assert_eq!(packed[0].kind, KIND_POINT);
+        assert_eq!(packed[0].position, [1.0, 2.0, 3.0]);
+        assert_eq!(packed[0].direction, [0.0, 0.0, -1.0]);
+    }
+
+    #[test]
+    fn only_first_directional_light_is_used() {
+        let mut world = World::new();
+        let mut lights = BTreeMap::new();
+        for kind in [
+            LightKind::Directional,
+            LightKind::Point,
+            LightKind::Directional,
+        ] {
+            let id = world.spawn(Transform::default());
+            lights.insert(
+                id,
+                Light {
+                    kind,
+                    ..Light::default()
+                },
+            );
+        }
+
+        let (packed, count) = pack_lights(&world, &lights);
+        assert_eq!(count, 2);
+        let kinds: Vec<u32> = packed[..2].iter().map(|l| l.kind).collect();
+        assert_eq!(kinds, vec![KIND_DIRECTIONAL, KIND_POINT]);
+    }
+}
@@ -0,0 +1,191 @@
+//! Per-instance mesh LOD selection.
+//!
+//! Entities whose mesh has a `MeshLodChain` draw the level matching how much
+//! of the viewport their bounds cover. Each level switches at a screen
+//! coverage threshold, widened by a hysteresis band around it so an entity
+//! sitting at a threshold does not flicker between levels from frame to
+//! frame.
+
+use crate::camera::{FlyCamera, Projection};
+use glam::Vec3;
+use std::collections::BTreeMap;
+use worldspace_common::EntityId;
+
+/// Tints drawn per LOD level when `LodSettings::debug_tint` is on; levels
+/// past the last reuse its tint.
+pub(crate) const LOD_TINTS: [[f32; 4]; 4] = [
+    [0.2, 0.9, 0.2, 1.0],
+    [0.9, 0.9, 0.2, 1.0],
+    [0.9, 0.5, 0.1, 1.0],
+    [0.9, 0.1, 0.1, 1.0],
+];
+
+/// How the renderer picks mesh LOD levels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LodSettings {
+    /// Screen coverage below which each coarser level is used: level `i + 1`
+    /// is drawn once the mesh's bounding sphere spans less than
+    /// `thresholds[i]` of the viewport height. Should be decreasing; levels
+    /// past the last threshold are never drawn.
+    pub thresholds: Vec<f32>,
+    /// Fraction of a threshold coverage must move past it before an entity
+    /// switches level, e.g. 0.1 for 10%.
+    pub hysteresis: f32,
+    /// Tint instances by their active LOD level instead of their material.
+    pub debug_tint: bool,
+}
+
+impl Default for LodSettings {
+    fn default() -> Self {
+        Self {
+            thresholds: vec![0.3, 0.15, 0.05],
+            hysteresis: 0.1,
+            debug_tint: false,
+        }
+    }
+}
+
+impl LodSettings {
+    /// Level of a chain of `levels` meshes for a mesh covering `coverage` of
+    /// the viewport height, given the level it drew with last frame.
+    pub(crate) fn select(&self, coverage: f32, levels: usize, previous: Option<usize>) -> usize {
+        let last = levels.min(self.thresholds.len() + 1).saturating_sub(1);
+        let (mut level, band) = match previous {
+            Some(previous) => (previous.min(last), self.hysteresis.max(0.0)),
+            None => (0, 0.0),
+        };
+        while level < last && coverage < self.thresholds[level] * (1.0 - band) {
+            level += 1;
+        }
+        while level > 0 && coverage > self.thresholds[level - 1] * (1.0 + band) {
+            level -= 1;
+        }
+        level
+    }
+}
+
+/// The level each entity drew with in each viewport, kept from one frame to
+/// the next for hysteresis.
+#[derive(Debug, Default)]
+pub(crate) struct LodHistory {
+    previous: BTreeMap<(usize, EntityId), usize>,
+    current: BTreeMap<(usize, EntityId), usize>,
+    viewport: usize,
+}
+
+impl LodHistory {
+    /// Select levels for viewport `index` from here on.
+    pub fn begin_viewport(&mut self, index: usize) {
+        self.viewport = index;
+    }
+
+    /// Level of a chain of `levels` meshes for `entity` covering `coverage`
+    /// of the current viewport's height, remembered for the next frame.
+    pub fn select(
+        &mut self,
+        settings: &LodSettings,
+        entity: EntityId,
+        coverage: f32,
+        levels: usize,
+    ) -> usize {
+        let key = (self.viewport, entity);
+        let level = settings.select(coverage, levels, self.previous.get(&key).copied());
+        self.current.insert(key, level);
+        level
+    }
+
+    /// Finish the frame; entities not drawn in it start afresh next time.
+    pub fn end_frame(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+    }
+}
+
+/// Fraction of the viewport height spanned by a sphere of `radius` at
+/// `center` seen through `camera`.
+pub(crate) fn screen_coverage(camera: &FlyCamera, center: Vec3, radius: f32) -> f32 {
+    match camera.projection {
+        Projection::Perspective => {
+            let distance = (center - camera.position).length().max(camera.near);
+            radius / (distance * (camera.fov * 0.5).tan())
+        }
+        Projection::Orthographic { height } => 2.0 * radius / height.max(f32::EPSILON),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coarser_levels_as_coverage_shrinks() {
+        let settings = LodSettings {
+            hysteresis: 0.0,
+            ..LodSettings::default()
+        };
+        assert_eq!(settings.select(0.5, 4, None), 0);
+        assert_eq!(settings.select(0.2, 4, None), 1);
+        assert_eq!(settings.select(0.1, 4, None), 2);
+        assert_eq!(settings.select(0.01, 4, None), 3);
+        // Short chains clamp to their last level.
+        assert_eq!(settings.select(0.01, 2, None), 1);
+        assert_eq!(settings.select(0.01, 1, None), 0);
+    }
+
+    #[test]
+    fn hysteresis_holds_level_near_threshold() {
+        let settings = LodSettings::default();
+        // Just under the 0.3 threshold, inside the band: keep level 0.
+        assert_eq!(settings.select(0.28, 4, Some(0)), 0);
+        assert_eq!(settings.select(0.26, 4, Some(0)), 1);
+        // Just over it coming back: keep level 1 until clear of the band.
+        assert_eq!(settings.select(0.32, 4, Some(1)), 1);
+        assert_eq!(settings.select(0.34, 4, Some(1)), 0);
+        // Large jumps still cross several levels at once.
+        assert_eq!(settings.select(0.01, 4, Some(0)), 3);
+    }
+
+    #[test]
+    fn history_is_per_viewport_and_frame() {
+        let settings = LodSettings::default();
+        let entity = EntityId::new();
+        let mut history = LodHistory::default();
+        assert_eq!(history.select(&settings, entity, 0.5, 4), 0);
+        history.begin_viewport(1);
+        assert_eq!(history.select(&settings, entity, 0.01, 4), 3);
+        history.end_frame();
+
+        // Inside the band each viewport keeps its own previous level.
+        history.begin_viewport(0);
+        assert_eq!(history.select(&settings, entity, 0.28, 4), 0);
+        history.begin_viewport(1);
+        assert_eq!(history.select(&settings, entity, 0.28, 4), 1);
+        history.end_frame();
+        history.end_frame();
+
+        // Not drawn last frame: no hysteresis.
+        history.begin_viewport(0);
+        assert_eq!(history.select(&settings, entity, 0.28, 4), 1);
+    }
+
+    #[test]
+    fn coverage_falls_with_distance() {
+        let camera = FlyCamera {
+            position: Vec3::ZERO,
+            fov: 90f32.to_radians(),
+            ..FlyCamera::default()
+        };
+        let near = screen_coverage(&camera, Vec3::new(0.0, 0.0, -10.0), 1.0);
+        let far = screen_coverage(&camera, Vec3::new(0.0, 0.0, -20.0), 1.0);
+        assert!((near - 0.1).abs() < 1e-5);
+        assert!((far - 0.05).abs() < 1e-5);
+
+        let ortho = FlyCamera {
+            projection: Projection::Orthographic { height: 10.0 },
+            ..camera
+        };
+        assert_eq!(
+            screen_coverage(&ortho, Vec3::new(0.0, 0.0, -20.0), 1.0),
+            0.2
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! GPU mesh cache keyed by mesh `AssetId`.
+//!
+//! Meshes upload as position + normal + UV vertices with 32-bit indices.
+//! Meshes imported without normals get smooth normals averaged from their
+//! triangles, and meshes without UVs map every vertex to the texture's
+//! corner. Placeholder meshes without geometry are not uploaded, so
+//! entities referencing them fall back to the unit cube.
+
+use crate::gpu::Vertex;
+use glam::Vec3;
+use std::collections::BTreeMap;
+use wgpu::util::DeviceExt;
+use worldspace_assets::{Aabb, AssetId, Mesh};
+
+/// Uploaded vertex and index buffers of a mesh.
+pub struct GpuMesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub index_count: u32,
+    /// Mesh-space bounds of the uploaded vertices, for picking.
+    pub aabb: Aabb,
+}
+
+/// GPU meshes uploaded from mesh assets.
+#[derive(Default)]
+pub struct MeshCache {
+    meshes: BTreeMap<AssetId, GpuMesh>,
+}
+
+impl MeshCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Upload `mesh` under `id`, replacing any previous upload. Returns
+    /// false, dropping any previous upload, if the mesh has no geometry or
+    /// its indices are malformed.
+    pub fn upload(&mut self, device: &wgpu::Device, id: AssetId, mesh: &Mesh) -> bool {
+        let Some((vertices, indices)) = mesh_geometry(mesh) else {
+            self.meshes.remove(&id);
+            return false;
+        };
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(mesh.name.as_str()),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(mesh.name.as_str()),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        self.meshes.insert(
+            id,
+            GpuMesh {
+                vertex_buffer,
+                index_buffer,
+                index_count: indices.len() as u32,
+                aabb: Aabb::from_points(&mesh.positions).unwrap_or(Aabb::UNIT_CUBE),
+            },
+        );
+        true
+    }
+
+    pub fn get(&self, id: AssetId) -> Option<&GpuMesh> {
+        self.meshes.get(&id)
+    }
+
+    pub fn remove(&mut self, id: AssetId) -> bool {
+        self.meshes.remove(&id).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.meshes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.meshes.is_empty()
+    }
+}
+
+/// Vertices and triangle list indices to upload for `mesh`, or `None` if it
+/// has no geometry or its indices are out of range or not whole triangles.
+/// Meshes without indices are drawn as an unindexed triangle list.
+pub(crate) fn mesh_geometry(mesh: &Mesh) -> Option<(Vec<Vertex>, Vec<u32>)> {
+    let positions = &mesh.positions;
+    if positions.is_empty() {
+        return None;
+    }
+    let indices: Vec<u32> = if mesh.indices.is_empty() {
+        (0..positions.len() as u32).collect()
+    } else {
+        mesh.indices.clone()
+    };
+    if indices.len() % 3 != 0 {
+        tracing::warn!(mesh = %mesh.name, indices = indices.len(), "mesh indices are not whole triangles");
+        return None;
+    }
+    if indices.iter().any(|&i| i as usize >= positions.len()) {
+        tracing::warn!(mesh = %mesh.name, "mesh index out of range");
+        return None;
+    }
+
+    let normals = if mesh.normals.len() == positions.len() {
+        mesh.normals.clone()
+    } else {
+        smooth_normals(positions, &indices)
+    };
+    let vertices = positions
+        .iter()
+        .zip(normals)
+        .enumerate()
+        .map(|(i, (position, normal))| Vertex {
+            position: *position,
+            normal,
+            uv: mesh.uvs.get(i).copied().unwrap_or_default(),
+        })
+        .collect();
+    Some((vertices, indices))
+}
+
+/// Per-vertex normals averaged from the area-weighted normals of the
+/// triangles sharing each vertex.
+fn smooth_normals(positions: &[[f32; 3]], indices: &[u32]) -> Vec<[f32; 3]> {
+    let mut sums = vec![Vec3::ZERO; positions.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| i as usize);
+        let (pa, pb, pc) = (
+            Vec3::from(positions[a]),
+            Vec3::from(positions[b]),
+            Vec3::from(positions[c]),
+        );
+        let face = (pb - pa).cross(pc - pa);
+        for i in [a, b, c] {
+            sums[i] += face;
+        }
+    }
+    sums.into_iter()
+        .map(|n| {
+            let n = n.normalize_or_zero();
+            let n = if n == Vec3::ZERO { Vec3::Y } else { n };
+            n.to_array()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle() -> Mesh {
+        Mesh {
+            name: "tri".into(),
+            vertex_count: 3,
+            index_count: 3,
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            indices: vec![0, 1, 2],
+            ..Mesh::default()
+        }
+    }
+
+    #[test]
+    fn geometry_generates_missing_normals() {
+        let (vertices, indices) = mesh_geometry(&triangle()).unwrap();
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert_eq!(vertices.len(), 3);
+        for vertex in vertices {
+            assert_eq!(vertex.normal, [0.0, 0.0, 1.0]);
+            assert_eq!(vertex.uv, [0.0, 0.0]);
+        }
+    }
+
+    #[test]
+    fn geometry_keeps_uvs() {
+        let mut mesh = triangle();
+        mesh.uvs = vec![[0.0, 1.0], [1.0, 1.0], [0.0, 0.0]];
+        let (vertices, _) = mesh_geometry(&mesh).unwrap();
+        let uvs: Vec<[f32; 2]> = vertices.iter().map(|v| v.uv).collect();
+        assert_eq!(uvs, mesh.uvs);
+    }
+
+    #[test]
+    fn placeholder_and_malformed_meshes_are_skipped() {
+        let placeholder = Mesh {
+            vertex_count: 24,
+            index_count: 36,
+            ..Mesh::default()
+        };
+        assert!(mesh_geometry(&placeholder).is_none());
+
+        let mut out_of_range = triangle();
+        out_of_range.indices = vec![0, 1, 3];
+        assert!(mesh_geometry(&out_of_range).is_none());
+
+        let mut partial = triangle();
+        partial.indices = vec![0, 1];
+        assert!(mesh_geometry(&partial).is_none());
+    }
+}
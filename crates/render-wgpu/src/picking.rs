@@ -0,0 +1,125 @@
+//! CPU ray picking against entity bounding boxes.
+//!
+//! Each drawn entity is tested as the mesh-space box of what it draws with
+//! (its uploaded mesh's bounds, or the unit cube) under its transform, so a
+//! pick hits the oriented box around the geometry rather than the geometry
+//! itself.
+
+use glam::{Mat4, Vec3};
+use worldspace_assets::Aabb;
+use worldspace_common::EntityId;
+use worldspace_ecs::{ComponentStore, Layer, Tag};
+use worldspace_kernel::World;
+
+/// Distance along the ray from `origin` in units of `dir` at which it enters
+/// the box from `min` to `max`, or `None` if it misses. A ray starting inside
+/// the box hits at 0.
+pub(crate) fn ray_aabb(origin: Vec3, dir: Vec3, min: Vec3, max: Vec3) -> Option<f32> {
+    let (mut near, mut far) = (0.0f32, f32::INFINITY);
+    for axis in 0..3 {
+        let (o, d) = (origin.to_array()[axis], dir.to_array()[axis]);
+        let (lo, hi) = (min.to_array()[axis], max.to_array()[axis]);
+        if d.abs() < f32::EPSILON {
+            if o < lo || o > hi {
+                return None;
+            }
+            continue;
+        }
+        let (t0, t1) = ((lo - o) / d, (hi - o) / d);
+        near = near.max(t0.min(t1));
+        far = far.min(t0.max(t1));
+        if near > far {
+            return None;
+        }
+    }
+    Some(near)
+}
+
+/// The nearest entity drawn on `layers` whose box, `bounds(id)` under its
+/// transform, the ray from `origin` along `dir` hits. Ties go to the lower
+/// EntityId.
+pub(crate) fn pick_entity(
+    origin: Vec3,
+    dir: Vec3,
+    world: &World,
+    components: &ComponentStore,
+    layers: Layer,
+    bounds: impl Fn(EntityId) -> Aabb,
+) -> Option<EntityId> {
+    let mut best: Option<(f32, EntityId)> = None;
+    for (id, entity) in world.entities() {
+        if components.has_tag(*id, Tag::Hidden)
+            || !components.is_visible(*id)
+            || !components.in_layers(*id, layers)
+        {
+            continue;
+        }
+        let t = &entity.transform;
+        let model = Mat4::from_scale_rotation_translation(t.scale, t.rotation, t.position);
+        // Unnormalized, so distances in mesh space equal world distances.
+        let inverse = model.inverse();
+        let local_origin = inverse.transform_point3(origin);
+        let local_dir = inverse.transform_vector3(dir);
+        let aabb = bounds(*id);
+        let Some(distance) = ray_aabb(
+            local_origin,
+            local_dir,
+            Vec3::from(aabb.min),
+            Vec3::from(aabb.max),
+        ) else {
+            continue;
+        };
+        if best.is_none_or(|(nearest, _)| distance < nearest) {
+            best = Some((distance, *id));
+        }
+    }
+    best.map(|(_, id)| id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+
+    fn at(world: &mut World, position: Vec3, scale: f32) -> EntityId {
+        world.spawn(Transform {
+            position,
+            scale: Vec3::splat(scale),
+            ..Transform::default()
+        })
+    }
+
+    #[test]
+    fn ray_enters_box_at_near_face() {
+        let (min, max) = (Vec3::splat(-1.0), Vec3::splat(1.0));
+        let hit = ray_aabb(Vec3::new(0.0, 0.0, 5.0), Vec3::NEG_Z, min, max);
+        assert_eq!(hit, Some(4.0));
+        assert_eq!(ray_aabb(Vec3::ZERO, Vec3::X, min, max), Some(0.0));
+        assert_eq!(
+            ray_aabb(Vec3::new(0.0, 3.0, 5.0), Vec3::NEG_Z, min, max),
+            None
+        );
+        assert_eq!(ray_aabb(Vec3::new(0.0, 0.0, 5.0), Vec3::Z, min, max), None);
+    }
+
+    #[test]
+    fn picks_nearest_visible_entity() {
+        let mut world = World::new();
+        let far = at(&mut world, Vec3::new(0.0, 0.0, -10.0), 1.0);
+        let near = at(&mut world, Vec3::new(0.0, 0.0, -5.0), 1.0);
+        // Scaled up, so the ray only reaches it through its scale.
+        let wide = at(&mut world, Vec3::new(4.0, 0.0, -2.0), 8.0);
+        let mut components = ComponentStore::new();
+        let unit = |_| Aabb::UNIT_CUBE;
+
+        let pick = |components: &ComponentStore, origin: Vec3| {
+            pick_entity(origin, Vec3::NEG_Z, &world, components, Layer::ALL, unit)
+        };
+        assert_eq!(pick(&components, Vec3::ZERO), Some(wide));
+        assert_eq!(pick(&components, Vec3::new(-0.2, 0.0, 0.0)), Some(near));
+        assert_eq!(pick(&components, Vec3::new(0.0, 5.0, 0.0)), None);
+
+        components.add_tag(near, Tag::Hidden);
+        assert_eq!(pick(&components, Vec3::new(-0.2, 0.0, 0.0)), Some(far));
+    }
+}
@@ -0,0 +1,142 @@
+//! Present mode selection and frame rate limiting.
+//!
+//! The surface is owned by the application, so the renderer only resolves
+//! which wgpu present mode to configure it with and paces frames when an FPS
+//! cap is set.
+
+use std::time::{Duration, Instant};
+
+/// How frames are handed to the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PresentMode {
+    /// Wait for vertical blank; no tearing, latency up to a refresh.
+    #[default]
+    Vsync,
+    /// Replace the queued frame with the newest one; no tearing, lower
+    /// latency, renders as fast as it can.
+    Mailbox,
+    /// Present right away; lowest latency, may tear.
+    Immediate,
+}
+
+impl PresentMode {
+    pub const ALL: [PresentMode; 3] = [Self::Vsync, Self::Mailbox, Self::Immediate];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Vsync => "Vsync",
+            Self::Mailbox => "Mailbox",
+            Self::Immediate => "Immediate",
+        }
+    }
+
+    /// The wgpu mode to configure a surface with, given the modes it
+    /// supports. Modes the surface lacks fall back to vsync, which every
+    /// surface supports.
+    pub fn resolve(self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+        let mode = match self {
+            Self::Vsync => return wgpu::PresentMode::AutoVsync,
+            Self::Mailbox => wgpu::PresentMode::Mailbox,
+            Self::Immediate => wgpu::PresentMode::Immediate,
+        };
+        if supported.contains(&mode) {
+            mode
+        } else {
+            wgpu::PresentMode::AutoVsync
+        }
+    }
+}
+
+/// Paces frames to an optional FPS cap.
+#[derive(Debug, Clone, Default)]
+pub struct FrameLimiter {
+    fps_cap: Option<u32>,
+    next_frame: Option<Instant>,
+}
+
+impl FrameLimiter {
+    /// A limiter capping frames at `fps_cap` per second; `None` or zero is
+    /// uncapped.
+    pub fn new(fps_cap: Option<u32>) -> Self {
+        Self {
+            fps_cap: fps_cap.filter(|&fps| fps > 0),
+            next_frame: None,
+        }
+    }
+
+    pub fn fps_cap(&self) -> Option<u32> {
+        self.fps_cap
+    }
+
+    pub fn set_fps_cap(&mut self, fps_cap: Option<u32>) {
+        *self = Self::new(fps_cap);
+    }
+
+    /// When the next frame should start; `None` if uncapped or no frame has
+    /// started yet.
+    pub fn next_frame(&self) -> Option<Instant> {
+        self.next_frame
+    }
+
+    /// True if a frame may start at `now`.
+    pub fn frame_due(&self, now: Instant) -> bool {
+        self.next_frame.is_none_or(|next| now >= next)
+    }
+
+    /// Record a frame starting at `now`. Frames keep a steady cadence unless
+    /// one starts more than a whole interval late, which resets it.
+    pub fn begin_frame(&mut self, now: Instant) {
+        self.next_frame = self.fps_cap.map(|fps| {
+            let interval = Duration::from_secs_f64(1.0 / fps as f64);
+            match self.next_frame {
+                Some(next) if now < next + interval => next + interval,
+                _ => now + interval,
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_modes_fall_back_to_vsync() {
+        let supported = [wgpu::PresentMode::Fifo, wgpu::PresentMode::Mailbox];
+        assert_eq!(
+            PresentMode::Mailbox.resolve(&supported),
+            wgpu::PresentMode::Mailbox
+        );
+        assert_eq!(
+            PresentMode::Immediate.resolve(&supported),
+            wgpu::PresentMode::AutoVsync
+        );
+        assert_eq!(
+            PresentMode::Vsync.resolve(&[]),
+            wgpu::PresentMode::AutoVsync
+        );
+    }
+
+    #[test]
+    fn limiter_paces_frames() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut limiter = FrameLimiter::new(Some(50));
+        assert!(limiter.frame_due(start));
+        limiter.begin_frame(start);
+        assert!(!limiter.frame_due(start + ms(10)));
+        assert!(limiter.frame_due(start + ms(20)));
+
+        // A slightly late frame keeps the cadence.
+        limiter.begin_frame(start + ms(25));
+        assert_eq!(limiter.next_frame(), Some(start + ms(40)));
+        // A frame more than an interval late starts a new one.
+        limiter.begin_frame(start + ms(100));
+        assert_eq!(limiter.next_frame(), Some(start + ms(120)));
+
+        limiter.set_fps_cap(Some(0));
+        assert_eq!(limiter.fps_cap(), None);
+        limiter.begin_frame(start);
+        assert!(limiter.frame_due(start));
+    }
+}
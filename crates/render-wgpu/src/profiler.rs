@@ -0,0 +1,186 @@
+//! GPU pass timing with timestamp queries.
+//!
+//! Each measured pass writes a timestamp at its start and end. The queries
+//! are resolved into a buffer that is mapped without blocking, so results
+//! are collected on a later frame; frames rendered while a readback is still
+//! in flight are not measured.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+use worldspace_render::GpuPassTimes;
+
+/// Passes measured per frame, in `GpuPassTimes` field order.
+const PASS_COUNT: u32 = 6;
+const QUERY_COUNT: u32 = PASS_COUNT * 2;
+const BUFFER_SIZE: u64 = QUERY_COUNT as u64 * wgpu::QUERY_SIZE as u64;
+
+const IDLE: u8 = 0;
+const MAPPING: u8 = 1;
+const MAPPED: u8 = 2;
+const FAILED: u8 = 3;
+
+/// Timestamp queries for the passes of one frame and their readback.
+pub(crate) struct GpuProfiler {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+    /// Readback buffer state, set from the map callback.
+    status: Arc<AtomicU8>,
+    last: Mutex<Option<GpuPassTimes>>,
+}
+
+impl GpuProfiler {
+    /// A profiler, or `None` if `device` lacks timestamp queries.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("pass_timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: QUERY_COUNT,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("timestamp_resolve_buffer"),
+            size: BUFFER_SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("timestamp_readback_buffer"),
+            size: BUFFER_SIZE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            period: queue.get_timestamp_period(),
+            status: Arc::new(AtomicU8::new(IDLE)),
+            last: Mutex::new(None),
+        })
+    }
+
+    /// Collect any finished readback and report whether this frame can be
+    /// measured, i.e. no readback is still in flight.
+    pub fn begin_frame(&self, device: &wgpu::Device) -> bool {
+        let _ = device.poll(wgpu::Maintain::Poll);
+        match self.status.load(Ordering::Acquire) {
+            MAPPING => return false,
+            MAPPED => {
+                let timestamps: Vec<u64> = self
+                    .readback_buffer
+                    .slice(..)
+                    .get_mapped_range()
+                    .chunks_exact(8)
+                    .map(|bytes| u64::from_le_bytes(bytes.try_into().expect("8-byte chunk")))
+                    .collect();
+                self.readback_buffer.unmap();
+                *self.last.lock().unwrap_or_else(|e| e.into_inner()) =
+                    Some(pass_times(&timestamps, self.period));
+            }
+            _ => {}
+        }
+        self.status.store(IDLE, Ordering::Release);
+        true
+    }
+
+    /// Timestamp writes bracketing pass `index`.
+    pub fn pass_writes(&self, index: u32) -> wgpu::RenderPassTimestampWrites<'_> {
+        wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(index * 2),
+            end_of_pass_write_index: Some(index * 2 + 1),
+        }
+    }
+
+    /// Timestamp write at the start of pass `index`, for a pass that runs
+    /// as several render passes; see `end_writes`.
+    pub fn begin_writes(&self, index: u32) -> wgpu::RenderPassTimestampWrites<'_> {
+        wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(index * 2),
+            end_of_pass_write_index: None,
+        }
+    }
+
+    /// Timestamp write at the end of pass `index`.
+    pub fn end_writes(&self, index: u32) -> wgpu::RenderPassTimestampWrites<'_> {
+        wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: None,
+            end_of_pass_write_index: Some(index * 2 + 1),
+        }
+    }
+
+    /// Record copying this frame's timestamps into the readback buffer.
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.resolve_query_set(&self.query_set, 0..QUERY_COUNT, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            BUFFER_SIZE,
+        );
+    }
+
+    /// Start mapping the readback buffer once the frame's commands are
+    /// submitted.
+    pub fn end_frame(&self) {
+        self.status.store(MAPPING, Ordering::Release);
+        let status = Arc::clone(&self.status);
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let state = if result.is_ok() { MAPPED } else { FAILED };
+                status.store(state, Ordering::Release);
+            });
+    }
+
+    /// Pass times of the most recently collected frame.
+    pub fn last(&self) -> Option<GpuPassTimes> {
+        *self.last.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Pass times from begin/end timestamp pairs ticking every `period`
+/// nanoseconds. Timestamps that run backwards count as zero, as do those of
+/// passes skipped this frame, which resolve to zero.
+fn pass_times(timestamps: &[u64], period: f32) -> GpuPassTimes {
+    let pass = |index: usize| {
+        let ticks = timestamps[index * 2 + 1].saturating_sub(timestamps[index * 2]);
+        Duration::from_nanos((ticks as f64 * period as f64) as u64)
+    };
+    GpuPassTimes {
+        depth_prepass: pass(0),
+        ssao: pass(1),
+        scene: pass(2),
+        post: pass(3),
+        antialias: pass(4),
+        present: pass(5),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pass_times_scale_ticks_by_period() {
+        let timestamps = [0, 0, 50, 100, 100, 1100, 1100, 1300, 1300, 1400, 2000, 1900];
+        let times = pass_times(&timestamps, 2.0);
+        assert_eq!(times.depth_prepass, Duration::ZERO);
+        assert_eq!(times.ssao, Duration::from_nanos(100));
+        assert_eq!(times.scene, Duration::from_nanos(2000));
+        assert_eq!(times.post, Duration::from_nanos(400));
+        assert_eq!(times.antialias, Duration::from_nanos(200));
+        assert_eq!(times.present, Duration::ZERO);
+        assert_eq!(times.total(), Duration::from_nanos(2700));
+    }
+}
@@ -1,29 +1,65 @@
-/// WGSL shader for rendering the grid floor and instanced cubes.
+/// WGSL shader for instanced entity meshes, tinted by the instance color and
+/// the material's base color texture (group 1), with ambient light scaled by
+/// the half-resolution ambient occlusion target (group 2, 1 when SSAO is
+/// off).
+///
+/// Custom material shaders keep its inputs, bind groups and `vs_main` /
+/// `fs_main` entry points, so it doubles as their template.
 pub const WORLD_SHADER: &str = r#"
+const MAX_LIGHTS: u32 = 16u;
+const LIGHT_DIRECTIONAL: u32 = 0u;
+const LIGHT_SPOT: u32 = 2u;
+// Spot cone: full intensity inside ~20 degrees, fading out by ~35 degrees.
+const SPOT_INNER_COS: f32 = 0.94;
+const SPOT_OUTER_COS: f32 = 0.82;
+const AMBIENT: f32 = 0.3;
+
+struct Light {
+    position: vec3<f32>,
+    range: f32,
+    direction: vec3<f32>,
+    kind: u32,
+    color: vec3<f32>,
+    intensity: f32,
+};
+
 struct Uniforms {
     view_proj: mat4x4<f32>,
+    light_count: u32,
+    lights: array<Light, MAX_LIGHTS>,
 };
 
 @group(0) @binding(0)
 var<uniform> uniforms: Uniforms;
 
+@group(1) @binding(0)
+var base_color_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var base_color_sampler: sampler;
+
+@group(2) @binding(0)
+var ambient_occlusion: texture_2d<f32>;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
 };
 
 struct InstanceInput {
-    @location(2) model_0: vec4<f32>,
-    @location(3) model_1: vec4<f32>,
-    @location(4) model_2: vec4<f32>,
-    @location(5) model_3: vec4<f32>,
-    @location(6) color: vec4<f32>,
+    @location(3) model_0: vec4<f32>,
+    @location(4) model_1: vec4<f32>,
+    @location(5) model_2: vec4<f32>,
+    @location(6) model_3: vec4<f32>,
+    @location(7) color: vec4<f32>,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) world_normal: vec3<f32>,
     @location(1) color: vec4<f32>,
+    @location(2) world_position: vec3<f32>,
+    @location(3) uv: vec2<f32>,
 };
 
 @vertex
@@ -41,16 +77,43 @@ fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
     out.clip_position = uniforms.view_proj * world_pos;
     out.world_normal = normalize(world_normal);
     out.color = instance.color;
+    out.world_position = world_pos.xyz;
+    out.uv = vertex.uv;
     return out;
 }
 
+fn shade(light: Light, position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    var to_light = -light.direction;
+    var attenuation = 1.0;
+    if (light.kind != LIGHT_DIRECTIONAL) {
+        let offset = light.position - position;
+        let dist = length(offset);
+        to_light = offset / max(dist, 0.0001);
+        // Inverse-square falloff, windowed to reach zero at the light's range.
+        let ratio = dist / max(light.range, 0.0001);
+        let window = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+        attenuation = window * window / (dist * dist + 1.0);
+        if (light.kind == LIGHT_SPOT) {
+            let cos_angle = dot(-to_light, light.direction);
+            attenuation = attenuation * smoothstep(SPOT_OUTER_COS, SPOT_INNER_COS, cos_angle);
+        }
+    }
+    let diffuse = max(dot(normal, to_light), 0.0);
+    return light.color * light.intensity * diffuse * attenuation;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let light_dir = normalize(vec3<f32>(0.3, 1.0, 0.5));
-    let ambient = 0.3;
-    let diffuse = max(dot(in.world_normal, light_dir), 0.0);
-    let lighting = ambient + diffuse * 0.7;
-    return vec4<f32>(in.color.rgb * lighting, in.color.a);
+    let normal = normalize(in.world_normal);
+    let ao_size = vec2<i32>(textureDimensions(ambient_occlusion));
+    let ao_pixel = min(vec2<i32>(in.clip_position.xy) / 2, ao_size - 1);
+    let ao = textureLoad(ambient_occlusion, ao_pixel, 0).r;
+    var lighting = vec3<f32>(AMBIENT * ao);
+    for (var i = 0u; i < min(uniforms.light_count, MAX_LIGHTS); i = i + 1u) {
+        lighting = lighting + shade(uniforms.lights[i], in.world_position, normal);
+    }
+    let base_color = in.color * textureSample(base_color_texture, base_color_sampler, in.uv);
+    return vec4<f32>(base_color.rgb * lighting, base_color.a);
 }
 "#;
 
@@ -86,3 +149,494 @@ fn fs_grid(in: GridOutput) -> @location(0) vec4<f32> {
     return in.color;
 }
 "#;
+
+/// WGSL shader for sprites: each instance expands into a camera-facing quad
+/// (a four-vertex triangle strip) textured with its atlas region, unlit.
+pub const SPRITE_SHADER: &str = r#"
+struct Uniforms {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@group(1) @binding(0)
+var atlas: texture_2d<f32>;
+@group(1) @binding(1)
+var atlas_sampler: sampler;
+
+struct SpriteInstance {
+    @location(0) center: vec3<f32>,
+    @location(1) right: vec3<f32>,
+    @location(2) up: vec3<f32>,
+    @location(3) uv_rect: vec4<f32>,
+    @location(4) color: vec4<f32>,
+};
+
+struct SpriteOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@vertex
+fn vs_sprite(@builtin(vertex_index) index: u32, sprite: SpriteInstance) -> SpriteOutput {
+    // Strip corners in [0, 1]: bottom-left, bottom-right, top-left, top-right.
+    let corner = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    let offset = corner * 2.0 - 1.0;
+    let position = sprite.center + sprite.right * offset.x + sprite.up * offset.y;
+
+    var out: SpriteOutput;
+    out.clip_position = uniforms.view_proj * vec4<f32>(position, 1.0);
+    // v points down the atlas while the quad's up points up the screen.
+    out.uv = vec2<f32>(
+        mix(sprite.uv_rect.x, sprite.uv_rect.z, corner.x),
+        mix(sprite.uv_rect.w, sprite.uv_rect.y, corner.y),
+    );
+    out.color = sprite.color;
+    return out;
+}
+
+@fragment
+fn fs_sprite(in: SpriteOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(atlas, atlas_sampler, in.uv) * in.color;
+    if color.a < 0.01 {
+        discard;
+    }
+    return color;
+}
+"#;
+
+/// WGSL shader copying the tonemapped frame to the surface with a fullscreen
+/// triangle. The frame and surface are the same size, so texels are loaded
+/// one to one.
+pub const BLIT_SHADER: &str = r#"
+@group(0) @binding(0)
+var frame: texture_2d<f32>;
+
+@vertex
+fn vs_blit(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_blit(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    return textureLoad(frame, vec2<i32>(position.xy), 0);
+}
+"#;
+
+/// WGSL shader scaling the HDR scene by exposure and tonemapping it into
+/// display range with a fullscreen triangle. Operator 0 is the ACES fit
+/// (Narkowicz), 1 is Reinhard.
+pub const TONEMAP_SHADER: &str = r#"
+struct Settings {
+    exposure: f32,
+    operator: u32,
+};
+
+@group(0) @binding(0)
+var hdr: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> settings: Settings;
+
+@vertex
+fn vs_tonemap(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn aces(x: vec3<f32>) -> vec3<f32> {
+    let mapped = (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+    return clamp(mapped, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn reinhard(x: vec3<f32>) -> vec3<f32> {
+    return x / (vec3<f32>(1.0) + x);
+}
+
+@fragment
+fn fs_tonemap(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let color = textureLoad(hdr, vec2<i32>(position.xy), 0);
+    let exposed = max(color.rgb * settings.exposure, vec3<f32>(0.0));
+    var mapped: vec3<f32>;
+    if (settings.operator == 1u) {
+        mapped = reinhard(exposed);
+    } else {
+        mapped = aces(exposed);
+    }
+    return vec4<f32>(mapped, 1.0);
+}
+"#;
+
+/// WGSL shader antialiasing the tonemapped frame with FXAA in a fullscreen
+/// triangle: finds edges from local luma contrast, searches along each edge
+/// for its ends and blends across it by how far the pixel is from them.
+pub const FXAA_SHADER: &str = r#"
+const EDGE_THRESHOLD: f32 = 0.125;
+const EDGE_THRESHOLD_MIN: f32 = 0.0312;
+const SUBPIXEL_QUALITY: f32 = 0.75;
+const SEARCH_STEPS: i32 = 10;
+
+@group(0) @binding(0)
+var frame: texture_2d<f32>;
+@group(0) @binding(1)
+var frame_sampler: sampler;
+
+@vertex
+fn vs_fxaa(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+// Perceptual luma of linear color.
+fn luma(color: vec3<f32>) -> f32 {
+    return sqrt(dot(color, vec3<f32>(0.299, 0.587, 0.114)));
+}
+
+fn luma_at(uv: vec2<f32>) -> f32 {
+    return luma(textureSampleLevel(frame, frame_sampler, uv, 0.0).rgb);
+}
+
+@fragment
+fn fs_fxaa(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(frame));
+    let uv = position.xy * texel;
+    let center = textureSampleLevel(frame, frame_sampler, uv, 0.0);
+    let l = luma(center.rgb);
+    let n = luma_at(uv + vec2<f32>(0.0, -texel.y));
+    let s = luma_at(uv + vec2<f32>(0.0, texel.y));
+    let w = luma_at(uv + vec2<f32>(-texel.x, 0.0));
+    let e = luma_at(uv + vec2<f32>(texel.x, 0.0));
+    let luma_min = min(l, min(min(n, s), min(w, e)));
+    let luma_max = max(l, max(max(n, s), max(w, e)));
+    let contrast = luma_max - luma_min;
+    if (contrast < max(EDGE_THRESHOLD_MIN, luma_max * EDGE_THRESHOLD)) {
+        return center;
+    }
+
+    let nw = luma_at(uv + vec2<f32>(-texel.x, -texel.y));
+    let ne = luma_at(uv + vec2<f32>(texel.x, -texel.y));
+    let sw = luma_at(uv + vec2<f32>(-texel.x, texel.y));
+    let se = luma_at(uv + vec2<f32>(texel.x, texel.y));
+
+    // Is the edge horizontal or vertical?
+    let horizontal_contrast = abs(nw + sw - 2.0 * w) + 2.0 * abs(n + s - 2.0 * l)
+        + abs(ne + se - 2.0 * e);
+    let vertical_contrast = abs(nw + ne - 2.0 * n) + 2.0 * abs(w + e - 2.0 * l)
+        + abs(sw + se - 2.0 * s);
+    let horizontal = horizontal_contrast >= vertical_contrast;
+
+    // Which side of the pixel the edge lies on.
+    var luma_a = w;
+    var luma_b = e;
+    var step_length = texel.x;
+    if (horizontal) {
+        luma_a = n;
+        luma_b = s;
+        step_length = texel.y;
+    }
+    let gradient_a = abs(luma_a - l);
+    let gradient_b = abs(luma_b - l);
+    var edge_luma = 0.5 * (l + luma_b);
+    if (gradient_a >= gradient_b) {
+        step_length = -step_length;
+        edge_luma = 0.5 * (l + luma_a);
+    }
+    let gradient = 0.25 * max(gradient_a, gradient_b);
+
+    // Walk along the edge in both directions until its luma changes.
+    var edge_uv = uv;
+    var along = vec2<f32>(texel.x, 0.0);
+    if (horizontal) {
+        edge_uv.y += step_length * 0.5;
+    } else {
+        edge_uv.x += step_length * 0.5;
+        along = vec2<f32>(0.0, texel.y);
+    }
+    var uv_neg = edge_uv - along;
+    var uv_pos = edge_uv + along;
+    var delta_neg = luma_at(uv_neg) - edge_luma;
+    var delta_pos = luma_at(uv_pos) - edge_luma;
+    var done_neg = abs(delta_neg) >= gradient;
+    var done_pos = abs(delta_pos) >= gradient;
+    for (var i = 1; i < SEARCH_STEPS && !(done_neg && done_pos); i = i + 1) {
+        if (!done_neg) {
+            uv_neg -= along;
+            delta_neg = luma_at(uv_neg) - edge_luma;
+            done_neg = abs(delta_neg) >= gradient;
+        }
+        if (!done_pos) {
+            uv_pos += along;
+            delta_pos = luma_at(uv_pos) - edge_luma;
+            done_pos = abs(delta_pos) >= gradient;
+        }
+    }
+
+    // Blend more the closer the pixel is to the nearer end of the edge,
+    // provided the edge ends on the side going the other way from the pixel.
+    var distance_neg = uv.x - uv_neg.x;
+    var distance_pos = uv_pos.x - uv.x;
+    if (!horizontal) {
+        distance_neg = uv.y - uv_neg.y;
+        distance_pos = uv_pos.y - uv.y;
+    }
+    let nearer_neg = distance_neg < distance_pos;
+    var end_delta = delta_pos;
+    if (nearer_neg) {
+        end_delta = delta_neg;
+    }
+    var edge_blend = 0.0;
+    if ((l - edge_luma < 0.0) != (end_delta < 0.0)) {
+        edge_blend = 0.5 - min(distance_neg, distance_pos) / (distance_neg + distance_pos);
+    }
+
+    // Blend single-pixel features by their contrast with the neighbourhood.
+    let average = (2.0 * (n + s + w + e) + nw + ne + sw + se) / 12.0;
+    let subpixel = clamp(abs(average - l) / contrast, 0.0, 1.0);
+    let subpixel_smooth = (-2.0 * subpixel + 3.0) * subpixel * subpixel;
+    let subpixel_blend = subpixel_smooth * subpixel_smooth * SUBPIXEL_QUALITY;
+
+    let amount = max(edge_blend, subpixel_blend);
+    var offset = vec2<f32>(step_length * amount, 0.0);
+    if (horizontal) {
+        offset = vec2<f32>(0.0, step_length * amount);
+    }
+    return vec4<f32>(textureSampleLevel(frame, frame_sampler, uv + offset, 0.0).rgb, 1.0);
+}
+"#;
+
+/// WGSL shader estimating ambient occlusion at half resolution from scene
+/// depth in a fullscreen triangle per viewport: samples a hemisphere around
+/// each pixel's reconstructed view-space normal, rotated per pixel by a 4x4
+/// pattern that `SSAO_BLUR_SHADER` averages out.
+pub const SSAO_SHADER: &str = r#"
+const SAMPLE_COUNT: u32 = 12u;
+const BIAS: f32 = 0.025;
+
+struct Settings {
+    projection: mat4x4<f32>,
+    inv_projection: mat4x4<f32>,
+    rect: vec4<f32>,
+    radius: f32,
+    intensity: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> settings: Settings;
+@group(0) @binding(1)
+var depth: texture_depth_2d;
+
+@vertex
+fn vs_ssao(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn depth_at(pixel: vec2<f32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(depth));
+    let full = clamp(vec2<i32>(pixel * 2.0), vec2<i32>(0), size - 1);
+    return textureLoad(depth, full, 0);
+}
+
+// View-space position of half-resolution `pixel` at `depth_value`.
+fn view_position(pixel: vec2<f32>, depth_value: f32) -> vec3<f32> {
+    let uv = (pixel - settings.rect.xy) / settings.rect.zw;
+    let ndc = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth_value, 1.0);
+    let position = settings.inv_projection * ndc;
+    return position.xyz / position.w;
+}
+
+@fragment
+fn fs_ssao(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let pixel = position.xy;
+    let center_depth = depth_at(pixel);
+    if (center_depth >= 1.0) {
+        return vec4<f32>(1.0);
+    }
+    let p = view_position(pixel, center_depth);
+    let right = view_position(pixel + vec2<f32>(1.0, 0.0), depth_at(pixel + vec2<f32>(1.0, 0.0)));
+    let down = view_position(pixel + vec2<f32>(0.0, 1.0), depth_at(pixel + vec2<f32>(0.0, 1.0)));
+    var normal = normalize(cross(right - p, down - p));
+    if (dot(normal, p) > 0.0) {
+        normal = -normal;
+    }
+
+    // Tangent frame rotated by the pixel's place in a 4x4 tile.
+    let tile = vec2<u32>(pixel) % vec2<u32>(4u);
+    let rotation = f32(tile.x * 4u + tile.y) * 0.3927;
+    var helper = vec3<f32>(cos(rotation), sin(rotation), 0.0);
+    if (abs(dot(helper, normal)) > 0.9) {
+        helper = vec3<f32>(0.0, 0.0, 1.0);
+    }
+    let tangent = normalize(helper - normal * dot(helper, normal));
+    let bitangent = cross(normal, tangent);
+
+    var occlusion = 0.0;
+    for (var i = 0u; i < SAMPLE_COUNT; i = i + 1u) {
+        // Cosine-weighted spiral over the hemisphere, denser near the pixel.
+        let t = (f32(i) + 0.5) / f32(SAMPLE_COUNT);
+        let angle = f32(i) * 2.39996;
+        let spread = sqrt(t);
+        let direction = tangent * cos(angle) * spread + bitangent * sin(angle) * spread
+            + normal * sqrt(1.0 - t);
+        let sample_point = p + direction * settings.radius * mix(0.1, 1.0, t * t);
+
+        let clip = settings.projection * vec4<f32>(sample_point, 1.0);
+        let ndc = clip.xy / clip.w;
+        let uv = vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+        if (any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0))) {
+            continue;
+        }
+        let sample_pixel = settings.rect.xy + uv * settings.rect.zw;
+        let scene = view_position(sample_pixel, depth_at(sample_pixel));
+        // Geometry in front of the sample hides it, unless it is far enough
+        // from the pixel to be a different object.
+        let in_range = smoothstep(0.0, 1.0, settings.radius / max(abs(p.z - scene.z), 0.0001));
+        if (scene.z >= sample_point.z + BIAS) {
+            occlusion += in_range;
+        }
+    }
+    let ao = 1.0 - occlusion / f32(SAMPLE_COUNT) * settings.intensity;
+    return vec4<f32>(clamp(ao, 0.0, 1.0));
+}
+"#;
+
+/// WGSL shader averaging the occlusion over 4x4 pixels, the size of
+/// `SSAO_SHADER`'s rotation pattern, in a fullscreen triangle.
+pub const SSAO_BLUR_SHADER: &str = r#"
+@group(0) @binding(0)
+var occlusion: texture_2d<f32>;
+
+@vertex
+fn vs_ssao_blur(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_ssao_blur(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(occlusion));
+    let pixel = vec2<i32>(position.xy);
+    var sum = 0.0;
+    for (var y = -2; y < 2; y = y + 1) {
+        for (var x = -2; x < 2; x = x + 1) {
+            let at = clamp(pixel + vec2<i32>(x, y), vec2<i32>(0), size - 1);
+            sum += textureLoad(occlusion, at, 0).r;
+        }
+    }
+    return vec4<f32>(sum / 16.0);
+}
+"#;
+
+/// WGSL compute shader for GPU culling: tests each instance's bounding sphere
+/// against its viewport's frustum and appends the visible ones to their
+/// draw's range of the compacted instance buffer, counting them into the
+/// draw's indirect arguments.
+pub const CULL_SHADER: &str = r#"
+const MAX_VIEWPORTS: u32 = 8u;
+
+struct Instance {
+    model_0: vec4<f32>,
+    model_1: vec4<f32>,
+    model_2: vec4<f32>,
+    model_3: vec4<f32>,
+    color: vec4<f32>,
+};
+
+struct Draw {
+    center: vec3<f32>,
+    radius: f32,
+    viewport: u32,
+    first_instance: u32,
+    index_count: u32,
+    _pad: u32,
+};
+
+struct DrawArgs {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+};
+
+struct CullUniforms {
+    planes: array<array<vec4<f32>, 6>, MAX_VIEWPORTS>,
+    instance_count: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> cull: CullUniforms;
+@group(0) @binding(1)
+var<storage, read> instances: array<Instance>;
+@group(0) @binding(2)
+var<storage, read> instance_draws: array<u32>;
+@group(0) @binding(3)
+var<storage, read> draws: array<Draw>;
+@group(0) @binding(4)
+var<storage, read_write> culled: array<Instance>;
+@group(0) @binding(5)
+var<storage, read_write> args: array<DrawArgs>;
+
+@compute @workgroup_size(64)
+fn cs_cull(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if (index >= cull.instance_count) {
+        return;
+    }
+    let instance = instances[index];
+    let draw_index = instance_draws[index];
+    let draw = draws[draw_index];
+
+    let model = mat4x4<f32>(
+        instance.model_0,
+        instance.model_1,
+        instance.model_2,
+        instance.model_3,
+    );
+    let center = (model * vec4<f32>(draw.center, 1.0)).xyz;
+    let scale = max(
+        length(instance.model_0.xyz),
+        max(length(instance.model_1.xyz), length(instance.model_2.xyz)),
+    );
+    let radius = draw.radius * scale;
+    for (var i = 0u; i < 6u; i = i + 1u) {
+        let plane = cull.planes[draw.viewport][i];
+        if (dot(plane.xyz, center) + plane.w < -radius) {
+            return;
+        }
+    }
+
+    let slot = atomicAdd(&args[draw_index].instance_count, 1u);
+    culled[draw.first_instance + slot] = instance;
+}
+"#;
+
+/// True if `source` defines the `vs_main` and `fs_main` entry points of a
+/// material shader.
+pub(crate) fn has_material_entry_points(source: &str) -> bool {
+    ["vs_main", "fs_main"].iter().all(|name| {
+        source.match_indices(name).any(|(i, _)| {
+            source[..i].trim_end().ends_with("fn")
+                && source[i + name.len()..].trim_start().starts_with('(')
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn material_entry_points() {
+        assert!(has_material_entry_points(WORLD_SHADER));
+        assert!(!has_material_entry_points(GRID_SHADER));
+        assert!(!has_material_entry_points(
+            "fn vs_main_helper() {} fn fs_main() {}"
+        ));
+        assert!(has_material_entry_points(
+            "@vertex fn vs_main (v: V) -> O {} @fragment fn fs_main(i: O) {}"
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+//! Camera-facing sprite quads.
+//!
+//! Each visible Sprite becomes one instance holding its world-space center
+//! and the camera's right and up axes scaled to the sprite's size; the vertex
+//! shader expands it into a quad. Sprites blend, so instances are sorted back
+//! to front.
+
+use crate::camera::FlyCamera;
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
+use worldspace_common::Frustum;
+use worldspace_ecs::{ComponentStore, Layer, Tag, TextureHandle};
+use worldspace_kernel::World;
+
+/// GPU layout of one sprite quad (matches `SpriteInstance` in the shader).
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub(crate) struct SpriteInstance {
+    pub center: [f32; 3],
+    /// Half the quad's width along the camera's right axis.
+    pub right: [f32; 3],
+    /// Half the quad's height along the camera's up axis.
+    pub up: [f32; 3],
+    pub uv_rect: [f32; 4],
+    pub color: [f32; 4],
+}
+
+/// Quads for up to `limit` sprites drawn on `layers` and inside `frustum`,
+/// facing `camera` and sorted back to front, each with the atlas texture it
+/// samples. Also returns how many sprites were culled.
+pub(crate) fn sprite_instances(
+    camera: &FlyCamera,
+    frustum: &Frustum,
+    limit: usize,
+    world: &World,
+    components: &ComponentStore,
+    layers: Layer,
+) -> (Vec<(TextureHandle, SpriteInstance)>, usize) {
+    let (right, up, forward) = (camera.right(), camera.up(), camera.forward());
+    let mut sprites: Vec<(f32, TextureHandle, SpriteInstance)> = Vec::new();
+    let mut culled_count = 0;
+    for (id, sprite) in components.sprites() {
+        if sprites.len() >= limit {
+            break;
+        }
+        let Some(entity) = world.get(*id) else {
+            continue;
+        };
+        if components.has_tag(*id, Tag::Hidden)
+            || !components.is_visible(*id)
+            || !components.in_layers(*id, layers)
+        {
+            continue;
+        }
+        let t = &entity.transform;
+        let half_width = sprite.size[0] * t.scale.x * 0.5;
+        let half_height = sprite.size[1] * t.scale.y * 0.5;
+        let radius = Vec3::new(half_width, half_height, 0.0).length();
+        if !frustum.intersects_aabb(
+            t.position - Vec3::splat(radius),
+            t.position + Vec3::splat(radius),
+        ) {
+            culled_count += 1;
+            continue;
+        }
+        let depth = (t.position - camera.position).dot(forward);
+        sprites.push((
+            depth,
+            sprite.texture,
+            SpriteInstance {
+                center: t.position.to_array(),
+                right: (right * half_width).to_array(),
+                up: (up * half_height).to_array(),
+                uv_rect: sprite.uv_rect,
+                color: sprite.color,
+            },
+        ));
+    }
+    sprites.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let instances = sprites
+        .into_iter()
+        .map(|(_, texture, instance)| (texture, instance))
+        .collect();
+    (instances, culled_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+    use worldspace_ecs::{Sprite, Visible};
+
+    fn spawn_sprite(world: &mut World, components: &mut ComponentStore, z: f32) {
+        let id = world.spawn(Transform {
+            position: Vec3::new(0.0, 0.0, z),
+            scale: Vec3::new(2.0, 1.0, 1.0),
+            ..Transform::default()
+        });
+        components.set_sprite(id, Sprite::new(TextureHandle(z.abs() as u64)));
+    }
+
+    #[test]
+    fn sprites_face_camera_back_to_front() {
+        let camera = FlyCamera {
+            position: Vec3::ZERO,
+            yaw: -std::f32::consts::FRAC_PI_2,
+            pitch: 0.0,
+            ..FlyCamera::default()
+        };
+        let mut world = World::new();
+        let mut components = ComponentStore::new();
+        for z in [-5.0, -20.0, 10.0, -10.0] {
+            spawn_sprite(&mut world, &mut components, z);
+        }
+        let hidden = world.spawn(Transform::default());
+        components.set_sprite(hidden, Sprite::new(TextureHandle(0)));
+        components.set_visible(hidden, Visible(false));
+
+        let (sprites, culled) = sprite_instances(
+            &camera,
+            &camera.frustum(),
+            usize::MAX,
+            &world,
+            &components,
+            Layer::ALL,
+        );
+        // The sprite behind the camera is culled; the rest sort far to near.
+        assert_eq!(culled, 1);
+        let textures: Vec<u64> = sprites.iter().map(|(texture, _)| texture.0).collect();
+        assert_eq!(textures, vec![20, 10, 5]);
+        let quad = sprites[0].1;
+        assert!((Vec3::from(quad.right) - Vec3::X).length() < 1e-5);
+        assert!((Vec3::from(quad.up) - Vec3::Y * 0.5).length() < 1e-5);
+    }
+}
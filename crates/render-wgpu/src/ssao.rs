@@ -0,0 +1,420 @@
+//! Screen-space ambient occlusion.
+//!
+//! With SSAO on, the depth pre-pass lays down scene depth, a half-resolution
+//! pass estimates for each pixel how much of the hemisphere above its
+//! surface nearby geometry hides, and a blur pass smooths out the pass's
+//! noise. The lit pass then scales ambient light by the blurred result, so
+//! creases and contact points darken and cluttered scenes read in depth.
+
+use crate::shaders;
+use crate::viewport::{MAX_VIEWPORTS, ViewportRect};
+use bytemuck::{Pod, Zeroable};
+use glam::Mat4;
+
+/// Format of the occlusion targets: 1 is unoccluded.
+const AO_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+
+/// How ambient occlusion is computed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SsaoSettings {
+    pub enabled: bool,
+    /// World-space distance within which geometry occludes a surface.
+    pub radius: f32,
+    /// Strength of the darkening; 1 removes all ambient light from fully
+    /// occluded pixels.
+    pub intensity: f32,
+}
+
+impl Default for SsaoSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 0.5,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// GPU layout of one viewport's SSAO settings (matches `Settings` in the
+/// shader).
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub(crate) struct SsaoUniforms {
+    projection: [[f32; 4]; 4],
+    inv_projection: [[f32; 4]; 4],
+    /// The viewport in half-resolution pixels: x, y, width, height.
+    rect: [f32; 4],
+    radius: f32,
+    intensity: f32,
+    _pad: [u32; 2],
+}
+
+impl SsaoUniforms {
+    /// Settings for a viewport covering `rect` of the full-resolution target
+    /// and seen through `projection`. Negative radii and intensities are
+    /// treated as 0.
+    pub fn new(projection: Mat4, rect: ViewportRect, settings: &SsaoSettings) -> Self {
+        Self {
+            projection: projection.to_cols_array_2d(),
+            inv_projection: projection.inverse().to_cols_array_2d(),
+            rect: [rect.x, rect.y, rect.width, rect.height].map(|v| v as f32 * 0.5),
+            radius: settings.radius.max(0.0),
+            intensity: settings.intensity.max(0.0),
+            _pad: [0; 2],
+        }
+    }
+}
+
+/// Size of the occlusion targets for a `width` x `height` frame.
+pub(crate) fn half_size(width: u32, height: u32) -> (u32, u32) {
+    (width.div_ceil(2).max(1), height.div_ceil(2).max(1))
+}
+
+/// Pipelines and half-resolution targets of the occlusion and blur passes.
+pub(crate) struct SsaoPass {
+    ao_pipeline: wgpu::RenderPipeline,
+    ao_layout: wgpu::BindGroupLayout,
+    ao_bind_group: wgpu::BindGroup,
+    blur_pipeline: wgpu::RenderPipeline,
+    blur_layout: wgpu::BindGroupLayout,
+    blur_bind_group: wgpu::BindGroup,
+    /// Bind group of the blurred occlusion, in the lit pass's layout.
+    output_bind_group: wgpu::BindGroup,
+    uniforms: wgpu::Buffer,
+    /// Bytes between viewports' uniforms, aligned for dynamic offsets.
+    uniform_stride: u64,
+    occlusion: wgpu::Texture,
+    blurred: wgpu::Texture,
+}
+
+impl SsaoPass {
+    /// Passes reading scene depth from `depth`, whose blurred output binds
+    /// with `output_layout` (one texture at binding 0).
+    pub fn new(
+        device: &wgpu::Device,
+        depth: &wgpu::TextureView,
+        output_layout: &wgpu::BindGroupLayout,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let uniform_stride = (std::mem::size_of::<SsaoUniforms>() as u64)
+            .next_multiple_of(device.limits().min_uniform_buffer_offset_alignment as u64);
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ssao_uniforms"),
+            size: uniform_stride * MAX_VIEWPORTS as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let ao_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("ssao_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let blur_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("ssao_blur_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+        let ao_pipeline = Self::create_pipeline(device, "ssao", &ao_layout, shaders::SSAO_SHADER);
+        let blur_pipeline =
+            Self::create_pipeline(device, "ssao_blur", &blur_layout, shaders::SSAO_BLUR_SHADER);
+
+        let (occlusion, blurred) = Self::create_targets(device, width, height);
+        let (ao_bind_group, blur_bind_group, output_bind_group) = Self::create_bind_groups(
+            device,
+            [&ao_layout, &blur_layout, output_layout],
+            &uniforms,
+            depth,
+            &occlusion,
+            &blurred,
+        );
+        Self {
+            ao_pipeline,
+            ao_layout,
+            ao_bind_group,
+            blur_pipeline,
+            blur_layout,
+            blur_bind_group,
+            output_bind_group,
+            uniforms,
+            uniform_stride,
+            occlusion,
+            blurred,
+        }
+    }
+
+    /// Recreate the targets for a `width` x `height` frame reading the
+    /// resized `depth`.
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        depth: &wgpu::TextureView,
+        output_layout: &wgpu::BindGroupLayout,
+        width: u32,
+        height: u32,
+    ) {
+        (self.occlusion, self.blurred) = Self::create_targets(device, width, height);
+        (
+            self.ao_bind_group,
+            self.blur_bind_group,
+            self.output_bind_group,
+        ) = Self::create_bind_groups(
+            device,
+            [&self.ao_layout, &self.blur_layout, output_layout],
+            &self.uniforms,
+            depth,
+            &self.occlusion,
+            &self.blurred,
+        );
+    }
+
+    /// Upload the settings of viewport `slot`.
+    pub fn write_uniforms(&self, queue: &wgpu::Queue, slot: usize, uniforms: &SsaoUniforms) {
+        queue.write_buffer(
+            &self.uniforms,
+            slot as u64 * self.uniform_stride,
+            bytemuck::bytes_of(uniforms),
+        );
+    }
+
+    /// Record the occlusion pass over the `viewports` (slot and rectangle)
+    /// and the blur pass. `timestamp_writes` bracket both passes together.
+    pub fn record(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        viewports: &[(usize, ViewportRect)],
+        timestamp_writes: Option<(
+            wgpu::RenderPassTimestampWrites<'_>,
+            wgpu::RenderPassTimestampWrites<'_>,
+        )>,
+    ) {
+        let (ao_writes, blur_writes) = timestamp_writes.unzip();
+        let occlusion_view = self.occlusion.create_view(&Default::default());
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("ssao_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &occlusion_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                timestamp_writes: ao_writes,
+                ..Default::default()
+            });
+            pass.set_pipeline(&self.ao_pipeline);
+            for &(slot, rect) in viewports {
+                pass.set_viewport(
+                    rect.x as f32 * 0.5,
+                    rect.y as f32 * 0.5,
+                    rect.width as f32 * 0.5,
+                    rect.height as f32 * 0.5,
+                    0.0,
+                    1.0,
+                );
+                let offset = (slot as u64 * self.uniform_stride) as u32;
+                pass.set_bind_group(0, &self.ao_bind_group, &[offset]);
+                pass.draw(0..3, 0..1);
+            }
+        }
+
+        let blurred_view = self.blurred.create_view(&Default::default());
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("ssao_blur_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &blurred_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            timestamp_writes: blur_writes,
+            ..Default::default()
+        });
+        pass.set_pipeline(&self.blur_pipeline);
+        pass.set_bind_group(0, &self.blur_bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+
+    /// The blurred occlusion, to bind in the lit pass.
+    pub fn output_bind_group(&self) -> &wgpu::BindGroup {
+        &self.output_bind_group
+    }
+
+    /// A fullscreen-triangle pipeline drawing `shader`'s `vs_<name>` and
+    /// `fs_<name>` entry points into an occlusion target.
+    fn create_pipeline(
+        device: &wgpu::Device,
+        name: &str,
+        layout: &wgpu::BindGroupLayout,
+        shader: &str,
+    ) -> wgpu::RenderPipeline {
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&format!("{name}_shader")),
+            source: wgpu::ShaderSource::Wgsl(shader.into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(&format!("{name}_pipeline_layout")),
+            bind_group_layouts: &[layout],
+            push_constant_ranges: &[],
+        });
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&format!("{name}_pipeline")),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: Some(&format!("vs_{name}")),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: Some(&format!("fs_{name}")),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: AO_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+    ) -> (wgpu::Texture, wgpu::Texture) {
+        let (width, height) = half_size(width, height);
+        let target = |label: &str| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: AO_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+        };
+        (target("ssao_texture"), target("ssao_blurred_texture"))
+    }
+
+    /// Bind groups of the occlusion pass, the blur pass and the lit pass,
+    /// given their `layouts` in that order.
+    fn create_bind_groups(
+        device: &wgpu::Device,
+        layouts: [&wgpu::BindGroupLayout; 3],
+        uniforms: &wgpu::Buffer,
+        depth: &wgpu::TextureView,
+        occlusion: &wgpu::Texture,
+        blurred: &wgpu::Texture,
+    ) -> (wgpu::BindGroup, wgpu::BindGroup, wgpu::BindGroup) {
+        let [ao_layout, blur_layout, output_layout] = layouts;
+        let ao = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ssao_bind_group"),
+            layout: ao_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: uniforms,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<SsaoUniforms>() as u64),
+                    }),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(depth),
+                },
+            ],
+        });
+        let texture_group =
+            |label: &str, layout: &wgpu::BindGroupLayout, texture: &wgpu::Texture| {
+                let view = texture.create_view(&Default::default());
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some(label),
+                    layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    }],
+                })
+            };
+        let blur = texture_group("ssao_blur_bind_group", blur_layout, occlusion);
+        let output = texture_group("ssao_output_bind_group", output_layout, blurred);
+        (ao, blur, output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uniforms_hold_half_res_rect_and_inverse() {
+        let projection = Mat4::perspective_rh(1.0, 2.0, 0.1, 100.0);
+        let rect = ViewportRect {
+            x: 100,
+            y: 50,
+            width: 401,
+            height: 200,
+        };
+        let settings = SsaoSettings {
+            radius: -1.0,
+            ..SsaoSettings::default()
+        };
+        let uniforms = SsaoUniforms::new(projection, rect, &settings);
+        assert_eq!(uniforms.rect, [50.0, 25.0, 200.5, 100.0]);
+        assert_eq!(uniforms.radius, 0.0);
+        let identity = Mat4::from_cols_array_2d(&uniforms.inv_projection) * projection;
+        assert!(identity.abs_diff_eq(Mat4::IDENTITY, 1e-5));
+        assert_eq!(std::mem::size_of::<SsaoUniforms>(), 160);
+
+        assert_eq!(half_size(801, 400), (401, 200));
+        assert_eq!(half_size(0, 1), (1, 1));
+    }
+}
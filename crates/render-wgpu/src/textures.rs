@@ -0,0 +1,162 @@
+//! GPU texture cache keyed by `TextureHandle`.
+//!
+//! RGBA8 textures upload as sRGB. KTX2 payloads upload as-is when the device
+//! supports their compressed format; otherwise the upload is refused.
+
+use std::collections::BTreeMap;
+use worldspace_assets::{Texture, TextureData};
+use worldspace_ecs::TextureHandle;
+
+/// An uploaded texture and its default view.
+pub struct GpuTexture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+}
+
+/// GPU textures uploaded from texture assets.
+#[derive(Default)]
+pub struct TextureCache {
+    textures: BTreeMap<TextureHandle, GpuTexture>,
+}
+
+impl TextureCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Upload `texture` under `handle`, replacing any previous upload.
+    /// Returns false if the texture's format is unsupported by `device`.
+    pub fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        handle: TextureHandle,
+        texture: &Texture,
+    ) -> bool {
+        let (format, levels): (wgpu::TextureFormat, Vec<&[u8]>) = match &texture.data {
+            TextureData::Rgba8(pixels) => {
+                (wgpu::TextureFormat::Rgba8UnormSrgb, vec![pixels.as_slice()])
+            }
+            TextureData::Compressed { vk_format, levels } => {
+                let Some(format) = wgpu_format(*vk_format) else {
+                    tracing::warn!(texture = %texture.name, vk_format, "unsupported texture format");
+                    return false;
+                };
+                if !device.features().contains(format.required_features()) {
+                    tracing::warn!(texture = %texture.name, ?format, "texture format not supported by device");
+                    return false;
+                }
+                (format, levels.iter().map(Vec::as_slice).collect())
+            }
+        };
+
+        let size = wgpu::Extent3d {
+            width: texture.width.max(1),
+            height: texture.height.max(1),
+            depth_or_array_layers: 1,
+        };
+        let desc = wgpu::TextureDescriptor {
+            label: Some(texture.name.as_str()),
+            size,
+            mip_level_count: levels.len() as u32,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        };
+        let gpu_texture = device.create_texture(&desc);
+
+        let (block_width, block_height) = format.block_dimensions();
+        let block_size = format.block_copy_size(None).unwrap_or(4);
+        for (level, data) in levels.into_iter().enumerate() {
+            let Some(mip_size) = desc.mip_level_size(level as u32) else {
+                break;
+            };
+            let blocks_wide = mip_size.width.div_ceil(block_width);
+            let blocks_high = mip_size.height.div_ceil(block_height);
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &gpu_texture,
+                    mip_level: level as u32,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                data,
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(blocks_wide * block_size),
+                    rows_per_image: Some(blocks_high),
+                },
+                mip_size.physical_size(format),
+            );
+        }
+
+        let view = gpu_texture.create_view(&Default::default());
+        self.textures.insert(
+            handle,
+            GpuTexture {
+                texture: gpu_texture,
+                view,
+            },
+        );
+        true
+    }
+
+    pub fn get(&self, handle: TextureHandle) -> Option<&GpuTexture> {
+        self.textures.get(&handle)
+    }
+
+    pub fn remove(&mut self, handle: TextureHandle) -> bool {
+        self.textures.remove(&handle).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+}
+
+/// Map a Vulkan format code (as stored in KTX2) to a wgpu format.
+pub fn wgpu_format(vk_format: u32) -> Option<wgpu::TextureFormat> {
+    use wgpu::TextureFormat as F;
+    Some(match vk_format {
+        37 => F::Rgba8Unorm,
+        43 => F::Rgba8UnormSrgb,
+        // BC1 RGB blocks decode identically as RGBA with opaque alpha.
+        131 | 133 => F::Bc1RgbaUnorm,
+        132 | 134 => F::Bc1RgbaUnormSrgb,
+        135 => F::Bc2RgbaUnorm,
+        136 => F::Bc2RgbaUnormSrgb,
+        137 => F::Bc3RgbaUnorm,
+        138 => F::Bc3RgbaUnormSrgb,
+        139 => F::Bc4RUnorm,
+        140 => F::Bc4RSnorm,
+        141 => F::Bc5RgUnorm,
+        142 => F::Bc5RgSnorm,
+        143 => F::Bc6hRgbUfloat,
+        144 => F::Bc6hRgbFloat,
+        145 => F::Bc7RgbaUnorm,
+        146 => F::Bc7RgbaUnormSrgb,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vk_format_mapping() {
+        assert_eq!(wgpu_format(43), Some(wgpu::TextureFormat::Rgba8UnormSrgb));
+        assert_eq!(wgpu_format(131), Some(wgpu::TextureFormat::Bc1RgbaUnorm));
+        assert_eq!(
+            wgpu_format(146),
+            Some(wgpu::TextureFormat::Bc7RgbaUnormSrgb)
+        );
+        assert_eq!(wgpu_format(0), None);
+    }
+}
@@ -0,0 +1,53 @@
+use bytemuck::{Pod, Zeroable};
+
+/// Format of the HDR target the scene is lit into before tonemapping.
+pub(crate) const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Operator mapping HDR scene color into display range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tonemap {
+    /// Filmic ACES fit: gentle toe, highlights roll off to white.
+    #[default]
+    Aces,
+    /// `c / (1 + c)`: keeps hues, compresses highlights softly.
+    Reinhard,
+}
+
+/// GPU layout of the tonemap settings (matches `Settings` in the shader).
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub(crate) struct TonemapUniforms {
+    pub exposure: f32,
+    pub operator: u32,
+    _pad: [u32; 2],
+}
+
+impl TonemapUniforms {
+    /// Settings for `tonemap` with HDR color scaled by `exposure`; negative
+    /// or NaN exposures are treated as 0.
+    pub fn new(tonemap: Tonemap, exposure: f32) -> Self {
+        Self {
+            exposure: exposure.max(0.0),
+            operator: match tonemap {
+                Tonemap::Aces => 0,
+                Tonemap::Reinhard => 1,
+            },
+            _pad: [0; 2],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uniforms_encode_operator_and_clamp_exposure() {
+        let aces = TonemapUniforms::new(Tonemap::default(), 1.5);
+        assert_eq!((aces.exposure, aces.operator), (1.5, 0));
+        let reinhard = TonemapUniforms::new(Tonemap::Reinhard, -2.0);
+        assert_eq!((reinhard.exposure, reinhard.operator), (0.0, 1));
+        assert_eq!(TonemapUniforms::new(Tonemap::Aces, f32::NAN).exposure, 0.0);
+        assert_eq!(std::mem::size_of::<TonemapUniforms>(), 16);
+    }
+}
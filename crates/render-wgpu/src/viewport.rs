@@ -0,0 +1,160 @@
+use crate::camera::FlyCamera;
+use glam::Mat4;
+
+/// Most viewports drawn in one frame; further viewports are skipped.
+pub const MAX_VIEWPORTS: usize = 8;
+
+/// A rectangle of the render target in pixels from its top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewportRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ViewportRect {
+    /// The whole of a `width` x `height` target.
+    pub fn full(width: u32, height: u32) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    /// The quadrants of a `width` x `height` target: top-left, top-right,
+    /// bottom-left, bottom-right. Odd sizes give the extra pixel to the
+    /// right and bottom quadrants.
+    pub fn quad(width: u32, height: u32) -> [Self; 4] {
+        let (left, top) = (width / 2, height / 2);
+        let (right, bottom) = (width - left, height - top);
+        [
+            Self {
+                x: 0,
+                y: 0,
+                width: left,
+                height: top,
+            },
+            Self {
+                x: left,
+                y: 0,
+                width: right,
+                height: top,
+            },
+            Self {
+                x: 0,
+                y: top,
+                width: left,
+                height: bottom,
+            },
+            Self {
+                x: left,
+                y: top,
+                width: right,
+                height: bottom,
+            },
+        ]
+    }
+
+    /// This rectangle clipped to a `width` x `height` target, or `None` if
+    /// nothing of it is on the target.
+    pub fn clip(self, width: u32, height: u32) -> Option<Self> {
+        let right = self.x.saturating_add(self.width).min(width);
+        let bottom = self.y.saturating_add(self.height).min(height);
+        (right > self.x && bottom > self.y).then(|| Self {
+            x: self.x,
+            y: self.y,
+            width: right - self.x,
+            height: bottom - self.y,
+        })
+    }
+
+    /// Width over height.
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height.max(1) as f32
+    }
+}
+
+/// A camera drawn into a rectangle of the render target.
+///
+/// The camera's projection is fitted to the rectangle's aspect ratio, so one
+/// camera can feed viewports of any shape.
+#[derive(Clone, Copy)]
+pub struct Viewport<'a> {
+    pub camera: &'a FlyCamera,
+    pub rect: ViewportRect,
+}
+
+impl<'a> Viewport<'a> {
+    pub fn new(camera: &'a FlyCamera, rect: ViewportRect) -> Self {
+        Self { camera, rect }
+    }
+
+    /// View-projection of the camera with the rectangle's aspect ratio.
+    pub fn view_projection(&self) -> Mat4 {
+        self.camera.projection_with_aspect(self.rect.aspect()) * self.camera.view_matrix()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quad_covers_target_without_overlap() {
+        let quads = ViewportRect::quad(801, 401);
+        let area: u32 = quads.iter().map(|r| r.width * r.height).sum();
+        assert_eq!(area, 801 * 401);
+        assert_eq!(quads[0], ViewportRect::full(400, 200));
+        assert_eq!(
+            quads[3],
+            ViewportRect {
+                x: 400,
+                y: 200,
+                width: 401,
+                height: 201,
+            }
+        );
+    }
+
+    #[test]
+    fn clip_trims_to_target() {
+        let rect = ViewportRect {
+            x: 600,
+            y: 100,
+            width: 400,
+            height: 100,
+        };
+        assert_eq!(
+            rect.clip(800, 600),
+            Some(ViewportRect {
+                x: 600,
+                y: 100,
+                width: 200,
+                height: 100,
+            })
+        );
+        assert_eq!(rect.clip(600, 600), None);
+        assert_eq!(ViewportRect::full(0, 10).clip(10, 10), None);
+    }
+
+    #[test]
+    fn projection_follows_rect_aspect() {
+        let camera = FlyCamera {
+            aspect: 4.0,
+            ..FlyCamera::default()
+        };
+        let square = FlyCamera {
+            aspect: 1.0,
+            ..FlyCamera::default()
+        };
+        let wide = Viewport::new(&square, ViewportRect::full(400, 100));
+        let (a, b) = (
+            wide.view_projection().to_cols_array(),
+            camera.view_projection().to_cols_array(),
+        );
+        assert!(a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5));
+    }
+}
@@ -0,0 +1,182 @@
+//! Canonical render state for determinism tests.
+//!
+//! `RenderState` derives what a renderer would draw for a world and view —
+//! which entities, with which mesh, color and transform — without touching a
+//! GPU. Its digest lets tests assert that replaying the same inputs yields
+//! the same render state, not just the same world state.
+
+use crate::draw::drawn_entities;
+use crate::renderer::RenderView;
+use worldspace_assets::{AssetId, AssetStore, Material};
+use worldspace_common::{EntityId, Frustum, Transform};
+use worldspace_ecs::ComponentStore;
+use worldspace_kernel::World;
+
+/// One entity instance as a renderer would draw it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderInstance {
+    pub entity: EntityId,
+    /// Mesh asset drawn; `None` for the unit cube drawn when the entity has
+    /// no Renderable or its mesh is not in the asset store.
+    pub mesh: Option<AssetId>,
+    /// Base color of the entity's material, or the default material's.
+    pub color: [f32; 4],
+    pub transform: Transform,
+}
+
+/// The instances drawn for a world and view, sorted by entity.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderState {
+    pub instances: Vec<RenderInstance>,
+}
+
+impl RenderState {
+    /// Derive the render state of `world` seen through `view`: the entities
+    /// `drawn_entities` yields for the view's layers whose mesh bounds lie
+    /// in the view frustum. Editor state such as the selection highlight and
+    /// renderer settings such as LOD levels are not part of the render state.
+    pub fn derive(
+        world: &World,
+        components: &ComponentStore,
+        assets: &AssetStore,
+        view: &RenderView,
+    ) -> Self {
+        let frustum = Frustum::from_view_projection(view.view_projection());
+        let default_color = Material::default().base_color;
+        let instances = drawn_entities(world, components, assets, view.layers)
+            .filter(|drawn| drawn.in_frustum(&frustum))
+            .map(|drawn| RenderInstance {
+                entity: drawn.entity,
+                mesh: drawn.mesh,
+                color: drawn.material.map_or(default_color, |m| m.base_color),
+                transform: drawn.transform,
+            })
+            .collect();
+        Self { instances }
+    }
+
+    /// Deterministic FNV-1a hash of the instances, bit-exact in their
+    /// colors and transforms.
+    pub fn digest(&self) -> u64 {
+        let mut h: u64 = 0xcbf2_9ce4_8422_2325; // FNV offset basis
+        let mut mix = |bytes: &[u8]| {
+            for &b in bytes {
+                h ^= b as u64;
+                h = h.wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        for instance in &self.instances {
+            mix(instance.entity.0.as_bytes());
+            match instance.mesh {
+                Some(mesh) => {
+                    mix(&[1]);
+                    mix(&mesh.0.to_le_bytes());
+                }
+                None => mix(&[0]),
+            }
+            let t = &instance.transform;
+            let floats = instance
+                .color
+                .into_iter()
+                .chain(t.position.to_array())
+                .chain(t.rotation.to_array())
+                .chain(t.scale.to_array());
+            for value in floats {
+                mix(&value.to_le_bytes());
+            }
+        }
+        h
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+    use worldspace_ecs::{Layer, MaterialHandle, MeshHandle, Renderable, Tag};
+
+    fn scene() -> (World, ComponentStore, AssetStore) {
+        let mut world = World::new();
+        let mut components = ComponentStore::new();
+        let mut assets = AssetStore::new();
+        let material = assets.register_material(Material {
+            name: "red".into(),
+            base_color: [1.0, 0.0, 0.0, 1.0],
+            ..Material::default()
+        });
+        for x in [-2.0, 0.0, 2.0] {
+            let id = world.spawn(Transform {
+                position: Vec3::new(x, 0.0, 0.0),
+                ..Transform::default()
+            });
+            components.set_renderable(
+                id,
+                Renderable {
+                    mesh: MeshHandle(0),
+                    material: MaterialHandle(material.0),
+                },
+            );
+        }
+        (world, components, assets)
+    }
+
+    #[test]
+    fn same_inputs_same_digest() {
+        let (world, components, assets) = scene();
+        let view = RenderView::default();
+        let a = RenderState::derive(&world, &components, &assets, &view);
+        let b = RenderState::derive(&world.clone(), &components, &assets, &view);
+        assert_eq!(a.instances.len(), 3);
+        assert!(a.instances.iter().all(|i| i.color == [1.0, 0.0, 0.0, 1.0]));
+        assert!(a.instances.iter().all(|i| i.mesh.is_none()));
+        assert!(a.instances.is_sorted_by_key(|i| i.entity));
+        assert_eq!(a.digest(), b.digest());
+    }
+
+    #[test]
+    fn digest_tracks_drawn_state() {
+        let (mut world, mut components, assets) = scene();
+        let view = RenderView::default();
+        let before = RenderState::derive(&world, &components, &assets, &view).digest();
+
+        let id = *world.entities().keys().next().unwrap();
+        world.set_transform(
+            id,
+            Transform {
+                position: Vec3::new(0.0, 1.0, 0.0),
+                ..Transform::default()
+            },
+        );
+        let moved = RenderState::derive(&world, &components, &assets, &view);
+        assert_ne!(moved.digest(), before);
+
+        // Hidden and out-of-view entities are not drawn.
+        components.add_tag(id, Tag::Hidden);
+        let hidden = RenderState::derive(&world, &components, &assets, &view);
+        assert_eq!(hidden.instances.len(), 2);
+        let behind = RenderView {
+            target: Vec3::new(0.0, 20.0, 20.0),
+            ..view
+        };
+        let state = RenderState::derive(&world, &components, &assets, &behind);
+        assert!(state.instances.is_empty());
+        assert_eq!(state.digest(), RenderState::default().digest());
+    }
+
+    #[test]
+    fn entities_off_the_view_layers_are_not_drawn() {
+        let (world, mut components, assets) = scene();
+        let id = *world.entities().keys().next().unwrap();
+        components.set_layer(id, Layer::EDITOR);
+
+        let all = RenderState::derive(&world, &components, &assets, &RenderView::default());
+        assert_eq!(all.instances.len(), 3);
+        let play = RenderView {
+            layers: Layer::PLAY,
+            ..RenderView::default()
+        };
+        let state = RenderState::derive(&world, &components, &assets, &play);
+        assert_eq!(state.instances.len(), 2);
+        assert!(state.instances.iter().all(|i| i.entity != id));
+    }
+}
@@ -0,0 +1,179 @@
+//! Which entities a renderer draws, and with what.
+//!
+//! `drawn_entities` applies the filtering every renderer shares: entities
+//! that are hidden, invisible, off the drawn layers or drawn as sprites are
+//! skipped, and each remaining entity's mesh and material are resolved
+//! through the renderer's `DrawAssets`. Frustum culling is left to the
+//! caller (`DrawnEntity::in_frustum`), since a renderer culling on the GPU
+//! tests every instance itself.
+
+use glam::{Mat4, Vec3};
+use worldspace_assets::{Aabb, AssetId, AssetStore, Material};
+use worldspace_common::{EntityId, Frustum, Transform};
+use worldspace_ecs::{ComponentStore, Layer, Tag};
+use worldspace_kernel::World;
+
+/// The meshes and materials a renderer can draw with.
+pub trait DrawAssets {
+    /// Mesh-space bounds of mesh `id`, or `None` if it cannot be drawn and
+    /// the unit cube stands in for it.
+    fn mesh_bounds(&self, id: AssetId) -> Option<Aabb>;
+
+    /// Material `id`, or `None` to draw with the default material.
+    fn material(&self, id: AssetId) -> Option<&Material>;
+}
+
+impl DrawAssets for AssetStore {
+    fn mesh_bounds(&self, id: AssetId) -> Option<Aabb> {
+        self.get_mesh(id).map(|mesh| mesh.bounds_or_unit().aabb)
+    }
+
+    fn material(&self, id: AssetId) -> Option<&Material> {
+        self.get_material(id)
+    }
+}
+
+/// An entity as a renderer draws it, before renderer state such as LOD
+/// levels or the selection highlight is applied.
+#[derive(Debug, Clone, Copy)]
+pub struct DrawnEntity<'a> {
+    pub entity: EntityId,
+    /// Mesh drawn; `None` for the unit cube drawn when the entity has no
+    /// Renderable or its mesh cannot be drawn.
+    pub mesh: Option<AssetId>,
+    /// Mesh-space bounds of what is drawn.
+    pub aabb: Aabb,
+    /// The entity's material; `None` for the default material.
+    pub material: Option<&'a Material>,
+    pub transform: Transform,
+    /// Model matrix of `transform`.
+    pub model: Mat4,
+}
+
+impl DrawnEntity<'_> {
+    /// World-space `(min, max)` of the entity's bounds.
+    pub fn world_bounds(&self) -> (Vec3, Vec3) {
+        world_aabb(self.model, &self.aabb)
+    }
+
+    /// True if the entity may be visible in `frustum`.
+    pub fn in_frustum(&self, frustum: &Frustum) -> bool {
+        let (min, max) = self.world_bounds();
+        frustum.intersects_aabb(min, max)
+    }
+}
+
+/// The entities of `world` drawn on `layers`, in EntityId order, with their
+/// meshes and materials resolved through `assets`. Entities that are hidden,
+/// invisible, on none of `layers` or drawn as sprites are skipped; nothing
+/// is culled.
+pub fn drawn_entities<'a>(
+    world: &'a World,
+    components: &'a ComponentStore,
+    assets: &'a impl DrawAssets,
+    layers: Layer,
+) -> impl Iterator<Item = DrawnEntity<'a>> + 'a {
+    let renderables = components.renderables();
+    world.entities().iter().filter_map(move |(id, data)| {
+        if components.has_tag(*id, Tag::Hidden)
+            || !components.is_visible(*id)
+            || !components.in_layers(*id, layers)
+            || components.get_sprite(*id).is_some()
+        {
+            return None;
+        }
+        let renderable = renderables.get(id);
+        let mesh = renderable
+            .map(|r| AssetId(r.mesh.0))
+            .and_then(|mesh| assets.mesh_bounds(mesh).map(|aabb| (mesh, aabb)));
+        let t = data.transform;
+        Some(DrawnEntity {
+            entity: *id,
+            mesh: mesh.map(|(mesh, _)| mesh),
+            aabb: mesh.map_or(Aabb::UNIT_CUBE, |(_, aabb)| aabb),
+            material: renderable.and_then(|r| assets.material(AssetId(r.material.0))),
+            transform: t,
+            model: Mat4::from_scale_rotation_translation(t.scale, t.rotation, t.position),
+        })
+    })
+}
+
+/// World-space `(min, max)` of the box around `aabb` transformed by `model`.
+/// Exact for the transformed box's corners; a rotated box grows to enclose
+/// them.
+pub fn world_aabb(model: Mat4, aabb: &Aabb) -> (Vec3, Vec3) {
+    let center = model.transform_point3(Vec3::from(aabb.center()));
+    let [hx, hy, hz] = aabb.half_extents();
+    let extent = model.x_axis.truncate().abs() * hx
+        + model.y_axis.truncate().abs() * hy
+        + model.z_axis.truncate().abs() * hz;
+    (center - extent, center + extent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Quat;
+    use worldspace_ecs::Visible;
+
+    #[test]
+    fn world_aabb_encloses_rotated_box() {
+        let model = Mat4::from_scale_rotation_translation(
+            Vec3::new(2.0, 1.0, 1.0),
+            Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            Vec3::new(10.0, 0.0, 0.0),
+        );
+        let (min, max) = world_aabb(model, &Aabb::UNIT_CUBE);
+        // The x scale ends up along z after a quarter turn about y.
+        assert!((min - Vec3::new(9.5, -0.5, -1.0)).length() < 1e-5);
+        assert!((max - Vec3::new(10.5, 0.5, 1.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn boxes_behind_the_camera_are_culled() {
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y);
+        let projection = Mat4::perspective_rh(60f32.to_radians(), 1.0, 0.1, 100.0);
+        let frustum = Frustum::from_view_projection(projection * view);
+        let at = |position, aabb| DrawnEntity {
+            entity: EntityId::new(),
+            mesh: None,
+            aabb,
+            material: None,
+            transform: Transform::default(),
+            model: Mat4::from_translation(position),
+        };
+
+        assert!(at(Vec3::new(0.0, 0.0, -10.0), Aabb::UNIT_CUBE).in_frustum(&frustum));
+        assert!(!at(Vec3::new(0.0, 0.0, 10.0), Aabb::UNIT_CUBE).in_frustum(&frustum));
+        assert!(!at(Vec3::new(50.0, 0.0, -10.0), Aabb::UNIT_CUBE).in_frustum(&frustum));
+        // A large mesh reaches into view from off to the side.
+        let wide = Aabb {
+            min: [-50.0, -1.0, -1.0],
+            max: [50.0, 1.0, 1.0],
+        };
+        assert!(at(Vec3::new(50.0, 0.0, -10.0), wide).in_frustum(&frustum));
+    }
+
+    #[test]
+    fn filtered_entities_are_not_drawn() {
+        let mut world = World::new();
+        let mut components = ComponentStore::new();
+        let assets = AssetStore::new();
+        let shown = world.spawn(Transform::default());
+        let hidden = world.spawn(Transform::default());
+        let invisible = world.spawn(Transform::default());
+        let editor_only = world.spawn(Transform::default());
+        components.add_tag(hidden, Tag::Hidden);
+        components.set_visible(invisible, Visible(false));
+        components.set_layer(editor_only, Layer::EDITOR);
+
+        let drawn: Vec<EntityId> = drawn_entities(&world, &components, &assets, Layer::PLAY)
+            .map(|d| d.entity)
+            .collect();
+        assert_eq!(drawn, vec![shown]);
+        assert_eq!(
+            drawn_entities(&world, &components, &assets, Layer::ALL).count(),
+            2
+        );
+    }
+}
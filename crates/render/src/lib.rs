@@ -2,16 +2,23 @@
 //!
 //! # Invariants
 //! - Renderer cannot mutate world truth directly.
-//! - Render state derives from world state and view.
+//! - Render state derives from world state and view; `RenderState` derives
+//!   it without a GPU so determinism tests can compare its digest.
 //!
 //! # Workaround
 //! Provides a trait-based renderer interface with a debug text renderer as a
 //! workaround for the wgpu GPU backend. The trait is stable; swap in a wgpu
 //! implementation without changing consumers.
 
+mod digest;
+mod draw;
 mod renderer;
+mod stats;
 
+pub use digest::{RenderInstance, RenderState};
+pub use draw::{DrawAssets, DrawnEntity, drawn_entities, world_aabb};
 pub use renderer::{DebugTextRenderer, RenderView, Renderer};
+pub use stats::{GpuPassTimes, RenderStats};
 
 pub fn crate_info() -> &'static str {
     "worldspace-render v0.1.0"
@@ -1,4 +1,5 @@
-use glam::Vec3;
+use glam::{Mat4, Vec3};
+use worldspace_ecs::Layer;
 use worldspace_kernel::World;
 
 /// Camera/view configuration for rendering.
@@ -10,6 +11,10 @@ pub struct RenderView {
     pub target: Vec3,
     /// Field of view in degrees.
     pub fov_degrees: f32,
+    /// Width over height of the view.
+    pub aspect: f32,
+    /// Layers drawn; entities on none of them are left out.
+    pub layers: Layer,
 }
 
 impl Default for RenderView {
@@ -18,10 +23,31 @@ impl Default for RenderView {
             eye: Vec3::new(0.0, 10.0, 10.0),
             target: Vec3::ZERO,
             fov_degrees: 60.0,
+            aspect: 16.0 / 9.0,
+            layers: Layer::ALL,
         }
     }
 }
 
+impl RenderView {
+    /// Near and far clip distances of `view_projection`.
+    pub const NEAR: f32 = 0.1;
+    pub const FAR: f32 = 1000.0;
+
+    /// Right-handed perspective view-projection looking from `eye` at
+    /// `target` with +Y up.
+    pub fn view_projection(&self) -> Mat4 {
+        let view = Mat4::look_at_rh(self.eye, self.target, Vec3::Y);
+        let projection = Mat4::perspective_rh(
+            self.fov_degrees.to_radians(),
+            self.aspect,
+            Self::NEAR,
+            Self::FAR,
+        );
+        projection * view
+    }
+}
+
 /// Renderer-agnostic interface. All renderers implement this trait.
 ///
 /// The renderer reads world state and a view configuration, then produces
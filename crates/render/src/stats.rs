@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+/// Per-frame render statistics reported by a renderer backend.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderStats {
+    /// Number of entity instances submitted this frame.
+    pub instance_count: usize,
+    /// Number of entities skipped this frame because their bounds were
+    /// outside the camera frustum.
+    pub culled_count: usize,
+    /// Number of draw calls issued this frame.
+    pub draw_calls: usize,
+    /// Number of entity mesh triangles drawn this frame, over all instances.
+    pub triangle_count: usize,
+    /// CPU time spent building and submitting the frame.
+    pub frame_time: Duration,
+    /// GPU time of each render pass, measured with timestamp queries. `None`
+    /// when the backend cannot measure GPU time. GPU results arrive after
+    /// the frame is submitted, so these may lag a frame or two behind.
+    pub gpu_times: Option<GpuPassTimes>,
+}
+
+/// GPU time spent in each render pass of a frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GpuPassTimes {
+    /// Laying down scene depth before the lit pass; zero when the backend
+    /// draws no depth pre-pass.
+    pub depth_prepass: Duration,
+    /// Screen-space ambient occlusion and its blur; zero when it is off.
+    pub ssao: Duration,
+    /// Drawing the lit scene.
+    pub scene: Duration,
+    /// Post-processing the scene, such as tonemapping.
+    pub post: Duration,
+    /// Post-process antialiasing; zero when it is off.
+    pub antialias: Duration,
+    /// Copying the finished frame to the surface.
+    pub present: Duration,
+}
+
+impl GpuPassTimes {
+    /// GPU time of all passes together.
+    pub fn total(&self) -> Duration {
+        self.depth_prepass + self.ssao + self.scene + self.post + self.antialias + self.present
+    }
+}
@@ -3,7 +3,7 @@ use std::time::Instant;
 
 use worldspace_common::Transform;
 use worldspace_kernel::World;
-use worldspace_stream::{CellCoord, GridPartition, StreamConfig, StreamState};
+use worldspace_stream::{CellCoord, GridPartition, QuadtreePartition, StreamConfig, StreamState};
 
 fn make_world(entity_count: usize, spacing: f32) -> World {
     let mut world = World::new();
@@ -51,6 +51,37 @@ fn bench_entities_in_radius(entity_count: usize, radius: i32, iterations: usize)
     );
 }
 
+fn bench_sparse_radius(radius: i32, iterations: usize) {
+    // Two clusters far apart: a grid visits every cell in the radius, the
+    // quadtree only the occupied quadrants.
+    let mut world = make_world(200, 4.0);
+    for i in 0..200 {
+        world.spawn(Transform {
+            position: glam::Vec3::new(100_000.0 + i as f32, 0.0, 100_000.0),
+            ..Transform::default()
+        });
+    }
+    let mut grid = GridPartition::new(16.0);
+    grid.rebuild(&world);
+    let mut tree = QuadtreePartition::new(16.0);
+    tree.rebuild(&world);
+
+    let center = CellCoord::new(0, 0);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let _ = black_box(grid.entities_in_radius(black_box(center), black_box(radius)));
+    }
+    let grid_time = start.elapsed() / iterations as u32;
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let _ = black_box(tree.entities_in_radius(black_box(center), black_box(radius)));
+    }
+    let tree_time = start.elapsed() / iterations as u32;
+    println!(
+        "  sparse radius query (r={radius}, {iterations} iters): grid {grid_time:?}/iter, quadtree {tree_time:?}/iter"
+    );
+}
+
 fn bench_stream_update(entity_count: usize, iterations: usize) {
     let world = make_world(entity_count, 4.0);
     let mut grid = GridPartition::new(16.0);
@@ -58,9 +89,13 @@ fn bench_stream_update(entity_count: usize, iterations: usize) {
 
     let config = StreamConfig {
         active_radius: 2,
+        reduced_radius: 3,
         preload_radius: 4,
         load_budget: 8,
         unload_budget: 8,
+        apply_budget: 8,
+        workers: 0,
+        memory_budget: None,
     };
     let mut state = StreamState::new(config);
 
@@ -90,6 +125,10 @@ fn main() {
     bench_entities_in_radius(1000, 3, 10000);
     bench_entities_in_radius(1000, 5, 1000);
 
+    println!("\nSparse world radius query (grid vs quadtree):");
+    bench_sparse_radius(8, 1000);
+    bench_sparse_radius(64, 100);
+
     println!("\nStream update (budgeted load/unload):");
     bench_stream_update(100, 10000);
     bench_stream_update(1000, 1000);
@@ -0,0 +1,355 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use worldspace_common::EntityId;
+use worldspace_kernel::World;
+
+/// Density thresholds for `AdaptivePartition`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveConfig {
+    /// A region holding more entities than this is split into quadrants.
+    pub max_entities: usize,
+    /// Quadrants holding this many entities or fewer between them are
+    /// merged back into one region. Lower than `max_entities`, so a region
+    /// near a threshold does not flip every rebuild.
+    pub min_entities: usize,
+    /// How many times a base cell may be halved.
+    pub max_split: u32,
+    /// How many times base cells may be doubled by merging.
+    pub max_merge: u32,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        Self {
+            max_entities: 64,
+            min_entities: 16,
+            max_split: 2,
+            max_merge: 2,
+        }
+    }
+}
+
+/// A square region of an `AdaptivePartition`, `cell_size * 2^scale` on a
+/// side: a base cell at scale 0, split cells below it, merged cells above.
+/// `x` and `z` count regions of that size from the origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CellRegion {
+    pub scale: i32,
+    pub x: i32,
+    pub z: i32,
+}
+
+impl CellRegion {
+    /// Side length in world units for a partition of `cell_size`.
+    pub fn size(&self, cell_size: f32) -> f32 {
+        cell_size * 2f32.powi(self.scale)
+    }
+}
+
+/// What an `AdaptivePartition::rebuild` changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RebuildReport {
+    /// Regions after the rebuild.
+    pub regions: usize,
+    /// Previous regions that were split into smaller ones.
+    pub split: Vec<CellRegion>,
+    /// New regions that each absorbed several previous ones.
+    pub merged: Vec<CellRegion>,
+}
+
+/// A node of the implicit quadtree over the finest cells: its level above
+/// the finest cells and its coordinates at that level.
+type Node = (u32, i32, i32);
+
+/// XZ partitioning whose cells adapt to entity density.
+///
+/// The world is covered by a quadtree of cells from `cell_size * 2^max_merge`
+/// down to `cell_size / 2^max_split` on a side. Each rebuild splits regions
+/// that hold more than `max_entities` and merges quadrants that together
+/// hold at most `min_entities`, so a dense city block ends up in small cells
+/// while an empty desert shares a few large ones.
+pub struct AdaptivePartition {
+    cell_size: f32,
+    config: AdaptiveConfig,
+    regions: HashMap<Node, HashSet<EntityId>>,
+    /// Nodes that were split on the last rebuild.
+    interior: HashSet<Node>,
+}
+
+impl AdaptivePartition {
+    /// Create an adaptive partition around base cells of `cell_size`.
+    pub fn new(cell_size: f32, config: AdaptiveConfig) -> Self {
+        assert!(cell_size > 0.0, "cell_size must be positive");
+        assert!(
+            config.min_entities < config.max_entities,
+            "min_entities must be below max_entities"
+        );
+        Self {
+            cell_size,
+            config,
+            regions: HashMap::new(),
+            interior: HashSet::new(),
+        }
+    }
+
+    /// Base cell size of this partition.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Rebuild the regions from the current world state, starting from the
+    /// previous layout, and report what was split and merged.
+    pub fn rebuild(&mut self, world: &World) -> RebuildReport {
+        let top = self.config.max_split + self.config.max_merge;
+        let placed: Vec<((i32, i32), EntityId)> = world
+            .entities()
+            .iter()
+            .map(|(id, data)| (self.finest_cell(data.transform.position), *id))
+            .collect();
+        let mut counts: HashMap<Node, usize> = HashMap::new();
+        for &((x, z), _) in &placed {
+            for level in 0..=top {
+                *counts.entry((level, x >> level, z >> level)).or_default() += 1;
+            }
+        }
+
+        let mut leaves = HashSet::new();
+        let mut interior = HashSet::new();
+        let mut stack: Vec<Node> = counts.keys().filter(|n| n.0 == top).copied().collect();
+        while let Some(node @ (level, x, z)) = stack.pop() {
+            let count = counts[&node];
+            let limit = if self.interior.contains(&node) {
+                self.config.min_entities
+            } else {
+                self.config.max_entities
+            };
+            if level == 0 || count <= limit {
+                leaves.insert(node);
+                continue;
+            }
+            interior.insert(node);
+            for (dx, dz) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let child = (level - 1, 2 * x + dx, 2 * z + dz);
+                if counts.contains_key(&child) {
+                    stack.push(child);
+                }
+            }
+        }
+
+        let mut regions: HashMap<Node, HashSet<EntityId>> = HashMap::new();
+        for ((x, z), id) in placed {
+            let node = (0..=top)
+                .map(|level| (level, x >> level, z >> level))
+                .find(|node| leaves.contains(node))
+                .expect("every placed cell is under a leaf");
+            regions.entry(node).or_default().insert(id);
+        }
+
+        let report = self.diff(&regions);
+        self.regions = regions;
+        self.interior = interior;
+        tracing::debug!(
+            regions = report.regions,
+            split = report.split.len(),
+            merged = report.merged.len(),
+            "adaptive partition rebuilt"
+        );
+        report
+    }
+
+    /// The region containing world position `pos`, if it holds entities.
+    pub fn region_at(&self, pos: glam::Vec3) -> Option<CellRegion> {
+        let (x, z) = self.finest_cell(pos);
+        (0..=self.config.max_split + self.config.max_merge)
+            .map(|level| (level, x >> level, z >> level))
+            .find(|node| self.regions.contains_key(node))
+            .map(|node| self.region(node))
+    }
+
+    /// Get all entity IDs in a region.
+    pub fn entities_in_region(&self, region: CellRegion) -> HashSet<EntityId> {
+        let level = region.scale + self.config.max_split as i32;
+        if level < 0 {
+            return HashSet::new();
+        }
+        self.regions
+            .get(&(level as u32, region.x, region.z))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The non-empty regions, in no particular order.
+    pub fn regions(&self) -> impl Iterator<Item = CellRegion> + '_ {
+        self.regions.keys().map(|&node| self.region(node))
+    }
+
+    /// Number of non-empty regions.
+    pub fn region_count(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Total number of entity placements across all regions.
+    pub fn total_placements(&self) -> usize {
+        self.regions.values().map(|s| s.len()).sum()
+    }
+
+    fn finest_cell(&self, pos: glam::Vec3) -> (i32, i32) {
+        let finest = self.cell_size / 2f32.powi(self.config.max_split as i32);
+        (
+            (pos.x / finest).floor() as i32,
+            (pos.z / finest).floor() as i32,
+        )
+    }
+
+    fn region(&self, (level, x, z): Node) -> CellRegion {
+        CellRegion {
+            scale: level as i32 - self.config.max_split as i32,
+            x,
+            z,
+        }
+    }
+
+    /// Compare `regions` against the current layout.
+    fn diff(&self, regions: &HashMap<Node, HashSet<EntityId>>) -> RebuildReport {
+        let top = self.config.max_split + self.config.max_merge;
+        let mut split = BTreeSet::new();
+        let mut merged = BTreeSet::new();
+        for &(level, x, z) in self.regions.keys() {
+            if regions.contains_key(&(level, x, z)) {
+                continue;
+            }
+            let parent = (level + 1..=top)
+                .map(|up| (up, x >> (up - level), z >> (up - level)))
+                .find(|node| regions.contains_key(node));
+            match parent {
+                Some(node) => merged.insert(self.region(node)),
+                // Emptied regions are neither split nor merged.
+                None if regions.keys().any(|&(l, cx, cz)| {
+                    l < level && (cx >> (level - l), cz >> (level - l)) == (x, z)
+                }) =>
+                {
+                    split.insert(self.region((level, x, z)))
+                }
+                None => false,
+            };
+        }
+        RebuildReport {
+            regions: regions.len(),
+            split: split.into_iter().collect(),
+            merged: merged.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+
+    fn spawn_at(world: &mut World, x: f32, z: f32) -> EntityId {
+        world.spawn(Transform {
+            position: glam::Vec3::new(x, 0.0, z),
+            ..Transform::default()
+        })
+    }
+
+    fn config() -> AdaptiveConfig {
+        AdaptiveConfig {
+            max_entities: 8,
+            min_entities: 2,
+            max_split: 2,
+            max_merge: 2,
+        }
+    }
+
+    #[test]
+    fn dense_cells_split_and_sparse_cells_merge() {
+        let mut world = World::new();
+        // A city block: 40 entities spread over one 16 m cell.
+        for i in 0..40 {
+            spawn_at(&mut world, (i % 8) as f32 * 2.0, (i / 8) as f32 * 3.0);
+        }
+        // A desert: two entities 20 m apart in a 64 m area.
+        let desert = spawn_at(&mut world, 130.0, 130.0);
+        spawn_at(&mut world, 150.0, 130.0);
+
+        let mut partition = AdaptivePartition::new(16.0, config());
+        let report = partition.rebuild(&world);
+        assert_eq!(report.regions, partition.region_count());
+        assert_eq!(partition.total_placements(), 42);
+
+        let region = partition
+            .region_at(glam::Vec3::new(131.0, 0.0, 131.0))
+            .unwrap();
+        assert_eq!(region.scale, 2);
+        assert_eq!(region.size(16.0), 64.0);
+        assert!(partition.entities_in_region(region).contains(&desert));
+        assert_eq!(partition.entities_in_region(region).len(), 2);
+
+        let city = partition.region_at(glam::Vec3::new(1.0, 0.0, 1.0)).unwrap();
+        assert!(city.scale < 0);
+        assert!(partition.regions().all(|r| {
+            let n = partition.entities_in_region(r).len();
+            n <= config().max_entities || r.scale == -2
+        }));
+        assert!(
+            partition
+                .region_at(glam::Vec3::new(-500.0, 0.0, 0.0))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn report_tracks_splits_and_merges() {
+        let mut world = World::new();
+        let mut partition = AdaptivePartition::new(16.0, config());
+        for i in 0..4 {
+            spawn_at(&mut world, i as f32 * 20.0, 0.0);
+        }
+        let report = partition.rebuild(&world);
+        assert_eq!(report.regions, 1);
+        assert!(report.split.is_empty() && report.merged.is_empty());
+
+        // Filling the area past max_entities splits the region.
+        let crowd: Vec<_> = (0..12)
+            .map(|i| spawn_at(&mut world, i as f32 * 5.0, 1.0))
+            .collect();
+        let report = partition.rebuild(&world);
+        assert_eq!(
+            report.split,
+            vec![CellRegion {
+                scale: 2,
+                x: 0,
+                z: 0
+            }]
+        );
+        assert!(report.regions > 1);
+
+        // Thinning out only merges once at or below min_entities.
+        for id in &crowd[..9] {
+            world.despawn(*id);
+        }
+        let report = partition.rebuild(&world);
+        let top = CellRegion {
+            scale: 2,
+            x: 0,
+            z: 0,
+        };
+        assert!(report.regions > 1 && !report.merged.contains(&top));
+        for id in &crowd[9..] {
+            world.despawn(*id);
+        }
+        for id in world
+            .entities()
+            .keys()
+            .copied()
+            .collect::<Vec<_>>()
+            .iter()
+            .skip(2)
+        {
+            world.despawn(*id);
+        }
+        let report = partition.rebuild(&world);
+        assert_eq!(report.regions, 1);
+        assert_eq!(report.merged, vec![top]);
+    }
+}
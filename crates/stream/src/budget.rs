@@ -1,120 +1,477 @@
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use crate::grid::{CellCoord, GridPartition};
+use worldspace_common::EntityId;
+use worldspace_kernel::{ActivationEvent, EntityData, World};
+
+use crate::grid::{CellCoord, SpatialPartition};
+use crate::loader::LoadPool;
+use crate::lod::CellLod;
 
 /// Streaming configuration: controls active and preload radii plus per-frame budgets.
 #[derive(Debug, Clone)]
 pub struct StreamConfig {
     /// Radius (in cells) around the viewer that is fully active (entities ticked + rendered).
     pub active_radius: i32,
-    /// Radius (in cells) around the viewer that is preloaded (data in memory, not ticked).
+    /// Radius (in cells) out to which loaded cells use `CellLod::Reduced`;
+    /// beyond it they are impostors.
+    pub reduced_radius: i32,
+    /// Radius (in cells) around the viewer that is preloaded (data in memory, not
+    /// ticked once `World::set_active` is given `StreamState::active_entities`).
     pub preload_radius: i32,
-    /// Maximum number of cells to load per frame.
+    /// Maximum number of cells to schedule for loading per frame.
     pub load_budget: usize,
     /// Maximum number of cells to unload per frame.
     pub unload_budget: usize,
+    /// Maximum number of finished loads to apply per frame; the rest wait
+    /// for the next frame.
+    pub apply_budget: usize,
+    /// Background threads loading cells. With 0, cells load on the calling
+    /// thread during `update`.
+    pub workers: usize,
+    /// Estimated bytes loaded cells may hold; when exceeded, the farthest
+    /// cells are unloaded. `None` for no limit.
+    pub memory_budget: Option<usize>,
 }
 
 impl Default for StreamConfig {
     fn default() -> Self {
         Self {
             active_radius: 2,
+            reduced_radius: 3,
             preload_radius: 4,
             load_budget: 4,
             unload_budget: 4,
+            apply_budget: 4,
+            workers: 2,
+            memory_budget: None,
+        }
+    }
+}
+
+/// A change to the set of loaded cells, reported by `StreamState::update`.
+///
+/// Each event lists the entities the partition holds in the cell, sorted,
+/// so renderers can add, remove or re-LOD just those instances.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent<C> {
+    /// A cell finished loading in the background and is now loaded.
+    CellLoaded {
+        coord: CellCoord,
+        entities: Vec<EntityId>,
+        content: C,
+    },
+    /// A cell left the preload radius, or was evicted to stay within the
+    /// memory budget, and was unloaded.
+    CellUnloaded {
+        coord: CellCoord,
+        entities: Vec<EntityId>,
+    },
+    /// A loaded cell moved to another LOD tier as the viewer moved.
+    CellLodChanged {
+        coord: CellCoord,
+        from: CellLod,
+        to: CellLod,
+        entities: Vec<EntityId>,
+    },
+    /// The loader panicked on a cell. It stays unloaded and is not loaded
+    /// again until it leaves and re-enters the preload radius.
+    CellLoadFailed {
+        coord: CellCoord,
+        entities: Vec<EntityId>,
+    },
+}
+
+impl<C> StreamEvent<C> {
+    /// The cell the event is about.
+    pub fn coord(&self) -> CellCoord {
+        match self {
+            StreamEvent::CellLoaded { coord, .. }
+            | StreamEvent::CellUnloaded { coord, .. }
+            | StreamEvent::CellLodChanged { coord, .. }
+            | StreamEvent::CellLoadFailed { coord, .. } => *coord,
         }
     }
+
+    /// The entities in the cell.
+    pub fn entities(&self) -> &[EntityId] {
+        match self {
+            StreamEvent::CellLoaded { entities, .. }
+            | StreamEvent::CellUnloaded { entities, .. }
+            | StreamEvent::CellLodChanged { entities, .. }
+            | StreamEvent::CellLoadFailed { entities, .. } => entities,
+        }
+    }
+
+    /// The same event with its loaded content converted by `f`.
+    pub fn map_content<D>(self, f: impl FnOnce(C) -> D) -> StreamEvent<D> {
+        match self {
+            StreamEvent::CellLoaded {
+                coord,
+                entities,
+                content,
+            } => StreamEvent::CellLoaded {
+                coord,
+                entities,
+                content: f(content),
+            },
+            StreamEvent::CellUnloaded { coord, entities } => {
+                StreamEvent::CellUnloaded { coord, entities }
+            }
+            StreamEvent::CellLodChanged {
+                coord,
+                from,
+                to,
+                entities,
+            } => StreamEvent::CellLodChanged {
+                coord,
+                from,
+                to,
+                entities,
+            },
+            StreamEvent::CellLoadFailed { coord, entities } => {
+                StreamEvent::CellLoadFailed { coord, entities }
+            }
+        }
+    }
+}
+
+/// Entities of `coord` in `grid`, sorted.
+fn entity_list(grid: &impl SpatialPartition, coord: CellCoord) -> Vec<EntityId> {
+    let mut entities: Vec<EntityId> = grid.entities_in_cell(coord).into_iter().collect();
+    entities.sort();
+    entities
 }
 
-/// Tracks which cells are currently loaded and manages load/unload budgets per frame.
-pub struct StreamState {
+/// Tracks which cells are loaded and loads cell content in the background.
+///
+/// Each loaded cell has a `CellLod` tier by its distance from the viewer.
+/// `update` never loads a cell itself: it schedules loads on a pool of
+/// worker threads and applies the loads that have finished, both within the
+/// per-frame budgets, so slow loaders (e.g. reading cells from disk) do not
+/// stall the frame. `C` is the content the loader produces for a cell.
+///
+/// Missing cells load in priority order: nearest first, and with a view
+/// direction set, cells in front of the viewer before cells behind it.
+///
+/// Each loaded cell has an estimated byte cost: its entities plus what
+/// `set_content_cost` reports for its content. Cells evicted to stay within
+/// `StreamConfig::memory_budget` are not loaded again until their last cost
+/// fits the budget or they leave and re-enter the preload radius, and so
+/// are cells whose loader panicked.
+pub struct StreamState<C = ()> {
     pub config: StreamConfig,
+    view_direction: Option<glam::Vec3>,
+    content_cost: ContentCost<C>,
     loaded_cells: HashSet<CellCoord>,
+    lods: HashMap<CellCoord, CellLod>,
+    costs: HashMap<CellCoord, usize>,
+    evicted: HashMap<CellCoord, usize>,
+    /// Cells whose loader panicked, kept out of loading while desired.
+    failed: HashSet<CellCoord>,
+    memory_used: usize,
+    pending: HashSet<CellCoord>,
+    desired: HashSet<CellCoord>,
+    pool: LoadPool<C>,
     stats: StreamStats,
 }
 
+type ContentCost<C> = Box<dyn Fn(&C) -> usize + Send + Sync>;
+
+/// Estimated bytes one entity in a loaded cell costs.
+pub const ENTITY_COST_BYTES: usize = std::mem::size_of::<(EntityId, EntityData)>();
+
 /// Per-frame streaming statistics for instrumentation.
 #[derive(Debug, Clone, Default)]
 pub struct StreamStats {
     pub cells_loaded_this_frame: usize,
+    /// Includes cells evicted for the memory budget.
     pub cells_unloaded_this_frame: usize,
+    pub cells_evicted_this_frame: usize,
+    pub cells_scheduled_this_frame: usize,
+    pub pending_loads: usize,
     pub total_loaded_cells: usize,
+    /// Estimated bytes held by loaded cells.
+    pub memory_used: usize,
     pub frame_time: Duration,
 }
 
-impl StreamState {
+impl StreamState<()> {
+    /// Stream cells without content, tracking only which are loaded.
     pub fn new(config: StreamConfig) -> Self {
+        Self::with_loader(config, |_| ())
+    }
+}
+
+impl<C: Send + 'static> StreamState<C> {
+    /// Stream cells whose content `loader` produces on the worker threads.
+    pub fn with_loader(
+        config: StreamConfig,
+        loader: impl Fn(CellCoord) -> C + Send + Sync + 'static,
+    ) -> Self {
+        let pool = LoadPool::new(config.workers, Arc::new(loader));
         Self {
             config,
+            view_direction: None,
+            content_cost: Box::new(|_| 0),
             loaded_cells: HashSet::new(),
+            lods: HashMap::new(),
+            costs: HashMap::new(),
+            evicted: HashMap::new(),
+            failed: HashSet::new(),
+            memory_used: 0,
+            pending: HashSet::new(),
+            desired: HashSet::new(),
+            pool,
             stats: StreamStats::default(),
         }
     }
 
-    /// Update streaming state based on the viewer's current cell position.
-    /// Returns the cells that were loaded and unloaded this frame.
-    /// Respects per-frame load/unload budgets.
+    /// Set the direction the viewer faces, so cells in front of it load
+    /// before equally distant cells behind it; `None` ranks by distance
+    /// only.
+    pub fn set_view_direction(&mut self, direction: Option<glam::Vec3>) {
+        self.view_direction = direction.filter(|d| d.length_squared() > 0.0);
+    }
+
+    /// The direction set with `set_view_direction`.
+    pub fn view_direction(&self) -> Option<glam::Vec3> {
+        self.view_direction
+    }
+
+    /// Estimate the bytes a loaded cell's content holds (e.g. the sizes of
+    /// the meshes it uses), added to the cost of its entities. Content costs
+    /// nothing by default.
+    pub fn set_content_cost(&mut self, cost: impl Fn(&C) -> usize + Send + Sync + 'static) {
+        self.content_cost = Box::new(cost);
+    }
+
+    /// Update streaming state based on the viewer's current cell position:
+    /// unload cells that left the preload radius, schedule loads for the
+    /// nearest missing cells and apply finished loads, each within its
+    /// per-frame budget. Then unloads the farthest cells while loaded cells
+    /// exceed the memory budget and moves the rest to the LOD tier for their
+    /// new distance. Returns the cells loaded, unloaded and re-tiered this
+    /// frame.
+    ///
+    /// Loads still waiting for a worker are re-ranked for the new viewer
+    /// position, and dropped if their cell left the preload radius; loads
+    /// that finish after their cell left it are dropped too.
     pub fn update(
         &mut self,
         viewer_cell: CellCoord,
-        grid: &GridPartition,
-    ) -> (Vec<CellCoord>, Vec<CellCoord>) {
+        grid: &impl SpatialPartition,
+    ) -> Vec<StreamEvent<C>> {
+        self.update_with(viewer_cell, grid, |c| !grid.entities_in_cell(c).is_empty())
+    }
+
+    /// Like `update`, but loads the cells `has_content` accepts instead of
+    /// the cells `grid` holds entities in, for content that is not resident
+    /// (e.g. cells stored on disk).
+    pub fn update_with(
+        &mut self,
+        viewer_cell: CellCoord,
+        grid: &impl SpatialPartition,
+        has_content: impl Fn(CellCoord) -> bool,
+    ) -> Vec<StreamEvent<C>> {
         let _span = tracing::info_span!("stream_update").entered();
         let frame_start = Instant::now();
+        let mut events = Vec::new();
 
         // Determine desired active + preload cells
-        let desired = cells_in_radius(viewer_cell, self.config.preload_radius);
-
-        // Cells to load = desired but not yet loaded
-        let to_load: Vec<CellCoord> = desired
-            .iter()
-            .filter(|c| !self.loaded_cells.contains(c))
-            // Only load cells that actually have content
-            .filter(|c| !grid.entities_in_cell(**c).is_empty())
-            .take(self.config.load_budget)
-            .copied()
-            .collect();
+        self.desired = grid.cells_in_radius(viewer_cell, self.config.preload_radius);
+        self.evicted.retain(|c, _| self.desired.contains(c));
+        self.failed.retain(|c| self.desired.contains(c));
 
         // Cells to unload = loaded but no longer desired
         let to_unload: Vec<CellCoord> = self
             .loaded_cells
             .iter()
-            .filter(|c| !desired.contains(c))
+            .filter(|c| !self.desired.contains(c))
             .take(self.config.unload_budget)
             .copied()
             .collect();
+        for c in to_unload {
+            tracing::debug!(?c, "unloading cell");
+            self.loaded_cells.remove(&c);
+            self.lods.remove(&c);
+            self.memory_used -= self.costs.remove(&c).unwrap_or(0);
+            events.push(StreamEvent::CellUnloaded {
+                coord: c,
+                entities: entity_list(grid, c),
+            });
+        }
 
-        for c in &to_load {
-            tracing::debug!(?c, "loading cell");
-            self.loaded_cells.insert(*c);
+        // Re-rank queued loads for the new viewer position
+        let direction = self.view_direction;
+        let cancelled = self.pool.requeue(|c| {
+            self.desired
+                .contains(&c)
+                .then(|| load_priority(viewer_cell, direction, c))
+        });
+        for c in cancelled {
+            tracing::debug!(?c, "cancelling load of a cell no longer wanted");
+            self.pending.remove(&c);
         }
-        for c in &to_unload {
-            tracing::debug!(?c, "unloading cell");
-            self.loaded_cells.remove(c);
+
+        // Cells to schedule = desired, not loaded or loading, most urgent first
+        let mut candidates: BinaryHeap<_> = self
+            .desired
+            .iter()
+            .filter(|c| {
+                !self.loaded_cells.contains(c)
+                    && !self.pending.contains(c)
+                    && !self.failed.contains(c)
+            })
+            // Only load cells that actually have content
+            .filter(|c| has_content(**c))
+            // Evicted cells come back only once they fit
+            .filter(|c| self.evicted.get(c).is_none_or(|&cost| self.fits(cost)))
+            .map(|&c| Reverse((load_priority(viewer_cell, direction, c), c)))
+            .collect();
+        let mut to_schedule = Vec::new();
+        while to_schedule.len() < self.config.load_budget {
+            let Some(Reverse((priority, c))) = candidates.pop() else {
+                break;
+            };
+            tracing::debug!(?c, priority, "scheduling cell load");
+            self.pending.insert(c);
+            self.pool.schedule(c, priority);
+            to_schedule.push(c);
+        }
+
+        // Apply finished loads
+        let mut applied = 0;
+        while applied < self.config.apply_budget {
+            let Some((coord, content)) = self.pool.try_finished() else {
+                break;
+            };
+            self.pending.remove(&coord);
+            if !self.desired.contains(&coord) {
+                tracing::debug!(?coord, "dropping load of a cell no longer wanted");
+                continue;
+            }
+            let Some(content) = content else {
+                self.failed.insert(coord);
+                events.push(StreamEvent::CellLoadFailed {
+                    coord,
+                    entities: entity_list(grid, coord),
+                });
+                applied += 1;
+                continue;
+            };
+            let cost = grid.entities_in_cell(coord).len() * ENTITY_COST_BYTES
+                + (self.content_cost)(&content);
+            tracing::debug!(?coord, cost, "loaded cell");
+            self.loaded_cells.insert(coord);
+            self.evicted.remove(&coord);
+            self.costs.insert(coord, cost);
+            self.memory_used += cost;
+            events.push(StreamEvent::CellLoaded {
+                coord,
+                entities: entity_list(grid, coord),
+                content,
+            });
+            applied += 1;
+        }
+
+        // Evict the least urgent cells until the rest fit the memory budget
+        let mut evicted = 0;
+        while !self.fits(0) {
+            let Some(&c) = self
+                .loaded_cells
+                .iter()
+                .max_by_key(|&&c| (load_priority(viewer_cell, direction, c), c))
+            else {
+                break;
+            };
+            let cost = self.costs.remove(&c).unwrap_or(0);
+            tracing::debug!(?c, cost, "evicting cell over memory budget");
+            self.loaded_cells.remove(&c);
+            self.lods.remove(&c);
+            self.evicted.insert(c, cost);
+            self.memory_used -= cost;
+            events.push(StreamEvent::CellUnloaded {
+                coord: c,
+                entities: entity_list(grid, c),
+            });
+            evicted += 1;
+        }
+        let unloaded = events.len() - applied;
+
+        // Re-tier loaded cells; newly loaded ones get their first tier here.
+        for &coord in &self.loaded_cells {
+            let to = CellLod::select(&self.config, viewer_cell, coord);
+            match self.lods.insert(coord, to) {
+                Some(from) if from != to => {
+                    tracing::debug!(?coord, ?from, ?to, "cell LOD changed");
+                    events.push(StreamEvent::CellLodChanged {
+                        coord,
+                        from,
+                        to,
+                        entities: entity_list(grid, coord),
+                    });
+                }
+                _ => {}
+            }
         }
 
         self.stats = StreamStats {
-            cells_loaded_this_frame: to_load.len(),
-            cells_unloaded_this_frame: to_unload.len(),
+            cells_loaded_this_frame: applied,
+            cells_unloaded_this_frame: unloaded,
+            cells_evicted_this_frame: evicted,
+            cells_scheduled_this_frame: to_schedule.len(),
+            pending_loads: self.pending.len(),
             total_loaded_cells: self.loaded_cells.len(),
+            memory_used: self.memory_used,
             frame_time: frame_start.elapsed(),
         };
 
         tracing::trace!(
-            loaded = to_load.len(),
-            unloaded = to_unload.len(),
+            loaded = applied,
+            unloaded,
+            evicted,
+            scheduled = to_schedule.len(),
+            pending = self.pending.len(),
             total = self.loaded_cells.len(),
             "stream update complete"
         );
 
-        (to_load, to_unload)
+        events
     }
 
     /// Get the set of currently active cells (within active_radius of the viewer).
     pub fn active_cells(&self, viewer_cell: CellCoord) -> HashSet<CellCoord> {
-        let active = cells_in_radius(viewer_cell, self.config.active_radius);
-        self.loaded_cells.intersection(&active).copied().collect()
+        self.loaded_cells
+            .iter()
+            .filter(|c| viewer_cell.distance(**c) <= self.config.active_radius)
+            .copied()
+            .collect()
+    }
+
+    /// Entities in the loaded `CellLod::Active` cells as of the last update:
+    /// the set to pass to `World::set_active`, so entities in preloaded
+    /// cells stay in memory without being ticked.
+    pub fn active_entities(&self, partition: &impl SpatialPartition) -> BTreeSet<EntityId> {
+        self.lods
+            .iter()
+            .filter(|(_, lod)| **lod == CellLod::Active)
+            .flat_map(|(coord, _)| partition.entities_in_cell(*coord))
+            .collect()
+    }
+
+    /// Hand `active_entities` to `World::set_active`: freeze the resident
+    /// entities of `world` outside the active cells of the last update and
+    /// thaw those back inside them. Schedulers call this every tick, before
+    /// stepping. Returns the entities that froze or thawed.
+    pub fn activate(
+        &self,
+        world: &mut World,
+        partition: &impl SpatialPartition,
+    ) -> Vec<ActivationEvent> {
+        world.set_active(&self.active_entities(partition))
     }
 
     /// Get all currently loaded cells.
@@ -122,6 +479,38 @@ impl StreamState {
         &self.loaded_cells
     }
 
+    /// LOD tier of a loaded cell as of the last update.
+    pub fn lod(&self, coord: CellCoord) -> Option<CellLod> {
+        self.lods.get(&coord).copied()
+    }
+
+    /// LOD tiers of all loaded cells as of the last update.
+    pub fn cell_lods(&self) -> &HashMap<CellCoord, CellLod> {
+        &self.lods
+    }
+
+    /// Cells scheduled for loading whose load has not been applied yet.
+    pub fn pending_cells(&self) -> &HashSet<CellCoord> {
+        &self.pending
+    }
+
+    /// Estimated bytes held by loaded cells.
+    pub fn memory_used(&self) -> usize {
+        self.memory_used
+    }
+
+    /// Estimated bytes a loaded cell holds.
+    pub fn cell_cost(&self, coord: CellCoord) -> Option<usize> {
+        self.costs.get(&coord).copied()
+    }
+
+    /// True if `cost` more bytes stay within the memory budget.
+    fn fits(&self, cost: usize) -> bool {
+        self.config
+            .memory_budget
+            .is_none_or(|budget| self.memory_used + cost <= budget)
+    }
+
     /// Get statistics from the last update.
     pub fn stats(&self) -> &StreamStats {
         &self.stats
@@ -133,15 +522,19 @@ impl StreamState {
     }
 }
 
-/// Compute all cells within a square radius of a center cell.
-fn cells_in_radius(center: CellCoord, radius: i32) -> HashSet<CellCoord> {
-    let mut result = HashSet::new();
-    for dx in -radius..=radius {
-        for dz in -radius..=radius {
-            result.insert(CellCoord::new(center.x + dx, center.z + dz));
-        }
-    }
-    result
+/// Load order of `cell` seen from `viewer`; lower loads first. The squared
+/// distance in cells, scaled from 1x for cells straight ahead of `direction`
+/// to 3x for cells straight behind it.
+fn load_priority(viewer: CellCoord, direction: Option<glam::Vec3>, cell: CellCoord) -> u64 {
+    let offset = glam::Vec3::new(
+        (cell.x - viewer.x) as f32,
+        (cell.y - viewer.y) as f32,
+        (cell.z - viewer.z) as f32,
+    );
+    let facing = direction.map_or(0.0, |d| {
+        offset.normalize_or_zero().dot(d.normalize_or_zero())
+    });
+    (offset.length_squared() * (2.0 - facing) * 256.0).round() as u64
 }
 
 /// Frame time tracker for instrumentation.
@@ -172,7 +565,11 @@ impl FrameTimer {
     }
 
     pub fn average(&self) -> Duration {
-        let count = if self.filled { self.capacity } else { self.index };
+        let count = if self.filled {
+            self.capacity
+        } else {
+            self.index
+        };
         if count == 0 {
             return Duration::ZERO;
         }
@@ -181,7 +578,11 @@ impl FrameTimer {
     }
 
     pub fn max(&self) -> Duration {
-        let count = if self.filled { self.capacity } else { self.index };
+        let count = if self.filled {
+            self.capacity
+        } else {
+            self.index
+        };
         self.history[..count]
             .iter()
             .copied()
@@ -190,7 +591,11 @@ impl FrameTimer {
     }
 
     pub fn min(&self) -> Duration {
-        let count = if self.filled { self.capacity } else { self.index };
+        let count = if self.filled {
+            self.capacity
+        } else {
+            self.index
+        };
         self.history[..count]
             .iter()
             .copied()
@@ -205,11 +610,25 @@ impl FrameTimer {
             self.index
         }
     }
+
+    /// The recorded duration at percentile `p` (0..=100) by nearest rank,
+    /// e.g. `percentile(99.0)` for the p99 frame time.
+    pub fn percentile(&self, p: f64) -> Duration {
+        let mut sorted = self.history[..self.count()].to_vec();
+        if sorted.is_empty() {
+            return Duration::ZERO;
+        }
+        sorted.sort();
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+        sorted[rank.saturating_sub(1)]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::grid::GridPartition;
+    use crate::quadtree::QuadtreePartition;
     use worldspace_common::Transform;
     use worldspace_kernel::World;
 
@@ -228,9 +647,12 @@ mod tests {
     fn stream_config_defaults() {
         let config = StreamConfig::default();
         assert_eq!(config.active_radius, 2);
+        assert_eq!(config.reduced_radius, 3);
         assert_eq!(config.preload_radius, 4);
         assert_eq!(config.load_budget, 4);
         assert_eq!(config.unload_budget, 4);
+        assert_eq!(config.apply_budget, 4);
+        assert_eq!(config.workers, 2);
     }
 
     #[test]
@@ -241,18 +663,23 @@ mod tests {
 
         let config = StreamConfig {
             active_radius: 1,
+            reduced_radius: 1,
             preload_radius: 2,
             load_budget: 2,
             unload_budget: 2,
+            apply_budget: 100,
+            workers: 0,
+            memory_budget: None,
         };
         let mut state = StreamState::new(config);
 
         let viewer = CellCoord::new(0, 0);
-        let (loaded, _unloaded) = state.update(viewer, &grid);
+        let events = state.update(viewer, &grid);
 
         // Should respect load budget of 2
-        assert!(loaded.len() <= 2);
-        assert_eq!(state.stats().cells_loaded_this_frame, loaded.len());
+        assert!(events.len() <= 2);
+        assert_eq!(state.stats().cells_scheduled_this_frame, events.len());
+        assert_eq!(state.stats().cells_loaded_this_frame, events.len());
     }
 
     #[test]
@@ -263,20 +690,43 @@ mod tests {
 
         let config = StreamConfig {
             active_radius: 1,
+            reduced_radius: 1,
             preload_radius: 1,
             load_budget: 100,
             unload_budget: 100,
+            apply_budget: 100,
+            workers: 0,
+            memory_budget: None,
         };
         let mut state = StreamState::new(config);
 
         // Load around origin
-        state.update(CellCoord::new(0, 0), &grid);
+        let events = state.update(CellCoord::new(0, 0), &grid);
         let loaded_at_origin = state.loaded_cells().len();
         assert!(loaded_at_origin > 0);
+        let origin = events
+            .iter()
+            .find(|e| {
+                matches!(e, StreamEvent::CellLoaded { .. }) && e.coord() == CellCoord::new(0, 0)
+            })
+            .unwrap();
+        let mut expected: Vec<_> = grid
+            .entities_in_cell(CellCoord::new(0, 0))
+            .into_iter()
+            .collect();
+        expected.sort();
+        assert_eq!(origin.entities(), expected);
 
         // Move far away - cells at origin should unload
-        let (_loaded, unloaded) = state.update(CellCoord::new(100, 100), &grid);
-        assert!(!unloaded.is_empty() || state.loaded_cells().is_empty());
+        let events = state.update(CellCoord::new(100, 100), &grid);
+        let unloaded = events
+            .iter()
+            .find(|e| {
+                matches!(e, StreamEvent::CellUnloaded { .. }) && e.coord() == CellCoord::new(0, 0)
+            })
+            .unwrap();
+        assert_eq!(unloaded.entities(), expected);
+        assert!(state.loaded_cells().is_empty());
     }
 
     #[test]
@@ -287,9 +737,13 @@ mod tests {
 
         let config = StreamConfig {
             active_radius: 1,
+            reduced_radius: 1,
             preload_radius: 3,
             load_budget: 100,
             unload_budget: 100,
+            apply_budget: 100,
+            workers: 0,
+            memory_budget: None,
         };
         let mut state = StreamState::new(config);
 
@@ -302,6 +756,372 @@ mod tests {
         }
     }
 
+    #[test]
+    fn only_active_cells_are_ticked() {
+        let mut world = make_world_with_entities(10, 8.0);
+        let mut grid = GridPartition::new(16.0);
+        grid.rebuild(&world);
+        let config = StreamConfig {
+            active_radius: 1,
+            preload_radius: 3,
+            workers: 0,
+            ..StreamConfig::default()
+        };
+        let mut state = StreamState::new(config);
+        state.update(CellCoord::new(0, 0), &grid);
+
+        // Cells 0 and 1 are active; cells 2 and 3 are only preloaded.
+        let active = state.active_entities(&grid);
+        assert_eq!(active.len(), 4);
+        let frozen = world.set_active(&active);
+        assert_eq!(frozen.len(), 6);
+        assert_eq!(world.active_entities().count(), 4);
+
+        state.update(CellCoord::new(2, 0), &grid);
+        let events = state.activate(&mut world, &grid);
+        let thawed = events
+            .iter()
+            .filter(|e| matches!(e, worldspace_kernel::ActivationEvent::Thawed(_)))
+            .count();
+        assert_eq!(thawed, 4);
+        assert_eq!(world.active_entities().count(), 6);
+    }
+
+    #[test]
+    fn background_loads_arrive_as_events() {
+        let world = make_world_with_entities(20, 8.0);
+        let mut grid = GridPartition::new(16.0);
+        grid.rebuild(&world);
+
+        let config = StreamConfig {
+            active_radius: 1,
+            reduced_radius: 1,
+            preload_radius: 2,
+            load_budget: 100,
+            unload_budget: 100,
+            apply_budget: 1,
+            workers: 2,
+            memory_budget: None,
+        };
+        let mut state = StreamState::with_loader(config, |c: CellCoord| c.x * 100 + c.z);
+        let viewer = CellCoord::new(0, 0);
+
+        let mut loaded = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            for event in state.update(viewer, &grid) {
+                if let StreamEvent::CellLoaded { coord, content, .. } = event {
+                    assert_eq!(content, coord.x * 100 + coord.z);
+                    loaded.push(coord);
+                }
+            }
+            assert!(state.stats().cells_loaded_this_frame <= 1);
+            if state.pending_cells().is_empty() {
+                break;
+            }
+            assert!(Instant::now() < deadline, "cells never finished loading");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        // Cells 0, 1 and 2 along +x hold entities within the preload radius.
+        loaded.sort_by_key(|c| c.x);
+        assert_eq!(
+            loaded,
+            vec![
+                CellCoord::new(0, 0),
+                CellCoord::new(1, 0),
+                CellCoord::new(2, 0)
+            ]
+        );
+        assert_eq!(state.loaded_cells().len(), 3);
+    }
+
+    #[test]
+    fn loads_finishing_after_the_viewer_left_are_dropped() {
+        let world = make_world_with_entities(20, 8.0);
+        let mut grid = GridPartition::new(16.0);
+        grid.rebuild(&world);
+
+        let config = StreamConfig {
+            active_radius: 1,
+            reduced_radius: 1,
+            preload_radius: 2,
+            load_budget: 100,
+            unload_budget: 100,
+            apply_budget: 1,
+            workers: 0,
+            memory_budget: None,
+        };
+        let mut state = StreamState::new(config);
+        let events = state.update(CellCoord::new(0, 0), &grid);
+        assert_eq!(events.len(), 1);
+        assert_eq!(state.pending_cells().len(), 2);
+
+        let events = state.update(CellCoord::new(100, 100), &grid);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], StreamEvent::CellUnloaded { .. }));
+        assert!(state.pending_cells().is_empty());
+        assert!(state.loaded_cells().is_empty());
+    }
+
+    #[test]
+    fn loaded_cells_change_lod_as_viewer_moves() {
+        let world = make_world_with_entities(20, 8.0);
+        let mut grid = GridPartition::new(16.0);
+        grid.rebuild(&world);
+
+        let config = StreamConfig {
+            active_radius: 0,
+            reduced_radius: 1,
+            preload_radius: 3,
+            load_budget: 100,
+            unload_budget: 100,
+            apply_budget: 100,
+            workers: 0,
+            memory_budget: None,
+        };
+        let mut state = StreamState::new(config);
+        state.update(CellCoord::new(0, 0), &grid);
+        assert_eq!(state.lod(CellCoord::new(0, 0)), Some(CellLod::Active));
+        assert_eq!(state.lod(CellCoord::new(1, 0)), Some(CellLod::Reduced));
+        assert_eq!(state.lod(CellCoord::new(2, 0)), Some(CellLod::Impostor));
+        assert_eq!(state.cell_lods().len(), state.loaded_cells().len());
+
+        let events = state.update(CellCoord::new(2, 0), &grid);
+        let changes: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::CellLodChanged {
+                    coord, from, to, ..
+                } => Some((coord.x, *from, *to)),
+                _ => None,
+            })
+            .collect();
+        assert!(changes.contains(&(2, CellLod::Impostor, CellLod::Active)));
+        assert!(changes.contains(&(0, CellLod::Active, CellLod::Impostor)));
+        assert!(!changes.iter().any(|&(x, ..)| x == 1));
+    }
+
+    #[test]
+    fn layered_grid_streams_by_height() {
+        let mut world = World::new();
+        for y in [0.0, 20.0, 100.0] {
+            world.spawn(Transform {
+                position: glam::Vec3::new(0.0, y, 0.0),
+                ..Transform::default()
+            });
+        }
+        let mut grid = GridPartition::new_3d(16.0, 16.0);
+        grid.rebuild(&world);
+
+        let config = StreamConfig {
+            active_radius: 0,
+            reduced_radius: 1,
+            preload_radius: 1,
+            load_budget: 100,
+            unload_budget: 100,
+            apply_budget: 100,
+            workers: 0,
+            memory_budget: None,
+        };
+        let mut state = StreamState::new(config);
+        state.update(CellCoord::new(0, 0), &grid);
+        let mut loaded: Vec<_> = state.loaded_cells().iter().map(|c| c.y).collect();
+        loaded.sort();
+        assert_eq!(loaded, vec![0, 1]);
+        assert_eq!(
+            state.lod(CellCoord::new_3d(0, 1, 0)),
+            Some(CellLod::Reduced)
+        );
+
+        // A flying viewer streams the layer it is on, not the ground.
+        state.update(CellCoord::new_3d(0, 6, 0), &grid);
+        let loaded: Vec<_> = state.loaded_cells().iter().copied().collect();
+        assert_eq!(loaded, vec![CellCoord::new_3d(0, 6, 0)]);
+    }
+
+    #[test]
+    fn view_direction_loads_cells_in_front_first() {
+        let mut world = World::new();
+        for x in [-16.0, 16.0] {
+            world.spawn(Transform {
+                position: glam::Vec3::new(x, 0.0, 0.0),
+                ..Transform::default()
+            });
+        }
+        let mut grid = GridPartition::new(16.0);
+        grid.rebuild(&world);
+
+        let config = StreamConfig {
+            load_budget: 1,
+            workers: 0,
+            ..StreamConfig::default()
+        };
+        let (ahead, behind) = (CellCoord::new(1, 0), CellCoord::new(-1, 0));
+        for (direction, first) in [(glam::Vec3::X, ahead), (glam::Vec3::NEG_X, behind)] {
+            let mut state = StreamState::new(config.clone());
+            state.set_view_direction(Some(direction));
+            let events = state.update(CellCoord::new(0, 0), &grid);
+            assert!(
+                events
+                    .iter()
+                    .any(|e| matches!(e, StreamEvent::CellLoaded { coord, .. } if *coord == first))
+            );
+            assert_eq!(state.loaded_cells().len(), 1);
+        }
+
+        let viewer = CellCoord::new(0, 0);
+        let far_ahead = load_priority(viewer, Some(glam::Vec3::X), CellCoord::new(2, 0));
+        let near_behind = load_priority(viewer, Some(glam::Vec3::X), behind);
+        assert!(near_behind < far_ahead);
+        assert_eq!(load_priority(viewer, None, viewer), 0);
+    }
+
+    #[test]
+    fn queued_loads_follow_the_viewer() {
+        // Hold the only worker on its first cell while the rest queue up.
+        let gate = Arc::new(std::sync::Mutex::new(()));
+        let held = gate.lock().unwrap();
+        let pool = {
+            let gate = Arc::clone(&gate);
+            LoadPool::new(
+                1,
+                Arc::new(move |c: CellCoord| {
+                    drop(gate.lock().unwrap());
+                    c
+                }),
+            )
+        };
+        for x in 0..=3 {
+            pool.schedule(CellCoord::new(x, 0), x as u64);
+        }
+        // The viewer turned around: cell 2 is no longer wanted and cell 3
+        // is now more urgent than cell 1.
+        let cancelled = pool.requeue(|c| match c.x {
+            0 => Some(0),
+            2 => None,
+            x => Some(10 - x as u64),
+        });
+        assert_eq!(cancelled, vec![CellCoord::new(2, 0)]);
+        drop(held);
+
+        let mut finished = Vec::new();
+        while finished.len() < 3 {
+            match pool.try_finished() {
+                Some((c, _)) => finished.push(c.x),
+                None => std::thread::yield_now(),
+            }
+        }
+        assert_eq!(finished, vec![0, 3, 1]);
+    }
+
+    #[test]
+    fn panicking_loader_fails_only_its_cell() {
+        let world = make_world_with_entities(20, 8.0);
+        let mut grid = GridPartition::new(16.0);
+        grid.rebuild(&world);
+
+        let config = StreamConfig {
+            preload_radius: 2,
+            load_budget: 100,
+            apply_budget: 100,
+            workers: 1,
+            ..StreamConfig::default()
+        };
+        let bad = CellCoord::new(1, 0);
+        let mut state = StreamState::with_loader(config, move |c: CellCoord| {
+            assert_ne!(c, bad, "corrupt cell");
+        });
+        let viewer = CellCoord::new(0, 0);
+
+        let mut failed = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            for event in state.update(viewer, &grid) {
+                if let StreamEvent::CellLoadFailed { coord, .. } = event {
+                    failed.push(coord);
+                }
+            }
+            if state.pending_cells().is_empty() {
+                break;
+            }
+            assert!(Instant::now() < deadline, "cells never finished loading");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        // The worker survived the panic and loaded the cells around it.
+        assert_eq!(failed, vec![bad]);
+        assert!(!state.is_loaded(bad));
+        assert!(state.is_loaded(CellCoord::new(0, 0)) && state.is_loaded(CellCoord::new(2, 0)));
+
+        // The failed cell is not retried while it stays in range.
+        assert!(state.update(viewer, &grid).is_empty());
+        assert!(state.pending_cells().is_empty());
+    }
+
+    #[test]
+    fn memory_budget_evicts_farthest_cells() {
+        let world = make_world_with_entities(4, 16.0);
+        let mut grid = GridPartition::new(16.0);
+        grid.rebuild(&world);
+
+        let config = StreamConfig {
+            load_budget: 100,
+            apply_budget: 100,
+            workers: 0,
+            memory_budget: Some(3 * ENTITY_COST_BYTES + 250),
+            ..StreamConfig::default()
+        };
+        let mut state = StreamState::with_loader(config, |c: CellCoord| c.x as usize * 100);
+        state.set_content_cost(|bytes| *bytes);
+        let events = state.update(CellCoord::new(0, 0), &grid);
+        // Cells 0 and 1 cost 0 and 100 content bytes; cells 2 and 3 do not fit.
+        let mut loaded: Vec<_> = state.loaded_cells().iter().map(|c| c.x).collect();
+        loaded.sort();
+        assert_eq!(loaded, vec![0, 1]);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            StreamEvent::CellUnloaded { coord, .. } if *coord == CellCoord::new(3, 0)
+        )));
+        assert_eq!(state.stats().cells_evicted_this_frame, 2);
+        assert_eq!(state.memory_used(), 2 * ENTITY_COST_BYTES + 100);
+        assert_eq!(
+            state.cell_cost(CellCoord::new(1, 0)),
+            Some(ENTITY_COST_BYTES + 100)
+        );
+
+        // Evicted cells stay unloaded while they do not fit.
+        let events = state.update(CellCoord::new(0, 0), &grid);
+        assert!(events.is_empty());
+
+        // Moving away unloads the near cells, so the far one fits again.
+        state.update(CellCoord::new(7, 0), &grid);
+        let loaded: Vec<_> = state.loaded_cells().iter().copied().collect();
+        assert_eq!(loaded, vec![CellCoord::new(3, 0)]);
+        assert_eq!(state.memory_used(), ENTITY_COST_BYTES + 300);
+    }
+
+    #[test]
+    fn streams_over_a_quadtree() {
+        let world = make_world_with_entities(20, 8.0);
+        let mut grid = GridPartition::new(16.0);
+        grid.rebuild(&world);
+        let mut tree = QuadtreePartition::new(16.0);
+        tree.rebuild(&world);
+
+        let config = StreamConfig {
+            load_budget: 100,
+            apply_budget: 100,
+            workers: 0,
+            ..StreamConfig::default()
+        };
+        let mut from_grid = StreamState::new(config.clone());
+        let mut from_tree = StreamState::new(config);
+        for viewer in [CellCoord::new(0, 0), CellCoord::new(6, 0)] {
+            from_grid.update(viewer, &grid);
+            from_tree.update(viewer, &tree);
+            assert_eq!(from_tree.loaded_cells(), from_grid.loaded_cells());
+        }
+    }
+
     #[test]
     fn frame_timer_tracks_history() {
         let mut timer = FrameTimer::new(3);
@@ -326,4 +1146,17 @@ mod tests {
         // Should contain 20 and 30
         assert_eq!(timer.average(), Duration::from_millis(25));
     }
+
+    #[test]
+    fn frame_timer_percentiles() {
+        let mut timer = FrameTimer::new(100);
+        assert_eq!(timer.percentile(99.0), Duration::ZERO);
+        for ms in (1..=100).rev() {
+            timer.record(Duration::from_millis(ms));
+        }
+        assert_eq!(timer.percentile(50.0), Duration::from_millis(50));
+        assert_eq!(timer.percentile(99.0), Duration::from_millis(99));
+        assert_eq!(timer.percentile(100.0), Duration::from_millis(100));
+        assert_eq!(timer.percentile(0.0), Duration::from_millis(1));
+    }
 }
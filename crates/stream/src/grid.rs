@@ -1,37 +1,90 @@
 use std::collections::{HashMap, HashSet};
-use worldspace_common::EntityId;
+use worldspace_common::{EntityId, Frustum};
 use worldspace_kernel::World;
 
-/// A 2D cell coordinate in the world grid (ignoring Y axis for partitioning).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A cell coordinate in the world grid. `y` is the vertical layer; it is
+/// always 0 in a flat (XZ-only) partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct CellCoord {
     pub x: i32,
+    pub y: i32,
     pub z: i32,
 }
 
 impl CellCoord {
+    /// A cell on layer 0, the only layer of a flat partition.
     pub fn new(x: i32, z: i32) -> Self {
-        Self { x, z }
+        Self { x, y: 0, z }
     }
+
+    /// A cell on layer `y` of a layered partition.
+    pub fn new_3d(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Distance in cells along the axis where the cells are furthest apart,
+    /// so the cells within `r` of a center form a square (or cube).
+    pub fn distance(self, other: CellCoord) -> i32 {
+        (self.x - other.x)
+            .abs()
+            .max((self.y - other.y).abs())
+            .max((self.z - other.z).abs())
+    }
+}
+
+/// An entity found by `GridPartition::raycast`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub entity: EntityId,
+    /// Cell the entity is in.
+    pub cell: CellCoord,
+    /// Distance along the ray to the entity's closest approach.
+    pub distance: f32,
+}
+
+/// A partition of the world into cells that streaming can run over.
+pub trait SpatialPartition {
+    /// All cells within `radius` (in cells) of `center`, occupied or not.
+    fn cells_in_radius(&self, center: CellCoord, radius: i32) -> HashSet<CellCoord>;
+
+    /// All entity IDs in a specific cell.
+    fn entities_in_cell(&self, coord: CellCoord) -> HashSet<EntityId>;
 }
 
 /// Fixed-size grid partitioning of the world.
 ///
 /// Workaround for the full LOD/async streaming system. Entities are assigned
-/// to cells based on their XZ position divided by cell_size. Cells can be
-/// queried by coordinate or within a radius of a point.
+/// to cells based on their XZ position divided by cell_size. A layered
+/// partition also divides Y by the layer height, so tall structures, caves
+/// and flying viewers get cells of their own. Cells can be queried by
+/// coordinate or within a radius of a point.
 pub struct GridPartition {
     cell_size: f32,
+    layer_height: Option<f32>,
     cells: HashMap<CellCoord, HashSet<EntityId>>,
+    /// Entity positions as of the last rebuild, for ray and distance queries.
+    positions: HashMap<EntityId, glam::Vec3>,
 }
 
 impl GridPartition {
-    /// Create a new grid partition with the given cell size.
+    /// Create a new flat grid partition with the given cell size.
     pub fn new(cell_size: f32) -> Self {
         assert!(cell_size > 0.0, "cell_size must be positive");
         Self {
             cell_size,
+            layer_height: None,
             cells: HashMap::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Create a grid partition that is also split into vertical layers of
+    /// `layer_height`.
+    pub fn new_3d(cell_size: f32, layer_height: f32) -> Self {
+        assert!(layer_height > 0.0, "layer_height must be positive");
+        Self {
+            layer_height: Some(layer_height),
+            ..Self::new(cell_size)
         }
     }
 
@@ -40,12 +93,19 @@ impl GridPartition {
         self.cell_size
     }
 
+    /// Height of the vertical layers; `None` for a flat partition.
+    pub fn layer_height(&self) -> Option<f32> {
+        self.layer_height
+    }
+
     /// Rebuild the entire grid from the current world state.
     pub fn rebuild(&mut self, world: &World) {
         self.cells.clear();
+        self.positions.clear();
         for (id, data) in world.entities() {
             let coord = self.position_to_cell(data.transform.position);
             self.cells.entry(coord).or_default().insert(*id);
+            self.positions.insert(*id, data.transform.position);
         }
     }
 
@@ -53,10 +113,66 @@ impl GridPartition {
     pub fn position_to_cell(&self, pos: glam::Vec3) -> CellCoord {
         CellCoord {
             x: (pos.x / self.cell_size).floor() as i32,
+            y: self
+                .layer_height
+                .map_or(0, |height| (pos.y / height).floor() as i32),
             z: (pos.z / self.cell_size).floor() as i32,
         }
     }
 
+    /// All cells within `radius` (in cells) of `center`: a square on a flat
+    /// partition, a cube on a layered one.
+    pub fn cells_in_radius(&self, center: CellCoord, radius: i32) -> HashSet<CellCoord> {
+        let layers = if self.layer_height.is_some() {
+            -radius..=radius
+        } else {
+            0..=0
+        };
+        let mut result = HashSet::new();
+        for dx in -radius..=radius {
+            for dy in layers.clone() {
+                for dz in -radius..=radius {
+                    result.insert(CellCoord::new_3d(
+                        center.x + dx,
+                        center.y + dy,
+                        center.z + dz,
+                    ));
+                }
+            }
+        }
+        result
+    }
+
+    /// Cells whose bounds intersect `frustum`, occupied or not. On a flat
+    /// partition each cell is a column spanning the frustum's height. Like
+    /// `Frustum::intersects_aabb`, the result is conservative near the edges
+    /// of the volume.
+    pub fn cells_in_frustum(&self, frustum: &Frustum) -> HashSet<CellCoord> {
+        let (min, max) = frustum.bounds();
+        let (low, high) = (self.position_to_cell(min), self.position_to_cell(max));
+        // Flat cells are columns over the frustum's height.
+        let (height, floor, ceiling) = match self.layer_height {
+            Some(height) => (height, None, None),
+            None => (1.0, Some(min.y), Some(max.y)),
+        };
+        let size = glam::Vec3::new(self.cell_size, height, self.cell_size);
+        let mut result = HashSet::new();
+        for x in low.x..=high.x {
+            for y in low.y..=high.y {
+                for z in low.z..=high.z {
+                    let mut cell_min = glam::Vec3::new(x as f32, y as f32, z as f32) * size;
+                    let mut cell_max = cell_min + size;
+                    cell_min.y = floor.unwrap_or(cell_min.y);
+                    cell_max.y = ceiling.unwrap_or(cell_max.y);
+                    if frustum.intersects_aabb(cell_min, cell_max) {
+                        result.insert(CellCoord::new_3d(x, y, z));
+                    }
+                }
+            }
+        }
+        result
+    }
+
     /// Get all entity IDs in a specific cell.
     pub fn entities_in_cell(&self, coord: CellCoord) -> HashSet<EntityId> {
         self.cells.get(&coord).cloned().unwrap_or_default()
@@ -65,17 +181,172 @@ impl GridPartition {
     /// Get all entity IDs within a radius (in cells) of a center cell.
     pub fn entities_in_radius(&self, center: CellCoord, radius: i32) -> HashSet<EntityId> {
         let mut result = HashSet::new();
-        for dx in -radius..=radius {
-            for dz in -radius..=radius {
-                let coord = CellCoord::new(center.x + dx, center.z + dz);
-                if let Some(entities) = self.cells.get(&coord) {
-                    result.extend(entities);
-                }
+        for coord in self.cells_in_radius(center, radius) {
+            if let Some(entities) = self.cells.get(&coord) {
+                result.extend(entities);
             }
         }
         result
     }
 
+    /// Entities in the cells a ray from `origin` along `dir` passes through
+    /// within `max_dist`, whose closest approach to the ray lies between
+    /// `origin` and `max_dist`. Entities are points, so this is the broad
+    /// phase of picking: hits come nearest first (ties in EntityId order)
+    /// for the caller to test against bounds. A flat partition walks the
+    /// XZ cells under the ray.
+    pub fn raycast(&self, origin: glam::Vec3, dir: glam::Vec3, max_dist: f32) -> Vec<RayHit> {
+        let dir = dir.normalize_or_zero();
+        if dir == glam::Vec3::ZERO || max_dist < 0.0 {
+            return Vec::new();
+        }
+        let mut hits = Vec::new();
+        for cell in self.cells_along_ray(origin, dir, max_dist) {
+            let Some(entities) = self.cells.get(&cell) else {
+                continue;
+            };
+            for id in entities {
+                let distance = (self.positions[id] - origin).dot(dir);
+                if (0.0..=max_dist).contains(&distance) {
+                    hits.push(RayHit {
+                        entity: *id,
+                        cell,
+                        distance,
+                    });
+                }
+            }
+        }
+        hits.sort_by(|a, b| {
+            a.distance
+                .total_cmp(&b.distance)
+                .then(a.entity.cmp(&b.entity))
+        });
+        hits
+    }
+
+    /// The entity closest to `point` within `max_radius`, and its distance.
+    /// Cells are searched in rings of growing radius around `point`'s cell,
+    /// stopping once no farther ring can hold a closer entity. Equally
+    /// distant entities resolve to the lowest EntityId.
+    pub fn nearest_entity(&self, point: glam::Vec3, max_radius: f32) -> Option<(EntityId, f32)> {
+        if self.cells.is_empty() || max_radius < 0.0 {
+            return None;
+        }
+        // Ring `r` lies at least `r - 1` cells away from `point` on some axis.
+        let extent = self
+            .layer_height
+            .map_or(self.cell_size, |height| height.min(self.cell_size));
+        let center = self.position_to_cell(point);
+        let reach = self
+            .cells
+            .keys()
+            .map(|c| center.distance(*c))
+            .max()
+            .unwrap_or(0);
+        let mut best: Option<(EntityId, f32)> = None;
+        for ring in 0..=reach {
+            let bound = (ring - 1).max(0) as f32 * extent;
+            if bound > max_radius || best.is_some_and(|(_, d)| d <= bound) {
+                break;
+            }
+            for cell in self.ring_cells(center, ring) {
+                let Some(entities) = self.cells.get(&cell) else {
+                    continue;
+                };
+                for id in entities {
+                    let distance = self.positions[id].distance(point);
+                    let closer = best.is_none_or(|(best_id, best_distance)| {
+                        distance < best_distance || (distance == best_distance && *id < best_id)
+                    });
+                    if distance <= max_radius && closer {
+                        best = Some((*id, distance));
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Cells a ray crosses within `max_dist`, in order, by stepping to the
+    /// nearest cell boundary on each iteration. `dir` must be normalized.
+    fn cells_along_ray(
+        &self,
+        origin: glam::Vec3,
+        dir: glam::Vec3,
+        max_dist: f32,
+    ) -> Vec<CellCoord> {
+        let mut cell = self.position_to_cell(origin);
+        let cell_index = [cell.x, cell.y, cell.z];
+        let sizes = [
+            Some(self.cell_size),
+            self.layer_height,
+            Some(self.cell_size),
+        ];
+        let (origin, dir) = (origin.to_array(), dir.to_array());
+        let mut step = [0; 3];
+        let mut t_max = [f32::INFINITY; 3];
+        let mut t_delta = [f32::INFINITY; 3];
+        for axis in 0..3 {
+            let Some(size) = sizes[axis] else {
+                continue;
+            };
+            if dir[axis] == 0.0 {
+                continue;
+            }
+            step[axis] = if dir[axis] > 0.0 { 1 } else { -1 };
+            let boundary = (cell_index[axis] + i32::from(dir[axis] > 0.0)) as f32 * size;
+            t_max[axis] = (boundary - origin[axis]) / dir[axis];
+            t_delta[axis] = size / dir[axis].abs();
+        }
+
+        let mut cells = vec![cell];
+        loop {
+            let axis = (0..3)
+                .min_by(|a, b| t_max[*a].total_cmp(&t_max[*b]))
+                .expect("three axes");
+            if t_max[axis] > max_dist {
+                return cells;
+            }
+            match axis {
+                0 => cell.x += step[0],
+                1 => cell.y += step[1],
+                _ => cell.z += step[2],
+            }
+            t_max[axis] += t_delta[axis];
+            cells.push(cell);
+        }
+    }
+
+    /// Cells exactly `ring` cells from `center`: the border of the square
+    /// (or cube, on a layered partition) `cells_in_radius` returns.
+    fn ring_cells(&self, center: CellCoord, ring: i32) -> Vec<CellCoord> {
+        let layers = if self.layer_height.is_some() {
+            -ring..=ring
+        } else {
+            0..=0
+        };
+        let mut cells = Vec::new();
+        for dx in -ring..=ring {
+            for dy in layers.clone() {
+                for dz in -ring..=ring {
+                    if dx.abs().max(dy.abs()).max(dz.abs()) == ring {
+                        cells.push(CellCoord::new_3d(
+                            center.x + dx,
+                            center.y + dy,
+                            center.z + dz,
+                        ));
+                    }
+                }
+            }
+        }
+        cells
+    }
+
+    /// Coordinates of the non-empty cells, in no particular order.
+    pub fn occupied_cells(&self) -> impl Iterator<Item = CellCoord> + '_ {
+        self.cells.keys().copied()
+    }
+
     /// Number of non-empty cells.
     pub fn cell_count(&self) -> usize {
         self.cells.len()
@@ -87,6 +358,16 @@ impl GridPartition {
     }
 }
 
+impl SpatialPartition for GridPartition {
+    fn cells_in_radius(&self, center: CellCoord, radius: i32) -> HashSet<CellCoord> {
+        self.cells_in_radius(center, radius)
+    }
+
+    fn entities_in_cell(&self, coord: CellCoord) -> HashSet<EntityId> {
+        self.entities_in_cell(coord)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,6 +397,9 @@ mod tests {
 
         assert_eq!(grid.cell_count(), 2);
         assert_eq!(grid.total_placements(), 2);
+        let mut occupied: Vec<_> = grid.occupied_cells().collect();
+        occupied.sort();
+        assert_eq!(occupied, vec![CellCoord::new(0, 0), CellCoord::new(1, 0)]);
     }
 
     #[test]
@@ -150,6 +434,166 @@ mod tests {
         assert!(far.is_empty());
     }
 
+    #[test]
+    fn layered_partition_separates_heights() {
+        let mut world = World::new();
+        let ground = world.spawn(Transform::default());
+        let roof = world.spawn(Transform {
+            position: glam::Vec3::new(0.0, 40.0, 0.0),
+            ..Transform::default()
+        });
+
+        let mut flat = GridPartition::new(16.0);
+        flat.rebuild(&world);
+        assert_eq!(flat.cell_count(), 1);
+
+        let mut grid = GridPartition::new_3d(16.0, 16.0);
+        grid.rebuild(&world);
+        assert_eq!(grid.cell_count(), 2);
+        assert_eq!(
+            grid.position_to_cell(glam::Vec3::new(0.0, -1.0, 0.0)),
+            CellCoord::new_3d(0, -1, 0)
+        );
+        assert!(
+            grid.entities_in_cell(CellCoord::new(0, 0))
+                .contains(&ground)
+        );
+        assert!(
+            grid.entities_in_cell(CellCoord::new_3d(0, 2, 0))
+                .contains(&roof)
+        );
+
+        let near_ground = grid.entities_in_radius(CellCoord::new(0, 0), 1);
+        assert!(near_ground.contains(&ground) && !near_ground.contains(&roof));
+        assert_eq!(grid.cells_in_radius(CellCoord::new(0, 0), 1).len(), 27);
+        assert_eq!(flat.cells_in_radius(CellCoord::new(0, 0), 1).len(), 9);
+        assert_eq!(
+            CellCoord::new_3d(0, 2, 0).distance(CellCoord::new(-1, 1)),
+            2
+        );
+    }
+
+    fn spawn_at(world: &mut World, x: f32, y: f32, z: f32) -> EntityId {
+        world.spawn(Transform {
+            position: glam::Vec3::new(x, y, z),
+            ..Transform::default()
+        })
+    }
+
+    #[test]
+    fn raycast_walks_cells_nearest_first() {
+        let mut world = World::new();
+        let far = spawn_at(&mut world, 50.0, 0.0, 1.0);
+        let near = spawn_at(&mut world, 10.0, 0.0, 2.0);
+        let behind = spawn_at(&mut world, -10.0, 0.0, 0.0);
+        let off_ray = spawn_at(&mut world, 30.0, 0.0, 40.0);
+        let beyond = spawn_at(&mut world, 90.0, 0.0, 0.0);
+        let mut grid = GridPartition::new(16.0);
+        grid.rebuild(&world);
+
+        let hits = grid.raycast(glam::Vec3::new(0.0, 5.0, 0.0), glam::Vec3::X, 80.0);
+        let ids: Vec<EntityId> = hits.iter().map(|h| h.entity).collect();
+        assert_eq!(ids, vec![near, far]);
+        assert_eq!(hits[1].cell, CellCoord::new(3, 0));
+        assert!((hits[0].distance - 10.0).abs() < 1e-5);
+        for id in [behind, off_ray, beyond] {
+            assert!(!ids.contains(&id));
+        }
+
+        // A diagonal ray crosses into the off-ray entity's cell; `near`
+        // shares the origin cell.
+        let diagonal = glam::Vec3::new(30.0, 0.0, 40.0);
+        let hits = grid.raycast(glam::Vec3::ZERO, diagonal, 100.0);
+        assert_eq!(
+            hits.iter().map(|h| h.entity).collect::<Vec<_>>(),
+            vec![near, off_ray]
+        );
+        assert!(
+            grid.raycast(glam::Vec3::ZERO, glam::Vec3::ZERO, 10.0)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn raycast_steps_through_layers() {
+        let mut world = World::new();
+        let below = spawn_at(&mut world, 1.0, -12.0, 1.0);
+        let above = spawn_at(&mut world, 1.0, 30.0, 1.0);
+        let mut grid = GridPartition::new_3d(16.0, 8.0);
+        grid.rebuild(&world);
+
+        let hits = grid.raycast(glam::Vec3::new(1.0, 0.0, 1.0), glam::Vec3::NEG_Y, 100.0);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entity, below);
+        assert_eq!(hits[0].cell, CellCoord::new_3d(0, -2, 0));
+        let hits = grid.raycast(glam::Vec3::new(1.0, 0.0, 1.0), glam::Vec3::Y, 20.0);
+        assert!(hits.is_empty());
+        let hits = grid.raycast(glam::Vec3::new(1.0, 0.0, 1.0), glam::Vec3::Y, 40.0);
+        assert_eq!(hits[0].entity, above);
+    }
+
+    #[test]
+    fn nearest_entity_searches_rings() {
+        let mut world = World::new();
+        let a = spawn_at(&mut world, 40.0, 0.0, 0.0);
+        let b = spawn_at(&mut world, -17.0, 0.0, 30.0);
+        spawn_at(&mut world, 300.0, 0.0, 300.0);
+        let mut grid = GridPartition::new(16.0);
+        grid.rebuild(&world);
+
+        let (id, distance) = grid.nearest_entity(glam::Vec3::ZERO, 100.0).unwrap();
+        assert_eq!(id, b);
+        assert!((distance - (17.0f32 * 17.0 + 30.0 * 30.0).sqrt()).abs() < 1e-4);
+        assert_eq!(
+            grid.nearest_entity(glam::Vec3::new(30.0, 0.0, 0.0), 100.0)
+                .map(|n| n.0),
+            Some(a)
+        );
+        assert!(grid.nearest_entity(glam::Vec3::ZERO, 30.0).is_none());
+        assert!(
+            grid.nearest_entity(glam::Vec3::ZERO, f32::INFINITY)
+                .is_some()
+        );
+
+        // Equally distant entities resolve to the lowest id.
+        let mut world = World::new();
+        let left = spawn_at(&mut world, -5.0, 0.0, 0.0);
+        let right = spawn_at(&mut world, 5.0, 0.0, 0.0);
+        grid.rebuild(&world);
+        let expected = left.min(right);
+        assert_eq!(
+            grid.nearest_entity(glam::Vec3::ZERO, 10.0).map(|n| n.0),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn frustum_selects_cells_in_view() {
+        // Looking along +X from above the origin, 90 degrees wide, out to 100.
+        let eye = glam::Vec3::new(0.0, 8.0, 8.0);
+        let view = glam::Mat4::look_at_rh(eye, eye + glam::Vec3::X, glam::Vec3::Y);
+        let projection = glam::Mat4::perspective_rh(90f32.to_radians(), 1.0, 0.5, 100.0);
+        let frustum = Frustum::from_view_projection(projection * view);
+
+        let grid = GridPartition::new(16.0);
+        let cells = grid.cells_in_frustum(&frustum);
+        assert!(cells.contains(&CellCoord::new(0, 0)));
+        assert!(cells.contains(&CellCoord::new(5, 0)));
+        // The volume widens with distance.
+        assert!(cells.contains(&CellCoord::new(5, 4)));
+        assert!(!cells.contains(&CellCoord::new(1, 4)));
+        // Behind the camera and past the far plane.
+        assert!(!cells.contains(&CellCoord::new(-2, 0)));
+        assert!(!cells.contains(&CellCoord::new(8, 0)));
+        assert!(cells.iter().all(|c| c.y == 0));
+
+        let layered = GridPartition::new_3d(16.0, 16.0);
+        let cells = layered.cells_in_frustum(&frustum);
+        assert!(cells.contains(&CellCoord::new_3d(3, 0, 0)));
+        assert!(cells.contains(&CellCoord::new_3d(5, 3, 0)));
+        assert!(!cells.contains(&CellCoord::new_3d(1, 3, 0)));
+    }
+
     #[test]
     fn empty_cell_returns_empty_set() {
         let grid = GridPartition::new(16.0);
@@ -6,14 +6,28 @@
 //!
 //! # Workaround
 //! Implements a simple fixed-size grid partitioning scheme as a workaround for
-//! a full LOD and async streaming system. Entities are assigned to cells based
-//! on position; cells can be queried by coordinate or radius.
+//! a full LOD system. Entities are assigned to cells based on position; cells
+//! can be queried by coordinate or radius; `QuadtreePartition` offers the
+//! same queries over a quadtree for sparse worlds. Cell content is loaded on
+//! background threads and applied within per-frame budgets, and each loaded
+//! cell gets a coarse LOD tier by distance from the viewer.
 
+mod adaptive;
 mod budget;
 mod grid;
+mod loader;
+mod lod;
+mod quadtree;
+mod serializer;
 
-pub use budget::{FrameTimer, StreamConfig, StreamState, StreamStats};
-pub use grid::{CellCoord, GridPartition};
+pub use adaptive::{AdaptiveConfig, AdaptivePartition, CellRegion, RebuildReport};
+pub use budget::{
+    ENTITY_COST_BYTES, FrameTimer, StreamConfig, StreamEvent, StreamState, StreamStats,
+};
+pub use grid::{CellCoord, GridPartition, RayHit, SpatialPartition};
+pub use lod::CellLod;
+pub use quadtree::QuadtreePartition;
+pub use serializer::{CellBlob, CellBlobError, CellSerializer};
 
 pub fn crate_info() -> &'static str {
     "worldspace-stream v0.1.0"
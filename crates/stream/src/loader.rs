@@ -0,0 +1,142 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+
+use crate::grid::CellCoord;
+
+type Loader<C> = Arc<dyn Fn(CellCoord) -> C + Send + Sync>;
+
+/// Cells waiting for a worker, lowest priority value first.
+#[derive(Default)]
+struct JobQueue {
+    jobs: BinaryHeap<Reverse<(u64, CellCoord)>>,
+    closed: bool,
+}
+
+type SharedQueue = Arc<(Mutex<JobQueue>, Condvar)>;
+
+/// A finished cell and its content, `None` if the loader panicked.
+type Finished<C> = (CellCoord, Option<C>);
+
+/// Worker threads that run a cell loader and report finished cells.
+///
+/// Workers take the queued cell with the lowest priority value next, and
+/// `requeue` re-ranks the queue as the viewer moves. With zero workers,
+/// cells are loaded on the calling thread as they are scheduled, which
+/// keeps tests and tools deterministic. A loader that panics fails only
+/// the cell it was loading; the worker goes on to the next one.
+pub(crate) struct LoadPool<C> {
+    loader: Loader<C>,
+    queue: SharedQueue,
+    done_tx: Sender<Finished<C>>,
+    done_rx: Receiver<Finished<C>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl<C: Send + 'static> LoadPool<C> {
+    pub(crate) fn new(workers: usize, loader: Loader<C>) -> Self {
+        let (done_tx, done_rx) = mpsc::channel();
+        let queue = SharedQueue::default();
+        let workers = (0..workers)
+            .map(|i| {
+                let queue = Arc::clone(&queue);
+                let done_tx = done_tx.clone();
+                let loader = Arc::clone(&loader);
+                std::thread::Builder::new()
+                    .name(format!("cell-loader-{i}"))
+                    .spawn(move || {
+                        while let Some(coord) = next_job(&queue) {
+                            tracing::debug!(?coord, "loading cell");
+                            if done_tx.send((coord, load(&loader, coord))).is_err() {
+                                break;
+                            }
+                        }
+                    })
+                    .expect("failed to spawn cell loader thread")
+            })
+            .collect();
+        Self {
+            loader,
+            queue,
+            done_tx,
+            done_rx,
+            workers,
+        }
+    }
+
+    /// Queue `coord` for loading ahead of cells with a higher `priority`.
+    pub(crate) fn schedule(&self, coord: CellCoord, priority: u64) {
+        if self.workers.is_empty() {
+            let _ = self.done_tx.send((coord, load(&self.loader, coord)));
+            return;
+        }
+        let (queue, ready) = &*self.queue;
+        let mut queue = queue.lock().expect("load queue poisoned");
+        queue.jobs.push(Reverse((priority, coord)));
+        ready.notify_one();
+    }
+
+    /// Re-rank the cells still waiting for a worker by `priority`, dropping
+    /// those it returns `None` for. Returns the dropped cells.
+    pub(crate) fn requeue(&self, priority: impl Fn(CellCoord) -> Option<u64>) -> Vec<CellCoord> {
+        let mut queue = self.queue.0.lock().expect("load queue poisoned");
+        let mut cancelled = Vec::new();
+        let jobs = std::mem::take(&mut queue.jobs);
+        for Reverse((_, coord)) in jobs {
+            match priority(coord) {
+                Some(priority) => queue.jobs.push(Reverse((priority, coord))),
+                None => cancelled.push(coord),
+            }
+        }
+        cancelled
+    }
+
+    /// A cell whose loading has finished, if any, with `None` content if
+    /// its loader panicked.
+    pub(crate) fn try_finished(&self) -> Option<Finished<C>> {
+        self.done_rx.try_recv().ok()
+    }
+}
+
+/// Run `loader` for `coord`, turning a panic into `None`.
+fn load<C>(loader: &Loader<C>, coord: CellCoord) -> Option<C> {
+    let content = panic::catch_unwind(AssertUnwindSafe(|| loader(coord)));
+    if content.is_err() {
+        tracing::warn!(?coord, "cell loader panicked");
+    }
+    content.ok()
+}
+
+/// Block until a cell is queued and take the most urgent one; `None` once
+/// the pool is closed.
+fn next_job(queue: &SharedQueue) -> Option<CellCoord> {
+    let (queue, ready) = &**queue;
+    let mut queue = queue.lock().expect("load queue poisoned");
+    loop {
+        if queue.closed {
+            return None;
+        }
+        if let Some(Reverse((_, coord))) = queue.jobs.pop() {
+            return Some(coord);
+        }
+        queue = ready.wait(queue).expect("load queue poisoned");
+    }
+}
+
+impl<C> Drop for LoadPool<C> {
+    fn drop(&mut self) {
+        // Workers stop once they finish their current cell; queued cells are
+        // abandoned.
+        let (queue, ready) = &*self.queue;
+        if let Ok(mut queue) = queue.lock() {
+            queue.closed = true;
+        }
+        ready.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
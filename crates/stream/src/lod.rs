@@ -0,0 +1,71 @@
+use crate::budget::StreamConfig;
+use crate::grid::CellCoord;
+
+/// Level of detail of a loaded cell, chosen by its distance from the viewer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CellLod {
+    /// Within `active_radius`: full detail, entities ticked.
+    Active,
+    /// Within `reduced_radius`: simplified meshes.
+    Reduced,
+    /// Out to `preload_radius`: impostors only.
+    Impostor,
+}
+
+impl CellLod {
+    /// The tier for a cell at `cell`, seen from `viewer`, by
+    /// `CellCoord::distance`, matching the square streaming radii.
+    pub fn select(config: &StreamConfig, viewer: CellCoord, cell: CellCoord) -> Self {
+        let distance = viewer.distance(cell);
+        if distance <= config.active_radius {
+            CellLod::Active
+        } else if distance <= config.reduced_radius {
+            CellLod::Reduced
+        } else {
+            CellLod::Impostor
+        }
+    }
+
+    /// Index into a mesh LOD chain of `levels` meshes (most detailed first)
+    /// for this tier: the source mesh, the first simplification, or the
+    /// coarsest level.
+    pub fn mesh_level(self, levels: usize) -> usize {
+        let last = levels.saturating_sub(1);
+        match self {
+            CellLod::Active => 0,
+            CellLod::Reduced => last.min(1),
+            CellLod::Impostor => last,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiers_follow_radii() {
+        let config = StreamConfig {
+            active_radius: 1,
+            reduced_radius: 3,
+            preload_radius: 5,
+            ..StreamConfig::default()
+        };
+        let viewer = CellCoord::new(10, -4);
+        let at = |dx, dz| CellLod::select(&config, viewer, CellCoord::new(10 + dx, -4 + dz));
+        assert_eq!(at(0, 0), CellLod::Active);
+        assert_eq!(at(-1, 1), CellLod::Active);
+        assert_eq!(at(2, 0), CellLod::Reduced);
+        assert_eq!(at(3, -3), CellLod::Reduced);
+        assert_eq!(at(0, 4), CellLod::Impostor);
+    }
+
+    #[test]
+    fn mesh_levels_map_onto_chain() {
+        assert_eq!(CellLod::Active.mesh_level(4), 0);
+        assert_eq!(CellLod::Reduced.mesh_level(4), 1);
+        assert_eq!(CellLod::Impostor.mesh_level(4), 3);
+        assert_eq!(CellLod::Impostor.mesh_level(1), 0);
+        assert_eq!(CellLod::Reduced.mesh_level(0), 0);
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashSet;
+use worldspace_common::EntityId;
+use worldspace_kernel::World;
+
+use crate::grid::{CellCoord, SpatialPartition};
+
+/// Quadtree partitioning of the world over XZ cells.
+///
+/// Entities are assigned to cells of `cell_size` like `GridPartition`, but
+/// the cells are the leaves of a quadtree fitted around the occupied area.
+/// Empty quadrants are never allocated, and radius queries skip them and
+/// take whole quadrants that lie inside the radius without visiting their
+/// cells, so sparse worlds stay cheap to query.
+pub struct QuadtreePartition {
+    cell_size: f32,
+    /// Cell at the minimum corner of the root quadrant.
+    origin: (i32, i32),
+    /// The root quadrant spans `2^depth` cells per side.
+    depth: u32,
+    root: Option<Node>,
+}
+
+struct Node {
+    /// Entities in this quadrant.
+    count: usize,
+    kind: NodeKind,
+}
+
+enum NodeKind {
+    Leaf(HashSet<EntityId>),
+    /// Quadrants in order (low x, low z), (high x, low z), (low x, high z),
+    /// (high x, high z).
+    Branch(Box<[Option<Node>; 4]>),
+}
+
+/// A square of cells: minimum corner and side length.
+#[derive(Clone, Copy)]
+struct Quadrant {
+    x: i32,
+    z: i32,
+    size: i32,
+}
+
+impl Quadrant {
+    fn child(self, index: usize) -> Quadrant {
+        let half = self.size / 2;
+        Quadrant {
+            x: self.x + if index & 1 == 0 { 0 } else { half },
+            z: self.z + if index & 2 == 0 { 0 } else { half },
+            size: half,
+        }
+    }
+
+    fn child_index(self, x: i32, z: i32) -> usize {
+        let half = self.size / 2;
+        usize::from(x >= self.x + half) | (usize::from(z >= self.z + half) << 1)
+    }
+
+    fn contains(self, x: i32, z: i32) -> bool {
+        (self.x..self.x + self.size).contains(&x) && (self.z..self.z + self.size).contains(&z)
+    }
+}
+
+/// Inclusive rectangle of cells a radius query covers.
+#[derive(Clone, Copy)]
+struct Rect {
+    min: (i32, i32),
+    max: (i32, i32),
+}
+
+impl Rect {
+    fn intersects(self, q: Quadrant) -> bool {
+        q.x <= self.max.0
+            && q.x + q.size > self.min.0
+            && q.z <= self.max.1
+            && q.z + q.size > self.min.1
+    }
+
+    fn covers(self, q: Quadrant) -> bool {
+        q.x >= self.min.0
+            && q.x + q.size - 1 <= self.max.0
+            && q.z >= self.min.1
+            && q.z + q.size - 1 <= self.max.1
+    }
+}
+
+impl Node {
+    fn insert(&mut self, quadrant: Quadrant, x: i32, z: i32, id: EntityId) {
+        self.count += 1;
+        match &mut self.kind {
+            NodeKind::Leaf(entities) => {
+                entities.insert(id);
+            }
+            NodeKind::Branch(children) => {
+                let index = quadrant.child_index(x, z);
+                let child = quadrant.child(index);
+                children[index]
+                    .get_or_insert_with(|| Node::empty(child))
+                    .insert(child, x, z, id);
+            }
+        }
+    }
+
+    fn empty(quadrant: Quadrant) -> Node {
+        let kind = if quadrant.size == 1 {
+            NodeKind::Leaf(HashSet::new())
+        } else {
+            NodeKind::Branch(Box::new([None, None, None, None]))
+        };
+        Node { count: 0, kind }
+    }
+
+    /// Every entity in this quadrant.
+    fn collect(&self, out: &mut HashSet<EntityId>) {
+        match &self.kind {
+            NodeKind::Leaf(entities) => out.extend(entities),
+            NodeKind::Branch(children) => {
+                for child in children.iter().flatten() {
+                    child.collect(out);
+                }
+            }
+        }
+    }
+
+    /// Entities in the cells of this quadrant that `rect` covers.
+    fn query(&self, quadrant: Quadrant, rect: Rect, out: &mut HashSet<EntityId>) {
+        if !rect.intersects(quadrant) {
+            return;
+        }
+        if rect.covers(quadrant) {
+            self.collect(out);
+            return;
+        }
+        if let NodeKind::Branch(children) = &self.kind {
+            for (index, child) in children.iter().enumerate() {
+                if let Some(child) = child {
+                    child.query(quadrant.child(index), rect, out);
+                }
+            }
+        }
+    }
+
+    fn leaves(&self, quadrant: Quadrant, out: &mut Vec<CellCoord>) {
+        match &self.kind {
+            NodeKind::Leaf(_) => out.push(CellCoord::new(quadrant.x, quadrant.z)),
+            NodeKind::Branch(children) => {
+                for (index, child) in children.iter().enumerate() {
+                    if let Some(child) = child {
+                        child.leaves(quadrant.child(index), out);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl QuadtreePartition {
+    /// Create a new quadtree partition with the given leaf cell size.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "cell_size must be positive");
+        Self {
+            cell_size,
+            origin: (0, 0),
+            depth: 0,
+            root: None,
+        }
+    }
+
+    /// Cell size used for this partition.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Levels below the root; the root spans `2^depth` cells per side.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Rebuild the tree from the current world state, fitted to the cells
+    /// the entities occupy.
+    pub fn rebuild(&mut self, world: &World) {
+        let placed: Vec<(CellCoord, EntityId)> = world
+            .entities()
+            .iter()
+            .map(|(id, data)| (self.position_to_cell(data.transform.position), *id))
+            .collect();
+        self.root = None;
+        let Some((first, _)) = placed.first() else {
+            return;
+        };
+        let (mut min, mut max) = ((first.x, first.z), (first.x, first.z));
+        for (c, _) in &placed {
+            min = (min.0.min(c.x), min.1.min(c.z));
+            max = (max.0.max(c.x), max.1.max(c.z));
+        }
+        let extent = (max.0 - min.0).max(max.1 - min.1) as u32 + 1;
+        self.origin = min;
+        self.depth = extent.next_power_of_two().trailing_zeros();
+        let quadrant = self.root_quadrant();
+        let mut root = Node::empty(quadrant);
+        for (c, id) in placed {
+            root.insert(quadrant, c.x, c.z, id);
+        }
+        self.root = Some(root);
+    }
+
+    /// Convert a world position to a cell coordinate.
+    pub fn position_to_cell(&self, pos: glam::Vec3) -> CellCoord {
+        CellCoord::new(
+            (pos.x / self.cell_size).floor() as i32,
+            (pos.z / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Get all entity IDs in a specific cell.
+    pub fn entities_in_cell(&self, coord: CellCoord) -> HashSet<EntityId> {
+        let mut quadrant = self.root_quadrant();
+        let mut node = self.root.as_ref();
+        while let Some(current) = node {
+            if coord.y != 0 || !quadrant.contains(coord.x, coord.z) {
+                break;
+            }
+            match &current.kind {
+                NodeKind::Leaf(entities) => return entities.clone(),
+                NodeKind::Branch(children) => {
+                    let index = quadrant.child_index(coord.x, coord.z);
+                    quadrant = quadrant.child(index);
+                    node = children[index].as_ref();
+                }
+            }
+        }
+        HashSet::new()
+    }
+
+    /// Get all entity IDs within a radius (in cells) of a center cell.
+    pub fn entities_in_radius(&self, center: CellCoord, radius: i32) -> HashSet<EntityId> {
+        let mut result = HashSet::new();
+        if let Some(root) = &self.root {
+            let rect = Rect {
+                min: (center.x - radius, center.z - radius),
+                max: (center.x + radius, center.z + radius),
+            };
+            root.query(self.root_quadrant(), rect, &mut result);
+        }
+        result
+    }
+
+    /// All cells within `radius` (in cells) of `center`, occupied or not.
+    pub fn cells_in_radius(&self, center: CellCoord, radius: i32) -> HashSet<CellCoord> {
+        let mut result = HashSet::new();
+        for dx in -radius..=radius {
+            for dz in -radius..=radius {
+                result.insert(CellCoord::new(center.x + dx, center.z + dz));
+            }
+        }
+        result
+    }
+
+    /// Coordinates of the non-empty cells.
+    pub fn occupied_cells(&self) -> impl Iterator<Item = CellCoord> + '_ {
+        let mut cells = Vec::new();
+        if let Some(root) = &self.root {
+            root.leaves(self.root_quadrant(), &mut cells);
+        }
+        cells.into_iter()
+    }
+
+    /// Number of non-empty cells.
+    pub fn cell_count(&self) -> usize {
+        self.occupied_cells().count()
+    }
+
+    /// Total number of entity placements across all cells.
+    pub fn total_placements(&self) -> usize {
+        self.root.as_ref().map_or(0, |root| root.count)
+    }
+
+    fn root_quadrant(&self) -> Quadrant {
+        Quadrant {
+            x: self.origin.0,
+            z: self.origin.1,
+            size: 1 << self.depth,
+        }
+    }
+}
+
+impl SpatialPartition for QuadtreePartition {
+    fn cells_in_radius(&self, center: CellCoord, radius: i32) -> HashSet<CellCoord> {
+        self.cells_in_radius(center, radius)
+    }
+
+    fn entities_in_cell(&self, coord: CellCoord) -> HashSet<EntityId> {
+        self.entities_in_cell(coord)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridPartition;
+    use worldspace_common::Transform;
+
+    fn world_at(points: &[(f32, f32)]) -> World {
+        let mut world = World::new();
+        for &(x, z) in points {
+            world.spawn(Transform {
+                position: glam::Vec3::new(x, 0.0, z),
+                ..Transform::default()
+            });
+        }
+        world
+    }
+
+    #[test]
+    fn matches_grid_queries() {
+        let points: Vec<(f32, f32)> = (0..40)
+            .map(|i| ((i * 37 % 300) as f32 - 150.0, (i * 53 % 200) as f32 - 20.0))
+            .collect();
+        let world = world_at(&points);
+        let mut grid = GridPartition::new(16.0);
+        grid.rebuild(&world);
+        let mut tree = QuadtreePartition::new(16.0);
+        tree.rebuild(&world);
+
+        assert_eq!(tree.cell_count(), grid.cell_count());
+        assert_eq!(tree.total_placements(), world.entity_count());
+        for cell in grid.occupied_cells() {
+            assert_eq!(tree.entities_in_cell(cell), grid.entities_in_cell(cell));
+        }
+        for (center, radius) in [((0, 0), 1), ((-5, 3), 2), ((20, 20), 3), ((3, 7), 0)] {
+            let center = CellCoord::new(center.0, center.1);
+            assert_eq!(
+                tree.entities_in_radius(center, radius),
+                grid.entities_in_radius(center, radius)
+            );
+        }
+    }
+
+    #[test]
+    fn sparse_world_stays_shallow() {
+        let world = world_at(&[(0.0, 0.0), (16_000.0, 16_000.0)]);
+        let mut tree = QuadtreePartition::new(16.0);
+        tree.rebuild(&world);
+        // 1001 cells per side fit in a 1024-cell root.
+        assert_eq!(tree.depth(), 10);
+        assert_eq!(tree.cell_count(), 2);
+        assert_eq!(
+            tree.entities_in_radius(CellCoord::new(500, 500), 400).len(),
+            0
+        );
+        assert_eq!(
+            tree.entities_in_radius(CellCoord::new(500, 500), 500).len(),
+            2
+        );
+        assert!(tree.entities_in_cell(CellCoord::new(-1, 0)).is_empty());
+    }
+
+    #[test]
+    fn empty_tree_returns_empty_sets() {
+        let mut tree = QuadtreePartition::new(16.0);
+        tree.rebuild(&World::new());
+        assert_eq!(tree.total_placements(), 0);
+        assert!(tree.entities_in_cell(CellCoord::new(0, 0)).is_empty());
+        assert!(tree.entities_in_radius(CellCoord::new(0, 0), 5).is_empty());
+    }
+}
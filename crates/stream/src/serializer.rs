@@ -0,0 +1,358 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+
+use worldspace_common::{EntityId, Transform};
+use worldspace_kernel::{EntityData, World};
+
+use crate::grid::CellCoord;
+
+const BLOB_MAGIC: &[u8; 4] = b"WSCB";
+const BLOB_VERSION: u8 = 1;
+/// Id plus position, rotation and scale.
+const ENTITY_BYTES: usize = 16 + 10 * 4;
+const HEADER_BYTES: usize = 4 + 1 + 3 * 4 + 4;
+
+/// Errors decoding a `CellBlob`.
+#[derive(Debug, thiserror::Error)]
+pub enum CellBlobError {
+    #[error("not a cell blob")]
+    BadMagic,
+    #[error("unsupported cell blob version {0}")]
+    UnsupportedVersion(u8),
+    #[error("cell blob truncated: expected {expected} bytes, got {actual}")]
+    Truncated { expected: usize, actual: usize },
+}
+
+/// A cell's entities serialized into a compact binary blob: a small header
+/// (magic, version, cell coordinate, entity count) followed by each
+/// entity's id and transform as little-endian values, in EntityId order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellBlob {
+    pub coord: CellCoord,
+    pub entity_count: usize,
+    pub bytes: Vec<u8>,
+}
+
+impl CellBlob {
+    /// Serialize `entities` of cell `coord`.
+    pub fn encode(coord: CellCoord, entities: &[(EntityId, EntityData)]) -> Self {
+        let mut sorted: Vec<&(EntityId, EntityData)> = entities.iter().collect();
+        sorted.sort_by_key(|(id, _)| *id);
+        let mut bytes = Vec::with_capacity(HEADER_BYTES + sorted.len() * ENTITY_BYTES);
+        bytes.extend_from_slice(BLOB_MAGIC);
+        bytes.push(BLOB_VERSION);
+        for v in [coord.x, coord.y, coord.z] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        bytes.extend_from_slice(&(sorted.len() as u32).to_le_bytes());
+        for (id, data) in sorted {
+            let t = &data.transform;
+            bytes.extend_from_slice(id.0.as_bytes());
+            let floats = [
+                t.position.x,
+                t.position.y,
+                t.position.z,
+                t.rotation.x,
+                t.rotation.y,
+                t.rotation.z,
+                t.rotation.w,
+                t.scale.x,
+                t.scale.y,
+                t.scale.z,
+            ];
+            for f in floats {
+                bytes.extend_from_slice(&f.to_le_bytes());
+            }
+        }
+        Self {
+            coord,
+            entity_count: entities.len(),
+            bytes,
+        }
+    }
+
+    /// Decode a blob produced by `encode` back into its cell and entities.
+    pub fn decode(bytes: &[u8]) -> Result<(CellCoord, Vec<(EntityId, EntityData)>), CellBlobError> {
+        if bytes.len() < HEADER_BYTES {
+            if !bytes.starts_with(&BLOB_MAGIC[..bytes.len().min(4)]) {
+                return Err(CellBlobError::BadMagic);
+            }
+            return Err(CellBlobError::Truncated {
+                expected: HEADER_BYTES,
+                actual: bytes.len(),
+            });
+        }
+        if &bytes[..4] != BLOB_MAGIC {
+            return Err(CellBlobError::BadMagic);
+        }
+        if bytes[4] != BLOB_VERSION {
+            return Err(CellBlobError::UnsupportedVersion(bytes[4]));
+        }
+        let word = |at: usize| <[u8; 4]>::try_from(&bytes[at..at + 4]).expect("4 bytes");
+        let coord = CellCoord::new_3d(
+            i32::from_le_bytes(word(5)),
+            i32::from_le_bytes(word(9)),
+            i32::from_le_bytes(word(13)),
+        );
+        let count = u32::from_le_bytes(word(17)) as usize;
+        let expected = HEADER_BYTES + count * ENTITY_BYTES;
+        if bytes.len() != expected {
+            return Err(CellBlobError::Truncated {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+        let entities = bytes[HEADER_BYTES..]
+            .chunks_exact(ENTITY_BYTES)
+            .map(|chunk| {
+                let id = EntityId(uuid::Uuid::from_bytes(
+                    chunk[..16].try_into().expect("16 bytes"),
+                ));
+                let f = |i: usize| {
+                    let at = 16 + i * 4;
+                    f32::from_le_bytes(chunk[at..at + 4].try_into().expect("4 bytes"))
+                };
+                let transform = Transform {
+                    position: glam::Vec3::new(f(0), f(1), f(2)),
+                    rotation: glam::Quat::from_xyzw(f(3), f(4), f(5), f(6)),
+                    scale: glam::Vec3::new(f(7), f(8), f(9)),
+                };
+                (id, EntityData { transform })
+            })
+            .collect();
+        Ok((coord, entities))
+    }
+}
+
+type Job = (CellCoord, Vec<(EntityId, EntityData)>);
+type Callback = Arc<dyn Fn(CellBlob) + Send + Sync>;
+
+/// Cells waiting to be serialized, plus those a worker is on.
+#[derive(Default)]
+struct SaveQueue {
+    jobs: VecDeque<Job>,
+    in_flight: usize,
+    closed: bool,
+}
+
+type SharedQueue = Arc<(Mutex<SaveQueue>, Condvar)>;
+
+/// Serializes unloaded cells on worker threads, so unload spikes stay off
+/// the frame.
+///
+/// Submit a cell's entity data as it unloads; a worker encodes it into a
+/// `CellBlob` and passes it to the completion callback, on the worker
+/// thread, for the caller to persist or cache. Cells are taken in submission
+/// order. With zero workers, cells are serialized on the calling thread as
+/// they are submitted. Dropping the serializer finishes every submitted
+/// cell first, so no unloaded data is lost.
+pub struct CellSerializer {
+    on_complete: Callback,
+    queue: SharedQueue,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl CellSerializer {
+    /// Start `workers` serializer threads reporting to `on_complete`.
+    pub fn new(workers: usize, on_complete: impl Fn(CellBlob) + Send + Sync + 'static) -> Self {
+        let on_complete: Callback = Arc::new(on_complete);
+        let queue = SharedQueue::default();
+        let workers = (0..workers)
+            .map(|i| {
+                let queue = Arc::clone(&queue);
+                let on_complete = Arc::clone(&on_complete);
+                std::thread::Builder::new()
+                    .name(format!("cell-serializer-{i}"))
+                    .spawn(move || {
+                        while let Some((coord, entities)) = next_job(&queue) {
+                            let blob = CellBlob::encode(coord, &entities);
+                            tracing::debug!(?coord, bytes = blob.bytes.len(), "serialized cell");
+                            on_complete(blob);
+                            let (state, changed) = &*queue;
+                            state.lock().expect("save queue poisoned").in_flight -= 1;
+                            changed.notify_all();
+                        }
+                    })
+                    .expect("failed to spawn cell serializer thread")
+            })
+            .collect();
+        Self {
+            on_complete,
+            queue,
+            workers,
+        }
+    }
+
+    /// Queue the entities of unloaded cell `coord` for serialization.
+    pub fn submit(&self, coord: CellCoord, entities: Vec<(EntityId, EntityData)>) {
+        if self.workers.is_empty() {
+            (self.on_complete)(CellBlob::encode(coord, &entities));
+            return;
+        }
+        let (state, changed) = &*self.queue;
+        state
+            .lock()
+            .expect("save queue poisoned")
+            .jobs
+            .push_back((coord, entities));
+        changed.notify_all();
+    }
+
+    /// Queue the entities `ids` of cell `coord` as they are in `world`, e.g.
+    /// those listed by a `StreamEvent::CellUnloaded`, before paging them
+    /// out. Ids not resident in `world` are skipped.
+    pub fn submit_from_world(&self, world: &World, coord: CellCoord, ids: &[EntityId]) {
+        let entities = ids
+            .iter()
+            .filter_map(|id| world.get(*id).map(|data| (*id, data.clone())))
+            .collect();
+        self.submit(coord, entities);
+    }
+
+    /// Cells submitted but not yet handed to the callback.
+    pub fn pending(&self) -> usize {
+        let state = self.queue.0.lock().expect("save queue poisoned");
+        state.jobs.len() + state.in_flight
+    }
+
+    /// Block until every submitted cell has been handed to the callback.
+    pub fn wait(&self) {
+        let (state, changed) = &*self.queue;
+        let mut state = state.lock().expect("save queue poisoned");
+        while !state.jobs.is_empty() || state.in_flight > 0 {
+            state = changed.wait(state).expect("save queue poisoned");
+        }
+    }
+}
+
+/// Block until a cell is queued and take the oldest; `None` once the
+/// serializer is closed and the queue is empty.
+fn next_job(queue: &SharedQueue) -> Option<Job> {
+    let (state, changed) = &**queue;
+    let mut state = state.lock().expect("save queue poisoned");
+    loop {
+        if let Some(job) = state.jobs.pop_front() {
+            state.in_flight += 1;
+            return Some(job);
+        }
+        if state.closed {
+            return None;
+        }
+        state = changed.wait(state).expect("save queue poisoned");
+    }
+}
+
+impl Drop for CellSerializer {
+    fn drop(&mut self) {
+        let (state, changed) = &*self.queue;
+        if let Ok(mut state) = state.lock() {
+            state.closed = true;
+        }
+        changed.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entities(count: usize) -> Vec<(EntityId, EntityData)> {
+        (0..count)
+            .map(|i| {
+                let transform = Transform {
+                    position: glam::Vec3::new(i as f32, -2.5, 1e6),
+                    rotation: glam::Quat::from_rotation_y(i as f32),
+                    scale: glam::Vec3::splat(0.5),
+                };
+                (EntityId::new(), EntityData { transform })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn blobs_round_trip() {
+        let coord = CellCoord::new_3d(-3, 2, 7);
+        let mut cell = entities(5);
+        let blob = CellBlob::encode(coord, &cell);
+        assert_eq!(blob.entity_count, 5);
+        assert_eq!(blob.bytes.len(), HEADER_BYTES + 5 * ENTITY_BYTES);
+
+        let (decoded_coord, decoded) = CellBlob::decode(&blob.bytes).unwrap();
+        assert_eq!(decoded_coord, coord);
+        cell.sort_by_key(|(id, _)| *id);
+        assert_eq!(decoded.len(), cell.len());
+        for ((id, data), (expected_id, expected)) in decoded.iter().zip(&cell) {
+            assert_eq!(id, expected_id);
+            assert_eq!(data.transform, expected.transform);
+        }
+
+        assert!(matches!(
+            CellBlob::decode(&blob.bytes[..blob.bytes.len() - 1]),
+            Err(CellBlobError::Truncated { .. })
+        ));
+        assert!(matches!(
+            CellBlob::decode(b"nope"),
+            Err(CellBlobError::BadMagic)
+        ));
+        let mut future = blob.bytes.clone();
+        future[4] = 9;
+        assert!(matches!(
+            CellBlob::decode(&future),
+            Err(CellBlobError::UnsupportedVersion(9))
+        ));
+    }
+
+    #[test]
+    fn workers_hand_back_every_cell() {
+        let done = Arc::new(Mutex::new(Vec::new()));
+        let serializer = {
+            let done = Arc::clone(&done);
+            CellSerializer::new(2, move |blob: CellBlob| {
+                done.lock().unwrap().push((blob.coord, blob.entity_count));
+            })
+        };
+        for x in 0..20 {
+            serializer.submit(CellCoord::new(x, 0), entities(x as usize % 4));
+        }
+        serializer.wait();
+        assert_eq!(serializer.pending(), 0);
+        let mut done = done.lock().unwrap().clone();
+        done.sort();
+        assert_eq!(done.len(), 20);
+        assert_eq!(done[7], (CellCoord::new(7, 0), 3));
+    }
+
+    #[test]
+    fn dropping_finishes_queued_cells() {
+        let done = Arc::new(Mutex::new(0));
+        let serializer = {
+            let done = Arc::clone(&done);
+            CellSerializer::new(1, move |_| *done.lock().unwrap() += 1)
+        };
+        for x in 0..10 {
+            serializer.submit(CellCoord::new(x, 0), entities(2));
+        }
+        drop(serializer);
+        assert_eq!(*done.lock().unwrap(), 10);
+    }
+
+    #[test]
+    fn inline_without_workers() {
+        let mut world = World::new();
+        let resident = world.spawn(Transform::default());
+        let done = Arc::new(Mutex::new(Vec::new()));
+        let serializer = {
+            let done = Arc::clone(&done);
+            CellSerializer::new(0, move |blob| done.lock().unwrap().push(blob))
+        };
+        serializer.submit_from_world(&world, CellCoord::new(0, 0), &[resident, EntityId::new()]);
+        let done = done.lock().unwrap();
+        assert_eq!(done.len(), 1);
+        let (_, entities) = CellBlob::decode(&done[0].bytes).unwrap();
+        assert_eq!(entities[0].0, resident);
+    }
+}
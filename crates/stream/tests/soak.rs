@@ -0,0 +1,134 @@
+//! Streaming soak test: flies a viewer over a large generated world and
+//! asserts that `StreamState::update` never hitches.
+//!
+//! The defaults keep the run short enough for CI. For a longer soak, or a
+//! tighter budget on a release build, set:
+//! - `WORLDSPACE_SOAK_FRAMES`: frames to simulate (default 600).
+//! - `WORLDSPACE_SOAK_P99_MS`: p99 update time budget in ms (default 4).
+//! - `WORLDSPACE_SOAK_ENTITIES`: entities in the world (default 50000).
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use worldspace_common::Transform;
+use worldspace_kernel::World;
+use worldspace_stream::{ENTITY_COST_BYTES, FrameTimer, GridPartition, StreamConfig, StreamState};
+
+const CELL_SIZE: f32 = 16.0;
+/// Side of the generated world in meters.
+const WORLD_SIZE: f32 = 4096.0;
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Deterministic pseudo-random stream (splitmix64), mapped to 0..1.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        ((z ^ (z >> 31)) >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Dense towns joined by sparse scatter, so some cells hold hundreds of
+/// entities and most hold a few or none.
+fn generate_world(entities: usize) -> World {
+    let mut rng = Rng(0x5eed);
+    let towns: Vec<(f32, f32)> = (0..24)
+        .map(|_| (rng.next() * WORLD_SIZE, rng.next() * WORLD_SIZE))
+        .collect();
+    let mut world = World::with_seed(7);
+    for i in 0..entities {
+        let (x, z) = if i % 4 == 0 {
+            (rng.next() * WORLD_SIZE, rng.next() * WORLD_SIZE)
+        } else {
+            let (tx, tz) = towns[i % towns.len()];
+            (
+                tx + (rng.next() - 0.5) * 96.0,
+                tz + (rng.next() - 0.5) * 96.0,
+            )
+        };
+        world.spawn(Transform {
+            position: glam::Vec3::new(x, 0.0, z),
+            ..Transform::default()
+        });
+    }
+    world
+}
+
+/// Viewer position at `frame`: a fast lap around the world with a
+/// crossing through its center.
+fn viewer_path(frame: usize) -> glam::Vec3 {
+    let t = frame as f32 * 0.004;
+    let r = WORLD_SIZE * 0.35 * (1.0 + 0.5 * (t * 3.0).sin());
+    let center = WORLD_SIZE * 0.5;
+    glam::Vec3::new(center + r * t.cos(), 0.0, center + r * t.sin())
+}
+
+#[test]
+fn update_time_stays_within_budget() {
+    let frames: usize = env_or("WORLDSPACE_SOAK_FRAMES", 600);
+    let budget = Duration::from_secs_f64(env_or("WORLDSPACE_SOAK_P99_MS", 4.0) / 1000.0);
+    let world = generate_world(env_or("WORLDSPACE_SOAK_ENTITIES", 50_000));
+
+    let mut grid = GridPartition::new(CELL_SIZE);
+    grid.rebuild(&world);
+    let grid = Arc::new(grid);
+
+    let config = StreamConfig {
+        active_radius: 2,
+        reduced_radius: 4,
+        preload_radius: 8,
+        load_budget: 16,
+        unload_budget: 32,
+        apply_budget: 16,
+        workers: 4,
+        memory_budget: Some(4_000 * ENTITY_COST_BYTES),
+    };
+    let loader = {
+        let grid = Arc::clone(&grid);
+        move |coord| grid.entities_in_cell(coord).len()
+    };
+    let mut state = StreamState::with_loader(config, loader);
+    state.set_content_cost(|count: &usize| count * ENTITY_COST_BYTES);
+
+    let mut timer = FrameTimer::new(frames);
+    let mut loaded = 0;
+    let mut previous = viewer_path(0);
+    for frame in 0..frames {
+        let position = viewer_path(frame);
+        state.set_view_direction(Some(position - previous));
+        previous = position;
+        let viewer = grid.position_to_cell(position);
+
+        let start = Instant::now();
+        state.update(viewer, grid.as_ref());
+        timer.record(start.elapsed());
+        loaded += state.stats().cells_loaded_this_frame;
+    }
+
+    let p99 = timer.percentile(99.0);
+    println!(
+        "soak: {frames} frames, {loaded} cells loaded, update avg {:?} p99 {p99:?} max {:?}, {} KiB resident",
+        timer.average(),
+        timer.max(),
+        state.memory_used() / 1024
+    );
+    assert!(loaded > 0, "the viewer path streamed nothing");
+    assert!(
+        state.memory_used() <= 4_000 * ENTITY_COST_BYTES,
+        "memory budget exceeded"
+    );
+    assert!(
+        p99 <= budget,
+        "p99 update time {p99:?} exceeds the {budget:?} budget"
+    );
+}
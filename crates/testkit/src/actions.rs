@@ -0,0 +1,215 @@
+use glam::Vec3;
+use worldspace_author::Editor;
+use worldspace_common::{EntityId, Transform};
+use worldspace_input::Action;
+use worldspace_kernel::World;
+
+/// A scripted stream of input actions, grouped into frames.
+///
+/// Build a script with `then()` and `next_frame()`, then feed it to an
+/// `ActionRunner` to drive a world the same way an embodiment mode would.
+#[derive(Debug, Clone)]
+pub struct ActionScript {
+    frames: Vec<Vec<Action>>,
+}
+
+impl ActionScript {
+    pub fn new() -> Self {
+        Self {
+            frames: vec![Vec::new()],
+        }
+    }
+
+    /// Append an action to the current frame.
+    pub fn then(mut self, action: Action) -> Self {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.push(action);
+        }
+        self
+    }
+
+    /// Start a new frame.
+    pub fn next_frame(mut self) -> Self {
+        self.frames.push(Vec::new());
+        self
+    }
+
+    /// Insert `count` empty frames.
+    pub fn wait(mut self, count: usize) -> Self {
+        for _ in 0..count {
+            self.frames.push(Vec::new());
+        }
+        self
+    }
+
+    /// Actions grouped by frame.
+    pub fn frames(&self) -> &[Vec<Action>] {
+        &self.frames
+    }
+
+    /// All actions in order, ignoring frame boundaries.
+    pub fn actions(&self) -> impl Iterator<Item = &Action> {
+        self.frames.iter().flatten()
+    }
+
+    /// Total number of actions.
+    pub fn len(&self) -> usize {
+        self.frames.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for ActionScript {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Applies scripted actions to a world through an `Editor`.
+///
+/// Authoring actions go through the editor so undo/redo behaves exactly as
+/// in the desktop app. Actions with no world effect (camera, save/load,
+/// inspector) are ignored.
+#[derive(Default)]
+pub struct ActionRunner {
+    pub world: World,
+    pub editor: Editor,
+    selected: Option<EntityId>,
+    spawned: Vec<EntityId>,
+}
+
+impl ActionRunner {
+    /// Create a runner over an existing world.
+    pub fn new(world: World) -> Self {
+        Self {
+            world,
+            editor: Editor::new(),
+            selected: None,
+            spawned: Vec::new(),
+        }
+    }
+
+    /// Currently selected entity.
+    pub fn selected(&self) -> Option<EntityId> {
+        self.selected
+    }
+
+    /// Ids of entities spawned by `SpawnEntity` actions, in order.
+    pub fn spawned(&self) -> &[EntityId] {
+        &self.spawned
+    }
+
+    /// Apply a single action.
+    pub fn apply(&mut self, action: &Action) {
+        match action {
+            Action::SpawnEntity(position) => {
+                let id = self.editor.spawn(
+                    &mut self.world,
+                    Transform {
+                        position: *position,
+                        ..Transform::default()
+                    },
+                );
+                self.spawned.push(id);
+                self.selected = Some(id);
+            }
+            Action::DespawnEntity(id) => {
+                if self.editor.despawn(&mut self.world, *id).is_ok() && self.selected == Some(*id) {
+                    self.selected = None;
+                }
+            }
+            Action::Select(id) => self.selected = Some(*id),
+            Action::Deselect => self.selected = None,
+            Action::TranslateSelected(delta) => {
+                if let Some(id) = self.selected {
+                    self.translate(id, *delta);
+                }
+            }
+            Action::Undo => {
+                self.editor.undo(&mut self.world);
+            }
+            Action::Redo => {
+                self.editor.redo(&mut self.world);
+            }
+            Action::Move(_)
+            | Action::Save
+            | Action::Load
+            | Action::ToggleInspector
+            | Action::Noop => {}
+        }
+    }
+
+    /// Apply every action in the script, stepping the world once per frame.
+    pub fn run(&mut self, script: &ActionScript) {
+        for frame in script.frames() {
+            for action in frame {
+                self.apply(action);
+            }
+            self.world.step();
+        }
+    }
+
+    fn translate(&mut self, id: EntityId, delta: Vec3) {
+        let Some(data) = self.world.get(id) else {
+            return;
+        };
+        let new = Transform {
+            position: data.transform.position + delta,
+            ..data.transform
+        };
+        let _ = self.editor.set_transform(&mut self.world, id, new);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_groups_actions_by_frame() {
+        let script = ActionScript::new()
+            .then(Action::SpawnEntity(Vec3::ZERO))
+            .then(Action::Deselect)
+            .next_frame()
+            .then(Action::Undo)
+            .wait(2);
+        assert_eq!(script.frames().len(), 4);
+        assert_eq!(script.len(), 3);
+    }
+
+    #[test]
+    fn runner_applies_authoring_actions() {
+        let script = ActionScript::new()
+            .then(Action::SpawnEntity(Vec3::ZERO))
+            .then(Action::TranslateSelected(Vec3::new(1.0, 0.0, 0.0)))
+            .next_frame()
+            .then(Action::SpawnEntity(Vec3::new(5.0, 0.0, 0.0)));
+
+        let mut runner = ActionRunner::new(World::with_seed(1));
+        runner.run(&script);
+
+        assert_eq!(runner.world.entity_count(), 2);
+        assert_eq!(runner.world.tick(), 2);
+        let first = runner.spawned()[0];
+        assert_eq!(
+            runner.world.get(first).unwrap().transform.position,
+            Vec3::new(1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn runner_undo_redo_through_editor() {
+        let script = ActionScript::new()
+            .then(Action::SpawnEntity(Vec3::ZERO))
+            .then(Action::Undo);
+        let mut runner = ActionRunner::new(World::new());
+        runner.run(&script);
+        assert_eq!(runner.world.entity_count(), 0);
+
+        runner.apply(&Action::Redo);
+        assert_eq!(runner.world.entity_count(), 1);
+    }
+}
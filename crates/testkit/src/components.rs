@@ -0,0 +1,120 @@
+use worldspace_common::EntityId;
+use worldspace_ecs::{Collider, ComponentStore, MaterialHandle, MeshHandle, Renderable, RigidBody};
+use worldspace_kernel::World;
+
+/// Builder for a populated `ComponentStore`.
+///
+/// Like `WorldBuilder`, the event queue is drained on build unless
+/// `keep_events()` is called.
+#[derive(Debug, Default)]
+pub struct ComponentStoreBuilder {
+    store: ComponentStore,
+    keep_events: bool,
+}
+
+impl ComponentStoreBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give an entity a name.
+    pub fn named(mut self, entity: EntityId, name: impl Into<String>) -> Self {
+        self.store.set_name(entity, name.into());
+        self
+    }
+
+    /// Name every entity in the world `entity_<n>` in canonical order.
+    pub fn name_all(mut self, world: &World) -> Self {
+        for (i, id) in world.entities().keys().enumerate() {
+            self.store.set_name(*id, format!("entity_{i}"));
+        }
+        self
+    }
+
+    /// Attach a renderable referencing the given mesh and material.
+    pub fn renderable(mut self, entity: EntityId, mesh: u64, material: u64) -> Self {
+        self.store.set_renderable(
+            entity,
+            Renderable {
+                mesh: MeshHandle(mesh),
+                material: MaterialHandle(material),
+            },
+        );
+        self
+    }
+
+    /// Attach the default renderable (mesh 0, material 0) to every entity in the world.
+    pub fn renderable_all(mut self, world: &World) -> Self {
+        for id in world.entities().keys() {
+            self = self.renderable(*id, 0, 0);
+        }
+        self
+    }
+
+    /// Attach a rigid body.
+    pub fn rigid_body(mut self, entity: EntityId, body: RigidBody) -> Self {
+        self.store.set_rigid_body(entity, body);
+        self
+    }
+
+    /// Attach a collider.
+    pub fn collider(mut self, entity: EntityId, collider: Collider) -> Self {
+        self.store.set_collider(entity, collider);
+        self
+    }
+
+    /// Keep the setup events in the store's event queue.
+    pub fn keep_events(mut self) -> Self {
+        self.keep_events = true;
+        self
+    }
+
+    /// Build the component store.
+    pub fn build(mut self) -> ComponentStore {
+        if !self.keep_events {
+            self.store.drain_events();
+        }
+        self.store
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WorldBuilder;
+
+    #[test]
+    fn builds_components_for_world() {
+        let world = WorldBuilder::new().line(3, 1.0).build();
+        let store = ComponentStoreBuilder::new()
+            .name_all(&world)
+            .renderable_all(&world)
+            .build();
+        assert_eq!(store.names().len(), 3);
+        assert_eq!(store.renderables().len(), 3);
+        assert!(store.events().is_empty());
+    }
+
+    #[test]
+    fn same_world_same_names() {
+        let build = || {
+            let world = WorldBuilder::new().seed(5).line(3, 1.0).build();
+            let store = ComponentStoreBuilder::new().name_all(&world).build();
+            (world, store)
+        };
+        let ((world_a, a), (world_b, b)) = (build(), build());
+        assert_eq!(world_a.state_hash(), world_b.state_hash());
+        assert_eq!(a.names(), b.names());
+    }
+
+    #[test]
+    fn keep_events_preserves_queue() {
+        let id = EntityId::new();
+        let store = ComponentStoreBuilder::new()
+            .named(id, "A")
+            .rigid_body(id, RigidBody::default())
+            .keep_events()
+            .build();
+        assert_eq!(store.events().len(), 2);
+    }
+}
@@ -0,0 +1,35 @@
+//! Test fixtures for games and tools built on the engine.
+//!
+//! Provides builders for populated worlds and component stores, temporary
+//! file-backed world stores, and scripted action streams, so integration
+//! tests don't need to copy setup code from the engine's own unit tests.
+//!
+//! # Invariants
+//! - Worlds and component stores built from the same seed and builder calls
+//!   are identical, entity ids included. Entities `ActionRunner` spawns get
+//!   fresh ids, as editor spawns do.
+//! - Temporary stores are removed when dropped.
+
+mod actions;
+mod components;
+mod store;
+mod world;
+
+pub use actions::{ActionRunner, ActionScript};
+pub use components::ComponentStoreBuilder;
+pub use store::TempWorldStore;
+pub use world::WorldBuilder;
+
+pub fn crate_info() -> &'static str {
+    "worldspace-testkit v0.1.0"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crate_loads() {
+        assert!(crate_info().contains("testkit"));
+    }
+}
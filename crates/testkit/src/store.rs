@@ -0,0 +1,84 @@
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use worldspace_kernel::World;
+use worldspace_persist::{StoreError, WorldStore};
+
+/// A `WorldStore` backed by a temporary directory.
+///
+/// The directory (and everything written to it) is removed when this value
+/// is dropped.
+pub struct TempWorldStore {
+    /// Always `Some` outside `reopen`.
+    store: Option<WorldStore>,
+    _dir: TempDir,
+    path: PathBuf,
+}
+
+impl TempWorldStore {
+    /// Create an empty store in a fresh temporary directory.
+    pub fn new() -> Result<Self, StoreError> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("world_data");
+        let store = WorldStore::open(&path)?;
+        Ok(Self {
+            store: Some(store),
+            _dir: dir,
+            path,
+        })
+    }
+
+    /// Create a store and persist `world` as its first snapshot, flushing any
+    /// pending events into an event segment.
+    pub fn with_world(world: &mut World) -> Result<Self, StoreError> {
+        let mut tmp = Self::new()?;
+        tmp.store_mut().take_snapshot(world)?;
+        tmp.store_mut().append_events(&world.drain_events())?;
+        Ok(tmp)
+    }
+
+    /// Path of the store root.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn store(&self) -> &WorldStore {
+        self.store.as_ref().expect("store failed to reopen")
+    }
+
+    pub fn store_mut(&mut self) -> &mut WorldStore {
+        self.store.as_mut().expect("store failed to reopen")
+    }
+
+    /// Reopen the store from disk, as a fresh process would. The old handle
+    /// is closed first to release its writer lock.
+    pub fn reopen(&mut self) -> Result<&mut WorldStore, StoreError> {
+        self.store = None;
+        Ok(self.store.insert(WorldStore::open(&self.path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WorldBuilder;
+
+    #[test]
+    fn temp_store_is_removed_on_drop() {
+        let path = {
+            let tmp = TempWorldStore::new().unwrap();
+            assert!(tmp.path().join("world.meta.json").exists());
+            tmp.path().to_path_buf()
+        };
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn with_world_persists_snapshot() {
+        let mut world = WorldBuilder::new().seed(3).line(4, 1.0).steps(2).build();
+        let mut tmp = TempWorldStore::with_world(&mut world).unwrap();
+        let store = tmp.reopen().unwrap();
+        assert_eq!(store.meta().snapshot_count, 1);
+        let loaded = store.load_latest().unwrap();
+        assert_eq!(loaded.state_hash(), world.state_hash());
+    }
+}
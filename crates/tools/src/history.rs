@@ -0,0 +1,271 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use worldspace_render::RenderStats;
+use worldspace_stream::StreamStats;
+
+use crate::inspector::WorldSummary;
+
+/// Errors from stats history persistence.
+#[derive(Debug, thiserror::Error)]
+pub enum StatsError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// A single point-in-time sample of world, streaming, and render statistics.
+///
+/// Durations are stored as microseconds so the history stays compact and
+/// readable when persisted as JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsSample {
+    pub tick: u64,
+    pub entity_count: usize,
+    pub pending_events: usize,
+    pub loaded_cells: usize,
+    pub cells_loaded: usize,
+    pub cells_unloaded: usize,
+    pub stream_time_us: u64,
+    pub instance_count: usize,
+    pub draw_calls: usize,
+    pub frame_time_us: u64,
+}
+
+impl StatsSample {
+    /// Build a sample from the individual subsystem stats.
+    pub fn new(summary: &WorldSummary, stream: &StreamStats, render: &RenderStats) -> Self {
+        Self {
+            tick: summary.tick,
+            entity_count: summary.entity_count,
+            pending_events: summary.pending_events,
+            loaded_cells: stream.total_loaded_cells,
+            cells_loaded: stream.cells_loaded_this_frame,
+            cells_unloaded: stream.cells_unloaded_this_frame,
+            stream_time_us: stream.frame_time.as_micros() as u64,
+            instance_count: render.instance_count,
+            draw_calls: render.draw_calls,
+            frame_time_us: render.frame_time.as_micros() as u64,
+        }
+    }
+}
+
+/// Ring buffer of stats samples taken every `interval` ticks.
+///
+/// Used by the perf overlay and CLI to plot entity growth and frame-time
+/// trends over a session. The history is persisted alongside a world store
+/// under `stats/history.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsHistory {
+    interval: u64,
+    capacity: usize,
+    samples: VecDeque<StatsSample>,
+}
+
+impl StatsHistory {
+    /// Create an empty history sampling every `interval` ticks, keeping at most
+    /// `capacity` samples.
+    pub fn new(interval: u64, capacity: usize) -> Self {
+        assert!(interval > 0, "interval must be positive");
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            interval,
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Sampling interval in ticks.
+    pub fn interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// Maximum number of samples retained.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Record a sample if the interval has elapsed since the last one.
+    ///
+    /// Returns true if a sample was taken. The first call always samples, and
+    /// so does a call whose tick is behind the last sample's, e.g. after a
+    /// rollback or after loading a history from a later session, so
+    /// recording resumes from the new tick.
+    pub fn record(
+        &mut self,
+        summary: &WorldSummary,
+        stream: &StreamStats,
+        render: &RenderStats,
+    ) -> bool {
+        let due = self.samples.back().is_none_or(|last| {
+            summary.tick < last.tick || summary.tick >= last.tick + self.interval
+        });
+        if !due {
+            return false;
+        }
+        self.push(StatsSample::new(summary, stream, render));
+        true
+    }
+
+    /// Push a sample unconditionally, evicting the oldest when full.
+    pub fn push(&mut self, sample: StatsSample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Samples from oldest to newest.
+    pub fn samples(&self) -> impl Iterator<Item = &StatsSample> {
+        self.samples.iter()
+    }
+
+    /// Most recent sample, if any.
+    pub fn latest(&self) -> Option<&StatsSample> {
+        self.samples.back()
+    }
+
+    /// Number of samples currently held.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Change in entity count between the oldest and newest sample.
+    pub fn entity_growth(&self) -> i64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(first), Some(last)) => last.entity_count as i64 - first.entity_count as i64,
+            _ => 0,
+        }
+    }
+
+    /// Average render frame time across all samples, in microseconds.
+    pub fn average_frame_time_us(&self) -> u64 {
+        if self.samples.is_empty() {
+            return 0;
+        }
+        let total: u64 = self.samples.iter().map(|s| s.frame_time_us).sum();
+        total / self.samples.len() as u64
+    }
+
+    /// Path of the history file inside a world store directory.
+    pub fn path_in(store_root: impl AsRef<Path>) -> PathBuf {
+        store_root.as_ref().join("stats").join("history.json")
+    }
+
+    /// Save the history alongside the world store at `store_root`.
+    pub fn save(&self, store_root: impl AsRef<Path>) -> Result<(), StatsError> {
+        let path = Self::path_in(store_root);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        serde_json::to_writer_pretty(std::fs::File::create(path)?, self)?;
+        Ok(())
+    }
+
+    /// Load a history previously saved alongside the world store at `store_root`.
+    pub fn load(store_root: impl AsRef<Path>) -> Result<Self, StatsError> {
+        let file = std::fs::File::open(Self::path_in(store_root))?;
+        Ok(serde_json::from_reader(file)?)
+    }
+}
+
+impl Default for StatsHistory {
+    fn default() -> Self {
+        Self::new(60, 600)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn summary(tick: u64, entity_count: usize) -> WorldSummary {
+        WorldSummary {
+            tick,
+            seed: 0,
+            entity_count,
+            pending_events: 0,
+        }
+    }
+
+    #[test]
+    fn record_respects_interval() {
+        let mut history = StatsHistory::new(10, 100);
+        let stream = StreamStats::default();
+        let render = RenderStats::default();
+
+        assert!(history.record(&summary(0, 1), &stream, &render));
+        assert!(!history.record(&summary(5, 1), &stream, &render));
+        assert!(history.record(&summary(10, 2), &stream, &render));
+        assert_eq!(history.len(), 2);
+    }
+
+    #[test]
+    fn record_resumes_after_tick_goes_back() {
+        let mut history = StatsHistory::new(10, 100);
+        let stream = StreamStats::default();
+        let render = RenderStats::default();
+
+        assert!(history.record(&summary(500, 1), &stream, &render));
+        assert!(history.record(&summary(20, 1), &stream, &render));
+        assert!(!history.record(&summary(25, 1), &stream, &render));
+        assert!(history.record(&summary(30, 1), &stream, &render));
+        let ticks: Vec<u64> = history.samples().map(|s| s.tick).collect();
+        assert_eq!(ticks, vec![500, 20, 30]);
+    }
+
+    #[test]
+    fn ring_buffer_evicts_oldest() {
+        let mut history = StatsHistory::new(1, 3);
+        let stream = StreamStats::default();
+        let render = RenderStats::default();
+        for tick in 0..5 {
+            history.record(&summary(tick, tick as usize), &stream, &render);
+        }
+        assert_eq!(history.len(), 3);
+        let ticks: Vec<u64> = history.samples().map(|s| s.tick).collect();
+        assert_eq!(ticks, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn trends_computed_from_samples() {
+        let mut history = StatsHistory::new(1, 10);
+        let stream = StreamStats::default();
+        let fast = RenderStats {
+            frame_time: Duration::from_micros(1000),
+            ..RenderStats::default()
+        };
+        let slow = RenderStats {
+            frame_time: Duration::from_micros(3000),
+            ..RenderStats::default()
+        };
+        history.record(&summary(0, 5), &stream, &fast);
+        history.record(&summary(1, 12), &stream, &slow);
+
+        assert_eq!(history.entity_growth(), 7);
+        assert_eq!(history.average_frame_time_us(), 2000);
+    }
+
+    #[test]
+    fn save_and_load_alongside_store() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut history = StatsHistory::new(1, 10);
+        history.record(
+            &summary(3, 4),
+            &StreamStats::default(),
+            &RenderStats::default(),
+        );
+        history.save(tmp.path()).unwrap();
+        assert!(StatsHistory::path_in(tmp.path()).exists());
+
+        let loaded = StatsHistory::load(tmp.path()).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.latest().unwrap().tick, 3);
+    }
+}
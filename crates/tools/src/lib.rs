@@ -1,11 +1,16 @@
-//! Developer Tooling: world inspector, profiling hooks.
+//! Developer Tooling: world inspector, stats history, streaming overlay,
+//! profiling hooks.
 //!
 //! # Invariants
 //! - Tools are first-class and tested where possible.
 
+mod history;
 mod inspector;
+mod stream_overlay;
 
-pub use inspector::WorldInspector;
+pub use history::{StatsError, StatsHistory, StatsSample};
+pub use inspector::{WorldInspector, WorldSummary};
+pub use stream_overlay::{OverlayCell, StreamOverlay};
 
 pub fn crate_info() -> &'static str {
     "worldspace-tools v0.1.0"
@@ -0,0 +1,314 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+use worldspace_stream::{
+    CellCoord, CellLod, SpatialPartition, StreamConfig, StreamState, StreamStats,
+};
+
+/// Streaming state of a cell as drawn by `StreamOverlay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayCell {
+    /// Scheduled for loading, not loaded yet.
+    Pending,
+    /// Loaded at the given LOD tier; `CellLod::Active` cells are ticked.
+    Loaded(CellLod),
+}
+
+/// Debug overlay for cell streaming.
+///
+/// Call `record` after each `StreamState::update` to snapshot the cells
+/// around the viewer, the streaming budgets and the frame's `StreamStats`.
+/// The overlay keeps the last `capacity` frames of stats so frame time,
+/// loads and memory can be plotted while tuning a `StreamConfig`. With the
+/// `egui` feature, `show` draws it all into an egui `Ui`.
+pub struct StreamOverlay {
+    capacity: usize,
+    history: VecDeque<StreamStats>,
+    config: StreamConfig,
+    viewer: CellCoord,
+    cells: BTreeMap<CellCoord, OverlayCell>,
+    /// Entities per cell in the drawn square, for occupied cells only.
+    entities: BTreeMap<CellCoord, usize>,
+}
+
+impl StreamOverlay {
+    /// Create an overlay keeping the stats of the last `capacity` frames.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            capacity,
+            history: VecDeque::with_capacity(capacity),
+            config: StreamConfig::default(),
+            viewer: CellCoord::new(0, 0),
+            cells: BTreeMap::new(),
+            entities: BTreeMap::new(),
+        }
+    }
+
+    /// Snapshot `state` after an update around `viewer`, counting entities
+    /// per cell with `partition`.
+    pub fn record<C: Send + 'static>(
+        &mut self,
+        state: &StreamState<C>,
+        partition: &impl SpatialPartition,
+        viewer: CellCoord,
+    ) {
+        self.config = state.config.clone();
+        self.viewer = viewer;
+        self.cells = state
+            .pending_cells()
+            .iter()
+            .map(|c| (*c, OverlayCell::Pending))
+            .chain(
+                state
+                    .cell_lods()
+                    .iter()
+                    .map(|(c, lod)| (*c, OverlayCell::Loaded(*lod))),
+            )
+            .collect();
+        self.entities = self
+            .grid_cells()
+            .filter_map(|c| {
+                let count = partition.entities_in_cell(c).len();
+                (count > 0).then_some((c, count))
+            })
+            .collect();
+        if self.history.len() == self.capacity {
+            self.history.pop_front();
+        }
+        self.history.push_back(state.stats().clone());
+    }
+
+    /// Configuration of the last recorded state.
+    pub fn config(&self) -> &StreamConfig {
+        &self.config
+    }
+
+    /// Viewer cell of the last recording.
+    pub fn viewer(&self) -> CellCoord {
+        self.viewer
+    }
+
+    /// Radius (in cells) of the drawn square: one cell past the preload
+    /// radius, so cells about to unload stay visible.
+    pub fn grid_radius(&self) -> i32 {
+        self.config.preload_radius + 1
+    }
+
+    /// Cells of the drawn square on the viewer's layer, row by row.
+    pub fn grid_cells(&self) -> impl Iterator<Item = CellCoord> + '_ {
+        let (r, v) = (self.grid_radius(), self.viewer);
+        (-r..=r)
+            .flat_map(move |dz| (-r..=r).map(move |dx| CellCoord::new_3d(v.x + dx, v.y, v.z + dz)))
+    }
+
+    /// Streaming state of `coord`, or `None` if it is neither loaded nor
+    /// pending.
+    pub fn cell(&self, coord: CellCoord) -> Option<OverlayCell> {
+        self.cells.get(&coord).copied()
+    }
+
+    /// Entities in `coord` at the last recording, for cells in the drawn
+    /// square.
+    pub fn entity_count(&self, coord: CellCoord) -> usize {
+        self.entities.get(&coord).copied().unwrap_or(0)
+    }
+
+    /// Recorded stats from oldest to newest.
+    pub fn history(&self) -> impl Iterator<Item = &StreamStats> {
+        self.history.iter()
+    }
+
+    /// Stats of the last recorded frame, if any.
+    pub fn latest(&self) -> Option<&StreamStats> {
+        self.history.back()
+    }
+
+    /// Longest recorded update time.
+    pub fn peak_frame_time(&self) -> Duration {
+        self.history
+            .iter()
+            .map(|s| s.frame_time)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Fraction of the memory budget in use at the last recording, or
+    /// `None` without a budget.
+    pub fn memory_fraction(&self) -> Option<f32> {
+        let budget = self.config.memory_budget?;
+        let used = self.latest().map_or(0, |s| s.memory_used);
+        Some(if budget == 0 {
+            1.0
+        } else {
+            used as f32 / budget as f32
+        })
+    }
+
+    /// Draw the overlay: budgets and the last frame's stats, the cell grid
+    /// around the viewer colored by state, and the frame time history.
+    #[cfg(feature = "egui")]
+    pub fn show(&self, ui: &mut egui::Ui) {
+        use egui::{Color32, Sense, Stroke, pos2, vec2};
+
+        let config = &self.config;
+        let stats = self.latest().cloned().unwrap_or_default();
+        ui.label(format!(
+            "Radii: active {}  reduced {}  preload {}",
+            config.active_radius, config.reduced_radius, config.preload_radius
+        ));
+        ui.label(format!(
+            "Budgets/frame: load {}  unload {}  apply {}  workers {}",
+            config.load_budget, config.unload_budget, config.apply_budget, config.workers
+        ));
+        ui.label(format!(
+            "Loaded {}  pending {}  +{} -{} (evicted {})  {:.2} ms",
+            stats.total_loaded_cells,
+            stats.pending_loads,
+            stats.cells_loaded_this_frame,
+            stats.cells_unloaded_this_frame,
+            stats.cells_evicted_this_frame,
+            stats.frame_time.as_secs_f64() * 1000.0
+        ));
+        match (config.memory_budget, self.memory_fraction()) {
+            (Some(budget), Some(fraction)) => {
+                ui.add(egui::ProgressBar::new(fraction.min(1.0)).text(format!(
+                    "{} / {} KiB",
+                    stats.memory_used / 1024,
+                    budget / 1024
+                )));
+            }
+            _ => {
+                ui.label(format!(
+                    "Memory: {} KiB (no budget)",
+                    stats.memory_used / 1024
+                ));
+            }
+        }
+
+        ui.separator();
+        let side = (2 * self.grid_radius() + 1) as f32;
+        let size = ui.available_width().min(240.0);
+        let (response, painter) = ui.allocate_painter(vec2(size, size), Sense::hover());
+        let cell_size = size / side;
+        let origin = response.rect.min;
+        let r = self.grid_radius();
+        for coord in self.grid_cells() {
+            let (dx, dz) = (coord.x - self.viewer.x + r, coord.z - self.viewer.z + r);
+            let rect = egui::Rect::from_min_size(
+                origin + vec2(dx as f32 * cell_size, dz as f32 * cell_size),
+                vec2(cell_size, cell_size),
+            );
+            let fill = match self.cell(coord) {
+                Some(OverlayCell::Loaded(CellLod::Active)) => Color32::from_rgb(60, 170, 80),
+                Some(OverlayCell::Loaded(CellLod::Reduced)) => Color32::from_rgb(50, 120, 170),
+                Some(OverlayCell::Loaded(CellLod::Impostor)) => Color32::from_rgb(70, 80, 130),
+                Some(OverlayCell::Pending) => Color32::from_rgb(180, 150, 50),
+                None => Color32::from_gray(40),
+            };
+            painter.rect_filled(rect.shrink(0.5), 0.0, fill);
+            if self.entity_count(coord) > 0 {
+                painter.circle_filled(rect.center(), cell_size * 0.15, Color32::WHITE);
+            }
+        }
+        painter.circle_stroke(
+            origin + vec2((r as f32 + 0.5) * cell_size, (r as f32 + 0.5) * cell_size),
+            cell_size * 0.35,
+            Stroke::new(2.0, Color32::RED),
+        );
+        ui.label("Green active, blue reduced/impostor, yellow pending; dots hold entities.");
+
+        ui.separator();
+        let peak = self.peak_frame_time().as_secs_f32().max(f32::EPSILON);
+        ui.label(format!("Update time (peak {:.2} ms)", peak * 1000.0));
+        let (response, painter) =
+            ui.allocate_painter(vec2(ui.available_width(), 60.0), Sense::hover());
+        let rect = response.rect;
+        painter.rect_filled(rect, 0.0, Color32::from_gray(25));
+        let step = rect.width() / self.capacity.max(2) as f32;
+        let points: Vec<egui::Pos2> = self
+            .history
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let t = s.frame_time.as_secs_f32() / peak;
+                pos2(
+                    rect.left() + i as f32 * step,
+                    rect.bottom() - t * rect.height(),
+                )
+            })
+            .collect();
+        for pair in points.windows(2) {
+            painter.line_segment([pair[0], pair[1]], Stroke::new(1.0, Color32::LIGHT_GREEN));
+        }
+    }
+}
+
+impl Default for StreamOverlay {
+    fn default() -> Self {
+        Self::new(240)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use worldspace_common::Transform;
+    use worldspace_kernel::World;
+    use worldspace_stream::GridPartition;
+
+    #[test]
+    fn records_cells_and_history() {
+        let mut world = World::new();
+        for x in [0.0, 20.0, 40.0] {
+            world.spawn(Transform {
+                position: glam::Vec3::new(x, 0.0, 0.0),
+                ..Transform::default()
+            });
+        }
+        let mut grid = GridPartition::new(16.0);
+        grid.rebuild(&world);
+        let config = StreamConfig {
+            active_radius: 0,
+            reduced_radius: 1,
+            preload_radius: 2,
+            workers: 0,
+            memory_budget: Some(1 << 20),
+            ..StreamConfig::default()
+        };
+        let mut state = StreamState::new(config);
+        let mut overlay = StreamOverlay::new(2);
+
+        let viewer = CellCoord::new(0, 0);
+        for _ in 0..3 {
+            state.update(viewer, &grid);
+            overlay.record(&state, &grid, viewer);
+        }
+        assert_eq!(overlay.history().count(), 2);
+        assert_eq!(overlay.grid_radius(), 3);
+        assert_eq!(overlay.grid_cells().count(), 49);
+        assert_eq!(
+            overlay.cell(viewer),
+            Some(OverlayCell::Loaded(CellLod::Active))
+        );
+        assert_eq!(
+            overlay.cell(CellCoord::new(2, 0)),
+            Some(OverlayCell::Loaded(CellLod::Impostor))
+        );
+        assert_eq!(overlay.cell(CellCoord::new(3, 0)), None);
+        assert_eq!(overlay.entity_count(CellCoord::new(1, 0)), 1);
+        assert_eq!(overlay.entity_count(CellCoord::new(0, 1)), 0);
+        assert_eq!(
+            overlay.latest().unwrap().total_loaded_cells,
+            state.loaded_cells().len()
+        );
+        assert!(overlay.memory_fraction().unwrap() < 1.0);
+    }
+
+    #[test]
+    fn no_budget_has_no_memory_fraction() {
+        let overlay = StreamOverlay::default();
+        assert_eq!(overlay.memory_fraction(), None);
+        assert_eq!(overlay.peak_frame_time(), Duration::ZERO);
+        assert!(overlay.latest().is_none());
+    }
+}